@@ -0,0 +1,137 @@
+/*
+ * C ABI for Svarog, the Star Citizen game file library.
+ *
+ * Conventions:
+ * - Archives and databases are opaque handles, created by an _open or _parse
+ *   function and released by the matching _close or _free function.
+ * - Functions that create something return NULL on failure; the others
+ *   return a SvarogStatus. Either way, svarog_last_error() describes the
+ *   failure.
+ * - Strings passed in are NUL-terminated UTF-8 and only borrowed for the call.
+ * - SvarogStr results borrow from their handle and stay valid until it is
+ *   released. SvarogBuffer, SvarogRecordList and char * results belong to the
+ *   caller and are released with their _free function.
+ * - Handles may be used from several threads at once; the last error is kept
+ *   per thread.
+ */
+
+#ifndef SVAROG_H
+#define SVAROG_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum SvarogStatus {
+    SVAROG_OK = 0,
+    /* A null pointer or malformed argument was passed in */
+    SVAROG_INVALID_ARGUMENT = 1,
+    /* The requested entry or record does not exist */
+    SVAROG_NOT_FOUND = 2,
+    /* Reading, parsing or converting failed */
+    SVAROG_FAILED = 3,
+    /* A bug in Svarog; the handle involved should not be used further */
+    SVAROG_PANIC = 4,
+} SvarogStatus;
+
+/* UTF-8 string borrowed from a handle. Not NUL-terminated. */
+typedef struct SvarogStr {
+    const char *ptr;
+    size_t len;
+} SvarogStr;
+
+/* Bytes owned by the caller, released with svarog_buffer_free() */
+typedef struct SvarogBuffer {
+    uint8_t *data;
+    size_t len;
+} SvarogBuffer;
+
+/* ---- Common ---- */
+
+/* Svarog version, as a static string */
+const char *svarog_version(void);
+
+/* Message of the last failed call on this thread, or NULL if none failed.
+ * Valid until the next failing call on the same thread. */
+const char *svarog_last_error(void);
+
+void svarog_buffer_free(SvarogBuffer buffer);
+void svarog_string_free(char *s);
+
+/* ---- P4K archives ---- */
+
+typedef struct SvarogP4k SvarogP4k;
+
+typedef struct SvarogP4kEntry {
+    /* Path inside the archive, with '\' separators. Borrowed from the archive. */
+    SvarogStr name;
+    uint64_t compressed_size;
+    uint64_t uncompressed_size;
+    uint32_t crc32;
+    bool encrypted;
+} SvarogP4kEntry;
+
+SvarogP4k *svarog_p4k_open(const char *path);
+void svarog_p4k_close(SvarogP4k *p4k);
+size_t svarog_p4k_entry_count(const SvarogP4k *p4k);
+SvarogStatus svarog_p4k_entry(const SvarogP4k *p4k, size_t index, SvarogP4kEntry *out);
+/* Case-insensitive; accepts '/' or '\' separators */
+SvarogStatus svarog_p4k_find(const SvarogP4k *p4k, const char *name, size_t *out_index);
+/* Decrypts and decompresses the entry; free out with svarog_buffer_free() */
+SvarogStatus svarog_p4k_read(const SvarogP4k *p4k, size_t index, SvarogBuffer *out);
+
+/* ---- DataCore databases ---- */
+
+typedef struct SvarogDataCore SvarogDataCore;
+
+typedef struct SvarogRecord {
+    /* Borrowed from the database */
+    SvarogStr name;
+    SvarogStr type_name;
+    SvarogStr file_name;
+    /* NUL-terminated, e.g. "04b6b41e-2d4b-4a62-a52f-2c8bd6f0c1e7" */
+    char guid[37];
+    uint32_t struct_index;
+    uint32_t instance_index;
+    /* Main record of its file rather than a sub-record */
+    bool is_main;
+} SvarogRecord;
+
+/* Record indices owned by the caller, released with svarog_record_list_free() */
+typedef struct SvarogRecordList {
+    size_t *indices;
+    size_t len;
+} SvarogRecordList;
+
+SvarogDataCore *svarog_dcb_open(const char *path);
+/* Copies the bytes, e.g. Data\Game.dcb read with svarog_p4k_read() */
+SvarogDataCore *svarog_dcb_parse(const uint8_t *data, size_t len);
+void svarog_dcb_free(SvarogDataCore *dcb);
+size_t svarog_dcb_record_count(const SvarogDataCore *dcb);
+SvarogStatus svarog_dcb_record(const SvarogDataCore *dcb, size_t index, SvarogRecord *out);
+/* Looks key up as a GUID, then as a record name (first match) */
+SvarogStatus svarog_dcb_find_record(const SvarogDataCore *dcb, const char *key, size_t *out_index);
+/* Records matching every filter; NULL filters match anything */
+SvarogStatus svarog_dcb_query(const SvarogDataCore *dcb, const char *type_name, const char *name,
+                              bool main_only, SvarogRecordList *out);
+void svarog_record_list_free(SvarogRecordList list);
+/* Returns NULL on failure; free with svarog_string_free() */
+char *svarog_dcb_record_xml(const SvarogDataCore *dcb, size_t index);
+
+/* ---- CryXmlB ---- */
+
+bool svarog_cryxml_is_cryxml(const uint8_t *data, size_t len);
+/* Returns NULL on failure; free with svarog_string_free() */
+char *svarog_cryxml_to_xml(const uint8_t *data, size_t len);
+/* Free out with svarog_buffer_free() */
+SvarogStatus svarog_xml_to_cryxml(const char *xml, SvarogBuffer *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SVAROG_H */
@@ -0,0 +1,96 @@
+//! CryXmlB conversion.
+
+use std::ffi::c_char;
+
+use svarog::cryxml::{CryXml, CryXmlDocument};
+
+use crate::error::{guard, guard_status};
+use crate::memory::{bytes_arg, into_c_string, out_arg, str_arg, SvarogBuffer};
+use crate::SvarogStatus;
+
+/// Whether `data` starts with the CryXmlB magic.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_cryxml_is_cryxml(data: *const u8, len: usize) -> bool {
+    bytes_arg(data, len).is_ok_and(CryXml::is_cryxml)
+}
+
+/// Convert CryXmlB to XML text. Returns null on failure; the string must be
+/// released with [`svarog_string_free`](crate::svarog_string_free).
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_cryxml_to_xml(data: *const u8, len: usize) -> *mut c_char {
+    guard(|| {
+        let cryxml = CryXml::parse(bytes_arg(data, len)?)?;
+        into_c_string(cryxml.to_xml_string()?)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Convert XML text to CryXmlB into `out`, which must be released with
+/// [`svarog_buffer_free`](crate::svarog_buffer_free).
+///
+/// # Safety
+///
+/// `xml` must be a NUL-terminated UTF-8 string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_xml_to_cryxml(
+    xml: *const c_char,
+    out: *mut SvarogBuffer,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out = out_arg(out, "out")?;
+        let document = CryXmlDocument::from_xml(str_arg(xml, "xml")?)?;
+        out.write(SvarogBuffer::from_vec(document.to_cryxml()?));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_round_trip() {
+        let mut buffer = SvarogBuffer::EMPTY;
+        let xml = c"<Vehicle name=\"Gladius\"><Part name=\"Nose\"/></Vehicle>";
+        let status = unsafe { svarog_xml_to_cryxml(xml.as_ptr(), &mut buffer) };
+        assert_eq!(status, SvarogStatus::Ok);
+        assert!(unsafe { svarog_cryxml_is_cryxml(buffer.data, buffer.len) });
+
+        let text = unsafe { svarog_cryxml_to_xml(buffer.data, buffer.len) };
+        assert!(!text.is_null());
+        let converted = unsafe { CStr::from_ptr(text) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(converted.contains("<Vehicle name=\"Gladius\">"));
+        assert!(converted.contains("<Part name=\"Nose\""));
+
+        unsafe {
+            crate::memory::svarog_string_free(text);
+            crate::memory::svarog_buffer_free(buffer);
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let data = b"<plain/>";
+        assert!(!unsafe { svarog_cryxml_is_cryxml(data.as_ptr(), data.len()) });
+        assert!(unsafe { svarog_cryxml_to_xml(data.as_ptr(), data.len()) }.is_null());
+        assert!(!unsafe { svarog_cryxml_is_cryxml(std::ptr::null(), 4) });
+
+        let mut buffer = SvarogBuffer::EMPTY;
+        let status = unsafe { svarog_xml_to_cryxml(std::ptr::null(), &mut buffer) };
+        assert_eq!(status, SvarogStatus::InvalidArgument);
+        let status = unsafe { svarog_xml_to_cryxml(c"<plain/>".as_ptr(), std::ptr::null_mut()) };
+        assert_eq!(status, SvarogStatus::InvalidArgument);
+    }
+}
@@ -0,0 +1,359 @@
+//! DataCore databases.
+
+use std::ffi::c_char;
+
+use svarog::datacore::structs::DataCoreRecord;
+use svarog::datacore::{DataCoreDatabase, XmlExporter};
+
+use crate::error::{guard, guard_status};
+use crate::memory::{bytes_arg, into_c_string, optional_str_arg, out_arg, str_arg, SvarogStr};
+use crate::{Error, SvarogStatus};
+
+/// A parsed DataCore database. Opaque to C.
+pub struct SvarogDataCore {
+    db: DataCoreDatabase,
+}
+
+/// Metadata of one record.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SvarogRecord {
+    /// Borrowed from the database, like the other strings
+    pub name: SvarogStr,
+    pub type_name: SvarogStr,
+    /// Source file of the record, e.g. `libs/foundry/records/...xml`
+    pub file_name: SvarogStr,
+    /// NUL-terminated GUID in its usual `xxxxxxxx-xxxx-...` form
+    pub guid: [c_char; 37],
+    pub struct_index: u32,
+    pub instance_index: u32,
+    /// Whether this is the main record of its file rather than a sub-record
+    pub is_main: bool,
+}
+
+/// Record indices owned by the caller, released with
+/// [`svarog_record_list_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct SvarogRecordList {
+    pub indices: *mut usize,
+    pub len: usize,
+}
+
+/// Open a `.dcb` file. Returns null on failure.
+///
+/// The handle must be released with [`svarog_dcb_free`].
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_open(path: *const c_char) -> *mut SvarogDataCore {
+    guard(|| {
+        let db = DataCoreDatabase::open(str_arg(path, "path")?)?;
+        Ok(Box::into_raw(Box::new(SvarogDataCore { db })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Parse a database from memory, such as `Data\Game.dcb` read from a P4K.
+/// The bytes are copied. Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_parse(data: *const u8, len: usize) -> *mut SvarogDataCore {
+    guard(|| {
+        let db = DataCoreDatabase::parse(bytes_arg(data, len)?)?;
+        Ok(Box::into_raw(Box::new(SvarogDataCore { db })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Release a database. Freeing null is a no-op.
+///
+/// Strings borrowed from the database are invalid afterwards.
+///
+/// # Safety
+///
+/// `dcb` must be null or a handle not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_free(dcb: *mut SvarogDataCore) {
+    if !dcb.is_null() {
+        drop(Box::from_raw(dcb));
+    }
+}
+
+/// Number of records, main and sub-records alike, or zero for null.
+///
+/// # Safety
+///
+/// `dcb` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_record_count(dcb: *const SvarogDataCore) -> usize {
+    dcb.as_ref().map_or(0, |dcb| dcb.db.records().len())
+}
+
+/// Get the record at `index`.
+///
+/// # Safety
+///
+/// `dcb` must be a valid handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_record(
+    dcb: *const SvarogDataCore,
+    index: usize,
+    out: *mut SvarogRecord,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out = out_arg(out, "out")?;
+        let db = &handle(dcb)?.db;
+        let record = record_at(db, index)?;
+        out.write(record_info(db, record));
+        Ok(())
+    })
+}
+
+/// Find a record by GUID or, failing that, by name, and store its index in
+/// `out_index`. Names are not unique; the first match is returned.
+///
+/// # Safety
+///
+/// `dcb` must be a valid handle, `key` a NUL-terminated UTF-8 string and
+/// `out_index` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_find_record(
+    dcb: *const SvarogDataCore,
+    key: *const c_char,
+    out_index: *mut usize,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out_index = out_arg(out_index, "out_index")?;
+        let db = &handle(dcb)?.db;
+        let key = str_arg(key, "key")?;
+        let records = db.records();
+        let index = match db.get_record_by_guid_str(key) {
+            Some(record) => records.iter().position(|r| r.id == record.id),
+            None => records.iter().position(|r| db.record_name(r) == Some(key)),
+        };
+        let index = index.ok_or_else(|| Error::NotFound(key.to_string()))?;
+        out_index.write(index);
+        Ok(())
+    })
+}
+
+/// List the records matching every given filter into `out`, which must be
+/// released with [`svarog_record_list_free`]. Null filters match anything.
+///
+/// # Safety
+///
+/// `dcb` must be a valid handle, `type_name` and `name` null or
+/// NUL-terminated UTF-8 strings, and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_query(
+    dcb: *const SvarogDataCore,
+    type_name: *const c_char,
+    name: *const c_char,
+    main_only: bool,
+    out: *mut SvarogRecordList,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out = out_arg(out, "out")?;
+        let db = &handle(dcb)?.db;
+        let type_name = optional_str_arg(type_name, "type_name")?;
+        let name = optional_str_arg(name, "name")?;
+
+        let indices: Vec<usize> = db
+            .records()
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| {
+                type_name.map_or(true, |t| db.struct_name(r.struct_index as usize) == Some(t))
+                    && name.map_or(true, |n| db.record_name(r) == Some(n))
+                    && (!main_only || db.is_main_record(r.id))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        let len = indices.len();
+        let list = SvarogRecordList {
+            indices: Box::into_raw(indices.into_boxed_slice()).cast(),
+            len,
+        };
+        out.write(list);
+        Ok(())
+    })
+}
+
+/// Release a record list. Freeing an empty list is a no-op.
+///
+/// # Safety
+///
+/// `list` must have been returned by [`svarog_dcb_query`] and not freed
+/// already.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_record_list_free(list: SvarogRecordList) {
+    if !list.indices.is_null() {
+        let slice = std::ptr::slice_from_raw_parts_mut(list.indices, list.len);
+        drop(Box::from_raw(slice));
+    }
+}
+
+/// Export the record at `index` as XML, as `svarog dcb-extract` writes it.
+/// Returns null on failure; the string must be released with
+/// [`svarog_string_free`](crate::svarog_string_free).
+///
+/// # Safety
+///
+/// `dcb` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_dcb_record_xml(
+    dcb: *const SvarogDataCore,
+    index: usize,
+) -> *mut c_char {
+    guard(|| {
+        let db = &handle(dcb)?.db;
+        let record = record_at(db, index)?;
+        into_c_string(XmlExporter::new(db).export_record(record)?)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+unsafe fn handle<'a>(dcb: *const SvarogDataCore) -> crate::Result<&'a SvarogDataCore> {
+    dcb.as_ref().ok_or(Error::InvalidArgument("dcb"))
+}
+
+fn record_at(db: &DataCoreDatabase, index: usize) -> crate::Result<&DataCoreRecord> {
+    db.records()
+        .get(index)
+        .ok_or_else(|| Error::NotFound(format!("record {}", index)))
+}
+
+fn record_info(db: &DataCoreDatabase, record: &DataCoreRecord) -> SvarogRecord {
+    let text = |s: Option<&str>| s.map_or(SvarogStr::EMPTY, SvarogStr::from);
+
+    let mut guid = [0; 37];
+    for (dst, src) in guid.iter_mut().zip(record.id.to_string().bytes()) {
+        *dst = src as c_char;
+    }
+
+    SvarogRecord {
+        name: text(db.record_name(record)),
+        type_name: text(db.struct_name(record.struct_index as usize)),
+        file_name: text(db.record_file_name(record)),
+        guid,
+        struct_index: record.struct_index as u32,
+        instance_index: record.instance_index as u32,
+        is_main: db.is_main_record(record.id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use svarog::datacore::{DataCoreBuilder, DataType};
+
+    fn sample() -> Vec<u8> {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "label", DataType::String);
+        let ammo = builder.add_struct("Ammo", None);
+        let rifle = builder.add_record("Rifle", item, "libs/foundry/records/rifle.xml");
+        builder.set_string(rifle, "label", "P4-AR");
+        builder.add_record("RifleMag", item, "libs/foundry/records/rifle.xml");
+        builder.add_record("Slug", ammo, "libs/foundry/records/ammo.xml");
+        builder.build().unwrap()
+    }
+
+    fn as_str(s: SvarogStr) -> &'static str {
+        let bytes = unsafe { std::slice::from_raw_parts(s.ptr.cast::<u8>(), s.len) };
+        std::str::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_records() {
+        let data = sample();
+        let dcb = unsafe { svarog_dcb_parse(data.as_ptr(), data.len()) };
+        assert!(!dcb.is_null());
+        assert_eq!(unsafe { svarog_dcb_record_count(dcb) }, 3);
+
+        let mut index = usize::MAX;
+        let status = unsafe { svarog_dcb_find_record(dcb, c"RifleMag".as_ptr(), &mut index) };
+        assert_eq!(status, SvarogStatus::Ok);
+        assert_eq!(index, 1);
+
+        let mut record = std::mem::MaybeUninit::uninit();
+        let status = unsafe { svarog_dcb_record(dcb, index, record.as_mut_ptr()) };
+        assert_eq!(status, SvarogStatus::Ok);
+        let record = unsafe { record.assume_init() };
+        assert_eq!(as_str(record.name), "RifleMag");
+        assert_eq!(as_str(record.type_name), "Item");
+        assert_eq!(as_str(record.file_name), "libs/foundry/records/rifle.xml");
+        assert!(!record.is_main);
+
+        // Looking the record up by its GUID finds the same index
+        let guid = unsafe { CStr::from_ptr(record.guid.as_ptr()) };
+        let status = unsafe { svarog_dcb_find_record(dcb, guid.as_ptr(), &mut index) };
+        assert_eq!(status, SvarogStatus::Ok);
+        assert_eq!(index, 1);
+
+        let status = unsafe { svarog_dcb_find_record(dcb, c"Missing".as_ptr(), &mut index) };
+        assert_eq!(status, SvarogStatus::NotFound);
+        let mut record = std::mem::MaybeUninit::uninit();
+        let status = unsafe { svarog_dcb_record(dcb, 3, record.as_mut_ptr()) };
+        assert_eq!(status, SvarogStatus::NotFound);
+
+        unsafe { svarog_dcb_free(dcb) };
+    }
+
+    #[test]
+    fn test_query_and_xml() {
+        let data = sample();
+        let dcb = unsafe { svarog_dcb_parse(data.as_ptr(), data.len()) };
+
+        let mut list = SvarogRecordList {
+            indices: std::ptr::null_mut(),
+            len: 0,
+        };
+        let status =
+            unsafe { svarog_dcb_query(dcb, c"Item".as_ptr(), std::ptr::null(), false, &mut list) };
+        assert_eq!(status, SvarogStatus::Ok);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(list.indices, list.len) },
+            [0, 1]
+        );
+        unsafe { svarog_record_list_free(list) };
+
+        let mut list = SvarogRecordList {
+            indices: std::ptr::null_mut(),
+            len: 0,
+        };
+        let status =
+            unsafe { svarog_dcb_query(dcb, std::ptr::null(), std::ptr::null(), true, &mut list) };
+        assert_eq!(status, SvarogStatus::Ok);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(list.indices, list.len) },
+            [0, 2]
+        );
+        unsafe { svarog_record_list_free(list) };
+
+        let xml = unsafe { svarog_dcb_record_xml(dcb, 0) };
+        assert!(!xml.is_null());
+        let text = unsafe { CStr::from_ptr(xml) }.to_str().unwrap().to_string();
+        assert!(text.contains("P4-AR"));
+        unsafe {
+            crate::memory::svarog_string_free(xml);
+            svarog_dcb_free(dcb);
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        let dcb = unsafe { svarog_dcb_parse(b"not a dcb".as_ptr(), 9) };
+        assert!(dcb.is_null());
+        assert!(!crate::svarog_last_error().is_null());
+        assert!(unsafe { svarog_dcb_record_xml(std::ptr::null(), 0) }.is_null());
+    }
+}
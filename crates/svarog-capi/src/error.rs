@@ -0,0 +1,153 @@
+//! Error reporting across the C ABI.
+//!
+//! Functions report failure through their return value: a [`SvarogStatus`]
+//! or a null handle. The message of the last failure on the calling thread
+//! is available from [`svarog_last_error`].
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use thiserror::Error;
+
+/// Errors raised by the C API.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A null pointer or malformed argument was passed in.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(&'static str),
+
+    /// The requested entry or record does not exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// P4K archive error.
+    #[error("{0}")]
+    P4k(#[from] svarog::p4k::Error),
+
+    /// DataCore database error.
+    #[error("{0}")]
+    DataCore(#[from] svarog::datacore::Error),
+
+    /// DataCore record export error.
+    #[error("{0}")]
+    Export(#[from] svarog::datacore::export::ExportError),
+
+    /// CryXmlB error.
+    #[error("{0}")]
+    CryXml(#[from] svarog::cryxml::Error),
+
+    /// A panic was caught at the ABI boundary.
+    #[error("internal error: {0}")]
+    Panic(String),
+}
+
+/// Result type for the C API.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Outcome of a C API call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvarogStatus {
+    Ok = 0,
+    /// A null pointer or malformed argument was passed in.
+    InvalidArgument = 1,
+    /// The requested entry or record does not exist.
+    NotFound = 2,
+    /// Reading, parsing or converting failed.
+    Failed = 3,
+    /// A bug in Svarog; the handle involved should not be used further.
+    Panic = 4,
+}
+
+impl Error {
+    /// Status reported to C callers for this error.
+    pub fn status(&self) -> SvarogStatus {
+        match self {
+            Error::InvalidArgument(_) => SvarogStatus::InvalidArgument,
+            Error::NotFound(_) => SvarogStatus::NotFound,
+            Error::Panic(_) => SvarogStatus::Panic,
+            _ => SvarogStatus::Failed,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: &Error) {
+    // Messages quoting file contents may hold NUL bytes
+    let message = error.to_string().replace('\0', "\\0");
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(message).ok());
+}
+
+/// Run the body of an exported function, recording its error or panic as
+/// the thread's last error.
+pub(crate) fn guard<T>(body: impl FnOnce() -> Result<T>) -> Result<T> {
+    let result = catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        Err(Error::Panic(message))
+    });
+    if let Err(e) = &result {
+        set_last_error(e);
+    }
+    result
+}
+
+/// [`guard`] for functions returning a status.
+pub(crate) fn guard_status(body: impl FnOnce() -> Result<()>) -> SvarogStatus {
+    match guard(body) {
+        Ok(()) => SvarogStatus::Ok,
+        Err(e) => e.status(),
+    }
+}
+
+/// Message of the last failed call on this thread, or null if none failed.
+///
+/// The string is owned by Svarog and stays valid until the next failing call
+/// on the same thread.
+#[no_mangle]
+pub extern "C" fn svarog_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn last_error() -> String {
+        let ptr = svarog_last_error();
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_guard_records_errors() {
+        let status = guard_status(|| Err(Error::NotFound("Data\\Game.dcb".to_string())));
+        assert_eq!(status, SvarogStatus::NotFound);
+        assert_eq!(last_error(), "not found: Data\\Game.dcb");
+
+        // Success leaves the previous message in place
+        assert_eq!(guard_status(|| Ok(())), SvarogStatus::Ok);
+        assert_eq!(last_error(), "not found: Data\\Game.dcb");
+    }
+
+    #[test]
+    fn test_guard_catches_panics() {
+        let status = guard_status(|| panic!("boom"));
+        assert_eq!(status, SvarogStatus::Panic);
+        assert_eq!(last_error(), "internal error: boom");
+    }
+}
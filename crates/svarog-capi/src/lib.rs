@@ -0,0 +1,68 @@
+//! C ABI for Svarog.
+//!
+//! Lets C, C++ and C# tools open P4K archives, read their entries, parse
+//! DataCore databases, query and export records, and convert CryXmlB. The
+//! declarations are in `include/svarog.h`; the crate builds as a shared
+//! and a static library.
+//!
+//! # Conventions
+//!
+//! - Archives and databases are opaque handles, created by an `_open` or
+//!   `_parse` function and released by the matching `_close` or `_free`
+//! - Functions that create something return null on failure; the others
+//!   return a [`SvarogStatus`]. Either way, [`svarog_last_error`] describes
+//!   the failure.
+//! - Strings passed in are NUL-terminated UTF-8 and only borrowed for the
+//!   call
+//! - [`SvarogStr`] results borrow from their handle and stay valid until it
+//!   is released; [`SvarogBuffer`], record lists and `char *` results belong
+//!   to the caller and are released with their `_free` function
+//! - Handles may be used from several threads at once; the last error is
+//!   kept per thread
+//! - Panics are caught at the boundary and reported as
+//!   [`SvarogStatus::Panic`]
+//!
+//! # Example
+//!
+//! ```c
+//! SvarogP4k *p4k = svarog_p4k_open("Data.p4k");
+//! size_t index;
+//! SvarogBuffer dcb_data;
+//! if (p4k && svarog_p4k_find(p4k, "Data\\Game.dcb", &index) == SVAROG_OK
+//!         && svarog_p4k_read(p4k, index, &dcb_data) == SVAROG_OK) {
+//!     SvarogDataCore *dcb = svarog_dcb_parse(dcb_data.data, dcb_data.len);
+//!     svarog_buffer_free(dcb_data);
+//!     /* ... */
+//!     svarog_dcb_free(dcb);
+//! } else {
+//!     fprintf(stderr, "%s\n", svarog_last_error());
+//! }
+//! svarog_p4k_close(p4k);
+//! ```
+
+use std::ffi::c_char;
+
+mod cryxml;
+mod datacore;
+mod error;
+mod memory;
+mod p4k;
+
+pub use cryxml::{svarog_cryxml_is_cryxml, svarog_cryxml_to_xml, svarog_xml_to_cryxml};
+pub use datacore::{
+    svarog_dcb_find_record, svarog_dcb_free, svarog_dcb_open, svarog_dcb_parse, svarog_dcb_query,
+    svarog_dcb_record, svarog_dcb_record_count, svarog_dcb_record_xml, svarog_record_list_free,
+    SvarogDataCore, SvarogRecord, SvarogRecordList,
+};
+pub use error::{svarog_last_error, Error, Result, SvarogStatus};
+pub use memory::{svarog_buffer_free, svarog_string_free, SvarogBuffer, SvarogStr};
+pub use p4k::{
+    svarog_p4k_close, svarog_p4k_entry, svarog_p4k_entry_count, svarog_p4k_find, svarog_p4k_open,
+    svarog_p4k_read, SvarogP4k, SvarogP4kEntry,
+};
+
+/// Svarog version, as a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn svarog_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
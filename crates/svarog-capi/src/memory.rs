@@ -0,0 +1,195 @@
+//! Strings and buffers passed across the C ABI.
+//!
+//! Svarog hands out three kinds of memory:
+//!
+//! - [`SvarogStr`]: a borrowed UTF-8 string, not NUL-terminated, valid as
+//!   long as the handle it came from
+//! - [`SvarogBuffer`]: owned bytes, released with [`svarog_buffer_free`]
+//! - `char *`: an owned NUL-terminated string, released with
+//!   [`svarog_string_free`]
+
+use std::ffi::{c_char, CStr, CString};
+use std::mem::MaybeUninit;
+
+use crate::{Error, Result};
+
+/// A UTF-8 string borrowed from a handle. Not NUL-terminated.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SvarogStr {
+    pub ptr: *const c_char,
+    pub len: usize,
+}
+
+impl SvarogStr {
+    pub const EMPTY: Self = Self {
+        ptr: std::ptr::null(),
+        len: 0,
+    };
+}
+
+impl From<&str> for SvarogStr {
+    fn from(s: &str) -> Self {
+        Self {
+            ptr: s.as_ptr().cast(),
+            len: s.len(),
+        }
+    }
+}
+
+/// Bytes owned by the caller, released with [`svarog_buffer_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct SvarogBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl SvarogBuffer {
+    pub const EMPTY: Self = Self {
+        data: std::ptr::null_mut(),
+        len: 0,
+    };
+
+    pub(crate) fn from_vec(data: Vec<u8>) -> Self {
+        let len = data.len();
+        Self {
+            data: Box::into_raw(data.into_boxed_slice()).cast(),
+            len,
+        }
+    }
+}
+
+/// Release a buffer returned by Svarog. Freeing an empty buffer is a no-op.
+///
+/// # Safety
+///
+/// `buffer` must have been returned by Svarog and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_buffer_free(buffer: SvarogBuffer) {
+    if !buffer.data.is_null() {
+        let slice = std::ptr::slice_from_raw_parts_mut(buffer.data, buffer.len);
+        drop(Box::from_raw(slice));
+    }
+}
+
+/// Release a string returned by Svarog. Freeing null is a no-op.
+///
+/// # Safety
+///
+/// `s` must be null or have been returned by Svarog and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Hand a string over to the caller as `char *`.
+pub(crate) fn into_c_string(s: String) -> Result<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|_| Error::InvalidArgument("output contains a NUL byte"))
+}
+
+/// Borrow a NUL-terminated UTF-8 argument.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a NUL-terminated string that outlives `'a`.
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &'static str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(Error::InvalidArgument(name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| Error::InvalidArgument(name))
+}
+
+/// Like [`str_arg`], with null meaning no value.
+///
+/// # Safety
+///
+/// As for [`str_arg`].
+pub(crate) unsafe fn optional_str_arg<'a>(
+    ptr: *const c_char,
+    name: &'static str,
+) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        str_arg(ptr, name).map(Some)
+    }
+}
+
+/// Borrow a byte range argument; null is allowed when `len` is zero.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` readable bytes that outlive `'a`.
+pub(crate) unsafe fn bytes_arg<'a>(data: *const u8, len: usize) -> Result<&'a [u8]> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(Error::InvalidArgument("data")),
+        (false, _) => Ok(std::slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Borrow an out pointer for writing. Check it before allocating what is
+/// written through it, as a value dropped on a null pointer would leak.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes for `'a`.
+pub(crate) unsafe fn out_arg<'a, T>(
+    out: *mut T,
+    name: &'static str,
+) -> Result<&'a mut MaybeUninit<T>> {
+    out.cast::<MaybeUninit<T>>()
+        .as_mut()
+        .ok_or(Error::InvalidArgument(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_round_trip() {
+        let buffer = SvarogBuffer::from_vec(b"CryXmlB\0".to_vec());
+        assert_eq!(buffer.len, 8);
+        assert_eq!(
+            unsafe { std::slice::from_raw_parts(buffer.data, buffer.len) },
+            b"CryXmlB\0"
+        );
+        unsafe { svarog_buffer_free(buffer) };
+
+        let empty = SvarogBuffer::from_vec(Vec::new());
+        assert_eq!(empty.len, 0);
+        unsafe {
+            svarog_buffer_free(empty);
+            svarog_buffer_free(SvarogBuffer::EMPTY);
+        }
+    }
+
+    #[test]
+    fn test_string_args() {
+        let s = into_c_string("Data\\Game.dcb".to_string()).unwrap();
+        assert_eq!(unsafe { str_arg(s, "path") }.unwrap(), "Data\\Game.dcb");
+        unsafe { svarog_string_free(s) };
+
+        assert!(into_c_string("a\0b".to_string()).is_err());
+        assert!(matches!(
+            unsafe { str_arg(std::ptr::null(), "path") },
+            Err(Error::InvalidArgument("path"))
+        ));
+        assert_eq!(
+            unsafe { optional_str_arg(std::ptr::null(), "name") }.unwrap(),
+            None
+        );
+        assert!(unsafe { bytes_arg(std::ptr::null(), 0) }
+            .unwrap()
+            .is_empty());
+        assert!(unsafe { bytes_arg(std::ptr::null(), 4) }.is_err());
+    }
+}
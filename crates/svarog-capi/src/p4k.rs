@@ -0,0 +1,167 @@
+//! P4K archives.
+
+use std::ffi::c_char;
+
+use svarog::p4k::P4kArchive;
+
+use crate::error::{guard, guard_status};
+use crate::memory::{out_arg, str_arg, SvarogBuffer, SvarogStr};
+use crate::{Error, SvarogStatus};
+
+/// An open P4K archive. Opaque to C.
+pub struct SvarogP4k {
+    archive: P4kArchive,
+}
+
+/// Metadata of one archive entry.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SvarogP4kEntry {
+    /// Path inside the archive, with `\` separators. Borrowed from the archive.
+    pub name: SvarogStr,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+    pub crc32: u32,
+    pub encrypted: bool,
+}
+
+/// Open a P4K archive. Returns null on failure.
+///
+/// The handle must be released with [`svarog_p4k_close`].
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_open(path: *const c_char) -> *mut SvarogP4k {
+    guard(|| {
+        let archive = P4kArchive::open(str_arg(path, "path")?)?;
+        Ok(Box::into_raw(Box::new(SvarogP4k { archive })))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Close an archive. Closing null is a no-op.
+///
+/// Entry names borrowed from the archive are invalid afterwards.
+///
+/// # Safety
+///
+/// `p4k` must be null or a handle from [`svarog_p4k_open`] not closed already.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_close(p4k: *mut SvarogP4k) {
+    if !p4k.is_null() {
+        drop(Box::from_raw(p4k));
+    }
+}
+
+/// Number of entries in the archive, or zero for null.
+///
+/// # Safety
+///
+/// `p4k` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_entry_count(p4k: *const SvarogP4k) -> usize {
+    p4k.as_ref().map_or(0, |p4k| p4k.archive.entry_count())
+}
+
+/// Get the entry at `index`.
+///
+/// # Safety
+///
+/// `p4k` must be a valid handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_entry(
+    p4k: *const SvarogP4k,
+    index: usize,
+    out: *mut SvarogP4kEntry,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out = out_arg(out, "out")?;
+        let archive = &handle(p4k)?.archive;
+        let entry = archive
+            .get(index)
+            .ok_or_else(|| Error::NotFound(format!("entry {}", index)))?;
+        let info = SvarogP4kEntry {
+            name: entry.name.into(),
+            compressed_size: entry.compressed_size,
+            uncompressed_size: entry.uncompressed_size,
+            crc32: entry.crc32,
+            encrypted: entry.is_encrypted,
+        };
+        out.write(info);
+        Ok(())
+    })
+}
+
+/// Find an entry by path, ignoring case and accepting `/` or `\`, and
+/// store its index in `out_index`.
+///
+/// # Safety
+///
+/// `p4k` must be a valid handle, `name` a NUL-terminated UTF-8 string and
+/// `out_index` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_find(
+    p4k: *const SvarogP4k,
+    name: *const c_char,
+    out_index: *mut usize,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out_index = out_arg(out_index, "out_index")?;
+        let archive = &handle(p4k)?.archive;
+        let name = str_arg(name, "name")?;
+        let index = archive
+            .index_of(name)
+            .ok_or_else(|| Error::NotFound(name.to_string()))?;
+        out_index.write(index);
+        Ok(())
+    })
+}
+
+/// Read, decrypt and decompress the entry at `index` into `out`, which must
+/// be released with [`svarog_buffer_free`](crate::svarog_buffer_free).
+///
+/// # Safety
+///
+/// `p4k` must be a valid handle and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn svarog_p4k_read(
+    p4k: *const SvarogP4k,
+    index: usize,
+    out: *mut SvarogBuffer,
+) -> SvarogStatus {
+    guard_status(|| {
+        let out = out_arg(out, "out")?;
+        let archive = &handle(p4k)?.archive;
+        if index >= archive.entry_count() {
+            return Err(Error::NotFound(format!("entry {}", index)));
+        }
+        let data = archive.read_index(index)?;
+        out.write(SvarogBuffer::from_vec(data));
+        Ok(())
+    })
+}
+
+unsafe fn handle<'a>(p4k: *const SvarogP4k) -> crate::Result<&'a SvarogP4k> {
+    p4k.as_ref().ok_or(Error::InvalidArgument("p4k"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_missing_archive() {
+        let path = c"/nonexistent/Data.p4k";
+        let p4k = unsafe { svarog_p4k_open(path.as_ptr()) };
+        assert!(p4k.is_null());
+        assert!(!crate::svarog_last_error().is_null());
+
+        assert!(unsafe { svarog_p4k_open(std::ptr::null()) }.is_null());
+        assert_eq!(unsafe { svarog_p4k_entry_count(std::ptr::null()) }, 0);
+        let mut index = 0;
+        let status = unsafe { svarog_p4k_find(std::ptr::null(), path.as_ptr(), &mut index) };
+        assert_eq!(status, SvarogStatus::InvalidArgument);
+    }
+}
@@ -0,0 +1,543 @@
+//! Declarative edits applied to many characters at once.
+//!
+//! A [`ChfScript`] is a list of [`ChfOp`]s such as "attach this item to the
+//! `eyes` port" or "scale all nose blends by 0.8". With the `json` feature a
+//! script can be loaded from a file:
+//!
+//! ```json
+//! {
+//!   "ops": [
+//!     { "op": "set_item", "port": "eyes", "item": "..." },
+//!     { "op": "scale_blends", "face_parts": ["Nose"], "factor": 0.8 },
+//!     { "op": "set_color", "param": "iris_color", "value": "#3a6ea5ff" }
+//!   ]
+//! }
+//! ```
+//!
+//! Single edits can also be written as `path=value` assignments, parsed with
+//! [`str::parse`]:
+//!
+//! | Assignment | Edit |
+//! |---|---|
+//! | `dna.nose.blend0=0.7` | [`ChfOp::SetBlend`] |
+//! | `dna.nose.scale=0.8`, `dna.scale=0.8` | [`ChfOp::ScaleBlends`] |
+//! | `port.eyes=<guid>`, `port.eyes=none` | [`ChfOp::SetItem`] |
+//! | `port.hair=remove` | [`ChfOp::RemovePort`] |
+//! | `material.eyes.iris_color=#3366ff` | [`ChfOp::SetColor`] |
+//! | `material.*.roughness=0.5`, `material.roughness=0.5` | [`ChfOp::SetFloat`] |
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use svarog_common::CigGuid;
+
+use crate::parts::{ChfData, ColorRgba, FacePart, NameHash, BLENDS_PER_FACE_PART};
+use crate::{ChfFile, Error, Result};
+
+/// A single edit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "op", rename_all = "snake_case")
+)]
+pub enum ChfOp {
+    /// Attach an item to an existing port, or detach it with `None`.
+    SetItem {
+        /// Port name.
+        port: NameHash,
+        /// Item record GUID.
+        item: Option<CigGuid>,
+    },
+    /// Remove a port and everything attached below it.
+    RemovePort {
+        /// Port name.
+        port: NameHash,
+    },
+    /// Set one blend weight of a face part, clamped to 0.0..=1.0.
+    SetBlend {
+        /// Face part.
+        face_part: FacePart,
+        /// Blend slot, below [`BLENDS_PER_FACE_PART`].
+        index: usize,
+        /// New weight.
+        percent: f32,
+    },
+    /// Multiply blend weights, clamping each to 0.0..=1.0.
+    ScaleBlends {
+        /// Face parts to scale; all of them if empty.
+        #[cfg_attr(feature = "serde", serde(default))]
+        face_parts: Vec<FacePart>,
+        /// Scale factor.
+        factor: f32,
+    },
+    /// Overwrite a float parameter in every sub-material that has it.
+    SetFloat {
+        /// Only edit this material; all materials if `None`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        material: Option<NameHash>,
+        /// Parameter name.
+        param: NameHash,
+        /// New value.
+        value: f32,
+    },
+    /// Overwrite a color parameter in every sub-material that has it.
+    SetColor {
+        /// Only edit this material; all materials if `None`.
+        #[cfg_attr(feature = "serde", serde(default))]
+        material: Option<NameHash>,
+        /// Parameter name.
+        param: NameHash,
+        /// New color, `#RRGGBBAA` in JSON.
+        value: ColorRgba,
+    },
+}
+
+impl ChfOp {
+    /// Apply the edit to `data`.
+    ///
+    /// Fails if the targeted port or parameter does not exist, leaving
+    /// `data` unchanged.
+    pub fn apply(&self, data: &mut ChfData) -> Result<()> {
+        match self {
+            ChfOp::SetItem { port, item } => {
+                let root = data.item_port_mut().ok_or(Error::PortNotFound(*port))?;
+                root.replace_item(*port, *item)?;
+            }
+            ChfOp::RemovePort { port } => {
+                data.item_port_mut()
+                    .and_then(|root| root.remove(*port))
+                    .ok_or(Error::PortNotFound(*port))?;
+            }
+            ChfOp::SetBlend {
+                face_part,
+                index,
+                percent,
+            } => {
+                let blend = data
+                    .dna_mut()
+                    .face_part_blends_mut(*face_part)
+                    .get_mut(*index)
+                    .ok_or_else(|| {
+                        Error::InvalidEdit(format!("{} has no blend {}", face_part, index))
+                    })?;
+                blend.percent = percent.clamp(0.0, 1.0);
+            }
+            ChfOp::ScaleBlends { face_parts, factor } => {
+                let face_parts = if face_parts.is_empty() {
+                    FacePart::all().to_vec()
+                } else {
+                    face_parts.clone()
+                };
+                for face_part in face_parts {
+                    for blend in data.dna_mut().face_part_blends_mut(face_part) {
+                        blend.percent = (blend.percent * factor).clamp(0.0, 1.0);
+                    }
+                }
+            }
+            ChfOp::SetFloat {
+                material,
+                param,
+                value,
+            } => set_param(data, *material, *param, |sub| {
+                sub.set_float_param(*param, *value)
+            })?,
+            ChfOp::SetColor {
+                material,
+                param,
+                value,
+            } => set_param(data, *material, *param, |sub| {
+                sub.set_color_param(*param, *value)
+            })?,
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ChfOp {
+    type Err = Error;
+
+    /// Parse a `path=value` assignment, see the [module docs](self).
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::InvalidEdit(format!("{}: {}", s, message));
+        let (path, value) = s
+            .split_once('=')
+            .ok_or_else(|| invalid("expected path=value"))?;
+        let (path, value) = (path.trim(), value.trim());
+        let float = || {
+            value
+                .parse::<f32>()
+                .map_err(|_| invalid("expected a number"))
+        };
+        let face_part = |name: &str| {
+            FacePart::all()
+                .into_iter()
+                .find(|fp| fp.name().eq_ignore_ascii_case(&name.replace('_', "")))
+                .ok_or_else(|| invalid("unknown face part"))
+        };
+
+        let segments: Vec<&str> = path.split('.').collect();
+        match segments.as_slice() {
+            ["dna", "scale"] => Ok(ChfOp::ScaleBlends {
+                face_parts: Vec::new(),
+                factor: float()?,
+            }),
+            ["dna", part, "scale"] => Ok(ChfOp::ScaleBlends {
+                face_parts: vec![face_part(part)?],
+                factor: float()?,
+            }),
+            ["dna", part, blend] => {
+                let index = blend
+                    .strip_prefix("blend")
+                    .and_then(|i| i.parse::<usize>().ok())
+                    .filter(|&i| i < BLENDS_PER_FACE_PART)
+                    .ok_or_else(|| invalid("expected blend0 to blend3 or scale"))?;
+                Ok(ChfOp::SetBlend {
+                    face_part: face_part(part)?,
+                    index,
+                    percent: float()?,
+                })
+            }
+            ["port", port] => {
+                let port = NameHash::from_name_or_hex(port);
+                match value.to_ascii_lowercase().as_str() {
+                    "remove" => Ok(ChfOp::RemovePort { port }),
+                    "none" => Ok(ChfOp::SetItem { port, item: None }),
+                    _ => Ok(ChfOp::SetItem {
+                        port,
+                        item: Some(value.parse().map_err(|_| invalid("expected a GUID"))?),
+                    }),
+                }
+            }
+            ["material", param] => material_op(None, param, value)
+                .ok_or_else(|| invalid("expected a number or #RRGGBB[AA] color")),
+            ["material", material, param] => {
+                let material = (*material != "*").then(|| NameHash::from_name_or_hex(material));
+                material_op(material, param, value)
+                    .ok_or_else(|| invalid("expected a number or #RRGGBB[AA] color"))
+            }
+            _ => Err(invalid(
+                "unknown path, expected dna.*, port.* or material.*",
+            )),
+        }
+    }
+}
+
+fn material_op(material: Option<NameHash>, param: &str, value: &str) -> Option<ChfOp> {
+    let param = NameHash::from_name_or_hex(param);
+    if value.starts_with('#') {
+        ColorRgba::from_hex(value).map(|value| ChfOp::SetColor {
+            material,
+            param,
+            value,
+        })
+    } else {
+        value.parse().ok().map(|value| ChfOp::SetFloat {
+            material,
+            param,
+            value,
+        })
+    }
+}
+
+fn set_param(
+    data: &mut ChfData,
+    material: Option<NameHash>,
+    param: NameHash,
+    mut set: impl FnMut(&mut crate::parts::SubMaterial) -> bool,
+) -> Result<()> {
+    let mut found = false;
+    for m in data.materials_mut() {
+        if material.is_some_and(|name| name != m.name()) {
+            continue;
+        }
+        for sub in m.sub_materials_mut() {
+            found |= set(sub);
+        }
+    }
+    if found {
+        Ok(())
+    } else {
+        Err(Error::ParamNotFound(param))
+    }
+}
+
+/// An ordered list of edits.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChfScript {
+    /// Edits, applied in order.
+    pub ops: Vec<ChfOp>,
+}
+
+/// Outcome of one file.
+#[derive(Debug)]
+pub struct ChfBatchResult {
+    /// Input file.
+    pub path: PathBuf,
+    /// Where the edited character was (or would have been) written.
+    pub output: PathBuf,
+    /// The error that stopped the edit, if any.
+    pub status: Result<()>,
+}
+
+/// Results of a [`ChfScript::apply_to_dir`] run, sorted by path.
+#[derive(Debug, Default)]
+pub struct ChfBatchReport {
+    /// One entry per `.chf` file.
+    pub results: Vec<ChfBatchResult>,
+}
+
+impl ChfBatchReport {
+    /// Number of files edited.
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.status.is_ok()).count()
+    }
+
+    /// Number of files that failed.
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+
+    /// Iterate over the failed files.
+    pub fn failures(&self) -> impl Iterator<Item = &ChfBatchResult> {
+        self.results.iter().filter(|r| r.status.is_err())
+    }
+}
+
+impl ChfScript {
+    /// Create a script from a list of edits.
+    pub fn new(ops: Vec<ChfOp>) -> Self {
+        Self { ops }
+    }
+
+    /// Parse a script from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Apply every edit in order.
+    ///
+    /// On error `data` may be partially edited; the file-level functions
+    /// only write characters that were edited completely.
+    pub fn apply(&self, data: &mut ChfData) -> Result<()> {
+        self.ops.iter().try_for_each(|op| op.apply(data))
+    }
+
+    /// Edit one `.chf` file, writing the result to `output` (which may be
+    /// `path` itself). The output is flagged as modded.
+    pub fn apply_to_file(&self, path: &Path, output: &Path) -> Result<()> {
+        let chf = ChfFile::from_chf(path)?;
+        let mut data = ChfData::parse(chf.data())?;
+        self.apply(&mut data)?;
+
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        ChfFile::new(data.to_bytes(), true).write_to_chf(output)
+    }
+
+    /// Edit every `.chf` file directly inside `dir`.
+    ///
+    /// Results are written to `output_dir` under the same file names, or
+    /// over the inputs if `output_dir` is `None`. A failing file does not
+    /// stop the run.
+    pub fn apply_to_dir(&self, dir: &Path, output_dir: Option<&Path>) -> Result<ChfBatchReport> {
+        let results = chf_files(dir)?
+            .into_iter()
+            .map(|path| {
+                let output = match (output_dir, path.file_name()) {
+                    (Some(out), Some(name)) => out.join(name),
+                    _ => path.clone(),
+                };
+                let status = self.apply_to_file(&path, &output);
+                ChfBatchResult {
+                    path,
+                    output,
+                    status,
+                }
+            })
+            .collect();
+        Ok(ChfBatchReport { results })
+    }
+}
+
+/// List the `.chf` files directly inside `dir`.
+pub fn chf_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_chf = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("chf"));
+        if is_chf && entry.file_type()?.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parts::{DnaPart, Material, SubMaterial};
+    use crate::ChfBuilder;
+
+    fn sample() -> ChfData {
+        let mut sub = SubMaterial::new(NameHash::from_str("eyes"));
+        sub.add_color_param(NameHash::from_str("iris_color"), ColorRgba::black());
+        let mut material = Material::new(NameHash::from_str("head"), CigGuid::from_bytes([2; 16]));
+        material.add_sub_material(sub);
+
+        ChfBuilder::new(CigGuid::from_bytes([1; 16]))
+            .face_part(
+                FacePart::Nose,
+                &[DnaPart::new(3, 0.8), DnaPart::new(4, 0.2)],
+            )
+            .port(NameHash::from_str("eyes"), None)
+            .material(material)
+            .data()
+            .clone()
+    }
+
+    #[test]
+    fn test_script_apply() {
+        let eyes = CigGuid::from_bytes([9; 16]);
+        let blue = ColorRgba::from_bytes(0x3a, 0x6e, 0xa5, 0xff);
+        let script = ChfScript::new(vec![
+            ChfOp::SetItem {
+                port: NameHash::from_str("eyes"),
+                item: Some(eyes),
+            },
+            ChfOp::ScaleBlends {
+                face_parts: vec![FacePart::Nose],
+                factor: 2.0,
+            },
+            ChfOp::SetColor {
+                material: None,
+                param: NameHash::from_str("iris_color"),
+                value: blue,
+            },
+        ]);
+
+        let mut data = sample();
+        script.apply(&mut data).unwrap();
+
+        let root = data.item_port().unwrap();
+        assert_eq!(root.children()[0].item_guid(), Some(&eyes));
+        let nose = data.dna().face_part_blends(FacePart::Nose);
+        assert_eq!(nose[0].percent, 1.0);
+        assert!((nose[1].percent - 0.4).abs() < 1e-6);
+        assert_eq!(data.dna().face_part_blends(FacePart::Jaw)[0].percent, 1.0);
+        let sub = &data.materials()[0].sub_materials()[0];
+        assert_eq!(
+            sub.color_param(NameHash::from_str("iris_color")),
+            Some(blue)
+        );
+    }
+
+    #[test]
+    fn test_parse_assignments() {
+        let op: ChfOp = "dna.nose.blend0=0.7".parse().unwrap();
+        assert_eq!(
+            op,
+            ChfOp::SetBlend {
+                face_part: FacePart::Nose,
+                index: 0,
+                percent: 0.7
+            }
+        );
+        let op: ChfOp = "dna.eye_left.scale=0.5".parse().unwrap();
+        assert_eq!(
+            op,
+            ChfOp::ScaleBlends {
+                face_parts: vec![FacePart::EyeLeft],
+                factor: 0.5
+            }
+        );
+        let op: ChfOp = "material.eyes.iris_color=#3366ff".parse().unwrap();
+        assert_eq!(
+            op,
+            ChfOp::SetColor {
+                material: Some(NameHash::from_str("eyes")),
+                param: NameHash::from_str("iris_color"),
+                value: ColorRgba::from_bytes(0x33, 0x66, 0xff, 0xff)
+            }
+        );
+        let op: ChfOp = "material.*.roughness=0.25".parse().unwrap();
+        assert!(matches!(op, ChfOp::SetFloat { material: None, .. }));
+        let op: ChfOp = "port.hair=remove".parse().unwrap();
+        assert!(matches!(op, ChfOp::RemovePort { .. }));
+
+        for invalid in [
+            "dna.nose.blend4=0.5",
+            "dna.snout.blend0=0.5",
+            "material.eyes.iris_color=blue",
+            "port.eyes=not-a-guid",
+            "skin.tone=3",
+            "dna.scale",
+        ] {
+            assert!(
+                matches!(invalid.parse::<ChfOp>(), Err(Error::InvalidEdit(_))),
+                "{}",
+                invalid
+            );
+        }
+
+        let mut data = sample();
+        "dna.nose.blend1=1.5"
+            .parse::<ChfOp>()
+            .unwrap()
+            .apply(&mut data)
+            .unwrap();
+        assert_eq!(data.dna().face_part_blends(FacePart::Nose)[1].percent, 1.0);
+    }
+
+    #[test]
+    fn test_missing_targets() {
+        let mut data = sample();
+        let op = ChfOp::RemovePort {
+            port: NameHash::from_str("hair"),
+        };
+        assert!(matches!(op.apply(&mut data), Err(Error::PortNotFound(_))));
+
+        let op = ChfOp::SetFloat {
+            material: None,
+            param: NameHash::from_str("roughness"),
+            value: 0.5,
+        };
+        assert!(matches!(op.apply(&mut data), Err(Error::ParamNotFound(_))));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_script_from_json() {
+        let script = ChfScript::from_json(
+            r##"{ "ops": [
+                { "op": "remove_port", "port": "eyes" },
+                { "op": "scale_blends", "factor": 0.5 },
+                { "op": "set_color", "material": "head", "param": "iris_color", "value": "#3a6ea5" }
+            ] }"##,
+        )
+        .unwrap();
+        assert_eq!(script.ops.len(), 3);
+        assert_eq!(
+            script.ops[1],
+            ChfOp::ScaleBlends {
+                face_parts: Vec::new(),
+                factor: 0.5
+            }
+        );
+
+        let mut data = sample();
+        script.apply(&mut data).unwrap();
+        assert!(data.item_port().unwrap().children().is_empty());
+
+        assert!(ChfScript::from_json(r#"{ "ops": [{ "op": "paint" }] }"#).is_err());
+    }
+}
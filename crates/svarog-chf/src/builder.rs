@@ -0,0 +1,162 @@
+//! Building characters from scratch.
+
+use svarog_common::CigGuid;
+
+use crate::parts::{ChfData, Dna, DnaPart, FacePart, ItemPort, Material, NameHash};
+use crate::{ChfFile, Result};
+
+/// Builds a complete character without starting from an existing file.
+///
+/// The defaults produce a loadable character: every face part fully blended
+/// to head 0, an item port tree consisting of a `body` root and no
+/// materials. The resulting [`ChfFile`] is flagged as modded, so the
+/// `diogotr7` trailer is written.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_chf::{ChfBuilder, FacePart, NameHash};
+/// use svarog_chf::parts::DnaPart;
+/// use svarog_common::CigGuid;
+///
+/// // Body and hair record GUIDs, e.g. looked up in the DataCore
+/// let gender: CigGuid = std::env::args().nth(1).unwrap().parse()?;
+/// let hair: CigGuid = std::env::args().nth(2).unwrap().parse()?;
+///
+/// ChfBuilder::new(gender)
+///     .face_part(FacePart::Nose, &[DnaPart::new(3, 0.6), DnaPart::new(7, 0.4)])
+///     .port(NameHash::from_str("hair"), Some(hair))
+///     .write_to_chf("custom.chf")?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChfBuilder {
+    data: ChfData,
+    modded: bool,
+}
+
+impl ChfBuilder {
+    /// Name of the default root item port.
+    pub const ROOT_PORT: &'static str = "body";
+
+    /// Start a character of the given gender (body record GUID).
+    pub fn new(gender_id: CigGuid) -> Self {
+        let mut dna = Dna::new();
+        for face_part in FacePart::all() {
+            dna.face_part_blends_mut(face_part)[0] = DnaPart::new(0, 1.0);
+        }
+
+        let mut data = ChfData::new(gender_id);
+        *data.dna_mut() = dna;
+        data.set_item_port(Some(ItemPort::new(NameHash::from_str(Self::ROOT_PORT))));
+
+        Self { data, modded: true }
+    }
+
+    /// Replace the whole DNA.
+    pub fn dna(mut self, dna: Dna) -> Self {
+        *self.data.dna_mut() = dna;
+        self
+    }
+
+    /// Set the blends of one face part; unused slots are cleared.
+    pub fn face_part(mut self, face_part: FacePart, blends: &[DnaPart]) -> Self {
+        let slots = self.data.dna_mut().face_part_blends_mut(face_part);
+        for (i, slot) in slots.iter_mut().enumerate() {
+            *slot = blends.get(i).copied().unwrap_or_default();
+        }
+        self
+    }
+
+    /// Replace the item port tree.
+    pub fn item_port(mut self, port: ItemPort) -> Self {
+        self.data.set_item_port(Some(port));
+        self
+    }
+
+    /// Add a port below the root, optionally with an item attached.
+    pub fn port(mut self, name: NameHash, item_guid: Option<CigGuid>) -> Self {
+        if let Some(root) = self.data.item_port_mut() {
+            root.add_port(name, item_guid);
+        }
+        self
+    }
+
+    /// Add a material.
+    pub fn material(mut self, material: Material) -> Self {
+        self.data.add_material(material);
+        self
+    }
+
+    /// Whether to write the modded trailer (default `true`).
+    pub fn modded(mut self, modded: bool) -> Self {
+        self.modded = modded;
+        self
+    }
+
+    /// The character data built so far.
+    pub fn data(&self) -> &ChfData {
+        &self.data
+    }
+
+    /// Finish into a [`ChfFile`], checking that the compressed data fits
+    /// into the fixed 4096-byte file.
+    pub fn build(self) -> Result<ChfFile> {
+        let file = ChfFile::new(self.data.to_bytes(), self.modded);
+        file.to_chf_bytes()?;
+        Ok(file)
+    }
+
+    /// Finish into the 4096-byte CHF file contents, with CRC32C, sizes and
+    /// trailer filled in.
+    pub fn to_chf_bytes(self) -> Result<Vec<u8>> {
+        ChfFile::new(self.data.to_bytes(), self.modded).to_chf_bytes()
+    }
+
+    /// Finish and write a `.chf` file.
+    pub fn write_to_chf<P: AsRef<std::path::Path>>(self, path: P) -> Result<()> {
+        ChfFile::new(self.data.to_bytes(), self.modded).write_to_chf(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::CHF_SIZE;
+
+    #[test]
+    fn test_build_round_trip() {
+        let gender = CigGuid::from_bytes([5; 16]);
+        let hair = CigGuid::from_bytes([6; 16]);
+
+        let bytes = ChfBuilder::new(gender)
+            .face_part(
+                FacePart::Nose,
+                &[DnaPart::new(3, 0.6), DnaPart::new(7, 0.4)],
+            )
+            .port(NameHash::from_str("hair"), Some(hair))
+            .material(Material::new(
+                NameHash::from_str("head"),
+                CigGuid::from_bytes([7; 16]),
+            ))
+            .to_chf_bytes()
+            .unwrap();
+        assert_eq!(bytes.len(), CHF_SIZE);
+        assert_eq!(&bytes[CHF_SIZE - 8..], b"diogotr7");
+
+        let file = ChfFile::parse(&bytes).unwrap();
+        assert!(file.is_modded());
+
+        let data = ChfData::parse(file.data()).unwrap();
+        assert_eq!(data.gender_id(), &gender);
+        let nose = data.dna().face_part_blends(FacePart::Nose);
+        assert_eq!(nose[0].head_id, 3);
+        assert!(nose[2].is_zero());
+        assert!(!data.dna().face_part_blends(FacePart::Jaw)[0].is_zero());
+
+        let root = data.item_port().unwrap();
+        assert_eq!(root.name(), NameHash::from_str(ChfBuilder::ROOT_PORT));
+        assert_eq!(root.children()[0].item_guid(), Some(&hair));
+        assert_eq!(data.materials().len(), 1);
+    }
+}
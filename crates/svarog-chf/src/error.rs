@@ -1,5 +1,6 @@
 //! Error types for CHF parsing.
 
+use svarog_common::ErrorLocation;
 use thiserror::Error;
 
 /// Errors that can occur when working with CHF files.
@@ -40,6 +41,38 @@ pub enum Error {
     /// Decompressed size mismatch.
     #[error("decompressed size mismatch: expected {expected}, got {actual}")]
     SizeMismatch { expected: usize, actual: usize },
+
+    /// A detail that lenient parsing would recover from.
+    #[error("non-canonical CHF file: {0}")]
+    NonCanonical(crate::ChfWarning),
+
+    /// No item port with the given name exists in the tree.
+    #[error("item port {0} not found")]
+    PortNotFound(crate::NameHash),
+
+    /// No sub-material has a parameter with the given name.
+    #[error("material parameter {0} not found")]
+    ParamNotFound(crate::NameHash),
+
+    /// An edit is malformed or targets something that cannot exist.
+    #[error("invalid edit: {0}")]
+    InvalidEdit(String),
+
+    /// Characters of different genders cannot be blended.
+    #[error("cannot blend characters of different genders: {from} and {to}")]
+    GenderMismatch {
+        from: svarog_common::CigGuid,
+        to: svarog_common::CigGuid,
+    },
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// Result type for CHF operations.
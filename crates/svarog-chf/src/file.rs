@@ -4,7 +4,7 @@ use std::fs;
 use std::io::Read;
 use std::path::Path;
 
-use svarog_common::{crc, BinaryReader};
+use svarog_common::{crc, BinaryReader, BinaryWriter};
 
 use crate::{Error, Result};
 
@@ -27,12 +27,143 @@ pub struct ChfFile {
     data: Vec<u8>,
     /// Whether this is a modded character.
     modded: bool,
+    /// Problems recovered from while parsing.
+    warnings: Vec<ChfWarning>,
+}
+
+/// Options for [`ChfFile::parse_with`].
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions {
+    strict: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { strict: true }
+    }
+}
+
+impl ParseOptions {
+    /// Create strict options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options that recover what they can and record warnings.
+    pub fn lenient() -> Self {
+        Self::new().strict(false)
+    }
+
+    /// Reject anything non-canonical (default `true`).
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether strict mode is enabled.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+}
+
+/// A non-canonical detail of a CHF file.
+///
+/// Lenient parsing records these in [`ChfFile::warnings`]; strict parsing
+/// fails with the matching [`Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChfWarning {
+    /// The file is longer than 4096 bytes; the extra bytes were ignored.
+    TrailingData {
+        /// Actual file length.
+        len: usize,
+    },
+    /// The file is shorter than 4096 bytes.
+    Truncated {
+        /// Actual file length.
+        len: usize,
+    },
+    /// The stored CRC32C does not match the contents.
+    CrcMismatch {
+        /// Stored checksum.
+        expected: u32,
+        /// Computed checksum.
+        actual: u32,
+    },
+    /// The compressed size points past the end of the file; the rest of
+    /// the file was decompressed instead.
+    CompressedSizeOutOfBounds {
+        /// Size in the header.
+        declared: usize,
+        /// Bytes available after the header.
+        available: usize,
+    },
+    /// The uncompressed size in the header is wrong.
+    UncompressedSizeMismatch {
+        /// Size in the header.
+        expected: usize,
+        /// Actual decompressed size.
+        actual: usize,
+    },
+    /// The trailer is neither the modded magic nor zeros; the file is
+    /// treated as not modded.
+    UnknownTrailer([u8; 8]),
+}
+
+impl ChfWarning {
+    fn into_error(self) -> Error {
+        match self {
+            ChfWarning::TrailingData { len } | ChfWarning::Truncated { len } => {
+                Error::InvalidSize(len)
+            }
+            ChfWarning::CrcMismatch { expected, actual } => Error::CrcMismatch { expected, actual },
+            ChfWarning::UncompressedSizeMismatch { expected, actual } => {
+                Error::SizeMismatch { expected, actual }
+            }
+            warning => Error::NonCanonical(warning),
+        }
+    }
+}
+
+impl std::fmt::Display for ChfWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChfWarning::TrailingData { len } => {
+                write!(f, "{} trailing bytes after 4096", len - CHF_SIZE)
+            }
+            ChfWarning::Truncated { len } => write!(f, "truncated to {} bytes", len),
+            ChfWarning::CrcMismatch { expected, actual } => write!(
+                f,
+                "CRC32C mismatch: expected {:#010x}, got {:#010x}",
+                expected, actual
+            ),
+            ChfWarning::CompressedSizeOutOfBounds {
+                declared,
+                available,
+            } => write!(
+                f,
+                "compressed size {} exceeds the {} available bytes",
+                declared, available
+            ),
+            ChfWarning::UncompressedSizeMismatch { expected, actual } => write!(
+                f,
+                "uncompressed size is {}, header says {}",
+                actual, expected
+            ),
+            ChfWarning::UnknownTrailer(trailer) => {
+                write!(f, "unknown trailer {:02x?}", trailer)
+            }
+        }
+    }
 }
 
 impl ChfFile {
     /// Create a new CHF file from raw (uncompressed) data.
     pub fn new(data: Vec<u8>, modded: bool) -> Self {
-        Self { data, modded }
+        Self {
+            data,
+            modded,
+            warnings: Vec::new(),
+        }
     }
 
     /// Get the decompressed data.
@@ -57,6 +188,11 @@ impl ChfFile {
 
     /// Read a CHF file from disk.
     pub fn from_chf<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_chf_with(path, &ParseOptions::default())
+    }
+
+    /// Read a CHF file from disk with explicit parse options.
+    pub fn from_chf_with<P: AsRef<Path>>(path: P, options: &ParseOptions) -> Result<Self> {
         let path = path.as_ref();
 
         // Validate extension
@@ -72,12 +208,7 @@ impl ChfFile {
         }
 
         let file_bytes = fs::read(path)?;
-
-        if file_bytes.len() != CHF_SIZE {
-            return Err(Error::InvalidSize(file_bytes.len()));
-        }
-
-        Self::parse(&file_bytes)
+        Self::parse_with(&file_bytes, options)
     }
 
     /// Read from a bin file (uncompressed).
@@ -99,11 +230,41 @@ impl ChfFile {
         Ok(Self::new(data, modded))
     }
 
-    /// Parse CHF data from bytes.
+    /// Parse CHF data from bytes, rejecting anything non-canonical.
     pub fn parse(data: &[u8]) -> Result<Self> {
-        if data.len() != CHF_SIZE {
+        Self::parse_with(data, &ParseOptions::default())
+    }
+
+    /// Parse CHF data from bytes.
+    ///
+    /// In lenient mode, recoverable problems are recorded in
+    /// [`ChfFile::warnings`] instead of failing. A wrong magic, a missing
+    /// header or undecodable data are always errors.
+    pub fn parse_with(data: &[u8], options: &ParseOptions) -> Result<Self> {
+        let mut warnings = Vec::new();
+        let mut warn = |warning: ChfWarning| {
+            if options.strict {
+                Err(warning.into_error())
+            } else {
+                warnings.push(warning);
+                Ok(())
+            }
+        };
+
+        if data.len() < 16 {
             return Err(Error::InvalidSize(data.len()));
         }
+        let data = match data.len() {
+            CHF_SIZE => data,
+            len if len > CHF_SIZE => {
+                warn(ChfWarning::TrailingData { len })?;
+                &data[..CHF_SIZE]
+            }
+            len => {
+                warn(ChfWarning::Truncated { len })?;
+                data
+            }
+        };
 
         let mut reader = BinaryReader::new(data);
 
@@ -126,38 +287,61 @@ impl ChfFile {
         let actual_crc = crc::hash_bytes(crc_data);
 
         if actual_crc != expected_crc {
-            return Err(Error::CrcMismatch {
+            warn(ChfWarning::CrcMismatch {
                 expected: expected_crc,
                 actual: actual_crc,
-            });
+            })?;
         }
 
-        // Decompress data
-        let compressed_data = reader.read_bytes(compressed_size)?;
-        let mut decompressed = Vec::with_capacity(uncompressed_size);
-
+        let available = reader.remaining();
+        let compressed_data = if compressed_size <= available {
+            reader.read_bytes(compressed_size)?
+        } else {
+            warn(ChfWarning::CompressedSizeOutOfBounds {
+                declared: compressed_size,
+                available,
+            })?;
+            reader.read_bytes(available)?
+        };
+
+        // Decompress data; a single frame, so padding after a wrong
+        // compressed size is ignored
+        let mut decompressed = Vec::with_capacity(uncompressed_size.min(CHF_SIZE * 64));
         zstd::Decoder::new(compressed_data)
             .map_err(|e| Error::Decompression(e.to_string()))?
+            .single_frame()
             .read_to_end(&mut decompressed)
             .map_err(|e| Error::Decompression(e.to_string()))?;
 
         if decompressed.len() != uncompressed_size {
-            return Err(Error::SizeMismatch {
+            warn(ChfWarning::UncompressedSizeMismatch {
                 expected: uncompressed_size,
                 actual: decompressed.len(),
-            });
+            })?;
         }
 
         // Check for modded magic at the end
-        let trailer = &data[CHF_SIZE - 8..];
-        let is_modded = Self::check_modded(trailer);
+        let mut modded = false;
+        if data.len() == CHF_SIZE {
+            let trailer: [u8; 8] = data[CHF_SIZE - 8..].try_into().unwrap();
+            modded = Self::check_modded(&trailer);
+            if !modded {
+                warn(ChfWarning::UnknownTrailer(trailer))?;
+            }
+        }
 
         Ok(Self {
             data: decompressed,
-            modded: is_modded,
+            modded,
+            warnings,
         })
     }
 
+    /// Problems recovered from by a lenient [`ChfFile::parse_with`].
+    pub fn warnings(&self) -> &[ChfWarning] {
+        &self.warnings
+    }
+
     /// Write to a CHF file.
     pub fn write_to_chf<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let path = path.as_ref();
@@ -199,8 +383,6 @@ impl ChfFile {
 
     /// Convert to CHF bytes for writing.
     pub fn to_chf_bytes(&self) -> Result<Vec<u8>> {
-        let mut output = vec![0u8; CHF_SIZE];
-
         // Compress data
         let compressed =
             zstd::encode_all(&self.data[..], 16).map_err(|e| Error::Compression(e.to_string()))?;
@@ -213,25 +395,27 @@ impl ChfFile {
         }
 
         // Write header
-        output[0..2].copy_from_slice(&CIG_MAGIC.to_le_bytes());
+        let mut output = BinaryWriter::with_capacity(CHF_SIZE);
+        output.write_u16(CIG_MAGIC);
         // Bytes 2-3 are unknown, leave as zero
+        output.write_zeros(2);
         // Bytes 4-7 are CRC, will be filled in later
-        output[8..12].copy_from_slice(&(compressed.len() as u32).to_le_bytes());
-        output[12..16].copy_from_slice(&(self.data.len() as u32).to_le_bytes());
-
-        // Write compressed data
-        output[16..16 + compressed.len()].copy_from_slice(&compressed);
-
-        // Write modded magic if applicable
-        if self.modded {
-            output[CHF_SIZE - 8..].copy_from_slice(MODDED_MAGIC);
+        let crc_slot = output.reserve_u32();
+        output.write_u32(compressed.len() as u32);
+        output.write_u32(self.data.len() as u32);
+
+        // Write compressed data, zero-padded up to the trailer, and the
+        // modded magic if applicable; the CRC covers all of it
+        let padding = vec![0u8; CHF_SIZE - 8 - output.len() - compressed.len()];
+        let trailer: &[u8] = if self.modded { MODDED_MAGIC } else { &[0; 8] };
+        let mut crc = crc::Hasher::new();
+        for part in [&compressed[..], &padding, trailer] {
+            output.write_bytes(part);
+            crc.update(part);
         }
+        output.patch_u32(crc_slot, crc.finalize());
 
-        // Calculate and write CRC
-        let crc = crc::hash_bytes(&output[16..]);
-        output[4..8].copy_from_slice(&crc.to_le_bytes());
-
-        Ok(output)
+        Ok(output.into_inner())
     }
 
     /// Check if the trailer indicates a modded file.
@@ -251,4 +435,61 @@ mod tests {
         assert!(ChfFile::check_modded(&[0u8; 8]));
         assert!(!ChfFile::check_modded(b"12345678"));
     }
+
+    fn sample_bytes() -> Vec<u8> {
+        ChfFile::new(vec![7; 300], true).to_chf_bytes().unwrap()
+    }
+
+    #[test]
+    fn test_strict_rejects_non_canonical() {
+        let bytes = sample_bytes();
+        let file = ChfFile::parse(&bytes).unwrap();
+        assert!(file.warnings().is_empty());
+
+        let mut long = bytes.clone();
+        long.extend_from_slice(b"garbage");
+        assert!(matches!(
+            ChfFile::parse(&long),
+            Err(Error::InvalidSize(4103))
+        ));
+
+        let mut wrong_size = bytes.clone();
+        wrong_size[12..16].copy_from_slice(&299u32.to_le_bytes());
+        assert!(matches!(
+            ChfFile::parse(&wrong_size),
+            Err(Error::SizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lenient_recovers() {
+        let lenient = ParseOptions::lenient();
+        let mut bytes = sample_bytes();
+        bytes[12..16].copy_from_slice(&299u32.to_le_bytes());
+        bytes[CHF_SIZE - 8..].copy_from_slice(b"othertoo");
+        bytes.extend_from_slice(b"garbage");
+
+        let file = ChfFile::parse_with(&bytes, &lenient).unwrap();
+        assert_eq!(file.data(), &[7; 300][..]);
+        assert!(!file.is_modded());
+        assert_eq!(
+            file.warnings()[0],
+            ChfWarning::TrailingData { len: CHF_SIZE + 7 }
+        );
+        assert!(matches!(file.warnings()[1], ChfWarning::CrcMismatch { .. }));
+        assert_eq!(
+            file.warnings()[2],
+            ChfWarning::UncompressedSizeMismatch {
+                expected: 299,
+                actual: 300
+            }
+        );
+        assert_eq!(file.warnings()[3], ChfWarning::UnknownTrailer(*b"othertoo"));
+
+        // Strict mode reports the first problem
+        assert!(matches!(
+            ChfFile::parse(&bytes[..CHF_SIZE]),
+            Err(Error::CrcMismatch { .. })
+        ));
+    }
 }
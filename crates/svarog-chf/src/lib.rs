@@ -44,13 +44,50 @@
 //! chf.write_to_chf("output.chf")?;
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! # JSON
+//!
+//! With the `json` feature, `ChfData::to_json` and `ChfData::from_json`
+//! convert characters to and from an editable JSON form:
+//!
+//! ```ignore
+//! let json = data.to_json()?;
+//! let rebuilt = ChfData::from_json(&json)?;
+//! ChfFile::new(rebuilt.to_bytes(), true).write_to_chf("edited.chf")?;
+//! ```
+//!
+//! # Batch Edits
+//!
+//! A [`ChfScript`] applies the same declarative edits to every character in
+//! a directory:
+//!
+//! ```ignore
+//! let script = ChfScript::from_json(&std::fs::read_to_string("ops.json")?)?;
+//! let report = script.apply_to_dir(Path::new("CustomCharacters"), None)?;
+//! println!("{} edited, {} failed", report.succeeded(), report.failed());
+//! ```
 
+mod batch;
+mod builder;
 mod error;
 mod file;
 pub mod parts;
+#[cfg(feature = "datacore")]
+mod resolve;
+#[cfg(feature = "datacore")]
+mod validate;
 
+pub use batch::{chf_files, ChfBatchReport, ChfBatchResult, ChfOp, ChfScript};
+pub use builder::ChfBuilder;
 pub use error::{Error, Result};
-pub use file::ChfFile;
+pub use file::{ChfFile, ChfWarning, ParseOptions};
+#[cfg(feature = "datacore")]
+pub use resolve::{
+    register_datacore_names, ChfResolver, ResolvedCharacter, ResolvedMaterial, ResolvedPort,
+    ResolvedRecord,
+};
+#[cfg(feature = "datacore")]
+pub use validate::{ChfIssue, ChfValidation};
 
 // Re-export commonly used types at crate root
-pub use parts::{ChfData, Dna, FacePart, ItemPort, Material, NameHash};
+pub use parts::{ChfData, Dna, FacePart, GenderMapping, ItemPort, Material, NameHash};
@@ -3,7 +3,7 @@
 //! The ChfData structure represents the decompressed contents of a CHF file,
 //! containing character gender, DNA (facial features), equipment ports, and materials.
 
-use svarog_common::{BinaryReader, CigGuid};
+use svarog_common::{BinaryReader, BinaryWriter, CigGuid, ResultExt};
 
 use super::dna::Dna;
 use super::itemport::ItemPort;
@@ -11,6 +11,9 @@ use super::material::Material;
 use super::name_hash::NameHash;
 use crate::{Error, Result};
 
+/// Size of an item port without children: name hash, GUID and child count.
+const EMPTY_PORT_SIZE: usize = 4 + 16 + 4;
+
 /// The main CHF data container.
 ///
 /// Contains all character customization data:
@@ -19,6 +22,7 @@ use crate::{Error, Result};
 /// - Item port tree (equipment attachment points)
 /// - Materials (appearance customizations)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChfData {
     /// The gender GUID.
     gender_id: CigGuid,
@@ -46,11 +50,13 @@ impl ChfData {
         let mut reader = BinaryReader::new(data);
 
         // Read gender GUID (16 bytes)
-        let guid_bytes = reader.read_bytes(16)?;
+        let guid_bytes = reader.read_bytes(16).in_section("gender", 0)?;
         let gender_id = CigGuid::from_bytes(guid_bytes.try_into().unwrap());
 
         // Read DNA (0xD8 bytes)
-        let dna_bytes = reader.read_bytes(super::dna::DNA_SIZE)?;
+        let dna_bytes = reader
+            .read_bytes(super::dna::DNA_SIZE)
+            .in_section("DNA", 16)?;
         let dna = Dna::parse(dna_bytes)?;
 
         // Check if there's an item port tree
@@ -65,11 +71,13 @@ impl ChfData {
 
             if maybe_hash.is_some() && maybe_hash != Some(0) {
                 // Try to parse item port tree
-                match read_item_port(&mut reader) {
-                    Ok(port) => Some(port),
-                    Err(_) => None,
-                }
+                read_item_port(&mut reader).ok()
             } else {
+                // Skip the empty placeholder port written by `to_bytes`
+                let placeholder = reader.peek_bytes(EMPTY_PORT_SIZE).ok();
+                if placeholder.is_some_and(|bytes| bytes.iter().all(|&b| b == 0)) {
+                    reader.advance(EMPTY_PORT_SIZE);
+                }
                 None
             }
         } else {
@@ -154,33 +162,61 @@ impl ChfData {
         self.materials.iter().find(|m| m.name() == name)
     }
 
+    /// Blend two characters, `t` going from 0.0 (`a`) to 1.0 (`b`).
+    ///
+    /// DNA is blended with [`Dna::lerp`] and materials present in both are
+    /// blended with [`Material::lerp`]. Equipment and unmatched materials come
+    /// from whichever side `t` is closer to. Both characters must share a
+    /// gender, as head IDs refer to different morphs per gender.
+    pub fn lerp(a: &ChfData, b: &ChfData, t: f32) -> Result<Self> {
+        if a.gender_id != b.gender_id {
+            return Err(Error::GenderMismatch {
+                from: a.gender_id,
+                to: b.gender_id,
+            });
+        }
+
+        let t = t.clamp(0.0, 1.0);
+        let mut result = if t < 0.5 { a.clone() } else { b.clone() };
+        result.dna = Dna::lerp(&a.dna, &b.dna, t);
+
+        for material in &mut result.materials {
+            let name = material.name();
+            if let (Some(from), Some(to)) = (a.find_material(name), b.find_material(name)) {
+                *material = Material::lerp(from, to, t);
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Convert to bytes for writing.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut writer = BinaryWriter::new();
 
         // Write gender GUID
-        bytes.extend_from_slice(self.gender_id.as_bytes());
+        writer.write_struct(&self.gender_id);
 
         // Write DNA
-        bytes.extend_from_slice(&self.dna.to_bytes());
+        self.dna.write_to(&mut writer);
 
         // Write item port tree
         if let Some(ref port) = self.item_port {
-            bytes.extend_from_slice(&port.to_bytes());
+            port.write_to(&mut writer);
         } else {
             // Write a placeholder empty port
-            bytes.extend_from_slice(&0u32.to_le_bytes()); // name hash = 0
-            bytes.extend_from_slice(&[0u8; 16]); // nil GUID
-            bytes.extend_from_slice(&0u32.to_le_bytes()); // no children
+            writer.write_u32(0); // name hash = 0
+            writer.write_zeros(16); // nil GUID
+            writer.write_u32(0); // no children
         }
 
         // Write materials
-        bytes.extend_from_slice(&(self.materials.len() as u32).to_le_bytes());
+        writer.write_u32(self.materials.len() as u32);
         for material in &self.materials {
-            bytes.extend_from_slice(&material.to_bytes());
+            material.write_to(&mut writer);
         }
 
-        bytes
+        writer.into_inner()
     }
 }
 
@@ -224,6 +260,7 @@ fn read_item_port(reader: &mut BinaryReader<'_>) -> Result<ItemPort> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use svarog_common::ErrorLocation;
 
     #[test]
     fn test_chf_data_new() {
@@ -234,4 +271,34 @@ mod tests {
         assert!(data.item_port().is_none());
         assert!(data.materials().is_empty());
     }
+
+    #[test]
+    fn test_chf_data_without_item_port_keeps_materials() {
+        let mut data = ChfData::new(CigGuid::from_bytes([1; 16]));
+        data.add_material(Material::new(
+            NameHash::from_str("head"),
+            CigGuid::from_bytes([2; 16]),
+        ));
+
+        let parsed = ChfData::parse(&data.to_bytes()).unwrap();
+        assert!(parsed.item_port().is_none());
+        assert_eq!(parsed.materials().len(), 1);
+    }
+
+    #[test]
+    fn test_chf_data_truncated_dna_reports_section() {
+        let err = ChfData::parse(&[0; 40]).unwrap_err();
+        assert_eq!(err.section(), Some("DNA"));
+        assert_eq!(err.offset(), Some(16));
+    }
+
+    #[test]
+    fn test_chf_data_lerp_requires_same_gender() {
+        let male = ChfData::new(CigGuid::from_bytes([1; 16]));
+        let female = ChfData::new(CigGuid::from_bytes([2; 16]));
+
+        assert!(ChfData::lerp(&male, &female, 0.5).is_err());
+        let blended = ChfData::lerp(&male, &male.clone(), 0.5).unwrap();
+        assert_eq!(blended.gender_id(), male.gender_id());
+    }
 }
@@ -4,7 +4,7 @@
 //! Each face part (eyebrow, eye, nose, etc.) has up to 4 blend targets, where each
 //! target specifies a "head ID" (the base morph) and a percentage (blend weight).
 
-use svarog_common::BinaryReader;
+use svarog_common::{BinaryReader, BinaryWriter};
 
 use crate::{Error, Result};
 
@@ -84,6 +84,11 @@ impl FacePart {
         }
     }
 
+    /// Look up a face part by its [`name`](FacePart::name).
+    pub fn from_name(name: &str) -> Option<FacePart> {
+        FacePart::all().into_iter().find(|fp| fp.name() == name)
+    }
+
     /// Get the starting index in the DNA parts array.
     pub const fn start_index(&self) -> usize {
         (*self as usize) * BLENDS_PER_FACE_PART
@@ -118,11 +123,34 @@ impl std::fmt::Display for FacePart {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for FacePart {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FacePart {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_name(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown face part {:?}", s)))
+    }
+}
+
 /// A single DNA blend target.
 ///
 /// Each face part can have up to 4 blend targets. Each target specifies
 /// a head ID (base morph shape) and a percentage (blend weight 0.0-1.0).
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DnaPart {
     /// The head ID (base morph shape index).
     pub head_id: u8,
@@ -183,8 +211,10 @@ impl Default for DnaPart {
 ///
 /// Contains 48 DNA parts organized as 12 face parts with 4 blends each.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dna {
     /// All 48 DNA parts (12 face parts × 4 blends).
+    #[cfg_attr(feature = "serde", serde(with = "serde_parts"))]
     parts: [DnaPart; DNA_PART_COUNT],
     /// Additional data at the end of the DNA block (24 bytes).
     /// Purpose unknown, preserved for round-trip compatibility.
@@ -259,17 +289,20 @@ impl Dna {
 
     /// Convert to bytes for writing.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(DNA_SIZE);
+        let mut writer = BinaryWriter::with_capacity(DNA_SIZE);
+        self.write_to(&mut writer);
+        writer.into_inner()
+    }
 
+    /// Write to a binary writer.
+    pub(crate) fn write_to(&self, writer: &mut BinaryWriter) {
         // Write all DNA parts
         for part in &self.parts {
-            bytes.extend_from_slice(&part.to_bytes());
+            writer.write_bytes(&part.to_bytes());
         }
 
         // Write extra data
-        bytes.extend_from_slice(&self.extra);
-
-        bytes
+        writer.write_bytes(&self.extra);
     }
 
     /// Iterate over face parts with their blend data.
@@ -278,6 +311,68 @@ impl Dna {
             .into_iter()
             .map(move |fp| (fp, self.face_part_blends(fp)))
     }
+
+    /// Blend two DNAs, `t` going from 0.0 (`a`) to 1.0 (`b`).
+    ///
+    /// For each face part the head weights of both sides are mixed. If more
+    /// than 4 heads end up with a weight, the lightest are dropped and the
+    /// rest rescaled so the face part keeps its total weight. The extra bytes
+    /// come from whichever side `t` is closer to.
+    pub fn lerp(a: &Dna, b: &Dna, t: f32) -> Dna {
+        let t = t.clamp(0.0, 1.0);
+        let mut result = Dna::new();
+
+        for face_part in FacePart::all() {
+            let blends = lerp_blends(
+                a.face_part_blends(face_part),
+                b.face_part_blends(face_part),
+                t,
+            );
+            result
+                .face_part_blends_mut(face_part)
+                .copy_from_slice(&blends);
+        }
+
+        result.extra = if t < 0.5 { a.extra } else { b.extra };
+        result
+    }
+}
+
+/// Mix the head weights of one face part.
+fn lerp_blends(a: &[DnaPart], b: &[DnaPart], t: f32) -> [DnaPart; BLENDS_PER_FACE_PART] {
+    // Accumulate per head, keeping the order heads first appear in
+    let mut weights: Vec<(u8, f32)> = Vec::with_capacity(BLENDS_PER_FACE_PART * 2);
+    for (blends, scale) in [(a, 1.0 - t), (b, t)] {
+        for part in blends {
+            let weight = part.percent * scale;
+            if weight <= 0.0 {
+                continue;
+            }
+            match weights.iter_mut().find(|(head, _)| *head == part.head_id) {
+                Some((_, w)) => *w += weight,
+                None => weights.push((part.head_id, weight)),
+            }
+        }
+    }
+
+    let total: f32 = weights.iter().map(|(_, w)| w).sum();
+    while weights.len() > BLENDS_PER_FACE_PART {
+        let lightest = weights
+            .iter()
+            .enumerate()
+            .min_by(|(_, x), (_, y)| x.1.total_cmp(&y.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        weights.remove(lightest);
+    }
+    let kept: f32 = weights.iter().map(|(_, w)| w).sum();
+    let scale = if kept > 0.0 { total / kept } else { 0.0 };
+
+    let mut result = [DnaPart::zero(); BLENDS_PER_FACE_PART];
+    for (slot, (head, weight)) in result.iter_mut().zip(weights) {
+        *slot = DnaPart::new(head, (weight * scale).min(1.0));
+    }
+    result
 }
 
 impl Default for Dna {
@@ -286,6 +381,38 @@ impl Default for Dna {
     }
 }
 
+/// Serde support for the part array, which is longer than serde's built-in
+/// array impls.
+#[cfg(feature = "serde")]
+mod serde_parts {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{DnaPart, DNA_PART_COUNT};
+
+    pub fn serialize<S>(
+        parts: &[DnaPart; DNA_PART_COUNT],
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        parts[..].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(
+        deserializer: D,
+    ) -> std::result::Result<[DnaPart; DNA_PART_COUNT], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let parts = Vec::<DnaPart>::deserialize(deserializer)?;
+        let len = parts.len();
+        parts
+            .try_into()
+            .map_err(|_| serde::de::Error::invalid_length(len, &"48 DNA parts"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,6 +437,70 @@ mod tests {
         assert!((parsed.percent - 0.75).abs() < 0.001);
     }
 
+    #[test]
+    fn test_dna_lerp() {
+        let mut a = Dna::new();
+        a.face_part_blends_mut(FacePart::Nose)[0] = DnaPart::new(3, 1.0);
+        a.set_extra([1; 24]);
+
+        let mut b = Dna::new();
+        b.face_part_blends_mut(FacePart::Nose)[0] = DnaPart::new(7, 1.0);
+        b.set_extra([2; 24]);
+
+        let half = Dna::lerp(&a, &b, 0.5);
+        let nose = half.face_part_blends(FacePart::Nose);
+        assert_eq!(nose[0], DnaPart::new(3, 0.5));
+        assert_eq!(nose[1], DnaPart::new(7, 0.5));
+        assert!(nose[2].is_zero());
+        assert_eq!(half.extra(), &[2; 24]);
+
+        let start = Dna::lerp(&a, &b, -1.0);
+        assert_eq!(
+            start.face_part_blends(FacePart::Nose)[0],
+            DnaPart::new(3, 1.0)
+        );
+        assert!(start.face_part_blends(FacePart::Nose)[1].is_zero());
+        assert_eq!(start.extra(), &[1; 24]);
+    }
+
+    #[test]
+    fn test_dna_lerp_keeps_four_heaviest() {
+        let mut a = Dna::new();
+        let mut b = Dna::new();
+        let weights = [0.4, 0.3, 0.2, 0.1];
+        for (i, w) in weights.into_iter().enumerate() {
+            a.face_part_blends_mut(FacePart::Jaw)[i] = DnaPart::new(i as u8, w);
+            b.face_part_blends_mut(FacePart::Jaw)[i] = DnaPart::new(10 + i as u8, w);
+        }
+
+        let jaw = Dna::lerp(&a, &b, 0.5);
+        let blends = jaw.face_part_blends(FacePart::Jaw);
+        let heads: Vec<u8> = blends.iter().map(|p| p.head_id).collect();
+        assert_eq!(heads, [0, 1, 10, 11]);
+        let total: f32 = blends.iter().map(|p| p.percent).sum();
+        assert!((total - 1.0).abs() < 1e-5);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_dna_serde_roundtrip() {
+        let mut dna = Dna::new();
+        dna.face_part_blends_mut(FacePart::Crown)[3] = DnaPart::new(9, 0.25);
+        dna.set_extra([5; 24]);
+
+        let json = serde_json::to_value(&dna).unwrap();
+        assert_eq!(json["parts"].as_array().unwrap().len(), DNA_PART_COUNT);
+        let parsed: Dna = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.parts(), dna.parts());
+        assert_eq!(parsed.extra(), &[5; 24]);
+
+        // All 48 parts are required
+        let mut short = json;
+        short["parts"].as_array_mut().unwrap().pop();
+        let err = serde_json::from_value::<Dna>(short).unwrap_err();
+        assert!(err.to_string().contains("48 DNA parts"));
+    }
+
     #[test]
     fn test_dna_size() {
         let dna = Dna::new();
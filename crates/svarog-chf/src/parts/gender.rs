@@ -0,0 +1,207 @@
+//! Conversion of characters between bodies.
+//!
+//! Head IDs, equipment and materials are specific to a body: head 5 of the
+//! male body is an unrelated morph on the female body, and most hair and
+//! clothing records exist once per body. A [`GenderMapping`] lists the
+//! equivalents and [`ChfData::convert_gender`] applies it.
+
+use std::collections::HashMap;
+
+use svarog_common::CigGuid;
+
+use super::data::ChfData;
+use super::dna::{DnaPart, FacePart};
+use super::itemport::ItemPort;
+use super::name_hash::NameHash;
+
+/// Equivalents on the target body for [`ChfData::convert_gender`].
+///
+/// Anything without an entry is kept as is. With the `json` feature a
+/// mapping can be loaded from JSON:
+///
+/// ```json
+/// {
+///   "heads": { "5": 2, "12": 7 },
+///   "head_count": 20,
+///   "records": { "<male hair GUID>": "<female hair GUID>" },
+///   "ports": { "beard": "hair" }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GenderMapping {
+    heads: HashMap<u8, u8>,
+    head_count: Option<u8>,
+    records: HashMap<CigGuid, CigGuid>,
+    ports: HashMap<NameHash, NameHash>,
+}
+
+impl GenderMapping {
+    /// Create an empty mapping, which only swaps the gender GUID.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a mapping from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Map a head ID to the closest head of the target body.
+    pub fn head(mut self, from: u8, to: u8) -> Self {
+        self.heads.insert(from, to);
+        self
+    }
+
+    /// Number of heads on the target body; unmapped head IDs beyond it are
+    /// clamped to the last head.
+    pub fn head_count(mut self, count: u8) -> Self {
+        self.head_count = Some(count);
+        self
+    }
+
+    /// Replace an item or material record.
+    pub fn record(mut self, from: CigGuid, to: CigGuid) -> Self {
+        self.records.insert(from, to);
+        self
+    }
+
+    /// Rename an item port.
+    pub fn port(mut self, from: NameHash, to: NameHash) -> Self {
+        self.ports.insert(from, to);
+        self
+    }
+
+    /// The head ID to use on the target body.
+    pub fn map_head(&self, head_id: u8) -> u8 {
+        match (self.heads.get(&head_id), self.head_count) {
+            (Some(&to), _) => to,
+            (None, Some(count)) if count > 0 => head_id.min(count - 1),
+            _ => head_id,
+        }
+    }
+
+    /// The record to use on the target body.
+    pub fn map_record(&self, guid: &CigGuid) -> CigGuid {
+        self.records.get(guid).copied().unwrap_or(*guid)
+    }
+
+    /// The port name to use on the target body.
+    pub fn map_port(&self, name: NameHash) -> NameHash {
+        self.ports.get(&name).copied().unwrap_or(name)
+    }
+
+    fn apply_to_port(&self, port: &mut ItemPort) {
+        port.set_name(self.map_port(port.name()));
+        if let Some(guid) = port.item_guid() {
+            let guid = self.map_record(guid);
+            port.set_item_guid(Some(guid));
+        }
+        for child in port.children_mut() {
+            self.apply_to_port(child);
+        }
+    }
+}
+
+impl ChfData {
+    /// Move the character to another body.
+    ///
+    /// Sets the gender GUID to `target_gender`, then remaps DNA head IDs,
+    /// item port names, items and materials through `mapping`. Blends of a
+    /// face part that end up on the same head are merged.
+    pub fn convert_gender(&mut self, target_gender: CigGuid, mapping: &GenderMapping) {
+        self.set_gender_id(target_gender);
+
+        for face_part in FacePart::all() {
+            let blends = self.dna_mut().face_part_blends_mut(face_part);
+            for i in 0..blends.len() {
+                if blends[i].is_zero() {
+                    continue;
+                }
+                let head_id = mapping.map_head(blends[i].head_id);
+                let merge_into = blends[..i]
+                    .iter()
+                    .position(|b| b.head_id == head_id && !b.is_zero());
+                match merge_into {
+                    Some(j) => {
+                        blends[j].percent = (blends[j].percent + blends[i].percent).min(1.0);
+                        blends[i] = DnaPart::default();
+                    }
+                    None => blends[i].head_id = head_id,
+                }
+            }
+        }
+
+        if let Some(root) = self.item_port_mut() {
+            mapping.apply_to_port(root);
+        }
+
+        for material in self.materials_mut() {
+            let guid = mapping.map_record(material.guid());
+            material.set_guid(guid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parts::Material;
+
+    #[test]
+    fn test_convert_gender() {
+        let male = CigGuid::from_bytes([1; 16]);
+        let female = CigGuid::from_bytes([2; 16]);
+        let male_hair = CigGuid::from_bytes([3; 16]);
+        let female_hair = CigGuid::from_bytes([4; 16]);
+
+        let mut data = ChfData::new(male);
+        let nose = data.dna_mut().face_part_blends_mut(FacePart::Nose);
+        nose[0] = DnaPart::new(5, 0.5);
+        nose[1] = DnaPart::new(6, 0.25);
+        nose[2] = DnaPart::new(30, 0.25);
+        let mut body = ItemPort::new(NameHash::from_str("body"));
+        body.add_port(NameHash::from_str("beard"), Some(male_hair));
+        data.set_item_port(Some(body));
+        data.add_material(Material::new(NameHash::from_str("hair"), male_hair));
+
+        let mapping = GenderMapping::new()
+            .head(5, 2)
+            .head(6, 2)
+            .head_count(20)
+            .record(male_hair, female_hair)
+            .port(NameHash::from_str("beard"), NameHash::from_str("hair"));
+        data.convert_gender(female, &mapping);
+
+        assert_eq!(data.gender_id(), &female);
+        let nose = data.dna().face_part_blends(FacePart::Nose);
+        assert_eq!(nose[0], DnaPart::new(2, 0.75));
+        assert!(nose[1].is_zero());
+        assert_eq!(nose[2].head_id, 19);
+
+        let port = &data.item_port().unwrap().children()[0];
+        assert_eq!(port.name(), NameHash::from_str("hair"));
+        assert_eq!(port.item_guid(), Some(&female_hair));
+        assert_eq!(data.materials()[0].guid(), &female_hair);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_mapping_from_json() {
+        let mapping = GenderMapping::from_json(
+            r#"{ "heads": { "5": 2 }, "head_count": 10, "ports": { "beard": "hair" } }"#,
+        )
+        .unwrap();
+        assert_eq!(mapping.map_head(5), 2);
+        assert_eq!(mapping.map_head(15), 9);
+        assert_eq!(
+            mapping.map_port(NameHash::from_str("beard")),
+            NameHash::from_str("hair")
+        );
+    }
+}
@@ -4,10 +4,10 @@
 //! equipment. Each item port has a name (stored as CRC32C hash), an optional
 //! GUID for the attached item, and zero or more child ports.
 
-use svarog_common::{BinaryReader, CigGuid};
+use svarog_common::{BinaryReader, BinaryWriter, CigGuid};
 
 use super::name_hash::NameHash;
-use crate::Result;
+use crate::{Error, Result};
 
 /// An item port in the character's equipment tree.
 ///
@@ -15,6 +15,7 @@ use crate::Result;
 /// child ports. This is used for equipment attachment (e.g., a torso
 /// armor port might have child ports for arm attachments).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemPort {
     /// The name hash of this port.
     name: NameHash,
@@ -100,6 +101,11 @@ impl ItemPort {
         self.name
     }
 
+    /// Rename this port.
+    pub fn set_name(&mut self, name: NameHash) {
+        self.name = name;
+    }
+
     /// Get the name of this port (if known).
     pub fn name_str(&self) -> Option<&'static str> {
         self.name.to_name()
@@ -130,6 +136,43 @@ impl ItemPort {
         self.children.push(child);
     }
 
+    /// Add a child port with an optional attached item and return it, so
+    /// nested ports can be added in turn.
+    pub fn add_port(&mut self, name: NameHash, item_guid: Option<CigGuid>) -> &mut ItemPort {
+        self.children
+            .push(ItemPort::with_children(name, item_guid, Vec::new()));
+        self.children.last_mut().expect("child was just added")
+    }
+
+    /// Remove the first port named `name` below this one (pre-order), and
+    /// return it with its subtree.
+    pub fn remove(&mut self, name: NameHash) -> Option<ItemPort> {
+        for i in 0..self.children.len() {
+            if self.children[i].name == name {
+                return Some(self.children.remove(i));
+            }
+            if let Some(removed) = self.children[i].remove(name) {
+                return Some(removed);
+            }
+        }
+        None
+    }
+
+    /// Attach `item_guid` (or nothing) to the first port named `name` in the
+    /// tree, returning the previously attached item.
+    ///
+    /// Used to swap hairstyles, eyes and other equipment.
+    pub fn replace_item(
+        &mut self,
+        name: NameHash,
+        item_guid: Option<CigGuid>,
+    ) -> Result<Option<CigGuid>> {
+        let port = self
+            .find_recursive_mut(name)
+            .ok_or(Error::PortNotFound(name))?;
+        Ok(std::mem::replace(&mut port.item_guid, item_guid))
+    }
+
     /// Check if this port has an item attached.
     pub fn has_item(&self) -> bool {
         self.item_guid.is_some()
@@ -163,6 +206,16 @@ impl ItemPort {
         None
     }
 
+    /// Recursively find a port by name hash in the tree (mutable).
+    pub fn find_recursive_mut(&mut self, name: NameHash) -> Option<&mut ItemPort> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_recursive_mut(name))
+    }
+
     /// Get the total number of ports in this tree (including self).
     pub fn count(&self) -> usize {
         1 + self.children.iter().map(|c| c.count()).sum::<usize>()
@@ -179,28 +232,28 @@ impl ItemPort {
 
     /// Convert to bytes for writing.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        self.write_to(&mut bytes);
-        bytes
+        let mut writer = BinaryWriter::new();
+        self.write_to(&mut writer);
+        writer.into_inner()
     }
 
-    /// Write to a byte buffer.
-    fn write_to(&self, bytes: &mut Vec<u8>) {
+    /// Write to a binary writer.
+    pub(crate) fn write_to(&self, writer: &mut BinaryWriter) {
         // Write name hash
-        bytes.extend_from_slice(&self.name.value().to_le_bytes());
+        writer.write_u32(self.name.value());
 
         // Write GUID (or zeros if none)
         match &self.item_guid {
-            Some(guid) => bytes.extend_from_slice(guid.as_bytes()),
-            None => bytes.extend_from_slice(&[0u8; 16]),
+            Some(guid) => writer.write_struct(guid),
+            None => writer.write_zeros(16),
         }
 
         // Write child count
-        bytes.extend_from_slice(&(self.children.len() as u32).to_le_bytes());
+        writer.write_u32(self.children.len() as u32);
 
         // Write children recursively
         for child in &self.children {
-            child.write_to(bytes);
+            child.write_to(writer);
         }
     }
 
@@ -271,4 +324,34 @@ mod tests {
         let names: Vec<_> = root.iter().map(|p| p.name().value()).collect();
         assert_eq!(names.len(), 3);
     }
+
+    #[test]
+    fn test_itemport_mutation() {
+        let hair = NameHash::from_str("hair");
+        let eyes = NameHash::from_str("eyes");
+        let old_hair = CigGuid::from_bytes([1; 16]);
+        let new_hair = CigGuid::from_bytes([2; 16]);
+
+        let mut root = ItemPort::new(NameHash::from_str("body"));
+        let head = root.add_port(NameHash::from_str("head"), None);
+        head.add_port(hair, Some(old_hair));
+        head.add_port(eyes, None);
+
+        assert_eq!(
+            root.replace_item(hair, Some(new_hair)).unwrap(),
+            Some(old_hair)
+        );
+        assert!(root.replace_item(NameHash::from_str("tail"), None).is_err());
+
+        let removed = root.remove(eyes).unwrap();
+        assert_eq!(removed.name(), eyes);
+        assert!(root.remove(eyes).is_none());
+
+        let parsed = ItemPort::parse(&root.to_bytes()).unwrap();
+        assert_eq!(parsed.count(), 3);
+        assert_eq!(
+            parsed.find_recursive(hair).unwrap().item_guid(),
+            Some(&new_hair)
+        );
+    }
 }
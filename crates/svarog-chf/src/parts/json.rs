@@ -0,0 +1,365 @@
+//! JSON representation of CHF characters.
+//!
+//! The JSON form is meant for hand editing and version control: name hashes
+//! are written as their known names (or `0x` hex when unknown), colors as
+//! `#RRGGBBAA` and opaque byte blocks as hex strings. Converting back with
+//! [`ChfData::from_json`] rebuilds the exact binary data.
+//!
+//! ```json
+//! {
+//!   "gender": "...",
+//!   "dna": {
+//!     "parts": { "Nose": [{ "head": 3, "percent": 0.5 }] },
+//!     "extra": "000000..."
+//!   },
+//!   "item_port": { "name": "body", "children": [...] },
+//!   "materials": [...]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use svarog_common::CigGuid;
+
+use super::data::ChfData;
+use super::dna::{Dna, DnaPart, FacePart, BLENDS_PER_FACE_PART};
+use super::itemport::ItemPort;
+use super::material::{ColorRgba, Material, SubMaterial, Texture};
+use super::name_hash::NameHash;
+
+impl ChfData {
+    /// Serialize the character to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&CharacterJson::from(self))
+    }
+
+    /// Rebuild a character from JSON produced by [`ChfData::to_json`] or
+    /// edited by hand.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let character: CharacterJson = serde_json::from_str(json)?;
+        character.into_data().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CharacterJson {
+    gender: CigGuid,
+    dna: DnaJson,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    item_port: Option<ItemPortJson>,
+    #[serde(default)]
+    materials: Vec<MaterialJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DnaJson {
+    /// Blends per face part; trailing unused blends are omitted.
+    #[serde(default)]
+    parts: BTreeMap<String, Vec<BlendJson>>,
+    #[serde(default)]
+    extra: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BlendJson {
+    head: u8,
+    percent: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ItemPortJson {
+    name: NameHash,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    item: Option<CigGuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    children: Vec<ItemPortJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MaterialJson {
+    name: NameHash,
+    guid: CigGuid,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    additional_params: String,
+    #[serde(default)]
+    sub_materials: Vec<SubMaterialJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SubMaterialJson {
+    name: NameHash,
+    #[serde(default)]
+    textures: Vec<TextureJson>,
+    #[serde(default)]
+    floats: Vec<FloatJson>,
+    #[serde(default)]
+    colors: Vec<ColorJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TextureJson {
+    #[serde(rename = "type")]
+    texture_type: NameHash,
+    path: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FloatJson {
+    name: NameHash,
+    value: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ColorJson {
+    name: NameHash,
+    /// `#RRGGBBAA`.
+    value: ColorRgba,
+}
+
+impl From<&ChfData> for CharacterJson {
+    fn from(data: &ChfData) -> Self {
+        Self {
+            gender: *data.gender_id(),
+            dna: DnaJson::from(data.dna()),
+            item_port: data.item_port().map(ItemPortJson::from),
+            materials: data.materials().iter().map(MaterialJson::from).collect(),
+        }
+    }
+}
+
+impl CharacterJson {
+    fn into_data(self) -> Result<ChfData, String> {
+        let mut data = ChfData::new(self.gender);
+        *data.dna_mut() = self.dna.into_dna()?;
+        data.set_item_port(self.item_port.map(ItemPortJson::into_port));
+        for material in self.materials {
+            data.add_material(material.into_material()?);
+        }
+        Ok(data)
+    }
+}
+
+impl From<&Dna> for DnaJson {
+    fn from(dna: &Dna) -> Self {
+        let parts = dna
+            .iter_face_parts()
+            .filter_map(|(face_part, blends)| {
+                let used = blends
+                    .iter()
+                    .rposition(|b| b.head_id != 0 || b.percent != 0.0)?;
+                let blends = blends[..=used]
+                    .iter()
+                    .map(|b| BlendJson {
+                        head: b.head_id,
+                        percent: b.percent,
+                    })
+                    .collect();
+                Some((face_part.name().to_string(), blends))
+            })
+            .collect();
+        Self {
+            parts,
+            extra: to_hex(dna.extra()),
+        }
+    }
+}
+
+impl DnaJson {
+    fn into_dna(self) -> Result<Dna, String> {
+        let mut dna = Dna::new();
+        for (name, blends) in self.parts {
+            let face_part = FacePart::from_name(&name)
+                .ok_or_else(|| format!("unknown face part {:?}", name))?;
+            if blends.len() > BLENDS_PER_FACE_PART {
+                return Err(format!(
+                    "face part {} has {} blends, at most {} allowed",
+                    name,
+                    blends.len(),
+                    BLENDS_PER_FACE_PART
+                ));
+            }
+            for (slot, blend) in dna.face_part_blends_mut(face_part).iter_mut().zip(blends) {
+                if !(0.0..=1.0).contains(&blend.percent) {
+                    return Err(format!(
+                        "face part {} percent {} outside 0..1",
+                        name, blend.percent
+                    ));
+                }
+                *slot = DnaPart::new(blend.head, blend.percent);
+            }
+        }
+
+        if !self.extra.is_empty() {
+            let extra = from_hex(&self.extra)?;
+            let len = dna.extra().len();
+            let extra = extra
+                .try_into()
+                .map_err(|_| format!("DNA extra must be {} bytes", len))?;
+            dna.set_extra(extra);
+        }
+        Ok(dna)
+    }
+}
+
+impl From<&ItemPort> for ItemPortJson {
+    fn from(port: &ItemPort) -> Self {
+        Self {
+            name: port.name(),
+            item: port.item_guid().copied(),
+            children: port.children().iter().map(ItemPortJson::from).collect(),
+        }
+    }
+}
+
+impl ItemPortJson {
+    fn into_port(self) -> ItemPort {
+        let children = self
+            .children
+            .into_iter()
+            .map(ItemPortJson::into_port)
+            .collect();
+        ItemPort::with_children(self.name, self.item, children)
+    }
+}
+
+impl From<&Material> for MaterialJson {
+    fn from(material: &Material) -> Self {
+        Self {
+            name: material.name(),
+            guid: *material.guid(),
+            additional_params: to_hex(material.additional_params()),
+            sub_materials: material
+                .sub_materials()
+                .iter()
+                .map(|sub| SubMaterialJson {
+                    name: sub.name(),
+                    textures: sub
+                        .textures()
+                        .iter()
+                        .map(|t| TextureJson {
+                            texture_type: t.texture_type,
+                            path: t.path.clone(),
+                        })
+                        .collect(),
+                    floats: sub
+                        .float_params()
+                        .iter()
+                        .map(|&(name, value)| FloatJson { name, value })
+                        .collect(),
+                    colors: sub
+                        .color_params()
+                        .iter()
+                        .map(|&(name, value)| ColorJson { name, value })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MaterialJson {
+    fn into_material(self) -> Result<Material, String> {
+        let mut material = Material::new(self.name, self.guid);
+        material.set_additional_params(from_hex(&self.additional_params)?);
+
+        for sub_json in self.sub_materials {
+            let mut sub = SubMaterial::new(sub_json.name);
+            for texture in sub_json.textures {
+                sub.add_texture(Texture::new(texture.texture_type, texture.path));
+            }
+            for float in sub_json.floats {
+                sub.add_float_param(float.name, float.value);
+            }
+            for color in sub_json.colors {
+                sub.add_color_param(color.name, color.value);
+            }
+            material.add_sub_material(sub);
+        }
+        Ok(material)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() % 2 != 0 {
+        return Err(format!("odd-length hex string {:?}", s));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("invalid hex string {:?}", s))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ChfData {
+        let mut data = ChfData::new(CigGuid::from_bytes([7; 16]));
+        data.dna_mut().face_part_blends_mut(FacePart::Nose)[0] = DnaPart::new(3, 0.25);
+        data.dna_mut().face_part_blends_mut(FacePart::Nose)[1] = DnaPart::new(9, 0.75);
+        data.dna_mut().set_extra([0xAB; 24]);
+
+        let mut body = ItemPort::new(NameHash::from_str("body"));
+        body.add_child(ItemPort::with_item(
+            NameHash::from_raw(0xDEADBEEF),
+            CigGuid::from_bytes([1; 16]),
+        ));
+        data.set_item_port(Some(body));
+
+        let mut sub = SubMaterial::new(NameHash::from_str("skin"));
+        sub.add_texture(Texture::new(NameHash::from_str("diffuse"), "skin.dds"));
+        sub.add_float_param(NameHash::from_str("roughness"), 0.3);
+        sub.add_color_param(
+            NameHash::from_str("tint"),
+            ColorRgba::from_bytes(255, 128, 0, 255),
+        );
+        let mut material = Material::new(NameHash::from_str("head"), CigGuid::from_bytes([2; 16]));
+        material.set_additional_params(vec![1, 2, 3]);
+        material.add_sub_material(sub);
+        data.add_material(material);
+
+        data
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let data = sample();
+        let json = data.to_json().unwrap();
+        assert!(json.contains("\"Nose\""));
+        assert!(json.contains("\"0xDEADBEEF\""));
+        assert!(json.contains("\"#ff8000ff\""));
+        assert!(json.contains("\"additional_params\": \"010203\""));
+
+        let parsed = ChfData::from_json(&json).unwrap();
+        assert_eq!(parsed.to_bytes(), data.to_bytes());
+    }
+
+    #[test]
+    fn test_json_hand_edited() {
+        let json = format!(
+            r#"{{
+                "gender": "{}",
+                "dna": {{ "parts": {{ "Jaw": [{{ "head": 4, "percent": 1.0 }}] }} }}
+            }}"#,
+            CigGuid::from_bytes([7; 16])
+        );
+        let data = ChfData::from_json(&json).unwrap();
+        assert_eq!(
+            data.dna().face_part_blends(FacePart::Jaw)[0],
+            DnaPart::new(4, 1.0)
+        );
+        assert!(data.item_port().is_none());
+
+        let bad = json.replace("Jaw", "Chin");
+        assert!(ChfData::from_json(&bad).is_err());
+    }
+}
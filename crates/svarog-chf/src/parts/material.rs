@@ -3,7 +3,7 @@
 //! Materials define the visual appearance of character parts. Each material
 //! can have multiple sub-materials, each with textures and shader parameters.
 
-use svarog_common::{BinaryReader, CigGuid};
+use svarog_common::{BinaryReader, BinaryWriter, CigGuid};
 
 use super::name_hash::NameHash;
 use crate::Result;
@@ -43,6 +43,23 @@ impl ColorRgba {
         ]
     }
 
+    /// Format as `#rrggbbaa`.
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.to_bytes();
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+
+    /// Parse `#RRGGBBAA` or `#RRGGBB` (opaque); the `#` is optional.
+    pub fn from_hex(s: &str) -> Option<Self> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if !hex.is_ascii() || !matches!(hex.len(), 6 | 8) {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let a = if hex.len() == 8 { byte(6)? } else { 255 };
+        Some(Self::from_bytes(byte(0)?, byte(2)?, byte(4)?, a))
+    }
+
     /// Read from binary data (4 bytes RGBA).
     pub fn read(reader: &mut BinaryReader<'_>) -> Result<Self> {
         let r = reader.read_u8()?;
@@ -72,6 +89,17 @@ impl ColorRgba {
         }
     }
 
+    /// Blend two colors component-wise, `t` going from 0.0 (`a`) to 1.0 (`b`).
+    pub fn lerp(a: ColorRgba, b: ColorRgba, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            r: lerp(a.r, b.r, t),
+            g: lerp(a.g, b.g, t),
+            b: lerp(a.b, b.b, t),
+            a: lerp(a.a, b.a, t),
+        }
+    }
+
     /// Create a transparent color.
     pub const fn transparent() -> Self {
         Self {
@@ -89,8 +117,32 @@ impl Default for ColorRgba {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for ColorRgba {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ColorRgba {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::from_hex(&s).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid color {:?}, expected #RRGGBBAA", s))
+        })
+    }
+}
+
 /// A material parameter with a typed value.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MaterialParam {
     /// Floating-point parameter.
     Float { name: NameHash, value: f32 },
@@ -126,6 +178,7 @@ impl MaterialParam {
 
 /// A texture reference.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Texture {
     /// The type of texture (diffuse, normal, specular, etc.).
     pub texture_type: NameHash,
@@ -154,16 +207,22 @@ impl Texture {
 
     /// Write to bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.texture_type.value().to_le_bytes());
-        bytes.extend_from_slice(&(self.path.len() as u32).to_le_bytes());
-        bytes.extend_from_slice(self.path.as_bytes());
-        bytes
+        let mut writer = BinaryWriter::new();
+        self.write_to(&mut writer);
+        writer.into_inner()
+    }
+
+    /// Write to a binary writer.
+    pub(crate) fn write_to(&self, writer: &mut BinaryWriter) {
+        writer.write_u32(self.texture_type.value());
+        writer.write_u32(self.path.len() as u32);
+        writer.write_bytes(self.path.as_bytes());
     }
 }
 
 /// A sub-material within a material.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubMaterial {
     /// The name hash of this sub-material.
     name: NameHash,
@@ -248,6 +307,46 @@ impl SubMaterial {
         &self.color_params
     }
 
+    /// Find a float parameter by name hash.
+    pub fn float_param(&self, name: NameHash) -> Option<f32> {
+        self.float_params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| *v)
+    }
+
+    /// Find a color parameter by name hash.
+    pub fn color_param(&self, name: NameHash) -> Option<ColorRgba> {
+        self.color_params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, c)| *c)
+    }
+
+    /// Overwrite an existing float parameter; returns `false` if the
+    /// sub-material has no parameter with that name.
+    pub fn set_float_param(&mut self, name: NameHash, value: f32) -> bool {
+        match self.float_params.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, v)) => {
+                *v = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Overwrite an existing color parameter; returns `false` if the
+    /// sub-material has no parameter with that name.
+    pub fn set_color_param(&mut self, name: NameHash, value: ColorRgba) -> bool {
+        match self.color_params.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, c)) => {
+                *c = value;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Add a texture.
     pub fn add_texture(&mut self, texture: Texture) {
         self.textures.push(texture);
@@ -265,36 +364,62 @@ impl SubMaterial {
 
     /// Write to bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut writer = BinaryWriter::new();
+        self.write_to(&mut writer);
+        writer.into_inner()
+    }
 
-        bytes.extend_from_slice(&self.name.value().to_le_bytes());
+    /// Write to a binary writer.
+    pub(crate) fn write_to(&self, writer: &mut BinaryWriter) {
+        writer.write_u32(self.name.value());
 
         // Write textures
-        bytes.extend_from_slice(&(self.textures.len() as u32).to_le_bytes());
+        writer.write_u32(self.textures.len() as u32);
         for texture in &self.textures {
-            bytes.extend_from_slice(&texture.to_bytes());
+            texture.write_to(writer);
         }
 
         // Write float parameters
-        bytes.extend_from_slice(&(self.float_params.len() as u32).to_le_bytes());
+        writer.write_u32(self.float_params.len() as u32);
         for (name, value) in &self.float_params {
-            bytes.extend_from_slice(&name.value().to_le_bytes());
-            bytes.extend_from_slice(&value.to_le_bytes());
+            writer.write_u32(name.value());
+            writer.write_f32(*value);
         }
 
         // Write color parameters
-        bytes.extend_from_slice(&(self.color_params.len() as u32).to_le_bytes());
+        writer.write_u32(self.color_params.len() as u32);
         for (name, color) in &self.color_params {
-            bytes.extend_from_slice(&name.value().to_le_bytes());
-            bytes.extend_from_slice(&color.to_bytes());
+            writer.write_u32(name.value());
+            writer.write_bytes(&color.to_bytes());
+        }
+    }
+
+    /// Blend two sub-materials, `t` going from 0.0 (`a`) to 1.0 (`b`).
+    ///
+    /// Parameters present on both sides are interpolated. Everything else,
+    /// including textures, is taken from whichever side `t` is closer to.
+    pub fn lerp(a: &SubMaterial, b: &SubMaterial, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mut result = if t < 0.5 { a.clone() } else { b.clone() };
+
+        for (name, value) in &mut result.float_params {
+            if let (Some(x), Some(y)) = (a.float_param(*name), b.float_param(*name)) {
+                *value = lerp(x, y, t);
+            }
+        }
+        for (name, value) in &mut result.color_params {
+            if let (Some(x), Some(y)) = (a.color_param(*name), b.color_param(*name)) {
+                *value = ColorRgba::lerp(x, y, t);
+            }
         }
 
-        bytes
+        result
     }
 }
 
 /// A material definition.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
     /// The name hash of this material.
     name: NameHash,
@@ -352,11 +477,21 @@ impl Material {
         &self.guid
     }
 
+    /// Set the GUID of this material.
+    pub fn set_guid(&mut self, guid: CigGuid) {
+        self.guid = guid;
+    }
+
     /// Get the additional parameters.
     pub fn additional_params(&self) -> &[u8] {
         &self.additional_params
     }
 
+    /// Set the additional parameters.
+    pub fn set_additional_params(&mut self, params: Vec<u8>) {
+        self.additional_params = params;
+    }
+
     /// Get the sub-materials.
     pub fn sub_materials(&self) -> &[SubMaterial] {
         &self.sub_materials
@@ -374,25 +509,53 @@ impl Material {
 
     /// Write to bytes.
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut writer = BinaryWriter::new();
+        self.write_to(&mut writer);
+        writer.into_inner()
+    }
 
-        bytes.extend_from_slice(&self.name.value().to_le_bytes());
-        bytes.extend_from_slice(self.guid.as_bytes());
+    /// Write to a binary writer.
+    pub(crate) fn write_to(&self, writer: &mut BinaryWriter) {
+        writer.write_u32(self.name.value());
+        writer.write_struct(&self.guid);
 
         // Write additional params
-        bytes.extend_from_slice(&(self.additional_params.len() as u32).to_le_bytes());
-        bytes.extend_from_slice(&self.additional_params);
+        writer.write_u32(self.additional_params.len() as u32);
+        writer.write_bytes(&self.additional_params);
 
         // Write sub-materials
-        bytes.extend_from_slice(&(self.sub_materials.len() as u32).to_le_bytes());
+        writer.write_u32(self.sub_materials.len() as u32);
         for sub in &self.sub_materials {
-            bytes.extend_from_slice(&sub.to_bytes());
+            sub.write_to(writer);
+        }
+    }
+
+    /// Blend two materials, `t` going from 0.0 (`a`) to 1.0 (`b`).
+    ///
+    /// Sub-materials are matched by name and blended with
+    /// [`SubMaterial::lerp`]; unmatched ones, the GUID and the additional
+    /// parameters come from whichever side `t` is closer to.
+    pub fn lerp(a: &Material, b: &Material, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mut result = if t < 0.5 { a.clone() } else { b.clone() };
+
+        for sub in &mut result.sub_materials {
+            let name = sub.name();
+            let from = a.sub_materials.iter().find(|s| s.name() == name);
+            let to = b.sub_materials.iter().find(|s| s.name() == name);
+            if let (Some(from), Some(to)) = (from, to) {
+                *sub = SubMaterial::lerp(from, to, t);
+            }
         }
 
-        bytes
+        result
     }
 }
 
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -421,4 +584,33 @@ mod tests {
         assert_eq!(sub.float_params().len(), 1);
         assert_eq!(sub.color_params().len(), 1);
     }
+
+    #[test]
+    fn test_material_lerp() {
+        let skin = NameHash::from_str("skin");
+        let roughness = NameHash::from_str("roughness");
+        let tint = NameHash::from_str("tint");
+
+        let mut from = SubMaterial::new(skin);
+        from.add_float_param(roughness, 0.2);
+        from.add_color_param(tint, ColorRgba::black());
+        from.add_texture(Texture::new(NameHash::from_str("diffuse"), "a.dds"));
+        let mut a = Material::new(NameHash::from_str("head"), CigGuid::default());
+        a.add_sub_material(from);
+
+        let mut to = SubMaterial::new(skin);
+        to.add_float_param(roughness, 0.6);
+        to.add_color_param(tint, ColorRgba::white());
+        to.add_texture(Texture::new(NameHash::from_str("diffuse"), "b.dds"));
+        let mut b = Material::new(NameHash::from_str("head"), CigGuid::default());
+        b.add_sub_material(to);
+
+        let blended = Material::lerp(&a, &b, 0.75);
+        let sub = &blended.sub_materials()[0];
+        assert!((sub.float_param(roughness).unwrap() - 0.5).abs() < 1e-6);
+        let color = sub.color_param(tint).unwrap();
+        assert!((color.r - 0.75).abs() < 1e-6);
+        assert_eq!(color.a, 1.0);
+        assert_eq!(sub.textures()[0].path, "b.dds");
+    }
 }
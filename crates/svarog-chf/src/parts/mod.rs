@@ -20,12 +20,19 @@
 
 mod data;
 mod dna;
+mod gender;
 mod itemport;
+#[cfg(feature = "json")]
+mod json;
 mod material;
 mod name_hash;
 
 pub use data::ChfData;
 pub use dna::{Dna, DnaPart, FacePart, BLENDS_PER_FACE_PART, DNA_PART_COUNT, DNA_SIZE};
+pub use gender::GenderMapping;
 pub use itemport::{ItemPort, ItemPortIter};
 pub use material::{ColorRgba, Material, MaterialParam, SubMaterial, Texture};
-pub use name_hash::{is_known_hash, known_hashes, NameHash};
+pub use name_hash::{
+    is_known_hash, known_hashes, load_dictionary, register_dictionary, register_name,
+    register_names, register_path_names, BruteForce, NameHash,
+};
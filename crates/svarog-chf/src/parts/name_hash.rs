@@ -3,9 +3,15 @@
 //! CHF files use CRC32C hashes to identify field names and item types.
 //! This module provides a lookup dictionary to reverse these hashes to
 //! human-readable names.
+//!
+//! The dictionary starts with a built-in list and can be extended at runtime
+//! with [`register_names`], [`load_dictionary`] or names harvested from game
+//! paths with [`register_path_names`]. Hashes that are still unknown can be
+//! searched for with [`BruteForce`].
 
-use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{LazyLock, PoisonError, RwLock};
 
 use svarog_common::crc;
 
@@ -15,6 +21,7 @@ pub struct NameHash(pub u32);
 
 impl NameHash {
     /// Create a new name hash from a string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         Self(crc::hash_str(s))
     }
@@ -31,7 +38,11 @@ impl NameHash {
 
     /// Look up the name for this hash.
     pub fn to_name(&self) -> Option<&'static str> {
-        NAME_LOOKUP.get(&self.0).copied()
+        NAME_LOOKUP
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&self.0)
+            .copied()
     }
 
     /// Get the name or a hex representation if unknown.
@@ -40,6 +51,16 @@ impl NameHash {
             .map(|s| s.to_string())
             .unwrap_or_else(|| format!("0x{:08X}", self.0))
     }
+
+    /// Parse the output of [`NameHash::to_name_or_hex`]: `0x` followed by 8
+    /// hex digits is a raw hash, anything else is hashed as a name.
+    pub fn from_name_or_hex(s: &str) -> Self {
+        s.strip_prefix("0x")
+            .filter(|hex| hex.len() == 8)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .map(Self)
+            .unwrap_or_else(|| Self::from_str(s))
+    }
 }
 
 impl std::fmt::Display for NameHash {
@@ -51,6 +72,27 @@ impl std::fmt::Display for NameHash {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for NameHash {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_name_or_hex())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NameHash {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from_name_or_hex(&s))
+    }
+}
+
 /// Known name strings and their CRC32C hashes.
 ///
 /// This list is derived from the .NET implementation and includes
@@ -206,25 +248,208 @@ static KNOWN_NAMES: &[&str] = &[
 ];
 
 /// Lookup table from CRC32C hash to name.
-static NAME_LOOKUP: LazyLock<HashMap<u32, &'static str>> = LazyLock::new(|| {
+static NAME_LOOKUP: LazyLock<RwLock<HashMap<u32, &'static str>>> = LazyLock::new(|| {
     let mut map = HashMap::with_capacity(KNOWN_NAMES.len());
     for &name in KNOWN_NAMES {
         let hash = crc::hash_str(name);
         map.insert(hash, name);
     }
-    map
+    RwLock::new(map)
 });
 
-/// Register additional names to the lookup table.
-/// Note: This won't work with LazyLock, so we provide a function
-/// to check if a hash is known.
+/// Check if a hash is in the dictionary.
 pub fn is_known_hash(hash: u32) -> bool {
-    NAME_LOOKUP.contains_key(&hash)
+    NAME_LOOKUP
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(&hash)
 }
 
-/// Get all known hashes.
+/// Get all known hashes, including registered ones.
 pub fn known_hashes() -> impl Iterator<Item = (u32, &'static str)> {
-    NAME_LOOKUP.iter().map(|(&k, &v)| (k, v))
+    let map = NAME_LOOKUP.read().unwrap_or_else(PoisonError::into_inner);
+    map.iter()
+        .map(|(&k, &v)| (k, v))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+/// Add a name to the dictionary and return its hash.
+///
+/// The first name registered for a hash wins. Registered names live for the
+/// rest of the process.
+pub fn register_name(name: &str) -> NameHash {
+    let hash = NameHash::from_str(name);
+    NAME_LOOKUP
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(hash.0)
+        .or_insert_with(|| Box::leak(name.to_owned().into_boxed_str()));
+    hash
+}
+
+/// Add several names to the dictionary, returning how many were new.
+pub fn register_names<I, S>(names: I) -> usize
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut map = NAME_LOOKUP.write().unwrap_or_else(PoisonError::into_inner);
+    let mut added = 0;
+    for name in names {
+        let name = name.as_ref();
+        if name.is_empty() {
+            continue;
+        }
+        map.entry(crc::hash_str(name)).or_insert_with(|| {
+            added += 1;
+            Box::leak(name.to_owned().into_boxed_str())
+        });
+    }
+    added
+}
+
+/// Register the components and file stems of game paths, e.g. P4K entry
+/// names, returning how many names were new.
+///
+/// `Data/Objects/Characters/Human/heads/male/eyes/eyes_blue.skin` adds
+/// `eyes`, `eyes_blue`, `eyes_blue.skin` and the other directory names.
+pub fn register_path_names<I, S>(paths: I) -> usize
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut names = HashSet::new();
+    for path in paths {
+        for component in path.as_ref().split(['/', '\\']) {
+            names.insert(component.to_string());
+            if let Some((stem, _)) = component.split_once('.') {
+                names.insert(stem.to_string());
+            }
+        }
+    }
+    register_names(names)
+}
+
+/// Load a dictionary file with one name per line, returning how many names
+/// were new.
+///
+/// See [`register_dictionary`] for the format.
+pub fn load_dictionary<P: AsRef<Path>>(path: P) -> crate::Result<usize> {
+    Ok(register_dictionary(&std::fs::read_to_string(path)?))
+}
+
+/// Register a dictionary with one name per line, returning how many names
+/// were new.
+///
+/// Surrounding whitespace is trimmed; empty lines and lines starting with
+/// `#` are skipped.
+pub fn register_dictionary(text: &str) -> usize {
+    register_names(
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#')),
+    )
+}
+
+/// Brute-force search for names matching unknown hashes.
+///
+/// Candidates are `prefix + word + suffix` for every word over the charset
+/// up to `max_len` characters. The search space grows as
+/// `charset.len() ^ max_len`; with the default 37-character charset a
+/// length of 5 takes seconds, 6 takes minutes. CRC32C has collisions, so a
+/// match is a candidate and not proof.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_chf::parts::{register_name, BruteForce};
+///
+/// let found = BruteForce::new(5).prefix("port_").find(&[0xDEADBEEF]);
+/// for (hash, name) in found {
+///     println!("{:08X} = {}", hash.value(), name);
+///     register_name(&name);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BruteForce {
+    charset: Vec<u8>,
+    max_len: usize,
+    prefix: String,
+    suffix: String,
+}
+
+impl BruteForce {
+    /// Default charset: lowercase letters, digits and `_`.
+    pub const DEFAULT_CHARSET: &'static str = "abcdefghijklmnopqrstuvwxyz0123456789_";
+
+    /// Search words of 1 to `max_len` characters.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            charset: Self::DEFAULT_CHARSET.as_bytes().to_vec(),
+            max_len,
+            prefix: String::new(),
+            suffix: String::new(),
+        }
+    }
+
+    /// Characters to build words from (ASCII).
+    pub fn charset(mut self, charset: &str) -> Self {
+        let mut bytes: Vec<u8> = charset.bytes().filter(u8::is_ascii).collect();
+        bytes.sort_unstable();
+        bytes.dedup();
+        self.charset = bytes;
+        self
+    }
+
+    /// Fixed text before each word, e.g. `port_`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Fixed text after each word, e.g. `_color`.
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Find every candidate matching one of `targets`, in search order.
+    pub fn find(&self, targets: &[u32]) -> Vec<(NameHash, String)> {
+        let targets: HashSet<u32> = targets.iter().copied().collect();
+        let mut found = Vec::new();
+        let mut word = Vec::with_capacity(self.max_len);
+        let seed = crc::hash_str(&self.prefix);
+        self.search(seed, &mut word, &targets, &mut found);
+        found
+    }
+
+    fn search(
+        &self,
+        crc: u32,
+        word: &mut Vec<u8>,
+        targets: &HashSet<u32>,
+        found: &mut Vec<(NameHash, String)>,
+    ) {
+        if word.len() == self.max_len {
+            return;
+        }
+        for &c in &self.charset {
+            let next = crc::hash_bytes_with_seed(&[c], crc);
+            word.push(c);
+            let hash = crc::hash_bytes_with_seed(self.suffix.as_bytes(), next);
+            if targets.contains(&hash) {
+                // The charset is ASCII, so the word is valid UTF-8
+                let word = String::from_utf8_lossy(word);
+                found.push((
+                    NameHash(hash),
+                    format!("{}{}{}", self.prefix, word, self.suffix),
+                ));
+            }
+            self.search(next, word, targets, found);
+            word.pop();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -252,4 +477,49 @@ mod tests {
         let unknown = NameHash::from_raw(0x12345678);
         assert_eq!(format!("{}", unknown), "0x12345678");
     }
+
+    #[test]
+    fn test_register_names() {
+        let hash = NameHash::from_str("svarog_test_registered");
+        assert_eq!(hash.to_name(), None);
+
+        assert_eq!(register_names(["svarog_test_registered", "head"]), 1);
+        assert_eq!(hash.to_name(), Some("svarog_test_registered"));
+        assert!(is_known_hash(hash.value()));
+
+        register_path_names(["Data/svarog_test_dir/svarog_test_file.skin"]);
+        assert_eq!(
+            NameHash::from_str("svarog_test_file").to_name(),
+            Some("svarog_test_file")
+        );
+        assert!(is_known_hash(crc::hash_str("svarog_test_dir")));
+    }
+
+    #[test]
+    fn test_register_dictionary() {
+        let text =
+            "# harvested names\n  svarog_test_dict_a \n\n#svarog_test_dict_b\nsvarog_test_dict_c\n";
+        assert_eq!(register_dictionary(text), 2);
+        assert!(is_known_hash(crc::hash_str("svarog_test_dict_a")));
+        assert!(!is_known_hash(crc::hash_str("#svarog_test_dict_b")));
+        assert!(!is_known_hash(crc::hash_str("svarog_test_dict_b")));
+        assert_eq!(register_dictionary(text), 0);
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let target = crc::hash_str("port_z9_color");
+        let found = BruteForce::new(3)
+            .prefix("port_")
+            .suffix("_color")
+            .find(&[target]);
+        assert!(found
+            .iter()
+            .any(|(hash, name)| hash.value() == target && name == "port_z9_color"));
+
+        let found = BruteForce::new(2)
+            .charset("ab")
+            .find(&[crc::hash_str("ba")]);
+        assert_eq!(found, vec![(NameHash::from_str("ba"), "ba".to_string())]);
+    }
 }
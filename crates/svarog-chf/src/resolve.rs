@@ -0,0 +1,267 @@
+//! Resolution of CHF GUIDs and name hashes through a DataCore database.
+//!
+//! CHF files only store CRC32C name hashes and record GUIDs. [`ChfResolver`]
+//! turns them into readable names: GUIDs become the DataCore records they
+//! reference (hair styles, eye colors, ...) and hashes are looked up in the
+//! built-in dictionary and in every string of the database.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use svarog_common::{crc, CigGuid};
+use svarog_datacore::DataCoreDatabase;
+
+use crate::parts::{register_names, ChfData, ItemPort, NameHash};
+
+/// A GUID reference and the record it resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedRecord {
+    /// The referenced GUID.
+    pub guid: CigGuid,
+    /// Record name, if the GUID is a record.
+    pub name: Option<String>,
+    /// Struct type of the record.
+    pub struct_name: Option<String>,
+    /// File the record is defined in.
+    pub file_name: Option<String>,
+}
+
+impl ResolvedRecord {
+    /// Whether the GUID was found in the database.
+    pub fn is_resolved(&self) -> bool {
+        self.name.is_some()
+    }
+}
+
+impl fmt::Display for ResolvedRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.name, &self.struct_name) {
+            (Some(name), Some(struct_name)) => write!(f, "{} [{}]", name, struct_name),
+            (Some(name), None) => write!(f, "{}", name),
+            _ => write!(f, "{} (not in DataCore)", self.guid),
+        }
+    }
+}
+
+/// An item port with its name and attached item resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPort {
+    /// Port name hash.
+    pub hash: NameHash,
+    /// Readable port name, or the hash in hex if unknown.
+    pub name: String,
+    /// The attached item, if any.
+    pub item: Option<ResolvedRecord>,
+    /// Child ports.
+    pub children: Vec<ResolvedPort>,
+}
+
+/// A material with its names resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedMaterial {
+    /// Readable material name, or the hash in hex if unknown.
+    pub name: String,
+    /// The material record.
+    pub record: ResolvedRecord,
+    /// Readable sub-material names.
+    pub sub_materials: Vec<String>,
+}
+
+/// A character with all references resolved, see [`ChfData::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedCharacter {
+    /// The gender (body) record.
+    pub gender: ResolvedRecord,
+    /// Root of the item port tree.
+    pub item_port: Option<ResolvedPort>,
+    /// Materials in file order.
+    pub materials: Vec<ResolvedMaterial>,
+}
+
+impl fmt::Display for ResolvedCharacter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Gender: {}", self.gender)?;
+        if let Some(port) = &self.item_port {
+            writeln!(f, "Item ports:")?;
+            write_port(f, port, 1)?;
+        }
+        if !self.materials.is_empty() {
+            writeln!(f, "Materials:")?;
+            for material in &self.materials {
+                writeln!(f, "  {}: {}", material.name, material.record)?;
+                for sub in &material.sub_materials {
+                    writeln!(f, "    {}", sub)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_port(f: &mut fmt::Formatter<'_>, port: &ResolvedPort, depth: usize) -> fmt::Result {
+    write!(f, "{:indent$}{}", "", port.name, indent = depth * 2)?;
+    match &port.item {
+        Some(item) => writeln!(f, " = {}", item)?,
+        None => writeln!(f)?,
+    }
+    for child in &port.children {
+        write_port(f, child, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Resolves CHF references against a DataCore database.
+///
+/// Building the resolver hashes every string in the database once; reuse it
+/// when resolving many characters.
+pub struct ChfResolver<'a> {
+    database: &'a DataCoreDatabase,
+    names: HashMap<u32, &'a str>,
+}
+
+impl<'a> ChfResolver<'a> {
+    /// Create a resolver for `database`.
+    pub fn new(database: &'a DataCoreDatabase) -> Self {
+        let mut names = HashMap::new();
+        for s in database_names(database) {
+            names.entry(crc::hash_str(s)).or_insert(s);
+        }
+        Self { database, names }
+    }
+
+    /// Readable name for a hash, or the hash in hex if unknown.
+    pub fn name(&self, hash: NameHash) -> String {
+        match hash.to_name() {
+            Some(name) => name.to_string(),
+            None => self
+                .names
+                .get(&hash.value())
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| hash.to_name_or_hex()),
+        }
+    }
+
+    /// Look up the record a GUID references.
+    pub fn record(&self, guid: &CigGuid) -> ResolvedRecord {
+        let record = self.database.get_record(guid);
+        ResolvedRecord {
+            guid: *guid,
+            name: record
+                .and_then(|r| self.database.record_name(r))
+                .map(str::to_string),
+            struct_name: record
+                .and_then(|r| self.database.struct_name(r.struct_index as usize))
+                .map(str::to_string),
+            file_name: record
+                .and_then(|r| self.database.record_file_name(r))
+                .map(str::to_string),
+        }
+    }
+
+    /// Resolve a whole character.
+    pub fn resolve(&self, data: &ChfData) -> ResolvedCharacter {
+        ResolvedCharacter {
+            gender: self.record(data.gender_id()),
+            item_port: data.item_port().map(|port| self.port(port)),
+            materials: data
+                .materials()
+                .iter()
+                .map(|material| ResolvedMaterial {
+                    name: self.name(material.name()),
+                    record: self.record(material.guid()),
+                    sub_materials: material
+                        .sub_materials()
+                        .iter()
+                        .map(|sub| self.name(sub.name()))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn port(&self, port: &ItemPort) -> ResolvedPort {
+        ResolvedPort {
+            hash: port.name(),
+            name: self.name(port.name()),
+            item: port
+                .item_guid()
+                .filter(|guid| !guid.is_empty())
+                .map(|guid| self.record(guid)),
+            children: port.children().iter().map(|c| self.port(c)).collect(),
+        }
+    }
+}
+
+/// Add every string of `database` to the global [`NameHash`] dictionary,
+/// returning how many names were new.
+///
+/// Unlike a [`ChfResolver`], this makes the names available to
+/// [`NameHash::to_name`] and JSON output everywhere in the process.
+pub fn register_datacore_names(database: &DataCoreDatabase) -> usize {
+    register_names(database_names(database))
+}
+
+/// Every string in the database, plus the short name after the last `.`.
+fn database_names(database: &DataCoreDatabase) -> impl Iterator<Item = &str> {
+    [database.raw_string_table_1(), database.raw_string_table_2()]
+        .into_iter()
+        .flat_map(|table| table.split(|&b| b == 0))
+        .filter_map(|s| std::str::from_utf8(s).ok())
+        .filter(|s| !s.is_empty())
+        .flat_map(|s| {
+            // Record names are "Type.name"; ports often use the short name
+            std::iter::once(s).chain(s.rsplit_once('.').map(|(_, short)| short))
+        })
+}
+
+impl ChfData {
+    /// Resolve GUIDs and name hashes against `database`.
+    ///
+    /// Use a [`ChfResolver`] directly to resolve several characters.
+    pub fn resolve(&self, database: &DataCoreDatabase) -> ResolvedCharacter {
+        ChfResolver::new(database).resolve(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_character_display() {
+        let hair = ResolvedRecord {
+            guid: CigGuid::from_bytes([1; 16]),
+            name: Some("hair_01".to_string()),
+            struct_name: Some("EntityClassDefinition".to_string()),
+            file_name: None,
+        };
+        let missing = ResolvedRecord {
+            guid: CigGuid::from_bytes([2; 16]),
+            name: None,
+            struct_name: None,
+            file_name: None,
+        };
+        assert!(hair.is_resolved());
+        assert!(!missing.is_resolved());
+
+        let character = ResolvedCharacter {
+            gender: missing.clone(),
+            item_port: Some(ResolvedPort {
+                hash: NameHash::from_str("body"),
+                name: "body".to_string(),
+                item: None,
+                children: vec![ResolvedPort {
+                    hash: NameHash::from_str("hair"),
+                    name: "hair".to_string(),
+                    item: Some(hair),
+                    children: Vec::new(),
+                }],
+            }),
+            materials: Vec::new(),
+        };
+
+        let text = character.to_string();
+        assert!(text.contains("(not in DataCore)"));
+        assert!(text.contains("\n    hair = hair_01 [EntityClassDefinition]\n"));
+    }
+}
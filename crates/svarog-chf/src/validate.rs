@@ -0,0 +1,186 @@
+//! Validation of CHF characters against a DataCore database.
+//!
+//! Characters reference DataCore records by GUID: the gender (body) record,
+//! the items attached to item ports and the materials. After a patch some of
+//! these records can be removed, which makes the character fail to load.
+//! [`ChfData::validate`] lists every reference that no longer resolves.
+
+use std::fmt;
+
+use svarog_common::CigGuid;
+use svarog_datacore::DataCoreDatabase;
+
+use crate::parts::{ChfData, NameHash};
+
+/// A reference in a character that does not resolve to a DataCore record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChfIssue {
+    /// The gender GUID is not a record.
+    UnknownGender {
+        /// The gender GUID.
+        guid: CigGuid,
+    },
+    /// An item port holds an item that is not a record.
+    UnknownItem {
+        /// Names of the ports from the root down to the offending port.
+        path: Vec<NameHash>,
+        /// The item GUID.
+        guid: CigGuid,
+    },
+    /// A material GUID is not a record.
+    UnknownMaterial {
+        /// The material name.
+        name: NameHash,
+        /// The material GUID.
+        guid: CigGuid,
+    },
+}
+
+impl fmt::Display for ChfIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChfIssue::UnknownGender { guid } => write!(f, "unknown gender {}", guid),
+            ChfIssue::UnknownItem { path, guid } => {
+                write!(f, "unknown item {} on port ", guid)?;
+                for (i, name) in path.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "/")?;
+                    }
+                    write!(f, "{}", name)?;
+                }
+                Ok(())
+            }
+            ChfIssue::UnknownMaterial { name, guid } => {
+                write!(f, "unknown material {} ({})", guid, name)
+            }
+        }
+    }
+}
+
+/// Result of [`ChfData::validate`].
+#[derive(Debug, Clone, Default)]
+pub struct ChfValidation {
+    /// Unresolved references, in file order.
+    pub issues: Vec<ChfIssue>,
+    /// Number of GUID references that were checked.
+    pub checked: usize,
+}
+
+impl ChfValidation {
+    /// Whether every reference resolved.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ChfValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        write!(
+            f,
+            "{} of {} references unresolved",
+            self.issues.len(),
+            self.checked
+        )
+    }
+}
+
+impl ChfData {
+    /// Check the gender, item port items and materials against the records
+    /// of `database`.
+    ///
+    /// Empty (all-zero) GUIDs are not references and are skipped.
+    pub fn validate(&self, database: &DataCoreDatabase) -> ChfValidation {
+        self.validate_with(|guid| database.get_record(guid).is_some())
+    }
+
+    /// Like [`ChfData::validate`], with a custom record lookup.
+    pub fn validate_with(&self, exists: impl Fn(&CigGuid) -> bool) -> ChfValidation {
+        let mut report = ChfValidation::default();
+        let mut check = |guid: &CigGuid| {
+            report.checked += 1;
+            exists(guid)
+        };
+
+        let mut issues = Vec::new();
+        let gender = *self.gender_id();
+        if !gender.is_empty() && !check(&gender) {
+            issues.push(ChfIssue::UnknownGender { guid: gender });
+        }
+
+        if let Some(root) = self.item_port() {
+            let mut stack = vec![(root, vec![root.name()])];
+            while let Some((port, path)) = stack.pop() {
+                if let Some(guid) = port.item_guid() {
+                    if !guid.is_empty() && !check(guid) {
+                        issues.push(ChfIssue::UnknownItem {
+                            path: path.clone(),
+                            guid: *guid,
+                        });
+                    }
+                }
+                for child in port.children().iter().rev() {
+                    let mut child_path = path.clone();
+                    child_path.push(child.name());
+                    stack.push((child, child_path));
+                }
+            }
+        }
+
+        for material in self.materials() {
+            let guid = *material.guid();
+            if !guid.is_empty() && !check(&guid) {
+                issues.push(ChfIssue::UnknownMaterial {
+                    name: material.name(),
+                    guid,
+                });
+            }
+        }
+
+        report.issues = issues;
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parts::{ItemPort, Material};
+
+    #[test]
+    fn test_validate_reports_missing_records() {
+        let known = [CigGuid::from_bytes([1; 16]), CigGuid::from_bytes([2; 16])];
+        let removed = CigGuid::from_bytes([9; 16]);
+
+        let mut data = ChfData::new(known[0]);
+        let mut body = ItemPort::new(NameHash::from_str("body"));
+        let head = body.add_port(NameHash::from_str("head"), None);
+        head.add_port(NameHash::from_str("hair"), Some(removed));
+        head.add_port(NameHash::from_str("eyes"), Some(known[1]));
+        data.set_item_port(Some(body));
+        data.add_material(Material::new(NameHash::from_str("skin"), removed));
+        data.add_material(Material::new(
+            NameHash::from_str("empty"),
+            CigGuid::default(),
+        ));
+
+        let report = data.validate_with(|guid| known.contains(guid));
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.issues.len(), 2);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.issues[0],
+            ChfIssue::UnknownItem {
+                path: vec![
+                    NameHash::from_str("body"),
+                    NameHash::from_str("head"),
+                    NameHash::from_str("hair"),
+                ],
+                guid: removed,
+            }
+        );
+        assert!(matches!(report.issues[1], ChfIssue::UnknownMaterial { .. }));
+    }
+}
@@ -0,0 +1,289 @@
+//! Append-only string storage with an optional memory budget.
+//!
+//! [`StringArena`] packs strings into large blocks instead of allocating each
+//! one separately, and hands out [`ArenaStr`] handles to look them up again.
+//! A budget caps the memory taken by blocks; what happens to strings past it
+//! is chosen with [`Overflow`].
+
+use crate::{Error, Result};
+
+/// What a [`StringArena`] does with a string that does not fit its budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Store the string in its own exactly sized heap allocation.
+    #[default]
+    Spill,
+    /// Refuse the string with [`Error::ArenaBudgetExceeded`].
+    Fail,
+}
+
+/// Options for [`StringArena::with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaOptions {
+    /// Size in bytes of the blocks strings are packed into. Longer strings
+    /// get a block of their own.
+    pub chunk_size: usize,
+    /// Maximum number of bytes taken by blocks, or `None` for no limit.
+    pub budget: Option<usize>,
+    /// What to do with strings once the budget is used up.
+    pub overflow: Overflow,
+}
+
+impl Default for ArenaOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64 * 1024,
+            budget: None,
+            overflow: Overflow::Spill,
+        }
+    }
+}
+
+/// Memory used by a [`StringArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ArenaStats {
+    /// Number of strings stored, spilled ones included.
+    pub strings: usize,
+    /// Number of blocks.
+    pub chunks: usize,
+    /// Bytes allocated for blocks.
+    pub capacity_bytes: usize,
+    /// Bytes of blocks holding strings.
+    pub used_bytes: usize,
+    /// Number of strings stored outside the blocks.
+    pub spilled_strings: usize,
+    /// Bytes of strings stored outside the blocks.
+    pub spilled_bytes: usize,
+}
+
+impl ArenaStats {
+    /// Bytes allocated for strings, blocks and spilled strings together.
+    pub fn total_bytes(&self) -> usize {
+        self.capacity_bytes + self.spilled_bytes
+    }
+}
+
+/// Handle to a string in a [`StringArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaStr {
+    /// Block index, or [`SPILLED`].
+    chunk: u32,
+    /// Offset in the block, or index of the spilled string.
+    start: u32,
+    len: u32,
+}
+
+/// Block index of handles to spilled strings.
+const SPILLED: u32 = u32::MAX;
+
+/// Append-only string storage.
+///
+/// Blocks are never grown once allocated, so storing a string never moves
+/// the ones before it.
+///
+/// # Example
+///
+/// ```
+/// use svarog_common::arena::{ArenaOptions, Overflow, StringArena};
+///
+/// let mut arena = StringArena::with_options(ArenaOptions {
+///     chunk_size: 16,
+///     budget: Some(16),
+///     overflow: Overflow::Spill,
+/// });
+/// let first = arena.alloc("Data\\Libs").unwrap();
+/// let second = arena.alloc("Data\\Objects").unwrap();
+/// assert_eq!(arena.get(first), "Data\\Libs");
+/// assert_eq!(arena.get(second), "Data\\Objects");
+/// assert_eq!(arena.stats().spilled_strings, 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct StringArena {
+    options: ArenaOptions,
+    chunks: Vec<String>,
+    spilled: Vec<Box<str>>,
+    stats: ArenaStats,
+}
+
+impl StringArena {
+    /// Create an empty arena without a budget.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty arena with the given options.
+    pub fn with_options(options: ArenaOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// The options the arena was created with.
+    pub fn options(&self) -> &ArenaOptions {
+        &self.options
+    }
+
+    /// Store a string and return its handle.
+    ///
+    /// Strings longer than `u32::MAX` bytes are always spilled.
+    pub fn alloc(&mut self, s: &str) -> Result<ArenaStr> {
+        let len = s.len();
+        let handle = match u32::try_from(len) {
+            Ok(len32) => match self.chunk_with_room(len) {
+                Some(chunk) => {
+                    let block = &mut self.chunks[chunk];
+                    let start = block.len() as u32;
+                    block.push_str(s);
+                    self.stats.used_bytes += len;
+                    ArenaStr {
+                        chunk: chunk as u32,
+                        start,
+                        len: len32,
+                    }
+                }
+                None if self.options.overflow == Overflow::Fail => {
+                    return Err(Error::ArenaBudgetExceeded {
+                        budget: self.options.budget.unwrap_or(0),
+                    });
+                }
+                None => self.spill(s),
+            },
+            Err(_) => self.spill(s),
+        };
+        self.stats.strings += 1;
+        Ok(handle)
+    }
+
+    /// Look up a stored string.
+    ///
+    /// # Panics
+    ///
+    /// May panic if `handle` was returned by another arena.
+    #[inline]
+    pub fn get(&self, handle: ArenaStr) -> &str {
+        if handle.chunk == SPILLED {
+            return &self.spilled[handle.start as usize];
+        }
+        let start = handle.start as usize;
+        &self.chunks[handle.chunk as usize][start..start + handle.len as usize]
+    }
+
+    /// Number of strings stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.stats.strings
+    }
+
+    /// Whether no strings are stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.stats.strings == 0
+    }
+
+    /// Current memory use.
+    #[inline]
+    pub fn stats(&self) -> ArenaStats {
+        self.stats
+    }
+
+    /// Index of a block with room for `len` bytes, allocating one if the
+    /// budget allows.
+    fn chunk_with_room(&mut self, len: usize) -> Option<usize> {
+        if let Some(last) = self.chunks.last() {
+            if last.capacity() - last.len() >= len {
+                return Some(self.chunks.len() - 1);
+            }
+        }
+        if self.chunks.len() >= SPILLED as usize {
+            return None;
+        }
+
+        let mut size = self.options.chunk_size.max(len).max(1);
+        if let Some(budget) = self.options.budget {
+            let remaining = budget.saturating_sub(self.stats.capacity_bytes);
+            if len > remaining {
+                return None;
+            }
+            size = size.min(remaining).max(1);
+        }
+        let block = String::with_capacity(size);
+        self.stats.capacity_bytes += block.capacity();
+        self.stats.chunks += 1;
+        self.chunks.push(block);
+        Some(self.chunks.len() - 1)
+    }
+
+    fn spill(&mut self, s: &str) -> ArenaStr {
+        self.spilled.push(s.into());
+        self.stats.spilled_strings += 1;
+        self.stats.spilled_bytes += s.len();
+        ArenaStr {
+            chunk: SPILLED,
+            start: (self.spilled.len() - 1) as u32,
+            len: s.len().min(u32::MAX as usize) as u32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc_and_get() {
+        let mut arena = StringArena::with_options(ArenaOptions {
+            chunk_size: 8,
+            ..ArenaOptions::default()
+        });
+        let handles: Vec<_> = ["one", "two", "three", "a longer string", ""]
+            .iter()
+            .map(|s| arena.alloc(s).unwrap())
+            .collect();
+
+        assert_eq!(arena.get(handles[0]), "one");
+        assert_eq!(arena.get(handles[2]), "three");
+        assert_eq!(arena.get(handles[3]), "a longer string");
+        assert_eq!(arena.get(handles[4]), "");
+        assert_eq!(arena.len(), 5);
+
+        let stats = arena.stats();
+        assert_eq!(stats.used_bytes, 26);
+        assert!(stats.chunks >= 3);
+        assert_eq!(stats.spilled_strings, 0);
+    }
+
+    #[test]
+    fn test_budget_spill() {
+        let mut arena = StringArena::with_options(ArenaOptions {
+            chunk_size: 4,
+            budget: Some(8),
+            overflow: Overflow::Spill,
+        });
+        let handles: Vec<_> = ["abcd", "efgh", "ijkl"]
+            .iter()
+            .map(|s| arena.alloc(s).unwrap())
+            .collect();
+
+        assert_eq!(arena.get(handles[2]), "ijkl");
+        let stats = arena.stats();
+        assert!(stats.capacity_bytes <= 8);
+        assert_eq!(stats.spilled_strings, 1);
+        assert_eq!(stats.spilled_bytes, 4);
+    }
+
+    #[test]
+    fn test_budget_fail() {
+        let mut arena = StringArena::with_options(ArenaOptions {
+            chunk_size: 4,
+            budget: Some(4),
+            overflow: Overflow::Fail,
+        });
+        assert!(arena.alloc("abcd").is_ok());
+        assert!(matches!(
+            arena.alloc("e"),
+            Err(Error::ArenaBudgetExceeded { budget: 4 })
+        ));
+        assert_eq!(arena.len(), 1);
+    }
+}
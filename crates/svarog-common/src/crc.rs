@@ -2,10 +2,14 @@
 //!
 //! CRC32C (Castagnoli) is used throughout Star Citizen files for checksums
 //! and as a hash for string identifiers.
+//!
+//! All functions use the SSE4.2 (x86-64) or ARMv8 CRC (aarch64) instructions
+//! when the CPU has them, detected at runtime, and fall back to a table
+//! implementation otherwise.
+
+use std::io::{self, Read};
 
 /// Compute CRC32C hash of a byte slice.
-///
-/// Uses hardware acceleration when available (SSE4.2 on x86).
 #[inline]
 pub fn hash_bytes(data: &[u8]) -> u32 {
     crc32c::crc32c(data)
@@ -27,6 +31,109 @@ pub fn hash_str(s: &str) -> u32 {
     hash_bytes(s.as_bytes())
 }
 
+/// Check whether CRC32C runs on dedicated CPU instructions.
+pub fn is_hardware_accelerated() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::arch::is_x86_feature_detected!("sse4.2")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("crc")
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// Incremental CRC32C hasher, for data that arrives in pieces.
+///
+/// Feeding the data in any split gives the same result as
+/// [`hash_bytes`] over the whole.
+///
+/// # Example
+///
+/// ```
+/// use svarog_common::crc::{hash_bytes, Hasher};
+///
+/// let mut hasher = Hasher::new();
+/// hasher.update(b"hello ");
+/// hasher.update(b"world");
+/// assert_eq!(hasher.finalize(), hash_bytes(b"hello world"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hasher {
+    state: u32,
+    length: u64,
+}
+
+impl Hasher {
+    /// Create a hasher with no data.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            state: 0,
+            length: 0,
+        }
+    }
+
+    /// Create a hasher continuing from a previous CRC.
+    #[inline]
+    pub const fn with_seed(seed: u32) -> Self {
+        Self {
+            state: seed,
+            length: 0,
+        }
+    }
+
+    /// Add data to the hash.
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) {
+        self.state = crc32c::crc32c_append(self.state, data);
+        self.length += data.len() as u64;
+    }
+
+    /// Get the CRC of the data so far. The hasher can keep being updated.
+    #[inline]
+    pub const fn finalize(&self) -> u32 {
+        self.state
+    }
+
+    /// Number of bytes hashed.
+    #[inline]
+    pub const fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Start over with no data.
+    #[inline]
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl io::Write for Hasher {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Compute the CRC32C of everything `reader` yields, without buffering it
+/// all in memory.
+pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<u32> {
+    let mut hasher = Hasher::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,4 +158,31 @@ mod tests {
         let hash2 = hash_bytes(b"test");
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn test_hasher_matches_one_shot() {
+        let mut hasher = Hasher::new();
+        hasher.update(b"1234");
+        hasher.update(b"56789");
+        // CRC-32C check value
+        assert_eq!(hasher.finalize(), 0xE306_9283);
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31) as u8).collect();
+        let expected = hash_bytes(&data);
+
+        for split in [1, 7, 4096] {
+            let mut hasher = Hasher::new();
+            for chunk in data.chunks(split) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), expected);
+            assert_eq!(hasher.length(), data.len() as u64);
+        }
+
+        assert_eq!(hash_reader(&data[..]).unwrap(), expected);
+
+        let mut hasher = Hasher::with_seed(hash_bytes(&data[..100]));
+        hasher.update(&data[100..]);
+        assert_eq!(hasher.finalize(), expected);
+    }
 }
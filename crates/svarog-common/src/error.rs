@@ -3,22 +3,36 @@
 use thiserror::Error;
 
 /// Common error type for Svarog operations.
+///
+/// Errors raised by [`BinaryReader`](crate::BinaryReader) carry the byte
+/// offset at which reading failed, relative to the start of the reader's
+/// buffer. Parsers can wrap them with [`Error::in_section`] (or
+/// [`ResultExt::in_section`]) to record which part of the file was being read.
 #[derive(Debug, Error)]
 pub enum Error {
     /// End of buffer reached while reading.
-    #[error("unexpected end of buffer: needed {needed} bytes but only {available} available")]
-    UnexpectedEof { needed: usize, available: usize },
+    #[error("unexpected end of buffer at offset {offset:#x}: needed {needed} bytes but only {available} available")]
+    UnexpectedEof {
+        offset: usize,
+        needed: usize,
+        available: usize,
+    },
 
     /// Invalid magic bytes encountered.
-    #[error("invalid magic: expected {expected:?}, got {actual:?}")]
+    #[error("invalid magic at offset {offset:#x}: expected {expected:?}, got {actual:?}")]
     InvalidMagic {
+        offset: usize,
         expected: Vec<u8>,
         actual: Vec<u8>,
     },
 
     /// Value did not match expected.
-    #[error("expected value {expected}, got {actual}")]
-    ExpectedValue { expected: String, actual: String },
+    #[error("expected value {expected} at offset {offset:#x}, got {actual}")]
+    ExpectedValue {
+        offset: usize,
+        expected: String,
+        actual: String,
+    },
 
     /// Invalid GUID format.
     #[error("invalid GUID format: {0}")]
@@ -32,10 +46,154 @@ pub enum Error {
     #[error("UTF-8 error: {0}")]
     Utf8(#[from] std::str::Utf8Error),
 
+    /// UTF-8 decoding error in a string read from the buffer.
+    #[error("invalid UTF-8 in string at offset {offset:#x}: {source}")]
+    InvalidString {
+        offset: usize,
+        source: std::str::Utf8Error,
+    },
+
     /// Missing null terminator in string.
-    #[error("string missing null terminator")]
-    MissingNullTerminator,
+    #[error("string at offset {offset:#x} missing null terminator")]
+    MissingNullTerminator { offset: usize },
+
+    /// A [`StringArena`](crate::arena::StringArena) refused a string that
+    /// would exceed its memory budget.
+    #[error("arena memory budget of {budget} bytes exceeded")]
+    ArenaBudgetExceeded { budget: usize },
+
+    /// Error raised while parsing a named section of a file.
+    #[error("{section} (at offset {offset:#x}): {source}")]
+    InSection {
+        /// Name of the section, e.g. "struct definitions".
+        section: &'static str,
+        /// Offset at which the section starts.
+        offset: usize,
+        /// The underlying error.
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Wrap this error with the name and start offset of the section being
+    /// parsed when it occurred.
+    pub fn in_section(self, section: &'static str, offset: usize) -> Self {
+        Error::InSection {
+            section,
+            offset,
+            source: Box::new(self),
+        }
+    }
+
+    /// Byte offset at which the error was detected, if known.
+    ///
+    /// For errors wrapped with [`in_section`](Self::in_section), this is the
+    /// offset of the innermost error, falling back to the section start.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Error::UnexpectedEof { offset, .. }
+            | Error::InvalidMagic { offset, .. }
+            | Error::ExpectedValue { offset, .. }
+            | Error::InvalidString { offset, .. }
+            | Error::MissingNullTerminator { offset } => Some(*offset),
+            Error::InSection { offset, source, .. } => source.offset().or(Some(*offset)),
+            Error::InvalidGuid(_)
+            | Error::Io(_)
+            | Error::Utf8(_)
+            | Error::ArenaBudgetExceeded { .. } => None,
+        }
+    }
+
+    /// Name of the innermost section being parsed when the error occurred.
+    pub fn section(&self) -> Option<&'static str> {
+        match self {
+            Error::InSection {
+                section, source, ..
+            } => source.section().or(Some(*section)),
+            _ => None,
+        }
+    }
+}
+
+/// Where in the input a crate's error was detected.
+///
+/// The error types of the format crates wrap [`Error`] in a `Common` variant.
+/// They implement [`common`](Self::common) to expose it, and
+/// [`own_offset`](Self::own_offset) for variants of their own that carry an
+/// offset; [`offset`](Self::offset) and [`section`](Self::section) follow.
+pub trait ErrorLocation {
+    /// The wrapped common error, if this is one.
+    fn common(&self) -> Option<&Error>;
+
+    /// Byte offset carried by a variant other than the common error.
+    fn own_offset(&self) -> Option<usize> {
+        None
+    }
+
+    /// Byte offset at which the error was detected, if known.
+    fn offset(&self) -> Option<usize> {
+        match self.common() {
+            Some(e) => e.offset(),
+            None => self.own_offset(),
+        }
+    }
+
+    /// Name of the file section being parsed when the error occurred, if known.
+    fn section(&self) -> Option<&'static str> {
+        self.common().and_then(Error::section)
+    }
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&Error> {
+        Some(self)
+    }
+}
+
+/// Extension trait adding section context to results.
+pub trait ResultExt<T> {
+    /// Wrap the error, if any, with the section being parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use svarog_common::{BinaryReader, ResultExt};
+    ///
+    /// let mut reader = BinaryReader::new(&[1, 2]);
+    /// let err = reader.read_u32().in_section("header", 0).unwrap_err();
+    /// assert_eq!(err.section(), Some("header"));
+    /// assert_eq!(err.offset(), Some(0));
+    /// ```
+    fn in_section(self, section: &'static str, offset: usize) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> ResultExt<T> for std::result::Result<T, E> {
+    fn in_section(self, section: &'static str, offset: usize) -> Result<T> {
+        self.map_err(|e| e.into().in_section(section, offset))
+    }
 }
 
 /// Result type alias using the common Error type.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryReader;
+
+    #[test]
+    fn test_section_context() {
+        let data = [0u8; 6];
+        let mut reader = BinaryReader::new(&data);
+        reader.advance(4);
+
+        let err = reader.read_u32().in_section("records", 4).unwrap_err();
+        assert_eq!(err.offset(), Some(4));
+        assert_eq!(err.section(), Some("records"));
+
+        let outer = err.in_section("file", 0);
+        assert_eq!(outer.section(), Some("records"));
+        let message = outer.to_string();
+        assert!(message.starts_with("file (at offset 0x0): records"));
+    }
+}
@@ -23,6 +23,14 @@ use crate::Error;
 /// - String positions 14-17 (third group): bytes 1,0
 /// - String positions 19-22 (fourth group): bytes 15,14
 /// - String positions 24-35 (fifth group): bytes 13,12,11,10,9,8
+///
+/// In other words, the GUID is stored as two little-endian `u64` values,
+/// bytes 0-7 holding the first 16 hex digits of the string form and bytes
+/// 8-15 the last 16. A standard UUID stores the same digits in string order
+/// (big-endian), see [`to_uuid_bytes`](Self::to_uuid_bytes).
+///
+/// GUIDs are ordered by their string form, so sorting them matches sorting
+/// their strings.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, FromBytes, IntoBytes, Immutable, KnownLayout)]
 #[repr(C)]
 pub struct CigGuid {
@@ -45,15 +53,21 @@ impl CigGuid {
     /// This is suitable for generating unique IDs but not for cryptographic purposes.
     #[inline]
     pub fn random() -> Self {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
         use std::time::{SystemTime, UNIX_EPOCH};
 
         // Simple LCG seeded from current time and a counter
         static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
 
+        // The clock panics on wasm32-unknown-unknown; the counter alone
+        // still keeps GUIDs unique within the process there
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
         let time_seed = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_nanos() as u64)
             .unwrap_or(0);
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let time_seed = 0u64;
 
         let counter = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         let mut state = time_seed.wrapping_add(counter).wrapping_mul(6364136223846793005);
@@ -67,6 +81,63 @@ impl CigGuid {
         Self { bytes }
     }
 
+    /// Generate a random version 4 GUID.
+    ///
+    /// Like [`random`](Self::random), with the version and RFC 4122 variant
+    /// bits set where a UUID parser reading the string form expects them.
+    pub fn new_v4() -> Self {
+        let mut uuid = Self::random().to_uuid_bytes();
+        uuid[6] = (uuid[6] & 0x0f) | 0x40;
+        uuid[8] = (uuid[8] & 0x3f) | 0x80;
+        Self::from_uuid_bytes(uuid)
+    }
+
+    /// Create a GUID from the 128-bit value whose hex digits are its string form.
+    #[inline]
+    pub const fn from_u128(value: u128) -> Self {
+        let high = ((value >> 64) as u64).to_le_bytes();
+        let low = (value as u64).to_le_bytes();
+        let mut bytes = [0u8; 16];
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = high[i];
+            bytes[i + 8] = low[i];
+            i += 1;
+        }
+        Self { bytes }
+    }
+
+    /// Get the 128-bit value whose hex digits are the string form.
+    #[inline]
+    pub fn to_u128(&self) -> u128 {
+        let (high, low) = self.bytes.split_at(8);
+        let high = u64::from_le_bytes(high.try_into().unwrap());
+        let low = u64::from_le_bytes(low.try_into().unwrap());
+        (u128::from(high) << 64) | u128::from(low)
+    }
+
+    /// Create a GUID from standard UUID bytes (RFC 4122 order, as in the
+    /// string form).
+    #[inline]
+    pub fn from_uuid_bytes(uuid: [u8; 16]) -> Self {
+        Self::from_u128(u128::from_be_bytes(uuid))
+    }
+
+    /// Get the standard UUID bytes (RFC 4122 order, as in the string form).
+    #[inline]
+    pub fn to_uuid_bytes(&self) -> [u8; 16] {
+        self.to_u128().to_be_bytes()
+    }
+
+    /// Get the UUID version, the first digit of the third group.
+    ///
+    /// Game GUIDs are mostly version 4; [`random`](Self::random) ones have
+    /// an arbitrary version.
+    #[inline]
+    pub fn version(&self) -> u8 {
+        self.to_uuid_bytes()[6] >> 4
+    }
+
     /// Get the raw bytes of the GUID.
     #[inline]
     pub const fn as_bytes(&self) -> &[u8; 16] {
@@ -80,6 +151,27 @@ impl CigGuid {
     }
 }
 
+impl AsRef<CigGuid> for CigGuid {
+    #[inline]
+    fn as_ref(&self) -> &CigGuid {
+        self
+    }
+}
+
+impl PartialOrd for CigGuid {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CigGuid {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.to_u128().cmp(&other.to_u128())
+    }
+}
+
 impl fmt::Debug for CigGuid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "CigGuid({})", self)
@@ -107,13 +199,33 @@ impl fmt::Display for CigGuid {
     }
 }
 
+/// Parses the hyphenated form, optionally wrapped in braces as in CryEngine
+/// level and prefab files, or the 32-digit form without hyphens.
 impl FromStr for CigGuid {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .unwrap_or(s);
+        if !s.is_ascii() {
+            return Err(Error::InvalidGuid("non-ASCII characters".into()));
+        }
+        if s.len() == 32 {
+            if let Some(position) = s.bytes().position(|b| !b.is_ascii_hexdigit()) {
+                return Err(Error::InvalidGuid(format!(
+                    "invalid hex at position {}",
+                    position
+                )));
+            }
+            let value =
+                u128::from_str_radix(s, 16).map_err(|e| Error::InvalidGuid(e.to_string()))?;
+            return Ok(Self::from_u128(value));
+        }
         if s.len() != 36 {
             return Err(Error::InvalidGuid(format!(
-                "expected 36 characters, got {}",
+                "expected 32 or 36 characters, got {}",
                 s.len()
             )));
         }
@@ -185,6 +297,22 @@ impl<'de> serde::Deserialize<'de> for CigGuid {
     }
 }
 
+/// Converts to the UUID with the same string form.
+#[cfg(feature = "uuid")]
+impl From<CigGuid> for uuid::Uuid {
+    fn from(guid: CigGuid) -> Self {
+        uuid::Uuid::from_bytes(guid.to_uuid_bytes())
+    }
+}
+
+/// Converts from the UUID with the same string form.
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for CigGuid {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Self::from_uuid_bytes(*uuid.as_bytes())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,5 +342,55 @@ mod tests {
     #[test]
     fn test_invalid_hyphens() {
         assert!("12345678_abcd-ef01-2345-6789abcdef01".parse::<CigGuid>().is_err());
+        assert!("12345678-abcd-ef01-2345-6789abcdef\u{e9}".parse::<CigGuid>().is_err());
+    }
+
+    #[test]
+    fn test_alternate_forms() {
+        let guid: CigGuid = "12345678-abcd-ef01-2345-6789abcdef01".parse().unwrap();
+        let braced: CigGuid = "{12345678-ABCD-EF01-2345-6789ABCDEF01}".parse().unwrap();
+        let simple: CigGuid = "12345678abcdef0123456789abcdef01".parse().unwrap();
+        assert_eq!(braced, guid);
+        assert_eq!(simple, guid);
+        assert!(CigGuid::from_str("+2345678abcdef0123456789abcdef01").is_err());
+    }
+
+    #[test]
+    fn test_byte_order() {
+        let guid: CigGuid = "12345678-abcd-ef01-2345-6789abcdef01".parse().unwrap();
+        assert_eq!(guid.to_u128(), 0x12345678_abcd_ef01_2345_6789abcdef01);
+        assert_eq!(CigGuid::from_u128(guid.to_u128()), guid);
+        assert_eq!(guid.to_uuid_bytes()[..4], [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(CigGuid::from_uuid_bytes(guid.to_uuid_bytes()), guid);
+        assert_eq!(guid.version(), 0xe);
+    }
+
+    #[test]
+    fn test_ordering_matches_string() {
+        let mut guids: Vec<CigGuid> = (0..32).map(|_| CigGuid::random()).collect();
+        guids.sort();
+        let strings: Vec<String> = guids.iter().map(|g| g.to_string()).collect();
+        let mut sorted = strings.clone();
+        sorted.sort();
+        assert_eq!(strings, sorted);
+    }
+
+    #[test]
+    fn test_new_v4() {
+        let guid = CigGuid::new_v4();
+        let s = guid.to_string();
+        assert_eq!(guid.version(), 4);
+        assert_eq!(&s[14..15], "4");
+        assert!(matches!(&s[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid_conversion() {
+        let original = "12345678-abcd-ef01-2345-6789abcdef01";
+        let guid: CigGuid = original.parse().unwrap();
+        let uuid = uuid::Uuid::from(guid);
+        assert_eq!(uuid.to_string(), original);
+        assert_eq!(CigGuid::from(uuid), guid);
     }
 }
@@ -3,21 +3,26 @@
 //! This crate provides foundational types and utilities used across all Svarog crates:
 //!
 //! - [`BinaryReader`] - Zero-copy binary reading from byte slices
+//! - [`BinaryWriter`] - Little-endian binary writing with patchable slots
 //! - [`CigGuid`] - Star Citizen's custom GUID format
-//! - [`crc`] - CRC32C hashing utilities
+//! - [`crc`] - CRC32C hashing utilities, one-shot or streaming
+//! - [`arena`] - String arena with an optional memory budget
 //! - [`simd`] - SIMD-accelerated operations (AVX2, SSE2, NEON)
 //! - Color types and other common structures
 
 mod error;
 mod guid;
 mod reader;
+mod writer;
 
+pub mod arena;
 pub mod crc;
 pub mod simd;
 
-pub use error::{Error, Result};
+pub use error::{Error, ErrorLocation, Result, ResultExt};
 pub use guid::CigGuid;
 pub use reader::BinaryReader;
+pub use writer::{BinaryWriter, Slot};
 
 /// Re-export zerocopy traits for convenience
 pub use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
@@ -92,6 +92,7 @@ impl<'a> BinaryReader<'a> {
     pub fn peek_bytes(&self, count: usize) -> Result<&'a [u8]> {
         if self.remaining() < count {
             return Err(Error::UnexpectedEof {
+                offset: self.position,
                 needed: count,
                 available: self.remaining(),
             });
@@ -195,25 +196,31 @@ impl<'a> BinaryReader<'a> {
         let null_pos = remaining
             .iter()
             .position(|&b| b == 0)
-            .ok_or_else(|| Error::MissingNullTerminator)?;
+            .ok_or(Error::MissingNullTerminator { offset: start })?;
 
         let string_bytes = &remaining[..null_pos];
         self.position = start + null_pos + 1; // Skip the null terminator
 
-        std::str::from_utf8(string_bytes).map_err(Error::Utf8)
+        std::str::from_utf8(string_bytes).map_err(|source| Error::InvalidString {
+            offset: start,
+            source,
+        })
     }
 
     /// Read a string of a specific length.
     pub fn read_string(&mut self, length: usize) -> Result<&'a str> {
+        let offset = self.position;
         let bytes = self.read_bytes(length)?;
-        std::str::from_utf8(bytes).map_err(Error::Utf8)
+        std::str::from_utf8(bytes).map_err(|source| Error::InvalidString { offset, source })
     }
 
     /// Read a string from a fixed-size buffer, stopping at the first null.
     pub fn read_string_in_buffer(&mut self, buffer_size: usize) -> Result<&'a str> {
+        let offset = self.position;
         let bytes = self.read_bytes(buffer_size)?;
         let null_pos = bytes.iter().position(|&b| b == 0).unwrap_or(buffer_size);
-        std::str::from_utf8(&bytes[..null_pos]).map_err(Error::Utf8)
+        std::str::from_utf8(&bytes[..null_pos])
+            .map_err(|source| Error::InvalidString { offset, source })
     }
 
     /// Read a struct using zerocopy.
@@ -221,9 +228,11 @@ impl<'a> BinaryReader<'a> {
     /// The struct must implement `FromBytes` from the zerocopy crate.
     #[inline]
     pub fn read_struct<T: FromBytes>(&mut self) -> Result<T> {
+        let offset = self.position;
         let size = std::mem::size_of::<T>();
         let bytes = self.read_bytes(size)?;
         T::read_from_bytes(bytes).map_err(|_| Error::UnexpectedEof {
+            offset,
             needed: size,
             available: bytes.len(),
         })
@@ -238,9 +247,11 @@ impl<'a> BinaryReader<'a> {
 
     /// Expect a specific value or return an error.
     pub fn expect<T: PartialEq + std::fmt::Debug + FromBytes>(&mut self, expected: T) -> Result<()> {
+        let offset = self.position;
         let actual = self.read_struct::<T>()?;
         if actual != expected {
             return Err(Error::ExpectedValue {
+                offset,
                 expected: format!("{:?}", expected),
                 actual: format!("{:?}", actual),
             });
@@ -250,9 +261,11 @@ impl<'a> BinaryReader<'a> {
 
     /// Expect specific magic bytes.
     pub fn expect_magic(&mut self, expected: &[u8]) -> Result<()> {
+        let offset = self.position;
         let actual = self.read_bytes(expected.len())?;
         if actual != expected {
             return Err(Error::InvalidMagic {
+                offset,
                 expected: expected.to_vec(),
                 actual: actual.to_vec(),
             });
@@ -338,5 +351,15 @@ mod tests {
         let mut reader = BinaryReader::new(&data);
 
         assert!(reader.read_u32().is_err());
+
+        reader.advance(1);
+        match reader.read_u16() {
+            Err(Error::UnexpectedEof {
+                offset,
+                needed,
+                available,
+            }) => assert_eq!((offset, needed, available), (1, 2, 1)),
+            other => panic!("unexpected result: {other:?}"),
+        }
     }
 }
@@ -0,0 +1,340 @@
+//! Binary writer for building little-endian byte buffers.
+//!
+//! This module provides [`BinaryWriter`], the counterpart of
+//! [`BinaryReader`](crate::BinaryReader): typed writes that append to a
+//! growable buffer, with slots that can be patched once a later value
+//! (a length, an offset) is known.
+
+use zerocopy::{Immutable, IntoBytes};
+
+/// A placeholder written by [`BinaryWriter::reserve_u32`] or
+/// [`BinaryWriter::reserve_u64`], to be filled in later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use = "a reserved slot should be patched"]
+pub struct Slot {
+    position: usize,
+    size: usize,
+}
+
+impl Slot {
+    /// Position of the slot in the buffer.
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Position right after the slot.
+    #[inline]
+    pub const fn end(&self) -> usize {
+        self.position + self.size
+    }
+}
+
+/// A binary writer that appends little-endian values to a byte buffer.
+///
+/// # Example
+///
+/// ```
+/// use svarog_common::BinaryWriter;
+///
+/// let mut writer = BinaryWriter::new();
+/// writer.write_u16(0x0201);
+/// let length = writer.reserve_u32();
+/// writer.write_cstring("hi");
+/// writer.patch_length(length);
+///
+/// assert_eq!(writer.as_bytes(), [0x01, 0x02, 3, 0, 0, 0, b'h', b'i', 0]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BinaryWriter {
+    data: Vec<u8>,
+}
+
+impl BinaryWriter {
+    /// Create an empty writer.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Create an empty writer with room for `capacity` bytes.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Get the current position, i.e. the number of bytes written.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Get the number of bytes written.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if nothing has been written yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Get the bytes written so far.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the writer and return the buffer.
+    #[inline]
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Write raw bytes.
+    #[inline]
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Write `count` zero bytes.
+    #[inline]
+    pub fn write_zeros(&mut self, count: usize) {
+        self.data.resize(self.data.len() + count, 0);
+    }
+
+    /// Write a single byte.
+    #[inline]
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    /// Write a signed byte.
+    #[inline]
+    pub fn write_i8(&mut self, value: i8) {
+        self.data.push(value as u8);
+    }
+
+    /// Write a boolean as one byte (0 or 1).
+    #[inline]
+    pub fn write_bool(&mut self, value: bool) {
+        self.data.push(value as u8);
+    }
+
+    /// Write a little-endian u16.
+    #[inline]
+    pub fn write_u16(&mut self, value: u16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian i16.
+    #[inline]
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian u32.
+    #[inline]
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian i32.
+    #[inline]
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian u64.
+    #[inline]
+    pub fn write_u64(&mut self, value: u64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian i64.
+    #[inline]
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian f32.
+    #[inline]
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a little-endian f64.
+    #[inline]
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Write a string followed by a null terminator.
+    pub fn write_cstring(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+        self.write_u8(0);
+    }
+
+    /// Write a string into a fixed-size, null-padded buffer, truncating it
+    /// if it does not fit.
+    pub fn write_string_in_buffer(&mut self, value: &str, buffer_size: usize) {
+        let bytes = &value.as_bytes()[..value.len().min(buffer_size)];
+        self.write_bytes(bytes);
+        self.write_zeros(buffer_size - bytes.len());
+    }
+
+    /// Write a struct using zerocopy.
+    #[inline]
+    pub fn write_struct<T: IntoBytes + Immutable>(&mut self, value: &T) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    /// Write a slice of structs using zerocopy.
+    #[inline]
+    pub fn write_slice<T: IntoBytes + Immutable>(&mut self, values: &[T]) {
+        self.write_bytes(values.as_bytes());
+    }
+
+    /// Pad with zeros up to the next multiple of `alignment`.
+    pub fn align(&mut self, alignment: usize) {
+        let padding = self.padding_to(alignment);
+        self.write_zeros(padding);
+    }
+
+    /// Number of bytes needed to reach the next multiple of `alignment`.
+    #[inline]
+    pub fn padding_to(&self, alignment: usize) -> usize {
+        self.data.len().next_multiple_of(alignment.max(1)) - self.data.len()
+    }
+
+    /// Write a zero u32 to be patched later.
+    pub fn reserve_u32(&mut self) -> Slot {
+        let slot = Slot {
+            position: self.position(),
+            size: 4,
+        };
+        self.write_u32(0);
+        slot
+    }
+
+    /// Write a zero u64 to be patched later.
+    pub fn reserve_u64(&mut self) -> Slot {
+        let slot = Slot {
+            position: self.position(),
+            size: 8,
+        };
+        self.write_u64(0);
+        slot
+    }
+
+    /// Fill a slot reserved with [`reserve_u32`](Self::reserve_u32).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot was reserved as a u64.
+    pub fn patch_u32(&mut self, slot: Slot, value: u32) {
+        assert_eq!(slot.size, 4, "slot is not a u32");
+        self.data[slot.position..slot.end()].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Fill a slot reserved with [`reserve_u64`](Self::reserve_u64).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot was reserved as a u32.
+    pub fn patch_u64(&mut self, slot: Slot, value: u64) {
+        assert_eq!(slot.size, 8, "slot is not a u64");
+        self.data[slot.position..slot.end()].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Fill a slot with the number of bytes written after it.
+    pub fn patch_length(&mut self, slot: Slot) {
+        let length = self.position() - slot.end();
+        match slot.size {
+            4 => self.patch_u32(slot, length as u32),
+            _ => self.patch_u64(slot, length as u64),
+        }
+    }
+
+    /// Fill a slot with the current position, e.g. the offset of the data
+    /// about to be written.
+    pub fn patch_offset(&mut self, slot: Slot) {
+        let position = self.position();
+        match slot.size {
+            4 => self.patch_u32(slot, position as u32),
+            _ => self.patch_u64(slot, position as u64),
+        }
+    }
+}
+
+impl std::io::Write for BinaryWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_bytes(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl From<BinaryWriter> for Vec<u8> {
+    fn from(writer: BinaryWriter) -> Self {
+        writer.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryReader;
+
+    #[test]
+    fn test_write_primitives() {
+        let mut writer = BinaryWriter::new();
+        writer.write_u32(0x04030201);
+        writer.write_i16(-2);
+        writer.write_bool(true);
+        writer.write_f32(1.5);
+        writer.write_cstring("hello");
+
+        let data = writer.into_inner();
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(reader.read_u32().unwrap(), 0x04030201);
+        assert_eq!(reader.read_i16().unwrap(), -2);
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(reader.read_f32().unwrap(), 1.5);
+        assert_eq!(reader.read_cstring().unwrap(), "hello");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_slots() {
+        let mut writer = BinaryWriter::new();
+        let offset = writer.reserve_u32();
+        let length = writer.reserve_u64();
+        writer.write_u8(7);
+        writer.align(4);
+        writer.patch_offset(offset);
+        writer.write_bytes(b"abc");
+        writer.patch_length(length);
+
+        assert_eq!(writer.len(), 19);
+        let mut reader = BinaryReader::new(writer.as_bytes());
+        assert_eq!(reader.read_u32().unwrap(), 16);
+        assert_eq!(reader.read_u64().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_string_in_buffer() {
+        let mut writer = BinaryWriter::new();
+        writer.write_string_in_buffer("abc", 5);
+        writer.write_string_in_buffer("abcdef", 4);
+        assert_eq!(writer.as_bytes(), b"abc\0\0abcd");
+        assert_eq!(writer.padding_to(8), 7);
+    }
+}
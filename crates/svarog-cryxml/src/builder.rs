@@ -5,10 +5,12 @@
 
 use std::collections::HashMap;
 
+use svarog_common::BinaryWriter;
+
 use crate::{CryXmlAttribute, CryXmlHeader, CryXmlNode, Error, Result};
 
 /// A node being built, before final serialization.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BuilderNode {
     /// Tag name of the element.
     pub tag: String,
@@ -21,6 +23,9 @@ pub struct BuilderNode {
 }
 
 impl BuilderNode {
+    /// Tag of nodes holding an XML comment in their content.
+    pub const COMMENT_TAG: &'static str = "!--";
+
     /// Create a new builder node with the given tag name.
     pub fn new(tag: impl Into<String>) -> Self {
         Self {
@@ -31,6 +36,26 @@ impl BuilderNode {
         }
     }
 
+    /// Create a node that is written as `<!--text-->` in XML output.
+    pub fn comment(text: impl Into<String>) -> Self {
+        Self::new(Self::COMMENT_TAG).content(text)
+    }
+
+    /// Create a node that is written as `<?target data?>` in XML output.
+    pub fn processing_instruction(target: &str, data: impl Into<String>) -> Self {
+        Self::new(format!("?{}", target)).content(data)
+    }
+
+    /// Whether this node holds a comment.
+    pub fn is_comment(&self) -> bool {
+        self.tag == Self::COMMENT_TAG
+    }
+
+    /// The target of a processing instruction node.
+    pub fn processing_instruction_target(&self) -> Option<&str> {
+        self.tag.strip_prefix('?')
+    }
+
     /// Set the text content of this node.
     pub fn content(mut self, content: impl Into<String>) -> Self {
         self.content = content.into();
@@ -54,6 +79,77 @@ impl BuilderNode {
         self.children.extend(children);
         self
     }
+
+    /// Get the value of an attribute.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Set an attribute, replacing its value if it exists or appending it otherwise.
+    pub fn set_attribute(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.attributes.push((key, value)),
+        }
+    }
+
+    /// Remove an attribute, returning its value.
+    pub fn remove_attribute(&mut self, key: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|(k, _)| k == key)?;
+        Some(self.attributes.remove(index).1)
+    }
+
+    /// Get the first child with the given tag.
+    pub fn find_child(&self, tag: &str) -> Option<&BuilderNode> {
+        self.children.iter().find(|c| c.tag == tag)
+    }
+
+    /// Get the first child with the given tag, for editing.
+    pub fn find_child_mut(&mut self, tag: &str) -> Option<&mut BuilderNode> {
+        self.children.iter_mut().find(|c| c.tag == tag)
+    }
+
+    /// Iterate over the children with the given tag.
+    pub fn children_named<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a BuilderNode> + 'a {
+        self.children.iter().filter(move |c| c.tag == tag)
+    }
+
+    /// Iterate over the children with the given tag, for editing.
+    pub fn children_named_mut<'a>(
+        &'a mut self,
+        tag: &'a str,
+    ) -> impl Iterator<Item = &'a mut BuilderNode> + 'a {
+        self.children.iter_mut().filter(move |c| c.tag == tag)
+    }
+
+    /// Remove all children matching a predicate, returning how many were removed.
+    pub fn remove_children(&mut self, mut predicate: impl FnMut(&BuilderNode) -> bool) -> usize {
+        let before = self.children.len();
+        self.children.retain(|c| !predicate(c));
+        before - self.children.len()
+    }
+}
+
+/// The XML event for a comment or processing instruction marker node, or
+/// `None` for regular elements.
+#[cfg(feature = "xml-output")]
+pub(crate) fn markup_event<'a>(tag: &'a str, content: &'a str) -> Option<quick_xml::events::Event<'a>> {
+    use quick_xml::events::{BytesPI, BytesText, Event};
+
+    if tag == BuilderNode::COMMENT_TAG {
+        return Some(Event::Comment(BytesText::from_escaped(content)));
+    }
+    let target = tag.strip_prefix('?')?;
+    Some(Event::PI(if content.is_empty() {
+        BytesPI::new(target)
+    } else {
+        BytesPI::new(format!("{} {}", target, content))
+    }))
 }
 
 /// Builder for constructing CryXmlB documents.
@@ -84,6 +180,16 @@ impl CryXmlBuilder {
         Self { root }
     }
 
+    /// The root node.
+    pub fn root(&self) -> &BuilderNode {
+        &self.root
+    }
+
+    /// Consume the builder and return its root node.
+    pub fn into_root(self) -> BuilderNode {
+        self.root
+    }
+
     /// Build the CryXmlB binary representation.
     pub fn build(&self) -> Result<Vec<u8>> {
         // Step 1: Collect all unique strings and build string table
@@ -136,33 +242,19 @@ impl CryXmlBuilder {
 
         // Step 5: Write everything
         let total_size = magic_size + xml_size;
-        let mut output = Vec::with_capacity(total_size as usize);
-
-        // Magic
-        output.extend_from_slice(CryXmlHeader::MAGIC);
-
-        // Header
-        output.extend_from_slice(zerocopy::IntoBytes::as_bytes(&header));
-
-        // Nodes
-        for node in &nodes {
-            output.extend_from_slice(zerocopy::IntoBytes::as_bytes(node));
-        }
+        let mut output = BinaryWriter::with_capacity(total_size as usize);
 
+        output.write_bytes(CryXmlHeader::MAGIC);
+        output.write_struct(&header);
+        output.write_slice(&nodes);
         // Child indices (before attributes to match game file layout)
-        for idx in &child_indices {
-            output.extend_from_slice(&idx.to_le_bytes());
+        for &idx in &child_indices {
+            output.write_i32(idx);
         }
+        output.write_slice(&attributes);
+        output.write_bytes(&string_data);
 
-        // Attributes
-        for attr in &attributes {
-            output.extend_from_slice(zerocopy::IntoBytes::as_bytes(attr));
-        }
-
-        // String data
-        output.extend_from_slice(&string_data);
-
-        Ok(output)
+        Ok(output.into_inner())
     }
 
     /// Recursively collect all strings from the tree.
@@ -282,12 +374,11 @@ impl StringTable {
 
     /// Convert the string table to bytes.
     fn into_bytes(self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(self.current_offset as usize);
-        for s in self.strings {
-            bytes.extend_from_slice(s.as_bytes());
-            bytes.push(0); // Null terminator
+        let mut bytes = BinaryWriter::with_capacity(self.current_offset as usize);
+        for s in &self.strings {
+            bytes.write_cstring(s);
         }
-        bytes
+        bytes.into_inner()
     }
 }
 
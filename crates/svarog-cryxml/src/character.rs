@@ -0,0 +1,419 @@
+//! Typed models of character definition (`.cdf`) and character parameter
+//! (`.chrparams`) files.
+//!
+//! A [`CharacterDefinition`] lists the base skeleton and the attachments
+//! (skins, bones, proxies) that make up a character. [`ChrParams`] holds the
+//! animation list and per-LOD joint lists of a skeleton. Together with the
+//! head data from `svarog-chf` they describe a complete character.
+//!
+//! As with [`Material`](crate::material::Material), unknown attributes and
+//! elements are kept so files can be edited and written back.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::character::CharacterDefinition;
+//!
+//! let cdf = CharacterDefinition::parse(&std::fs::read("pilot.cdf")?)?;
+//! if let Some(model) = &cdf.model {
+//!     println!("skeleton: {}", model.file);
+//! }
+//! for attachment in &cdf.attachments {
+//!     println!("{} [{}] -> {}", attachment.name, attachment.attachment_type, attachment.binding);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::builder::BuilderNode;
+use crate::material::{format_floats, Attributes};
+use crate::{CryXmlDocument, Error, Result};
+
+/// A character definition (`<CharacterDefinition>`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CharacterDefinition {
+    /// Base skeleton (`Model`).
+    pub model: Option<CharacterModel>,
+    /// Attachments (`AttachmentList/Attachment`).
+    pub attachments: Vec<Attachment>,
+    /// Child elements not covered by the fields above, e.g. `ShapeDeformation`.
+    pub extra_children: Vec<BuilderNode>,
+}
+
+/// The base skeleton of a character.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CharacterModel {
+    /// Skeleton file (`File`), usually a `.chr`.
+    pub file: String,
+    /// Material override (`Material`).
+    pub material: Option<String>,
+    /// Attributes not covered by the fields above.
+    pub extra_attributes: Vec<(String, String)>,
+}
+
+/// An attachment of a character.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attachment {
+    /// Attachment name (`AName`).
+    pub name: String,
+    /// Attachment type (`Type`), e.g. `CA_SKIN`, `CA_BONE` or `CA_PROX`.
+    pub attachment_type: String,
+    /// Attached geometry (`Binding`), e.g. a `.skin` or `.cgf` file.
+    pub binding: String,
+    /// Material override (`Material`).
+    pub material: Option<String>,
+    /// Bone the attachment follows (`BoneName`).
+    pub bone_name: Option<String>,
+    /// Attachment flags (`Flags`).
+    pub flags: u32,
+    /// Position relative to the bone (`Position`).
+    pub position: Option<[f32; 3]>,
+    /// Rotation quaternion relative to the bone, `w,x,y,z` (`Rotation`).
+    pub rotation: Option<[f32; 4]>,
+    /// Attributes not covered by the fields above.
+    pub extra_attributes: Vec<(String, String)>,
+    /// Child elements, e.g. `SimulationParams`.
+    pub children: Vec<BuilderNode>,
+}
+
+/// Character parameters of a skeleton (`<Params>`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChrParams {
+    /// Animation list entries (`AnimationList/Animation`), in file order.
+    pub animations: Vec<AnimationEntry>,
+    /// Joints per LOD (`Lod/JointList`).
+    pub lods: Vec<LodJointList>,
+    /// Child elements not covered by the fields above, e.g. `IK_Definition`.
+    pub extra_children: Vec<BuilderNode>,
+}
+
+/// An entry of a `.chrparams` animation list.
+///
+/// Names starting with `#` or `$` are directives rather than animations:
+/// `#filepath` sets the base folder, `$AnimEventDatabase` and
+/// `$TracksDatabase` reference databases.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimationEntry {
+    /// Animation name or directive (`name`).
+    pub name: String,
+    /// Path, relative to `#filepath` for animations (`path`).
+    pub path: String,
+    /// Attributes not covered by the fields above.
+    pub extra_attributes: Vec<(String, String)>,
+}
+
+/// Joints kept at one level of detail.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LodJointList {
+    /// LOD level (`lod`).
+    pub lod: u32,
+    /// Joint names (`Joint` `name`).
+    pub joints: Vec<String>,
+}
+
+impl CharacterDefinition {
+    /// Parse a character definition from CryXmlB or XML bytes.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        Self::from_node(CryXmlDocument::parse(data)?.root())
+    }
+
+    /// Read a character definition from a `<CharacterDefinition>` element.
+    pub fn from_node(node: &BuilderNode) -> Result<Self> {
+        expect_tag(node, "CharacterDefinition")?;
+
+        let mut cdf = Self::default();
+        for child in &node.children {
+            match child.tag.as_str() {
+                "Model" if cdf.model.is_none() => {
+                    let mut attrs = Attributes(child.attributes.clone());
+                    cdf.model = Some(CharacterModel {
+                        file: attrs.take("File").unwrap_or_default(),
+                        material: attrs.take("Material"),
+                        extra_attributes: attrs.0,
+                    });
+                }
+                "AttachmentList" => cdf.attachments.extend(
+                    child
+                        .children_named("Attachment")
+                        .map(Attachment::from_node),
+                ),
+                _ => cdf.extra_children.push(child.clone()),
+            }
+        }
+        Ok(cdf)
+    }
+
+    /// Convert back to a `<CharacterDefinition>` element.
+    pub fn to_node(&self) -> BuilderNode {
+        let mut node = BuilderNode::new("CharacterDefinition");
+        if let Some(model) = &self.model {
+            let mut attrs = Attributes(Vec::new());
+            attrs.put("File", Some(model.file.clone()));
+            attrs.put("Material", model.material.clone());
+            attrs.0.extend(model.extra_attributes.iter().cloned());
+
+            let mut model_node = BuilderNode::new("Model");
+            model_node.attributes = attrs.0;
+            node.children.push(model_node);
+        }
+        if !self.attachments.is_empty() {
+            node.children.push(
+                BuilderNode::new("AttachmentList")
+                    .children(self.attachments.iter().map(Attachment::to_node)),
+            );
+        }
+        node.children.extend(self.extra_children.iter().cloned());
+        node
+    }
+
+    /// Serialize to CryXmlB bytes.
+    pub fn to_cryxml(&self) -> Result<Vec<u8>> {
+        CryXmlDocument::new(self.to_node()).to_cryxml()
+    }
+
+    /// Get an attachment by name.
+    pub fn attachment(&self, name: &str) -> Option<&Attachment> {
+        self.attachments.iter().find(|a| a.name == name)
+    }
+
+    /// Iterate over the attachments of a type, e.g. `CA_SKIN`.
+    pub fn attachments_of_type<'a>(
+        &'a self,
+        attachment_type: &'a str,
+    ) -> impl Iterator<Item = &'a Attachment> + 'a {
+        self.attachments
+            .iter()
+            .filter(move |a| a.attachment_type == attachment_type)
+    }
+}
+
+impl Attachment {
+    fn from_node(node: &BuilderNode) -> Self {
+        let mut attrs = Attributes(node.attributes.clone());
+        Self {
+            name: attrs.take("AName").unwrap_or_default(),
+            attachment_type: attrs.take("Type").unwrap_or_default(),
+            binding: attrs.take("Binding").unwrap_or_default(),
+            material: attrs.take("Material"),
+            bone_name: attrs.take("BoneName"),
+            flags: attrs.take_parsed("Flags").unwrap_or(0),
+            position: attrs.take_floats("Position"),
+            rotation: attrs.take_floats("Rotation"),
+            extra_attributes: attrs.0,
+            children: node.children.clone(),
+        }
+    }
+
+    fn to_node(&self) -> BuilderNode {
+        let mut attrs = Attributes(Vec::new());
+        attrs.put("Type", Some(self.attachment_type.clone()));
+        attrs.put("AName", Some(self.name.clone()));
+        attrs.put("Binding", Some(self.binding.clone()));
+        attrs.put("Material", self.material.clone());
+        attrs.put("BoneName", self.bone_name.clone());
+        attrs.put("Flags", Some(self.flags.to_string()));
+        attrs.put("Position", self.position.map(format_floats));
+        attrs.put("Rotation", self.rotation.map(format_floats));
+        attrs.0.extend(self.extra_attributes.iter().cloned());
+
+        let mut node = BuilderNode::new("Attachment").children(self.children.iter().cloned());
+        node.attributes = attrs.0;
+        node
+    }
+}
+
+impl ChrParams {
+    /// Parse character parameters from CryXmlB or XML bytes.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        Self::from_node(CryXmlDocument::parse(data)?.root())
+    }
+
+    /// Read character parameters from a `<Params>` element.
+    pub fn from_node(node: &BuilderNode) -> Result<Self> {
+        expect_tag(node, "Params")?;
+
+        let mut params = Self::default();
+        for child in &node.children {
+            match child.tag.as_str() {
+                "AnimationList" => {
+                    for animation in child.children_named("Animation") {
+                        let mut attrs = Attributes(animation.attributes.clone());
+                        params.animations.push(AnimationEntry {
+                            name: attrs.take("name").unwrap_or_default(),
+                            path: attrs.take("path").unwrap_or_default(),
+                            extra_attributes: attrs.0,
+                        });
+                    }
+                }
+                "Lod" => {
+                    for list in child.children_named("JointList") {
+                        params.lods.push(LodJointList {
+                            lod: list
+                                .attribute("lod")
+                                .and_then(|l| l.parse().ok())
+                                .unwrap_or(0),
+                            joints: list
+                                .children_named("Joint")
+                                .filter_map(|j| j.attribute("name"))
+                                .map(str::to_string)
+                                .collect(),
+                        });
+                    }
+                }
+                _ => params.extra_children.push(child.clone()),
+            }
+        }
+        Ok(params)
+    }
+
+    /// Convert back to a `<Params>` element.
+    pub fn to_node(&self) -> BuilderNode {
+        let mut node = BuilderNode::new("Params");
+        if !self.lods.is_empty() {
+            node.children.push(
+                BuilderNode::new("Lod").children(self.lods.iter().map(|list| {
+                    BuilderNode::new("JointList")
+                        .attr("lod", list.lod.to_string())
+                        .children(
+                            list.joints.iter().map(|joint| {
+                                BuilderNode::new("Joint").attr("name", joint.as_str())
+                            }),
+                        )
+                })),
+            );
+        }
+        node.children.extend(self.extra_children.iter().cloned());
+        if !self.animations.is_empty() {
+            node.children
+                .push(
+                    BuilderNode::new("AnimationList").children(self.animations.iter().map(
+                        |entry| {
+                            let mut animation = BuilderNode::new("Animation")
+                                .attr("name", entry.name.as_str())
+                                .attr("path", entry.path.as_str());
+                            animation
+                                .attributes
+                                .extend(entry.extra_attributes.iter().cloned());
+                            animation
+                        },
+                    )),
+                );
+        }
+        node
+    }
+
+    /// Serialize to CryXmlB bytes.
+    pub fn to_cryxml(&self) -> Result<Vec<u8>> {
+        CryXmlDocument::new(self.to_node()).to_cryxml()
+    }
+
+    /// Base folder of the animations (`#filepath`).
+    pub fn file_path(&self) -> Option<&str> {
+        self.directive("#filepath")
+    }
+
+    /// Animation event database (`$AnimEventDatabase`).
+    pub fn anim_event_database(&self) -> Option<&str> {
+        self.directive("$AnimEventDatabase")
+    }
+
+    /// Animation tracks database (`$TracksDatabase`).
+    pub fn tracks_database(&self) -> Option<&str> {
+        self.directive("$TracksDatabase")
+    }
+
+    /// Iterate over the animation entries that are not directives.
+    pub fn animation_entries(&self) -> impl Iterator<Item = &AnimationEntry> {
+        self.animations
+            .iter()
+            .filter(|a| !a.name.starts_with('#') && !a.name.starts_with('$'))
+    }
+
+    fn directive(&self, name: &str) -> Option<&str> {
+        self.animations
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.path.as_str())
+    }
+}
+
+fn expect_tag(node: &BuilderNode, tag: &str) -> Result<()> {
+    if node.tag == tag {
+        Ok(())
+    } else {
+        Err(Error::Xml(format!(
+            "expected <{}> element, found <{}>",
+            tag, node.tag
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CDF: &str = r#"<CharacterDefinition>
+  <Model File="objects/characters/human/male_v7/body.chr" Material="objects/characters/human/body.mtl"/>
+  <AttachmentList>
+    <Attachment Type="CA_SKIN" AName="head" Binding="objects/characters/head.skin" Flags="0" SkinJointsOverride="1"/>
+    <Attachment Type="CA_BONE" AName="helmet" Binding="objects/helmet.cgf" BoneName="Head" Flags="2" Position="0,0.1,0" Rotation="1,0,0,0"/>
+  </AttachmentList>
+  <ShapeDeformation COL0="0"/>
+</CharacterDefinition>"#;
+
+    const CHRPARAMS: &str = r##"<Params>
+  <Lod>
+    <JointList lod="1">
+      <Joint name="Hips"/>
+      <Joint name="Spine"/>
+    </JointList>
+  </Lod>
+  <AnimationList>
+    <Animation name="#filepath" path="animations/human/male"/>
+    <Animation name="$AnimEventDatabase" path="animations/human/male.animevents"/>
+    <Animation name="*" path="*/*.caf"/>
+  </AnimationList>
+</Params>"##;
+
+    #[test]
+    fn test_character_definition() {
+        let cdf = CharacterDefinition::parse(CDF.as_bytes()).unwrap();
+        assert!(cdf.model.as_ref().unwrap().file.ends_with("body.chr"));
+        assert_eq!(cdf.attachments_of_type("CA_SKIN").count(), 1);
+
+        let helmet = cdf.attachment("helmet").unwrap();
+        assert_eq!(helmet.bone_name.as_deref(), Some("Head"));
+        assert_eq!(helmet.position, Some([0.0, 0.1, 0.0]));
+        assert_eq!(helmet.rotation, Some([1.0, 0.0, 0.0, 0.0]));
+        assert_eq!(
+            cdf.attachment("head").unwrap().extra_attributes,
+            [("SkinJointsOverride".into(), "1".into())]
+        );
+        assert_eq!(cdf.extra_children[0].tag, "ShapeDeformation");
+
+        let reparsed = CharacterDefinition::parse(&cdf.to_cryxml().unwrap()).unwrap();
+        assert_eq!(reparsed, cdf);
+    }
+
+    #[test]
+    fn test_chrparams() {
+        let params = ChrParams::parse(CHRPARAMS.as_bytes()).unwrap();
+        assert_eq!(params.file_path(), Some("animations/human/male"));
+        assert_eq!(
+            params.anim_event_database(),
+            Some("animations/human/male.animevents")
+        );
+        assert_eq!(params.tracks_database(), None);
+        assert_eq!(params.animation_entries().count(), 1);
+        assert_eq!(params.lods[0].lod, 1);
+        assert_eq!(params.lods[0].joints, ["Hips", "Spine"]);
+
+        let reparsed = ChrParams::parse(&params.to_cryxml().unwrap()).unwrap();
+        assert_eq!(reparsed, params);
+    }
+
+    #[test]
+    fn test_rejects_other_roots() {
+        assert!(ChrParams::parse(CDF.as_bytes()).is_err());
+        assert!(CharacterDefinition::parse(CHRPARAMS.as_bytes()).is_err());
+    }
+}
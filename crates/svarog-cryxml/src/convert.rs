@@ -0,0 +1,250 @@
+//! Parallel batch conversion of CryXmlB files to XML text.
+//!
+//! [`convert_tree`] walks a directory, detects CryXmlB files by their magic
+//! bytes and converts them on the rayon thread pool, either in place or into
+//! a mirrored output directory. Every visited file gets a [`ConvertResult`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::{convert_tree, ConvertOptions};
+//!
+//! let options = ConvertOptions::new()
+//!     .output_dir("converted/")
+//!     .extensions(&["xml", "mtl", "cdf", "chrparams"]);
+//! let report = convert_tree("extracted/", &options)?;
+//! println!("{} converted, {} failed", report.converted(), report.failed());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::{CryXml, CryXmlHeader, Result};
+
+/// Options for [`convert_tree`].
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    output_dir: Option<PathBuf>,
+    extensions: Option<Vec<String>>,
+}
+
+impl ConvertOptions {
+    /// Create options that convert every CryXmlB file in place.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write converted files below `dir`, mirroring the input layout, instead
+    /// of overwriting them.
+    ///
+    /// Files that are not CryXmlB are not copied.
+    pub fn output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    /// Only look at files with these extensions (case-insensitive, without
+    /// the dot). By default every file is checked.
+    pub fn extensions(mut self, extensions: &[&str]) -> Self {
+        self.extensions = Some(extensions.iter().map(|e| e.to_ascii_lowercase()).collect());
+        self
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        let Some(extensions) = &self.extensions else {
+            return true;
+        };
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+    }
+}
+
+/// What happened to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertStatus {
+    /// The file was CryXmlB and was written as XML.
+    Converted,
+    /// The file is not CryXmlB and was left alone.
+    NotCryXml,
+}
+
+/// Outcome of one file.
+#[derive(Debug)]
+pub struct ConvertResult {
+    /// Input file.
+    pub path: PathBuf,
+    /// Where the XML was (or would have been) written.
+    pub output: PathBuf,
+    /// Status, or the error that stopped the conversion.
+    pub status: Result<ConvertStatus>,
+}
+
+/// Results of a [`convert_tree`] run, in no particular order.
+#[derive(Debug, Default)]
+pub struct ConvertReport {
+    /// One entry per visited file.
+    pub results: Vec<ConvertResult>,
+}
+
+impl ConvertReport {
+    /// Number of files converted.
+    pub fn converted(&self) -> usize {
+        self.count(|s| matches!(s, Ok(ConvertStatus::Converted)))
+    }
+
+    /// Number of files that are not CryXmlB.
+    pub fn not_cryxml(&self) -> usize {
+        self.count(|s| matches!(s, Ok(ConvertStatus::NotCryXml)))
+    }
+
+    /// Number of files that failed to convert.
+    pub fn failed(&self) -> usize {
+        self.count(|s| s.is_err())
+    }
+
+    /// Iterate over the failed files.
+    pub fn failures(&self) -> impl Iterator<Item = &ConvertResult> {
+        self.results.iter().filter(|r| r.status.is_err())
+    }
+
+    fn count(&self, f: impl Fn(&Result<ConvertStatus>) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.status)).count()
+    }
+}
+
+/// Convert all CryXmlB files below `dir` to XML text in parallel.
+pub fn convert_tree(dir: impl AsRef<Path>, options: &ConvertOptions) -> Result<ConvertReport> {
+    convert_tree_with(dir, options, |_| {})
+}
+
+/// Like [`convert_tree`], calling `on_file` from the worker threads as each
+/// file finishes, e.g. to advance a progress bar.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "cryxml_convert", skip_all, fields(dir = %dir.as_ref().display()))
+)]
+pub fn convert_tree_with<F>(
+    dir: impl AsRef<Path>,
+    options: &ConvertOptions,
+    on_file: F,
+) -> Result<ConvertReport>
+where
+    F: Fn(&ConvertResult) + Sync,
+{
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
+    let dir = dir.as_ref();
+    let mut files = Vec::new();
+    collect_files(dir, options, &mut files)?;
+
+    let results = files
+        .into_par_iter()
+        .map(|path| {
+            let output = match &options.output_dir {
+                Some(out) => out.join(path.strip_prefix(dir).unwrap_or(&path)),
+                None => path.clone(),
+            };
+            let status = convert_file(&path, &output);
+            let result = ConvertResult {
+                path,
+                output,
+                status,
+            };
+            on_file(&result);
+            result
+        })
+        .collect();
+
+    let report = ConvertReport { results };
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        converted = report.converted(),
+        not_cryxml = report.not_cryxml(),
+        failed = report.failed(),
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "converted CryXmlB tree"
+    );
+    Ok(report)
+}
+
+/// Convert one file if it starts with the CryXmlB magic.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(path = %path.display()))
+)]
+pub fn convert_file(path: &Path, output: &Path) -> Result<ConvertStatus> {
+    // Check the magic before reading the whole file
+    let mut magic = [0u8; CryXmlHeader::MAGIC_LEN];
+    let mut file = fs::File::open(path)?;
+    if file.read_exact(&mut magic).is_err() || !CryXml::is_cryxml(&magic) {
+        return Ok(ConvertStatus::NotCryXml);
+    }
+
+    let mut data = magic.to_vec();
+    file.read_to_end(&mut data)?;
+    let xml = CryXml::parse(&data)?.to_xml_string()?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, xml)?;
+    Ok(ConvertStatus::Converted)
+}
+
+fn collect_files(dir: &Path, options: &ConvertOptions, out: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_files(&path, options, out)?;
+        } else if file_type.is_file() && options.accepts(&path) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CryXmlBuilder;
+
+    #[test]
+    fn test_convert_tree_to_output_dir() {
+        let root = std::env::temp_dir().join(format!("svarog-convert-{}", std::process::id()));
+        let input = root.join("in");
+        let output = root.join("out");
+        fs::create_dir_all(input.join("materials")).unwrap();
+
+        let cryxml = CryXmlBuilder::from_xml(r#"<Material Name="Hull"/>"#)
+            .unwrap()
+            .build()
+            .unwrap();
+        fs::write(input.join("materials/hull.mtl"), &cryxml).unwrap();
+        fs::write(input.join("materials/plain.mtl"), "<Material/>").unwrap();
+        fs::write(input.join("broken.mtl"), b"CryXmlB\0garbage").unwrap();
+        fs::write(input.join("readme.txt"), &cryxml).unwrap();
+
+        let options = ConvertOptions::new()
+            .output_dir(&output)
+            .extensions(&["MTL"]);
+        let report = convert_tree(&input, &options).unwrap();
+
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.converted(), 1);
+        assert_eq!(report.not_cryxml(), 1);
+        assert_eq!(report.failed(), 1);
+
+        let xml = fs::read_to_string(output.join("materials/hull.mtl")).unwrap();
+        assert!(xml.contains(r#"<Material Name="Hull"/>"#));
+        assert!(!output.join("materials/plain.mtl").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,427 @@
+//! Serde deserialization of CryXml documents.
+//!
+//! Elements are presented to serde as maps, so any `Deserialize` struct can
+//! be filled directly from a CryXmlB or XML file:
+//!
+//! - Attributes are entries keyed by attribute name. Their values are parsed
+//!   on demand: numbers, booleans (`0`/`1`/`true`/`false`), strings, unit
+//!   enum variants, and comma-separated sequences such as `"1,0.5,0.25"`.
+//! - Child elements are entries keyed by tag. All children with the same tag
+//!   form one entry, which deserializes as a sequence or, for non-sequence
+//!   fields, as the first of them.
+//! - Text content is the entry `$text`. A child element deserialized as a
+//!   scalar yields its text content.
+//!
+//! Missing attributes and children map to `None` for `Option` fields.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Material {
+//!     #[serde(rename = "Name", default)]
+//!     name: String,
+//!     #[serde(rename = "Diffuse")]
+//!     diffuse: Option<[f32; 3]>,
+//!     #[serde(rename = "Textures")]
+//!     textures: Option<Textures>,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Textures {
+//!     #[serde(rename = "Texture", default)]
+//!     texture: Vec<Texture>,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Texture {
+//!     #[serde(rename = "Map")]
+//!     map: String,
+//!     #[serde(rename = "File")]
+//!     file: String,
+//! }
+//!
+//! let material: Material = svarog_cryxml::from_cryxml(&std::fs::read("hull.mtl")?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+
+use crate::builder::BuilderNode;
+use crate::{CryXmlDocument, Error, Result};
+
+/// Key of the text content entry.
+pub const TEXT_KEY: &str = "$text";
+
+/// Deserialize a value from CryXmlB or XML bytes.
+pub fn from_cryxml<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    let document = CryXmlDocument::parse(data)?;
+    from_node(document.root())
+}
+
+/// Deserialize a value from an element tree, borrowing strings from it.
+pub fn from_node<'a, T: Deserialize<'a>>(node: &'a BuilderNode) -> Result<T> {
+    T::deserialize(NodeDeserializer(node))
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+/// Forward deserializer methods to another deserializer returned by `self.$via()`.
+macro_rules! forward_via {
+    ($via:ident: $($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value> {
+                self.$via().$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+/// Deserializes an element.
+struct NodeDeserializer<'a>(&'a BuilderNode);
+
+impl<'a> NodeDeserializer<'a> {
+    fn text(self) -> TextDeserializer<'a> {
+        TextDeserializer(&self.0.content)
+    }
+}
+
+impl<'de> Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Leaf elements with text act as their text
+        if self.0.attributes.is_empty() && self.0.children.is_empty() && !self.0.content.is_empty()
+        {
+            return self.text().deserialize_any(visitor);
+        }
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(ElementAccess::new(self.0))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut seq = SeqDeserializer::new(std::iter::once(self));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    // Scalars come from the text content
+    forward_via! { text:
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_i128() deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_u128() deserialize_f32() deserialize_f64() deserialize_char() deserialize_str()
+        deserialize_string() deserialize_bytes() deserialize_byte_buf() deserialize_identifier()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
+
+/// Yields attributes, the text content and child groups of an element.
+struct ElementAccess<'a> {
+    node: &'a BuilderNode,
+    attribute: usize,
+    text_done: bool,
+    groups: Vec<(&'a str, Vec<&'a BuilderNode>)>,
+    group: usize,
+    value: Option<Pending<'a>>,
+}
+
+enum Pending<'a> {
+    Text(&'a str),
+    Children(Vec<&'a BuilderNode>),
+}
+
+impl<'a> ElementAccess<'a> {
+    fn new(node: &'a BuilderNode) -> Self {
+        let mut groups: Vec<(&'a str, Vec<&'a BuilderNode>)> = Vec::new();
+        for child in &node.children {
+            match groups.iter_mut().find(|(tag, _)| *tag == child.tag) {
+                Some((_, nodes)) => nodes.push(child),
+                None => groups.push((&child.tag, vec![child])),
+            }
+        }
+        Self {
+            node,
+            attribute: 0,
+            text_done: node.content.is_empty(),
+            groups,
+            group: 0,
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for ElementAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let key: &'de str = if let Some((key, value)) = self.node.attributes.get(self.attribute) {
+            self.attribute += 1;
+            self.value = Some(Pending::Text(value));
+            key
+        } else if !self.text_done {
+            self.text_done = true;
+            self.value = Some(Pending::Text(&self.node.content));
+            TEXT_KEY
+        } else if let Some((tag, nodes)) = self.groups.get_mut(self.group) {
+            self.group += 1;
+            self.value = Some(Pending::Children(std::mem::take(nodes)));
+            tag
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(Pending::Text(text)) => seed.deserialize(TextDeserializer(text)),
+            Some(Pending::Children(nodes)) => seed.deserialize(ChildrenDeserializer(nodes)),
+            None => Err(Error::Serde("value requested before key".to_string())),
+        }
+    }
+}
+
+/// Deserializes all children sharing a tag.
+struct ChildrenDeserializer<'a>(Vec<&'a BuilderNode>);
+
+impl<'de> Deserializer<'de> for ChildrenDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.0.len() == 1 {
+            NodeDeserializer(self.0[0]).deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut seq = SeqDeserializer::new(self.0.into_iter().map(NodeDeserializer));
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_via! { first:
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+        deserialize_i128() deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+        deserialize_u128() deserialize_f32() deserialize_f64() deserialize_char() deserialize_str()
+        deserialize_string() deserialize_bytes() deserialize_byte_buf() deserialize_identifier()
+        deserialize_unit() deserialize_map()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
+
+impl<'a> ChildrenDeserializer<'a> {
+    fn first(self) -> NodeDeserializer<'a> {
+        // Groups are never empty
+        NodeDeserializer(self.0[0])
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for NodeDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializes an attribute value or text content.
+struct TextDeserializer<'a>(&'a str);
+
+impl TextDeserializer<'_> {
+    fn parse<T: std::str::FromStr>(&self, what: &str) -> Result<T> {
+        self.0
+            .trim()
+            .parse()
+            .map_err(|_| Error::Serde(format!("invalid {}: {:?}", what, self.0)))
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.parse::<$ty>(stringify!($ty))?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for TextDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0.trim() {
+            "1" | "true" | "True" | "TRUE" => visitor.visit_bool(true),
+            "0" | "false" | "False" | "FALSE" => visitor.visit_bool(false),
+            _ => Err(Error::Serde(format!("invalid bool: {:?}", self.0))),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_char => visit_char: char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Comma-separated lists such as colors and vectors
+        let items = self
+            .0
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(TextDeserializer);
+        let mut seq = SeqDeserializer::new(items);
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        BorrowedStrDeserializer::<Error>::new(self.0.trim())
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for TextDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -0,0 +1,437 @@
+//! Structural diffing of CryXml documents.
+//!
+//! Text diffs of converted CryXmlB files are noisy because attribute and
+//! sibling order is not stable between builds. [`CryXmlDiff`] compares two
+//! trees at the node and attribute level instead: attributes are compared by
+//! name, and same-tag siblings are paired by an identifying attribute such as
+//! `Name` or `Map` when they have one, by position otherwise.
+//!
+//! Change paths use the [`query`](crate::query) syntax, e.g.
+//! `Material/SubMaterials/Material[@Name='paint']/Textures/Texture[@Map='Diffuse']`,
+//! so they can be fed back into `select`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::CryXmlDiff;
+//!
+//! let old = std::fs::read("old/hull.mtl")?;
+//! let new = std::fs::read("new/hull.mtl")?;
+//! let diff = CryXmlDiff::compare_bytes(&old, &new)?;
+//! print!("{}", diff);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::builder::BuilderNode;
+use crate::{CryXmlDocument, Result};
+
+/// Attributes used by default to pair same-tag siblings, in priority order.
+pub const DEFAULT_KEY_ATTRIBUTES: &[&str] = &["Name", "name", "AName", "Map", "id", "ID", "GUID"];
+
+/// Options for [`CryXmlDiff::compare_with`].
+#[derive(Debug, Clone)]
+pub struct CryXmlDiffOptions {
+    key_attributes: Vec<String>,
+}
+
+impl Default for CryXmlDiffOptions {
+    fn default() -> Self {
+        Self {
+            key_attributes: DEFAULT_KEY_ATTRIBUTES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl CryXmlDiffOptions {
+    /// Create options with the [`DEFAULT_KEY_ATTRIBUTES`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the attributes used to pair same-tag siblings, in priority
+    /// order. An empty list pairs siblings by position only.
+    pub fn key_attributes(mut self, keys: &[&str]) -> Self {
+        self.key_attributes = keys.iter().map(|s| s.to_string()).collect();
+        self
+    }
+}
+
+/// A single difference between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryXmlChange {
+    /// An element only present in the new document.
+    NodeAdded {
+        /// Path of the element.
+        path: String,
+    },
+    /// An element only present in the old document.
+    NodeRemoved {
+        /// Path of the element.
+        path: String,
+    },
+    /// An attribute only present in the new element.
+    AttributeAdded {
+        /// Path of the element.
+        path: String,
+        /// Attribute name.
+        name: String,
+        /// New value.
+        value: String,
+    },
+    /// An attribute only present in the old element.
+    AttributeRemoved {
+        /// Path of the element.
+        path: String,
+        /// Attribute name.
+        name: String,
+        /// Old value.
+        value: String,
+    },
+    /// An attribute whose value changed.
+    AttributeChanged {
+        /// Path of the element.
+        path: String,
+        /// Attribute name.
+        name: String,
+        /// Old value.
+        old: String,
+        /// New value.
+        new: String,
+    },
+    /// Text content that changed.
+    ContentChanged {
+        /// Path of the element.
+        path: String,
+        /// Old content.
+        old: String,
+        /// New content.
+        new: String,
+    },
+}
+
+impl CryXmlChange {
+    /// Path of the element the change belongs to.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::NodeAdded { path }
+            | Self::NodeRemoved { path }
+            | Self::AttributeAdded { path, .. }
+            | Self::AttributeRemoved { path, .. }
+            | Self::AttributeChanged { path, .. }
+            | Self::ContentChanged { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for CryXmlChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NodeAdded { path } => write!(f, "+ {}", path),
+            Self::NodeRemoved { path } => write!(f, "- {}", path),
+            Self::AttributeAdded { path, name, value } => {
+                write!(f, "+ {}/@{} = {:?}", path, name, value)
+            }
+            Self::AttributeRemoved { path, name, value } => {
+                write!(f, "- {}/@{} = {:?}", path, name, value)
+            }
+            Self::AttributeChanged {
+                path,
+                name,
+                old,
+                new,
+            } => {
+                write!(f, "~ {}/@{}: {:?} -> {:?}", path, name, old, new)
+            }
+            Self::ContentChanged { path, old, new } => {
+                write!(f, "~ {}/text(): {:?} -> {:?}", path, old, new)
+            }
+        }
+    }
+}
+
+/// Differences between two CryXml documents, in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CryXmlDiff {
+    /// The changes.
+    pub changes: Vec<CryXmlChange>,
+}
+
+impl CryXmlDiff {
+    /// Compare two element trees with the default options.
+    pub fn compare(old: &BuilderNode, new: &BuilderNode) -> Self {
+        Self::compare_with(old, new, &CryXmlDiffOptions::default())
+    }
+
+    /// Compare two element trees.
+    pub fn compare_with(old: &BuilderNode, new: &BuilderNode, options: &CryXmlDiffOptions) -> Self {
+        let mut diff = Self::default();
+        if old.tag == new.tag {
+            diff.node(old, new, &escape_step(&old.tag), options);
+        } else {
+            diff.changes.push(CryXmlChange::NodeRemoved {
+                path: old.tag.clone(),
+            });
+            diff.changes.push(CryXmlChange::NodeAdded {
+                path: new.tag.clone(),
+            });
+        }
+        diff
+    }
+
+    /// Parse two documents, each CryXmlB or XML text, and compare them.
+    pub fn compare_bytes(old: &[u8], new: &[u8]) -> Result<Self> {
+        let old = CryXmlDocument::parse(old)?;
+        let new = CryXmlDocument::parse(new)?;
+        Ok(Self::compare(old.root(), new.root()))
+    }
+
+    /// Whether the documents are structurally equal.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn node(
+        &mut self,
+        old: &BuilderNode,
+        new: &BuilderNode,
+        path: &str,
+        options: &CryXmlDiffOptions,
+    ) {
+        // Attributes, in old order followed by new-only ones
+        for (name, old_value) in &old.attributes {
+            match new.attribute(name) {
+                Some(new_value) if new_value == old_value => {}
+                Some(new_value) => self.changes.push(CryXmlChange::AttributeChanged {
+                    path: path.to_string(),
+                    name: name.clone(),
+                    old: old_value.clone(),
+                    new: new_value.to_string(),
+                }),
+                None => self.changes.push(CryXmlChange::AttributeRemoved {
+                    path: path.to_string(),
+                    name: name.clone(),
+                    value: old_value.clone(),
+                }),
+            }
+        }
+        for (name, value) in &new.attributes {
+            if old.attribute(name).is_none() {
+                self.changes.push(CryXmlChange::AttributeAdded {
+                    path: path.to_string(),
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        if old.content != new.content {
+            self.changes.push(CryXmlChange::ContentChanged {
+                path: path.to_string(),
+                old: old.content.clone(),
+                new: new.content.clone(),
+            });
+        }
+
+        // Children, grouped by tag in order of first appearance
+        let mut tags: Vec<&str> = Vec::new();
+        for child in old.children.iter().chain(&new.children) {
+            if !tags.contains(&child.tag.as_str()) {
+                tags.push(&child.tag);
+            }
+        }
+        for tag in tags {
+            let old_group: Vec<&BuilderNode> =
+                old.children.iter().filter(|c| c.tag == tag).collect();
+            let new_group: Vec<&BuilderNode> =
+                new.children.iter().filter(|c| c.tag == tag).collect();
+            self.group(tag, &old_group, &new_group, path, options);
+        }
+    }
+
+    fn group(
+        &mut self,
+        tag: &str,
+        old: &[&BuilderNode],
+        new: &[&BuilderNode],
+        parent: &str,
+        options: &CryXmlDiffOptions,
+    ) {
+        let step = escape_step(tag);
+        let single = old.len() <= 1 && new.len() <= 1;
+
+        let key = options
+            .key_attributes
+            .iter()
+            .find(|key| unique_key(old, key) && unique_key(new, key));
+
+        match key {
+            Some(key) => {
+                let path = |node: &BuilderNode| {
+                    let value = node.attribute(key).unwrap_or_default();
+                    format!("{}/{}[@{}={}]", parent, step, key, quote(value))
+                };
+                for old_child in old {
+                    let value = old_child.attribute(key);
+                    match new.iter().find(|n| n.attribute(key) == value) {
+                        Some(new_child) => {
+                            self.node(old_child, new_child, &path(old_child), options)
+                        }
+                        None => self.changes.push(CryXmlChange::NodeRemoved {
+                            path: path(old_child),
+                        }),
+                    }
+                }
+                for new_child in new {
+                    let value = new_child.attribute(key);
+                    if !old.iter().any(|o| o.attribute(key) == value) {
+                        self.changes.push(CryXmlChange::NodeAdded {
+                            path: path(new_child),
+                        });
+                    }
+                }
+            }
+            None => {
+                let path = |index: usize| {
+                    if single {
+                        format!("{}/{}", parent, step)
+                    } else {
+                        format!("{}/{}[{}]", parent, step, index + 1)
+                    }
+                };
+                for index in 0..old.len().max(new.len()) {
+                    match (old.get(index), new.get(index)) {
+                        (Some(o), Some(n)) => self.node(o, n, &path(index), options),
+                        (Some(_), None) => self
+                            .changes
+                            .push(CryXmlChange::NodeRemoved { path: path(index) }),
+                        (None, Some(_)) => self
+                            .changes
+                            .push(CryXmlChange::NodeAdded { path: path(index) }),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for CryXmlDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether every node has `key` and no two share a value.
+fn unique_key(nodes: &[&BuilderNode], key: &str) -> bool {
+    let mut seen = HashSet::new();
+    nodes
+        .iter()
+        .all(|node| node.attribute(key).is_some_and(|value| seen.insert(value)))
+}
+
+/// Quote a predicate value for a query path.
+fn quote(value: &str) -> String {
+    if value.contains('\'') {
+        format!("\"{}\"", value)
+    } else {
+        format!("'{}'", value)
+    }
+}
+
+/// Marker nodes for comments and processing instructions get readable steps.
+fn escape_step(tag: &str) -> String {
+    if tag == BuilderNode::COMMENT_TAG {
+        "comment()".to_string()
+    } else if let Some(target) = tag.strip_prefix('?') {
+        format!("processing-instruction({})", target)
+    } else {
+        tag.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CryXmlBuilder;
+
+    fn root(xml: &str) -> BuilderNode {
+        CryXmlBuilder::from_xml(xml).unwrap().into_root()
+    }
+
+    #[test]
+    fn test_attribute_and_sibling_order_ignored() {
+        let old = root(
+            r#"<Material A="1" B="2"><Textures><Texture Map="Diffuse" File="a.dds"/><Texture Map="Bumpmap" File="b.dds"/></Textures></Material>"#,
+        );
+        let new = root(
+            r#"<Material B="2" A="1"><Textures><Texture File="b.dds" Map="Bumpmap"/><Texture Map="Diffuse" File="a.dds"/></Textures></Material>"#,
+        );
+        assert!(CryXmlDiff::compare(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_changes_reported_with_paths() {
+        let old = root(
+            r#"<Material Shader="Illum"><Textures><Texture Map="Diffuse" File="a.dds"/><Texture Map="Bumpmap" File="b.dds"/></Textures><Note>x</Note></Material>"#,
+        );
+        let new = root(
+            r#"<Material Surface="metal"><Textures><Texture Map="Diffuse" File="c.dds"/><Texture Map="Specular" File="s.dds"/></Textures><Note>y</Note></Material>"#,
+        );
+        let diff = CryXmlDiff::compare(&old, &new);
+
+        let textures = "Material/Textures/Texture";
+        assert_eq!(
+            diff.changes,
+            [
+                CryXmlChange::AttributeRemoved {
+                    path: "Material".into(),
+                    name: "Shader".into(),
+                    value: "Illum".into()
+                },
+                CryXmlChange::AttributeAdded {
+                    path: "Material".into(),
+                    name: "Surface".into(),
+                    value: "metal".into()
+                },
+                CryXmlChange::AttributeChanged {
+                    path: format!("{}[@Map='Diffuse']", textures),
+                    name: "File".into(),
+                    old: "a.dds".into(),
+                    new: "c.dds".into()
+                },
+                CryXmlChange::NodeRemoved {
+                    path: format!("{}[@Map='Bumpmap']", textures)
+                },
+                CryXmlChange::NodeAdded {
+                    path: format!("{}[@Map='Specular']", textures)
+                },
+                CryXmlChange::ContentChanged {
+                    path: "Material/Note".into(),
+                    old: "x".into(),
+                    new: "y".into()
+                },
+            ]
+        );
+
+        // Paths can be queried
+        let path = diff.changes[2].path();
+        assert_eq!(new.select(path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_positional_pairing_without_keys() {
+        let old = root(r#"<Root><Item v="1"/><Item v="2"/></Root>"#);
+        let new = root(r#"<Root><Item v="1"/><Item v="3"/><Item v="4"/></Root>"#);
+        let text = CryXmlDiff::compare(&old, &new).to_string();
+        assert_eq!(text, "~ Root/Item[2]/@v: \"2\" -> \"3\"\n+ Root/Item[3]\n");
+    }
+}
@@ -0,0 +1,245 @@
+//! Mutable CryXml document.
+//!
+//! [`CryXml`] is a read-only view over the binary tables of a CryXmlB file and
+//! [`CryXmlBuilder`] only writes. A [`CryXmlDocument`] owns its node tree, so
+//! a file can be parsed from CryXmlB or XML text, edited in place and written
+//! back in either format.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::CryXmlDocument;
+//!
+//! let data = std::fs::read("material.mtl")?;
+//! let mut doc = CryXmlDocument::parse(&data)?;
+//!
+//! if let Some(textures) = doc.root_mut().find_child_mut("Textures") {
+//!     for texture in textures.children_named_mut("Texture") {
+//!         if texture.attribute("Map") == Some("Diffuse") {
+//!             texture.set_attribute("File", "textures/new_diffuse.dds");
+//!         }
+//!     }
+//! }
+//!
+//! std::fs::write("material.mtl", doc.to_cryxml()?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::io::Write;
+
+use crate::builder::{BuilderNode, CryXmlBuilder};
+use crate::{CryXml, CryXmlNode, FromXmlOptions, Result};
+
+/// An editable CryXml document.
+#[derive(Debug, Clone)]
+pub struct CryXmlDocument {
+    root: BuilderNode,
+}
+
+impl CryXmlDocument {
+    /// Create a document with the given root element.
+    pub fn new(root: BuilderNode) -> Self {
+        Self { root }
+    }
+
+    /// Parse a document from CryXmlB bytes, or from XML text if the data is
+    /// not CryXmlB.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if CryXml::is_cryxml(data) {
+            CryXml::parse(data)?.to_document()
+        } else {
+            Self::from_xml_bytes(data)
+        }
+    }
+
+    /// Parse a document from XML text.
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        Ok(Self::new(CryXmlBuilder::from_xml(xml)?.into_root()))
+    }
+
+    /// Parse a document from XML text, handling comments and processing
+    /// instructions according to `options`.
+    pub fn from_xml_with(xml: &str, options: &FromXmlOptions) -> Result<Self> {
+        Ok(Self::new(
+            CryXmlBuilder::from_xml_with(xml, options)?.into_root(),
+        ))
+    }
+
+    /// Parse a document from XML bytes.
+    pub fn from_xml_bytes(xml: &[u8]) -> Result<Self> {
+        Ok(Self::new(CryXmlBuilder::from_xml_bytes(xml)?.into_root()))
+    }
+
+    /// The root element.
+    pub fn root(&self) -> &BuilderNode {
+        &self.root
+    }
+
+    /// The root element, for editing.
+    pub fn root_mut(&mut self) -> &mut BuilderNode {
+        &mut self.root
+    }
+
+    /// Consume the document and return its root element.
+    pub fn into_root(self) -> BuilderNode {
+        self.root
+    }
+
+    /// Serialize to CryXmlB bytes.
+    pub fn to_cryxml(&self) -> Result<Vec<u8>> {
+        CryXmlBuilder::new(self.root.clone()).build()
+    }
+
+    /// Convert to XML string.
+    ///
+    /// The output matches [`CryXml::to_xml_string`] for the same tree.
+    #[cfg(feature = "xml-output")]
+    pub fn to_xml_string(&self) -> Result<String> {
+        let mut output = Vec::new();
+        self.write_xml(&mut output)?;
+        String::from_utf8(output).map_err(|e| crate::Error::Xml(e.to_string()))
+    }
+
+    /// Write XML to a writer.
+    #[cfg(feature = "xml-output")]
+    pub fn write_xml<W: Write>(&self, writer: &mut W) -> Result<()> {
+        use quick_xml::events::{BytesDecl, Event};
+        use quick_xml::Writer;
+
+        let mut xml_writer = Writer::new_with_indent(writer, b' ', 2);
+        xml_writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))
+            .map_err(|e| crate::Error::Xml(e.to_string()))?;
+
+        write_element(&mut xml_writer, &self.root)
+    }
+}
+
+impl From<BuilderNode> for CryXmlDocument {
+    fn from(root: BuilderNode) -> Self {
+        Self::new(root)
+    }
+}
+
+#[cfg(feature = "xml-output")]
+fn write_element<W: Write>(writer: &mut quick_xml::Writer<W>, node: &BuilderNode) -> Result<()> {
+    use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+
+    if let Some(event) = crate::builder::markup_event(&node.tag, &node.content) {
+        return writer
+            .write_event(event)
+            .map_err(|e| crate::Error::Xml(e.to_string()));
+    }
+
+    let mut elem = BytesStart::new(node.tag.as_str());
+    for (key, value) in &node.attributes {
+        // Skip xmlns attributes, like CryXml::write_xml
+        if key.starts_with("xmlns") {
+            continue;
+        }
+        elem.push_attribute((key.as_str(), value.as_str()));
+    }
+
+    if node.children.is_empty() && node.content.is_empty() {
+        return writer
+            .write_event(Event::Empty(elem))
+            .map_err(|e| crate::Error::Xml(e.to_string()));
+    }
+
+    writer
+        .write_event(Event::Start(elem))
+        .map_err(|e| crate::Error::Xml(e.to_string()))?;
+    if node.children.is_empty() {
+        writer
+            .write_event(Event::Text(BytesText::new(&node.content)))
+            .map_err(|e| crate::Error::Xml(e.to_string()))?;
+    } else {
+        for child in &node.children {
+            write_element(writer, child)?;
+        }
+    }
+    writer
+        .write_event(Event::End(BytesEnd::new(node.tag.as_str())))
+        .map_err(|e| crate::Error::Xml(e.to_string()))
+}
+
+impl CryXml {
+    /// Convert the parsed document into an editable [`CryXmlDocument`].
+    pub fn to_document(&self) -> Result<CryXmlDocument> {
+        let root = self
+            .root()
+            .ok_or_else(|| crate::Error::Xml("document has no root node".to_string()))?;
+        Ok(CryXmlDocument::new(self.to_builder_node(root)?))
+    }
+
+    /// Copy a node and its subtree into an owned [`BuilderNode`].
+    pub fn to_builder_node(&self, node: &CryXmlNode) -> Result<BuilderNode> {
+        let mut out = BuilderNode::new(self.get_string(node.tag_string_offset)?);
+        out.content = self.get_string(node.content_string_offset)?.to_string();
+
+        for attr in self.node_attributes(node) {
+            out.attributes.push((
+                self.get_string(attr.key_string_offset)?.to_string(),
+                self.get_string(attr.value_string_offset)?.to_string(),
+            ));
+        }
+
+        for child in self.children(node) {
+            out.children.push(self.to_builder_node(child)?);
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MATERIAL: &str = r#"<Material Name="Hull">
+  <Textures>
+    <Texture Map="Diffuse" File="hull_diff.dds"/>
+    <Texture Map="Bumpmap" File="hull_ddna.dds"/>
+  </Textures>
+</Material>"#;
+
+    #[test]
+    fn test_edit_and_reserialize() {
+        let bytes = CryXmlBuilder::from_xml(MATERIAL).unwrap().build().unwrap();
+        let mut doc = CryXmlDocument::parse(&bytes).unwrap();
+
+        let textures = doc.root_mut().find_child_mut("Textures").unwrap();
+        textures
+            .children_named_mut("Texture")
+            .next()
+            .unwrap()
+            .set_attribute("File", "new.dds");
+        textures.remove_children(|t| t.attribute("Map") == Some("Bumpmap"));
+        textures
+            .children
+            .push(BuilderNode::new("Texture").attr("Map", "Specular"));
+        doc.root_mut().remove_attribute("Name");
+
+        let reparsed = CryXmlDocument::parse(&doc.to_cryxml().unwrap()).unwrap();
+        let textures = reparsed.root().find_child("Textures").unwrap();
+        let maps: Vec<_> = textures
+            .children
+            .iter()
+            .filter_map(|t| t.attribute("Map"))
+            .collect();
+        assert_eq!(maps, ["Diffuse", "Specular"]);
+        assert_eq!(textures.children[0].attribute("File"), Some("new.dds"));
+        assert_eq!(reparsed.root().attribute("Name"), None);
+    }
+
+    #[test]
+    fn test_xml_output_matches_parser() {
+        let bytes = CryXmlBuilder::from_xml(MATERIAL).unwrap().build().unwrap();
+        let parsed = CryXml::parse(&bytes).unwrap();
+        let doc = parsed.to_document().unwrap();
+        assert_eq!(
+            doc.to_xml_string().unwrap(),
+            parsed.to_xml_string().unwrap()
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for CryXmlB parsing and writing.
 
+use svarog_common::ErrorLocation;
 use thiserror::Error;
 
 /// Errors that can occur when parsing or writing CryXmlB files.
@@ -32,6 +33,23 @@ pub enum Error {
     /// XML parsing or writing error.
     #[error("XML error: {0}")]
     Xml(String),
+
+    /// Malformed query expression.
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// Serde serialization or deserialization error.
+    #[error("serde error: {0}")]
+    Serde(String),
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// Result type for CryXmlB operations.
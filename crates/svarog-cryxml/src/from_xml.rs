@@ -1,4 +1,11 @@
 //! Parse XML text into CryXmlB binary format.
+//!
+//! CryXmlB only stores elements, attributes and text. CDATA sections are
+//! always kept as text content. Comments and processing instructions are
+//! handled according to a [`MarkupPolicy`]: stripped, rejected, or preserved
+//! as marker nodes (see [`BuilderNode::comment`] and
+//! [`BuilderNode::processing_instruction`]) that the XML writers turn back
+//! into comments and processing instructions.
 
 use quick_xml::events::Event;
 use quick_xml::Reader;
@@ -6,6 +13,57 @@ use quick_xml::Reader;
 use crate::builder::{BuilderNode, CryXmlBuilder};
 use crate::{Error, Result};
 
+/// How [`CryXmlBuilder::from_xml_with`] treats markup that CryXmlB has no
+/// native representation for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkupPolicy {
+    /// Drop it.
+    #[default]
+    Strip,
+    /// Keep it as a marker node.
+    ///
+    /// Markup outside the root element is moved to the start of the root.
+    Preserve,
+    /// Fail with an error, so nothing is lost silently.
+    Reject,
+}
+
+/// Options for [`CryXmlBuilder::from_xml_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FromXmlOptions {
+    comments: MarkupPolicy,
+    processing_instructions: MarkupPolicy,
+}
+
+impl FromXmlOptions {
+    /// Create options that strip comments and processing instructions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options that preserve comments and processing instructions.
+    pub fn preserve_all() -> Self {
+        Self::new()
+            .comments(MarkupPolicy::Preserve)
+            .processing_instructions(MarkupPolicy::Preserve)
+    }
+
+    /// Set the policy for `<!-- comments -->`.
+    pub fn comments(mut self, policy: MarkupPolicy) -> Self {
+        self.comments = policy;
+        self
+    }
+
+    /// Set the policy for `<?target data?>` processing instructions.
+    ///
+    /// The XML declaration is not a processing instruction and is always
+    /// regenerated on output.
+    pub fn processing_instructions(mut self, policy: MarkupPolicy) -> Self {
+        self.processing_instructions = policy;
+        self
+    }
+}
+
 impl CryXmlBuilder {
     /// Parse XML text and create a builder that can produce CryXmlB bytes.
     ///
@@ -25,24 +83,52 @@ impl CryXmlBuilder {
     /// let bytes = builder.build().unwrap();
     /// ```
     pub fn from_xml(xml: &str) -> Result<Self> {
-        let root = parse_xml_to_node(xml)?;
+        Self::from_xml_with(xml, &FromXmlOptions::default())
+    }
+
+    /// Parse XML text with explicit handling of comments and processing
+    /// instructions.
+    pub fn from_xml_with(xml: &str, options: &FromXmlOptions) -> Result<Self> {
+        let root = parse_xml_to_node(xml, options)?;
         Ok(Self::new(root))
     }
 
     /// Parse XML bytes and create a builder.
     pub fn from_xml_bytes(xml: &[u8]) -> Result<Self> {
+        Self::from_xml_bytes_with(xml, &FromXmlOptions::default())
+    }
+
+    /// Parse XML bytes with explicit handling of comments and processing
+    /// instructions.
+    pub fn from_xml_bytes_with(xml: &[u8], options: &FromXmlOptions) -> Result<Self> {
         let xml_str = std::str::from_utf8(xml).map_err(Error::Utf8)?;
-        Self::from_xml(xml_str)
+        Self::from_xml_with(xml_str, options)
+    }
+}
+
+/// Apply a policy to a comment or processing instruction node.
+fn apply_policy(
+    policy: MarkupPolicy,
+    node: BuilderNode,
+    what: &str,
+) -> Result<Option<BuilderNode>> {
+    match policy {
+        MarkupPolicy::Strip => Ok(None),
+        MarkupPolicy::Preserve => Ok(Some(node)),
+        MarkupPolicy::Reject => Err(Error::Xml(format!("{} not supported by CryXmlB", what))),
     }
 }
 
 /// Parse XML text into a BuilderNode tree.
-fn parse_xml_to_node(xml: &str) -> Result<BuilderNode> {
+fn parse_xml_to_node(xml: &str, options: &FromXmlOptions) -> Result<BuilderNode> {
+    // Text is trimmed per element rather than per event, so text split by
+    // CDATA sections, comments or children keeps its inner whitespace
     let mut reader = Reader::from_str(xml);
-    reader.config_mut().trim_text(true);
 
     let mut stack: Vec<BuilderNode> = Vec::new();
     let mut root: Option<BuilderNode> = None;
+    // Preserved markup outside the root element
+    let mut outside: Vec<BuilderNode> = Vec::new();
 
     loop {
         match reader.read_event() {
@@ -77,7 +163,11 @@ fn parse_xml_to_node(xml: &str) -> Result<BuilderNode> {
                 }
             }
             Ok(Event::End(_)) => {
-                if let Some(node) = stack.pop() {
+                if let Some(mut node) = stack.pop() {
+                    let trimmed = node.content.trim();
+                    if trimmed.len() != node.content.len() {
+                        node.content = trimmed.to_string();
+                    }
                     if let Some(parent) = stack.last_mut() {
                         parent.children.push(node);
                     } else {
@@ -88,18 +178,52 @@ fn parse_xml_to_node(xml: &str) -> Result<BuilderNode> {
             Ok(Event::Text(e)) => {
                 if let Some(node) = stack.last_mut() {
                     let text = e.unescape().map_err(|e| Error::Xml(e.to_string()))?;
-                    if !text.trim().is_empty() {
-                        node.content = text.into_owned();
+                    node.content.push_str(&text);
+                }
+            }
+            Ok(Event::CData(e)) => {
+                if let Some(node) = stack.last_mut() {
+                    node.content.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Ok(Event::Comment(e)) => {
+                let text = String::from_utf8_lossy(&e).into_owned();
+                if let Some(node) =
+                    apply_policy(options.comments, BuilderNode::comment(text), "comment")?
+                {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => outside.push(node),
+                    }
+                }
+            }
+            Ok(Event::PI(e)) => {
+                let raw = String::from_utf8_lossy(&e);
+                let (target, data) = raw.split_once(char::is_whitespace).unwrap_or((&raw, ""));
+                let node = BuilderNode::processing_instruction(target, data.trim_start());
+                if let Some(node) = apply_policy(
+                    options.processing_instructions,
+                    node,
+                    "processing instruction",
+                )? {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(node),
+                        None => outside.push(node),
                     }
                 }
             }
             Ok(Event::Eof) => break,
-            Ok(_) => {} // Ignore other events (declarations, comments, etc.)
+            Ok(_) => {} // Ignore declarations and doctypes
             Err(e) => return Err(Error::Xml(format!("XML parse error: {}", e))),
         }
     }
 
-    root.ok_or_else(|| Error::Xml("No root element found in XML".to_string()))
+    let mut root = root.ok_or_else(|| Error::Xml("No root element found in XML".to_string()))?;
+    if !outside.is_empty() {
+        outside.append(&mut root.children);
+        root.children = outside;
+    }
+    Ok(root)
 }
 
 #[cfg(test)]
@@ -215,6 +339,57 @@ mod tests {
         assert!(result.is_err());
     }
 
+    const MARKUP: &str = r#"<?xml version="1.0"?>
+<!-- header -->
+<Root>
+    <?editor fold="true"?>
+    <Child><![CDATA[a < b]]> and more</Child>
+    <!-- note -->
+</Root>"#;
+
+    #[test]
+    fn test_markup_stripped_by_default() {
+        let root = CryXmlBuilder::from_xml(MARKUP).unwrap().into_root();
+        assert_eq!(root.children.len(), 1);
+        assert_eq!(root.children[0].content, "a < b and more");
+    }
+
+    #[test]
+    fn test_markup_preserved() {
+        let root = CryXmlBuilder::from_xml_with(MARKUP, &FromXmlOptions::preserve_all())
+            .unwrap()
+            .into_root();
+        let tags: Vec<_> = root.children.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(
+            tags,
+            [
+                BuilderNode::COMMENT_TAG,
+                "?editor",
+                "Child",
+                BuilderNode::COMMENT_TAG
+            ]
+        );
+        assert_eq!(root.children[0].content, " header ");
+        assert_eq!(
+            root.children[1].processing_instruction_target(),
+            Some("editor")
+        );
+        assert_eq!(root.children[1].content, r#"fold="true""#);
+
+        // Markers survive CryXmlB and come back out as XML markup
+        let bytes = CryXmlBuilder::new(root).build().unwrap();
+        let xml = CryXml::parse(&bytes).unwrap().to_xml_string().unwrap();
+        assert!(xml.contains("<!-- note -->"));
+        assert!(xml.contains(r#"<?editor fold="true"?>"#));
+    }
+
+    #[test]
+    fn test_markup_rejected() {
+        let options = FromXmlOptions::new().comments(MarkupPolicy::Reject);
+        assert!(CryXmlBuilder::from_xml_with(MARKUP, &options).is_err());
+        assert!(CryXmlBuilder::from_xml_with("<Root/>", &options).is_ok());
+    }
+
     #[test]
     fn test_from_xml_text_content() {
         let xml = r#"<Root><Child>Hello World</Child></Root>"#;
@@ -46,6 +46,46 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
+//! # Editing Documents
+//!
+//! ```no_run
+//! use svarog_cryxml::CryXmlDocument;
+//!
+//! let mut doc = CryXmlDocument::parse(&std::fs::read("material.mtl")?)?;
+//! doc.root_mut().set_attribute("Name", "Renamed");
+//! std::fs::write("material.mtl", doc.to_cryxml()?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! # Querying
+//!
+//! ```no_run
+//! use svarog_cryxml::CryXml;
+//!
+//! let cryxml = CryXml::parse(&std::fs::read("material.mtl")?)?;
+//! for file in cryxml.select("Material/Textures/Texture[@Map='Diffuse']/@File")? {
+//!     println!("{}", file.value().unwrap_or(""));
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! # Typed Access with Serde
+//!
+//! With the `serde` feature, files map directly onto your own structs:
+//!
+//! ```ignore
+//! #[derive(serde::Deserialize, serde::Serialize)]
+//! struct Material {
+//!     #[serde(rename = "Name")]
+//!     name: String,
+//!     #[serde(rename = "Diffuse")]
+//!     diffuse: Option<[f32; 3]>,
+//! }
+//!
+//! let material: Material = svarog_cryxml::from_cryxml(&std::fs::read("material.mtl")?)?;
+//! std::fs::write("material.mtl", svarog_cryxml::to_cryxml(&material)?)?;
+//! ```
+//!
 //! # Writing CryXmlB Programmatically
 //!
 //! ```no_run
@@ -70,10 +110,36 @@ mod node;
 mod attribute;
 mod parser;
 pub mod builder;
+pub mod character;
+#[cfg(feature = "parallel")]
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+mod diff;
+mod document;
 mod from_xml;
+pub mod material;
+pub mod query;
+mod reader;
+#[cfg(feature = "serde")]
+mod ser;
 
 pub use error::{Error, Result};
 pub use header::CryXmlHeader;
 pub use node::CryXmlNode;
 pub use attribute::CryXmlAttribute;
 pub use parser::CryXml;
+pub use diff::{CryXmlChange, CryXmlDiff, CryXmlDiffOptions, DEFAULT_KEY_ATTRIBUTES};
+pub use document::CryXmlDocument;
+pub use from_xml::{FromXmlOptions, MarkupPolicy};
+#[cfg(feature = "parallel")]
+pub use convert::{
+    convert_file, convert_tree, convert_tree_with, ConvertOptions, ConvertReport, ConvertResult,
+    ConvertStatus,
+};
+pub use query::{CryXmlPath, Selected};
+pub use reader::{CryXmlReader, CryXmlVisitor, Visit};
+#[cfg(feature = "serde")]
+pub use de::{from_cryxml, from_node, TEXT_KEY};
+#[cfg(feature = "serde")]
+pub use ser::{to_cryxml, to_node};
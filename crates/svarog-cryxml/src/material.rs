@@ -0,0 +1,425 @@
+//! Typed model of `.mtl` material files.
+//!
+//! A [`Material`] exposes the parts of a material that texture and shader
+//! tooling works with (shader, colors, texture slots, public shader
+//! parameters, layer blends and sub-materials) as fields instead of attribute
+//! lookups. Attributes and child elements the model does not know are kept,
+//! so a material can be edited and written back without losing data.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::material::Material;
+//!
+//! let mut material = Material::parse(&std::fs::read("hull.mtl")?)?;
+//! for sub in &mut material.sub_materials {
+//!     if let Some(diffuse) = sub.texture_mut("Diffuse") {
+//!         diffuse.file = diffuse.file.replace(".tif", ".dds");
+//!     }
+//! }
+//! std::fs::write("hull.mtl", material.to_cryxml()?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::builder::BuilderNode;
+use crate::{CryXmlDocument, Error, Result};
+
+/// An RGB color, stored as `"r,g,b"` in attributes.
+pub type Color = [f32; 3];
+
+/// A material or sub-material.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Material {
+    /// Material name (`Name`).
+    pub name: String,
+    /// Shader name (`Shader`), e.g. `Illum` or `LayerBlend`.
+    pub shader: Option<String>,
+    /// Material flags (`MtlFlags`).
+    pub flags: u32,
+    /// Shader generation flags (`StringGenMask`), e.g. `%BUMP_MAP%SPECULAR_MAP`.
+    pub gen_mask: Option<String>,
+    /// Surface type for physics and effects (`SurfaceType`).
+    pub surface_type: Option<String>,
+    /// Diffuse color (`Diffuse`).
+    pub diffuse: Option<Color>,
+    /// Specular color (`Specular`).
+    pub specular: Option<Color>,
+    /// Emissive color (`Emissive`).
+    pub emissive: Option<Color>,
+    /// Opacity (`Opacity`).
+    pub opacity: Option<f32>,
+    /// Shininess (`Shininess`).
+    pub shininess: Option<f32>,
+    /// Texture slots (`Textures/Texture`).
+    pub textures: Vec<Texture>,
+    /// Public shader parameters (`PublicParams` attributes), in file order.
+    pub shader_params: Vec<(String, String)>,
+    /// Layer blends (`MatLayers/Layer`).
+    pub layers: Vec<MaterialLayer>,
+    /// Sub-materials (`SubMaterials/Material`).
+    pub sub_materials: Vec<Material>,
+    /// Attributes not covered by the fields above.
+    pub extra_attributes: Vec<(String, String)>,
+    /// Child elements not covered by the fields above.
+    pub extra_children: Vec<BuilderNode>,
+}
+
+/// A texture slot of a material.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Texture {
+    /// Slot name (`Map`), e.g. `Diffuse`, `Bumpmap` or `Specular`.
+    pub map: String,
+    /// Texture path (`File`).
+    pub file: String,
+    /// Attributes other than `Map` and `File`.
+    pub extra_attributes: Vec<(String, String)>,
+    /// Child elements, e.g. `TexMod`.
+    pub children: Vec<BuilderNode>,
+}
+
+/// A layer of a layer-blend material.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaterialLayer {
+    /// Layer name (`Name`).
+    pub name: String,
+    /// Path of the layer's material file (`Path`).
+    pub path: String,
+    /// Sub-material of the layer material to use (`Submtl`).
+    pub sub_material: Option<String>,
+    /// Tint color (`TintColor`).
+    pub tint_color: Option<Color>,
+    /// UV tiling factor (`UVTiling`).
+    pub uv_tiling: Option<f32>,
+    /// Attributes not covered by the fields above.
+    pub extra_attributes: Vec<(String, String)>,
+}
+
+impl Material {
+    /// Create an empty material with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse a material from CryXmlB or XML bytes.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        Self::from_node(CryXmlDocument::parse(data)?.root())
+    }
+
+    /// Read a material from a `<Material>` element.
+    pub fn from_node(node: &BuilderNode) -> Result<Self> {
+        if node.tag != "Material" {
+            return Err(Error::Xml(format!(
+                "expected <Material> element, found <{}>",
+                node.tag
+            )));
+        }
+
+        let mut attrs = Attributes(node.attributes.clone());
+        let mut material = Self {
+            name: attrs.take("Name").unwrap_or_default(),
+            shader: attrs.take("Shader"),
+            flags: attrs.take_parsed("MtlFlags").unwrap_or(0),
+            gen_mask: attrs.take("StringGenMask"),
+            surface_type: attrs.take("SurfaceType"),
+            diffuse: attrs.take_floats("Diffuse"),
+            specular: attrs.take_floats("Specular"),
+            emissive: attrs.take_floats("Emissive"),
+            opacity: attrs.take_parsed("Opacity"),
+            shininess: attrs.take_parsed("Shininess"),
+            ..Self::default()
+        };
+        material.extra_attributes = attrs.0;
+
+        for child in &node.children {
+            match child.tag.as_str() {
+                "Textures" => material
+                    .textures
+                    .extend(child.children_named("Texture").map(Texture::from_node)),
+                "PublicParams" => material
+                    .shader_params
+                    .extend(child.attributes.iter().cloned()),
+                "MatLayers" => material
+                    .layers
+                    .extend(child.children_named("Layer").map(MaterialLayer::from_node)),
+                "SubMaterials" => {
+                    for sub in child.children_named("Material") {
+                        material.sub_materials.push(Self::from_node(sub)?);
+                    }
+                }
+                _ => material.extra_children.push(child.clone()),
+            }
+        }
+
+        Ok(material)
+    }
+
+    /// Convert back to a `<Material>` element.
+    pub fn to_node(&self) -> BuilderNode {
+        let mut attrs = Attributes(Vec::new());
+        // Root materials of multi-material files have no name
+        if !self.name.is_empty() {
+            attrs.put("Name", Some(self.name.clone()));
+        }
+        attrs.put("Shader", self.shader.clone());
+        if self.flags != 0 {
+            attrs.put("MtlFlags", Some(self.flags.to_string()));
+        }
+        attrs.put("StringGenMask", self.gen_mask.clone());
+        attrs.put("SurfaceType", self.surface_type.clone());
+        attrs.put("Diffuse", self.diffuse.map(format_floats));
+        attrs.put("Specular", self.specular.map(format_floats));
+        attrs.put("Emissive", self.emissive.map(format_floats));
+        attrs.put("Opacity", self.opacity.map(|v| v.to_string()));
+        attrs.put("Shininess", self.shininess.map(|v| v.to_string()));
+        attrs.0.extend(self.extra_attributes.iter().cloned());
+
+        let mut node = BuilderNode::new("Material");
+        node.attributes = attrs.0;
+
+        if !self.textures.is_empty() {
+            node.children.push(
+                BuilderNode::new("Textures").children(self.textures.iter().map(Texture::to_node)),
+            );
+        }
+        if !self.shader_params.is_empty() {
+            let mut params = BuilderNode::new("PublicParams");
+            params.attributes = self.shader_params.clone();
+            node.children.push(params);
+        }
+        if !self.layers.is_empty() {
+            node.children.push(
+                BuilderNode::new("MatLayers")
+                    .children(self.layers.iter().map(MaterialLayer::to_node)),
+            );
+        }
+        node.children.extend(self.extra_children.iter().cloned());
+        if !self.sub_materials.is_empty() {
+            node.children.push(
+                BuilderNode::new("SubMaterials")
+                    .children(self.sub_materials.iter().map(Self::to_node)),
+            );
+        }
+
+        node
+    }
+
+    /// Convert to an editable document.
+    pub fn to_document(&self) -> CryXmlDocument {
+        CryXmlDocument::new(self.to_node())
+    }
+
+    /// Serialize to CryXmlB bytes.
+    pub fn to_cryxml(&self) -> Result<Vec<u8>> {
+        self.to_document().to_cryxml()
+    }
+
+    /// Get the texture in a slot.
+    pub fn texture(&self, map: &str) -> Option<&Texture> {
+        self.textures.iter().find(|t| t.map == map)
+    }
+
+    /// Get the texture in a slot, for editing.
+    pub fn texture_mut(&mut self, map: &str) -> Option<&mut Texture> {
+        self.textures.iter_mut().find(|t| t.map == map)
+    }
+
+    /// Get a public shader parameter.
+    pub fn shader_param(&self, name: &str) -> Option<&str> {
+        self.shader_params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get a sub-material by name.
+    pub fn sub_material(&self, name: &str) -> Option<&Material> {
+        self.sub_materials.iter().find(|m| m.name == name)
+    }
+
+    /// Iterate over the textures of this material and all sub-materials.
+    pub fn all_textures(&self) -> Box<dyn Iterator<Item = &Texture> + '_> {
+        Box::new(
+            self.textures
+                .iter()
+                .chain(self.sub_materials.iter().flat_map(|m| m.all_textures())),
+        )
+    }
+}
+
+impl Texture {
+    /// Create a texture slot.
+    pub fn new(map: impl Into<String>, file: impl Into<String>) -> Self {
+        Self {
+            map: map.into(),
+            file: file.into(),
+            ..Self::default()
+        }
+    }
+
+    fn from_node(node: &BuilderNode) -> Self {
+        let mut attrs = Attributes(node.attributes.clone());
+        Self {
+            map: attrs.take("Map").unwrap_or_default(),
+            file: attrs.take("File").unwrap_or_default(),
+            extra_attributes: attrs.0,
+            children: node.children.clone(),
+        }
+    }
+
+    fn to_node(&self) -> BuilderNode {
+        let mut node = BuilderNode::new("Texture")
+            .attr("Map", self.map.as_str())
+            .attr("File", self.file.as_str())
+            .children(self.children.iter().cloned());
+        node.attributes
+            .extend(self.extra_attributes.iter().cloned());
+        node
+    }
+}
+
+impl MaterialLayer {
+    fn from_node(node: &BuilderNode) -> Self {
+        let mut attrs = Attributes(node.attributes.clone());
+        Self {
+            name: attrs.take("Name").unwrap_or_default(),
+            path: attrs.take("Path").unwrap_or_default(),
+            sub_material: attrs.take("Submtl"),
+            tint_color: attrs.take_floats("TintColor"),
+            uv_tiling: attrs.take_parsed("UVTiling"),
+            extra_attributes: attrs.0,
+        }
+    }
+
+    fn to_node(&self) -> BuilderNode {
+        let mut attrs = Attributes(Vec::new());
+        attrs.put("Name", Some(self.name.clone()));
+        attrs.put("Path", Some(self.path.clone()));
+        attrs.put("Submtl", self.sub_material.clone());
+        attrs.put("TintColor", self.tint_color.map(format_floats));
+        attrs.put("UVTiling", self.uv_tiling.map(|v| v.to_string()));
+        attrs.0.extend(self.extra_attributes.iter().cloned());
+
+        let mut node = BuilderNode::new("Layer");
+        node.attributes = attrs.0;
+        node
+    }
+}
+
+/// Attribute list that known attributes are taken out of, leaving the rest.
+pub(crate) struct Attributes(pub(crate) Vec<(String, String)>);
+
+impl Attributes {
+    pub(crate) fn take(&mut self, key: &str) -> Option<String> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// Take an attribute that parses as `T`; unparsable values stay in the list.
+    pub(crate) fn take_parsed<T: std::str::FromStr>(&mut self, key: &str) -> Option<T> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        let value = self.0[index].1.trim().parse().ok()?;
+        self.0.remove(index);
+        Some(value)
+    }
+
+    /// Take a comma-separated list of exactly `N` floats, e.g. a color.
+    pub(crate) fn take_floats<const N: usize>(&mut self, key: &str) -> Option<[f32; N]> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        let value = parse_floats(&self.0[index].1)?;
+        self.0.remove(index);
+        Some(value)
+    }
+
+    pub(crate) fn put(&mut self, key: &str, value: Option<String>) {
+        if let Some(value) = value {
+            self.0.push((key.to_string(), value));
+        }
+    }
+}
+
+fn parse_floats<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let mut out = [0.0; N];
+    let mut parts = value.split(',');
+    for slot in &mut out {
+        *slot = parts.next()?.trim().parse().ok()?;
+    }
+    parts.next().is_none().then_some(out)
+}
+
+pub(crate) fn format_floats<const N: usize>(values: [f32; N]) -> String {
+    values.map(|v| v.to_string()).join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HULL: &str = r#"<Material MtlFlags="524544" vertModifType="0">
+  <SubMaterials>
+    <Material Name="paint" MtlFlags="524416" Shader="LayerBlend" StringGenMask="%BLENDLAYER" SurfaceType="metal" Diffuse="1,0.5,0.25" Opacity="1" Shininess="255" GlowAmount="0">
+      <Textures>
+        <Texture Map="Diffuse" File="textures/paint_diff.dds"/>
+        <Texture Map="Bumpmap" File="textures/paint_ddna.dds">
+          <TexMod TileU="2" TileV="2"/>
+        </Texture>
+      </Textures>
+      <PublicParams BlendFactor="8" BlendFalloff="1"/>
+      <MatLayers>
+        <Layer Name="Primary" Path="materials/base.mtl" Submtl="steel" TintColor="0.5,0.5,0.5" UVTiling="4" GlossMult="1"/>
+      </MatLayers>
+      <VertexDeform Type="0"/>
+    </Material>
+    <Material Name="glass" Shader="Glass" Diffuse="bogus"/>
+  </SubMaterials>
+</Material>"#;
+
+    #[test]
+    fn test_typed_fields() {
+        let material = Material::parse(HULL.as_bytes()).unwrap();
+        assert_eq!(material.flags, 524544);
+        assert_eq!(material.sub_materials.len(), 2);
+
+        let paint = material.sub_material("paint").unwrap();
+        assert_eq!(paint.shader.as_deref(), Some("LayerBlend"));
+        assert_eq!(paint.diffuse, Some([1.0, 0.5, 0.25]));
+        assert_eq!(paint.texture("Bumpmap").unwrap().children[0].tag, "TexMod");
+        assert_eq!(paint.shader_param("BlendFactor"), Some("8"));
+        assert_eq!(paint.layers[0].sub_material.as_deref(), Some("steel"));
+        assert_eq!(paint.layers[0].uv_tiling, Some(4.0));
+        assert_eq!(
+            paint.layers[0].extra_attributes,
+            [("GlossMult".into(), "1".into())]
+        );
+        assert_eq!(paint.extra_children[0].tag, "VertexDeform");
+
+        // Unparsable values are kept verbatim
+        let glass = material.sub_material("glass").unwrap();
+        assert_eq!(glass.diffuse, None);
+        assert_eq!(glass.extra_attributes, [("Diffuse".into(), "bogus".into())]);
+
+        let files: Vec<_> = material.all_textures().map(|t| t.file.as_str()).collect();
+        assert_eq!(
+            files,
+            ["textures/paint_diff.dds", "textures/paint_ddna.dds"]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut material = Material::parse(HULL.as_bytes()).unwrap();
+        material.sub_materials[1]
+            .textures
+            .push(Texture::new("Diffuse", "textures/glass.dds"));
+
+        let reparsed = Material::parse(&material.to_cryxml().unwrap()).unwrap();
+        assert_eq!(reparsed, material);
+    }
+
+    #[test]
+    fn test_rejects_other_roots() {
+        assert!(Material::parse(b"<Character/>").is_err());
+    }
+}
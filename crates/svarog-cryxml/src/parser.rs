@@ -2,7 +2,7 @@
 
 use std::io::Write;
 
-use svarog_common::BinaryReader;
+use svarog_common::{BinaryReader, ResultExt};
 use zerocopy::FromBytes;
 
 use crate::{CryXmlAttribute, CryXmlHeader, CryXmlNode, Error, Result};
@@ -34,6 +34,10 @@ impl CryXml {
     /// # Returns
     ///
     /// A parsed `CryXml` document, or an error if parsing fails.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "cryxml_parse", level = "trace", skip_all, fields(size = data.len()))
+    )]
     pub fn parse(data: &[u8]) -> Result<Self> {
         // Check magic
         if !Self::is_cryxml(data) {
@@ -42,61 +46,64 @@ impl CryXml {
             });
         }
 
-        let mut reader = BinaryReader::new(&data[CryXmlHeader::MAGIC_LEN..]);
-        let header: CryXmlHeader = reader.read_struct()?;
+        let mut reader = BinaryReader::new_at(data, CryXmlHeader::MAGIC_LEN);
+        let header: CryXmlHeader = reader
+            .read_struct()
+            .in_section("header", CryXmlHeader::MAGIC_LEN)?;
 
         // Use positions from header to read each section
         // Positions are relative to start of file (after magic)
 
         // Read nodes at node_table_position
-        let node_start = header.node_table_position as usize;
         let node_size = std::mem::size_of::<CryXmlNode>();
+        let node_data = table_bytes(
+            data,
+            header.node_table_position as usize,
+            (header.node_count as usize).saturating_mul(node_size),
+            "node table",
+        )?;
         let mut nodes = Vec::with_capacity(header.node_count as usize);
-        for i in 0..header.node_count as usize {
-            let offset = node_start + i * node_size;
-            if offset + node_size > data.len() {
-                return Err(Error::Xml(format!("Node {} out of bounds", i)));
-            }
-            let node_data = &data[offset..offset + node_size];
-            let node = CryXmlNode::read_from_bytes(node_data)
+        for chunk in node_data.chunks_exact(node_size) {
+            let node = CryXmlNode::read_from_bytes(chunk)
                 .map_err(|_| Error::Xml("Failed to read node".to_string()))?;
             nodes.push(node);
         }
 
         // Read child indices at child_table_position
-        let child_start = header.child_table_position as usize;
-        let mut child_indices = Vec::with_capacity(header.child_count as usize);
-        for i in 0..header.child_count as usize {
-            let offset = child_start + i * 4;
-            if offset + 4 > data.len() {
-                return Err(Error::Xml(format!("Child index {} out of bounds", i)));
-            }
-            let idx = i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
-            child_indices.push(idx);
-        }
+        let child_data = table_bytes(
+            data,
+            header.child_table_position as usize,
+            (header.child_count as usize).saturating_mul(4),
+            "child table",
+        )?;
+        let child_indices: Vec<i32> = child_data
+            .chunks_exact(4)
+            .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
 
         // Read attributes at attribute_table_position
-        let attr_start = header.attribute_table_position as usize;
         let attr_size = std::mem::size_of::<CryXmlAttribute>();
+        let attr_data = table_bytes(
+            data,
+            header.attribute_table_position as usize,
+            (header.attribute_count as usize).saturating_mul(attr_size),
+            "attribute table",
+        )?;
         let mut attributes = Vec::with_capacity(header.attribute_count as usize);
-        for i in 0..header.attribute_count as usize {
-            let offset = attr_start + i * attr_size;
-            if offset + attr_size > data.len() {
-                return Err(Error::Xml(format!("Attribute {} out of bounds", i)));
-            }
-            let attr_data = &data[offset..offset + attr_size];
-            let attr = CryXmlAttribute::read_from_bytes(attr_data)
+        for chunk in attr_data.chunks_exact(attr_size) {
+            let attr = CryXmlAttribute::read_from_bytes(chunk)
                 .map_err(|_| Error::Xml("Failed to read attribute".to_string()))?;
             attributes.push(attr);
         }
 
         // Read string data at string_data_position
-        let string_start = header.string_data_position as usize;
-        let string_end = string_start + header.string_data_size as usize;
-        if string_end > data.len() {
-            return Err(Error::Xml("String data out of bounds".to_string()));
-        }
-        let string_data = data[string_start..string_end].to_vec();
+        let string_data = table_bytes(
+            data,
+            header.string_data_position as usize,
+            header.string_data_size as usize,
+            "string data",
+        )?
+        .to_vec();
 
         Ok(Self {
             nodes,
@@ -223,6 +230,12 @@ impl CryXml {
 
                     // Check for text content
                     let content = self.get_string(node.content_string_offset)?;
+
+                    // Comment and processing instruction markers from from_xml_with
+                    if let Some(event) = crate::builder::markup_event(tag_name, content) {
+                        writer.write_event(event).map_err(|e| Error::Xml(e.to_string()))?;
+                        continue;
+                    }
                     let has_content = !content.is_empty();
 
                     // Create start element
@@ -315,9 +328,29 @@ impl CryXml {
     }
 }
 
+/// Get `len` bytes of the section starting at `start`, or an error naming the
+/// section if it runs past the end of the file.
+fn table_bytes<'a>(
+    data: &'a [u8],
+    start: usize,
+    len: usize,
+    section: &'static str,
+) -> svarog_common::Result<&'a [u8]> {
+    match start.checked_add(len) {
+        Some(end) if end <= data.len() => Ok(&data[start..end]),
+        _ => Err(svarog_common::Error::UnexpectedEof {
+            offset: start,
+            needed: len,
+            available: data.len().saturating_sub(start),
+        }
+        .in_section(section, start)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use svarog_common::ErrorLocation;
 
     #[test]
     fn test_is_cryxml() {
@@ -331,4 +364,11 @@ mod tests {
         let result = CryXml::parse(b"InvalidMagic");
         assert!(matches!(result, Err(Error::InvalidMagic { .. })));
     }
+
+    #[test]
+    fn test_truncated_header_reports_section() {
+        let err = CryXml::parse(b"CryXmlB\0\x01\x02").unwrap_err();
+        assert_eq!(err.section(), Some("header"));
+        assert_eq!(err.offset(), Some(CryXmlHeader::MAGIC_LEN));
+    }
 }
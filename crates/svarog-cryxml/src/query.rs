@@ -0,0 +1,495 @@
+//! XPath-like queries over CryXml documents.
+//!
+//! Supports a small subset of XPath that covers typical lookups in material
+//! and character files:
+//!
+//! - `Material/Textures/Texture` - child steps, the first step matches the root
+//! - `//Texture` - descendants at any depth
+//! - `*` - any element
+//! - `Texture[@Map='Diffuse']` - attribute value predicate (`"` quotes work too)
+//! - `Texture[@Map]` - attribute presence predicate
+//! - `Texture[2]` - position among the matches of a step (1-based)
+//! - `.../@File`, `.../@*` - attribute selection as the last step
+//! - `.../text()` - element content as the last step
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::{CryXml, Selected};
+//!
+//! let cryxml = CryXml::parse(&std::fs::read("material.mtl")?)?;
+//! for selected in cryxml.select("Material/Textures/Texture[@Map='Diffuse']/@File")? {
+//!     if let Selected::Attribute { value, .. } = selected {
+//!         println!("{}", value);
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::builder::BuilderNode;
+use crate::{CryXml, CryXmlDocument, CryXmlNode, Error, Result};
+
+/// A query match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selected<'a, N> {
+    /// A matching element.
+    Node(N),
+    /// A matching attribute of an element.
+    Attribute {
+        /// Element holding the attribute.
+        node: N,
+        /// Attribute name.
+        name: &'a str,
+        /// Attribute value.
+        value: &'a str,
+    },
+    /// The text content of a matching element.
+    Text {
+        /// Element holding the content.
+        node: N,
+        /// Text content.
+        text: &'a str,
+    },
+}
+
+impl<'a, N> Selected<'a, N> {
+    /// The selected string: attribute value or text content. `None` for elements.
+    pub fn value(&self) -> Option<&'a str> {
+        match self {
+            Self::Node(_) => None,
+            Self::Attribute { value, .. } => Some(value),
+            Self::Text { text, .. } => Some(text),
+        }
+    }
+}
+
+/// A parsed query expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CryXmlPath {
+    steps: Vec<Step>,
+    target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    descendant: bool,
+    /// `None` matches any tag.
+    tag: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    HasAttribute(String),
+    AttributeEquals(String, String),
+    Position(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Node,
+    /// `None` selects all attributes.
+    Attribute(Option<String>),
+    Text,
+}
+
+impl CryXmlPath {
+    /// Parse a query expression.
+    pub fn parse(expr: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidQuery(format!("{}: {}", reason, expr));
+
+        let mut steps = Vec::new();
+        let mut target = Target::Node;
+        let mut rest = expr.trim();
+        let mut descendant = false;
+
+        if let Some(r) = rest.strip_prefix("//") {
+            descendant = true;
+            rest = r;
+        } else if let Some(r) = rest.strip_prefix('/') {
+            rest = r;
+        }
+
+        while !rest.is_empty() {
+            if target != Target::Node {
+                return Err(invalid("attribute or text() must be the last step"));
+            }
+
+            let end = step_end(rest);
+            let step = &rest[..end];
+            rest = &rest[end..];
+
+            if let Some(name) = step.strip_prefix('@') {
+                target = Target::Attribute((name != "*").then(|| name.to_string()));
+            } else if step == "text()" {
+                target = Target::Text;
+            } else {
+                steps.push(parse_step(step, descendant).ok_or_else(|| invalid("invalid step"))?);
+            }
+
+            descendant = false;
+            if let Some(r) = rest.strip_prefix("//") {
+                descendant = true;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('/') {
+                rest = r;
+            }
+            if rest.is_empty() && expr.ends_with('/') {
+                return Err(invalid("trailing '/'"));
+            }
+        }
+
+        if steps.is_empty() {
+            return Err(invalid("no element step"));
+        }
+
+        Ok(Self { steps, target })
+    }
+}
+
+/// Byte index where the current step ends: the next `/` outside of brackets.
+fn step_end(s: &str) -> usize {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('[', None) => depth += 1,
+            (']', None) => depth = depth.saturating_sub(1),
+            ('/', None) if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn parse_step(step: &str, descendant: bool) -> Option<Step> {
+    let (name, mut rest) = match step.find('[') {
+        Some(i) => (&step[..i], &step[i..]),
+        None => (step, ""),
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut predicates = Vec::new();
+    while !rest.is_empty() {
+        let inner = rest.strip_prefix('[')?;
+        let close = step_close(inner)?;
+        predicates.push(parse_predicate(inner[..close].trim())?);
+        rest = &inner[close + 1..];
+    }
+
+    Some(Step {
+        descendant,
+        tag: (name != "*").then(|| name.to_string()),
+        predicates,
+    })
+}
+
+/// Index of the `]` closing a predicate, skipping quoted strings.
+fn step_close(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (']', None) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_predicate(pred: &str) -> Option<Predicate> {
+    if let Some(attr) = pred.strip_prefix('@') {
+        return match attr.split_once('=') {
+            Some((name, value)) => {
+                let value = value.trim();
+                let quote = value.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+                let value = value.strip_prefix(quote)?.strip_suffix(quote)?;
+                Some(Predicate::AttributeEquals(
+                    name.trim().to_string(),
+                    value.to_string(),
+                ))
+            }
+            None => Some(Predicate::HasAttribute(attr.to_string())),
+        };
+    }
+    match pred.parse::<usize>() {
+        Ok(n) if n > 0 => Some(Predicate::Position(n)),
+        _ => None,
+    }
+}
+
+/// Read access to a node of either tree representation.
+trait QueryTree<'a> {
+    type Node: Copy;
+
+    fn tag(&self, node: Self::Node) -> &'a str;
+    fn content(&self, node: Self::Node) -> &'a str;
+    fn attributes(&self, node: Self::Node) -> Vec<(&'a str, &'a str)>;
+    fn children(&self, node: Self::Node) -> Vec<Self::Node>;
+}
+
+struct BinaryTree<'a>(&'a CryXml);
+
+impl<'a> QueryTree<'a> for BinaryTree<'a> {
+    type Node = &'a CryXmlNode;
+
+    fn tag(&self, node: Self::Node) -> &'a str {
+        self.0.get_string(node.tag_string_offset).unwrap_or("")
+    }
+
+    fn content(&self, node: Self::Node) -> &'a str {
+        self.0.get_string(node.content_string_offset).unwrap_or("")
+    }
+
+    fn attributes(&self, node: Self::Node) -> Vec<(&'a str, &'a str)> {
+        self.0
+            .node_attributes(node)
+            .iter()
+            .filter_map(|a| {
+                Some((
+                    self.0.get_string(a.key_string_offset).ok()?,
+                    self.0.get_string(a.value_string_offset).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    fn children(&self, node: Self::Node) -> Vec<Self::Node> {
+        self.0.children(node).collect()
+    }
+}
+
+struct OwnedTree;
+
+impl<'a> QueryTree<'a> for OwnedTree {
+    type Node = &'a BuilderNode;
+
+    fn tag(&self, node: Self::Node) -> &'a str {
+        &node.tag
+    }
+
+    fn content(&self, node: Self::Node) -> &'a str {
+        &node.content
+    }
+
+    fn attributes(&self, node: Self::Node) -> Vec<(&'a str, &'a str)> {
+        node.attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect()
+    }
+
+    fn children(&self, node: Self::Node) -> Vec<Self::Node> {
+        node.children.iter().collect()
+    }
+}
+
+fn evaluate<'a, T: QueryTree<'a>>(
+    tree: &T,
+    root: T::Node,
+    path: &CryXmlPath,
+) -> Vec<Selected<'a, T::Node>> {
+    // The first step is matched against the root itself (or any node for `//`)
+    let mut current: Vec<T::Node> = Vec::new();
+    for (i, step) in path.steps.iter().enumerate() {
+        let candidates: Vec<T::Node> = if i == 0 {
+            if step.descendant {
+                let mut all = vec![root];
+                collect_descendants(tree, root, &mut all);
+                all
+            } else {
+                vec![root]
+            }
+        } else {
+            let mut next = Vec::new();
+            for &node in &current {
+                if step.descendant {
+                    collect_descendants(tree, node, &mut next);
+                } else {
+                    next.extend(tree.children(node));
+                }
+            }
+            next
+        };
+
+        current = apply_step(tree, step, candidates);
+        if current.is_empty() {
+            break;
+        }
+    }
+
+    let mut out = Vec::new();
+    for node in current {
+        match &path.target {
+            Target::Node => out.push(Selected::Node(node)),
+            Target::Text => out.push(Selected::Text {
+                node,
+                text: tree.content(node),
+            }),
+            Target::Attribute(name) => {
+                for (key, value) in tree.attributes(node) {
+                    if name.as_deref().map_or(true, |n| n == key) {
+                        out.push(Selected::Attribute {
+                            node,
+                            name: key,
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+fn collect_descendants<'a, T: QueryTree<'a>>(tree: &T, node: T::Node, out: &mut Vec<T::Node>) {
+    for child in tree.children(node) {
+        out.push(child);
+        collect_descendants(tree, child, out);
+    }
+}
+
+fn apply_step<'a, T: QueryTree<'a>>(
+    tree: &T,
+    step: &Step,
+    candidates: Vec<T::Node>,
+) -> Vec<T::Node> {
+    let mut matched: Vec<T::Node> = candidates
+        .into_iter()
+        .filter(|&node| {
+            step.tag
+                .as_deref()
+                .map_or(true, |tag| tree.tag(node) == tag)
+        })
+        .collect();
+
+    for predicate in &step.predicates {
+        matched = match predicate {
+            Predicate::Position(n) => matched.get(n - 1).copied().into_iter().collect(),
+            Predicate::HasAttribute(name) => matched
+                .into_iter()
+                .filter(|&node| tree.attributes(node).iter().any(|(k, _)| k == name))
+                .collect(),
+            Predicate::AttributeEquals(name, value) => matched
+                .into_iter()
+                .filter(|&node| {
+                    tree.attributes(node)
+                        .iter()
+                        .any(|(k, v)| k == name && v == value)
+                })
+                .collect(),
+        };
+    }
+
+    matched
+}
+
+impl CryXml {
+    /// Select nodes, attributes or text with an XPath-like expression.
+    ///
+    /// See the [`query`](crate::query) module for the supported syntax.
+    pub fn select(&self, expr: &str) -> Result<Vec<Selected<'_, &CryXmlNode>>> {
+        let path = CryXmlPath::parse(expr)?;
+        Ok(self.select_path(&path))
+    }
+
+    /// Select with a pre-parsed expression, to reuse it across many files.
+    pub fn select_path(&self, path: &CryXmlPath) -> Vec<Selected<'_, &CryXmlNode>> {
+        match self.root() {
+            Some(root) => evaluate(&BinaryTree(self), root, path),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl BuilderNode {
+    /// Select nodes, attributes or text below this node with an XPath-like
+    /// expression whose first step matches this node.
+    pub fn select(&self, expr: &str) -> Result<Vec<Selected<'_, &BuilderNode>>> {
+        let path = CryXmlPath::parse(expr)?;
+        Ok(self.select_path(&path))
+    }
+
+    /// Select with a pre-parsed expression.
+    pub fn select_path(&self, path: &CryXmlPath) -> Vec<Selected<'_, &BuilderNode>> {
+        evaluate(&OwnedTree, self, path)
+    }
+}
+
+impl CryXmlDocument {
+    /// Select nodes, attributes or text with an XPath-like expression.
+    pub fn select(&self, expr: &str) -> Result<Vec<Selected<'_, &BuilderNode>>> {
+        self.root().select(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CryXmlBuilder;
+
+    const MATERIAL: &str = r#"<Material Name="Hull">
+  <SubMaterials>
+    <Material Name="Paint">
+      <Textures>
+        <Texture Map="Diffuse" File="paint_diff.dds"/>
+        <Texture Map="Bumpmap" File="paint_ddna.dds"/>
+      </Textures>
+    </Material>
+    <Material Name="Glass">
+      <Textures>
+        <Texture Map="Diffuse" File="glass_diff.dds"/>
+      </Textures>
+    </Material>
+  </SubMaterials>
+</Material>"#;
+
+    fn values<N>(selected: &[Selected<'_, N>]) -> Vec<String> {
+        selected
+            .iter()
+            .filter_map(|s| s.value())
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_binary() {
+        let bytes = CryXmlBuilder::from_xml(MATERIAL).unwrap().build().unwrap();
+        let cryxml = CryXml::parse(&bytes).unwrap();
+
+        let files = cryxml
+            .select("Material/SubMaterials/Material/Textures/Texture[@Map='Diffuse']/@File")
+            .unwrap();
+        assert_eq!(values(&files), ["paint_diff.dds", "glass_diff.dds"]);
+
+        let files = cryxml.select("//Texture[@Map=\"Bumpmap\"]/@File").unwrap();
+        assert_eq!(values(&files), ["paint_ddna.dds"]);
+
+        let names = cryxml.select("Material/SubMaterials/*[2]/@Name").unwrap();
+        assert_eq!(values(&names), ["Glass"]);
+
+        assert_eq!(cryxml.select("//Texture").unwrap().len(), 3);
+        assert!(cryxml.select("Other/Textures").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_select_document() {
+        let doc = CryXmlDocument::from_xml(MATERIAL).unwrap();
+        let attrs = doc.select("//Material[@Name='Glass']/@*").unwrap();
+        assert_eq!(values(&attrs), ["Glass"]);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert!(CryXmlPath::parse("").is_err());
+        assert!(CryXmlPath::parse("A/@B/C").is_err());
+        assert!(CryXmlPath::parse("A[@B='x").is_err());
+        assert!(CryXmlPath::parse("A[0]").is_err());
+        assert!(CryXmlPath::parse("A/").is_err());
+    }
+}
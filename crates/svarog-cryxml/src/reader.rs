@@ -0,0 +1,390 @@
+//! Streaming CryXmlB reader.
+//!
+//! [`CryXml::parse`] copies the node, child and attribute tables and the
+//! string data into owned buffers. For bulk scanning, e.g. collecting texture
+//! paths from tens of thousands of `.mtl` files, [`CryXmlReader`] walks the
+//! tables in place and reports the document through a [`CryXmlVisitor`]:
+//! nothing is allocated per node and all strings borrow from the input.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_cryxml::{CryXmlReader, CryXmlVisitor, Visit};
+//!
+//! #[derive(Default)]
+//! struct TextureFiles(Vec<String>);
+//!
+//! impl CryXmlVisitor for TextureFiles {
+//!     fn start_element(&mut self, tag: &str, _depth: usize) -> Visit {
+//!         if tag == "Texture" || tag == "Material" || tag == "Textures" || tag == "SubMaterials" {
+//!             Visit::Continue
+//!         } else {
+//!             Visit::SkipChildren
+//!         }
+//!     }
+//!
+//!     fn attribute(&mut self, key: &str, value: &str) {
+//!         if key == "File" {
+//!             self.0.push(value.to_string());
+//!         }
+//!     }
+//! }
+//!
+//! let data = std::fs::read("material.mtl")?;
+//! let mut files = TextureFiles::default();
+//! CryXmlReader::new(&data)?.read(&mut files)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use zerocopy::FromBytes;
+
+use crate::{CryXml, CryXmlAttribute, CryXmlHeader, CryXmlNode, Error, Result};
+
+/// What the reader should do after [`CryXmlVisitor::start_element`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visit {
+    /// Report the element's attributes, content and children.
+    #[default]
+    Continue,
+    /// Report the element's attributes and content, but not its children.
+    SkipChildren,
+    /// Stop reading the document.
+    Stop,
+}
+
+/// Callbacks for [`CryXmlReader::read`].
+///
+/// For every element the reader calls `start_element`, then `attribute` for
+/// each attribute, `content` if the element has text, the callbacks of its
+/// children, and finally `end_element`. All methods have empty defaults.
+pub trait CryXmlVisitor {
+    /// An element starts. `depth` is 0 for the root.
+    fn start_element(&mut self, tag: &str, depth: usize) -> Visit {
+        let _ = (tag, depth);
+        Visit::Continue
+    }
+
+    /// An attribute of the element that started last.
+    fn attribute(&mut self, key: &str, value: &str) {
+        let _ = (key, value);
+    }
+
+    /// Text content of the element that started last.
+    fn content(&mut self, text: &str) {
+        let _ = text;
+    }
+
+    /// An element ends.
+    fn end_element(&mut self, tag: &str) {
+        let _ = tag;
+    }
+}
+
+/// Event-based CryXmlB reader over borrowed bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct CryXmlReader<'a> {
+    data: &'a [u8],
+    header: CryXmlHeader,
+    strings: &'a [u8],
+}
+
+/// Traversal state of one open element.
+struct Frame {
+    tag_offset: u32,
+    first_child: usize,
+    child_count: usize,
+    next_child: usize,
+}
+
+impl<'a> CryXmlReader<'a> {
+    /// Validate the header of a CryXmlB file.
+    ///
+    /// The node, attribute and child tables are bounds-checked lazily while
+    /// reading.
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        if !CryXml::is_cryxml(data) {
+            return Err(Error::InvalidMagic {
+                actual: data[..CryXmlHeader::MAGIC_LEN.min(data.len())].to_vec(),
+            });
+        }
+
+        let header = CryXmlHeader::read_from_prefix(&data[CryXmlHeader::MAGIC_LEN..])
+            .map_err(|_| Error::Xml("Header out of bounds".to_string()))?
+            .0;
+
+        let string_start = header.string_data_position as usize;
+        let string_end = string_start + header.string_data_size as usize;
+        let strings = data
+            .get(string_start..string_end)
+            .ok_or_else(|| Error::Xml("String data out of bounds".to_string()))?;
+
+        Ok(Self {
+            data,
+            header,
+            strings,
+        })
+    }
+
+    /// Number of elements in the document.
+    pub fn node_count(&self) -> usize {
+        self.header.node_count as usize
+    }
+
+    /// Walk the document, reporting it to `visitor`.
+    pub fn read<V: CryXmlVisitor + ?Sized>(&self, visitor: &mut V) -> Result<()> {
+        if self.header.node_count == 0 {
+            return Ok(());
+        }
+
+        let mut stack: Vec<Frame> = Vec::with_capacity(32);
+        match self.open(visitor, 0, 0)? {
+            Opened::Frame(frame) => stack.push(frame),
+            Opened::Closed | Opened::Stop => return Ok(()),
+        }
+
+        while let Some(frame) = stack.last_mut() {
+            if frame.next_child == frame.child_count {
+                let tag_offset = frame.tag_offset;
+                stack.pop();
+                visitor.end_element(self.string(tag_offset)?);
+                continue;
+            }
+
+            let child_index = self.child_index(frame.first_child + frame.next_child)?;
+            frame.next_child += 1;
+
+            let depth = stack.len();
+            match self.open(visitor, child_index, depth)? {
+                Opened::Frame(child) => stack.push(child),
+                Opened::Closed => {}
+                Opened::Stop => return Ok(()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Report an element's start, attributes and content, and end it right
+    /// away if its children are not visited.
+    fn open<V: CryXmlVisitor + ?Sized>(
+        &self,
+        visitor: &mut V,
+        index: usize,
+        depth: usize,
+    ) -> Result<Opened> {
+        // A tree can't be deeper than its node count; anything else is a cycle
+        if depth >= self.node_count() {
+            return Err(Error::Xml(format!("Node {} is part of a cycle", index)));
+        }
+
+        let node = self.node(index)?;
+        let tag = self.string(node.tag_string_offset)?;
+
+        let visit = visitor.start_element(tag, depth);
+        if visit == Visit::Stop {
+            return Ok(Opened::Stop);
+        }
+
+        let first_attribute = node.first_attribute_index.max(0) as usize;
+        for i in first_attribute..first_attribute + node.attribute_count as usize {
+            let attr = self.attribute(i)?;
+            visitor.attribute(
+                self.string(attr.key_string_offset)?,
+                self.string(attr.value_string_offset)?,
+            );
+        }
+
+        let content = self.string(node.content_string_offset)?;
+        if !content.is_empty() {
+            visitor.content(content);
+        }
+
+        if visit == Visit::SkipChildren || node.child_count == 0 {
+            visitor.end_element(tag);
+            return Ok(Opened::Closed);
+        }
+
+        Ok(Opened::Frame(Frame {
+            tag_offset: node.tag_string_offset,
+            first_child: node.first_child_index.max(0) as usize,
+            child_count: node.child_count as usize,
+            next_child: 0,
+        }))
+    }
+
+    fn node(&self, index: usize) -> Result<CryXmlNode> {
+        if index >= self.header.node_count as usize {
+            return Err(Error::NodeIndexOutOfBounds {
+                index: index as i32,
+                count: self.header.node_count as usize,
+            });
+        }
+        let size = std::mem::size_of::<CryXmlNode>();
+        let offset = self.header.node_table_position as usize + index * size;
+        self.data
+            .get(offset..offset + size)
+            .and_then(|bytes| CryXmlNode::read_from_bytes(bytes).ok())
+            .ok_or_else(|| Error::Xml(format!("Node {} out of bounds", index)))
+    }
+
+    fn attribute(&self, index: usize) -> Result<CryXmlAttribute> {
+        let size = std::mem::size_of::<CryXmlAttribute>();
+        let offset = self.header.attribute_table_position as usize + index * size;
+        if index >= self.header.attribute_count as usize {
+            return Err(Error::Xml(format!("Attribute {} out of bounds", index)));
+        }
+        self.data
+            .get(offset..offset + size)
+            .and_then(|bytes| CryXmlAttribute::read_from_bytes(bytes).ok())
+            .ok_or_else(|| Error::Xml(format!("Attribute {} out of bounds", index)))
+    }
+
+    fn child_index(&self, slot: usize) -> Result<usize> {
+        let offset = self.header.child_table_position as usize + slot * 4;
+        if slot >= self.header.child_count as usize {
+            return Err(Error::Xml(format!("Child index {} out of bounds", slot)));
+        }
+        let bytes = self
+            .data
+            .get(offset..offset + 4)
+            .ok_or_else(|| Error::Xml(format!("Child index {} out of bounds", slot)))?;
+        let index = i32::from_le_bytes(bytes.try_into().unwrap());
+        usize::try_from(index).map_err(|_| Error::NodeIndexOutOfBounds {
+            index,
+            count: self.header.node_count as usize,
+        })
+    }
+
+    fn string(&self, offset: u32) -> Result<&'a str> {
+        let start = offset as usize;
+        if start >= self.strings.len() {
+            return Err(Error::StringOffsetOutOfBounds {
+                offset,
+                size: self.strings.len(),
+            });
+        }
+        let rest = &self.strings[start..];
+        let end = svarog_common::memchr::memchr(0, rest).unwrap_or(rest.len());
+        std::str::from_utf8(&rest[..end]).map_err(Error::Utf8)
+    }
+}
+
+enum Opened {
+    /// The element has children to visit.
+    Frame(Frame),
+    /// The element was started and ended.
+    Closed,
+    /// The visitor stopped reading.
+    Stop,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CryXmlBuilder;
+
+    #[derive(Default)]
+    struct Events {
+        log: Vec<String>,
+        skip: Option<&'static str>,
+        stop: Option<&'static str>,
+    }
+
+    impl CryXmlVisitor for Events {
+        fn start_element(&mut self, tag: &str, depth: usize) -> Visit {
+            self.log.push(format!("<{}:{}", tag, depth));
+            if self.stop == Some(tag) {
+                Visit::Stop
+            } else if self.skip == Some(tag) {
+                Visit::SkipChildren
+            } else {
+                Visit::Continue
+            }
+        }
+
+        fn attribute(&mut self, key: &str, value: &str) {
+            self.log.push(format!("{}={}", key, value));
+        }
+
+        fn content(&mut self, text: &str) {
+            self.log.push(format!("'{}'", text));
+        }
+
+        fn end_element(&mut self, tag: &str) {
+            self.log.push(format!("{}>", tag));
+        }
+    }
+
+    fn document() -> Vec<u8> {
+        CryXmlBuilder::from_xml(
+            r#"<Material Name="Hull"><Textures><Texture Map="Diffuse"/><Note>hi</Note></Textures><Extra/></Material>"#,
+        )
+        .unwrap()
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_events_in_document_order() {
+        let data = document();
+        let mut events = Events::default();
+        CryXmlReader::new(&data).unwrap().read(&mut events).unwrap();
+        assert_eq!(
+            events.log,
+            [
+                "<Material:0",
+                "Name=Hull",
+                "<Textures:1",
+                "<Texture:2",
+                "Map=Diffuse",
+                "Texture>",
+                "<Note:2",
+                "'hi'",
+                "Note>",
+                "Textures>",
+                "<Extra:1",
+                "Extra>",
+                "Material>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_and_stop() {
+        let data = document();
+        let reader = CryXmlReader::new(&data).unwrap();
+
+        let mut events = Events {
+            skip: Some("Textures"),
+            ..Default::default()
+        };
+        reader.read(&mut events).unwrap();
+        assert_eq!(
+            events.log,
+            [
+                "<Material:0",
+                "Name=Hull",
+                "<Textures:1",
+                "Textures>",
+                "<Extra:1",
+                "Extra>",
+                "Material>"
+            ]
+        );
+
+        let mut events = Events {
+            stop: Some("Texture"),
+            ..Default::default()
+        };
+        reader.read(&mut events).unwrap();
+        assert_eq!(events.log.last().map(String::as_str), Some("<Texture:2"));
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        assert!(matches!(
+            CryXmlReader::new(b"NotCryXml"),
+            Err(Error::InvalidMagic { .. })
+        ));
+    }
+}
@@ -0,0 +1,485 @@
+//! Serde serialization to CryXml documents.
+//!
+//! The inverse of the mapping used by [`from_cryxml`](crate::from_cryxml):
+//!
+//! - The root value must be a struct; its name (after `#[serde(rename)]`)
+//!   becomes the root tag.
+//! - Scalar fields become attributes. Booleans are written as `1`/`0`.
+//! - Struct and map fields become child elements tagged with the field name.
+//! - Sequences of scalars become one comma-separated attribute; sequences
+//!   of structs become repeated child elements.
+//! - A field named `$text` becomes the element's text content.
+//! - `None` fields are omitted.
+//!
+//! Enums are supported as unit variants only, written as the variant name.
+
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::builder::{BuilderNode, CryXmlBuilder};
+use crate::de::TEXT_KEY;
+use crate::{Error, Result};
+
+/// Serialize a value to CryXmlB bytes.
+pub fn to_cryxml<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    CryXmlBuilder::new(to_node(value)?).build()
+}
+
+/// Serialize a value to an element tree.
+pub fn to_node<T: Serialize + ?Sized>(value: &T) -> Result<BuilderNode> {
+    match value.serialize(ValueSerializer)? {
+        Value::Element(node) if !node.tag.is_empty() => Ok(node),
+        _ => Err(Error::Serde("root value must be a struct".to_string())),
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+/// Intermediate form of a serialized value, placed by its parent.
+enum Value {
+    None,
+    Scalar(String),
+    Element(BuilderNode),
+    Seq(Vec<Value>),
+}
+
+/// Place a field value into `node` under `key`.
+fn place(node: &mut BuilderNode, key: &str, value: Value) -> Result<()> {
+    match value {
+        Value::None => {}
+        Value::Scalar(text) if key == TEXT_KEY => node.content = text,
+        Value::Scalar(text) => node.attributes.push((key.to_string(), text)),
+        Value::Element(mut child) => {
+            child.tag = key.to_string();
+            node.children.push(child);
+        }
+        Value::Seq(items) if items.iter().all(|v| matches!(v, Value::Scalar(_))) => {
+            let joined = items
+                .into_iter()
+                .filter_map(|v| match v {
+                    Value::Scalar(text) => Some(text),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            place(node, key, Value::Scalar(joined))?;
+        }
+        Value::Seq(items) => {
+            for item in items {
+                match item {
+                    Value::None => {}
+                    Value::Scalar(text) => node.children.push(BuilderNode::new(key).content(text)),
+                    Value::Element(_) => place(node, key, item)?,
+                    Value::Seq(_) => {
+                        return Err(Error::Serde(format!("nested sequence in field {:?}", key)))
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::Serde(format!("{} cannot be serialized to CryXml", what))
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = ElementSerializer;
+    type SerializeStruct = ElementSerializer;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Scalar(if v { "1" } else { "0" }.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::Scalar(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value> {
+        Err(unsupported("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::None)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value> {
+        Ok(Value::Element(BuilderNode::new(name)))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        Ok(Value::Scalar(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value> {
+        Err(unsupported("newtype enum variants"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(unsupported("tuple enum variants"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ElementSerializer> {
+        Ok(ElementSerializer {
+            node: BuilderNode::new(""),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<ElementSerializer> {
+        Ok(ElementSerializer {
+            node: BuilderNode::new(name),
+            key: None,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(unsupported("struct enum variants"))
+    }
+}
+
+struct SeqSerializer(Vec<Value>);
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.0.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Seq(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Builds an element from struct fields or map entries.
+struct ElementSerializer {
+    node: BuilderNode,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for ElementSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        match key.serialize(ValueSerializer)? {
+            Value::Scalar(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(Error::Serde("map keys must be scalars".to_string())),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Serde("map value serialized before its key".to_string()))?;
+        place(&mut self.node, &key, value.serialize(ValueSerializer)?)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Element(self.node))
+    }
+}
+
+impl ser::SerializeStruct for ElementSerializer {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        place(&mut self.node, key, value.serialize(ValueSerializer)?)
+    }
+
+    fn end(self) -> Result<Value> {
+        Ok(Value::Element(self.node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{from_cryxml, from_node, to_cryxml, to_node, CryXml};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename = "Material")]
+    struct Material {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Shader")]
+        shader: Option<String>,
+        #[serde(rename = "Diffuse")]
+        diffuse: [f32; 3],
+        #[serde(rename = "Opacity")]
+        opacity: f32,
+        #[serde(rename = "Hidden", default)]
+        hidden: bool,
+        #[serde(rename = "Quality")]
+        quality: Quality,
+        #[serde(rename = "Textures")]
+        textures: Option<Textures>,
+        #[serde(rename = "Note")]
+        note: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Quality {
+        Low,
+        High,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Textures {
+        #[serde(rename = "Texture", default)]
+        texture: Vec<Texture>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Texture {
+        #[serde(rename = "Map")]
+        map: String,
+        #[serde(rename = "File")]
+        file: String,
+    }
+
+    const XML: &str = r#"<Material Name="Hull" Diffuse="1,0.5,0.25" Opacity="0.75" Hidden="1" Quality="High">
+        <Textures>
+            <Texture Map="Diffuse" File="hull_diff.dds"/>
+            <Texture Map="Bumpmap" File="hull_ddna.dds"/>
+        </Textures>
+        <Note>rusty</Note>
+        <Unknown Ignored="yes"/>
+    </Material>"#;
+
+    #[test]
+    fn test_from_cryxml() {
+        let material: Material = from_cryxml(XML.as_bytes()).unwrap();
+        assert_eq!(material.name, "Hull");
+        assert_eq!(material.shader, None);
+        assert_eq!(material.diffuse, [1.0, 0.5, 0.25]);
+        assert_eq!(material.opacity, 0.75);
+        assert!(material.hidden);
+        assert_eq!(material.quality, Quality::High);
+        assert_eq!(material.note.as_deref(), Some("rusty"));
+
+        let textures = material.textures.unwrap().texture;
+        assert_eq!(textures.len(), 2);
+        assert_eq!(textures[1].file, "hull_ddna.dds");
+    }
+
+    #[test]
+    fn test_round_trip_through_cryxmlb() {
+        let material = Material {
+            name: "Glass".to_string(),
+            shader: Some("Glass".to_string()),
+            diffuse: [0.1, 0.2, 0.3],
+            opacity: 0.5,
+            hidden: false,
+            quality: Quality::Low,
+            textures: Some(Textures {
+                texture: vec![Texture {
+                    map: "Diffuse".to_string(),
+                    file: "glass.dds".to_string(),
+                }],
+            }),
+            note: Some("clear".to_string()),
+        };
+
+        let bytes = to_cryxml(&material).unwrap();
+        let xml = CryXml::parse(&bytes).unwrap().to_xml_string().unwrap();
+        assert!(xml.contains(r#"<Material Name="Glass" Shader="Glass" Diffuse="0.1,0.2,0.3""#));
+        assert!(xml.contains(r#"Note="clear""#));
+
+        let parsed: Material = from_cryxml(&bytes).unwrap();
+        assert_eq!(parsed, material);
+    }
+
+    #[test]
+    fn test_text_content_and_borrowing() {
+        #[derive(Deserialize)]
+        struct Entry<'a> {
+            name: &'a str,
+            #[serde(rename = "$text")]
+            text: &'a str,
+        }
+
+        #[derive(Serialize)]
+        struct Root {
+            #[serde(rename = "Entry")]
+            entries: Vec<String>,
+        }
+
+        let node = to_node(&Root {
+            entries: vec!["a".to_string(), "b".to_string()],
+        })
+        .unwrap();
+        assert_eq!(node.tag, "Root");
+        assert_eq!(node.attribute("Entry"), Some("a,b"));
+
+        let node = crate::builder::BuilderNode::new("Entry")
+            .attr("name", "x")
+            .content("body");
+        let entry: Entry = from_node(&node).unwrap();
+        assert_eq!((entry.name, entry.text), ("x", "body"));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(to_node(&42).is_err());
+        assert!(from_cryxml::<Material>(
+            br#"<Material Name="x" Diffuse="1,2,3" Opacity="nan?" Quality="Low"/>"#
+        )
+        .is_err());
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! Tests ALL data types and verifies exact value preservation.
 
+#![allow(clippy::approx_constant, clippy::redundant_guards)]
+
 use svarog_datacore::{DataCoreBuilder, DataCoreDatabase, DataType, Value};
 use svarog_common::CigGuid;
 
@@ -20,7 +22,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test 4: Nested structs
     test_nested_structs()?;
 
-    // Test 5: Edge cases (skip enums for now - not fully implemented)
+    // Test 5: Edge cases and enums
     test_edge_cases()?;
 
     // Test 6: Real file roundtrip
@@ -86,7 +88,7 @@ fn test_all_primitives() -> Result<(), Box<dyn std::error::Error>> {
     let mut errors = Vec::new();
 
     match record.get("boolVal") {
-        Some(Value::Bool(v)) if v == true => println!("  ✓ boolVal = true"),
+        Some(Value::Bool(v)) if v => println!("  ✓ boolVal = true"),
         other => errors.push(format!("boolVal: expected Bool(true), got {:?}", other)),
     }
 
@@ -448,6 +450,8 @@ fn test_edge_cases() -> Result<(), Box<dyn std::error::Error>> {
     builder.add_property(test_struct, "zeroFloat", DataType::Single);
     builder.add_property(test_struct, "negativeFloat", DataType::Single);
     builder.add_property(test_struct, "emptyGuid", DataType::Guid);
+    let quality = builder.add_enum("Quality", &["Low", "Medium", "High"]);
+    builder.add_enum_property(test_struct, "quality", quality);
 
     let record = builder.add_record("EdgeRecord", test_struct, "test/edges.xml");
     builder.set_string(record, "emptyString", "");
@@ -457,6 +461,7 @@ fn test_edge_cases() -> Result<(), Box<dyn std::error::Error>> {
     builder.set_float(record, "zeroFloat", 0.0);
     builder.set_float(record, "negativeFloat", -999.999);
     builder.set_guid(record, "emptyGuid", CigGuid::EMPTY);
+    builder.set_enum(record, "quality", "High")?;
 
     let path = "/tmp/test_edges.dcb";
     builder.write_to_file(path)?;
@@ -503,6 +508,11 @@ fn test_edge_cases() -> Result<(), Box<dyn std::error::Error>> {
         other => errors.push(format!("emptyGuid: expected empty GUID, got {:?}", other)),
     }
 
+    match record.get("quality") {
+        Some(Value::Enum(v)) if v == "High" => println!("  ✓ quality = High"),
+        other => errors.push(format!("quality: expected High, got {:?}", other)),
+    }
+
     std::fs::remove_file(path)?;
 
     if !errors.is_empty() {
@@ -27,6 +27,7 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+use std::fmt;
 use std::io::{self, Write};
 use std::path::Path;
 
@@ -34,14 +35,23 @@ use hashbrown::HashMap as FastHashMap;
 use rustc_hash::FxHasher;
 use std::hash::BuildHasherDefault;
 
-use svarog_common::CigGuid;
+use svarog_common::{BinaryWriter, CigGuid};
 
 use crate::structs::{
     DataCoreDataMapping, DataCoreEnumDefinition, DataCorePointer, DataCorePropertyDefinition,
     DataCoreRecord, DataCoreReference, DataCoreStringId, DataCoreStringId2,
     DataCoreStructDefinition,
 };
-use crate::DataType;
+use crate::{DataType, PoolType};
+
+mod merge;
+#[cfg(feature = "xml-export")]
+mod xml_import;
+
+pub use merge::{MergeStats, MergeStrategy};
+
+#[cfg(feature = "xml-export")]
+pub use xml_import::XmlImporter;
 
 type FxHashMap<K, V> = FastHashMap<K, V, BuildHasherDefault<FxHasher>>;
 
@@ -133,6 +143,7 @@ struct RecordDef {
     struct_index: u32,
     guid: CigGuid,
     instance_index: u16,
+    removed: bool,
 }
 
 /// String table for interning strings.
@@ -183,6 +194,16 @@ impl StringTable {
         offset
     }
 
+    /// Get the string starting at an offset.
+    fn get(&self, offset: i32) -> String {
+        let start = (offset.max(0) as usize).min(self.data.len());
+        let end = self.data[start..]
+            .iter()
+            .position(|&b| b == 0)
+            .map_or(self.data.len(), |p| start + p);
+        String::from_utf8_lossy(&self.data[start..end]).into_owned()
+    }
+
     /// Get the total size of the string table.
     fn len(&self) -> usize {
         self.data.len()
@@ -201,6 +222,231 @@ pub struct EnumHandle(pub u32);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RecordHandle(pub u32);
 
+/// A record identified by handle or GUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordKey {
+    Handle(RecordHandle),
+    Guid(CigGuid),
+}
+
+impl From<RecordHandle> for RecordKey {
+    fn from(handle: RecordHandle) -> Self {
+        Self::Handle(handle)
+    }
+}
+
+impl From<CigGuid> for RecordKey {
+    fn from(guid: CigGuid) -> Self {
+        Self::Guid(guid)
+    }
+}
+
+/// Handle to a struct instance in the builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceHandle {
+    /// Index of the struct type.
+    pub struct_index: u32,
+    /// Instance index within the struct type's data.
+    pub instance_index: u32,
+}
+
+/// The owner of property values: a record or an embedded instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuilderTarget {
+    Record(RecordHandle),
+    Instance(InstanceHandle),
+}
+
+impl From<RecordHandle> for BuilderTarget {
+    fn from(record: RecordHandle) -> Self {
+        Self::Record(record)
+    }
+}
+
+impl From<InstanceHandle> for BuilderTarget {
+    fn from(instance: InstanceHandle) -> Self {
+        Self::Instance(instance)
+    }
+}
+
+/// Value pools that hold array elements.
+const POOLS: [PoolType; 18] = [
+    PoolType::Bool,
+    PoolType::Int8,
+    PoolType::Int16,
+    PoolType::Int32,
+    PoolType::Int64,
+    PoolType::UInt8,
+    PoolType::UInt16,
+    PoolType::UInt32,
+    PoolType::UInt64,
+    PoolType::Float,
+    PoolType::Double,
+    PoolType::Guid,
+    PoolType::StringId,
+    PoolType::Locale,
+    PoolType::EnumValue,
+    PoolType::Strong,
+    PoolType::Weak,
+    PoolType::Reference,
+];
+
+fn pool_element_size(pool: PoolType) -> usize {
+    match pool {
+        PoolType::Bool | PoolType::Int8 | PoolType::UInt8 => 1,
+        PoolType::Int16 | PoolType::UInt16 => 2,
+        PoolType::Int32 | PoolType::UInt32 | PoolType::Float => 4,
+        PoolType::StringId | PoolType::Locale | PoolType::EnumValue | PoolType::EnumOption => 4,
+        PoolType::Int64 | PoolType::UInt64 | PoolType::Double => 8,
+        PoolType::Strong | PoolType::Weak => 8,
+        PoolType::Guid => 16,
+        PoolType::Reference => 20,
+    }
+}
+
+/// Statistics reported by [`DataCoreBuilder::compact`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactStats {
+    /// Struct instances no record could reach.
+    pub instances_removed: usize,
+    /// Value pool entries no array used.
+    pub pool_entries_removed: usize,
+    /// Strings dropped from the string tables.
+    pub strings_removed: usize,
+    /// Reduction of the output size in bytes.
+    pub bytes_saved: usize,
+}
+
+/// A problem found by [`DataCoreBuilder::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// A struct's declared size does not match the size of its properties.
+    StructSizeMismatch {
+        struct_name: String,
+        declared: usize,
+        computed: usize,
+    },
+    /// A reference points to a GUID that no record has.
+    MissingReference {
+        record: String,
+        property: String,
+        target: CigGuid,
+    },
+    /// An array header points past the end of its value pool or instance list.
+    ArrayOutOfRange {
+        record: String,
+        property: String,
+        first: u32,
+        count: u32,
+        available: usize,
+    },
+    /// A pointer refers to an instance that does not exist.
+    PointerOutOfRange {
+        record: String,
+        property: String,
+        struct_index: i32,
+        instance_index: i32,
+    },
+    /// An enum-choice value is not one of its enum's options.
+    InvalidEnumValue {
+        record: String,
+        property: String,
+        enum_name: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StructSizeMismatch { struct_name, declared, computed } => write!(
+                f,
+                "struct {} declares {} bytes but its properties take {}; define a struct's properties \
+                 before deriving from it or using it as an inline Class",
+                struct_name, declared, computed
+            ),
+            Self::MissingReference { record, property, target } => write!(
+                f,
+                "{}.{} references missing record {}; add the record or clear the reference",
+                record, property, target
+            ),
+            Self::ArrayOutOfRange { record, property, first, count, available } => write!(
+                f,
+                "{}.{} uses elements {}..{} but only {} exist; set the array again",
+                record,
+                property,
+                first,
+                first + count,
+                available
+            ),
+            Self::PointerOutOfRange { record, property, struct_index, instance_index } => write!(
+                f,
+                "{}.{} points to instance {} of struct {}, which does not exist; point it at an \
+                 existing instance or set it to None",
+                record, property, instance_index, struct_index
+            ),
+            Self::InvalidEnumValue { record, property, enum_name, value } => write!(
+                f,
+                "{}.{} is {:?}, which is not an option of {}",
+                record, property, value, enum_name
+            ),
+        }
+    }
+}
+
+/// State of a deep copy in [`DataCoreBuilder::clone_record`].
+struct Cloner {
+    /// Instances of records, which are never copied.
+    records: std::collections::HashSet<InstanceHandle>,
+    /// Copies made so far, by source instance.
+    cloned: FxHashMap<InstanceHandle, InstanceHandle>,
+}
+
+fn read_array_header(data: &[u8], offset: usize) -> (u32, u32) {
+    let count = i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let first = i32::from_le_bytes(data[offset + 4..offset + 8].try_into().unwrap());
+    (count.max(0) as u32, first.max(0) as u32)
+}
+
+fn read_pointer(data: &[u8], offset: usize) -> DataCorePointer {
+    let raw = |i: usize| i32::from_le_bytes(data[offset + i..offset + i + 4].try_into().unwrap());
+    DataCorePointer {
+        struct_index: raw(0),
+        instance_index: raw(4),
+    }
+}
+
+fn write_array_header(data: &mut [u8], offset: usize, count: usize, first_index: i32) {
+    data[offset..offset + 4].copy_from_slice(&(count as i32).to_le_bytes());
+    data[offset + 4..offset + 8].copy_from_slice(&first_index.to_le_bytes());
+}
+
+fn read_guid(data: &[u8], offset: usize) -> CigGuid {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&data[offset..offset + 16]);
+    CigGuid::from_bytes(bytes)
+}
+
+fn pointer_target(pointer: &DataCorePointer) -> Option<InstanceHandle> {
+    (!pointer.is_null()).then_some(InstanceHandle {
+        struct_index: pointer.struct_index as u32,
+        instance_index: pointer.instance_index as u32,
+    })
+}
+
+fn instance_pointer(instance: Option<InstanceHandle>) -> DataCorePointer {
+    match instance {
+        Some(instance) => DataCorePointer {
+            struct_index: instance.struct_index as i32,
+            instance_index: instance.instance_index as i32,
+        },
+        None => DataCorePointer {
+            struct_index: -1,
+            instance_index: -1,
+        },
+    }
+}
+
 impl DataCoreBuilder {
     /// Create a new empty database builder.
     pub fn new() -> Self {
@@ -322,6 +568,7 @@ impl DataCoreBuilder {
                 struct_index,
                 guid: raw_record.id,
                 instance_index,
+                removed: false,
             });
         }
 
@@ -347,7 +594,6 @@ impl DataCoreBuilder {
 
         // Copy value pools - the instance data may contain array headers with indices
         // into these pools, so we must preserve them exactly
-        use crate::PoolType;
 
         // Copy all value pools as raw bytes and reinterpret
 
@@ -553,6 +799,9 @@ impl DataCoreBuilder {
     }
 
     /// Add a property that references another struct type (for Class, StrongPointer, WeakPointer).
+    ///
+    /// Class properties are stored inline, so the target struct must have all
+    /// of its properties before it is used here.
     pub fn add_typed_property(
         &mut self,
         struct_handle: StructHandle,
@@ -560,7 +809,29 @@ impl DataCoreBuilder {
         data_type: DataType,
         target_struct: StructHandle,
     ) {
-        self.add_property_internal(struct_handle, name, data_type, Some(target_struct), false);
+        self.add_property_internal(struct_handle, name, data_type, Some(target_struct.0 as u16), false);
+    }
+
+    /// Add an array property of another struct type (for Class, StrongPointer, WeakPointer).
+    pub fn add_typed_array_property(
+        &mut self,
+        struct_handle: StructHandle,
+        name: &str,
+        element_type: DataType,
+        target_struct: StructHandle,
+    ) {
+        self.add_property_internal(struct_handle, name, element_type, Some(target_struct.0 as u16), true);
+    }
+
+    /// Add an enum-choice property whose values are options of the given enum.
+    pub fn add_enum_property(&mut self, struct_handle: StructHandle, name: &str, enum_handle: EnumHandle) {
+        self.add_property_internal(
+            struct_handle,
+            name,
+            DataType::EnumChoice,
+            Some(enum_handle.0 as u16),
+            false,
+        );
     }
 
     fn add_property_internal(
@@ -568,30 +839,27 @@ impl DataCoreBuilder {
         struct_handle: StructHandle,
         name: &str,
         data_type: DataType,
-        target_struct: Option<StructHandle>,
+        target_index: Option<u16>,
         is_array: bool,
     ) {
-        let struct_index = target_struct.map(|h| h.0 as u16).unwrap_or(0);
+        let struct_index = target_index.unwrap_or(0);
         // For new properties: 0 = not array, 1 = complex array
         let conversion_type = if is_array { 1 } else { 0 };
 
-        self.properties.push(PropertyDef {
+        let property = PropertyDef {
             name: name.to_string(),
             struct_index,
             data_type,
             conversion_type,
-        });
+        };
+
+        // Calculate size contribution
+        let size = self.property_size(&property);
+        self.properties.push(property);
 
         // Update the struct's property count and size
         let s = &mut self.structs[struct_handle.0 as usize];
         s.property_count += 1;
-
-        // Calculate size contribution
-        let size = if is_array {
-            8 // count + first_index
-        } else {
-            data_type.inline_size()
-        };
         s.size += size as u32;
     }
 
@@ -645,13 +913,7 @@ impl DataCoreBuilder {
         let struct_index = struct_handle.0;
 
         // Allocate instance data for this record
-        let instance_index = self.struct_instance_counts[struct_index as usize];
-        self.struct_instance_counts[struct_index as usize] += 1;
-
-        // Initialize instance data with zeros
-        let struct_size = self.structs[struct_index as usize].size as usize;
-        let instance_data = &mut self.instance_data[struct_index as usize];
-        instance_data.resize(instance_data.len() + struct_size, 0);
+        let instance_index = self.allocate_instance(struct_index as usize);
 
         self.records.push(RecordDef {
             name: name.to_string(),
@@ -659,98 +921,927 @@ impl DataCoreBuilder {
             struct_index,
             guid,
             instance_index: instance_index as u16,
+            removed: false,
         });
 
         RecordHandle(record_index)
     }
 
+    /// Find a record by GUID.
+    pub fn find_record(&self, guid: &CigGuid) -> Option<RecordHandle> {
+        self.records
+            .iter()
+            .position(|r| !r.removed && r.guid == *guid)
+            .map(|i| RecordHandle(i as u32))
+    }
+
+    /// Remove a record.
+    ///
+    /// The record's instance data is kept so that other handles stay valid.
+    /// Returns the remaining records which still reference the removed record
+    /// by GUID; those references are left dangling.
+    pub fn remove_record(&mut self, record: impl Into<RecordKey>) -> io::Result<Vec<RecordHandle>> {
+        let handle = self.resolve_record(record.into())?;
+        let guid = self.records[handle.0 as usize].guid;
+        self.records[handle.0 as usize].removed = true;
+
+        let referencing = (0..self.records.len() as u32)
+            .map(RecordHandle)
+            .filter(|&r| !self.records[r.0 as usize].removed)
+            .filter(|&r| self.record_references(r).contains(&guid))
+            .collect();
+        Ok(referencing)
+    }
+
+    /// Clone a record under a new name and GUID.
+    ///
+    /// The record's instance and all embedded instances it owns through Class
+    /// arrays and strong pointers are deep-copied; weak pointers into the copied
+    /// instances are redirected to the copies. Pointers to other records'
+    /// instances, references and primitive arrays are shared with the source.
+    ///
+    /// The clone is stored next to the source file, in `<new_name>.xml`
+    /// (lowercased), so that it becomes a main record of its own.
+    pub fn clone_record(
+        &mut self,
+        source: impl Into<RecordKey>,
+        new_name: &str,
+        new_guid: CigGuid,
+    ) -> io::Result<RecordHandle> {
+        let source = self.resolve_record(source.into())?;
+        if self.find_record(&new_guid).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("record {} already exists", new_guid),
+            ));
+        }
+
+        let source_def = self.records[source.0 as usize].clone();
+        let file_name = match source_def.file_name.rfind('/') {
+            Some(slash) => format!("{}/{}.xml", &source_def.file_name[..slash], new_name.to_lowercase()),
+            None => format!("{}.xml", new_name.to_lowercase()),
+        };
+
+        let mut cloner = Cloner {
+            records: self
+                .records
+                .iter()
+                .filter(|r| !r.removed)
+                .map(|r| InstanceHandle {
+                    struct_index: r.struct_index,
+                    instance_index: r.instance_index as u32,
+                })
+                .collect(),
+            cloned: FxHashMap::default(),
+        };
+        let root = self.record_instance(source);
+        let instance = self.copy_instance(root);
+        cloner.cloned.insert(root, instance);
+        self.clone_children(instance, &mut cloner);
+        self.redirect_weak_pointers(&cloner);
+
+        let record_index = self.records.len() as u32;
+        self.records.push(RecordDef {
+            name: new_name.to_string(),
+            file_name,
+            struct_index: instance.struct_index,
+            guid: new_guid,
+            instance_index: instance.instance_index as u16,
+            removed: false,
+        });
+        Ok(RecordHandle(record_index))
+    }
+
+    fn resolve_record(&self, key: RecordKey) -> io::Result<RecordHandle> {
+        let handle = match key {
+            RecordKey::Handle(handle) => Some(handle),
+            RecordKey::Guid(guid) => self.find_record(&guid),
+        };
+        handle
+            .filter(|h| self.records.get(h.0 as usize).is_some_and(|r| !r.removed))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("record not found: {:?}", key)))
+    }
+
+    /// GUIDs referenced from a record's instance and everything it points to.
+    fn record_references(&self, record: RecordHandle) -> Vec<CigGuid> {
+        let mut guids = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        let mut stack = vec![self.record_instance(record)];
+
+        while let Some(instance) = stack.pop() {
+            if !self.instance_exists(instance) || !visited.insert(instance) {
+                continue;
+            }
+            for (prop, offset) in self.instance_fields(instance) {
+                let data = &self.instance_data[instance.struct_index as usize];
+                match (prop.data_type, prop.conversion_type != 0) {
+                    (DataType::Reference, false) => {
+                        guids.push(read_guid(data, offset));
+                    }
+                    (DataType::Reference, true) => {
+                        for r in self.pool_range(&self.reference_pool, data, offset) {
+                            guids.push(r.record_id);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            stack.extend(self.instance_children(instance, false));
+        }
+
+        guids
+    }
+
+    /// Instances an instance refers to through Class arrays and pointers.
+    fn instance_children(&self, instance: InstanceHandle, include_weak: bool) -> Vec<InstanceHandle> {
+        let mut children = Vec::new();
+        let data = &self.instance_data[instance.struct_index as usize];
+
+        for (prop, offset) in self.instance_fields(instance) {
+            let is_array = prop.conversion_type != 0;
+            let pool = match prop.data_type {
+                DataType::StrongPointer => &self.strong_pool,
+                DataType::WeakPointer if include_weak => &self.weak_pool,
+                DataType::Class if is_array => {
+                    let (count, first) = read_array_header(data, offset);
+                    children.extend((first..first + count).map(|i| InstanceHandle {
+                        struct_index: prop.struct_index as u32,
+                        instance_index: i,
+                    }));
+                    continue;
+                }
+                _ => continue,
+            };
+            if is_array {
+                children.extend(self.pool_range(pool, data, offset).iter().filter_map(pointer_target));
+            } else {
+                children.extend(pointer_target(&read_pointer(data, offset)));
+            }
+        }
+
+        children
+    }
+
+    /// Remove unreferenced instances, pool entries and strings.
+    ///
+    /// Instances are kept if a record reaches them through inline classes,
+    /// Class arrays or pointers, pool entries if a kept array uses them and
+    /// strings if anything still refers to them. All indices and string ids
+    /// are rewritten, so [`InstanceHandle`]s taken before compacting are
+    /// invalid afterwards. Record handles stay valid.
+    pub fn compact(&mut self) -> CompactStats {
+        self.finalize_strings();
+        let size_before = self.payload_len();
+        let strings_before = self.string_table_1.offsets.len() + self.string_table_2.offsets.len();
+        let pool_entries_before = self.pool_entry_count();
+        let mut stats = CompactStats::default();
+
+        // Mark instances reachable from live records
+        let mut reachable: Vec<Vec<bool>> = self
+            .struct_instance_counts
+            .iter()
+            .map(|&count| vec![false; count as usize])
+            .collect();
+        let mut stack: Vec<InstanceHandle> = (0..self.records.len() as u32)
+            .filter(|&i| !self.records[i as usize].removed)
+            .map(|i| self.record_instance(RecordHandle(i)))
+            .collect();
+        while let Some(instance) = stack.pop() {
+            if !self.instance_exists(instance) {
+                continue;
+            }
+            let seen = &mut reachable[instance.struct_index as usize][instance.instance_index as usize];
+            if !*seen {
+                *seen = true;
+                stack.extend(self.instance_children(instance, true));
+            }
+        }
+
+        // Drop unreachable instances, keeping the order of the rest so that
+        // Class array elements stay consecutive
+        let mut instance_map: Vec<Vec<Option<u32>>> = Vec::with_capacity(self.structs.len());
+        for (struct_index, keep) in reachable.iter().enumerate() {
+            let size = self.structs[struct_index].size as usize;
+            let old = std::mem::take(&mut self.instance_data[struct_index]);
+            let mut map = Vec::with_capacity(keep.len());
+            let mut next = 0;
+            for (i, &kept) in keep.iter().enumerate() {
+                if kept {
+                    self.instance_data[struct_index].extend_from_slice(&old[i * size..(i + 1) * size]);
+                    map.push(Some(next));
+                    next += 1;
+                } else {
+                    map.push(None);
+                    stats.instances_removed += 1;
+                }
+            }
+            self.struct_instance_counts[struct_index] = next;
+            instance_map.push(map);
+        }
+        let remap_instance = |handle: InstanceHandle| {
+            instance_map
+                .get(handle.struct_index as usize)
+                .and_then(|m| m.get(handle.instance_index as usize).copied().flatten())
+        };
+        let remap_pointer = |pointer: &DataCorePointer| {
+            let target = pointer_target(pointer).and_then(|t| {
+                remap_instance(t).map(|i| InstanceHandle {
+                    struct_index: t.struct_index,
+                    instance_index: i,
+                })
+            });
+            instance_pointer(target)
+        };
+        for record in self.records.iter_mut().filter(|r| !r.removed) {
+            let handle = InstanceHandle {
+                struct_index: record.struct_index,
+                instance_index: record.instance_index as u32,
+            };
+            record.instance_index = remap_instance(handle).unwrap_or(0) as u16;
+        }
+
+        // Mark pool entries used by the remaining arrays
+        let mut fields = Vec::new();
+        for struct_index in 0..self.structs.len() {
+            for i in 0..self.struct_instance_counts[struct_index] {
+                let instance = InstanceHandle {
+                    struct_index: struct_index as u32,
+                    instance_index: i,
+                };
+                for (prop, offset) in self.instance_fields(instance) {
+                    fields.push((struct_index, prop, offset));
+                }
+            }
+        }
+        let mut used: FxHashMap<PoolType, Vec<bool>> = FxHashMap::default();
+        for (struct_index, prop, offset) in &fields {
+            let Some(pool) = PoolType::for_data_type(prop.data_type).filter(|_| prop.conversion_type != 0) else {
+                continue;
+            };
+            let (count, first) = read_array_header(&self.instance_data[*struct_index], *offset);
+            let marks = used.entry(pool).or_insert_with(|| vec![false; self.pool_size(pool)]);
+            let end = (first + count).min(marks.len() as u32);
+            for mark in &mut marks[first.min(end) as usize..end as usize] {
+                *mark = true;
+            }
+        }
+
+        // Compact pools and point the surviving pointers at the moved instances
+        let mut pool_maps: FxHashMap<PoolType, Vec<Option<u32>>> = FxHashMap::default();
+        for (pool, marks) in &used {
+            pool_maps.insert(*pool, self.retain_pool_entries(*pool, marks));
+        }
+        for pool in POOLS {
+            if !used.contains_key(&pool) {
+                self.retain_pool_entries(pool, &[]);
+            }
+        }
+        for pointer in self.strong_pool.iter_mut().chain(self.weak_pool.iter_mut()) {
+            *pointer = remap_pointer(pointer);
+        }
+
+        // Rebuild string table 1 from the strings still in use
+        let old_strings = std::mem::take(&mut self.string_table_1);
+        let mut string_map: FxHashMap<i32, i32> = FxHashMap::default();
+        let mut intern = |table: &mut StringTable, id: i32| {
+            *string_map
+                .entry(id)
+                .or_insert_with(|| table.add(&old_strings.get(id)))
+        };
+        for (struct_index, prop, offset) in &fields {
+            let data = &mut self.instance_data[*struct_index];
+            let offset = *offset;
+            match (prop.data_type, prop.conversion_type != 0) {
+                (DataType::Class, true) => {
+                    let (count, first) = read_array_header(data, offset);
+                    let target = InstanceHandle {
+                        struct_index: prop.struct_index as u32,
+                        instance_index: first,
+                    };
+                    let first = if count > 0 { remap_instance(target).unwrap_or(0) } else { 0 };
+                    write_array_header(data, offset, count as usize, first as i32);
+                }
+                (data_type, true) => {
+                    let (count, first) = read_array_header(data, offset);
+                    let first = PoolType::for_data_type(data_type)
+                        .and_then(|pool| pool_maps.get(&pool))
+                        .and_then(|map| map.get(first as usize).copied().flatten())
+                        .filter(|_| count > 0)
+                        .unwrap_or(0);
+                    write_array_header(data, offset, count as usize, first as i32);
+                }
+                (DataType::StrongPointer | DataType::WeakPointer, false) => {
+                    let pointer = remap_pointer(&read_pointer(data, offset));
+                    data[offset..offset + 8].copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+                }
+                (DataType::String | DataType::Locale | DataType::EnumChoice, false) => {
+                    let id = i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+                    let id = intern(&mut self.string_table_1, id);
+                    data[offset..offset + 4].copy_from_slice(&id.to_le_bytes());
+                }
+                _ => {}
+            }
+        }
+        for string_id in self
+            .string_id_pool
+            .iter_mut()
+            .chain(self.locale_pool.iter_mut())
+            .chain(self.enum_value_pool.iter_mut())
+        {
+            *string_id = DataCoreStringId::new(intern(&mut self.string_table_1, string_id.id()));
+        }
+
+        // Names are re-added from the definitions
+        self.string_table_2 = StringTable::new();
+        self.finalize_strings();
+
+        let strings_after = self.string_table_1.offsets.len() + self.string_table_2.offsets.len();
+        stats.strings_removed = strings_before.saturating_sub(strings_after);
+        stats.pool_entries_removed = pool_entries_before - self.pool_entry_count();
+        stats.bytes_saved = size_before.saturating_sub(self.payload_len());
+        stats
+    }
+
+    /// Check the builder for problems that would produce a broken database.
+    ///
+    /// Struct sizes are checked first; data checks are skipped while they are
+    /// inconsistent, since property offsets cannot be trusted. Data checks
+    /// cover every instance reachable from a live record and report the
+    /// record they were reached from.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for (struct_index, struct_def) in self.structs.iter().enumerate() {
+            let computed = self.computed_struct_size(struct_index);
+            if computed != struct_def.size as usize {
+                issues.push(ValidationIssue::StructSizeMismatch {
+                    struct_name: struct_def.name.clone(),
+                    declared: struct_def.size as usize,
+                    computed,
+                });
+            }
+        }
+        if !issues.is_empty() {
+            return issues;
+        }
+
+        let guids: std::collections::HashSet<CigGuid> =
+            self.records.iter().filter(|r| !r.removed).map(|r| r.guid).collect();
+        let mut visited = std::collections::HashSet::new();
+
+        for (i, record) in self.records.iter().enumerate().filter(|(_, r)| !r.removed) {
+            let mut stack = vec![self.record_instance(RecordHandle(i as u32))];
+            while let Some(instance) = stack.pop() {
+                if !self.instance_exists(instance) || !visited.insert(instance) {
+                    continue;
+                }
+                self.validate_instance(&record.name, instance, &guids, &mut issues);
+                stack.extend(self.instance_children(instance, true));
+            }
+        }
+
+        issues
+    }
+
+    fn validate_instance(
+        &self,
+        record: &str,
+        instance: InstanceHandle,
+        guids: &std::collections::HashSet<CigGuid>,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        let data = &self.instance_data[instance.struct_index as usize];
+
+        for (prop, offset) in self.instance_fields(instance) {
+            let is_array = prop.conversion_type != 0;
+            let mut check_reference = |guid: CigGuid| {
+                if !guid.is_empty() && !guids.contains(&guid) {
+                    issues.push(ValidationIssue::MissingReference {
+                        record: record.to_string(),
+                        property: prop.name.clone(),
+                        target: guid,
+                    });
+                }
+            };
+
+            if is_array {
+                let (count, first) = read_array_header(data, offset);
+                let available = match PoolType::for_data_type(prop.data_type) {
+                    Some(pool) => self.pool_size(pool),
+                    None => self
+                        .struct_instance_counts
+                        .get(prop.struct_index as usize)
+                        .map_or(0, |&c| c as usize),
+                };
+                if (first + count) as usize > available {
+                    issues.push(ValidationIssue::ArrayOutOfRange {
+                        record: record.to_string(),
+                        property: prop.name.clone(),
+                        first,
+                        count,
+                        available,
+                    });
+                    continue;
+                }
+            }
+
+            match prop.data_type {
+                DataType::Reference if is_array => {
+                    for reference in self.pool_range(&self.reference_pool, data, offset) {
+                        check_reference(reference.record_id);
+                    }
+                }
+                DataType::Reference => check_reference(read_guid(data, offset)),
+                DataType::StrongPointer | DataType::WeakPointer => {
+                    let pointers = if !is_array {
+                        vec![read_pointer(data, offset)]
+                    } else if prop.data_type == DataType::StrongPointer {
+                        self.pool_range(&self.strong_pool, data, offset).to_vec()
+                    } else {
+                        self.pool_range(&self.weak_pool, data, offset).to_vec()
+                    };
+                    for pointer in pointers {
+                        if pointer_target(&pointer).is_some_and(|t| !self.instance_exists(t)) {
+                            issues.push(ValidationIssue::PointerOutOfRange {
+                                record: record.to_string(),
+                                property: prop.name.clone(),
+                                struct_index: pointer.struct_index,
+                                instance_index: pointer.instance_index,
+                            });
+                        }
+                    }
+                }
+                DataType::EnumChoice => {
+                    let ids: Vec<i32> = if is_array {
+                        self.pool_range(&self.enum_value_pool, data, offset)
+                            .iter()
+                            .map(|id| id.id())
+                            .collect()
+                    } else {
+                        vec![i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())]
+                    };
+                    let Some(enum_def) = self.enums.get(prop.struct_index as usize) else {
+                        continue;
+                    };
+                    let first_option = enum_def.first_value_index as usize;
+                    let options = self
+                        .enum_options
+                        .get(first_option..first_option + enum_def.value_count as usize)
+                        .unwrap_or(&[]);
+                    for id in ids {
+                        let value = self.string_table_1.get(id);
+                        if !value.is_empty() && !options.contains(&value) {
+                            issues.push(ValidationIssue::InvalidEnumValue {
+                                record: record.to_string(),
+                                property: prop.name.clone(),
+                                enum_name: enum_def.name.clone(),
+                                value,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Size of a struct computed from its parent and its own properties.
+    fn computed_struct_size(&self, struct_index: usize) -> usize {
+        let struct_def = &self.structs[struct_index];
+        let parent_size = match struct_def.parent_index {
+            -1 => 0,
+            parent => self.structs.get(parent as usize).map_or(0, |p| p.size as usize),
+        };
+        let first_prop = struct_def.first_property_index as usize;
+        let prop_count = struct_def.property_count as usize;
+        parent_size
+            + self.properties[first_prop..first_prop + prop_count]
+                .iter()
+                .map(|p| self.property_size(p))
+                .sum::<usize>()
+    }
+
+    /// Keep the pool entries marked as used, returning where each entry moved.
+    fn retain_pool_entries(&mut self, pool: PoolType, used: &[bool]) -> Vec<Option<u32>> {
+        fn retain<T>(entries: &mut Vec<T>, used: &[bool]) -> Vec<Option<u32>> {
+            let mut map = Vec::with_capacity(entries.len());
+            let mut next = 0;
+            let mut index = 0;
+            entries.retain(|_| {
+                let keep = used.get(index).copied().unwrap_or(false);
+                index += 1;
+                map.push(keep.then_some(next));
+                next += keep as u32;
+                keep
+            });
+            map
+        }
+
+        match pool {
+            PoolType::Bool => retain(&mut self.bool_pool, used),
+            PoolType::Int8 => retain(&mut self.int8_pool, used),
+            PoolType::Int16 => retain(&mut self.int16_pool, used),
+            PoolType::Int32 => retain(&mut self.int32_pool, used),
+            PoolType::Int64 => retain(&mut self.int64_pool, used),
+            PoolType::UInt8 => retain(&mut self.uint8_pool, used),
+            PoolType::UInt16 => retain(&mut self.uint16_pool, used),
+            PoolType::UInt32 => retain(&mut self.uint32_pool, used),
+            PoolType::UInt64 => retain(&mut self.uint64_pool, used),
+            PoolType::Float => retain(&mut self.float_pool, used),
+            PoolType::Double => retain(&mut self.double_pool, used),
+            PoolType::Guid => retain(&mut self.guid_pool, used),
+            PoolType::StringId => retain(&mut self.string_id_pool, used),
+            PoolType::Locale => retain(&mut self.locale_pool, used),
+            PoolType::EnumValue => retain(&mut self.enum_value_pool, used),
+            PoolType::Strong => retain(&mut self.strong_pool, used),
+            PoolType::Weak => retain(&mut self.weak_pool, used),
+            PoolType::Reference => retain(&mut self.reference_pool, used),
+            PoolType::EnumOption => Vec::new(),
+        }
+    }
+
+    /// Number of entries in a value pool.
+    fn pool_size(&self, pool: PoolType) -> usize {
+        match pool {
+            PoolType::Bool => self.bool_pool.len(),
+            PoolType::Int8 => self.int8_pool.len(),
+            PoolType::Int16 => self.int16_pool.len(),
+            PoolType::Int32 => self.int32_pool.len(),
+            PoolType::Int64 => self.int64_pool.len(),
+            PoolType::UInt8 => self.uint8_pool.len(),
+            PoolType::UInt16 => self.uint16_pool.len(),
+            PoolType::UInt32 => self.uint32_pool.len(),
+            PoolType::UInt64 => self.uint64_pool.len(),
+            PoolType::Float => self.float_pool.len(),
+            PoolType::Double => self.double_pool.len(),
+            PoolType::Guid => self.guid_pool.len(),
+            PoolType::StringId => self.string_id_pool.len(),
+            PoolType::Locale => self.locale_pool.len(),
+            PoolType::EnumValue => self.enum_value_pool.len(),
+            PoolType::Strong => self.strong_pool.len(),
+            PoolType::Weak => self.weak_pool.len(),
+            PoolType::Reference => self.reference_pool.len(),
+            PoolType::EnumOption => self.enum_options.len(),
+        }
+    }
+
+    fn pool_entry_count(&self) -> usize {
+        POOLS.iter().map(|&pool| self.pool_size(pool)).sum()
+    }
+
+    /// Size of the instance data, value pools and string tables in bytes.
+    fn payload_len(&self) -> usize {
+        let pools: usize = POOLS
+            .iter()
+            .map(|&pool| self.pool_size(pool) * pool_element_size(pool))
+            .sum();
+        let instances: usize = self.instance_data.iter().map(Vec::len).sum();
+        pools + instances + self.string_table_1.len() + self.string_table_2.len()
+    }
+
+    fn instance_exists(&self, instance: InstanceHandle) -> bool {
+        self.struct_instance_counts
+            .get(instance.struct_index as usize)
+            .is_some_and(|&count| instance.instance_index < count)
+    }
+
+    /// Append a copy of an instance to its struct's data block.
+    fn copy_instance(&mut self, source: InstanceHandle) -> InstanceHandle {
+        let bytes = self.instance_bytes(source).to_vec();
+        let struct_index = source.struct_index as usize;
+        let instance_index = self.allocate_instance(struct_index);
+
+        let data = &mut self.instance_data[struct_index];
+        let start = data.len() - bytes.len();
+        data[start..].copy_from_slice(&bytes);
+
+        InstanceHandle {
+            struct_index: source.struct_index,
+            instance_index,
+        }
+    }
+
+    /// Deep-copy an embedded instance, reusing copies made earlier.
+    fn clone_instance(&mut self, source: InstanceHandle, cloner: &mut Cloner) -> InstanceHandle {
+        if cloner.records.contains(&source) || !self.instance_exists(source) {
+            return source;
+        }
+        if let Some(&copy) = cloner.cloned.get(&source) {
+            return copy;
+        }
+
+        let copy = self.copy_instance(source);
+        cloner.cloned.insert(source, copy);
+        self.clone_children(copy, cloner);
+        copy
+    }
+
+    /// Replace the embedded instances owned by a fresh copy with copies of their own.
+    fn clone_children(&mut self, instance: InstanceHandle, cloner: &mut Cloner) {
+        let struct_index = instance.struct_index as usize;
+
+        for (prop, offset) in self.instance_fields(instance) {
+            let data = &self.instance_data[struct_index];
+            match (prop.data_type, prop.conversion_type != 0) {
+                (DataType::StrongPointer, false) => {
+                    if let Some(target) = pointer_target(&read_pointer(data, offset)) {
+                        let copy = self.clone_instance(target, cloner);
+                        let pointer = instance_pointer(Some(copy));
+                        self.instance_data[struct_index][offset..offset + 8]
+                            .copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+                    }
+                }
+                (DataType::StrongPointer, true) => {
+                    let pointers = self.pool_range(&self.strong_pool, data, offset).to_vec();
+                    let copies: Vec<DataCorePointer> = pointers
+                        .iter()
+                        .map(|p| match pointer_target(p) {
+                            Some(target) => instance_pointer(Some(self.clone_instance(target, cloner))),
+                            None => *p,
+                        })
+                        .collect();
+                    let first_index = self.strong_pool.len() as i32;
+                    self.strong_pool.extend(copies);
+                    write_array_header(&mut self.instance_data[struct_index], offset, pointers.len(), first_index);
+                }
+                (DataType::Class, true) => {
+                    let (count, first) = read_array_header(data, offset);
+                    if count == 0 {
+                        continue;
+                    }
+                    // Copy the elements first so that they stay consecutive
+                    let copies: Vec<InstanceHandle> = (first..first + count)
+                        .map(|i| {
+                            let source = InstanceHandle {
+                                struct_index: prop.struct_index as u32,
+                                instance_index: i,
+                            };
+                            let copy = self.copy_instance(source);
+                            cloner.cloned.entry(source).or_insert(copy);
+                            copy
+                        })
+                        .collect();
+                    for &copy in &copies {
+                        self.clone_children(copy, cloner);
+                    }
+                    let first_index = copies[0].instance_index as i32;
+                    write_array_header(&mut self.instance_data[struct_index], offset, copies.len(), first_index);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Point weak pointers inside cloned instances at the clones.
+    fn redirect_weak_pointers(&mut self, cloner: &Cloner) {
+        let remap = |p: &DataCorePointer| {
+            pointer_target(p)
+                .and_then(|target| cloner.cloned.get(&target))
+                .map(|&copy| instance_pointer(Some(copy)))
+        };
+
+        for &copy in cloner.cloned.values() {
+            let struct_index = copy.struct_index as usize;
+            for (prop, offset) in self.instance_fields(copy) {
+                if prop.data_type != DataType::WeakPointer {
+                    continue;
+                }
+                let data = &self.instance_data[struct_index];
+                if prop.conversion_type == 0 {
+                    if let Some(pointer) = remap(&read_pointer(data, offset)) {
+                        self.instance_data[struct_index][offset..offset + 8]
+                            .copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+                    }
+                } else {
+                    let pointers = self.pool_range(&self.weak_pool, data, offset);
+                    if pointers.iter().any(|p| remap(p).is_some()) {
+                        let redirected: Vec<DataCorePointer> =
+                            pointers.iter().map(|p| remap(p).unwrap_or(*p)).collect();
+                        let count = redirected.len();
+                        let first_index = self.weak_pool.len() as i32;
+                        self.weak_pool.extend(redirected);
+                        write_array_header(&mut self.instance_data[struct_index], offset, count, first_index);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Properties of an instance with their offsets in the struct's data block.
+    ///
+    /// Inline classes are flattened into their properties.
+    fn instance_fields(&self, instance: InstanceHandle) -> Vec<(PropertyDef, usize)> {
+        let struct_index = instance.struct_index as usize;
+        let base = instance.instance_index as usize * self.structs[struct_index].size as usize;
+        let mut fields = Vec::new();
+        self.collect_fields(struct_index, base, &mut fields);
+        fields
+    }
+
+    fn collect_fields(&self, struct_index: usize, base: usize, fields: &mut Vec<(PropertyDef, usize)>) {
+        let struct_def = &self.structs[struct_index];
+        let mut offset = base;
+        if struct_def.parent_index >= 0 {
+            let parent_index = struct_def.parent_index as usize;
+            self.collect_fields(parent_index, offset, fields);
+            offset += self.structs[parent_index].size as usize;
+        }
+
+        let first_prop = struct_def.first_property_index as usize;
+        let prop_count = struct_def.property_count as usize;
+        for prop in &self.properties[first_prop..first_prop + prop_count] {
+            if prop.conversion_type == 0 && prop.data_type == DataType::Class {
+                self.collect_fields(prop.struct_index as usize, offset, fields);
+            } else {
+                fields.push((prop.clone(), offset));
+            }
+            offset += self.property_size(prop);
+        }
+    }
+
+    /// Pool elements referenced by the array header at `offset`.
+    fn pool_range<'p, T>(&self, pool: &'p [T], data: &[u8], offset: usize) -> &'p [T] {
+        let (count, first) = read_array_header(data, offset);
+        let start = (first as usize).min(pool.len());
+        let end = (first as usize + count as usize).min(pool.len());
+        &pool[start..end]
+    }
+
+    /// Allocate a new instance of a struct type that is not a record.
+    ///
+    /// Embedded instances are reachable through Class properties, Class arrays
+    /// and strong pointers that refer to them. Their values are set with the
+    /// same setters as records.
+    pub fn add_instance(&mut self, struct_handle: StructHandle) -> InstanceHandle {
+        let instance_index = self.allocate_instance(struct_handle.0 as usize);
+        InstanceHandle {
+            struct_index: struct_handle.0,
+            instance_index,
+        }
+    }
+
+    /// Get the instance holding a record's values.
+    pub fn record_instance(&self, record: RecordHandle) -> InstanceHandle {
+        let record_def = &self.records[record.0 as usize];
+        InstanceHandle {
+            struct_index: record_def.struct_index,
+            instance_index: record_def.instance_index as u32,
+        }
+    }
+
+    /// Append a zero-initialized instance to a struct's data block.
+    fn allocate_instance(&mut self, struct_index: usize) -> u32 {
+        let instance_index = self.struct_instance_counts[struct_index];
+        self.struct_instance_counts[struct_index] += 1;
+
+        let struct_size = self.structs[struct_index].size as usize;
+        let instance_data = &mut self.instance_data[struct_index];
+        instance_data.resize(instance_data.len() + struct_size, 0);
+
+        // Pointers start out null rather than pointing at instance 0
+        let instance = InstanceHandle {
+            struct_index: struct_index as u32,
+            instance_index,
+        };
+        for (prop, offset) in self.instance_fields(instance) {
+            if prop.conversion_type == 0
+                && matches!(prop.data_type, DataType::StrongPointer | DataType::WeakPointer)
+            {
+                let null = instance_pointer(None);
+                self.instance_data[struct_index][offset..offset + 8]
+                    .copy_from_slice(zerocopy::IntoBytes::as_bytes(&null));
+            }
+        }
+
+        instance_index
+    }
+
+    fn resolve_target(&self, target: BuilderTarget) -> InstanceHandle {
+        match target {
+            BuilderTarget::Record(record) => self.record_instance(record),
+            BuilderTarget::Instance(instance) => instance,
+        }
+    }
+
+    fn instance_bytes(&self, instance: InstanceHandle) -> &[u8] {
+        let struct_index = instance.struct_index as usize;
+        let struct_size = self.structs[struct_index].size as usize;
+        let start = instance.instance_index as usize * struct_size;
+        &self.instance_data[struct_index][start..start + struct_size]
+    }
+
     /// Set a boolean property value.
-    pub fn set_bool(&mut self, record: RecordHandle, property: &str, value: bool) {
+    pub fn set_bool(&mut self, record: impl Into<BuilderTarget>, property: &str, value: bool) {
         self.set_value(record, property, |offset, data| {
             data[offset] = value as u8;
         });
     }
 
     /// Set an i8 property value.
-    pub fn set_i8(&mut self, record: RecordHandle, property: &str, value: i8) {
+    pub fn set_i8(&mut self, record: impl Into<BuilderTarget>, property: &str, value: i8) {
         self.set_value(record, property, |offset, data| {
             data[offset] = value as u8;
         });
     }
 
     /// Set an i16 property value.
-    pub fn set_i16(&mut self, record: RecordHandle, property: &str, value: i16) {
+    pub fn set_i16(&mut self, record: impl Into<BuilderTarget>, property: &str, value: i16) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set an i32 property value.
-    pub fn set_i32(&mut self, record: RecordHandle, property: &str, value: i32) {
+    pub fn set_i32(&mut self, record: impl Into<BuilderTarget>, property: &str, value: i32) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set an i64 property value.
-    pub fn set_i64(&mut self, record: RecordHandle, property: &str, value: i64) {
+    pub fn set_i64(&mut self, record: impl Into<BuilderTarget>, property: &str, value: i64) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a u8 property value.
-    pub fn set_u8(&mut self, record: RecordHandle, property: &str, value: u8) {
+    pub fn set_u8(&mut self, record: impl Into<BuilderTarget>, property: &str, value: u8) {
         self.set_value(record, property, |offset, data| {
             data[offset] = value;
         });
     }
 
     /// Set a u16 property value.
-    pub fn set_u16(&mut self, record: RecordHandle, property: &str, value: u16) {
+    pub fn set_u16(&mut self, record: impl Into<BuilderTarget>, property: &str, value: u16) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a u32 property value.
-    pub fn set_u32(&mut self, record: RecordHandle, property: &str, value: u32) {
+    pub fn set_u32(&mut self, record: impl Into<BuilderTarget>, property: &str, value: u32) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a u64 property value.
-    pub fn set_u64(&mut self, record: RecordHandle, property: &str, value: u64) {
+    pub fn set_u64(&mut self, record: impl Into<BuilderTarget>, property: &str, value: u64) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a float property value.
-    pub fn set_float(&mut self, record: RecordHandle, property: &str, value: f32) {
+    pub fn set_float(&mut self, record: impl Into<BuilderTarget>, property: &str, value: f32) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a double property value.
-    pub fn set_double(&mut self, record: RecordHandle, property: &str, value: f64) {
+    pub fn set_double(&mut self, record: impl Into<BuilderTarget>, property: &str, value: f64) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
         });
     }
 
     /// Set a string property value.
-    pub fn set_string(&mut self, record: RecordHandle, property: &str, value: &str) {
+    pub fn set_string(&mut self, record: impl Into<BuilderTarget>, property: &str, value: &str) {
+        let string_id = DataCoreStringId::new(self.string_table_1.add(value));
+        self.set_value(record, property, |offset, data| {
+            data[offset..offset + 4].copy_from_slice(&string_id.id().to_le_bytes());
+        });
+    }
+
+    /// Set an enum-choice property value by option name.
+    ///
+    /// Fails if the property is not an enum choice or `value` is not one of
+    /// the enum's options.
+    pub fn set_enum(&mut self, record: impl Into<BuilderTarget>, property: &str, value: &str) -> io::Result<()> {
+        let record = self.resolve_target(record.into());
+        let prop = self.expect_property(record, property, DataType::EnumChoice, false)?;
+
+        let enum_def = &self.enums[prop.struct_index as usize];
+        let first = enum_def.first_value_index as usize;
+        let options = &self.enum_options[first..first + enum_def.value_count as usize];
+        if !options.iter().any(|o| o == value) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} is not an option of {}", value, enum_def.name),
+            ));
+        }
+
         let string_id = DataCoreStringId::new(self.string_table_1.add(value));
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 4].copy_from_slice(&string_id.id().to_le_bytes());
         });
+        Ok(())
     }
 
     /// Set a GUID property value.
-    pub fn set_guid(&mut self, record: RecordHandle, property: &str, value: CigGuid) {
+    pub fn set_guid(&mut self, record: impl Into<BuilderTarget>, property: &str, value: CigGuid) {
         self.set_value(record, property, |offset, data| {
             data[offset..offset + 16].copy_from_slice(value.as_bytes());
         });
@@ -759,7 +1850,7 @@ impl DataCoreBuilder {
     /// Set a strong pointer property.
     pub fn set_strong_pointer(
         &mut self,
-        record: RecordHandle,
+        record: impl Into<BuilderTarget>,
         property: &str,
         target: Option<RecordHandle>,
     ) {
@@ -786,7 +1877,7 @@ impl DataCoreBuilder {
     /// Set a weak pointer property.
     pub fn set_weak_pointer(
         &mut self,
-        record: RecordHandle,
+        record: impl Into<BuilderTarget>,
         property: &str,
         target: Option<RecordHandle>,
     ) {
@@ -795,7 +1886,7 @@ impl DataCoreBuilder {
     }
 
     /// Set a reference property (by GUID).
-    pub fn set_reference(&mut self, record: RecordHandle, property: &str, target_guid: CigGuid) {
+    pub fn set_reference(&mut self, record: impl Into<BuilderTarget>, property: &str, target_guid: CigGuid) {
         let reference = DataCoreReference {
             record_id: target_guid,
             instance_index: 0,
@@ -807,29 +1898,118 @@ impl DataCoreBuilder {
         });
     }
 
+    /// Set an inline Class property by copying the values of an instance.
+    ///
+    /// Class values are stored inline in their owner, so later changes to
+    /// `instance` are not reflected in the property.
+    pub fn set_class(
+        &mut self,
+        record: impl Into<BuilderTarget>,
+        property: &str,
+        instance: InstanceHandle,
+    ) -> io::Result<()> {
+        let record = self.resolve_target(record.into());
+        let prop = self.expect_property(record, property, DataType::Class, false)?;
+        self.expect_instance_of(&prop, instance)?;
+
+        let bytes = self.instance_bytes(instance).to_vec();
+        self.set_value(record, property, |offset, data| {
+            data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        });
+        Ok(())
+    }
+
+    /// Set a strong pointer property to an embedded instance.
+    pub fn set_strong_pointer_instance(
+        &mut self,
+        record: impl Into<BuilderTarget>,
+        property: &str,
+        instance: Option<InstanceHandle>,
+    ) {
+        let pointer = instance_pointer(instance);
+        self.set_value(record, property, |offset, data| {
+            data[offset..offset + 8].copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+        });
+    }
+
+    /// Set an array property with Class values.
+    ///
+    /// Class array elements are consecutive instances of the property's struct
+    /// type. If `instances` are not consecutive, their values are copied into
+    /// newly allocated instances.
+    pub fn set_class_array(
+        &mut self,
+        record: impl Into<BuilderTarget>,
+        property: &str,
+        instances: &[InstanceHandle],
+    ) -> io::Result<()> {
+        let record = self.resolve_target(record.into());
+        let prop = self.expect_property(record, property, DataType::Class, true)?;
+        for &instance in instances {
+            self.expect_instance_of(&prop, instance)?;
+        }
+
+        let consecutive = instances
+            .windows(2)
+            .all(|w| w[1].instance_index == w[0].instance_index + 1);
+        let first_index = match instances.first() {
+            None => 0,
+            Some(first) if consecutive => first.instance_index,
+            Some(_) => {
+                let struct_index = prop.struct_index as usize;
+                let first_index = self.struct_instance_counts[struct_index];
+                for &instance in instances {
+                    let bytes = self.instance_bytes(instance).to_vec();
+                    self.allocate_instance(struct_index);
+                    let data = &mut self.instance_data[struct_index];
+                    let start = data.len() - bytes.len();
+                    data[start..].copy_from_slice(&bytes);
+                }
+                first_index
+            }
+        };
+
+        self.set_array_header(record, property, instances.len() as i32, first_index as i32);
+        Ok(())
+    }
+
+    /// Set an array property with strong pointers to embedded instances.
+    pub fn set_strong_pointer_array(
+        &mut self,
+        record: impl Into<BuilderTarget>,
+        property: &str,
+        instances: &[InstanceHandle],
+    ) {
+        let first_index = self.strong_pool.len() as i32;
+        for &instance in instances {
+            self.strong_pool.push(instance_pointer(Some(instance)));
+        }
+        self.set_array_header(record, property, instances.len() as i32, first_index);
+    }
+
     /// Set an array property with boolean values.
-    pub fn set_bool_array(&mut self, record: RecordHandle, property: &str, values: &[bool]) {
+    pub fn set_bool_array(&mut self, record: impl Into<BuilderTarget>, property: &str, values: &[bool]) {
         let first_index = self.bool_pool.len() as i32;
         self.bool_pool.extend_from_slice(values);
         self.set_array_header(record, property, values.len() as i32, first_index);
     }
 
     /// Set an array property with i32 values.
-    pub fn set_i32_array(&mut self, record: RecordHandle, property: &str, values: &[i32]) {
+    pub fn set_i32_array(&mut self, record: impl Into<BuilderTarget>, property: &str, values: &[i32]) {
         let first_index = self.int32_pool.len() as i32;
         self.int32_pool.extend_from_slice(values);
         self.set_array_header(record, property, values.len() as i32, first_index);
     }
 
     /// Set an array property with f32 values.
-    pub fn set_float_array(&mut self, record: RecordHandle, property: &str, values: &[f32]) {
+    pub fn set_float_array(&mut self, record: impl Into<BuilderTarget>, property: &str, values: &[f32]) {
         let first_index = self.float_pool.len() as i32;
         self.float_pool.extend_from_slice(values);
         self.set_array_header(record, property, values.len() as i32, first_index);
     }
 
     /// Set an array property with string values.
-    pub fn set_string_array(&mut self, record: RecordHandle, property: &str, values: &[&str]) {
+    pub fn set_string_array(&mut self, record: impl Into<BuilderTarget>, property: &str, values: &[&str]) {
         let first_index = self.string_id_pool.len() as i32;
         for value in values {
             let string_id = DataCoreStringId::new(self.string_table_1.add(value));
@@ -839,15 +2019,25 @@ impl DataCoreBuilder {
     }
 
     /// Set an array property with GUID values.
-    pub fn set_guid_array(&mut self, record: RecordHandle, property: &str, values: &[CigGuid]) {
+    pub fn set_guid_array(&mut self, record: impl Into<BuilderTarget>, property: &str, values: &[CigGuid]) {
         let first_index = self.guid_pool.len() as i32;
         self.guid_pool.extend_from_slice(values);
         self.set_array_header(record, property, values.len() as i32, first_index);
     }
 
+    /// Set an array property with references to records (by GUID).
+    pub fn set_reference_array(&mut self, record: impl Into<BuilderTarget>, property: &str, targets: &[CigGuid]) {
+        let first_index = self.reference_pool.len() as i32;
+        self.reference_pool.extend(targets.iter().map(|&record_id| DataCoreReference {
+            record_id,
+            instance_index: 0,
+        }));
+        self.set_array_header(record, property, targets.len() as i32, first_index);
+    }
+
     fn set_array_header(
         &mut self,
-        record: RecordHandle,
+        record: impl Into<BuilderTarget>,
         property: &str,
         count: i32,
         first_index: i32,
@@ -858,13 +2048,13 @@ impl DataCoreBuilder {
         });
     }
 
-    fn set_value<F>(&mut self, record: RecordHandle, property: &str, setter: F)
+    fn set_value<F>(&mut self, record: impl Into<BuilderTarget>, property: &str, setter: F)
     where
         F: FnOnce(usize, &mut [u8]),
     {
-        let record_def = &self.records[record.0 as usize];
-        let struct_index = record_def.struct_index as usize;
-        let instance_index = record_def.instance_index as usize;
+        let instance = self.resolve_target(record.into());
+        let struct_index = instance.struct_index as usize;
+        let instance_index = instance.instance_index as usize;
         let struct_def = &self.structs[struct_index];
         let struct_size = struct_def.size as usize;
 
@@ -876,6 +2066,61 @@ impl DataCoreBuilder {
         }
     }
 
+    /// Look up a property of an instance, checking its type and arrayness.
+    fn expect_property(
+        &self,
+        instance: InstanceHandle,
+        property: &str,
+        data_type: DataType,
+        is_array: bool,
+    ) -> io::Result<PropertyDef> {
+        let prop = self
+            .find_property(instance.struct_index as usize, property)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("unknown property: {}", property))
+            })?;
+        if prop.data_type != data_type || (prop.conversion_type != 0) != is_array {
+            let kind = if is_array { "array" } else { "property" };
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a {} {}", property, data_type.as_str(), kind),
+            ));
+        }
+        Ok(prop.clone())
+    }
+
+    fn expect_instance_of(&self, prop: &PropertyDef, instance: InstanceHandle) -> io::Result<()> {
+        if instance.struct_index != prop.struct_index as u32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} expects instances of {}, got {}",
+                    prop.name,
+                    self.structs[prop.struct_index as usize].name,
+                    self.structs[instance.struct_index as usize].name
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Find a property definition of a struct, traversing the inheritance chain.
+    fn find_property(&self, struct_index: usize, property: &str) -> Option<&PropertyDef> {
+        let struct_def = &self.structs[struct_index];
+
+        if struct_def.parent_index >= 0 {
+            if let Some(prop) = self.find_property(struct_def.parent_index as usize, property) {
+                return Some(prop);
+            }
+        }
+
+        let first_prop = struct_def.first_property_index as usize;
+        let prop_count = struct_def.property_count as usize;
+        self.properties[first_prop..first_prop + prop_count]
+            .iter()
+            .find(|p| p.name == property)
+    }
+
     /// Find the offset of a property within a struct, traversing the inheritance chain.
     fn find_property_offset(&self, struct_index: usize, property: &str) -> Option<usize> {
         let struct_def = &self.structs[struct_index];
@@ -901,17 +2146,23 @@ impl DataCoreBuilder {
                 return Some(offset);
             }
 
-            // Advance offset - array if conversion_type != 0
-            offset += if prop.conversion_type != 0 {
-                8
-            } else {
-                prop.data_type.inline_size()
-            };
+            offset += self.property_size(prop);
         }
 
         None
     }
 
+    /// Size of a property within its struct; inline classes take their struct's size.
+    fn property_size(&self, prop: &PropertyDef) -> usize {
+        if prop.conversion_type != 0 {
+            8 // count + first_index
+        } else if prop.data_type == DataType::Class {
+            self.structs[prop.struct_index as usize].size as usize
+        } else {
+            prop.data_type.inline_size()
+        }
+    }
+
     /// Build the database and write to a file.
     pub fn write_to_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
         let data = self.build()?;
@@ -1005,6 +2256,7 @@ impl DataCoreBuilder {
         let records: Vec<DataCoreRecord> = self
             .records
             .iter()
+            .filter(|r| !r.removed)
             .map(|r| DataCoreRecord {
                 name_offset: DataCoreStringId2::new(self.string_table_2_offset(&r.name)),
                 file_name_offset: DataCoreStringId::new(self.string_table_1.offsets.get(&r.file_name).copied().unwrap_or(-1)),
@@ -1023,148 +2275,115 @@ impl DataCoreBuilder {
             .collect();
 
         // Write header
-        writer.write_all(&unknown1.to_le_bytes())?;
-        writer.write_all(&version.to_le_bytes())?;
-        writer.write_all(&unknown2.to_le_bytes())?;
-        writer.write_all(&unknown3.to_le_bytes())?;
+        let mut out = BinaryWriter::with_capacity(self.payload_len());
+        out.write_u32(unknown1);
+        out.write_u32(version);
+        out.write_u32(unknown2);
+        out.write_u32(unknown3);
 
         // Counts
-        writer.write_all(&(struct_defs.len() as i32).to_le_bytes())?;
-        writer.write_all(&(property_defs.len() as i32).to_le_bytes())?;
-        writer.write_all(&(enum_defs.len() as i32).to_le_bytes())?;
-        writer.write_all(&(data_mappings.len() as i32).to_le_bytes())?;
-        writer.write_all(&(records.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.bool_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.int8_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.int16_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.int32_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.int64_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.uint8_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.uint16_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.uint32_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.uint64_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.float_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.double_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.guid_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.string_id_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.locale_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.enum_value_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.strong_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.weak_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.reference_pool.len() as i32).to_le_bytes())?;
-        writer.write_all(&(enum_option_ids.len() as i32).to_le_bytes())?;
-        writer.write_all(&(self.string_table_1.len() as u32).to_le_bytes())?;
-        writer.write_all(&(self.string_table_2.len() as u32).to_le_bytes())?;
+        for count in [
+            struct_defs.len(),
+            property_defs.len(),
+            enum_defs.len(),
+            data_mappings.len(),
+            records.len(),
+            self.bool_pool.len(),
+            self.int8_pool.len(),
+            self.int16_pool.len(),
+            self.int32_pool.len(),
+            self.int64_pool.len(),
+            self.uint8_pool.len(),
+            self.uint16_pool.len(),
+            self.uint32_pool.len(),
+            self.uint64_pool.len(),
+            self.float_pool.len(),
+            self.double_pool.len(),
+            self.guid_pool.len(),
+            self.string_id_pool.len(),
+            self.locale_pool.len(),
+            self.enum_value_pool.len(),
+            self.strong_pool.len(),
+            self.weak_pool.len(),
+            self.reference_pool.len(),
+            enum_option_ids.len(),
+        ] {
+            out.write_i32(count as i32);
+        }
+        out.write_u32(self.string_table_1.len() as u32);
+        out.write_u32(self.string_table_2.len() as u32);
 
         // Write definitions
-        for s in &struct_defs {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(s))?;
-        }
-        for p in &property_defs {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(p))?;
-        }
-        for e in &enum_defs {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(e))?;
-        }
-        for m in &data_mappings {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(m))?;
-        }
-        for r in &records {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(r))?;
-        }
+        out.write_slice(&struct_defs);
+        out.write_slice(&property_defs);
+        out.write_slice(&enum_defs);
+        out.write_slice(&data_mappings);
+        out.write_slice(&records);
 
         // Write value pools
-        // int8
-        for v in &self.int8_pool {
-            writer.write_all(&v.to_le_bytes())?;
-        }
-        // int16
-        for v in &self.int16_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.int8_pool {
+            out.write_i8(v);
         }
-        // int32
-        for v in &self.int32_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.int16_pool {
+            out.write_i16(v);
         }
-        // int64
-        for v in &self.int64_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.int32_pool {
+            out.write_i32(v);
         }
-        // uint8
-        for v in &self.uint8_pool {
-            writer.write_all(&[*v])?;
+        for &v in &self.int64_pool {
+            out.write_i64(v);
         }
-        // uint16
-        for v in &self.uint16_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        out.write_bytes(&self.uint8_pool);
+        for &v in &self.uint16_pool {
+            out.write_u16(v);
         }
-        // uint32
-        for v in &self.uint32_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.uint32_pool {
+            out.write_u32(v);
         }
-        // uint64
-        for v in &self.uint64_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.uint64_pool {
+            out.write_u64(v);
         }
-        // bool
-        for v in &self.bool_pool {
-            writer.write_all(&[*v as u8])?;
+        for &v in &self.bool_pool {
+            out.write_bool(v);
         }
-        // float
-        for v in &self.float_pool {
-            writer.write_all(&v.to_le_bytes())?;
+        for &v in &self.float_pool {
+            out.write_f32(v);
         }
-        // double
-        for v in &self.double_pool {
-            writer.write_all(&v.to_le_bytes())?;
-        }
-        // guid
-        for v in &self.guid_pool {
-            writer.write_all(v.as_bytes())?;
+        for &v in &self.double_pool {
+            out.write_f64(v);
         }
+        out.write_slice(&self.guid_pool);
 
         // Reference pools
-        // string_id
         for v in &self.string_id_pool {
-            writer.write_all(&v.id().to_le_bytes())?;
+            out.write_i32(v.id());
         }
-        // locale
         for v in &self.locale_pool {
-            writer.write_all(&v.id().to_le_bytes())?;
+            out.write_i32(v.id());
         }
-        // enum_value
         for v in &self.enum_value_pool {
-            writer.write_all(&v.id().to_le_bytes())?;
-        }
-        // strong
-        for v in &self.strong_pool {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(v))?;
-        }
-        // weak
-        for v in &self.weak_pool {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(v))?;
+            out.write_i32(v.id());
         }
-        // reference
-        for v in &self.reference_pool {
-            writer.write_all(zerocopy::IntoBytes::as_bytes(v))?;
-        }
-        // enum options
+        out.write_slice(&self.strong_pool);
+        out.write_slice(&self.weak_pool);
+        out.write_slice(&self.reference_pool);
         for v in &enum_option_ids {
-            writer.write_all(&v.id().to_le_bytes())?;
+            out.write_i32(v.id());
         }
 
         // Write string tables
-        writer.write_all(&self.string_table_1.data)?;
-        writer.write_all(&self.string_table_2.data)?;
+        out.write_bytes(&self.string_table_1.data);
+        out.write_bytes(&self.string_table_2.data);
 
         // Write instance data (data section) in data_mappings order
         // This is critical - instance data must be written in the order
         // specified by data_mappings, not in struct index order
         for mapping in &data_mappings {
             let struct_index = mapping.struct_index as usize;
-            writer.write_all(&self.instance_data[struct_index])?;
+            out.write_bytes(&self.instance_data[struct_index]);
         }
 
+        writer.write_all(out.as_bytes())?;
         Ok(())
     }
 
@@ -1196,7 +2415,7 @@ impl DataCoreBuilder {
         }
 
         // Add all record names
-        for r in &self.records {
+        for r in self.records.iter().filter(|r| !r.removed) {
             self.string_table_2.add(&r.name);
             self.string_table_1.add(&r.file_name);
         }
@@ -1232,6 +2451,194 @@ mod tests {
         assert!(!data.is_empty());
     }
 
+    #[test]
+    fn test_enum_property() {
+        let mut builder = DataCoreBuilder::new();
+        let grade = builder.add_enum("Grade", &["A", "B", "C"]);
+        let item = builder.add_struct("Item", None);
+        builder.add_enum_property(item, "grade", grade);
+        builder.add_property(item, "name", DataType::String);
+
+        let record = builder.add_record("Item1", item, "items/item1.xml");
+        builder.set_enum(record, "grade", "B").unwrap();
+        assert!(builder.set_enum(record, "grade", "D").is_err());
+        assert!(builder.set_enum(record, "name", "A").is_err());
+
+        let db = crate::DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Item1").unwrap();
+        assert_eq!(record.get("grade"), Some(crate::Value::Enum("B")));
+        assert_eq!(db.enum_name({ db.property_definitions()[0].struct_index } as usize), Some("Grade"));
+    }
+
+    #[test]
+    fn test_embedded_instances() {
+        let mut builder = DataCoreBuilder::new();
+        let stats = builder.add_struct("Stats", None);
+        builder.add_property(stats, "health", DataType::Int32);
+        let component = builder.add_struct("Component", None);
+        builder.add_property(component, "name", DataType::String);
+        let loadout = builder.add_struct("Loadout", None);
+        builder.add_typed_property(loadout, "stats", DataType::Class, stats);
+        builder.add_property(loadout, "name", DataType::String);
+        builder.add_typed_array_property(loadout, "components", DataType::Class, component);
+        builder.add_typed_array_property(loadout, "ports", DataType::StrongPointer, component);
+
+        let record = builder.add_record("Loadout1", loadout, "loadouts/loadout1.xml");
+        let base_stats = builder.add_instance(stats);
+        builder.set_i32(base_stats, "health", 250);
+        builder.set_class(record, "stats", base_stats).unwrap();
+        builder.set_string(record, "name", "default");
+
+        let a = builder.add_instance(component);
+        let b = builder.add_instance(component);
+        builder.set_string(a, "name", "Shield");
+        builder.set_string(b, "name", "Cooler");
+        builder.set_class_array(record, "components", &[b, a]).unwrap();
+        builder.set_strong_pointer_array(record, "ports", &[a]);
+        assert!(builder.set_class(record, "stats", a).is_err());
+
+        let db = crate::DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Loadout1").unwrap();
+        assert_eq!(record.get_str("name"), Some("default"));
+
+        let xml = crate::XmlExporter::new(&db).export_record(&db.records()[0]).unwrap();
+        assert!(xml.contains("<health>250</health>"), "{}", xml);
+        let cooler = xml.find("Cooler").unwrap();
+        let shield = xml.find("Shield").unwrap();
+        assert!(cooler < shield);
+    }
+
+    #[test]
+    fn test_remove_and_clone_record() {
+        let mut builder = DataCoreBuilder::new();
+        let component = builder.add_struct("Component", None);
+        builder.add_property(component, "name", DataType::String);
+        let ship = builder.add_struct("Ship", None);
+        builder.add_property(ship, "name", DataType::String);
+        builder.add_typed_array_property(ship, "components", DataType::StrongPointer, component);
+        builder.add_typed_property(ship, "parent", DataType::Reference, ship);
+
+        let base = builder.add_record("Base", ship, "ships/base.xml");
+        let shield = builder.add_instance(component);
+        builder.set_string(shield, "name", "Shield");
+        builder.set_string(base, "name", "base");
+        builder.set_strong_pointer_array(base, "components", &[shield]);
+
+        let variant_guid = CigGuid::random();
+        let base_guid = builder.records[base.0 as usize].guid;
+        let variant = builder.clone_record(base_guid, "Variant", variant_guid).unwrap();
+        builder.set_string(variant, "name", "variant");
+        builder.set_reference(variant, "parent", base_guid);
+        assert!(builder.clone_record(base, "Again", variant_guid).is_err());
+
+        let copy = builder.strong_pool[1];
+        assert_ne!({ copy.instance_index }, shield.instance_index as i32);
+        assert_eq!(builder.records[variant.0 as usize].file_name, "ships/variant.xml");
+
+        let db = crate::DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Variant").unwrap();
+        assert_eq!(record.get_str("name"), Some("variant"));
+        assert_eq!(db.record_by_name("Base").unwrap().get_str("name"), Some("base"));
+        assert!(db.is_main_record(variant_guid));
+
+        assert_eq!(builder.remove_record(base).unwrap(), vec![variant]);
+        assert!(builder.remove_record(base_guid).is_err());
+        assert!(builder.find_record(&base_guid).is_none());
+
+        let db = crate::DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        assert_eq!(db.records().len(), 1);
+        assert!(db.record_by_name("Base").is_none());
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "name", DataType::String);
+        builder.add_array_property(item, "tags", DataType::String);
+        builder.add_array_property(item, "weights", DataType::Single);
+        builder.add_typed_array_property(item, "children", DataType::StrongPointer, item);
+
+        let dropped = builder.add_record("Dropped", item, "items/dropped.xml");
+        builder.set_string(dropped, "name", "dropped");
+        builder.set_float_array(dropped, "weights", &[1.0, 2.0, 3.0]);
+        let kept = builder.add_record("Kept", item, "items/kept.xml");
+        let child = builder.add_instance(item);
+        builder.set_string(child, "name", "child");
+        builder.set_string(kept, "name", "old");
+        builder.set_string(kept, "name", "kept");
+        builder.set_string_array(kept, "tags", &["a", "b"]);
+        builder.set_float_array(kept, "weights", &[4.0]);
+        builder.set_strong_pointer_array(kept, "children", &[child]);
+        builder.remove_record(dropped).unwrap();
+
+        let stats = builder.compact();
+        assert_eq!(stats.instances_removed, 1);
+        assert_eq!(stats.pool_entries_removed, 3);
+        assert_eq!(stats.strings_removed, 2);
+        assert!(stats.bytes_saved > 0);
+        assert_eq!(builder.compact().bytes_saved, 0);
+
+        let db = crate::DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Kept").unwrap();
+        assert_eq!(record.get_str("name"), Some("kept"));
+        let xml = crate::XmlExporter::new(&db).export_record(&db.records()[0]).unwrap();
+        assert!(xml.contains(">4<") && xml.contains("child") && xml.contains(">b<"), "{}", xml);
+        assert!(!db.raw_string_table_1().windows(3).any(|w| w == b"old"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut builder = DataCoreBuilder::new();
+        let grade = builder.add_enum("Grade", &["A", "B"]);
+        let item = builder.add_struct("Item", None);
+        builder.add_enum_property(item, "grade", grade);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder.add_typed_property(item, "child", DataType::StrongPointer, item);
+        builder.add_array_property(item, "weights", DataType::Single);
+
+        let record = builder.add_record("Item1", item, "items/item1.xml");
+        builder.set_enum(record, "grade", "A").unwrap();
+        assert!(builder.validate().is_empty());
+
+        let missing = CigGuid::random();
+        builder.set_reference(record, "parent", missing);
+        builder.set_string(record, "grade", "Z");
+        builder.set_float_array(record, "weights", &[1.0, 2.0]);
+        builder.float_pool.truncate(1);
+        builder.set_strong_pointer_instance(
+            record,
+            "child",
+            Some(InstanceHandle { struct_index: 1, instance_index: 7 }),
+        );
+
+        let issues = builder.validate();
+        assert_eq!(issues.len(), 4);
+        assert!(issues.contains(&ValidationIssue::MissingReference {
+            record: "Item1".into(),
+            property: "parent".into(),
+            target: missing,
+        }));
+        assert!(issues.iter().any(|i| matches!(i, ValidationIssue::InvalidEnumValue { value, .. } if value == "Z")));
+        assert!(issues.iter().any(|i| matches!(i, ValidationIssue::ArrayOutOfRange { available: 1, .. })));
+        assert!(issues.iter().any(|i| matches!(i, ValidationIssue::PointerOutOfRange { instance_index: 7, .. })));
+        assert!(issues.iter().all(|i| i.to_string().starts_with("Item1.")));
+
+        // Growing a parent after deriving from it leaves the child too small.
+        let base = builder.add_struct("Base", None);
+        let derived = builder.add_struct("Derived", Some(base));
+        builder.add_property(derived, "x", DataType::Int32);
+        builder.add_property(base, "late", DataType::Int32);
+        assert_eq!(
+            builder.validate(),
+            vec![ValidationIssue::StructSizeMismatch {
+                struct_name: "Derived".into(),
+                declared: 4,
+                computed: 8,
+            }]
+        );
+    }
+
     #[test]
     fn test_string_table() {
         let mut table = StringTable::new();
@@ -0,0 +1,970 @@
+//! Merging a second database into a builder.
+//!
+//! Struct and enum definitions are matched by name. Structs missing from the
+//! builder are imported; structs present in both must have the same layout.
+//! Records are deep-copied together with every instance they own, and
+//! conflicts with existing records are resolved by a [`MergeStrategy`].
+
+use std::io;
+
+use svarog_common::CigGuid;
+
+use super::{
+    instance_pointer, pointer_target, read_array_header, read_guid, read_pointer,
+    write_array_header, DataCoreBuilder, EnumDef, FxHashMap, InstanceHandle, PropertyDef,
+    RecordDef, RecordHandle, StructDef,
+};
+use crate::structs::{DataCorePointer, DataCoreReference, DataCoreStringId};
+use crate::{DataCoreDatabase, DataType, PoolType};
+
+/// How [`DataCoreBuilder::merge`] resolves conflicts between records.
+///
+/// An incoming record conflicts with an existing one if they share a GUID, or
+/// if they have the same struct type and name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Fail without changing the builder.
+    #[default]
+    Error,
+    /// Keep the existing record. References to the incoming record are
+    /// redirected to it.
+    KeepExisting,
+    /// Replace the existing record. The incoming record takes over the
+    /// existing GUID, so references from either database stay valid.
+    Replace,
+    /// Import the incoming record under a new GUID if its GUID is taken, and
+    /// under a suffixed name and file if its name is taken.
+    Rename,
+}
+
+/// Counts reported by [`DataCoreBuilder::merge`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    /// Struct types imported from the other database.
+    pub structs_added: usize,
+    /// Enum types imported from the other database.
+    pub enums_added: usize,
+    /// Existing enums that gained options.
+    pub enums_extended: usize,
+    /// Records imported without a conflict.
+    pub records_added: usize,
+    /// Existing records replaced by incoming ones.
+    pub records_replaced: usize,
+    /// Incoming records imported under a new GUID or name.
+    pub records_renamed: usize,
+    /// Incoming records skipped in favor of existing ones.
+    pub records_skipped: usize,
+}
+
+/// What to do with one incoming record.
+enum RecordPlan {
+    Add {
+        guid: CigGuid,
+        name: String,
+        file_name: String,
+    },
+    Replace {
+        existing: RecordHandle,
+    },
+    Skip {
+        existing: RecordHandle,
+    },
+}
+
+/// State of a merge in progress.
+struct Merger {
+    /// Builder struct index for each struct of the other database.
+    structs: Vec<u32>,
+    /// Final GUID of each incoming record.
+    guids: FxHashMap<CigGuid, CigGuid>,
+    /// Copies made so far, by source instance.
+    copied: FxHashMap<InstanceHandle, InstanceHandle>,
+    /// Every instance filled from the other database.
+    filled: Vec<InstanceHandle>,
+}
+
+impl DataCoreBuilder {
+    /// Merge the struct definitions and records of another database into
+    /// this builder.
+    ///
+    /// Mod frameworks can use this to layer custom records on top of the
+    /// shipped database. Missing structs and enums are imported, and enums
+    /// present in both gain the other database's extra options. Each record
+    /// is deep-copied with its embedded instances; references to incoming
+    /// records follow the GUIDs chosen by `strategy`.
+    ///
+    /// Fails without changing the builder if a struct exists in both
+    /// databases with a different layout, or if a record conflicts and
+    /// `strategy` is [`MergeStrategy::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use svarog_datacore::{DataCoreBuilder, DataCoreDatabase, MergeStrategy};
+    ///
+    /// let game = DataCoreDatabase::open("Game.dcb")?;
+    /// let addon = DataCoreDatabase::open("MyMod.dcb")?;
+    ///
+    /// let mut builder = DataCoreBuilder::from_database(&game)?;
+    /// let stats = builder.merge(&addon, MergeStrategy::Replace)?;
+    /// println!("{} records added, {} replaced", stats.records_added, stats.records_replaced);
+    /// builder.write_to_file("Game_modded.dcb")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn merge(
+        &mut self,
+        other: &DataCoreDatabase,
+        strategy: MergeStrategy,
+    ) -> io::Result<MergeStats> {
+        let other = DataCoreBuilder::from_database(other)?;
+        let mut stats = MergeStats::default();
+
+        self.check_struct_layouts(&other)?;
+        let plans = self.plan_records(&other, strategy)?;
+
+        let enums = self.merge_enums(&other, &mut stats);
+        let structs = self.merge_structs(&other, &enums, &mut stats);
+        let mut merger = Merger {
+            structs,
+            guids: FxHashMap::default(),
+            copied: FxHashMap::default(),
+            filled: Vec::new(),
+        };
+
+        for (record, plan) in other.records.iter().zip(&plans) {
+            let guid = match plan {
+                RecordPlan::Add { guid, .. } => *guid,
+                RecordPlan::Replace { existing } | RecordPlan::Skip { existing } => {
+                    self.records[existing.0 as usize].guid
+                }
+            };
+            merger.guids.insert(record.guid, guid);
+        }
+
+        for (record, plan) in other.records.iter().zip(plans) {
+            let (guid, name, file_name) = match plan {
+                RecordPlan::Skip { .. } => {
+                    stats.records_skipped += 1;
+                    continue;
+                }
+                RecordPlan::Replace { existing } => {
+                    stats.records_replaced += 1;
+                    let existing = &mut self.records[existing.0 as usize];
+                    existing.removed = true;
+                    (existing.guid, record.name.clone(), record.file_name.clone())
+                }
+                RecordPlan::Add {
+                    guid,
+                    name,
+                    file_name,
+                } => {
+                    if guid != record.guid || name != record.name {
+                        stats.records_renamed += 1;
+                    } else {
+                        stats.records_added += 1;
+                    }
+                    (guid, name, file_name)
+                }
+            };
+
+            let source = InstanceHandle {
+                struct_index: record.struct_index,
+                instance_index: record.instance_index as u32,
+            };
+            let instance = self.merge_instance(&other, source, &mut merger);
+            self.records.push(RecordDef {
+                name,
+                file_name,
+                struct_index: instance.struct_index,
+                guid,
+                instance_index: instance.instance_index as u16,
+                removed: false,
+            });
+        }
+
+        self.merge_weak_pointers(&merger);
+        Ok(stats)
+    }
+
+    /// Check that structs defined in both builders have the same layout.
+    fn check_struct_layouts(&self, other: &DataCoreBuilder) -> io::Result<()> {
+        let existing = self.struct_indices_by_name();
+        for (other_index, other_struct) in other.structs.iter().enumerate() {
+            let Some(&index) = existing.get(other_struct.name.as_str()) else {
+                continue;
+            };
+            if self.structs[index as usize].size != other_struct.size
+                || self.layout_signature(index as usize) != other.layout_signature(other_index)
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "struct {} has a different layout in the merged database",
+                        other_struct.name
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Property names, types and target type names in layout order.
+    fn layout_signature(&self, struct_index: usize) -> Vec<(String, DataType, bool, &str)> {
+        let instance = InstanceHandle {
+            struct_index: struct_index as u32,
+            instance_index: 0,
+        };
+        self.instance_fields(instance)
+            .into_iter()
+            .map(|(prop, _)| {
+                let target = self.property_target_name(&prop);
+                (prop.name, prop.data_type, prop.conversion_type != 0, target)
+            })
+            .collect()
+    }
+
+    fn property_target_name(&self, prop: &PropertyDef) -> &str {
+        let index = prop.struct_index as usize;
+        match prop.data_type {
+            DataType::Class
+            | DataType::StrongPointer
+            | DataType::WeakPointer
+            | DataType::Reference => self.structs.get(index).map_or("", |s| s.name.as_str()),
+            DataType::EnumChoice => self.enums.get(index).map_or("", |e| e.name.as_str()),
+            _ => "",
+        }
+    }
+
+    fn struct_indices_by_name(&self) -> FxHashMap<&str, u32> {
+        self.structs
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name.as_str(), i as u32))
+            .collect()
+    }
+
+    /// An existing record with the given GUID, or struct type and name.
+    fn conflicting_record(
+        &self,
+        guid: &CigGuid,
+        (type_name, name): (&str, &str),
+    ) -> Option<RecordHandle> {
+        self.find_record(guid).or_else(|| {
+            self.records
+                .iter()
+                .position(|r| {
+                    !r.removed
+                        && r.name == name
+                        && self.structs[r.struct_index as usize].name == type_name
+                })
+                .map(|i| RecordHandle(i as u32))
+        })
+    }
+
+    /// Decide what happens to each incoming record, before changing anything.
+    fn plan_records(
+        &self,
+        other: &DataCoreBuilder,
+        strategy: MergeStrategy,
+    ) -> io::Result<Vec<RecordPlan>> {
+        let mut taken_guids: std::collections::HashSet<CigGuid> = self
+            .records
+            .iter()
+            .filter(|r| !r.removed)
+            .map(|r| r.guid)
+            .collect();
+        let mut taken_names: std::collections::HashSet<(String, String)> = self
+            .records
+            .iter()
+            .filter(|r| !r.removed)
+            .map(|r| {
+                (
+                    self.structs[r.struct_index as usize].name.clone(),
+                    r.name.clone(),
+                )
+            })
+            .collect();
+
+        let mut plans = Vec::with_capacity(other.records.len());
+        for record in &other.records {
+            let type_name = other.structs[record.struct_index as usize].name.as_str();
+            let existing = self.conflicting_record(&record.guid, (type_name, &record.name));
+
+            let plan = match (existing, strategy) {
+                (None, _) => RecordPlan::Add {
+                    guid: record.guid,
+                    name: record.name.clone(),
+                    file_name: record.file_name.clone(),
+                },
+                (Some(existing), MergeStrategy::Error) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!(
+                            "record {} ({}) conflicts with existing record {} ({})",
+                            record.name,
+                            record.guid,
+                            self.records[existing.0 as usize].name,
+                            self.records[existing.0 as usize].guid
+                        ),
+                    ));
+                }
+                (Some(existing), MergeStrategy::KeepExisting) => RecordPlan::Skip { existing },
+                (Some(existing), MergeStrategy::Replace) => RecordPlan::Replace { existing },
+                (Some(_), MergeStrategy::Rename) => {
+                    let guid = if taken_guids.contains(&record.guid) {
+                        CigGuid::random()
+                    } else {
+                        record.guid
+                    };
+                    let (name, file_name) =
+                        if taken_names.contains(&(type_name.to_string(), record.name.clone())) {
+                            let name = (1..)
+                                .map(|n| format!("{}_{}", record.name, n))
+                                .find(|name| {
+                                    !taken_names.contains(&(type_name.to_string(), name.clone()))
+                                })
+                                .unwrap();
+                            let file_name = match record.file_name.rfind('/') {
+                                Some(slash) => format!(
+                                    "{}/{}.xml",
+                                    &record.file_name[..slash],
+                                    name.to_lowercase()
+                                ),
+                                None => format!("{}.xml", name.to_lowercase()),
+                            };
+                            (name, file_name)
+                        } else {
+                            (record.name.clone(), record.file_name.clone())
+                        };
+                    RecordPlan::Add {
+                        guid,
+                        name,
+                        file_name,
+                    }
+                }
+            };
+
+            if let RecordPlan::Add { guid, name, .. } = &plan {
+                taken_guids.insert(*guid);
+                taken_names.insert((type_name.to_string(), name.clone()));
+            }
+            plans.push(plan);
+        }
+
+        Ok(plans)
+    }
+
+    /// Import missing enums and extend existing ones, returning the builder
+    /// enum index for each enum of the other database.
+    fn merge_enums(&mut self, other: &DataCoreBuilder, stats: &mut MergeStats) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(other.enums.len());
+
+        for other_enum in &other.enums {
+            let start = other_enum.first_value_index as usize;
+            let options = &other.enum_options[start..start + other_enum.value_count as usize];
+
+            let Some(index) = self.enums.iter().position(|e| e.name == other_enum.name) else {
+                let options: Vec<&str> = options.iter().map(String::as_str).collect();
+                indices.push(self.add_enum(&other_enum.name, &options).0);
+                stats.enums_added += 1;
+                continue;
+            };
+
+            let existing = &self.enums[index];
+            let start = existing.first_value_index as usize;
+            let mut merged =
+                self.enum_options[start..start + existing.value_count as usize].to_vec();
+            let count = merged.len();
+            for option in options {
+                if !merged.contains(option) {
+                    merged.push(option.clone());
+                }
+            }
+            if merged.len() > count {
+                // Options of an enum are contiguous, so move the whole list to the end
+                self.enums[index] = EnumDef {
+                    name: other_enum.name.clone(),
+                    first_value_index: self.enum_options.len() as u16,
+                    value_count: merged.len() as u16,
+                };
+                self.enum_options.extend(merged);
+                stats.enums_extended += 1;
+            }
+            indices.push(index as u32);
+        }
+
+        indices
+    }
+
+    /// Import missing structs, returning the builder struct index for each
+    /// struct of the other database.
+    fn merge_structs(
+        &mut self,
+        other: &DataCoreBuilder,
+        enums: &[u32],
+        stats: &mut MergeStats,
+    ) -> Vec<u32> {
+        let indices: Vec<u32> = {
+            let existing = self.struct_indices_by_name();
+            let mut next = self.structs.len() as u32;
+            other
+                .structs
+                .iter()
+                .map(|s| {
+                    existing.get(s.name.as_str()).copied().unwrap_or_else(|| {
+                        next += 1;
+                        next - 1
+                    })
+                })
+                .collect()
+        };
+
+        for (other_index, other_struct) in other.structs.iter().enumerate() {
+            if (indices[other_index] as usize) < self.structs.len() {
+                continue;
+            }
+
+            let first_property = other_struct.first_property_index as usize;
+            let own_properties = &other.properties
+                [first_property..first_property + other_struct.property_count as usize];
+            self.structs.push(StructDef {
+                name: other_struct.name.clone(),
+                parent_index: match other_struct.parent_index {
+                    -1 => -1,
+                    parent => indices[parent as usize] as i32,
+                },
+                first_property_index: self.properties.len() as u16,
+                property_count: other_struct.property_count,
+                size: other_struct.size,
+            });
+            for prop in own_properties {
+                let struct_index = match prop.data_type {
+                    DataType::Class
+                    | DataType::StrongPointer
+                    | DataType::WeakPointer
+                    | DataType::Reference => indices[prop.struct_index as usize] as u16,
+                    DataType::EnumChoice => enums[prop.struct_index as usize] as u16,
+                    _ => prop.struct_index,
+                };
+                self.properties.push(PropertyDef {
+                    struct_index,
+                    ..prop.clone()
+                });
+            }
+            self.instance_data.push(Vec::new());
+            self.struct_instance_counts.push(0);
+            if let Some(order) = &mut self.original_data_mapping_order {
+                order.push(self.structs.len() - 1);
+            }
+            stats.structs_added += 1;
+        }
+
+        indices
+    }
+
+    /// Deep-copy an instance of the other builder, reusing copies made earlier.
+    fn merge_instance(
+        &mut self,
+        other: &DataCoreBuilder,
+        source: InstanceHandle,
+        merger: &mut Merger,
+    ) -> InstanceHandle {
+        if let Some(&copy) = merger.copied.get(&source) {
+            return copy;
+        }
+
+        let struct_index = merger.structs[source.struct_index as usize];
+        let copy = InstanceHandle {
+            struct_index,
+            instance_index: self.allocate_instance(struct_index as usize),
+        };
+        merger.copied.insert(source, copy);
+        self.fill_instance(other, source, copy, merger);
+        copy
+    }
+
+    /// Copy an instance's values, translating strings, GUIDs, pointers and
+    /// arrays into this builder.
+    fn fill_instance(
+        &mut self,
+        other: &DataCoreBuilder,
+        source: InstanceHandle,
+        target: InstanceHandle,
+        merger: &mut Merger,
+    ) {
+        let struct_index = target.struct_index as usize;
+        let size = self.structs[struct_index].size as usize;
+        let start = target.instance_index as usize * size;
+        self.instance_data[struct_index][start..start + size]
+            .copy_from_slice(other.instance_bytes(source));
+        merger.filled.push(target);
+
+        let source_data = &other.instance_data[source.struct_index as usize];
+        for ((prop, source_offset), (_, offset)) in other
+            .instance_fields(source)
+            .into_iter()
+            .zip(self.instance_fields(target))
+        {
+            match (prop.data_type, prop.conversion_type != 0) {
+                (DataType::String | DataType::Locale | DataType::EnumChoice, false) => {
+                    let id = i32::from_le_bytes(
+                        source_data[source_offset..source_offset + 4]
+                            .try_into()
+                            .unwrap(),
+                    );
+                    let id = self.string_table_1.add(&other.string_table_1.get(id));
+                    self.instance_data[struct_index][offset..offset + 4]
+                        .copy_from_slice(&id.to_le_bytes());
+                }
+                (DataType::Reference, false) => {
+                    let guid = read_guid(source_data, source_offset);
+                    let guid = merger.guids.get(&guid).copied().unwrap_or(guid);
+                    self.instance_data[struct_index][offset..offset + 16]
+                        .copy_from_slice(guid.as_bytes());
+                }
+                (DataType::StrongPointer, false) => {
+                    let copy = pointer_target(&read_pointer(source_data, source_offset))
+                        .filter(|&child| other.instance_exists(child))
+                        .map(|child| self.merge_instance(other, child, merger));
+                    self.instance_data[struct_index][offset..offset + 8]
+                        .copy_from_slice(zerocopy::IntoBytes::as_bytes(&instance_pointer(copy)));
+                }
+                (DataType::Class, true) => {
+                    let (count, first) = read_array_header(source_data, source_offset);
+                    let element_struct = merger.structs[prop.struct_index as usize];
+                    // Class array elements must be consecutive, so allocate them all first
+                    let elements: Vec<InstanceHandle> = (0..count)
+                        .map(|_| InstanceHandle {
+                            struct_index: element_struct,
+                            instance_index: self.allocate_instance(element_struct as usize),
+                        })
+                        .collect();
+                    for (i, &element) in elements.iter().enumerate() {
+                        let source = InstanceHandle {
+                            struct_index: prop.struct_index as u32,
+                            instance_index: first + i as u32,
+                        };
+                        if other.instance_exists(source) {
+                            merger.copied.entry(source).or_insert(element);
+                            self.fill_instance(other, source, element, merger);
+                        }
+                    }
+                    let first = elements.first().map_or(0, |e| e.instance_index as i32);
+                    write_array_header(
+                        &mut self.instance_data[struct_index],
+                        offset,
+                        elements.len(),
+                        first,
+                    );
+                }
+                (data_type, true) => {
+                    let Some(pool) = PoolType::for_data_type(data_type) else {
+                        continue;
+                    };
+                    let (count, first) = read_array_header(source_data, source_offset);
+                    let (first, count) =
+                        self.merge_pool_range(other, pool, first as usize, count as usize, merger);
+                    write_array_header(&mut self.instance_data[struct_index], offset, count, first);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Append a range of one of the other builder's value pools, returning
+    /// the new first index and element count.
+    fn merge_pool_range(
+        &mut self,
+        other: &DataCoreBuilder,
+        pool: PoolType,
+        first: usize,
+        count: usize,
+        merger: &mut Merger,
+    ) -> (i32, usize) {
+        fn append<T: Clone>(
+            target: &mut Vec<T>,
+            source: &[T],
+            first: usize,
+            count: usize,
+        ) -> (i32, usize) {
+            let start = first.min(source.len());
+            let end = (first + count).min(source.len());
+            let index = target.len() as i32;
+            target.extend_from_slice(&source[start..end]);
+            (index, end - start)
+        }
+        fn range<T>(source: &[T], first: usize, count: usize) -> &[T] {
+            &source[first.min(source.len())..(first + count).min(source.len())]
+        }
+
+        match pool {
+            PoolType::Bool => append(&mut self.bool_pool, &other.bool_pool, first, count),
+            PoolType::Int8 => append(&mut self.int8_pool, &other.int8_pool, first, count),
+            PoolType::Int16 => append(&mut self.int16_pool, &other.int16_pool, first, count),
+            PoolType::Int32 => append(&mut self.int32_pool, &other.int32_pool, first, count),
+            PoolType::Int64 => append(&mut self.int64_pool, &other.int64_pool, first, count),
+            PoolType::UInt8 => append(&mut self.uint8_pool, &other.uint8_pool, first, count),
+            PoolType::UInt16 => append(&mut self.uint16_pool, &other.uint16_pool, first, count),
+            PoolType::UInt32 => append(&mut self.uint32_pool, &other.uint32_pool, first, count),
+            PoolType::UInt64 => append(&mut self.uint64_pool, &other.uint64_pool, first, count),
+            PoolType::Float => append(&mut self.float_pool, &other.float_pool, first, count),
+            PoolType::Double => append(&mut self.double_pool, &other.double_pool, first, count),
+            PoolType::Guid => append(&mut self.guid_pool, &other.guid_pool, first, count),
+            // Weak pointers are redirected once every instance has been copied
+            PoolType::Weak => append(&mut self.weak_pool, &other.weak_pool, first, count),
+            PoolType::StringId | PoolType::Locale | PoolType::EnumValue => {
+                let source = match pool {
+                    PoolType::StringId => &other.string_id_pool,
+                    PoolType::Locale => &other.locale_pool,
+                    _ => &other.enum_value_pool,
+                };
+                let ids: Vec<_> = range(source, first, count)
+                    .iter()
+                    .map(|id| {
+                        DataCoreStringId::new(
+                            self.string_table_1.add(&other.string_table_1.get(id.id())),
+                        )
+                    })
+                    .collect();
+                let target = match pool {
+                    PoolType::StringId => &mut self.string_id_pool,
+                    PoolType::Locale => &mut self.locale_pool,
+                    _ => &mut self.enum_value_pool,
+                };
+                append(target, &ids, 0, ids.len())
+            }
+            PoolType::Reference => {
+                let references: Vec<_> = range(&other.reference_pool, first, count)
+                    .iter()
+                    .map(|r| DataCoreReference {
+                        record_id: merger
+                            .guids
+                            .get(&r.record_id)
+                            .copied()
+                            .unwrap_or(r.record_id),
+                        instance_index: r.instance_index,
+                    })
+                    .collect();
+                append(&mut self.reference_pool, &references, 0, references.len())
+            }
+            PoolType::Strong => {
+                let pointers = range(&other.strong_pool, first, count).to_vec();
+                let copies: Vec<_> = pointers
+                    .iter()
+                    .map(|p| {
+                        let copy = pointer_target(p)
+                            .filter(|&child| other.instance_exists(child))
+                            .map(|child| self.merge_instance(other, child, merger));
+                        instance_pointer(copy)
+                    })
+                    .collect();
+                append(&mut self.strong_pool, &copies, 0, copies.len())
+            }
+            PoolType::EnumOption => (0, 0),
+        }
+    }
+
+    /// Point weak pointers of merged instances at the copies of their targets,
+    /// or clear them if the target was not copied.
+    fn merge_weak_pointers(&mut self, merger: &Merger) {
+        let remap = |p: &DataCorePointer| {
+            instance_pointer(
+                pointer_target(p).and_then(|target| merger.copied.get(&target).copied()),
+            )
+        };
+
+        for &instance in &merger.filled {
+            let struct_index = instance.struct_index as usize;
+            for (prop, offset) in self.instance_fields(instance) {
+                if prop.data_type != DataType::WeakPointer {
+                    continue;
+                }
+                let data = &self.instance_data[struct_index];
+                if prop.conversion_type == 0 {
+                    let pointer = remap(&read_pointer(data, offset));
+                    self.instance_data[struct_index][offset..offset + 8]
+                        .copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+                } else {
+                    let (count, first) = read_array_header(data, offset);
+                    let end = ((first + count) as usize).min(self.weak_pool.len());
+                    for pointer in &mut self.weak_pool[(first as usize).min(end)..end] {
+                        *pointer = remap(pointer);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> DataCoreBuilder {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "label", DataType::String);
+        builder.add_property(item, "mass", DataType::Single);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder
+    }
+
+    #[test]
+    fn test_merge_records() {
+        let mut builder = base();
+        let item = super::super::StructHandle(0);
+        let rifle = builder.add_record_with_guid(
+            "Rifle",
+            item,
+            "items/rifle.xml",
+            CigGuid::from_bytes([1; 16]),
+        );
+        builder.set_string(rifle, "label", "Rifle");
+        builder.set_float(rifle, "mass", 4.0);
+        let base_db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        // The mod adds a new struct with a Class array, replaces the rifle under
+        // a new GUID and adds a scope referencing it
+        let mut builder = base();
+        let grade = builder.add_enum("Grade", &["A", "B"]);
+        let stat = builder.add_struct("Stat", None);
+        builder.add_property(stat, "value", DataType::Int32);
+        let attachment = builder.add_struct("Attachment", None);
+        builder.add_enum_property(attachment, "grade", grade);
+        builder.add_typed_property(attachment, "item", DataType::Reference, item);
+        builder.add_typed_array_property(attachment, "stats", DataType::Class, stat);
+        builder.add_array_property(attachment, "tags", DataType::String);
+
+        let rifle_guid = CigGuid::from_bytes([7; 16]);
+        let rifle = builder.add_record_with_guid("Rifle", item, "items/rifle.xml", rifle_guid);
+        builder.set_string(rifle, "label", "Modded Rifle");
+        builder.set_float(rifle, "mass", 3.5);
+        let scope = builder.add_record("Scope", attachment, "items/scope.xml");
+        builder.set_enum(scope, "grade", "B").unwrap();
+        builder.set_reference(scope, "item", rifle_guid);
+        let stats: Vec<_> = [10, 20]
+            .iter()
+            .map(|&value| {
+                let instance = builder.add_instance(stat);
+                builder.set_i32(instance, "value", value);
+                instance
+            })
+            .collect();
+        builder.set_class_array(scope, "stats", &stats).unwrap();
+        builder.set_string_array(scope, "tags", &["optic", "long"]);
+        let mod_db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let mut conflicting = DataCoreBuilder::from_database(&base_db).unwrap();
+        let error = conflicting
+            .merge(&mod_db, MergeStrategy::Error)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+
+        let mut merged = DataCoreBuilder::from_database(&base_db).unwrap();
+        let stats = merged.merge(&mod_db, MergeStrategy::Replace).unwrap();
+        assert_eq!(stats.structs_added, 2);
+        assert_eq!(stats.enums_added, 1);
+        assert_eq!((stats.records_added, stats.records_replaced), (1, 1));
+
+        let db = DataCoreDatabase::parse(&merged.build().unwrap()).unwrap();
+        assert_eq!(db.records().len(), 2);
+        let rifle = db.record(&CigGuid::from_bytes([1; 16])).unwrap();
+        assert_eq!(rifle.get_str("label"), Some("Modded Rifle"));
+        assert_eq!(rifle.get_f32("mass"), Some(3.5));
+
+        let scope = db.record_by_name("Scope").unwrap();
+        assert_eq!(scope.get_str("grade"), Some("B"));
+        assert_eq!(
+            scope.get_path("item.label").and_then(|v| v.as_str()),
+            Some("Modded Rifle")
+        );
+        assert_eq!(
+            scope.get_path("stats[1].value").and_then(|v| v.as_i32()),
+            Some(20)
+        );
+        let tags: Vec<_> = scope
+            .get_array("tags")
+            .unwrap()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(tags, ["optic", "long"]);
+    }
+
+    #[test]
+    fn test_merge_rename_and_keep() {
+        let mut builder = base();
+        let item = super::super::StructHandle(0);
+        builder.add_record_with_guid(
+            "Rifle",
+            item,
+            "items/rifle.xml",
+            CigGuid::from_bytes([1; 16]),
+        );
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let mut renamed = DataCoreBuilder::from_database(&db).unwrap();
+        let stats = renamed.merge(&db, MergeStrategy::Rename).unwrap();
+        assert_eq!(stats.records_renamed, 1);
+        let merged = DataCoreDatabase::parse(&renamed.build().unwrap()).unwrap();
+        let copy = merged.record_by_name("Rifle_1").unwrap();
+        assert_ne!(copy.id(), CigGuid::from_bytes([1; 16]));
+        assert_eq!(copy.file_name(), Some("items/rifle_1.xml"));
+
+        let mut kept = DataCoreBuilder::from_database(&db).unwrap();
+        let stats = kept.merge(&db, MergeStrategy::KeepExisting).unwrap();
+        assert_eq!(stats.records_skipped, 1);
+        assert_eq!(
+            DataCoreDatabase::parse(&kept.build().unwrap())
+                .unwrap()
+                .records()
+                .len(),
+            1
+        );
+    }
+
+    fn rifle_guid() -> CigGuid {
+        CigGuid::from_bytes([1; 16])
+    }
+
+    /// A database holding one rifle with the given label, weights and part
+    /// values, or no records without a label.
+    fn rifle(label: Option<&str>, weights: &[f32], parts: &[i32]) -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let part = builder.add_struct("Part", None);
+        builder.add_property(part, "value", DataType::Int32);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "label", DataType::String);
+        builder.add_array_property(item, "weights", DataType::Single);
+        builder.add_typed_array_property(item, "parts", DataType::Class, part);
+
+        if let Some(label) = label {
+            let rifle =
+                builder.add_record_with_guid("Rifle", item, "items/rifle.xml", rifle_guid());
+            builder.set_string(rifle, "label", label);
+            builder.set_float_array(rifle, "weights", weights);
+            let parts: Vec<_> = parts
+                .iter()
+                .map(|&value| {
+                    let instance = builder.add_instance(part);
+                    builder.set_i32(instance, "value", value);
+                    instance
+                })
+                .collect();
+            builder.set_class_array(rifle, "parts", &parts).unwrap();
+        }
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    /// Merge `theirs` into a builder of `ours` and parse the result.
+    fn merge(
+        ours: &DataCoreDatabase,
+        theirs: &DataCoreDatabase,
+        strategy: MergeStrategy,
+    ) -> (MergeStats, DataCoreDatabase) {
+        let mut builder = DataCoreBuilder::from_database(ours).unwrap();
+        let stats = builder.merge(theirs, strategy).unwrap();
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        (stats, db)
+    }
+
+    #[test]
+    fn test_merge_same_field_conflict() {
+        let ours = rifle(Some("Ours"), &[], &[]);
+        let theirs = rifle(Some("Theirs"), &[], &[]);
+
+        let mut builder = DataCoreBuilder::from_database(&ours).unwrap();
+        let error = builder.merge(&theirs, MergeStrategy::Error).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert!(error.to_string().contains("Rifle"), "{}", error);
+        let unchanged = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        assert_eq!(unchanged.records().len(), 1);
+        assert_eq!(
+            unchanged.record(&rifle_guid()).unwrap().get_str("label"),
+            Some("Ours")
+        );
+
+        let (stats, db) = merge(&ours, &theirs, MergeStrategy::KeepExisting);
+        assert_eq!(stats.records_skipped, 1);
+        assert_eq!(
+            db.record(&rifle_guid()).unwrap().get_str("label"),
+            Some("Ours")
+        );
+
+        let (stats, db) = merge(&ours, &theirs, MergeStrategy::Replace);
+        assert_eq!(stats.records_replaced, 1);
+        assert_eq!(db.records().len(), 1);
+        assert_eq!(
+            db.record(&rifle_guid()).unwrap().get_str("label"),
+            Some("Theirs")
+        );
+    }
+
+    #[test]
+    fn test_merge_deleted_record() {
+        let ours = rifle(Some("Rifle"), &[1.0], &[1]);
+        let theirs = rifle(Some("Modified"), &[2.0], &[2]);
+
+        // A record we deleted and they modified comes back with their values
+        let mut builder = DataCoreBuilder::from_database(&ours).unwrap();
+        builder.remove_record(rifle_guid()).unwrap();
+        let stats = builder.merge(&theirs, MergeStrategy::Error).unwrap();
+        assert_eq!(stats.records_added, 1);
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        assert_eq!(db.records().len(), 1);
+        let record = db.record(&rifle_guid()).unwrap();
+        assert_eq!(record.get_str("label"), Some("Modified"));
+        assert_eq!(
+            record.get_path("parts[0].value").and_then(|v| v.as_i32()),
+            Some(2)
+        );
+
+        // A record they deleted and we modified is kept, since merging only
+        // adds records
+        let (stats, db) = merge(&theirs, &rifle(None, &[], &[]), MergeStrategy::Error);
+        assert_eq!(stats, MergeStats::default());
+        assert_eq!(
+            db.record(&rifle_guid()).unwrap().get_str("label"),
+            Some("Modified")
+        );
+    }
+
+    #[test]
+    fn test_merge_array_length_conflict() {
+        let ours = rifle(Some("Rifle"), &[1.0, 2.0, 3.0], &[1, 2, 3]);
+        let theirs = rifle(Some("Rifle"), &[9.0], &[7, 8]);
+
+        let arrays = |db: &DataCoreDatabase, guid: &CigGuid| {
+            let record = db.record(guid).unwrap();
+            let weights: Vec<_> = record
+                .get_array("weights")
+                .unwrap()
+                .filter_map(|v| v.as_f32())
+                .collect();
+            let parts: Vec<_> = record
+                .get_array("parts")
+                .unwrap()
+                .filter_map(|v| db.resolve(&v)?.get_i32("value"))
+                .collect();
+            (weights, parts)
+        };
+
+        let (_, db) = merge(&ours, &theirs, MergeStrategy::Replace);
+        assert_eq!(arrays(&db, &rifle_guid()), (vec![9.0], vec![7, 8]));
+
+        let (_, db) = merge(&ours, &theirs, MergeStrategy::KeepExisting);
+        assert_eq!(
+            arrays(&db, &rifle_guid()),
+            (vec![1.0, 2.0, 3.0], vec![1, 2, 3])
+        );
+
+        // Renamed copies keep their own lengths next to the originals
+        let (stats, db) = merge(&ours, &theirs, MergeStrategy::Rename);
+        assert_eq!(stats.records_renamed, 1);
+        let copy = db.record_by_name("Rifle_1").unwrap().id();
+        assert_eq!(
+            arrays(&db, &rifle_guid()),
+            (vec![1.0, 2.0, 3.0], vec![1, 2, 3])
+        );
+        assert_eq!(arrays(&db, &copy), (vec![9.0], vec![7, 8]));
+    }
+}
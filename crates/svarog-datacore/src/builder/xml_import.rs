@@ -0,0 +1,865 @@
+//! XML import for DataCore records.
+//!
+//! This is the inverse of [`XmlExporter`](crate::XmlExporter): it reads record
+//! files in the exported layout and writes their values into a
+//! [`DataCoreBuilder`], so that exported records can be edited as text and
+//! rebuilt into a database.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreBuilder, DataCoreDatabase, XmlImporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let mut builder = DataCoreBuilder::from_database(&db)?;
+//!
+//! XmlImporter::new(&mut builder).import_dir("./datacore")?;
+//! builder.write_to_file("Game.modded.dcb")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use svarog_common::CigGuid;
+
+use super::{instance_pointer, DataCoreBuilder, InstanceHandle, PropertyDef, RecordHandle};
+use crate::export::encode_xml_name;
+use crate::patch::encode_scalar;
+use crate::structs::{DataCoreReference, DataCoreStringId};
+use crate::{DataType, Error, PatchValue, Result};
+
+/// Imports exported record XML into a [`DataCoreBuilder`].
+///
+/// Records are matched by their `RecordId`; records that do not exist yet are
+/// added with the struct named by their `Type` attribute. Properties missing
+/// from the XML keep their current values. Instances previously owned by a
+/// re-imported record are left behind and can be dropped with
+/// [`DataCoreBuilder::compact`].
+///
+/// Exports written with localized text cannot be imported faithfully, since
+/// `Locale` values hold the localized text instead of their keys.
+pub struct XmlImporter<'a> {
+    builder: &'a mut DataCoreBuilder,
+    struct_names: HashMap<String, usize>,
+    /// Main record of each file name.
+    files: HashMap<String, CigGuid>,
+    /// File name of the record being imported.
+    file_name: String,
+    /// Instances carrying a `Pointer` attribute, by pointer ID.
+    pointers: HashMap<usize, InstanceHandle>,
+    /// Weak pointers waiting for their target's pointer ID.
+    fixups: Vec<(WeakSlot, usize)>,
+}
+
+/// Location of a weak pointer.
+enum WeakSlot {
+    Field { struct_index: usize, offset: usize },
+    Pool(usize),
+}
+
+/// A parsed XML element.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+}
+
+impl<'a> XmlImporter<'a> {
+    /// Create an importer writing into a builder.
+    pub fn new(builder: &'a mut DataCoreBuilder) -> Self {
+        let struct_names = builder
+            .structs
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.name.clone(), i))
+            .collect();
+
+        let mut files = HashMap::new();
+        for record in builder.records.iter().filter(|r| !r.removed) {
+            files.entry(record.file_name.clone()).or_insert(record.guid);
+        }
+
+        Self {
+            builder,
+            struct_names,
+            files,
+            file_name: String::new(),
+            pointers: HashMap::new(),
+            fixups: Vec::new(),
+        }
+    }
+
+    /// Import a record file.
+    ///
+    /// `file_name` is the record's path in the database, such as
+    /// `libs/foundry/records/ships/aegs_gladius.xml`. It is used for records
+    /// the file adds. References to other files must point at records which
+    /// already exist in the builder.
+    pub fn import_record(&mut self, xml: &str, file_name: &str) -> Result<RecordHandle> {
+        let root = parse_element(xml)?;
+        let record = self.declare(&root, file_name)?;
+        self.apply(&root, record, file_name)?;
+        Ok(record)
+    }
+
+    /// Import every `.xml` file below a directory written by
+    /// [`XmlExporter::export_all`](crate::XmlExporter::export_all).
+    ///
+    /// All files are declared before any values are applied, so files may
+    /// reference records added by other files. Returns the number of files.
+    pub fn import_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize> {
+        let dir = dir.as_ref();
+        let mut paths = Vec::new();
+        collect_xml_files(dir, &mut paths)?;
+        paths.sort();
+
+        let mut files = Vec::with_capacity(paths.len());
+        for path in &paths {
+            let xml = std::fs::read_to_string(path)?;
+            let root =
+                parse_element(&xml).map_err(|e| import_error(&path.display().to_string(), e))?;
+            let file_name = path
+                .strip_prefix(dir)
+                .unwrap_or(path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((root, file_name));
+        }
+
+        let mut records = Vec::with_capacity(files.len());
+        for (root, file_name) in &files {
+            records.push(self.declare(root, file_name)?);
+        }
+        for ((root, file_name), record) in files.iter().zip(records) {
+            self.apply(root, record, file_name)?;
+        }
+
+        Ok(files.len())
+    }
+
+    /// Find or add the record described by a root element.
+    fn declare(&mut self, root: &Element, file_name: &str) -> Result<RecordHandle> {
+        let guid = match root.attribute("RecordId") {
+            Some(id) => parse_guid(id).map_err(|e| import_error(file_name, e))?,
+            None => CigGuid::random(),
+        };
+        self.ensure_record(guid, &root.name, root.attribute("Type"), file_name)
+            .map_err(|e| import_error(file_name, e))
+    }
+
+    /// Write the values of a root element into its record.
+    fn apply(&mut self, root: &Element, record: RecordHandle, file_name: &str) -> Result<()> {
+        self.file_name = file_name.to_string();
+        self.pointers.clear();
+        self.fixups.clear();
+
+        let instance = self.builder.record_instance(record);
+        self.apply_instance(instance, root)
+            .map_err(|e| import_error(file_name, e))?;
+        self.resolve_weak_pointers();
+        Ok(())
+    }
+
+    fn ensure_record(
+        &mut self,
+        guid: CigGuid,
+        name: &str,
+        type_name: Option<&str>,
+        file_name: &str,
+    ) -> Result<RecordHandle> {
+        let struct_index = type_name.map(|t| self.struct_index(t)).transpose()?;
+
+        if let Some(record) = self.builder.find_record(&guid) {
+            let record_def = &self.builder.records[record.0 as usize];
+            if let Some(struct_index) = struct_index {
+                if record_def.struct_index as usize != struct_index {
+                    return Err(Error::Import(format!(
+                        "record {} is a {}, not a {}",
+                        guid,
+                        self.builder.structs[record_def.struct_index as usize].name,
+                        type_name.unwrap_or_default()
+                    )));
+                }
+            }
+            return Ok(record);
+        }
+
+        let struct_index = struct_index
+            .ok_or_else(|| Error::Import(format!("new record {} has no Type attribute", name)))?;
+        let record = self.builder.add_record_with_guid(
+            name,
+            super::StructHandle(struct_index as u32),
+            file_name,
+            guid,
+        );
+        self.files.entry(file_name.to_string()).or_insert(guid);
+        Ok(record)
+    }
+
+    fn struct_index(&self, name: &str) -> Result<usize> {
+        self.struct_names
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::Import(format!("unknown struct type {}", name)))
+    }
+
+    fn apply_instance(&mut self, instance: InstanceHandle, element: &Element) -> Result<()> {
+        if let Some(id) = element.attribute("Pointer").and_then(parse_pointer_id) {
+            self.pointers.insert(id, instance);
+        }
+
+        let struct_index = instance.struct_index as usize;
+        let base =
+            instance.instance_index as usize * self.builder.structs[struct_index].size as usize;
+        self.apply_struct(struct_index, base, struct_index, element)
+    }
+
+    /// Write the properties of `struct_index` found in `element` into the
+    /// data block of `block`, starting at `base`.
+    fn apply_struct(
+        &mut self,
+        block: usize,
+        base: usize,
+        struct_index: usize,
+        element: &Element,
+    ) -> Result<()> {
+        let struct_def = &self.builder.structs[struct_index];
+        let first_prop = struct_def.first_property_index as usize;
+        let prop_count = struct_def.property_count as usize;
+        let parent_index = struct_def.parent_index;
+
+        let mut offset = base;
+        if parent_index >= 0 {
+            self.apply_struct(block, offset, parent_index as usize, element)?;
+            offset += self.builder.structs[parent_index as usize].size as usize;
+        }
+
+        let props = self.builder.properties[first_prop..first_prop + prop_count].to_vec();
+        for prop in props {
+            if let Some(child) = element.child(&encode_xml_name(&prop.name)) {
+                let result = if prop.conversion_type == 0 {
+                    self.apply_value(block, offset, &prop, child)
+                } else {
+                    self.apply_array(block, offset, &prop, child)
+                };
+                result.map_err(|e| import_error(&prop.name, e))?;
+            }
+            offset += self.builder.property_size(&prop);
+        }
+
+        Ok(())
+    }
+
+    fn apply_value(
+        &mut self,
+        block: usize,
+        offset: usize,
+        prop: &PropertyDef,
+        element: &Element,
+    ) -> Result<()> {
+        let bytes = match prop.data_type {
+            DataType::Class => {
+                return self.apply_struct(block, offset, prop.struct_index as usize, element);
+            }
+            DataType::StrongPointer => {
+                let target = self.new_instance(element)?;
+                zerocopy::IntoBytes::as_bytes(&instance_pointer(target)).to_vec()
+            }
+            DataType::WeakPointer => {
+                if let Some(id) = element.attribute("PointsTo").and_then(parse_pointer_id) {
+                    self.fixups.push((
+                        WeakSlot::Field {
+                            struct_index: block,
+                            offset,
+                        },
+                        id,
+                    ));
+                }
+                zerocopy::IntoBytes::as_bytes(&instance_pointer(None)).to_vec()
+            }
+            DataType::Reference => {
+                let reference = self.reference(element)?;
+                zerocopy::IntoBytes::as_bytes(&reference).to_vec()
+            }
+            DataType::String | DataType::Locale | DataType::EnumChoice => self
+                .string_id(prop, &element.text)?
+                .id()
+                .to_le_bytes()
+                .to_vec(),
+            data_type => encode_scalar(data_type, &PatchValue::parse(data_type, &element.text)?)?,
+        };
+
+        self.builder.instance_data[block][offset..offset + bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn apply_array(
+        &mut self,
+        block: usize,
+        offset: usize,
+        prop: &PropertyDef,
+        element: &Element,
+    ) -> Result<()> {
+        let items = &element.children;
+        let builder = &mut *self.builder;
+
+        macro_rules! push_scalars {
+            ($pool:ident, $ty:ty) => {{
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    let value = PatchValue::parse(prop.data_type, &item.text)?;
+                    let bytes = encode_scalar(prop.data_type, &value)?;
+                    values.push(<$ty>::from_le_bytes(bytes.try_into().unwrap()));
+                }
+                let first = builder.$pool.len();
+                builder.$pool.extend(values);
+                first
+            }};
+        }
+
+        let first = match prop.data_type {
+            DataType::Boolean => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(
+                        PatchValue::parse(DataType::Boolean, &item.text)? == PatchValue::Bool(true),
+                    );
+                }
+                let first = builder.bool_pool.len();
+                builder.bool_pool.extend(values);
+                first
+            }
+            DataType::SByte => push_scalars!(int8_pool, i8),
+            DataType::Int16 => push_scalars!(int16_pool, i16),
+            DataType::Int32 => push_scalars!(int32_pool, i32),
+            DataType::Int64 => push_scalars!(int64_pool, i64),
+            DataType::Byte => push_scalars!(uint8_pool, u8),
+            DataType::UInt16 => push_scalars!(uint16_pool, u16),
+            DataType::UInt32 => push_scalars!(uint32_pool, u32),
+            DataType::UInt64 => push_scalars!(uint64_pool, u64),
+            DataType::Single => push_scalars!(float_pool, f32),
+            DataType::Double => push_scalars!(double_pool, f64),
+            DataType::Guid => {
+                let values = items
+                    .iter()
+                    .map(|i| parse_guid(&i.text))
+                    .collect::<Result<Vec<_>>>()?;
+                let first = builder.guid_pool.len();
+                builder.guid_pool.extend(values);
+                first
+            }
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(self.string_id(prop, &item.text)?);
+                }
+                let pool = match prop.data_type {
+                    DataType::String => &mut self.builder.string_id_pool,
+                    DataType::Locale => &mut self.builder.locale_pool,
+                    _ => &mut self.builder.enum_value_pool,
+                };
+                let first = pool.len();
+                pool.extend(values);
+                first
+            }
+            DataType::Reference => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(self.reference(item)?);
+                }
+                let first = self.builder.reference_pool.len();
+                self.builder.reference_pool.extend(values);
+                first
+            }
+            DataType::StrongPointer => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(instance_pointer(self.new_instance(item)?));
+                }
+                let first = self.builder.strong_pool.len();
+                self.builder.strong_pool.extend(values);
+                first
+            }
+            DataType::WeakPointer => {
+                let first = builder.weak_pool.len();
+                for (i, item) in items.iter().enumerate() {
+                    if let Some(id) = item.attribute("PointsTo").and_then(parse_pointer_id) {
+                        self.fixups.push((WeakSlot::Pool(first + i), id));
+                    }
+                }
+                builder
+                    .weak_pool
+                    .extend(items.iter().map(|_| instance_pointer(None)));
+                first
+            }
+            DataType::Class => {
+                // Elements are consecutive, so allocate them all before their
+                // values add nested instances of the same struct
+                let struct_index = prop.struct_index as usize;
+                let first = builder.struct_instance_counts[struct_index] as usize;
+                for _ in items {
+                    builder.allocate_instance(struct_index);
+                }
+                for (i, item) in items.iter().enumerate() {
+                    let instance = InstanceHandle {
+                        struct_index: struct_index as u32,
+                        instance_index: (first + i) as u32,
+                    };
+                    self.apply_instance(instance, item)?;
+                }
+                first
+            }
+        };
+
+        super::write_array_header(
+            &mut self.builder.instance_data[block],
+            offset,
+            items.len(),
+            first as i32,
+        );
+        Ok(())
+    }
+
+    /// Allocate and fill the instance held by a strong pointer element, or
+    /// `None` if the pointer is null.
+    fn new_instance(&mut self, element: &Element) -> Result<Option<InstanceHandle>> {
+        let Some(type_name) = element.attribute("Type") else {
+            return Ok(None);
+        };
+        let struct_index = self.struct_index(type_name)?;
+        let instance = InstanceHandle {
+            struct_index: struct_index as u32,
+            instance_index: self.builder.allocate_instance(struct_index),
+        };
+        self.apply_instance(instance, element)?;
+        Ok(Some(instance))
+    }
+
+    /// Resolve a reference element, importing records written inline.
+    fn reference(&mut self, element: &Element) -> Result<DataCoreReference> {
+        let guid = if let Some(id) = element.attribute("RecordId") {
+            let guid = parse_guid(id)?;
+            // Records of the same file are written inline with their values
+            if let Some(type_name) = element.attribute("Type") {
+                let name = element.attribute("RecordName").unwrap_or(&element.name);
+                let file_name = self.file_name.clone();
+                let record = self.ensure_record(guid, name, Some(type_name), &file_name)?;
+                let instance = self.builder.record_instance(record);
+                self.apply_instance(instance, element)?;
+            }
+            guid
+        } else if let Some(path) = element.attribute("ReferencedFile") {
+            let file_name = path.trim_start_matches("file://./");
+            let file_name = file_name.trim_start_matches("../");
+            *self.files.get(file_name).ok_or_else(|| {
+                Error::Import(format!("no record for referenced file {}", file_name))
+            })?
+        } else {
+            return Ok(DataCoreReference {
+                record_id: CigGuid::EMPTY,
+                instance_index: 0,
+            });
+        };
+
+        let instance_index = self
+            .builder
+            .find_record(&guid)
+            .map_or(0, |r| self.builder.record_instance(r).instance_index as i32);
+        Ok(DataCoreReference {
+            record_id: guid,
+            instance_index,
+        })
+    }
+
+    /// Intern the text of a string, locale or enum-choice value.
+    fn string_id(&mut self, prop: &PropertyDef, text: &str) -> Result<DataCoreStringId> {
+        if prop.data_type == DataType::EnumChoice && !text.is_empty() {
+            let enum_def = &self.builder.enums[prop.struct_index as usize];
+            let first = enum_def.first_value_index as usize;
+            let options = &self.builder.enum_options[first..first + enum_def.value_count as usize];
+            if !options.iter().any(|o| o == text) {
+                return Err(Error::Import(format!(
+                    "{:?} is not an option of {}",
+                    text, enum_def.name
+                )));
+            }
+        }
+        Ok(DataCoreStringId::new(self.builder.string_table_1.add(text)))
+    }
+
+    /// Point weak pointers at the instances carrying their pointer IDs.
+    ///
+    /// Pointers to instances outside the imported file become null.
+    fn resolve_weak_pointers(&mut self) {
+        for (slot, id) in std::mem::take(&mut self.fixups) {
+            let pointer = instance_pointer(self.pointers.get(&id).copied());
+            match slot {
+                WeakSlot::Field {
+                    struct_index,
+                    offset,
+                } => {
+                    self.builder.instance_data[struct_index][offset..offset + 8]
+                        .copy_from_slice(zerocopy::IntoBytes::as_bytes(&pointer));
+                }
+                WeakSlot::Pool(index) => self.builder.weak_pool[index] = pointer,
+            }
+        }
+    }
+}
+
+/// Parse XML text into an element tree.
+fn parse_element(xml: &str) -> Result<Element> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+
+    let xml_error = |e: &dyn std::fmt::Display| Error::Import(format!("XML parse error: {}", e));
+
+    loop {
+        match reader.read_event() {
+            Ok(event @ (Event::Start(_) | Event::Empty(_))) => {
+                let (Event::Start(start) | Event::Empty(start)) = &event else {
+                    unreachable!();
+                };
+                let mut element = Element {
+                    name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+                    ..Default::default()
+                };
+                for attr in start.attributes() {
+                    let attr = attr.map_err(|e| xml_error(&e))?;
+                    let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                    let value = attr
+                        .unescape_value()
+                        .map_err(|e| xml_error(&e))?
+                        .into_owned();
+                    element.attributes.push((key, value));
+                }
+
+                if matches!(event, Event::Start(_)) {
+                    stack.push(element);
+                } else if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                } else {
+                    root = Some(element);
+                }
+            }
+            Ok(Event::End(_)) => {
+                if let Some(element) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                }
+            }
+            Ok(Event::Text(text)) => {
+                if let Some(element) = stack.last_mut() {
+                    element
+                        .text
+                        .push_str(&text.unescape().map_err(|e| xml_error(&e))?);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(xml_error(&e)),
+        }
+    }
+
+    root.ok_or_else(|| Error::Import("no root element found in XML".to_string()))
+}
+
+fn collect_xml_files(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_xml_files(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("xml"))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn parse_guid(text: &str) -> Result<CigGuid> {
+    text.parse()
+        .map_err(|_| Error::Import(format!("invalid GUID {:?}", text)))
+}
+
+/// Parse a `ptr:N` pointer ID.
+fn parse_pointer_id(text: &str) -> Option<usize> {
+    text.strip_prefix("ptr:")?.parse().ok()
+}
+
+/// Prefix an import error with where it happened.
+fn import_error(context: &str, error: Error) -> Error {
+    Error::Import(format!(
+        "{}: {}",
+        context,
+        error.to_string().trim_start_matches("import error: ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCoreDatabase, Value, XmlExporter};
+
+    #[test]
+    fn test_import_roundtrip() {
+        let mut builder = DataCoreBuilder::new();
+        let grade = builder.add_enum("Grade", &["Common", "Rare"]);
+        let part = builder.add_struct("Part", None);
+        builder.add_property(part, "label", DataType::String);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "count", DataType::Int32);
+        builder.add_enum_property(item, "grade", grade);
+        builder.add_array_property(item, "weights", DataType::Single);
+        builder.add_typed_property(item, "main", DataType::StrongPointer, part);
+        builder.add_typed_array_property(item, "parts", DataType::Class, part);
+        builder.add_typed_property(item, "other", DataType::Reference, item);
+
+        let other_id = CigGuid::random();
+        let record = builder.add_record("Item1", item, "items/item1.xml");
+        builder.add_record_with_guid("Item2", item, "items/item2.xml", other_id);
+        builder.set_i32(record, "count", 5);
+        builder.set_enum(record, "grade", "Common").unwrap();
+        builder.set_float_array(record, "weights", &[0.5, 1.5]);
+        let main = builder.add_instance(part);
+        builder.set_string(main, "label", "core & shell");
+        builder.set_strong_pointer_instance(record, "main", Some(main));
+        let a = builder.add_instance(part);
+        let b = builder.add_instance(part);
+        builder.set_string(a, "label", "a");
+        builder.set_string(b, "label", "b");
+        builder.set_class_array(record, "parts", &[a, b]).unwrap();
+        builder.set_reference(record, "other", other_id);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Item1").unwrap();
+        let xml = XmlExporter::new(&db)
+            .export_record(&db.records()[0])
+            .unwrap();
+        assert!(xml.contains("ReferencedFile"), "{}", xml);
+
+        // Importing unchanged XML reproduces the same export
+        let mut builder = DataCoreBuilder::from_database(&db).unwrap();
+        XmlImporter::new(&mut builder)
+            .import_record(&xml, "items/item1.xml")
+            .unwrap();
+        let rebuilt = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record_id = record.id();
+        let exported = XmlExporter::new(&rebuilt)
+            .export_record(rebuilt.get_record(&record_id).unwrap())
+            .unwrap();
+        assert_eq!(exported, xml);
+
+        // Edited values and new records are applied
+        let edited = xml
+            .replace("<count>5</count>", "<count>7</count>")
+            .replace("<grade>Common</grade>", "<grade>Rare</grade>")
+            .replace(&format!(" RecordId=\"{}\"", record_id), "")
+            .replace("Item1", "Item3");
+        let mut importer = XmlImporter::new(&mut builder);
+        importer.import_record(&edited, "items/item3.xml").unwrap();
+        let error = importer
+            .import_record(&xml.replace(">Common<", ">Epic<"), "items/item1.xml")
+            .unwrap_err();
+        assert!(error.to_string().contains("grade"), "{}", error);
+
+        let rebuilt = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let added = rebuilt.record_by_name("Item3").unwrap();
+        assert_ne!(added.id(), record_id);
+        assert_eq!(added.get_i32("count"), Some(7));
+        assert_eq!(added.get_str("grade"), Some("Rare"));
+        let exported = XmlExporter::new(&rebuilt)
+            .export_record(rebuilt.get_record(&added.id()).unwrap())
+            .unwrap();
+        assert!(exported.contains("core &amp; shell"), "{}", exported);
+    }
+
+    /// Build a database from a builder after importing `xml` into it.
+    fn import(builder: &mut DataCoreBuilder, xml: &str, file_name: &str) -> DataCoreDatabase {
+        XmlImporter::new(builder)
+            .import_record(xml, file_name)
+            .unwrap();
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_import_weak_pointers() {
+        let mut builder = DataCoreBuilder::new();
+        let node = builder.add_struct("Node", None);
+        builder.add_property(node, "label", DataType::String);
+        let item = builder.add_struct("Item", None);
+        builder.add_typed_property(item, "main", DataType::StrongPointer, node);
+        builder.add_typed_property(item, "link", DataType::WeakPointer, node);
+        builder.add_typed_array_property(item, "links", DataType::WeakPointer, node);
+
+        // Weak pointers may come before their target, and IDs without a
+        // target in the file become null
+        let xml = r#"<Item1 RecordId="11111111-1111-1111-1111-111111111111" Type="Item">
+            <link PointsTo="ptr:1" />
+            <main Type="Node" Pointer="ptr:1"><label>target</label></main>
+            <links>
+                <Node PointsTo="ptr:1" />
+                <Node PointsTo="ptr:9" />
+            </links>
+        </Item1>"#;
+        let db = import(&mut builder, xml, "items/item1.xml");
+        let record = db.record_by_name("Item1").unwrap();
+
+        let main = record.get("main").unwrap().as_instance().unwrap();
+        assert_eq!(main.resolve(&db).get_str("label"), Some("target"));
+        assert!(matches!(record.get("link"), Some(Value::WeakPointer(Some(r))) if r == main));
+
+        let links: Vec<_> = record.get_array("links").unwrap().collect();
+        assert_eq!(links.len(), 2);
+        assert!(matches!(links[0], Value::WeakPointer(Some(r)) if r == main));
+        assert!(matches!(links[1], Value::WeakPointer(None)));
+    }
+
+    #[test]
+    fn test_import_class_array() {
+        let mut builder = DataCoreBuilder::new();
+        let part = builder.add_struct("Part", None);
+        builder.add_property(part, "label", DataType::String);
+        builder.add_typed_array_property(part, "children", DataType::Class, part);
+        let item = builder.add_struct("Item", None);
+        builder.add_typed_array_property(item, "parts", DataType::Class, part);
+
+        // The nested array of the first element is allocated after both
+        // elements, which stay consecutive
+        let xml = r#"<Item1 RecordId="11111111-1111-1111-1111-111111111111" Type="Item">
+            <parts>
+                <Part>
+                    <label>a</label>
+                    <children><Part><label>a.0</label></Part></children>
+                </Part>
+                <Part><label>b</label></Part>
+            </parts>
+        </Item1>"#;
+        let db = import(&mut builder, xml, "items/item1.xml");
+        let record = db.record_by_name("Item1").unwrap();
+
+        let parts: Vec<_> = record
+            .get_array("parts")
+            .unwrap()
+            .map(|v| v.as_instance().unwrap().resolve(&db))
+            .collect();
+        let labels: Vec<_> = parts.iter().map(|p| p.get_str("label")).collect();
+        assert_eq!(labels, [Some("a"), Some("b")]);
+        assert_eq!(parts[1].instance_index(), parts[0].instance_index() + 1);
+
+        let children: Vec<_> = parts[0]
+            .get_array("children")
+            .unwrap()
+            .map(|v| v.as_instance().unwrap().resolve(&db).get_str("label"))
+            .collect();
+        assert_eq!(children, [Some("a.0")]);
+        assert_eq!(parts[1].get_array("children").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_import_referenced_file() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_typed_property(item, "other", DataType::Reference, item);
+        let target_id = CigGuid::random();
+        builder.add_record_with_guid("Target", item, "items/ammo/target.xml", target_id);
+
+        let xml = r#"<Item1 Type="Item">
+            <other ReferencedFile="file://./../../items/ammo/target.xml" />
+        </Item1>"#;
+        let db = import(&mut builder, xml, "items/guns/item1.xml");
+        let record = db.record_by_name("Item1").unwrap();
+        assert_eq!(
+            record.get("other").unwrap().as_record_ref().map(|r| r.guid),
+            Some(target_id)
+        );
+
+        let error = XmlImporter::new(&mut builder)
+            .import_record(
+                &xml.replace("ammo/target", "ammo/missing"),
+                "items/guns/item2.xml",
+            )
+            .unwrap_err();
+        let message = error.to_string();
+        assert!(
+            message.contains("items/guns/item2.xml: other"),
+            "{}",
+            message
+        );
+        assert!(message.contains("items/ammo/missing.xml"), "{}", message);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let mut builder = DataCoreBuilder::new();
+        let grade = builder.add_enum("Grade", &["Common", "Rare"]);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "count", DataType::Int32);
+        builder.add_enum_property(item, "grade", grade);
+        builder.add_struct("Other", None);
+        let record_id = CigGuid::random();
+        builder.add_record_with_guid("Item1", item, "items/item1.xml", record_id);
+
+        let mut importer = XmlImporter::new(&mut builder);
+        let mut import = |xml: String| {
+            importer
+                .import_record(&xml, "items/item1.xml")
+                .unwrap_err()
+                .to_string()
+        };
+
+        let error = import(format!(
+            r#"<Item1 RecordId="{}"><grade>Epic</grade></Item1>"#,
+            record_id
+        ));
+        assert!(error.contains("grade"), "{}", error);
+        assert!(
+            error.contains("\"Epic\" is not an option of Grade"),
+            "{}",
+            error
+        );
+
+        let error = import(format!(
+            r#"<Item1 RecordId="{}"><count>many</count></Item1>"#,
+            record_id
+        ));
+        assert!(error.contains("items/item1.xml: count"), "{}", error);
+
+        let error = import(format!(
+            r#"<Item1 RecordId="{}" Type="Other" />"#,
+            record_id
+        ));
+        assert!(error.contains("is a Item, not a Other"), "{}", error);
+
+        let error = import(r#"<Item2 Type="Missing" />"#.to_string());
+        assert!(error.contains("unknown struct type Missing"), "{}", error);
+
+        let error = import("<Item2><count>1</count></Item2>".to_string());
+        assert!(error.contains("has no Type attribute"), "{}", error);
+    }
+}
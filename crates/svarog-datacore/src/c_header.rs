@@ -3,10 +3,16 @@
 //! This module generates valid C header files from DataCore struct and enum definitions.
 //! The output can be parsed by standard C compilers (clang, gcc) without errors.
 //!
+//! Structs are emitted in dependency order: a parent struct and every struct
+//! embedded by value (`Class` properties) is defined before the struct using it.
+//! Inheritance is expressed by embedding the parent as a nested `_parent` field
+//! by default, or by flattening inherited fields into each struct with
+//! [`CHeaderInheritance::Flatten`].
+//!
 //! # Example
 //!
 //! ```no_run
-//! use svarog_datacore::{DataCoreDatabase, CHeaderExporter};
+//! use svarog_datacore::{CHeaderExporter, CHeaderInheritance, DataCoreDatabase};
 //!
 //! let db = DataCoreDatabase::open("Game.dcb")?;
 //! let exporter = CHeaderExporter::new(&db);
@@ -17,12 +23,19 @@
 //!
 //! // Export specific structs
 //! let header = exporter.export_structs(&[0, 1, 2]);
+//!
+//! // One header per struct with flattened inheritance
+//! CHeaderExporter::new(&db)
+//!     .inheritance(CHeaderInheritance::Flatten)
+//!     .export_split("include/")?;
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 use std::fmt::Write;
+use std::path::Path;
 
+use crate::structs::DataCorePropertyDefinition;
 use crate::{DataCoreDatabase, DataType};
 
 /// C header preamble with type definitions for DataCore types
@@ -86,60 +99,168 @@ typedef struct {
 } dc_array;
 
 #endif /* DATACORE_TYPES_H */
+"#;
 
+/// Banner written between the preamble and the forward declarations.
+const FORWARD_DECLARATIONS_BANNER: &str = r#"
 /* =========================================================================== */
 /* Forward Declarations */
 /* =========================================================================== */
 "#;
 
+/// File name of the shared type definitions in split output.
+const TYPES_FILE: &str = "datacore_types.h";
+
+/// File name of the enum definitions in split output.
+const ENUMS_FILE: &str = "datacore_enums.h";
+
+/// File name of the umbrella header in split output.
+const UMBRELLA_FILE: &str = "datacore.h";
+
+/// How struct inheritance is represented in the generated header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CHeaderInheritance {
+    /// Embed the parent struct as the first field, named `_parent`.
+    #[default]
+    Nested,
+    /// Copy all inherited fields into each struct, without a `_parent` field.
+    Flatten,
+}
+
 /// Exporter for generating C headers from DataCore schema.
 pub struct CHeaderExporter<'a> {
     db: &'a DataCoreDatabase,
+    inheritance: CHeaderInheritance,
+    include_guard: String,
 }
 
 impl<'a> CHeaderExporter<'a> {
     /// Create a new C header exporter.
     pub fn new(db: &'a DataCoreDatabase) -> Self {
-        Self { db }
+        Self {
+            db,
+            inheritance: CHeaderInheritance::default(),
+            include_guard: "DATACORE_SCHEMA_H".to_string(),
+        }
+    }
+
+    /// Set how inherited fields are represented (nested `_parent` by default).
+    pub fn inheritance(mut self, inheritance: CHeaderInheritance) -> Self {
+        self.inheritance = inheritance;
+        self
+    }
+
+    /// Set the include guard macro wrapping single-file output.
+    pub fn include_guard(mut self, guard: &str) -> Self {
+        self.include_guard = c_ident(guard);
+        self
     }
 
     /// Export all structs and enums to a C header string.
     pub fn export_all(&self) -> String {
         let all_structs: Vec<usize> = (0..self.db.struct_definitions().len()).collect();
-        self.export_structs(&all_structs)
+        let all_enums: BTreeSet<usize> = (0..self.db.enum_definitions().len()).collect();
+        let (struct_order, _) = self.topo_sort_structs(&all_structs);
+        self.render(&struct_order, &all_enums)
     }
 
     /// Export specific structs (and their dependencies) to a C header string.
     pub fn export_structs(&self, struct_indices: &[usize]) -> String {
+        let (struct_order, enums) = self.topo_sort_structs(struct_indices);
+        self.render(&struct_order, &enums)
+    }
+
+    /// Export all structs and enums as a set of headers in `output_dir`.
+    ///
+    /// Writes `datacore_types.h` with the shared DataCore types,
+    /// `datacore_enums.h` with every enum, one `<Struct>.h` per struct that
+    /// includes the headers of its parent and embedded structs, and a
+    /// `datacore.h` umbrella header including everything in dependency order.
+    ///
+    /// Returns the number of files written.
+    pub fn export_split<P: AsRef<Path>>(&self, output_dir: P) -> std::io::Result<usize> {
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir)?;
+
+        std::fs::write(output_dir.join(TYPES_FILE), C_HEADER_PREAMBLE)?;
+
+        let mut enums = String::new();
+        let _ = writeln!(enums, "#ifndef DATACORE_ENUMS_H");
+        let _ = writeln!(enums, "#define DATACORE_ENUMS_H\n");
+        let _ = writeln!(enums, "#include \"{}\"", TYPES_FILE);
+        for e in 0..self.db.enum_definitions().len() {
+            enums.push('\n');
+            enums.push_str(&self.generate_enum(e));
+        }
+        let _ = writeln!(enums, "\n#endif /* DATACORE_ENUMS_H */");
+        std::fs::write(output_dir.join(ENUMS_FILE), enums)?;
+
+        let all_structs: Vec<usize> = (0..self.db.struct_definitions().len()).collect();
+        let (struct_order, _) = self.topo_sort_structs(&all_structs);
+
+        let mut umbrella = String::new();
+        let _ = writeln!(umbrella, "#ifndef {}", self.include_guard);
+        let _ = writeln!(umbrella, "#define {}\n", self.include_guard);
+        let _ = writeln!(umbrella, "#include \"{}\"", TYPES_FILE);
+        let _ = writeln!(umbrella, "#include \"{}\"", ENUMS_FILE);
+
+        for &idx in &struct_order {
+            let name = self.struct_ident(idx);
+            let guard = format!("DATACORE_{}_H", name.to_ascii_uppercase());
+
+            let mut out = String::new();
+            let _ = writeln!(out, "#ifndef {}", guard);
+            let _ = writeln!(out, "#define {}\n", guard);
+            let _ = writeln!(out, "#include \"{}\"", ENUMS_FILE);
+            for dep in self.struct_dependencies(idx) {
+                let _ = writeln!(out, "#include \"{}.h\"", self.struct_ident(dep));
+            }
+            out.push('\n');
+            out.push_str(&self.generate_struct(idx));
+            let _ = writeln!(out, "\n#endif /* {} */", guard);
+            std::fs::write(output_dir.join(format!("{}.h", name)), out)?;
+
+            let _ = writeln!(umbrella, "#include \"{}.h\"", name);
+        }
+
+        let _ = writeln!(umbrella, "\n#endif /* {} */", self.include_guard);
+        std::fs::write(output_dir.join(UMBRELLA_FILE), umbrella)?;
+
+        Ok(struct_order.len() + 3)
+    }
+
+    /// Render a single header with the given structs (already in dependency order) and enums.
+    fn render(&self, struct_order: &[usize], enums: &BTreeSet<usize>) -> String {
         let mut buf = String::new();
 
+        let _ = writeln!(buf, "#ifndef {}", self.include_guard);
+        let _ = writeln!(buf, "#define {}\n", self.include_guard);
+
         // C header preamble
         buf.push_str(C_HEADER_PREAMBLE);
+        buf.push_str(FORWARD_DECLARATIONS_BANNER);
         buf.push('\n');
 
-        // Topological sort and collect enum dependencies
-        let (struct_order, enum_order) = self.topo_sort_structs(struct_indices);
-
         // Forward declarations for structs
-        for s in &struct_order {
-            if let Some(name) = self.db.struct_name(*s) {
-                let _ = writeln!(buf, "struct {};", name);
-            }
+        for &s in struct_order {
+            let _ = writeln!(buf, "struct {};", self.struct_ident(s));
         }
         buf.push('\n');
 
         // Enum definitions (must come before structs that use them)
-        for e in &enum_order {
-            buf.push_str(&self.generate_enum(*e));
+        for &e in enums {
+            buf.push_str(&self.generate_enum(e));
             buf.push('\n');
         }
 
         // Struct definitions
-        for idx in &struct_order {
-            buf.push_str(&self.generate_struct(*idx));
+        for &idx in struct_order {
+            buf.push_str(&self.generate_struct(idx));
             buf.push('\n');
         }
 
+        let _ = writeln!(buf, "#endif /* {} */", self.include_guard);
+
         buf
     }
 
@@ -149,28 +270,26 @@ impl<'a> CHeaderExporter<'a> {
         let mut output = String::new();
 
         // Forward declarations
-        for enum_idx in &enum_order {
-            if let Some(name) = self.db.enum_name(*enum_idx) {
-                let _ = writeln!(output, "enum {};", name);
+        for &enum_idx in &enum_order {
+            if let Some(name) = self.db.enum_name(enum_idx) {
+                let _ = writeln!(output, "enum {};", c_ident(name));
             }
         }
-        for struct_idx in &struct_order {
-            if let Some(name) = self.db.struct_name(*struct_idx) {
-                let _ = writeln!(output, "struct {};", name);
-            }
+        for &struct_idx in &struct_order {
+            let _ = writeln!(output, "struct {};", self.struct_ident(struct_idx));
         }
         if !struct_order.is_empty() || !enum_order.is_empty() {
             output.push('\n');
         }
 
         // Definitions
-        for enum_idx in &enum_order {
-            output.push_str(&self.generate_enum(*enum_idx));
+        for &enum_idx in &enum_order {
+            output.push_str(&self.generate_enum(enum_idx));
             output.push('\n');
         }
 
-        for s_idx in &struct_order {
-            output.push_str(&self.generate_struct(*s_idx));
+        for &s_idx in &struct_order {
+            output.push_str(&self.generate_struct(s_idx));
             output.push('\n');
         }
 
@@ -183,15 +302,14 @@ impl<'a> CHeaderExporter<'a> {
     }
 
     /// Topologically sort structs and collect enum dependencies.
-    fn topo_sort_structs(&self, roots: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    fn topo_sort_structs(&self, roots: &[usize]) -> (Vec<usize>, BTreeSet<usize>) {
         let mut order = Vec::new();
         let mut temp = HashSet::new();
         let mut perm = HashSet::new();
-        let mut enums = Vec::new();
-        let mut enum_seen = HashSet::new();
+        let mut enums = BTreeSet::new();
 
         for &r in roots {
-            self.dfs(r, &mut temp, &mut perm, &mut order, &mut enums, &mut enum_seen);
+            self.dfs(r, &mut temp, &mut perm, &mut order, &mut enums);
         }
 
         (order, enums)
@@ -203,42 +321,23 @@ impl<'a> CHeaderExporter<'a> {
         temp: &mut HashSet<usize>,
         perm: &mut HashSet<usize>,
         order: &mut Vec<usize>,
-        enums: &mut Vec<usize>,
-        enum_seen: &mut HashSet<usize>,
+        enums: &mut BTreeSet<usize>,
     ) {
-        if perm.contains(&idx) || temp.contains(&idx) {
+        if idx >= self.db.struct_definitions().len() || perm.contains(&idx) || temp.contains(&idx) {
             return;
         }
         temp.insert(idx);
 
-        // Process parent struct dependency first (inheritance)
-        if let Some(def) = self.db.struct_definitions().get(idx) {
-            if def.parent_type_index >= 0 {
-                let parent_idx = def.parent_type_index as usize;
-                self.dfs(parent_idx, temp, perm, order, enums, enum_seen);
-            }
+        // Parent and embedded structs need their full definition first
+        for dep in self.struct_dependencies(idx) {
+            self.dfs(dep, temp, perm, order, enums);
         }
 
-        // Process property dependencies
-        // Note: Only Class (embedded struct) requires full definition before use.
-        // StrongPointer, WeakPointer, and arrays use dc_strong_ptr/dc_weak_ptr/dc_array
-        // which don't require the target type to be fully defined.
         for prop in self.db.get_struct_properties(idx) {
-            if let Some(dt) = DataType::from_u16(prop.data_type) {
-                match dt {
-                    DataType::Class if !prop.is_array() => {
-                        // Only non-array Class fields need the full definition
-                        let dep = prop.struct_index as usize;
-                        self.dfs(dep, temp, perm, order, enums, enum_seen);
-                    }
-                    DataType::EnumChoice => {
-                        let eidx = prop.struct_index as usize;
-                        if enum_seen.insert(eidx) {
-                            enums.push(eidx);
-                        }
-                    }
-                    _ => {}
-                }
+            if prop.data_type == DataType::EnumChoice as u16
+                && self.db.enum_name(prop.struct_index as usize).is_some()
+            {
+                enums.insert(prop.struct_index as usize);
             }
         }
 
@@ -247,6 +346,56 @@ impl<'a> CHeaderExporter<'a> {
         order.push(idx);
     }
 
+    /// Structs that must be fully defined before `struct_index`.
+    ///
+    /// Only the nested parent and non-array Class (embedded struct) fields
+    /// count. StrongPointer, WeakPointer, and arrays use dc_strong_ptr/
+    /// dc_weak_ptr/dc_array which don't require the target type at all.
+    fn struct_dependencies(&self, struct_index: usize) -> Vec<usize> {
+        let mut deps = Vec::new();
+        let Some(def) = self.db.struct_definitions().get(struct_index) else {
+            return deps;
+        };
+
+        if self.inheritance == CHeaderInheritance::Nested && def.parent_type_index >= 0 {
+            deps.push(def.parent_type_index as usize);
+        }
+
+        for prop in self.layout_properties(struct_index) {
+            if prop.data_type == DataType::Class as u16 && !prop.is_array() {
+                let dep = prop.struct_index as usize;
+                if dep < self.db.struct_definitions().len() && !deps.contains(&dep) {
+                    deps.push(dep);
+                }
+            }
+        }
+
+        deps
+    }
+
+    /// Properties laid out directly in the struct, depending on the inheritance mode.
+    fn layout_properties(&self, struct_index: usize) -> Vec<&'a DataCorePropertyDefinition> {
+        let props = self.db.get_struct_properties(struct_index);
+        match self.inheritance {
+            CHeaderInheritance::Flatten => props,
+            CHeaderInheritance::Nested => {
+                let own = self
+                    .db
+                    .struct_definitions()
+                    .get(struct_index)
+                    .map(|d| d.attribute_count as usize)
+                    .unwrap_or(0);
+                let skip = props.len().saturating_sub(own);
+                props.into_iter().skip(skip).collect()
+            }
+        }
+    }
+
+    /// C identifier of a struct.
+    fn struct_ident(&self, struct_index: usize) -> String {
+        c_ident(self.db.struct_name(struct_index).unwrap_or("Unknown"))
+    }
+
     /// Generate C-compatible enum definition with prefixed values to avoid collisions.
     fn generate_enum(&self, enum_index: usize) -> String {
         let mut out = String::new();
@@ -257,7 +406,7 @@ impl<'a> CHeaderExporter<'a> {
         let value_count = def.value_count;
         let first_value_index = def.first_value_index;
 
-        let name = self.db.enum_name(enum_index).unwrap_or("Unknown");
+        let name = c_ident(self.db.enum_name(enum_index).unwrap_or("Unknown"));
         let values = self.db.enum_options(def);
 
         let _ = writeln!(out, "/*");
@@ -271,10 +420,12 @@ impl<'a> CHeaderExporter<'a> {
         if values.is_empty() {
             let _ = writeln!(out, "    {}_EMPTY_ = 0", name);
         } else {
+            let mut used = HashSet::new();
             for (i, v) in values.iter().enumerate() {
                 // Prefix each value with enum name to avoid collisions
+                let ident = unique_ident(format!("{}_{}", name, c_ident(v)), &mut used);
                 let comma = if i + 1 < values.len() { "," } else { "" };
-                let _ = writeln!(out, "    {}_{} = {}{}", name, v, i, comma);
+                let _ = writeln!(out, "    {} = {}{}", ident, i, comma);
             }
         }
         let _ = writeln!(out, "}} {};", name);
@@ -294,16 +445,19 @@ impl<'a> CHeaderExporter<'a> {
         let first_attr = def.first_attribute_index;
         let parent_index = def.parent_type_index;
 
-        let name = self.db.struct_name(struct_index).unwrap_or("Unknown");
+        let name = self.struct_ident(struct_index);
         let parent_name = if parent_index >= 0 {
-            self.db.struct_name(parent_index as usize).unwrap_or("Unknown")
+            self.struct_ident(parent_index as usize)
         } else {
-            ""
+            String::new()
         };
+        let nested_parent = parent_index >= 0 && self.inheritance == CHeaderInheritance::Nested;
 
         // Build layout
+        let base = self.layout_base(struct_index);
         let layout = self.build_struct_layout(struct_index);
-        let payload_size: usize = layout.iter().map(|f| f.size).sum();
+        let fields_size: usize = layout.iter().filter(|f| !f.is_padding).map(|f| f.size).sum();
+        let payload_size = base + fields_size;
 
         let _ = writeln!(output, "/*");
         let _ = writeln!(output, " * struct_index : {}", struct_index);
@@ -342,7 +496,7 @@ impl<'a> CHeaderExporter<'a> {
         let _ = writeln!(output, "typedef struct {} {{", name);
 
         // Embed parent as first field (C doesn't support inheritance)
-        if parent_index >= 0 {
+        if nested_parent {
             let _ = writeln!(
                 output,
                 "    struct {} _parent;  /* inherited fields */",
@@ -351,7 +505,7 @@ impl<'a> CHeaderExporter<'a> {
         }
 
         // Empty structs are not allowed in C - add a placeholder byte
-        if parent_index < 0 && layout.is_empty() {
+        if !nested_parent && layout.is_empty() {
             let _ = writeln!(
                 output,
                 "    uint8_t _empty;  /* placeholder for empty struct */"
@@ -381,7 +535,7 @@ impl<'a> CHeaderExporter<'a> {
     }
 
     /// Describe a DataCore type for C header export.
-    fn describe_type(&self, prop: &crate::structs::DataCorePropertyDefinition) -> String {
+    fn describe_type(&self, prop: &DataCorePropertyDefinition) -> String {
         let struct_idx = prop.struct_index;
         let data_type = prop.data_type;
         let Some(dt) = DataType::from_u16(data_type) else {
@@ -406,54 +560,40 @@ impl<'a> CHeaderExporter<'a> {
             DataType::EnumChoice => self
                 .db
                 .enum_name(struct_idx as usize)
-                .unwrap_or("int32_t")
-                .to_string(),
-            DataType::Class => {
-                let target = self
-                    .db
-                    .struct_name(struct_idx as usize)
-                    .unwrap_or("Unknown");
-                format!("struct {}", target)
-            }
+                .map(c_ident)
+                .unwrap_or_else(|| "int32_t".to_string()),
+            DataType::Class => format!("struct {}", self.struct_ident(struct_idx as usize)),
             DataType::StrongPointer => "dc_strong_ptr".to_string(),
             DataType::WeakPointer => "dc_weak_ptr".to_string(),
             DataType::Reference => "dc_record_ref".to_string(),
         }
     }
 
-    /// Get property size for C layout.
-    fn property_size(&self, prop: &crate::structs::DataCorePropertyDefinition) -> usize {
-        if prop.is_array() {
-            return 8; // dc_array is 8 bytes
-        }
-
-        let data_type = prop.data_type;
-        let Some(dt) = DataType::from_u16(data_type) else {
-            return 0;
-        };
-
-        match dt {
-            DataType::Class => self
-                .db
-                .struct_definitions()
-                .get(prop.struct_index as usize)
-                .map(|d| d.struct_size as usize)
+    /// Offset of the first laid out field: the nested parent's size, or zero.
+    fn layout_base(&self, struct_index: usize) -> usize {
+        let defs = self.db.struct_definitions();
+        match (self.inheritance, defs.get(struct_index)) {
+            (CHeaderInheritance::Nested, Some(def)) if def.parent_type_index >= 0 => defs
+                .get(def.parent_type_index as usize)
+                .map(|p| p.struct_size as usize)
                 .unwrap_or(0),
-            _ => dt.inline_size(),
+            _ => 0,
         }
     }
 
     /// Build struct layout for C export.
     fn build_struct_layout(&self, struct_index: usize) -> Vec<FieldLayout> {
         let mut layout = Vec::new();
-        let mut offset = 0usize;
+        let mut offset = self.layout_base(struct_index);
 
-        let props = self.db.get_struct_properties(struct_index);
-        for prop in props {
+        // Flattened inheritance can repeat a name declared by a parent
+        let mut used = HashSet::from(["_parent".to_string(), "_empty".to_string()]);
+
+        for prop in self.layout_properties(struct_index) {
             let raw_name = self.db.property_name(prop).unwrap_or("Unknown");
-            let name = escape_c_keyword(raw_name);
+            let name = unique_ident(c_ident(raw_name), &mut used);
             let base_type = self.describe_type(prop);
-            let size = self.property_size(prop);
+            let size = self.db.property_size(prop);
 
             // Arrays use dc_array struct, not flexible array members
             let type_name = if prop.is_array() {
@@ -522,6 +662,33 @@ fn escape_c_keyword(name: &str) -> String {
     }
 }
 
+/// Convert a DataCore name into a valid C identifier.
+fn c_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    escape_c_keyword(&ident)
+}
+
+/// Make `ident` unique among `used` by appending a numeric suffix.
+fn unique_ident(ident: String, used: &mut HashSet<String>) -> String {
+    if used.insert(ident.clone()) {
+        return ident;
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}_{}", ident, n);
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
 #[derive(Debug)]
 struct FieldLayout {
     name: String,
@@ -534,6 +701,7 @@ struct FieldLayout {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DataCoreBuilder;
 
     #[test]
     fn test_preamble_contains_required_types() {
@@ -548,4 +716,48 @@ mod tests {
         assert!(C_HEADER_PREAMBLE.contains("typedef signed char int8_t"));
         assert!(C_HEADER_PREAMBLE.contains("typedef unsigned char bool"));
     }
+
+    fn sample_db() -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let vec3 = builder.add_struct("Vec3", None);
+        builder.add_property(vec3, "x", DataType::Single);
+        let base = builder.add_struct("Base", None);
+        builder.add_property(base, "name", DataType::String);
+        let item = builder.add_struct("Item", Some(base));
+        builder.add_typed_property(item, "offset", DataType::Class, vec3);
+        builder.add_property(item, "default", DataType::Int32);
+        builder.add_enum("Grade", &["A", "Not-B"]);
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(c_ident("default"), "default_");
+        assert_eq!(c_ident("Foo.Bar"), "Foo_Bar");
+        assert_eq!(c_ident("2D"), "_2D");
+    }
+
+    #[test]
+    fn test_export_order_and_inheritance() {
+        let db = sample_db();
+
+        let header = CHeaderExporter::new(&db).include_guard("TEST_H").export_structs(&[2]);
+        assert!(header.starts_with("#ifndef TEST_H\n#define TEST_H\n"));
+        assert!(header.trim_end().ends_with("#endif /* TEST_H */"));
+        let vec3 = header.find("typedef struct Vec3 {").unwrap();
+        let base = header.find("typedef struct Base {").unwrap();
+        let item = header.find("typedef struct Item {").unwrap();
+        assert!(vec3 < item && base < item);
+        assert!(header.contains("    struct Base _parent;"));
+        assert!(header.contains("    struct Vec3 offset;"));
+        assert!(header.contains("    int32_t default_;"));
+        assert_eq!(header.matches("dc_string name;").count(), 1);
+
+        let header = CHeaderExporter::new(&db)
+            .inheritance(CHeaderInheritance::Flatten)
+            .export_all();
+        assert!(!header.contains("_parent"));
+        assert_eq!(header.matches("dc_string name;").count(), 2);
+        assert!(header.contains("    Grade_Not_B = 1"));
+    }
 }
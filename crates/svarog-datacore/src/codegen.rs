@@ -0,0 +1,408 @@
+//! Rust code generation for DataCore schema.
+//!
+//! This module generates Rust structs and enums from DataCore struct and enum
+//! definitions. The generated types derive serde's `Serialize`/`Deserialize`
+//! and use the original DataCore names through `#[serde(rename)]`, so they can
+//! be filled from a record's properties instead of using string-keyed access.
+//!
+//! Rust has no struct inheritance, so every generated struct contains its
+//! inherited properties first, followed by its own.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, RustCodegen};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let codegen = RustCodegen::new(&db);
+//!
+//! // Generate all structs and enums
+//! std::fs::write("datacore_types.rs", codegen.export_all())?;
+//!
+//! // Generate specific structs and everything they refer to
+//! let code = codegen.export_structs(&[0, 1, 2]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+use crate::structs::DataCorePropertyDefinition;
+use crate::{DataCoreDatabase, DataType};
+
+/// Preamble for generated Rust code with the DataCore wrapper types.
+///
+/// Generated code depends on `serde` (with `derive`) and on `svarog-common`
+/// with its `serde` feature enabled.
+pub const RUST_PREAMBLE: &str = r#"//! DataCore Schema Export
+//! Auto-generated Rust types for Star Citizen game data structures.
+
+#![allow(non_camel_case_types, non_snake_case, dead_code, clippy::all)]
+
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use svarog_common::CigGuid;
+
+/// Reference to another record by GUID.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Reference<T> {
+    pub guid: CigGuid,
+    #[serde(skip)]
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Reference<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Reference<T> {}
+
+impl<T> PartialEq for Reference<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.guid == other.guid
+    }
+}
+
+/// Non-owning pointer to a struct instance.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeakPointer<T> {
+    pub struct_index: u32,
+    pub instance_index: u32,
+    #[serde(skip)]
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for WeakPointer<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for WeakPointer<T> {}
+
+impl<T> PartialEq for WeakPointer<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.struct_index == other.struct_index && self.instance_index == other.instance_index
+    }
+}
+"#;
+
+/// Exporter for generating Rust types from DataCore schema.
+pub struct RustCodegen<'a> {
+    db: &'a DataCoreDatabase,
+}
+
+impl<'a> RustCodegen<'a> {
+    /// Create a new Rust code generator.
+    pub fn new(db: &'a DataCoreDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Generate all structs and enums as a Rust source file.
+    pub fn export_all(&self) -> String {
+        let structs = (0..self.db.struct_definitions().len()).collect();
+        let enums = (0..self.db.enum_definitions().len()).collect();
+        self.render(&structs, &enums)
+    }
+
+    /// Generate specific structs (and every type they refer to) as a Rust source file.
+    pub fn export_structs(&self, struct_indices: &[usize]) -> String {
+        let (structs, enums) = self.collect_types(struct_indices);
+        self.render(&structs, &enums)
+    }
+
+    fn render(&self, structs: &BTreeSet<usize>, enums: &BTreeSet<usize>) -> String {
+        let mut buf = String::new();
+        buf.push_str(RUST_PREAMBLE);
+
+        for &idx in enums {
+            buf.push('\n');
+            buf.push_str(&self.generate_enum(idx));
+        }
+
+        for &idx in structs {
+            buf.push('\n');
+            buf.push_str(&self.generate_struct(idx));
+        }
+
+        buf
+    }
+
+    /// Collect the given structs plus all struct and enum types they use.
+    fn collect_types(&self, roots: &[usize]) -> (BTreeSet<usize>, BTreeSet<usize>) {
+        let mut structs = BTreeSet::new();
+        let mut enums = BTreeSet::new();
+        let mut stack: Vec<usize> = roots.to_vec();
+
+        while let Some(idx) = stack.pop() {
+            if idx >= self.db.struct_definitions().len() || !structs.insert(idx) {
+                continue;
+            }
+
+            for prop in self.db.get_struct_properties(idx) {
+                match DataType::from_u16(prop.data_type) {
+                    Some(
+                        DataType::Class
+                        | DataType::StrongPointer
+                        | DataType::WeakPointer
+                        | DataType::Reference,
+                    ) => stack.push(prop.struct_index as usize),
+                    Some(DataType::EnumChoice)
+                        if self.db.enum_name(prop.struct_index as usize).is_some() =>
+                    {
+                        enums.insert(prop.struct_index as usize);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        (structs, enums)
+    }
+
+    /// Generate a serde-compatible Rust enum.
+    fn generate_enum(&self, enum_index: usize) -> String {
+        let mut out = String::new();
+        let Some(def) = self.db.enum_definitions().get(enum_index) else {
+            return out;
+        };
+        let name = self.db.enum_name(enum_index).unwrap_or("Unknown");
+
+        let _ = writeln!(out, "/// DataCore enum `{}`.", name);
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]"
+        );
+        let _ = writeln!(out, "pub enum {} {{", type_ident(name));
+
+        let mut used = HashSet::new();
+        for value in self.db.enum_options(def) {
+            let ident = unique_ident(type_ident(value), &mut used);
+            let _ = writeln!(out, "    #[serde(rename = \"{}\")]", escape_str(value));
+            let _ = writeln!(out, "    {},", ident);
+        }
+
+        // Values added after the code was generated
+        let _ = writeln!(out, "    #[serde(other)]");
+        let _ = writeln!(
+            out,
+            "    {},",
+            unique_ident("Unknown".to_string(), &mut used)
+        );
+        let _ = writeln!(out, "}}");
+
+        out
+    }
+
+    /// Generate a serde-compatible Rust struct with inherited fields flattened in.
+    fn generate_struct(&self, struct_index: usize) -> String {
+        let mut out = String::new();
+        let Some(def) = self.db.struct_definitions().get(struct_index) else {
+            return out;
+        };
+        let name = self.db.struct_name(struct_index).unwrap_or("Unknown");
+
+        let _ = writeln!(out, "/// DataCore struct `{}`.", name);
+        if def.parent_type_index >= 0 {
+            let parent = self
+                .db
+                .struct_name(def.parent_type_index as usize)
+                .unwrap_or("Unknown");
+            let _ = writeln!(out, "///");
+            let _ = writeln!(out, "/// Inherits from `{}`.", parent);
+        }
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]"
+        );
+        let _ = writeln!(out, "pub struct {} {{", type_ident(name));
+
+        let mut used = HashSet::new();
+        for prop in self.db.get_struct_properties(struct_index) {
+            let raw_name = self.db.property_name(prop).unwrap_or("unknown");
+            let field = unique_ident(field_ident(raw_name), &mut used);
+            let _ = writeln!(out, "    #[serde(rename = \"{}\")]", escape_str(raw_name));
+            let _ = writeln!(out, "    pub {}: {},", field, self.describe_type(prop));
+        }
+
+        let _ = writeln!(out, "}}");
+
+        out
+    }
+
+    /// Describe a property's Rust type.
+    fn describe_type(&self, prop: &DataCorePropertyDefinition) -> String {
+        let struct_idx = prop.struct_index as usize;
+        let Some(dt) = DataType::from_u16(prop.data_type) else {
+            return "()".to_string();
+        };
+        let target = || type_ident(self.db.struct_name(struct_idx).unwrap_or("Unknown"));
+
+        let element = match dt {
+            DataType::Boolean => "bool".to_string(),
+            DataType::SByte => "i8".to_string(),
+            DataType::Int16 => "i16".to_string(),
+            DataType::Int32 => "i32".to_string(),
+            DataType::Int64 => "i64".to_string(),
+            DataType::Byte => "u8".to_string(),
+            DataType::UInt16 => "u16".to_string(),
+            DataType::UInt32 => "u32".to_string(),
+            DataType::UInt64 => "u64".to_string(),
+            DataType::Single => "f32".to_string(),
+            DataType::Double => "f64".to_string(),
+            DataType::String | DataType::Locale => "String".to_string(),
+            DataType::Guid => "CigGuid".to_string(),
+            DataType::EnumChoice => match self.db.enum_name(struct_idx) {
+                Some(name) => type_ident(name),
+                None => "String".to_string(),
+            },
+            DataType::Class => target(),
+            DataType::StrongPointer => format!("Option<Box<{}>>", target()),
+            DataType::WeakPointer => format!("Option<WeakPointer<{}>>", target()),
+            DataType::Reference => format!("Option<Reference<{}>>", target()),
+        };
+
+        if prop.is_array() {
+            format!("Vec<{}>", element)
+        } else {
+            element
+        }
+    }
+}
+
+/// Rust keywords that need escaping when used as identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers.
+const RUST_RESERVED: &[&str] = &["self", "Self", "super", "crate", "_"];
+
+/// Sanitize a name into a valid Rust identifier, keeping its casing.
+fn type_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    escape_keyword(ident)
+}
+
+/// Convert a property name into a snake_case Rust field name.
+fn field_ident(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let boundary = match prev {
+                Some(p) if p.is_ascii_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_ascii_uppercase() => next.is_some_and(|n| n.is_ascii_lowercase()),
+                _ => false,
+            };
+            if boundary && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+
+    type_ident(&out)
+}
+
+fn escape_keyword(ident: String) -> String {
+    if RUST_RESERVED.contains(&ident.as_str()) {
+        format!("{}_", ident)
+    } else if RUST_KEYWORDS.contains(&ident.as_str()) {
+        format!("r#{}", ident)
+    } else {
+        ident
+    }
+}
+
+/// Append a numeric suffix if the identifier was already used.
+fn unique_ident(ident: String, used: &mut HashSet<String>) -> String {
+    if used.insert(ident.clone()) {
+        return ident;
+    }
+    let base = ident.trim_start_matches("r#").to_string();
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}_{}", base, n);
+        if used.insert(candidate.clone()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn escape_str(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(field_ident("displayName"), "display_name");
+        assert_eq!(field_ident("maxHPValue"), "max_hp_value");
+        assert_eq!(field_ident("type"), "r#type");
+        assert_eq!(field_ident("Self"), "self_");
+        assert_eq!(type_ident("2DVector"), "_2DVector");
+        assert_eq!(type_ident("Foo.Bar"), "Foo_Bar");
+    }
+
+    #[test]
+    fn test_generate_structs() {
+        let mut builder = DataCoreBuilder::new();
+        let base = builder.add_struct("Base", None);
+        builder.add_property(base, "name", DataType::String);
+        let item = builder.add_struct("Item", Some(base));
+        builder.add_property(item, "Mass", DataType::Single);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder.add_array_property(item, "tags", DataType::Int32);
+        builder.add_struct("Unused", None);
+        builder.add_enum("Grade", &["A", "B"]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let codegen = RustCodegen::new(&db);
+        let code = codegen.export_structs(&[1]);
+
+        assert!(code.contains("pub struct Item {"));
+        assert!(code.contains("    pub name: String,"));
+        assert!(code.contains("    #[serde(rename = \"Mass\")]\n    pub mass: f32,"));
+        assert!(code.contains("    pub parent: Option<Reference<Item>>,"));
+        assert!(code.contains("    pub tags: Vec<i32>,"));
+        assert!(!code.contains("Unused"));
+
+        let code = codegen.generate_enum(0);
+        assert!(code.contains("pub enum Grade {"));
+        assert!(code.contains("    #[serde(rename = \"B\")]\n    B,"));
+        assert!(code.contains("    #[serde(other)]\n    Unknown,"));
+    }
+}
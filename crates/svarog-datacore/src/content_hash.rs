@@ -0,0 +1,338 @@
+//! Content hashes of records for incremental export.
+//!
+//! A record's content hash covers everything that ends up in its exported
+//! XML file: its name, type and file, all property values reachable through
+//! embedded structs and strong pointers, records of the same file that are
+//! written inline, and the names of referenced records in other files.
+//!
+//! Strings are hashed by their text rather than their string table offset,
+//! so a record whose data did not change keeps its hash between game
+//! versions even when the string tables are laid out differently.
+//!
+//! The hash is a 64-bit FNV-1a and stable across runs and platforms, so it
+//! can be stored in an [`ExportManifest`](crate::ExportManifest).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{ContentHasher, DataCoreDatabase};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let hasher = ContentHasher::new(&db);
+//! for record in db.main_records() {
+//!     println!("{:016x} {}", hasher.hash_record(record), db.record_name(record).unwrap_or(""));
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashSet;
+
+use svarog_common::BinaryReader;
+
+use crate::database::PoolType;
+use crate::structs::{DataCorePointer, DataCoreRecord, DataCoreReference};
+use crate::{DataCoreDatabase, DataType, LocaleResolver};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Computes content hashes of records.
+pub struct ContentHasher<'a> {
+    database: &'a DataCoreDatabase,
+    locale: Option<&'a LocaleResolver>,
+    seed: u64,
+}
+
+impl<'a> ContentHasher<'a> {
+    /// Create a new hasher.
+    pub fn new(database: &'a DataCoreDatabase) -> Self {
+        Self {
+            database,
+            locale: None,
+            seed: FNV_OFFSET,
+        }
+    }
+
+    /// Hash the localized text of `Locale` values instead of only their keys.
+    pub fn with_locale(mut self, locale: &'a LocaleResolver) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Mix extra bytes into every hash, e.g. export options that change the output.
+    pub fn with_seed(mut self, seed: &[u8]) -> Self {
+        let mut state = HashState(self.seed);
+        state.bytes(seed);
+        self.seed = state.0;
+        self
+    }
+
+    /// Hash a record and all data its export contains.
+    pub fn hash_record(&self, record: &DataCoreRecord) -> u64 {
+        let mut walk = HashWalk {
+            database: self.database,
+            locale: self.locale,
+            state: HashState(self.seed),
+            file_name: self.database.record_file_name(record).unwrap_or(""),
+            visited: HashSet::new(),
+        };
+
+        walk.state
+            .str(self.database.record_name(record).unwrap_or(""));
+        walk.state.str(walk.file_name);
+        walk.state.str(
+            self.database
+                .struct_name(record.struct_index as usize)
+                .unwrap_or(""),
+        );
+        walk.instance(record.struct_index, record.instance_index as usize);
+
+        walk.state.0
+    }
+}
+
+/// Streaming FNV-1a state.
+struct HashState(u64);
+
+impl HashState {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Length-prefixed so adjacent strings can't run into each other.
+    fn str(&mut self, s: &str) {
+        self.bytes(&(s.len() as u32).to_le_bytes());
+        self.bytes(s.as_bytes());
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.bytes(&[tag]);
+    }
+}
+
+struct HashWalk<'a> {
+    database: &'a DataCoreDatabase,
+    locale: Option<&'a LocaleResolver>,
+    state: HashState,
+    file_name: &'a str,
+    visited: HashSet<(i32, usize)>,
+}
+
+impl HashWalk<'_> {
+    fn instance(&mut self, struct_index: i32, instance_index: usize) {
+        // Instances reachable twice are exported twice but only hashed once;
+        // this also guards against strong pointer cycles
+        if !self.visited.insert((struct_index, instance_index)) {
+            self.state.tag(b'v');
+            return;
+        }
+        let mut reader = self
+            .database
+            .get_instance_reader(struct_index as usize, instance_index);
+        self.struct_data(struct_index, &mut reader);
+    }
+
+    fn struct_data(&mut self, struct_index: i32, reader: &mut BinaryReader<'_>) {
+        for prop in self.database.get_struct_properties(struct_index as usize) {
+            let Some(data_type) = DataType::from_u16(prop.data_type) else {
+                continue;
+            };
+            self.state
+                .str(self.database.property_name(prop).unwrap_or(""));
+
+            if prop.conversion_type == 0 {
+                self.attribute(data_type, prop.struct_index as i32, reader);
+            } else {
+                self.array(data_type, prop.struct_index as i32, reader);
+            }
+        }
+    }
+
+    fn attribute(&mut self, data_type: DataType, struct_index: i32, reader: &mut BinaryReader<'_>) {
+        match data_type {
+            DataType::Class => self.struct_data(struct_index, reader),
+            DataType::StrongPointer => {
+                if let Ok(pointer) = reader.read_struct::<DataCorePointer>() {
+                    self.strong_pointer(&pointer);
+                }
+            }
+            DataType::WeakPointer => {
+                if let Ok(pointer) = reader.read_struct::<DataCorePointer>() {
+                    self.weak_pointer(&pointer);
+                }
+            }
+            DataType::Reference => {
+                if let Ok(reference) = reader.read_struct::<DataCoreReference>() {
+                    self.reference(&reference);
+                }
+            }
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                if let Ok(id) = reader.read_struct::<crate::structs::DataCoreStringId>() {
+                    let text = self.database.get_string(&id).unwrap_or("");
+                    self.text(data_type, text);
+                }
+            }
+            _ => {
+                if let Ok(bytes) = reader.read_bytes(data_type.inline_size()) {
+                    self.state.bytes(bytes);
+                }
+            }
+        }
+    }
+
+    fn array(&mut self, data_type: DataType, struct_index: i32, reader: &mut BinaryReader<'_>) {
+        let count = reader.read_i32().unwrap_or(0).max(0) as usize;
+        let first_index = reader.read_i32().unwrap_or(0).max(0) as usize;
+        self.state.bytes(&(count as u32).to_le_bytes());
+
+        for index in first_index..first_index + count {
+            match data_type {
+                DataType::Class => self.instance(struct_index, index),
+                DataType::StrongPointer => {
+                    if let Some(pointer) = self.database.strong_value(index) {
+                        self.strong_pointer(&pointer);
+                    }
+                }
+                DataType::WeakPointer => {
+                    if let Some(pointer) = self.database.weak_value(index) {
+                        self.weak_pointer(&pointer);
+                    }
+                }
+                DataType::Reference => {
+                    if let Some(reference) = self.database.reference_value(index) {
+                        self.reference(&reference);
+                    }
+                }
+                DataType::String => {
+                    let text = self.database.string_id_value(index);
+                    let text = text
+                        .and_then(|id| self.database.get_string(&id))
+                        .unwrap_or("");
+                    self.text(data_type, text);
+                }
+                DataType::Locale => {
+                    let text = self.database.locale_value(index);
+                    let text = text
+                        .and_then(|id| self.database.get_string(&id))
+                        .unwrap_or("");
+                    self.text(data_type, text);
+                }
+                DataType::EnumChoice => {
+                    let text = self.database.enum_value(index);
+                    let text = text
+                        .and_then(|id| self.database.get_string(&id))
+                        .unwrap_or("");
+                    self.text(data_type, text);
+                }
+                _ => {
+                    let Some(pool) = PoolType::for_data_type(data_type) else {
+                        continue;
+                    };
+                    let size = data_type.inline_size();
+                    let data = self.database.raw_pool_data(pool);
+                    if let Some(bytes) = data.get(index * size..(index + 1) * size) {
+                        self.state.bytes(bytes);
+                    }
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, data_type: DataType, text: &str) {
+        self.state.str(text);
+        if data_type == DataType::Locale {
+            if let Some(locale) = self.locale {
+                self.state.str(locale.resolve(text));
+            }
+        }
+    }
+
+    fn strong_pointer(&mut self, pointer: &DataCorePointer) {
+        if pointer.is_null() {
+            self.state.tag(b'0');
+            return;
+        }
+        self.state.tag(b's');
+        self.state.str(
+            self.database
+                .struct_name(pointer.struct_index as usize)
+                .unwrap_or(""),
+        );
+        self.instance(pointer.struct_index, pointer.instance_index as usize);
+    }
+
+    fn weak_pointer(&mut self, pointer: &DataCorePointer) {
+        if pointer.is_null() {
+            self.state.tag(b'0');
+            return;
+        }
+        // Pointer ids in the export depend on the target's position
+        self.state.tag(b'w');
+        self.state.bytes(&{ pointer.struct_index }.to_le_bytes());
+        self.state.bytes(&{ pointer.instance_index }.to_le_bytes());
+        let target = self
+            .database
+            .record_for_instance(pointer.struct_index as u32, pointer.instance_index as u32);
+        if let Some(name) = target.and_then(|record| self.database.record_name(record)) {
+            self.state.str(name);
+        }
+    }
+
+    fn reference(&mut self, reference: &DataCoreReference) {
+        if reference.is_null() {
+            self.state.tag(b'0');
+            return;
+        }
+        let Some(record) = self.database.get_record(&reference.record_id) else {
+            self.state.tag(b'?');
+            return;
+        };
+
+        self.state.tag(b'r');
+        self.state.bytes(reference.record_id.as_bytes());
+        self.state
+            .str(self.database.record_file_name(record).unwrap_or(""));
+        self.state
+            .str(self.database.record_name(record).unwrap_or(""));
+
+        // Non-main records of the same file are written inline
+        let record_file = self.database.record_file_name(record).unwrap_or("");
+        if record_file == self.file_name && !self.database.is_main_record(reference.record_id) {
+            self.instance(record.struct_index, record.instance_index as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    fn build(health: f32) -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "health", DataType::Single);
+        let record = builder.add_record("Item.Sword", item, "libs/items/sword.xml");
+        builder.set_float(record, "health", health);
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_hash_tracks_content() {
+        let a = build(10.0);
+        let b = build(10.0);
+        let c = build(20.0);
+        let hash = |db: &DataCoreDatabase| ContentHasher::new(db).hash_record(&db.records()[0]);
+
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&c));
+
+        let seeded = ContentHasher::new(&a)
+            .with_seed(b"canonical")
+            .hash_record(&a.records()[0]);
+        assert_ne!(hash(&a), seeded);
+    }
+}
@@ -3,21 +3,25 @@
 //! Key optimizations:
 //! - Zero-copy slices for value pools (backed by mmap)
 //! - FxHashMap for O(1) lookups with fast hashing
-//! - String interning to avoid duplicate allocations
-//! - Parallel parsing of independent sections
+//! - String interning to avoid duplicate allocations, deferred until first use
+//! - Optional lazy strings borrowed straight from the file data
+//! - Parallel parsing of independent sections (with the `parallel` feature)
 //! - Cache-aligned data structures
 
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::OnceLock;
 
-use bumpalo::Bump;
 use hashbrown::HashMap as FastHashMap;
+#[cfg(feature = "mmap")]
 use memmap2::Mmap;
 use rustc_hash::FxHasher;
-use svarog_common::{BinaryReader, CigGuid};
+use svarog_common::arena::{ArenaOptions, ArenaStr, StringArena};
+use svarog_common::{BinaryReader, CigGuid, ResultExt};
 use zerocopy::FromBytes;
 
 use crate::structs::*;
-use crate::{Error, Result};
+use crate::{DataType, Error, Result};
 
 type FxHashMap<K, V> = FastHashMap<K, V, std::hash::BuildHasherDefault<FxHasher>>;
 
@@ -45,8 +49,21 @@ pub struct PoolCounts {
     pub enum_option_count: usize,
 }
 
+/// A property of a struct, own or inherited, placed in the struct's instances.
+#[derive(Debug, Clone, Copy)]
+pub struct StructField<'a> {
+    pub property: &'a DataCorePropertyDefinition,
+    /// Struct declaring the property: the struct itself or one of its ancestors.
+    pub declared_by: usize,
+    /// Byte offset of the value in an instance.
+    pub offset: usize,
+    /// Bytes the value takes in an instance.
+    pub size: usize,
+}
+
 /// Pool type identifier for raw data access.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
 pub enum PoolType {
     Bool,
     Int8,
@@ -69,14 +86,85 @@ pub enum PoolType {
     EnumOption,
 }
 
+impl PoolType {
+    /// All pools, in file order.
+    pub const ALL: [PoolType; 19] = [
+        Self::Int8,
+        Self::Int16,
+        Self::Int32,
+        Self::Int64,
+        Self::UInt8,
+        Self::UInt16,
+        Self::UInt32,
+        Self::UInt64,
+        Self::Bool,
+        Self::Float,
+        Self::Double,
+        Self::Guid,
+        Self::StringId,
+        Self::Locale,
+        Self::EnumValue,
+        Self::Strong,
+        Self::Weak,
+        Self::Reference,
+        Self::EnumOption,
+    ];
+
+    /// Value pool holding array elements of a data type.
+    ///
+    /// Class arrays have no pool; their elements are struct instances.
+    pub(crate) fn for_data_type(data_type: DataType) -> Option<Self> {
+        Some(match data_type {
+            DataType::Boolean => Self::Bool,
+            DataType::SByte => Self::Int8,
+            DataType::Int16 => Self::Int16,
+            DataType::Int32 => Self::Int32,
+            DataType::Int64 => Self::Int64,
+            DataType::Byte => Self::UInt8,
+            DataType::UInt16 => Self::UInt16,
+            DataType::UInt32 => Self::UInt32,
+            DataType::UInt64 => Self::UInt64,
+            DataType::Single => Self::Float,
+            DataType::Double => Self::Double,
+            DataType::Guid => Self::Guid,
+            DataType::String => Self::StringId,
+            DataType::Locale => Self::Locale,
+            DataType::EnumChoice => Self::EnumValue,
+            DataType::StrongPointer => Self::Strong,
+            DataType::WeakPointer => Self::Weak,
+            DataType::Reference => Self::Reference,
+            DataType::Class => return None,
+        })
+    }
+}
+
+/// Contents of a database file: memory-mapped, or read into memory without
+/// the `mmap` feature, as on `wasm32` targets.
+#[cfg(feature = "mmap")]
+type FileData = Mmap;
+#[cfg(not(feature = "mmap"))]
+type FileData = Vec<u8>;
+
+fn read_file(path: &Path) -> Result<FileData> {
+    #[cfg(feature = "mmap")]
+    {
+        let file = std::fs::File::open(path)?;
+        Ok(unsafe { Mmap::map(&file)? })
+    }
+    #[cfg(not(feature = "mmap"))]
+    {
+        Ok(std::fs::read(path)?)
+    }
+}
+
 /// Optimized DataCore database with zero-copy access.
 ///
 /// This implementation uses memory-mapped I/O and zero-copy slices
 /// to minimize allocations and maximize cache efficiency.
 #[allow(dead_code)]
 pub struct DataCoreDatabase {
-    /// Memory-mapped file (if loaded from file)
-    _mmap: Option<Mmap>,
+    /// File contents (if loaded from file)
+    _file: Option<FileData>,
 
     /// Owned data (if loaded from bytes)
     _owned_data: Option<Vec<u8>>,
@@ -84,6 +172,7 @@ pub struct DataCoreDatabase {
     /// Raw data pointer for zero-copy access
     data: *const u8,
     data_len: usize,
+    version: u32,
 
     // Schema definitions (small, worth copying for cache locality)
     struct_definitions: Vec<DataCoreStructDefinition>,
@@ -146,29 +235,69 @@ pub struct DataCoreDatabase {
     // Computed data (use FxHashMap for speed)
     struct_offsets: Vec<usize>,
     record_map: FxHashMap<CigGuid, usize>,
-    main_records: FxHashMap<CigGuid, ()>,
-
-    // String cache with interning (arena-allocated)
-    string_arena: Bump,
-    string_cache_1: FxHashMap<i32, *const str>,
-    string_cache_2: FxHashMap<i32, *const str>,
+    // Record indices per file name offset; the first is the file's main record
+    file_records: FxHashMap<i32, Vec<u32>>,
+    main_record_indices: Vec<u32>,
+    // Record index per (struct index, instance index), built on first lookup
+    instance_records: OnceLock<FxHashMap<(i32, u16), u32>>,
+
+    // String caches with interning, built on first lookup
+    lazy_strings: bool,
+    string_arena: Option<ArenaOptions>,
+    string_cache_1: OnceLock<StringCache>,
+    string_cache_2: OnceLock<StringCache>,
 }
 
 // SAFETY: The raw pointers are derived from owned data or mmap which lives
-// as long as the struct. String pointers point into the arena.
+// as long as the struct.
 unsafe impl Send for DataCoreDatabase {}
 unsafe impl Sync for DataCoreDatabase {}
 
+/// Interned strings of one string table, keyed by offset.
+struct StringCache {
+    arena: StringArena,
+    strings: FxHashMap<i32, ArenaStr>,
+    /// Whether the arena refused strings, which are then read from the table.
+    partial: bool,
+}
+
+impl StringCache {
+    /// Intern the strings of `data`, by default in a single block the size
+    /// of the table.
+    fn build(data: &[u8], options: Option<ArenaOptions>) -> Self {
+        let options = options.unwrap_or(ArenaOptions {
+            chunk_size: data.len(),
+            ..ArenaOptions::default()
+        });
+        let mut arena = StringArena::with_options(options);
+        let (strings, partial) = DataCoreDatabase::build_string_cache_fast(data, &mut arena);
+        Self {
+            arena,
+            strings,
+            partial,
+        }
+    }
+
+    #[inline]
+    fn get<'a>(&'a self, table: &'a [u8], id: i32) -> Option<&'a str> {
+        match self.strings.get(&id) {
+            Some(&s) => Some(self.arena.get(s)),
+            None if self.partial => DataCoreDatabase::lazy_string(table, id),
+            None => None,
+        }
+    }
+}
+
 impl DataCoreDatabase {
     /// Parse from a file path (memory-mapped for zero-copy).
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = std::fs::File::open(path)?;
-        let mmap = unsafe { Mmap::map(&file)? };
-        let data_ptr = mmap.as_ptr();
-        let data_len = mmap.len();
+        let file = read_file(path.as_ref())?;
+        let data_ptr = file.as_ptr();
+        let data_len = file.len();
 
         let mut db = Self::parse_internal(data_ptr, data_len)?;
-        db._mmap = Some(mmap);
+        db._file = Some(file);
+        db.build_indexes();
         Ok(db)
     }
 
@@ -181,10 +310,114 @@ impl DataCoreDatabase {
 
         let mut db = Self::parse_internal(data_ptr, data_len)?;
         db._owned_data = Some(owned);
+        db.build_indexes();
         Ok(db)
     }
 
+    /// Parse from a file path without interning strings.
+    ///
+    /// String lookups borrow directly from the memory map instead of an
+    /// interned copy of both string tables. Each lookup scans for the string
+    /// terminator, so this suits query-only workloads that resolve few
+    /// strings and care about resident memory.
+    pub fn open_lazy<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = read_file(path.as_ref())?;
+        let data_ptr = file.as_ptr();
+        let data_len = file.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._file = Some(file);
+        db.lazy_strings = true;
+        db.build_indexes();
+        Ok(db)
+    }
+
+    /// Parse a DataCore database from bytes without interning strings.
+    ///
+    /// See [`open_lazy`](Self::open_lazy).
+    pub fn parse_lazy(data: &[u8]) -> Result<Self> {
+        let owned = data.to_vec();
+        let data_ptr = owned.as_ptr();
+        let data_len = owned.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._owned_data = Some(owned);
+        db.lazy_strings = true;
+        db.build_indexes();
+        Ok(db)
+    }
+
+    /// Parse from a file path, interning strings with the given arena options.
+    ///
+    /// A budget caps the memory taken by interned strings. Past it, strings
+    /// are spilled to individual allocations, or with
+    /// [`Overflow::Fail`](svarog_common::arena::Overflow::Fail) read from the
+    /// file as in [`open_lazy`](Self::open_lazy).
+    pub fn open_with_arena<P: AsRef<Path>>(path: P, options: ArenaOptions) -> Result<Self> {
+        let file = read_file(path.as_ref())?;
+        let data_ptr = file.as_ptr();
+        let data_len = file.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._file = Some(file);
+        db.string_arena = Some(options);
+        db.build_indexes();
+        Ok(db)
+    }
+
+    /// Parse a DataCore database from bytes, interning strings with the
+    /// given arena options.
+    ///
+    /// See [`open_with_arena`](Self::open_with_arena).
+    pub fn parse_with_arena(data: &[u8], options: ArenaOptions) -> Result<Self> {
+        let owned = data.to_vec();
+        let data_ptr = owned.as_ptr();
+        let data_len = owned.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._owned_data = Some(owned);
+        db.string_arena = Some(options);
+        db.build_indexes();
+        Ok(db)
+    }
+
+    /// Parse from a file path, building lookup tables on rayon workers.
+    ///
+    /// Unlike [`open`](Self::open), both string caches are built up front,
+    /// so the first string lookups don't pay for them.
+    #[cfg(feature = "parallel")]
+    pub fn open_parallel<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = read_file(path.as_ref())?;
+        let data_ptr = file.as_ptr();
+        let data_len = file.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._file = Some(file);
+        db.build_indexes_parallel();
+        Ok(db)
+    }
+
+    /// Parse a DataCore database from bytes, building lookup tables on
+    /// rayon workers.
+    #[cfg(feature = "parallel")]
+    pub fn parse_parallel(data: &[u8]) -> Result<Self> {
+        let owned = data.to_vec();
+        let data_ptr = owned.as_ptr();
+        let data_len = owned.len();
+
+        let mut db = Self::parse_internal(data_ptr, data_len)?;
+        db._owned_data = Some(owned);
+        db.build_indexes_parallel();
+        Ok(db)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_parse", skip_all, fields(size = data_len))
+    )]
     fn parse_internal(data_ptr: *const u8, data_len: usize) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
         // SAFETY: data_ptr is valid for data_len bytes
         let data = unsafe { std::slice::from_raw_parts(data_ptr, data_len) };
         let mut reader = BinaryReader::new(data);
@@ -193,7 +426,7 @@ impl DataCoreDatabase {
         let _unknown1 = reader.read_u32()?;
         let version = reader.read_u32()?;
 
-        if version < 5 || version > 6 {
+        if !(5..=6).contains(&version) {
             return Err(Error::UnsupportedVersion(version));
         }
 
@@ -229,11 +462,14 @@ impl DataCoreDatabase {
         let text_length_2 = reader.read_u32()? as usize;
 
         // Read definitions (these are small, worth copying for cache locality)
-        let struct_definitions = Self::read_structs(&mut reader, struct_def_count)?;
-        let property_definitions = Self::read_structs(&mut reader, property_def_count)?;
-        let enum_definitions = Self::read_structs(&mut reader, enum_def_count)?;
-        let data_mappings = Self::read_structs(&mut reader, data_mapping_count)?;
-        let records: Vec<DataCoreRecord> = Self::read_structs(&mut reader, record_def_count)?;
+        let struct_definitions =
+            Self::read_structs(&mut reader, struct_def_count, "struct definitions")?;
+        let property_definitions =
+            Self::read_structs(&mut reader, property_def_count, "property definitions")?;
+        let enum_definitions = Self::read_structs(&mut reader, enum_def_count, "enum definitions")?;
+        let data_mappings = Self::read_structs(&mut reader, data_mapping_count, "data mappings")?;
+        let records: Vec<DataCoreRecord> =
+            Self::read_structs(&mut reader, record_def_count, "records")?;
 
         // Record offsets for value pools (zero-copy access)
         let int8_offset = reader.position();
@@ -309,46 +545,30 @@ impl DataCoreDatabase {
 
         // Data section
         let data_section_offset = reader.position();
+        if data_section_offset > data_len {
+            let err = svarog_common::Error::UnexpectedEof {
+                offset: int8_offset,
+                needed: data_section_offset - int8_offset,
+                available: data_len.saturating_sub(int8_offset),
+            };
+            return Err(err.in_section("value pools", int8_offset).into());
+        }
 
-        // Compute struct offsets
-        let struct_offsets = Self::compute_struct_offsets_fast(
-            &data_mappings,
-            &struct_definitions,
-            data_section_offset,
-        );
-
-        // Build record map with FxHash
-        let record_map: FxHashMap<CigGuid, usize> = records
-            .iter()
-            .enumerate()
-            .map(|(i, r)| (r.id, i))
-            .collect();
-
-        // Compute main records
-        let main_records = Self::compute_main_records_fast(&records);
-
-        // Create string arena for interned strings
-        let string_arena = Bump::with_capacity(text_length_1 + text_length_2);
-
-        // Build string caches
-        let string_cache_1 = Self::build_string_cache_fast(
-            unsafe { std::slice::from_raw_parts(data_ptr.add(string_table_1_offset), string_table_1_len) },
-            &string_arena,
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            version,
+            structs = struct_definitions.len(),
+            records = records.len(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "parsed DataCore database"
         );
-        let string_cache_2 = if version >= 6 {
-            Self::build_string_cache_fast(
-                unsafe { std::slice::from_raw_parts(data_ptr.add(string_table_2_offset), string_table_2_len) },
-                &string_arena,
-            )
-        } else {
-            string_cache_1.clone()
-        };
 
         Ok(Self {
-            _mmap: None,
+            _file: None,
             _owned_data: None,
             data: data_ptr,
             data_len,
+            version,
             struct_definitions,
             property_definitions,
             enum_definitions,
@@ -397,17 +617,91 @@ impl DataCoreDatabase {
             string_table_2_offset,
             string_table_2_len,
             data_section_offset,
-            struct_offsets,
-            record_map,
-            main_records,
-            string_arena,
-            string_cache_1,
-            string_cache_2,
+            struct_offsets: Vec::new(),
+            record_map: FxHashMap::default(),
+            file_records: FxHashMap::default(),
+            main_record_indices: Vec::new(),
+            instance_records: OnceLock::new(),
+            lazy_strings: false,
+            string_arena: None,
+            string_cache_1: OnceLock::new(),
+            string_cache_2: OnceLock::new(),
         })
     }
 
+    /// Build struct offsets and record lookup tables.
+    ///
+    /// String caches are left to be built on first lookup.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_index", level = "debug", skip_all)
+    )]
+    fn build_indexes(&mut self) {
+        self.struct_offsets = Self::compute_struct_offsets_fast(
+            &self.data_mappings,
+            &self.struct_definitions,
+            self.data_section_offset,
+        );
+        self.record_map = Self::compute_record_map(&self.records);
+        (self.file_records, self.main_record_indices) = Self::compute_file_records(&self.records);
+    }
+
+    /// Build struct offsets, record lookup tables and both string caches
+    /// concurrently.
+    #[cfg(feature = "parallel")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_index", level = "debug", skip_all)
+    )]
+    fn build_indexes_parallel(&mut self) {
+        let this = &*self;
+        let ((struct_offsets, record_map), ((file_records, main_record_indices), (cache_1, cache_2))) = rayon::join(
+            || {
+                rayon::join(
+                    || {
+                        Self::compute_struct_offsets_fast(
+                            &this.data_mappings,
+                            &this.struct_definitions,
+                            this.data_section_offset,
+                        )
+                    },
+                    || Self::compute_record_map(&this.records),
+                )
+            },
+            || {
+                rayon::join(
+                    || Self::compute_file_records(&this.records),
+                    || {
+                        rayon::join(
+                            || StringCache::build(this.raw_string_table_1(), this.string_arena),
+                            || {
+                                (this.version >= 6)
+                                    .then(|| StringCache::build(this.raw_string_table_2(), this.string_arena))
+                            },
+                        )
+                    },
+                )
+            },
+        );
+
+        self.struct_offsets = struct_offsets;
+        self.record_map = record_map;
+        self.file_records = file_records;
+        self.main_record_indices = main_record_indices;
+        let _ = self.string_cache_1.set(cache_1);
+        if let Some(cache_2) = cache_2 {
+            let _ = self.string_cache_2.set(cache_2);
+        }
+    }
+
     // Accessor methods
 
+    /// File format version (5 or 6).
+    #[inline]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     #[inline]
     pub fn struct_definitions(&self) -> &[DataCoreStructDefinition] {
         &self.struct_definitions
@@ -436,19 +730,54 @@ impl DataCoreDatabase {
     /// Get a string from string table 1 (interned).
     #[inline]
     pub fn get_string(&self, id: &DataCoreStringId) -> Option<&str> {
-        self.string_cache_1.get(&id.id()).map(|&ptr| {
-            // SAFETY: ptr points into our arena
-            unsafe { &*ptr }
-        })
+        let table = self.raw_string_table_1();
+        if self.lazy_strings {
+            return Self::lazy_string(table, id.id());
+        }
+        self.strings_1().get(table, id.id())
     }
 
     /// Get a string from string table 2 (interned).
     #[inline]
     pub fn get_string2(&self, id: &DataCoreStringId2) -> Option<&str> {
-        self.string_cache_2.get(&id.id()).map(|&ptr| {
-            // SAFETY: ptr points into our arena
-            unsafe { &*ptr }
-        })
+        let table = if self.version >= 6 {
+            self.raw_string_table_2()
+        } else {
+            self.raw_string_table_1()
+        };
+        if self.lazy_strings {
+            return Self::lazy_string(table, id.id());
+        }
+        self.strings_2().get(table, id.id())
+    }
+
+    /// Read the string starting at `offset` in a string table.
+    ///
+    /// Like the interned lookup, offsets into the middle of a string are
+    /// rejected.
+    fn lazy_string(table: &[u8], offset: i32) -> Option<&str> {
+        let start = usize::try_from(offset).ok()?;
+        if start > 0 && *table.get(start - 1)? != 0 {
+            return None;
+        }
+        let rest = table.get(start..).filter(|rest| !rest.is_empty())?;
+        let end = memchr::memchr(0, rest).unwrap_or(rest.len());
+        std::str::from_utf8(&rest[..end]).ok()
+    }
+
+    fn strings_1(&self) -> &StringCache {
+        self.string_cache_1
+            .get_or_init(|| StringCache::build(self.raw_string_table_1(), self.string_arena))
+    }
+
+    /// Version 5 databases keep all strings in table 1.
+    fn strings_2(&self) -> &StringCache {
+        if self.version >= 6 {
+            self.string_cache_2
+                .get_or_init(|| StringCache::build(self.raw_string_table_2(), self.string_arena))
+        } else {
+            self.strings_1()
+        }
     }
 
     #[inline]
@@ -492,15 +821,124 @@ impl DataCoreDatabase {
             .collect()
     }
 
+    /// Index of a record in [`records`](Self::records) by GUID.
     #[inline]
-    pub fn is_main_record(&self, guid: &CigGuid) -> bool {
-        self.main_records.contains_key(guid)
+    pub(crate) fn record_index(&self, guid: &CigGuid) -> Option<usize> {
+        self.record_map.get(guid).copied()
     }
 
+    /// Get a record by its GUID string.
+    ///
+    /// Accepts the hyphenated form, optionally wrapped in braces and
+    /// surrounding whitespace. Returns `None` if the string is not a valid
+    /// GUID or no record has that GUID.
+    pub fn get_record_by_guid_str(&self, guid: &str) -> Option<&DataCoreRecord> {
+        let guid = guid.trim();
+        let guid = guid
+            .strip_prefix('{')
+            .and_then(|g| g.strip_suffix('}'))
+            .unwrap_or(guid);
+        self.get_record(&guid.parse().ok()?)
+    }
+
+    /// Get the record whose data is the given instance, if any.
+    ///
+    /// This is the reverse of a record's struct and instance index. The
+    /// lookup table is built on first use.
+    pub fn record_for_instance(&self, struct_index: u32, instance_index: u32) -> Option<&DataCoreRecord> {
+        let key = (i32::try_from(struct_index).ok()?, u16::try_from(instance_index).ok()?);
+        let instance_records = self.instance_records.get_or_init(|| {
+            self.records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| ((r.struct_index, r.instance_index), i as u32))
+                .collect()
+        });
+        instance_records.get(&key).map(|&i| &self.records[i as usize])
+    }
+
+    /// Check whether a record is the main record of its file.
+    ///
+    /// Accepts a record or its GUID. Records that are not main records are
+    /// embedded in their file's main record when exported.
+    #[inline]
+    pub fn is_main_record(&self, record: impl AsRef<CigGuid>) -> bool {
+        self.record_map
+            .get(record.as_ref())
+            .is_some_and(|&index| self.file_main_index(&self.records[index]) == Some(index))
+    }
+
+    /// Main records (the first record of each file), in record order.
     pub fn main_records(&self) -> impl Iterator<Item = &DataCoreRecord> {
-        self.records
+        self.main_record_indices
             .iter()
-            .filter(|r| self.main_records.contains_key(&r.id))
+            .map(|&i| &self.records[i as usize])
+    }
+
+    /// Number of main records.
+    #[inline]
+    pub fn main_record_count(&self) -> usize {
+        self.main_record_indices.len()
+    }
+
+    /// Get a main record by its position in [`main_records`](Self::main_records).
+    #[inline]
+    pub fn main_record(&self, index: usize) -> Option<&DataCoreRecord> {
+        self.main_record_indices
+            .get(index)
+            .map(|&i| &self.records[i as usize])
+    }
+
+    /// Get the main record of the file a record belongs to.
+    ///
+    /// Returns the record itself if it is a main record.
+    pub fn main_record_of(&self, record: &DataCoreRecord) -> Option<&DataCoreRecord> {
+        self.file_main_index(record).map(|i| &self.records[i])
+    }
+
+    /// All records stored in a file, main record first.
+    ///
+    /// Backslashes in `path` are treated as forward slashes.
+    pub fn records_in_file<'a>(&'a self, path: &str) -> impl Iterator<Item = &'a DataCoreRecord> + 'a {
+        let path = path.replace('\\', "/");
+        let indices = self
+            .file_records
+            .iter()
+            .find(|(&offset, _)| self.get_string(&DataCoreStringId::new(offset)) == Some(path.as_str()))
+            .map(|(_, indices)| indices.as_slice())
+            .unwrap_or_default();
+        indices.iter().map(|&i| &self.records[i as usize])
+    }
+
+    /// Group main records by the first `depth` directories of their file path.
+    ///
+    /// With a depth of 2, `libs/foundry/records/ammo.xml` is grouped under
+    /// `libs/foundry`. Files with fewer directories are grouped under their
+    /// full directory, and files at the root under `""`.
+    pub fn main_records_by_directory(&self, depth: usize) -> BTreeMap<&str, Vec<&DataCoreRecord>> {
+        let mut groups: BTreeMap<&str, Vec<&DataCoreRecord>> = BTreeMap::new();
+        for record in self.main_records() {
+            let path = self.record_file_name(record).unwrap_or("");
+            let directory = &path[..path.rfind('/').unwrap_or(0)];
+            let prefix = if depth == 0 {
+                ""
+            } else {
+                match directory.match_indices('/').nth(depth - 1) {
+                    Some((end, _)) => &directory[..end],
+                    None => directory,
+                }
+            };
+            groups.entry(prefix).or_default().push(record);
+        }
+        groups
+    }
+
+    /// Index of the main record of the file a record belongs to.
+    fn file_main_index(&self, record: &DataCoreRecord) -> Option<usize> {
+        self.file_records
+            .get(&record.file_name_offset.id())
+            .and_then(|indices| indices.first())
+            .map(|&i| i as usize)
     }
 
     // Zero-copy value pool accessors
@@ -715,6 +1153,84 @@ impl DataCoreDatabase {
         properties
     }
 
+    /// Bytes a property takes in an instance: the array header for arrays,
+    /// the whole struct for inline classes, the value size otherwise.
+    pub fn property_size(&self, prop: &DataCorePropertyDefinition) -> usize {
+        if prop.is_array() {
+            return 8; // count and first index
+        }
+
+        let Some(dt) = DataType::from_u16(prop.data_type) else {
+            return 0;
+        };
+
+        match dt {
+            DataType::Class => self
+                .struct_definitions
+                .get(prop.struct_index as usize)
+                .map(|d| d.struct_size as usize)
+                .unwrap_or(0),
+            _ => dt.inline_size(),
+        }
+    }
+
+    /// Ancestors of a struct, nearest parent first.
+    pub fn struct_ancestors(&self, struct_index: usize) -> Vec<usize> {
+        let mut ancestors = Vec::new();
+        let parent_of = |index: usize| self.struct_definitions.get(index).map_or(-1, |d| d.parent_type_index);
+        let mut current = parent_of(struct_index);
+
+        // Bounded so a malformed parent cycle cannot loop forever
+        while current >= 0 && ancestors.len() < self.struct_definitions.len() {
+            ancestors.push(current as usize);
+            current = parent_of(current as usize);
+        }
+
+        ancestors
+    }
+
+    /// Structs deriving directly from a struct, in index order.
+    pub fn struct_children(&self, struct_index: usize) -> Vec<usize> {
+        self.struct_definitions
+            .iter()
+            .enumerate()
+            .filter(|(_, def)| def.parent_type_index == struct_index as i32)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// All properties of a struct in instance order, inherited ones first,
+    /// with the ancestor declaring each and its offset in an instance.
+    pub fn struct_fields(&self, struct_index: usize) -> Vec<StructField<'_>> {
+        let mut chain = self.struct_ancestors(struct_index);
+        chain.reverse();
+        chain.push(struct_index);
+
+        let mut fields = Vec::new();
+        let mut offset = 0;
+
+        for declared_by in chain {
+            let Some(def) = self.struct_definitions.get(declared_by) else {
+                continue;
+            };
+            let start = def.first_attribute_index as usize;
+            let end = start + def.attribute_count as usize;
+
+            for property in self.property_definitions.get(start..end).unwrap_or_default() {
+                let size = self.property_size(property);
+                fields.push(StructField {
+                    property,
+                    declared_by,
+                    offset,
+                    size,
+                });
+                offset += size;
+            }
+        }
+
+        fields
+    }
+
     pub fn get_instance_reader(&self, struct_index: usize, instance_index: usize) -> BinaryReader<'_> {
         let struct_offset = self.struct_offsets[struct_index];
         let struct_size = self.struct_definitions[struct_index].struct_size as usize;
@@ -734,11 +1250,23 @@ impl DataCoreDatabase {
     fn read_structs<T: zerocopy::FromBytes>(
         reader: &mut BinaryReader,
         count: usize,
+        section: &'static str,
     ) -> Result<Vec<T>> {
+        let offset = reader.position();
+        let needed = count.saturating_mul(std::mem::size_of::<T>());
+        if needed > reader.remaining() {
+            let err = svarog_common::Error::UnexpectedEof {
+                offset,
+                needed,
+                available: reader.remaining(),
+            };
+            return Err(err.in_section(section, offset).into());
+        }
+
         let mut result = Vec::with_capacity(count);
 
         for _ in 0..count {
-            result.push(reader.read_struct::<T>()?);
+            result.push(reader.read_struct::<T>().in_section(section, offset)?);
         }
 
         Ok(result)
@@ -793,7 +1321,16 @@ impl DataCoreDatabase {
 
     /// Get raw pool data for a specific pool type.
     pub fn raw_pool_data(&self, pool_type: PoolType) -> &[u8] {
-        let (offset, count, elem_size) = match pool_type {
+        let (offset, count, elem_size) = self.pool_layout(pool_type);
+
+        unsafe {
+            std::slice::from_raw_parts(self.data.add(offset), count * elem_size)
+        }
+    }
+
+    /// Absolute file offset, element count and element size of a value pool.
+    pub(crate) fn pool_layout(&self, pool_type: PoolType) -> (usize, usize, usize) {
+        match pool_type {
             PoolType::Bool => (self.bool_offset, self.bool_count, 1),
             PoolType::Int8 => (self.int8_offset, self.int8_count, 1),
             PoolType::Int16 => (self.int16_offset, self.int16_count, 2),
@@ -813,14 +1350,71 @@ impl DataCoreDatabase {
             PoolType::Weak => (self.weak_offset, self.weak_count, 8),
             PoolType::Reference => (self.reference_offset, self.reference_count, 20),
             PoolType::EnumOption => (self.enum_option_offset, self.enum_option_count, 4),
-        };
+        }
+    }
 
-        unsafe {
-            std::slice::from_raw_parts(self.data.add(offset), count * elem_size)
+    /// Size of the underlying file data.
+    pub(crate) fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Bytes allocated for interned strings, and the number of strings in
+    /// tables 1 and 2.
+    pub(crate) fn string_cache_usage(&self) -> (usize, usize, usize) {
+        let count = |table: &[u8]| memchr::memchr_iter(0, table).count();
+        let table_2 = if self.version >= 6 {
+            self.raw_string_table_2()
+        } else {
+            self.raw_string_table_1()
+        };
+        if self.lazy_strings {
+            return (0, count(self.raw_string_table_1()), count(table_2));
         }
+        let (strings_1, strings_2) = (self.strings_1(), self.strings_2());
+        let mut bytes = strings_1.arena.stats().total_bytes();
+        if self.version >= 6 {
+            bytes += strings_2.arena.stats().total_bytes();
+        }
+        // Partial caches only hold the strings that fit the budget
+        let strings = |cache: &StringCache, table: &[u8]| {
+            if cache.partial {
+                count(table)
+            } else {
+                cache.strings.len()
+            }
+        };
+        (
+            bytes,
+            strings(strings_1, self.raw_string_table_1()),
+            strings(strings_2, table_2),
+        )
+    }
+
+    /// Absolute file offset of a struct instance.
+    pub(crate) fn instance_offset(&self, struct_index: usize, instance_index: usize) -> usize {
+        let struct_size = self.struct_definitions[struct_index].struct_size as usize;
+        self.struct_offsets[struct_index] + struct_size * instance_index
     }
 
-    fn build_string_cache_fast(data: &[u8], arena: &Bump) -> FxHashMap<i32, *const str> {
+    /// Absolute file offset of the record table.
+    pub(crate) fn records_offset(&self) -> usize {
+        self.int8_offset - self.records.len() * std::mem::size_of::<DataCoreRecord>()
+    }
+
+    /// Absolute file offsets and lengths of string tables 1 and 2.
+    pub(crate) fn string_table_layout(&self) -> ((usize, usize), (usize, usize)) {
+        (
+            (self.string_table_1_offset, self.string_table_1_len),
+            (self.string_table_2_offset, self.string_table_2_len),
+        )
+    }
+
+    /// Intern every string of a table, returning the handles by offset and
+    /// whether the arena ran out of budget before the end of the table.
+    fn build_string_cache_fast(
+        data: &[u8],
+        arena: &mut StringArena,
+    ) -> (FxHashMap<i32, ArenaStr>, bool) {
         let mut cache = FxHashMap::default();
         cache.reserve(data.len() / 20); // Estimate average string length
 
@@ -836,14 +1430,16 @@ impl DataCoreDatabase {
 
             if let Ok(s) = std::str::from_utf8(&data[start..null_pos]) {
                 // Intern the string in the arena
-                let interned = arena.alloc_str(s);
-                cache.insert(start as i32, interned as *const str);
+                let Ok(interned) = arena.alloc(s) else {
+                    return (cache, true);
+                };
+                cache.insert(start as i32, interned);
             }
 
             offset = null_pos + 1;
         }
 
-        cache
+        (cache, false)
     }
 
     fn compute_struct_offsets_fast(
@@ -865,17 +1461,26 @@ impl DataCoreDatabase {
         offsets
     }
 
-    fn compute_main_records_fast(records: &[DataCoreRecord]) -> FxHashMap<CigGuid, ()> {
-        let mut seen_files: FxHashMap<i32, CigGuid> = FxHashMap::default();
-        seen_files.reserve(records.len() / 2);
+    fn compute_record_map(records: &[DataCoreRecord]) -> FxHashMap<CigGuid, usize> {
+        records.iter().enumerate().map(|(i, r)| (r.id, i)).collect()
+    }
 
-        for record in records {
-            seen_files
-                .entry(record.file_name_offset.id())
-                .or_insert(record.id);
+    /// Group record indices by file, and collect the first record of each
+    /// file as its main record.
+    fn compute_file_records(records: &[DataCoreRecord]) -> (FxHashMap<i32, Vec<u32>>, Vec<u32>) {
+        let mut file_records: FxHashMap<i32, Vec<u32>> = FxHashMap::default();
+        file_records.reserve(records.len() / 2);
+        let mut main_records = Vec::new();
+
+        for (i, record) in records.iter().enumerate() {
+            let indices = file_records.entry(record.file_name_offset.id()).or_default();
+            if indices.is_empty() {
+                main_records.push(i as u32);
+            }
+            indices.push(i as u32);
         }
 
-        seen_files.into_values().map(|id| (id, ())).collect()
+        (file_records, main_records)
     }
 }
 
@@ -889,3 +1494,195 @@ impl std::fmt::Debug for DataCoreDatabase {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+
+    fn sample() -> Vec<u8> {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "label", DataType::String);
+        for i in 0..3 {
+            let record = builder.add_record(&format!("Item{}", i), item, &format!("items/item{}.xml", i));
+            builder.set_string(record, "label", &format!("label {}", i));
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_deferred_string_caches() {
+        let db = DataCoreDatabase::parse(&sample()).unwrap();
+        assert!(db.string_cache_1.get().is_none());
+        assert!(db.string_cache_2.get().is_none());
+
+        let record = db.record_by_name("Item1").unwrap();
+        assert!(db.string_cache_2.get().is_some());
+        assert_eq!(record.get_str("label"), Some("label 1"));
+        assert!(db.string_cache_1.get().is_some());
+    }
+
+    #[test]
+    fn test_struct_fields() {
+        let mut builder = DataCoreBuilder::new();
+        let vec3 = builder.add_struct("Vec3", None);
+        for axis in ["x", "y", "z"] {
+            builder.add_property(vec3, axis, DataType::Single);
+        }
+        let base = builder.add_struct("Base", None);
+        builder.add_property(base, "id", DataType::Int32);
+        builder.add_typed_property(base, "position", DataType::Class, vec3);
+        let derived = builder.add_struct("Derived", Some(base));
+        builder.add_array_property(derived, "tags", DataType::Int32);
+        builder.add_property(derived, "enabled", DataType::Boolean);
+        let sibling = builder.add_struct("Sibling", Some(base));
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let (base, derived, sibling) = (base.0 as usize, derived.0 as usize, sibling.0 as usize);
+        assert_eq!(db.struct_ancestors(derived), [base]);
+        assert!(db.struct_ancestors(base).is_empty());
+        assert_eq!(db.struct_children(base), [derived, sibling]);
+        assert!(db.struct_children(derived).is_empty());
+
+        let fields: Vec<_> = db
+            .struct_fields(derived)
+            .iter()
+            .map(|f| (db.property_name(f.property).unwrap(), f.declared_by, f.offset, f.size))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("id", base, 0, 4),
+                ("position", base, 4, 12),
+                ("tags", derived, 16, 8),
+                ("enabled", derived, 24, 1),
+            ]
+        );
+        assert_eq!({ db.struct_definitions()[derived].struct_size }, 25);
+    }
+
+    #[test]
+    fn test_record_classification() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_record("Rifle", item, "libs/foundry/records/weapons/rifle.xml");
+        builder.add_record("RifleMag", item, "libs/foundry/records/weapons/rifle.xml");
+        builder.add_record("Ammo", item, "libs/foundry/records/ammo.xml");
+        builder.add_record("Root", item, "root.xml");
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let records = db.records();
+        assert!(db.is_main_record(records[0]));
+        assert!(!db.is_main_record(records[1].id));
+        assert_eq!(db.main_record_count(), 3);
+        assert_eq!(db.main_record(1).map(|r| r.id), Some(records[2].id));
+        assert_eq!(db.main_record_of(&records[1]).map(|r| r.id), Some(records[0].id));
+
+        let in_file: Vec<_> = db
+            .records_in_file("libs\\foundry\\records\\weapons\\rifle.xml")
+            .map(|r| db.record_name(r).unwrap())
+            .collect();
+        assert_eq!(in_file, ["Rifle", "RifleMag"]);
+        assert_eq!(db.records_in_file("missing.xml").count(), 0);
+
+        let groups = db.main_records_by_directory(3);
+        let keys: Vec<_> = groups.keys().copied().collect();
+        assert_eq!(keys, ["", "libs/foundry/records"]);
+        assert_eq!(groups["libs/foundry/records"].len(), 2);
+        assert_eq!(db.main_records_by_directory(0)[""].len(), 3);
+    }
+
+    #[test]
+    fn test_guid_str_lookup() {
+        let data = sample();
+        let db = DataCoreDatabase::parse(&data).unwrap();
+
+        let record = &db.records()[1];
+        let guid = record.id.to_string();
+        assert_eq!(db.get_record_by_guid_str(&guid).map(|r| r.id), Some(record.id));
+        let braced = format!(" {{{}}}\n", guid.to_uppercase());
+        assert_eq!(db.get_record_by_guid_str(&braced).map(|r| r.id), Some(record.id));
+        assert!(db.get_record_by_guid_str("not-a-guid").is_none());
+        assert!(db.get_record_by_guid_str(&CigGuid::default().to_string()).is_none());
+
+        let owner = db
+            .record_for_instance(record.struct_index as u32, record.instance_index as u32)
+            .unwrap();
+        assert_eq!(owner.id, record.id);
+        assert!(db.record_for_instance(u32::MAX, 0).is_none());
+    }
+
+    #[test]
+    fn test_lazy_strings() {
+        let data = sample();
+        let interned = DataCoreDatabase::parse(&data).unwrap();
+        let lazy = DataCoreDatabase::parse_lazy(&data).unwrap();
+
+        for record in interned.records() {
+            let name = interned.record_name(record).unwrap();
+            assert_eq!(lazy.record_name(record), Some(name));
+            assert_eq!(lazy.record_file_name(record), interned.record_file_name(record));
+            let label = lazy.record_by_name(name).unwrap().get_str("label");
+            assert_eq!(label, interned.record_by_name(name).unwrap().get_str("label"));
+        }
+        // Offsets into the middle of a string are not string starts
+        assert_eq!(lazy.get_string(&DataCoreStringId::new(1)), interned.get_string(&DataCoreStringId::new(1)));
+        assert!(lazy.string_cache_1.get().is_none());
+        assert!(lazy.string_cache_2.get().is_none());
+        assert_eq!(lazy.statistics().string_cache_bytes, 0);
+    }
+
+    #[test]
+    fn test_string_arena_budget() {
+        use svarog_common::arena::Overflow;
+
+        let data = sample();
+        let interned = DataCoreDatabase::parse(&data).unwrap();
+        let expected = interned.statistics();
+
+        for overflow in [Overflow::Spill, Overflow::Fail] {
+            let options = ArenaOptions {
+                chunk_size: 8,
+                budget: Some(16),
+                overflow,
+            };
+            let db = DataCoreDatabase::parse_with_arena(&data, options).unwrap();
+            for record in interned.records() {
+                let name = interned.record_name(record).unwrap();
+                assert_eq!(db.record_name(record), Some(name));
+                assert_eq!(db.record_file_name(record), interned.record_file_name(record));
+                let label = db.record_by_name(name).unwrap().get_str("label");
+                assert_eq!(label, interned.record_by_name(name).unwrap().get_str("label"));
+            }
+            assert_eq!(db.get_string(&DataCoreStringId::new(1)), None);
+
+            let stats = db.statistics();
+            assert_eq!(stats.string_tables[0].strings, expected.string_tables[0].strings);
+            if overflow == Overflow::Fail {
+                assert!(db.strings_1().partial);
+                assert!(stats.string_cache_bytes <= 32);
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parse_parallel() {
+        let data = sample();
+        let serial = DataCoreDatabase::parse(&data).unwrap();
+        let parallel = DataCoreDatabase::parse_parallel(&data).unwrap();
+        assert!(parallel.string_cache_1.get().is_some());
+
+        assert_eq!(serial.struct_offsets, parallel.struct_offsets);
+        assert_eq!(serial.record_map, parallel.record_map);
+        assert_eq!(serial.file_records, parallel.file_records);
+        assert_eq!(serial.main_record_indices, parallel.main_record_indices);
+        for record in serial.records() {
+            let name = serial.record_name(record);
+            assert_eq!(name, parallel.record_name(record));
+            let label = serial.record_by_name(name.unwrap()).unwrap().get_str("label");
+            assert_eq!(label, parallel.record_by_name(name.unwrap()).unwrap().get_str("label"));
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Serde deserialization of DataCore instances.
+//!
+//! Instances are presented to serde as maps keyed by property name, so any
+//! `Deserialize` struct whose field names (or `#[serde(rename)]`s) match the
+//! DataCore properties can be filled directly from a record. Unknown
+//! properties are ignored unless the target uses `deny_unknown_fields`.
+//!
+//! Values map onto serde's data model as follows:
+//!
+//! - Primitives, strings, locale keys and enum choices deserialize as
+//!   themselves (strings are borrowed from the database).
+//! - GUIDs and record references deserialize as GUID strings; null
+//!   references deserialize as `None`.
+//! - Inline classes and strong pointers deserialize as nested maps; null
+//!   pointers deserialize as `None`.
+//! - Weak pointers deserialize as `{ struct_index, instance_index }` maps.
+//! - Arrays deserialize as sequences.
+//!
+//! This matches the types generated by [`RustCodegen`](crate::RustCodegen).
+
+use serde::de::value::{BorrowedStrDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+use svarog_common::BinaryReader;
+
+use crate::instance::{data_type_to_array_element, read_single_value, ArrayIterator};
+use crate::structs::DataCorePropertyDefinition;
+use crate::value::{ArrayRef, InstanceRef, Value};
+use crate::{DataCoreDatabase, DataType, Error, Instance, Record, Result};
+
+/// Maximum nesting of instances, guarding against pointer cycles.
+const MAX_DEPTH: usize = 128;
+
+impl<'a> Record<'a> {
+    /// Deserialize this record's properties into a typed value.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use svarog_datacore::DataCoreDatabase;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Weapon<'a> {
+    ///     #[serde(rename = "displayName")]
+    ///     display_name: &'a str,
+    ///     #[serde(rename = "baseDamage")]
+    ///     base_damage: f32,
+    /// }
+    ///
+    /// let db = DataCoreDatabase::open("Game.dcb")?;
+    /// for record in db.records_by_type("WeaponParams") {
+    ///     let weapon: Weapon = record.deserialize()?;
+    ///     println!("{}: {}", weapon.display_name, weapon.base_damage);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn deserialize<T: Deserialize<'a>>(&self) -> Result<T> {
+        self.as_instance().deserialize()
+    }
+}
+
+impl<'a> Instance<'a> {
+    /// Deserialize this instance's properties into a typed value.
+    pub fn deserialize<T: Deserialize<'a>>(&self) -> Result<T> {
+        let database = self.database();
+        let mut reader = database
+            .get_instance_reader(self.struct_index() as usize, self.instance_index() as usize);
+
+        T::deserialize(StructDeserializer {
+            database,
+            struct_index: self.struct_index(),
+            reader: &mut reader,
+            depth: 0,
+        })
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Deserialize(msg.to_string())
+    }
+}
+
+/// Deserializes a struct instance read from the current reader position.
+struct StructDeserializer<'a, 'r> {
+    database: &'a DataCoreDatabase,
+    struct_index: u32,
+    reader: &'r mut BinaryReader<'a>,
+    depth: usize,
+}
+
+impl<'de, 'r> Deserializer<'de> for StructDeserializer<'de, 'r> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.depth > MAX_DEPTH {
+            return Err(Error::Deserialize(format!(
+                "instance nesting exceeds {} levels",
+                MAX_DEPTH
+            )));
+        }
+
+        visitor.visit_map(PropertyAccess {
+            database: self.database,
+            properties: self
+                .database
+                .get_struct_properties(self.struct_index as usize),
+            index: 0,
+            reader: self.reader,
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Yields `(property name, value)` pairs of a struct instance.
+struct PropertyAccess<'a, 'r> {
+    database: &'a DataCoreDatabase,
+    properties: Vec<&'a DataCorePropertyDefinition>,
+    index: usize,
+    reader: &'r mut BinaryReader<'a>,
+    depth: usize,
+}
+
+impl<'de> MapAccess<'de> for PropertyAccess<'de, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some(prop) = self.properties.get(self.index) else {
+            return Ok(None);
+        };
+        let name = self.database.property_name(prop).unwrap_or("");
+        seed.deserialize(BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let prop = self.properties[self.index];
+        self.index += 1;
+
+        let data_type =
+            DataType::from_u16(prop.data_type).ok_or(Error::InvalidDataType(prop.data_type))?;
+
+        if prop.conversion_type == 0 {
+            if data_type == DataType::Class {
+                // Inline classes continue reading from the same position
+                return seed.deserialize(StructDeserializer {
+                    database: self.database,
+                    struct_index: prop.struct_index as u32,
+                    reader: self.reader,
+                    depth: self.depth + 1,
+                });
+            }
+
+            let value = read_single_value(
+                self.database,
+                data_type,
+                prop.struct_index as u32,
+                self.reader,
+            )
+            .ok_or_else(|| Error::Deserialize("unexpected end of instance data".to_string()))?;
+            seed.deserialize(ValueDeserializer {
+                database: self.database,
+                value,
+                depth: self.depth,
+            })
+        } else {
+            let count = self.reader.read_i32()? as u32;
+            let first_index = self.reader.read_i32()? as u32;
+            let value = Value::Array(ArrayRef {
+                element_type: data_type_to_array_element(data_type),
+                struct_index: prop.struct_index as u32,
+                count,
+                first_index,
+            });
+            seed.deserialize(ValueDeserializer {
+                database: self.database,
+                value,
+                depth: self.depth,
+            })
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.properties.len() - self.index)
+    }
+}
+
+/// Deserializes a single decoded value.
+struct ValueDeserializer<'a> {
+    database: &'a DataCoreDatabase,
+    value: Value<'a>,
+    depth: usize,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn instance<'de, V: Visitor<'de>>(&self, r: InstanceRef, visitor: V) -> Result<V::Value>
+    where
+        'a: 'de,
+    {
+        let mut reader = self
+            .database
+            .get_instance_reader(r.struct_index as usize, r.instance_index as usize);
+        StructDeserializer {
+            database: self.database,
+            struct_index: r.struct_index,
+            reader: &mut reader,
+            depth: self.depth + 1,
+        }
+        .deserialize_any(visitor)
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::Int8(v) => visitor.visit_i8(v),
+            Value::Int16(v) => visitor.visit_i16(v),
+            Value::Int32(v) => visitor.visit_i32(v),
+            Value::Int64(v) => visitor.visit_i64(v),
+            Value::UInt8(v) => visitor.visit_u8(v),
+            Value::UInt16(v) => visitor.visit_u16(v),
+            Value::UInt32(v) => visitor.visit_u32(v),
+            Value::UInt64(v) => visitor.visit_u64(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::String(s) | Value::Locale(s) | Value::Enum(s) => visitor.visit_borrowed_str(s),
+            Value::Guid(g) => visitor.visit_string(g.to_string()),
+            Value::Reference(Some(r)) => visitor.visit_string(r.guid.to_string()),
+            Value::Class(r) | Value::StrongPointer(Some(r)) => self.instance(r, visitor),
+            Value::WeakPointer(Some(r)) => visitor.visit_map(MapDeserializer::new(
+                [
+                    ("struct_index", r.struct_index),
+                    ("instance_index", r.instance_index),
+                ]
+                .into_iter(),
+            )),
+            Value::Array(array) => {
+                let (database, depth) = (self.database, self.depth);
+                let elements =
+                    ArrayIterator::new(database, array).map(move |value| ValueDeserializer {
+                        database,
+                        value,
+                        depth,
+                    });
+                let mut seq = SeqDeserializer::new(elements);
+                let result = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(result)
+            }
+            Value::StrongPointer(None)
+            | Value::WeakPointer(None)
+            | Value::Reference(None)
+            | Value::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.value {
+            Value::StrongPointer(None)
+            | Value::WeakPointer(None)
+            | Value::Reference(None)
+            | Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.value {
+            Value::String(s) | Value::Locale(s) | Value::Enum(s) => {
+                BorrowedStrDeserializer::<Error>::new(s).deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use svarog_common::CigGuid;
+
+    use crate::{DataCoreBuilder, DataCoreDatabase, DataType};
+
+    #[derive(Debug, Deserialize)]
+    struct Item<'a> {
+        name: &'a str,
+        #[serde(rename = "Mass")]
+        mass: f32,
+        tags: Vec<i32>,
+        parent: Option<CigGuid>,
+        child: Option<Box<Item<'a>>>,
+    }
+
+    #[test]
+    fn test_deserialize_record() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "name", DataType::String);
+        builder.add_property(item, "Mass", DataType::Single);
+        builder.add_array_property(item, "tags", DataType::Int32);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder.add_typed_property(item, "child", DataType::StrongPointer, item);
+        builder.add_property(item, "ignored", DataType::Boolean);
+
+        let parent_id = CigGuid::random();
+        let root = builder.add_record("Root", item, "libs/root.xml");
+        let child = builder.add_record_with_guid("Child", item, "libs/child.xml", parent_id);
+        builder.set_string(root, "name", "root");
+        builder.set_float(root, "Mass", 12.5);
+        builder.set_i32_array(root, "tags", &[1, 2, 3]);
+        builder.set_reference(root, "parent", parent_id);
+        builder.set_strong_pointer(root, "child", Some(child));
+        builder.set_string(child, "name", "child");
+        builder.set_strong_pointer(child, "child", None);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Root").unwrap();
+        let value: Item = record.deserialize().unwrap();
+
+        assert_eq!(value.name, "root");
+        assert_eq!(value.mass, 12.5);
+        assert_eq!(value.tags, vec![1, 2, 3]);
+        assert_eq!(value.parent, Some(parent_id));
+
+        let child = value.child.unwrap();
+        assert_eq!(child.name, "child");
+        assert!(child.parent.is_none());
+        assert!(child.child.is_none());
+    }
+}
@@ -0,0 +1,1118 @@
+//! Record and schema diffing between two DataCore databases.
+//!
+//! Records are paired by [`RecordMatching`]: by GUID, then by name, then by
+//! structural similarity. Each matched pair is flattened into a list of
+//! `path = value` leaves (following inline classes, arrays and owned strong
+//! pointers) and compared leaf by leaf.
+//!
+//! Struct and enum definitions are matched by name and compared property by
+//! property and value by value (see [`SchemaDiff`]).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, DataCoreDiff};
+//!
+//! let old = DataCoreDatabase::open("Game_old.dcb")?;
+//! let new = DataCoreDatabase::open("Game_new.dcb")?;
+//!
+//! let diff = DataCoreDiff::compare(&old, &new);
+//! println!("{} added, {} removed, {} changed", diff.added.len(), diff.removed.len(), diff.changed.len());
+//! print!("{}", diff);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use svarog_common::{BinaryReader, CigGuid};
+
+use crate::structs::{
+    DataCorePointer, DataCorePropertyDefinition, DataCoreRecord, DataCoreReference,
+    DataCoreStringId,
+};
+use crate::{DataCoreDatabase, DataType, RecordMatching};
+
+/// Differences between two DataCore databases.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct DataCoreDiff {
+    /// Struct and enum definition changes.
+    pub schema: SchemaDiff,
+    /// Records present only in the new database.
+    pub added: Vec<RecordSummary>,
+    /// Records present only in the old database.
+    pub removed: Vec<RecordSummary>,
+    /// Records present in both databases whose contents differ.
+    pub changed: Vec<RecordChange>,
+}
+
+/// Identifying information for a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct RecordSummary {
+    /// The record GUID.
+    pub id: CigGuid,
+    /// The record name.
+    pub name: String,
+    /// The struct type name.
+    pub type_name: String,
+    /// The file the record belongs to.
+    pub file_name: String,
+}
+
+/// A record that exists in both databases with differing contents.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct RecordChange {
+    /// The record as it appears in the new database.
+    pub record: RecordSummary,
+    /// Individual property changes, ordered by path.
+    pub properties: Vec<PropertyChange>,
+}
+
+/// A single changed leaf value.
+///
+/// `old` is `None` when the path only exists in the new record (for example a
+/// grown array), and `new` is `None` when it only exists in the old one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct PropertyChange {
+    /// Dotted property path, e.g. `damage.physical` or `ports[2].name`.
+    ///
+    /// Record metadata changes use the pseudo-paths `@id`, `@name`, `@type`
+    /// and `@file`.
+    pub path: String,
+    /// The old value.
+    pub old: Option<String>,
+    /// The new value.
+    pub new: Option<String>,
+}
+
+impl DataCoreDiff {
+    /// Compare two databases and collect added, removed and changed records.
+    pub fn compare(old: &DataCoreDatabase, new: &DataCoreDatabase) -> Self {
+        Self::compare_matched(old, new, &RecordMatching::compute(old, new))
+    }
+
+    /// Compare two databases using an existing record matching.
+    pub fn compare_matched(
+        old: &DataCoreDatabase,
+        new: &DataCoreDatabase,
+        matching: &RecordMatching,
+    ) -> Self {
+        let mut diff = Self {
+            schema: SchemaDiff::compare(old, new),
+            ..Self::default()
+        };
+
+        diff.removed = matching
+            .unmatched_old
+            .iter()
+            .filter_map(|id| old.get_record(id))
+            .map(|record| RecordSummary::new(old, record))
+            .collect();
+        diff.added = matching
+            .unmatched_new
+            .iter()
+            .filter_map(|id| new.get_record(id))
+            .map(|record| RecordSummary::new(new, record))
+            .collect();
+
+        for pair in &matching.matches {
+            let (Some(old_record), Some(record)) =
+                (old.get_record(&pair.old_id), new.get_record(&pair.new_id))
+            else {
+                continue;
+            };
+
+            let properties = compare_records(old, old_record, new, record);
+            if !properties.is_empty() {
+                diff.changed.push(RecordChange {
+                    record: RecordSummary::new(new, record),
+                    properties,
+                });
+            }
+        }
+
+        diff
+    }
+
+    /// Check if the databases contained identical records and schemas.
+    pub fn is_empty(&self) -> bool {
+        self.schema.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    /// Keep only the added, removed and changed records matching `keep`.
+    ///
+    /// The schema diff is left untouched.
+    pub fn retain_records(&mut self, mut keep: impl FnMut(&RecordSummary) -> bool) {
+        self.added.retain(&mut keep);
+        self.removed.retain(&mut keep);
+        self.changed.retain(|change| keep(&change.record));
+    }
+
+    /// Serialize the diff as pretty-printed JSON.
+    #[cfg(feature = "json-export")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Render the diff as a Markdown report, with a summary table, a list of
+    /// schema changes and one property table per changed record.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String cannot fail
+        let _ = self.write_markdown(&mut out);
+        out
+    }
+
+    fn write_markdown(&self, out: &mut String) -> fmt::Result {
+        use std::fmt::Write;
+
+        writeln!(out, "# DataCore diff")?;
+        writeln!(out)?;
+        writeln!(out, "| | Count |")?;
+        writeln!(out, "|---|---:|")?;
+        writeln!(out, "| Added records | {} |", self.added.len())?;
+        writeln!(out, "| Removed records | {} |", self.removed.len())?;
+        writeln!(out, "| Changed records | {} |", self.changed.len())?;
+        writeln!(
+            out,
+            "| Changed structs | {} |",
+            self.schema.struct_change_count()
+        )?;
+        writeln!(
+            out,
+            "| Changed enums | {} |",
+            self.schema.enum_change_count()
+        )?;
+
+        if !self.schema.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "## Schema")?;
+            writeln!(out)?;
+            for name in &self.schema.added_structs {
+                writeln!(out, "- Added struct `{}`", name)?;
+            }
+            for name in &self.schema.removed_structs {
+                writeln!(out, "- Removed struct `{}`", name)?;
+            }
+            for change in &self.schema.changed_structs {
+                writeln!(out, "- Changed struct `{}`", change.name)?;
+                if let Some((old, new)) = &change.parent {
+                    writeln!(
+                        out,
+                        "  - parent: {} → {}",
+                        old.as_deref().unwrap_or("none"),
+                        new.as_deref().unwrap_or("none")
+                    )?;
+                }
+                for prop in &change.added_properties {
+                    writeln!(out, "  - added `{}`: `{}`", prop.name, prop.type_name)?;
+                }
+                for prop in &change.removed_properties {
+                    writeln!(out, "  - removed `{}`: `{}`", prop.name, prop.type_name)?;
+                }
+                for prop in &change.retyped_properties {
+                    writeln!(
+                        out,
+                        "  - `{}`: `{}` → `{}`",
+                        prop.name, prop.old_type, prop.new_type
+                    )?;
+                }
+            }
+            for name in &self.schema.added_enums {
+                writeln!(out, "- Added enum `{}`", name)?;
+            }
+            for name in &self.schema.removed_enums {
+                writeln!(out, "- Removed enum `{}`", name)?;
+            }
+            for change in &self.schema.changed_enums {
+                writeln!(out, "- Changed enum `{}`", change.name)?;
+                for value in &change.added_values {
+                    writeln!(out, "  - added `{}`", value)?;
+                }
+                for value in &change.removed_values {
+                    writeln!(out, "  - removed `{}`", value)?;
+                }
+            }
+        }
+
+        for (title, records) in [
+            ("Added records", &self.added),
+            ("Removed records", &self.removed),
+        ] {
+            if records.is_empty() {
+                continue;
+            }
+            writeln!(out)?;
+            writeln!(out, "## {}", title)?;
+            writeln!(out)?;
+            writeln!(out, "| Name | Type | File | GUID |")?;
+            writeln!(out, "|---|---|---|---|")?;
+            for record in records {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | `{}` |",
+                    markdown_cell(&record.name),
+                    markdown_cell(&record.type_name),
+                    markdown_cell(&record.file_name),
+                    record.id
+                )?;
+            }
+        }
+
+        if !self.changed.is_empty() {
+            writeln!(out)?;
+            writeln!(out, "## Changed records")?;
+            for change in &self.changed {
+                let record = &change.record;
+                writeln!(out)?;
+                writeln!(out, "### {} ({})", record.name, record.type_name)?;
+                writeln!(out)?;
+                writeln!(out, "`{}` · `{}`", record.file_name, record.id)?;
+                writeln!(out)?;
+                writeln!(out, "| Property | Old | New |")?;
+                writeln!(out, "|---|---|---|")?;
+                for prop in &change.properties {
+                    writeln!(
+                        out,
+                        "| `{}` | {} | {} |",
+                        prop.path,
+                        prop.old
+                            .as_deref()
+                            .map_or_else(|| "*none*".to_string(), markdown_cell),
+                        prop.new
+                            .as_deref()
+                            .map_or_else(|| "*none*".to_string(), markdown_cell)
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Escape a value for use in a Markdown table cell.
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+impl RecordSummary {
+    pub(crate) fn new(db: &DataCoreDatabase, record: &DataCoreRecord) -> Self {
+        Self {
+            id: record.id,
+            name: db.record_name(record).unwrap_or("").to_string(),
+            type_name: db
+                .struct_name(record.struct_index as usize)
+                .unwrap_or("")
+                .to_string(),
+            file_name: db.record_file_name(record).unwrap_or("").to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RecordSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] {}", self.name, self.type_name, self.id)
+    }
+}
+
+impl fmt::Display for DataCoreDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.schema.is_empty() {
+            write!(f, "{}", self.schema)?;
+        }
+
+        writeln!(f, "Added ({}):", self.added.len())?;
+        for record in &self.added {
+            writeln!(f, "  + {}", record)?;
+        }
+
+        writeln!(f, "Removed ({}):", self.removed.len())?;
+        for record in &self.removed {
+            writeln!(f, "  - {}", record)?;
+        }
+
+        writeln!(f, "Changed ({}):", self.changed.len())?;
+        for change in &self.changed {
+            writeln!(f, "  ~ {}", change.record)?;
+            for prop in &change.properties {
+                writeln!(
+                    f,
+                    "      {}: {} -> {}",
+                    prop.path,
+                    prop.old.as_deref().unwrap_or("<none>"),
+                    prop.new.as_deref().unwrap_or("<none>")
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Differences between the struct and enum definitions of two databases.
+///
+/// Types are matched by name. Struct properties are compared by name against
+/// the struct's own (non-inherited) property list; inheritance changes are
+/// reported through [`StructChange::parent`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct SchemaDiff {
+    /// Struct types present only in the new database.
+    pub added_structs: Vec<String>,
+    /// Struct types present only in the old database.
+    pub removed_structs: Vec<String>,
+    /// Struct types present in both databases with differing definitions.
+    pub changed_structs: Vec<StructChange>,
+    /// Enum types present only in the new database.
+    pub added_enums: Vec<String>,
+    /// Enum types present only in the old database.
+    pub removed_enums: Vec<String>,
+    /// Enum types present in both databases with differing values.
+    pub changed_enums: Vec<EnumChange>,
+}
+
+/// Changes to a single struct definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct StructChange {
+    /// The struct type name.
+    pub name: String,
+    /// Old and new parent type names, if the inheritance changed.
+    pub parent: Option<(Option<String>, Option<String>)>,
+    /// Properties added to the struct.
+    pub added_properties: Vec<PropertySchema>,
+    /// Properties removed from the struct.
+    pub removed_properties: Vec<PropertySchema>,
+    /// Properties whose type changed.
+    pub retyped_properties: Vec<PropertyRetype>,
+}
+
+/// A property name with a human-readable type description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct PropertySchema {
+    /// The property name.
+    pub name: String,
+    /// The property type, e.g. `Single`, `Class<Foo>` or `Reference<Bar>[]`.
+    pub type_name: String,
+}
+
+/// A property whose type changed between versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct PropertyRetype {
+    /// The property name.
+    pub name: String,
+    /// The old type description.
+    pub old_type: String,
+    /// The new type description.
+    pub new_type: String,
+}
+
+/// Changes to a single enum definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct EnumChange {
+    /// The enum type name.
+    pub name: String,
+    /// Values added to the enum.
+    pub added_values: Vec<String>,
+    /// Values removed from the enum.
+    pub removed_values: Vec<String>,
+}
+
+impl SchemaDiff {
+    /// Compare the struct and enum definitions of two databases.
+    pub fn compare(old: &DataCoreDatabase, new: &DataCoreDatabase) -> Self {
+        let mut diff = Self::default();
+
+        let old_structs = struct_indices_by_name(old);
+        let new_structs = struct_indices_by_name(new);
+
+        for (name, &old_index) in &old_structs {
+            match new_structs.get(name) {
+                None => diff.removed_structs.push(name.to_string()),
+                Some(&new_index) => {
+                    let change = compare_structs(old, old_index, new, new_index);
+                    if !change.is_empty() {
+                        diff.changed_structs.push(change);
+                    }
+                }
+            }
+        }
+        for name in new_structs.keys() {
+            if !old_structs.contains_key(name) {
+                diff.added_structs.push(name.to_string());
+            }
+        }
+
+        let old_enums = enum_values_by_name(old);
+        let new_enums = enum_values_by_name(new);
+
+        for (name, old_values) in &old_enums {
+            match new_enums.get(name) {
+                None => diff.removed_enums.push(name.to_string()),
+                Some(new_values) => {
+                    let change = EnumChange {
+                        name: name.to_string(),
+                        added_values: new_values
+                            .iter()
+                            .filter(|v| !old_values.contains(*v))
+                            .map(|v| v.to_string())
+                            .collect(),
+                        removed_values: old_values
+                            .iter()
+                            .filter(|v| !new_values.contains(*v))
+                            .map(|v| v.to_string())
+                            .collect(),
+                    };
+                    if !change.added_values.is_empty() || !change.removed_values.is_empty() {
+                        diff.changed_enums.push(change);
+                    }
+                }
+            }
+        }
+        for name in new_enums.keys() {
+            if !old_enums.contains_key(name) {
+                diff.added_enums.push(name.to_string());
+            }
+        }
+
+        diff
+    }
+
+    /// Number of added, removed and changed struct types.
+    pub fn struct_change_count(&self) -> usize {
+        self.added_structs.len() + self.removed_structs.len() + self.changed_structs.len()
+    }
+
+    /// Number of added, removed and changed enum types.
+    pub fn enum_change_count(&self) -> usize {
+        self.added_enums.len() + self.removed_enums.len() + self.changed_enums.len()
+    }
+
+    /// Check if the schemas were identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_structs.is_empty()
+            && self.removed_structs.is_empty()
+            && self.changed_structs.is_empty()
+            && self.added_enums.is_empty()
+            && self.removed_enums.is_empty()
+            && self.changed_enums.is_empty()
+    }
+}
+
+impl StructChange {
+    fn is_empty(&self) -> bool {
+        self.parent.is_none()
+            && self.added_properties.is_empty()
+            && self.removed_properties.is_empty()
+            && self.retyped_properties.is_empty()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Structs: {} added, {} removed, {} changed",
+            self.added_structs.len(),
+            self.removed_structs.len(),
+            self.changed_structs.len()
+        )?;
+        for name in &self.added_structs {
+            writeln!(f, "  + {}", name)?;
+        }
+        for name in &self.removed_structs {
+            writeln!(f, "  - {}", name)?;
+        }
+        for change in &self.changed_structs {
+            writeln!(f, "  ~ {}", change.name)?;
+            if let Some((old, new)) = &change.parent {
+                writeln!(
+                    f,
+                    "      parent: {} -> {}",
+                    old.as_deref().unwrap_or("<none>"),
+                    new.as_deref().unwrap_or("<none>")
+                )?;
+            }
+            for prop in &change.added_properties {
+                writeln!(f, "      + {}: {}", prop.name, prop.type_name)?;
+            }
+            for prop in &change.removed_properties {
+                writeln!(f, "      - {}: {}", prop.name, prop.type_name)?;
+            }
+            for prop in &change.retyped_properties {
+                writeln!(
+                    f,
+                    "      ~ {}: {} -> {}",
+                    prop.name, prop.old_type, prop.new_type
+                )?;
+            }
+        }
+
+        writeln!(
+            f,
+            "Enums: {} added, {} removed, {} changed",
+            self.added_enums.len(),
+            self.removed_enums.len(),
+            self.changed_enums.len()
+        )?;
+        for name in &self.added_enums {
+            writeln!(f, "  + {}", name)?;
+        }
+        for name in &self.removed_enums {
+            writeln!(f, "  - {}", name)?;
+        }
+        for change in &self.changed_enums {
+            writeln!(f, "  ~ {}", change.name)?;
+            for value in &change.added_values {
+                writeln!(f, "      + {}", value)?;
+            }
+            for value in &change.removed_values {
+                writeln!(f, "      - {}", value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn struct_indices_by_name(db: &DataCoreDatabase) -> BTreeMap<&str, usize> {
+    (0..db.struct_definitions().len())
+        .filter_map(|i| Some((db.struct_name(i)?, i)))
+        .collect()
+}
+
+fn enum_values_by_name(db: &DataCoreDatabase) -> BTreeMap<&str, BTreeSet<&str>> {
+    db.enum_definitions()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, def)| Some((db.enum_name(i)?, db.enum_options(def).into_iter().collect())))
+        .collect()
+}
+
+fn compare_structs(
+    old_db: &DataCoreDatabase,
+    old_index: usize,
+    new_db: &DataCoreDatabase,
+    new_index: usize,
+) -> StructChange {
+    let old_def = &old_db.struct_definitions()[old_index];
+    let new_def = &new_db.struct_definitions()[new_index];
+
+    let old_parent = parent_name(old_db, old_def.parent_type_index);
+    let new_parent = parent_name(new_db, new_def.parent_type_index);
+
+    let old_props = own_properties(old_db, old_index);
+    let new_props = own_properties(new_db, new_index);
+
+    let mut change = StructChange {
+        name: old_db.struct_name(old_index).unwrap_or("").to_string(),
+        ..StructChange::default()
+    };
+
+    if old_parent != new_parent {
+        change.parent = Some((old_parent.map(String::from), new_parent.map(String::from)));
+    }
+
+    for (name, old_type) in &old_props {
+        match new_props.get(name) {
+            None => change.removed_properties.push(PropertySchema {
+                name: name.to_string(),
+                type_name: old_type.clone(),
+            }),
+            Some(new_type) if new_type != old_type => {
+                change.retyped_properties.push(PropertyRetype {
+                    name: name.to_string(),
+                    old_type: old_type.clone(),
+                    new_type: new_type.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, new_type) in &new_props {
+        if !old_props.contains_key(name) {
+            change.added_properties.push(PropertySchema {
+                name: name.to_string(),
+                type_name: new_type.clone(),
+            });
+        }
+    }
+
+    change
+}
+
+fn parent_name(db: &DataCoreDatabase, parent_index: i32) -> Option<&str> {
+    if parent_index < 0 {
+        None
+    } else {
+        db.struct_name(parent_index as usize)
+    }
+}
+
+/// The struct's own properties (excluding inherited ones) mapped to type descriptions.
+fn own_properties(db: &DataCoreDatabase, struct_index: usize) -> BTreeMap<&str, String> {
+    let def = &db.struct_definitions()[struct_index];
+    let start = def.first_attribute_index as usize;
+    let end = start + def.attribute_count as usize;
+
+    db.property_definitions()
+        .get(start..end)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(|prop| Some((db.property_name(prop)?, describe_property(db, prop))))
+        .collect()
+}
+
+fn describe_property(db: &DataCoreDatabase, prop: &DataCorePropertyDefinition) -> String {
+    let base = match DataType::from_u16(prop.data_type) {
+        Some(
+            dt @ (DataType::Class
+            | DataType::StrongPointer
+            | DataType::WeakPointer
+            | DataType::Reference),
+        ) => format!(
+            "{}<{}>",
+            dt,
+            db.struct_name(prop.struct_index as usize).unwrap_or("?")
+        ),
+        Some(DataType::EnumChoice) => match db.enum_name(prop.struct_index as usize) {
+            Some(name) => format!("EnumChoice<{}>", name),
+            None => DataType::EnumChoice.to_string(),
+        },
+        Some(dt) => dt.to_string(),
+        None => format!("Unknown(0x{:04x})", { prop.data_type }),
+    };
+
+    if prop.conversion_type == 0 {
+        base
+    } else {
+        format!("{}[]", base)
+    }
+}
+
+fn compare_records(
+    old_db: &DataCoreDatabase,
+    old: &DataCoreRecord,
+    new_db: &DataCoreDatabase,
+    new: &DataCoreRecord,
+) -> Vec<PropertyChange> {
+    let old_leaves = flatten_record(old_db, old);
+    let mut new_leaves = flatten_record(new_db, new);
+    let mut changes = Vec::new();
+
+    if old.id != new.id {
+        changes.push(PropertyChange {
+            path: "@id".to_string(),
+            old: Some(old.id.to_string()),
+            new: Some(new.id.to_string()),
+        });
+    }
+
+    for (path, old_value) in old_leaves {
+        match new_leaves.remove(&path) {
+            Some(new_value) if new_value == old_value => {}
+            new_value => changes.push(PropertyChange {
+                path,
+                old: Some(old_value),
+                new: new_value,
+            }),
+        }
+    }
+
+    for (path, new_value) in new_leaves {
+        changes.push(PropertyChange {
+            path,
+            old: None,
+            new: Some(new_value),
+        });
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// Flatten a record into an ordered map of `path -> value` leaves.
+///
+/// Record metadata is included under the `@name`, `@type` and `@file` keys.
+pub(crate) fn flatten_record(
+    db: &DataCoreDatabase,
+    record: &DataCoreRecord,
+) -> BTreeMap<String, String> {
+    let mut flattener = Flattener {
+        database: db,
+        leaves: BTreeMap::new(),
+        visited: HashSet::new(),
+    };
+
+    let summary = RecordSummary::new(db, record);
+    flattener.leaves.insert("@name".to_string(), summary.name);
+    flattener
+        .leaves
+        .insert("@type".to_string(), summary.type_name);
+    flattener
+        .leaves
+        .insert("@file".to_string(), summary.file_name);
+
+    flattener.flatten_instance("", record.struct_index, record.instance_index as usize);
+    flattener.leaves
+}
+
+/// Walks instance data the same way the XML exporter does, emitting leaves.
+struct Flattener<'a> {
+    database: &'a DataCoreDatabase,
+    leaves: BTreeMap<String, String>,
+    visited: HashSet<(i32, usize)>,
+}
+
+impl Flattener<'_> {
+    fn flatten_instance(&mut self, prefix: &str, struct_index: i32, instance_index: usize) {
+        // Guard against strong pointer cycles
+        if !self.visited.insert((struct_index, instance_index)) {
+            return;
+        }
+
+        let mut reader = self
+            .database
+            .get_instance_reader(struct_index as usize, instance_index);
+        self.flatten_struct(prefix, struct_index, &mut reader);
+
+        self.visited.remove(&(struct_index, instance_index));
+    }
+
+    fn flatten_struct(&mut self, prefix: &str, struct_index: i32, reader: &mut BinaryReader<'_>) {
+        let properties = self.database.get_struct_properties(struct_index as usize);
+
+        for prop in properties {
+            let Some(data_type) = DataType::from_u16(prop.data_type) else {
+                continue;
+            };
+            let name = self.database.property_name(prop).unwrap_or("Unknown");
+            let path = join_path(prefix, name);
+
+            if prop.conversion_type == 0 {
+                self.flatten_attribute(&path, data_type, prop.struct_index as i32, reader);
+            } else {
+                self.flatten_array(&path, data_type, prop.struct_index as i32, reader);
+            }
+        }
+    }
+
+    fn flatten_attribute(
+        &mut self,
+        path: &str,
+        data_type: DataType,
+        struct_index: i32,
+        reader: &mut BinaryReader<'_>,
+    ) {
+        match data_type {
+            DataType::Class => self.flatten_struct(path, struct_index, reader),
+            DataType::StrongPointer => {
+                if let Ok(pointer) = reader.read_struct::<DataCorePointer>() {
+                    self.flatten_strong_pointer(path, &pointer);
+                }
+            }
+            DataType::WeakPointer => {
+                if let Ok(pointer) = reader.read_struct::<DataCorePointer>() {
+                    let value = self.weak_pointer_value(&pointer);
+                    self.leaves.insert(path.to_string(), value);
+                }
+            }
+            DataType::Reference => {
+                if let Ok(reference) = reader.read_struct::<DataCoreReference>() {
+                    self.leaves
+                        .insert(path.to_string(), reference_value(&reference));
+                }
+            }
+            _ => {
+                if let Some(value) = self.read_primitive(data_type, reader) {
+                    self.leaves.insert(path.to_string(), value);
+                }
+            }
+        }
+    }
+
+    fn flatten_array(
+        &mut self,
+        path: &str,
+        data_type: DataType,
+        struct_index: i32,
+        reader: &mut BinaryReader<'_>,
+    ) {
+        let count = reader.read_i32().unwrap_or(0).max(0) as usize;
+        let first_index = reader.read_i32().unwrap_or(0).max(0) as usize;
+
+        self.leaves
+            .insert(format!("{}.@count", path), count.to_string());
+
+        for i in 0..count {
+            let index = first_index + i;
+            let element_path = format!("{}[{}]", path, i);
+
+            match data_type {
+                DataType::Class => self.flatten_instance(&element_path, struct_index, index),
+                DataType::StrongPointer => {
+                    if let Some(pointer) = self.database.strong_value(index) {
+                        self.flatten_strong_pointer(&element_path, &pointer);
+                    }
+                }
+                DataType::WeakPointer => {
+                    if let Some(pointer) = self.database.weak_value(index) {
+                        let value = self.weak_pointer_value(&pointer);
+                        self.leaves.insert(element_path, value);
+                    }
+                }
+                DataType::Reference => {
+                    if let Some(reference) = self.database.reference_value(index) {
+                        self.leaves
+                            .insert(element_path, reference_value(&reference));
+                    }
+                }
+                _ => {
+                    if let Some(value) = self.pool_value(data_type, index) {
+                        self.leaves.insert(element_path, value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn flatten_strong_pointer(&mut self, path: &str, pointer: &DataCorePointer) {
+        if pointer.is_null() {
+            self.leaves.insert(path.to_string(), "null".to_string());
+            return;
+        }
+
+        let type_name = self
+            .database
+            .struct_name(pointer.struct_index as usize)
+            .unwrap_or("Unknown");
+        self.leaves
+            .insert(format!("{}.@type", path), type_name.to_string());
+        self.flatten_instance(path, pointer.struct_index, pointer.instance_index as usize);
+    }
+
+    /// Weak pointer indices are not stable across builds, so only the target type is compared.
+    fn weak_pointer_value(&self, pointer: &DataCorePointer) -> String {
+        if pointer.is_null() {
+            return "null".to_string();
+        }
+        let type_name = self
+            .database
+            .struct_name(pointer.struct_index as usize)
+            .unwrap_or("Unknown");
+        format!("WeakPtr({})", type_name)
+    }
+
+    fn read_primitive(&self, data_type: DataType, reader: &mut BinaryReader<'_>) -> Option<String> {
+        Some(match data_type {
+            DataType::Boolean => reader.read_bool().ok()?.to_string(),
+            DataType::SByte => reader.read_i8().ok()?.to_string(),
+            DataType::Int16 => reader.read_i16().ok()?.to_string(),
+            DataType::Int32 => reader.read_i32().ok()?.to_string(),
+            DataType::Int64 => reader.read_i64().ok()?.to_string(),
+            DataType::Byte => reader.read_u8().ok()?.to_string(),
+            DataType::UInt16 => reader.read_u16().ok()?.to_string(),
+            DataType::UInt32 => reader.read_u32().ok()?.to_string(),
+            DataType::UInt64 => reader.read_u64().ok()?.to_string(),
+            DataType::Single => reader.read_f32().ok()?.to_string(),
+            DataType::Double => reader.read_f64().ok()?.to_string(),
+            DataType::Guid => reader.read_struct::<CigGuid>().ok()?.to_string(),
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                let string_id: DataCoreStringId = reader.read_struct().ok()?;
+                self.database
+                    .get_string(&string_id)
+                    .unwrap_or("")
+                    .to_string()
+            }
+            DataType::Class
+            | DataType::StrongPointer
+            | DataType::WeakPointer
+            | DataType::Reference => return None,
+        })
+    }
+
+    fn pool_value(&self, data_type: DataType, index: usize) -> Option<String> {
+        let db = self.database;
+        Some(match data_type {
+            DataType::Boolean => db.bool_value(index)?.to_string(),
+            DataType::SByte => db.int8_value(index)?.to_string(),
+            DataType::Int16 => db.int16_value(index)?.to_string(),
+            DataType::Int32 => db.int32_value(index)?.to_string(),
+            DataType::Int64 => db.int64_value(index)?.to_string(),
+            DataType::Byte => db.uint8_value(index)?.to_string(),
+            DataType::UInt16 => db.uint16_value(index)?.to_string(),
+            DataType::UInt32 => db.uint32_value(index)?.to_string(),
+            DataType::UInt64 => db.uint64_value(index)?.to_string(),
+            DataType::Single => db.float_value(index)?.to_string(),
+            DataType::Double => db.double_value(index)?.to_string(),
+            DataType::Guid => db.guid_value(index)?.to_string(),
+            DataType::String => db.get_string(&db.string_id_value(index)?)?.to_string(),
+            DataType::Locale => db.get_string(&db.locale_value(index)?)?.to_string(),
+            DataType::EnumChoice => db.get_string(&db.enum_value(index)?)?.to_string(),
+            DataType::Class
+            | DataType::StrongPointer
+            | DataType::WeakPointer
+            | DataType::Reference => return None,
+        })
+    }
+}
+
+fn reference_value(reference: &DataCoreReference) -> String {
+    if reference.is_null() {
+        "null".to_string()
+    } else {
+        reference.record_id.to_string()
+    }
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    fn build(damage: f32, extra: bool) -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("Weapon", None);
+        builder.add_property(weapon, "damage", DataType::Single);
+        builder.add_array_property(weapon, "tags", DataType::String);
+
+        let guid = CigGuid::from_bytes([1; 16]);
+        let record = builder.add_record_with_guid("Rifle", weapon, "weapons/rifle.xml", guid);
+        builder.set_float(record, "damage", damage);
+        builder.set_string_array(record, "tags", &["laser"]);
+
+        if extra {
+            let guid = CigGuid::from_bytes([2; 16]);
+            builder.add_record_with_guid("Pistol", weapon, "weapons/pistol.xml", guid);
+        }
+
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_records() {
+        let old = build(100.0, false);
+        let new = build(125.0, true);
+
+        let diff = DataCoreDiff::compare(&old, &new);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].name, "Pistol");
+        assert!(diff.removed.is_empty());
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].properties,
+            vec![PropertyChange {
+                path: "damage".to_string(),
+                old: Some("100".to_string()),
+                new: Some("125".to_string()),
+            }]
+        );
+
+        assert!(DataCoreDiff::compare(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_diff_filter_and_markdown() {
+        let old = build(100.0, false);
+        let new = build(125.0, true);
+
+        let mut diff = DataCoreDiff::compare(&old, &new);
+        let markdown = diff.to_markdown();
+        assert!(markdown.contains("| Added records | 1 |"));
+        assert!(markdown.contains("| Pistol | Weapon | weapons/pistol.xml |"));
+        assert!(markdown.contains("### Rifle (Weapon)"));
+        assert!(markdown.contains("| `damage` | 100 | 125 |"));
+
+        diff.retain_records(|record| record.file_name.starts_with("weapons/rifle"));
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert!(!diff.to_markdown().contains("## Added records"));
+    }
+
+    #[test]
+    fn test_diff_reidentified_record() {
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("Weapon", None);
+        builder.add_property(weapon, "damage", DataType::Single);
+        let record = builder.add_record_with_guid(
+            "Rifle",
+            weapon,
+            "weapons/rifle.xml",
+            CigGuid::from_bytes([1; 16]),
+        );
+        builder.set_float(record, "damage", 100.0);
+        let old = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("Weapon", None);
+        builder.add_property(weapon, "damage", DataType::Single);
+        let record = builder.add_record_with_guid(
+            "Rifle",
+            weapon,
+            "weapons/rifle.xml",
+            CigGuid::from_bytes([9; 16]),
+        );
+        builder.set_float(record, "damage", 100.0);
+        let new = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let diff = DataCoreDiff::compare(&old, &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].properties[0].path, "@id");
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let mut builder = DataCoreBuilder::new();
+        builder.add_struct("Base", None);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "mass", DataType::Single);
+        builder.add_property(item, "size", DataType::Int32);
+        builder.add_enum("Grade", &["A", "B"]);
+        let old = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let mut builder = DataCoreBuilder::new();
+        let base = builder.add_struct("Base", None);
+        let item = builder.add_struct("Item", Some(base));
+        builder.add_property(item, "mass", DataType::Double);
+        builder.add_property(item, "tags", DataType::String);
+        builder.add_struct("Ship", None);
+        builder.add_enum("Grade", &["A", "C"]);
+        let new = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let diff = SchemaDiff::compare(&old, &new);
+        assert_eq!(diff.added_structs, vec!["Ship"]);
+        assert!(diff.removed_structs.is_empty());
+
+        let change = &diff.changed_structs[0];
+        assert_eq!(change.name, "Item");
+        assert_eq!(change.parent, Some((None, Some("Base".to_string()))));
+        assert_eq!(change.added_properties[0].name, "tags");
+        assert_eq!(change.removed_properties[0].name, "size");
+        assert_eq!(change.retyped_properties[0].old_type, "Single");
+        assert_eq!(change.retyped_properties[0].new_type, "Double");
+
+        assert_eq!(diff.changed_enums[0].added_values, vec!["C"]);
+        assert_eq!(diff.changed_enums[0].removed_values, vec!["B"]);
+    }
+}
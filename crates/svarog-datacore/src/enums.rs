@@ -0,0 +1,192 @@
+//! Enum introspection.
+//!
+//! This module provides the `Enum` type for looking up enum definitions by
+//! name and converting between option names and their indices.
+
+use crate::structs::DataCoreEnumDefinition;
+use crate::{DataCoreDatabase, DataType};
+
+/// A view of an enum definition within the DataCore database.
+///
+/// Options are identified by their position in the definition, which is
+/// stable within one database but not across game versions.
+#[derive(Clone, Copy)]
+pub struct Enum<'a> {
+    database: &'a DataCoreDatabase,
+    index: u32,
+}
+
+impl<'a> Enum<'a> {
+    /// Get the enum definition index.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Get the name of the enum type.
+    #[inline]
+    pub fn name(&self) -> Option<&'a str> {
+        self.database.enum_name(self.index as usize)
+    }
+
+    /// Get the raw enum definition.
+    #[inline]
+    pub fn definition(&self) -> &'a DataCoreEnumDefinition {
+        &self.database.enum_definitions()[self.index as usize]
+    }
+
+    /// Number of options.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.definition().value_count as usize
+    }
+
+    /// Whether the enum has no options.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the option names in definition order.
+    pub fn values(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let database = self.database;
+        let first = self.definition().first_value_index as usize;
+        (first..first + self.len()).filter_map(move |i| {
+            database
+                .enum_option_value(i)
+                .and_then(|option| database.get_string2(&option))
+        })
+    }
+
+    /// Get the option name at `index`.
+    pub fn value(&self, index: usize) -> Option<&'a str> {
+        if index >= self.len() {
+            return None;
+        }
+        let option = self
+            .database
+            .enum_option_value(self.definition().first_value_index as usize + index)?;
+        self.database.get_string2(&option)
+    }
+
+    /// Get the index of the option named `value`.
+    pub fn index_of(&self, value: &str) -> Option<usize> {
+        (0..self.len()).find(|&i| self.value(i) == Some(value))
+    }
+
+    /// Check whether `value` is one of the enum's options.
+    #[inline]
+    pub fn contains(&self, value: &str) -> bool {
+        self.index_of(value).is_some()
+    }
+}
+
+impl std::fmt::Debug for Enum<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Enum")
+            .field("name", &self.name().unwrap_or("Unknown"))
+            .field("index", &self.index)
+            .field("values", &self.len())
+            .finish()
+    }
+}
+
+impl DataCoreDatabase {
+    /// Get an enum by definition index.
+    pub fn enum_at(&self, index: u32) -> Option<Enum<'_>> {
+        ((index as usize) < self.enum_definitions().len()).then_some(Enum {
+            database: self,
+            index,
+        })
+    }
+
+    /// Find an enum by name, e.g. `"EWeaponSize"`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use svarog_datacore::DataCoreDatabase;
+    ///
+    /// let db = DataCoreDatabase::open("Game.dcb")?;
+    ///
+    /// if let Some(sizes) = db.enum_by_name("EWeaponSize") {
+    ///     for (i, value) in sizes.values().enumerate() {
+    ///         println!("{}: {}", i, value);
+    ///     }
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn enum_by_name(&self, name: &str) -> Option<Enum<'_>> {
+        self.enums().find(|e| e.name() == Some(name))
+    }
+
+    /// Iterate over all enums in definition order.
+    pub fn enums(&self) -> impl Iterator<Item = Enum<'_>> {
+        (0..self.enum_definitions().len() as u32).map(move |index| Enum {
+            database: self,
+            index,
+        })
+    }
+
+    /// Enums used by a struct's properties, including inherited ones.
+    ///
+    /// Each enum is listed once, in property order. Enums used by nested
+    /// classes are not included.
+    pub fn enums_used_by_struct(&self, struct_index: usize) -> Vec<Enum<'_>> {
+        if struct_index >= self.struct_definitions().len() {
+            return Vec::new();
+        }
+
+        let mut enums: Vec<Enum<'_>> = Vec::new();
+        for prop in self.get_struct_properties(struct_index) {
+            if DataType::from_u16(prop.data_type) != Some(DataType::EnumChoice) {
+                continue;
+            }
+            let index = prop.struct_index as u32;
+            if enums.iter().all(|e| e.index != index) {
+                enums.extend(self.enum_at(index));
+            }
+        }
+        enums
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::DataCoreBuilder;
+    use crate::DataCoreDatabase;
+
+    #[test]
+    fn test_enum_lookup() {
+        let mut builder = DataCoreBuilder::new();
+        let size = builder.add_enum("EWeaponSize", &["Small", "Medium", "Large"]);
+        let kind = builder.add_enum("EWeaponKind", &["Ballistic", "Energy"]);
+        builder.add_enum("EUnused", &[]);
+        let base = builder.add_struct("WeaponBase", None);
+        builder.add_enum_property(base, "kind", kind);
+        let weapon = builder.add_struct("Weapon", Some(base));
+        builder.add_enum_property(weapon, "size", size);
+        builder.add_enum_property(weapon, "maxSize", size);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let sizes = db.enum_by_name("EWeaponSize").unwrap();
+        assert_eq!(
+            sizes.values().collect::<Vec<_>>(),
+            ["Small", "Medium", "Large"]
+        );
+        assert_eq!(sizes.value(2), Some("Large"));
+        assert_eq!(sizes.value(3), None);
+        assert_eq!(sizes.index_of("Medium"), Some(1));
+        assert!(!sizes.contains("Huge"));
+        assert!(db.enum_by_name("EUnused").unwrap().is_empty());
+        assert!(db.enum_by_name("EMissing").is_none());
+
+        let used: Vec<_> = db
+            .enums_used_by_struct(weapon.0 as usize)
+            .iter()
+            .map(|e| e.name().unwrap())
+            .collect();
+        assert_eq!(used, ["EWeaponKind", "EWeaponSize"]);
+    }
+}
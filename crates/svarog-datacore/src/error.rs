@@ -1,5 +1,6 @@
 //! Error types for DataCore parsing.
 
+use svarog_common::ErrorLocation;
 use thiserror::Error;
 
 /// Errors that can occur when working with DataCore databases.
@@ -36,6 +37,27 @@ pub enum Error {
     /// Export error.
     #[error("export error: {0}")]
     Export(String),
+
+    /// Typed deserialization error.
+    #[error("deserialize error: {0}")]
+    Deserialize(String),
+
+    /// Patching error.
+    #[error("patch error: {0}")]
+    Patch(String),
+
+    /// XML import error.
+    #[error("import error: {0}")]
+    Import(String),
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// Result type for DataCore operations.
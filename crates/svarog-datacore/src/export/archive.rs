@@ -0,0 +1,144 @@
+//! ZIP archive export for DataCore records.
+//!
+//! Exporting every record as its own file creates hundreds of thousands of
+//! small files. These methods stream the same XML into a single ZIP archive
+//! instead, with entry paths matching [`XmlExporter::export_all`].
+
+use std::io::{Seek, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::exporter::with_extension;
+use super::{ExportError, XmlExporter};
+use crate::structs::DataCoreRecord;
+
+/// Compression used for archive entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZipCompression {
+    /// No compression.
+    Stored,
+    /// Deflate, readable by every ZIP tool.
+    #[default]
+    Deflate,
+    /// Zstandard, smaller and faster but not universally supported.
+    Zstd,
+}
+
+/// Options for [`XmlExporter::export_to_zip`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZipExportOptions {
+    /// Compression method for all entries.
+    pub compression: ZipCompression,
+    /// Compression level, or the method's default if `None`.
+    pub compression_level: Option<i64>,
+}
+
+impl<'a> XmlExporter<'a> {
+    /// Export all main records into a ZIP archive.
+    ///
+    /// Returns the underlying writer once the archive is finished.
+    pub fn export_to_zip<W: Write + Seek>(
+        &self,
+        writer: W,
+        options: &ZipExportOptions,
+    ) -> Result<W, ExportError> {
+        let main_records: Vec<_> = self.database.main_records().collect();
+        self.export_records_to_zip(&main_records, writer, options, |_, _| {})
+    }
+
+    /// Export the given records into a ZIP archive.
+    ///
+    /// Each record is stored under its file name with an `.xml` extension.
+    pub fn export_records_to_zip<W: Write + Seek>(
+        &self,
+        records: &[&DataCoreRecord],
+        writer: W,
+        options: &ZipExportOptions,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<W, ExportError> {
+        let method = match options.compression {
+            ZipCompression::Stored => CompressionMethod::Stored,
+            ZipCompression::Deflate => CompressionMethod::Deflated,
+            ZipCompression::Zstd => CompressionMethod::Zstd,
+        };
+        let file_options = SimpleFileOptions::default()
+            .compression_method(method)
+            .compression_level(options.compression_level);
+
+        let mut zip = ZipWriter::new(writer);
+        let total = records.len();
+
+        for (i, record) in records.iter().enumerate() {
+            progress(i, total);
+
+            let file_name = self
+                .database
+                .record_file_name(record)
+                .unwrap_or("unknown.xml");
+
+            zip.start_file(archive_path(file_name), file_options)
+                .map_err(|e| ExportError::Io(e.to_string()))?;
+            self.write_record(record, &mut zip)?;
+        }
+
+        progress(total, total);
+        zip.finish().map_err(|e| ExportError::Io(e.to_string()))
+    }
+}
+
+/// Archive entry path for a record file name, with the extension replaced by `.xml`.
+fn archive_path(file_name: &str) -> String {
+    with_extension(&file_name.replace('\\', "/"), "xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::{DataCoreDatabase, DataType};
+
+    #[test]
+    fn test_archive_path() {
+        assert_eq!(
+            archive_path("libs/foundry/records/ammo.xml"),
+            "libs/foundry/records/ammo.xml"
+        );
+        assert_eq!(
+            archive_path("entities/ship.v2/gladius"),
+            "entities/ship.v2/gladius.xml"
+        );
+        assert_eq!(archive_path("items/.hidden"), "items/.hidden.xml");
+    }
+
+    #[test]
+    fn test_export_to_zip() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "health", DataType::Int32);
+        let record = builder.add_record("Rifle", item, "items/weapons/rifle.xml");
+        builder.set_i32(record, "health", 75);
+        builder.add_record("Ammo", item, "items/ammo.xml");
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let exporter = XmlExporter::new(&db);
+        let options = ZipExportOptions {
+            compression: ZipCompression::Zstd,
+            ..Default::default()
+        };
+        let archive = exporter
+            .export_to_zip(Cursor::new(Vec::new()), &options)
+            .unwrap();
+
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive.into_inner())).unwrap();
+        assert_eq!(zip.len(), 2);
+        let mut xml = String::new();
+        zip.by_name("items/weapons/rifle.xml")
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        assert_eq!(xml, exporter.export_record(&db.records()[0]).unwrap());
+    }
+}
@@ -0,0 +1,511 @@
+//! Pluggable export formats.
+//!
+//! An [`Exporter`] turns one record into the contents of one file. The CLI
+//! and GUI export pipelines look formats up by name in an
+//! [`ExporterRegistry`], so a new format only needs an implementation of
+//! the trait, not changes to the pipelines.
+//!
+//! Formats that are not written in Rust plug in as external programs named
+//! `svarog-export-<format>` on the `PATH`, see [`CommandExporter`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use svarog_datacore::export::{ExportError, Exporter, ExporterRegistry};
+//! use svarog_datacore::{DataCoreDatabase, ExportJob, Record};
+//!
+//! /// One wiki infobox per record.
+//! struct WikiExporter;
+//!
+//! impl Exporter for WikiExporter {
+//!     fn name(&self) -> &str {
+//!         "wiki"
+//!     }
+//!
+//!     fn extension(&self) -> &str {
+//!         "wiki"
+//!     }
+//!
+//!     fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError> {
+//!         let mut text = format!("{{{{Infobox {}\n", record.type_name().unwrap_or("Record"));
+//!         for property in record.properties() {
+//!             text.push_str(&format!("| {} = {}\n", property.name, property.value));
+//!         }
+//!         text.push_str("}}\n");
+//!         Ok(text.into_bytes())
+//!     }
+//! }
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let mut registry = ExporterRegistry::with_builtin(&db);
+//! registry.register(WikiExporter);
+//!
+//! let records: Vec<_> = db.main_records().collect();
+//! let exporter = registry.get("wiki").unwrap();
+//! let stats = exporter.export_to_dir(&db, &records, Path::new("out"), &ExportJob::new());
+//! println!("{} exported, {} failed", stats.exported, stats.errors);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::path::Path;
+
+use super::{ExportError, ExportFailure, ExportJob, ExportStats, XmlExporter};
+use crate::structs::DataCoreRecord;
+use crate::{DataCoreDatabase, Record};
+
+/// An export format: one record in, one file out.
+pub trait Exporter: Send + Sync {
+    /// Name the format is selected by, e.g. `xml`.
+    fn name(&self) -> &str;
+
+    /// Extension of the written files, without the dot.
+    fn extension(&self) -> &str;
+
+    /// Convert a record into the contents of its file.
+    fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError>;
+
+    /// Path of a record's file relative to the output directory, with `/`
+    /// separators.
+    ///
+    /// Defaults to the record's file name with its extension replaced by
+    /// [`extension`](Self::extension).
+    fn path(&self, record: Record<'_>) -> String {
+        with_extension(record.file_name().unwrap_or("unknown"), self.extension())
+    }
+
+    /// Export records to a directory, controlled by a job handle.
+    ///
+    /// The export stops before the next record once the job is cancelled
+    /// and blocks while it is paused. Failures are collected in the job
+    /// instead of aborting the export.
+    fn export_to_dir(
+        &self,
+        database: &DataCoreDatabase,
+        records: &[&DataCoreRecord],
+        output_dir: &Path,
+        job: &ExportJob,
+    ) -> ExportStats {
+        job.start(records.len());
+
+        for record in records {
+            if !job.checkpoint() {
+                break;
+            }
+            export_job_record(self, Record::new(database, record), output_dir, job);
+        }
+
+        job.finish()
+    }
+}
+
+/// Write one record below `output_dir` and report the outcome to `job`.
+pub(super) fn export_job_record<E: Exporter + ?Sized>(
+    exporter: &E,
+    record: Record<'_>,
+    output_dir: &Path,
+    job: &ExportJob,
+) {
+    let output_path = output_dir.join(
+        exporter
+            .path(record)
+            .replace('/', std::path::MAIN_SEPARATOR_STR),
+    );
+
+    let result = (|| {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ExportError::Io(e.to_string()))?;
+        }
+        let data = exporter.export(record)?;
+        std::fs::write(&output_path, &data).map_err(|e| ExportError::Io(e.to_string()))?;
+        Ok(data.len())
+    })();
+
+    match result {
+        Ok(bytes) => job.record_success(bytes),
+        Err(error) => job.record_failure(ExportFailure {
+            record_id: record.id(),
+            path: output_path,
+            error,
+        }),
+    }
+}
+
+/// Replace the extension of the last component of a `/` separated path.
+pub(super) fn with_extension(path: &str, extension: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => format!("{}.{}", &path[..name_start + dot], extension),
+        _ => format!("{}.{}", path, extension),
+    }
+}
+
+impl Exporter for XmlExporter<'_> {
+    fn name(&self) -> &str {
+        "xml"
+    }
+
+    fn extension(&self) -> &str {
+        "xml"
+    }
+
+    fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError> {
+        self.export_record(record.raw()).map(String::into_bytes)
+    }
+}
+
+/// Exports records as JSON objects.
+///
+/// Each file holds the record's identity (`guid`, `name`, `type`, `file`,
+/// `main`) and its `properties` as produced by [`Record::deserialize`].
+#[cfg(feature = "json-export")]
+#[derive(Debug, Clone, Copy)]
+pub struct JsonExporter {
+    pretty: bool,
+}
+
+#[cfg(feature = "json-export")]
+impl Default for JsonExporter {
+    fn default() -> Self {
+        Self { pretty: true }
+    }
+}
+
+#[cfg(feature = "json-export")]
+impl JsonExporter {
+    /// Create a JSON exporter writing indented output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write indented output (the default) or a single line.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// A record as the JSON value written by this exporter.
+    pub fn record_value(record: Record<'_>) -> Result<serde_json::Value, ExportError> {
+        let properties = record
+            .deserialize::<serde_json::Value>()
+            .map_err(|e| ExportError::Read(e.to_string()))?;
+        Ok(serde_json::json!({
+            "guid": record.id().to_string(),
+            "name": record.name(),
+            "type": record.type_name(),
+            "file": record.file_name(),
+            "main": record.is_main(),
+            "properties": properties,
+        }))
+    }
+}
+
+#[cfg(feature = "json-export")]
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError> {
+        let value = Self::record_value(record)?;
+        let result = if self.pretty {
+            serde_json::to_vec_pretty(&value)
+        } else {
+            serde_json::to_vec(&value)
+        };
+        result.map_err(|e| ExportError::Format(e.to_string()))
+    }
+}
+
+/// Exports records through an external program.
+///
+/// The program receives the record as a single line of JSON on stdin, in
+/// the form written by [`JsonExporter`], and writes the file contents to
+/// stdout. A non-zero exit status fails the record with the program's
+/// stderr as the error. The format name doubles as the file extension.
+#[cfg(feature = "json-export")]
+#[derive(Debug, Clone)]
+pub struct CommandExporter {
+    name: String,
+    program: std::path::PathBuf,
+}
+
+#[cfg(feature = "json-export")]
+impl CommandExporter {
+    /// Prefix of the program names found by [`discover`](Self::discover).
+    pub const PREFIX: &'static str = "svarog-export-";
+
+    /// Create an exporter for format `name` running `program`.
+    pub fn new(name: impl Into<String>, program: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            program: program.into(),
+        }
+    }
+
+    /// Program run for each record.
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Find `svarog-export-<format>` programs in the `PATH` directories.
+    ///
+    /// When several directories provide the same format, the first one wins
+    /// as it would for the shell.
+    pub fn discover() -> Vec<Self> {
+        let Some(path) = std::env::var_os("PATH") else {
+            return Vec::new();
+        };
+
+        let mut found: Vec<Self> = Vec::new();
+        for dir in std::env::split_paths(&path) {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut programs: Vec<_> = entries
+                .flatten()
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().into_string().ok()?;
+                    let name = file_name
+                        .strip_prefix(Self::PREFIX)?
+                        .strip_suffix(std::env::consts::EXE_SUFFIX)?;
+                    (!name.is_empty()).then(|| Self::new(name, entry.path()))
+                })
+                .collect();
+            programs.sort_by(|a, b| a.name.cmp(&b.name));
+
+            for program in programs {
+                if !found.iter().any(|f| f.name == program.name) {
+                    found.push(program);
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(feature = "json-export")]
+impl Exporter for CommandExporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extension(&self) -> &str {
+        &self.name
+    }
+
+    fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let mut input = serde_json::to_vec(&JsonExporter::record_value(record)?)
+            .map_err(|e| ExportError::Format(e.to_string()))?;
+        input.push(b'\n');
+
+        let mut child = Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| ExportError::Io(format!("{}: {}", self.program.display(), e)))?;
+
+        // Feed stdin from another thread so a program that writes before it
+        // has read everything cannot deadlock on full pipes
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let output = std::thread::scope(|scope| {
+            scope.spawn(move || {
+                // A program may exit without reading its input
+                let _ = stdin.write_all(&input);
+            });
+            child.wait_with_output()
+        })
+        .map_err(|e| ExportError::Io(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(ExportError::Format(format!(
+                "{} failed ({}): {}",
+                self.program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    }
+}
+
+/// Export formats by name.
+///
+/// Formats are kept in registration order; registering a format under a
+/// name that is already taken replaces the earlier one.
+#[derive(Default)]
+pub struct ExporterRegistry<'a> {
+    exporters: Vec<Box<dyn Exporter + 'a>>,
+}
+
+impl<'a> ExporterRegistry<'a> {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in formats: `xml`, and `json` with
+    /// the `json-export` feature.
+    pub fn with_builtin(database: &'a DataCoreDatabase) -> Self {
+        let mut registry = Self::new();
+        registry.register(XmlExporter::new(database));
+        #[cfg(feature = "json-export")]
+        registry.register(JsonExporter::new());
+        registry
+    }
+
+    /// Add a format, replacing any format of the same name.
+    pub fn register(&mut self, exporter: impl Exporter + 'a) {
+        self.register_boxed(Box::new(exporter));
+    }
+
+    /// Add a boxed format, replacing any format of the same name.
+    pub fn register_boxed(&mut self, exporter: Box<dyn Exporter + 'a>) {
+        match self
+            .exporters
+            .iter_mut()
+            .find(|e| e.name() == exporter.name())
+        {
+            Some(existing) => *existing = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    /// Add every `svarog-export-<format>` program on the `PATH`, without
+    /// replacing formats that are already registered.
+    #[cfg(feature = "json-export")]
+    pub fn register_commands(&mut self) {
+        for command in CommandExporter::discover() {
+            if self.get(command.name()).is_none() {
+                self.register(command);
+            }
+        }
+    }
+
+    /// Look a format up by name, ignoring case.
+    pub fn get(&self, name: &str) -> Option<&(dyn Exporter + 'a)> {
+        self.exporters
+            .iter()
+            .find(|e| e.name().eq_ignore_ascii_case(name))
+            .map(|e| e.as_ref())
+    }
+
+    /// Names of the registered formats.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.exporters.iter().map(|e| e.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::DataType;
+
+    struct UpperExporter;
+
+    impl Exporter for UpperExporter {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn export(&self, record: Record<'_>) -> Result<Vec<u8>, ExportError> {
+            Ok(record
+                .name()
+                .unwrap_or_default()
+                .to_uppercase()
+                .into_bytes())
+        }
+    }
+
+    fn database() -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "health", DataType::Int32);
+        let rifle = builder.add_record("Rifle", item, "items/weapons/rifle.xml");
+        builder.set_i32(rifle, "health", 75);
+        builder.add_record("Ammo", item, "items/ammo");
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_with_extension() {
+        assert_eq!(
+            with_extension("libs/records/ammo.xml", "json"),
+            "libs/records/ammo.json"
+        );
+        assert_eq!(
+            with_extension("entities/ship.v2/gladius", "xml"),
+            "entities/ship.v2/gladius.xml"
+        );
+        assert_eq!(with_extension("items/.hidden", "txt"), "items/.hidden.txt");
+    }
+
+    #[cfg(feature = "json-export")]
+    #[test]
+    fn test_registry() {
+        let db = database();
+        let mut registry = ExporterRegistry::with_builtin(&db);
+        registry.register(UpperExporter);
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            ["xml", "json", "upper"]
+        );
+
+        registry.register(JsonExporter::new().pretty(false));
+        assert_eq!(registry.names().count(), 3);
+        assert!(registry.get("JSON").is_some());
+        assert!(registry.get("wiki").is_none());
+
+        let rifle = db.record_by_name("Rifle").unwrap();
+        let xml = registry.get("xml").unwrap().export(rifle).unwrap();
+        assert_eq!(
+            xml,
+            XmlExporter::new(&db)
+                .export_record(rifle.raw())
+                .unwrap()
+                .into_bytes()
+        );
+
+        let json = registry.get("json").unwrap().export(rifle).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["name"], "Rifle");
+        assert_eq!(value["file"], "items/weapons/rifle.xml");
+        assert_eq!(value["properties"]["health"], 75);
+        assert!(!json.contains(&b'\n'));
+    }
+
+    #[test]
+    fn test_export_to_dir() {
+        let db = database();
+        let dir = std::env::temp_dir().join(format!("svarog-exporter-{}", std::process::id()));
+        let records: Vec<_> = db.main_records().collect();
+
+        let job = ExportJob::new();
+        let stats = UpperExporter.export_to_dir(&db, &records, &dir, &job);
+        assert!(stats.is_complete(), "{:?}", job.failures());
+        assert_eq!(
+            std::fs::read(dir.join("items/weapons/rifle.txt")).unwrap(),
+            b"RIFLE"
+        );
+        assert_eq!(std::fs::read(dir.join("items/ammo.txt")).unwrap(), b"AMMO");
+        assert_eq!(job.progress().bytes_written, 9);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+//! Cancellable, progress-reporting export jobs.
+//!
+//! An [`ExportJob`] is a cheaply cloneable handle shared between the thread
+//! running an export and the code observing it (e.g. a GUI dialog). The
+//! observer can pause, resume or cancel the export and poll its progress,
+//! throughput and per-file failures while it runs.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, ExportJob, XmlExporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let job = ExportJob::new();
+//!
+//! let handle = job.clone();
+//! std::thread::spawn(move || {
+//!     std::thread::sleep(std::time::Duration::from_secs(5));
+//!     handle.cancel();
+//! });
+//!
+//! let records: Vec<_> = db.main_records().collect();
+//! let stats = XmlExporter::new(&db).export_with_job(&records, "out/", &job);
+//! println!("{} exported, {} failed", stats.exported, stats.errors);
+//! for failure in job.failures() {
+//!     eprintln!("{}: {}", failure.path.display(), failure.error);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use svarog_common::CigGuid;
+
+use super::ExportError;
+
+/// Handle to a running export that can be paused, resumed and cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct ExportJob {
+    inner: Arc<JobState>,
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    cancelled: AtomicBool,
+    paused: Mutex<bool>,
+    resumed: Condvar,
+    total: AtomicUsize,
+    exported: AtomicUsize,
+    failed: AtomicUsize,
+    bytes_written: AtomicU64,
+    started: Mutex<Option<Instant>>,
+    finished: Mutex<Option<Instant>>,
+    failures: Mutex<Vec<ExportFailure>>,
+}
+
+/// A record that could not be exported.
+#[derive(Debug, Clone)]
+pub struct ExportFailure {
+    /// GUID of the record.
+    pub record_id: CigGuid,
+    /// Output path the record was written to.
+    pub path: PathBuf,
+    /// Why the export failed.
+    pub error: ExportError,
+}
+
+/// Snapshot of an export job's progress.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportProgress {
+    /// Number of records the job will process.
+    pub total: usize,
+    /// Number of records written successfully.
+    pub exported: usize,
+    /// Number of records that failed.
+    pub failed: usize,
+    /// Bytes of XML written so far.
+    pub bytes_written: u64,
+    /// Time spent since the job started, until it finished.
+    pub elapsed: Duration,
+}
+
+impl ExportProgress {
+    /// Number of records processed, successfully or not.
+    pub fn completed(&self) -> usize {
+        self.exported + self.failed
+    }
+
+    /// Fraction of records processed, between 0.0 and 1.0.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.completed() as f32 / self.total as f32
+    }
+
+    /// Processed records per second.
+    pub fn records_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.completed() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Written bytes per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_written as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Statistics from a finished export operation.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportStats {
+    /// Number of records successfully exported.
+    pub exported: usize,
+    /// Number of records that failed to export.
+    pub errors: usize,
+    /// Total number of records attempted.
+    pub total: usize,
+}
+
+impl ExportStats {
+    /// Check if all records were exported successfully.
+    pub fn is_complete(&self) -> bool {
+        self.errors == 0 && self.exported == self.total
+    }
+}
+
+impl ExportJob {
+    /// Create a new job handle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation. Records already being written are finished.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        // Wake paused workers so they can observe the cancellation
+        self.resume();
+    }
+
+    /// Whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Pause the export before the next record.
+    pub fn pause(&self) {
+        *self.inner.paused.lock().unwrap() = true;
+    }
+
+    /// Resume a paused export.
+    pub fn resume(&self) {
+        *self.inner.paused.lock().unwrap() = false;
+        self.inner.resumed.notify_all();
+    }
+
+    /// Whether the export is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.inner.paused.lock().unwrap()
+    }
+
+    /// Whether the export has finished, including by cancellation.
+    pub fn is_finished(&self) -> bool {
+        self.inner.finished.lock().unwrap().is_some()
+    }
+
+    /// Current progress of the export.
+    pub fn progress(&self) -> ExportProgress {
+        let started = *self.inner.started.lock().unwrap();
+        let finished = *self.inner.finished.lock().unwrap();
+        let elapsed = match (started, finished) {
+            (Some(start), Some(end)) => end.duration_since(start),
+            (Some(start), None) => start.elapsed(),
+            _ => Duration::ZERO,
+        };
+
+        ExportProgress {
+            total: self.inner.total.load(Ordering::Relaxed),
+            exported: self.inner.exported.load(Ordering::Relaxed),
+            failed: self.inner.failed.load(Ordering::Relaxed),
+            bytes_written: self.inner.bytes_written.load(Ordering::Relaxed),
+            elapsed,
+        }
+    }
+
+    /// Records that failed so far.
+    pub fn failures(&self) -> Vec<ExportFailure> {
+        self.inner.failures.lock().unwrap().clone()
+    }
+
+    /// Mark the job as started with `total` records to process.
+    pub(crate) fn start(&self, total: usize) {
+        self.inner.total.store(total, Ordering::Relaxed);
+        *self.inner.started.lock().unwrap() = Some(Instant::now());
+        *self.inner.finished.lock().unwrap() = None;
+    }
+
+    /// Mark the job as finished and return its final statistics.
+    pub(crate) fn finish(&self) -> ExportStats {
+        *self.inner.finished.lock().unwrap() = Some(Instant::now());
+        let progress = self.progress();
+        ExportStats {
+            exported: progress.exported,
+            errors: progress.failed,
+            total: progress.total,
+        }
+    }
+
+    /// Block while the job is paused.
+    ///
+    /// Returns `false` if the job was cancelled and no further records
+    /// should be started.
+    pub(crate) fn checkpoint(&self) -> bool {
+        let mut paused = self.inner.paused.lock().unwrap();
+        while *paused && !self.is_cancelled() {
+            paused = self.inner.resumed.wait(paused).unwrap();
+        }
+        !self.is_cancelled()
+    }
+
+    pub(crate) fn record_success(&self, bytes: usize) {
+        self.inner.exported.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .bytes_written
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_failure(&self, failure: ExportFailure) {
+        self.inner.failed.fetch_add(1, Ordering::Relaxed);
+        self.inner.failures.lock().unwrap().push(failure);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_releases_paused_checkpoint() {
+        let job = ExportJob::new();
+        job.start(2);
+        job.pause();
+        assert!(job.is_paused());
+
+        let handle = job.clone();
+        let worker = std::thread::spawn(move || handle.checkpoint());
+        job.cancel();
+        assert!(!worker.join().unwrap());
+
+        job.record_success(10);
+        let stats = job.finish();
+        assert!(job.is_finished());
+        assert_eq!(stats.exported, 1);
+        assert!(!stats.is_complete());
+        assert_eq!(job.progress().bytes_written, 10);
+    }
+}
@@ -0,0 +1,127 @@
+//! Manifest of exported record content hashes.
+//!
+//! The manifest is a plain text file in the export directory with one
+//! `<hash> <record file name>` line per exported file. A re-export compares
+//! each record's [`ContentHasher`](crate::ContentHasher) hash with the stored
+//! one and skips files whose content did not change.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::Path;
+
+/// Content hashes of the files in an export directory, keyed by the
+/// DataCore file name of the record they were exported from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportManifest {
+    entries: BTreeMap<String, u64>,
+}
+
+impl ExportManifest {
+    /// Name of the manifest file inside the export directory.
+    pub const FILE_NAME: &'static str = ".svarog-manifest";
+
+    /// Create an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the manifest of an export directory.
+    ///
+    /// A missing manifest loads as empty, so every record is exported.
+    /// Malformed lines are ignored.
+    pub fn load<P: AsRef<Path>>(output_dir: P) -> io::Result<Self> {
+        let text = match std::fs::read_to_string(output_dir.as_ref().join(Self::FILE_NAME)) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(Self::parse(&text))
+    }
+
+    /// Parse manifest text.
+    pub fn parse(text: &str) -> Self {
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let (hash, path) = line.split_once(' ')?;
+                let hash = u64::from_str_radix(hash, 16).ok()?;
+                Some((path.to_string(), hash))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Write the manifest into an export directory.
+    pub fn save<P: AsRef<Path>>(&self, output_dir: P) -> io::Result<()> {
+        std::fs::write(output_dir.as_ref().join(Self::FILE_NAME), self.to_text())
+    }
+
+    /// Serialize the manifest, sorted by file name.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for (path, hash) in &self.entries {
+            let _ = writeln!(out, "{:016x} {}", hash, path);
+        }
+        out
+    }
+
+    /// Stored hash of a file.
+    pub fn get(&self, file_name: &str) -> Option<u64> {
+        self.entries.get(file_name).copied()
+    }
+
+    /// Whether the stored hash of a file equals `hash`.
+    pub fn is_current(&self, file_name: &str, hash: u64) -> bool {
+        self.get(file_name) == Some(hash)
+    }
+
+    /// Store the hash of a file.
+    pub fn insert(&mut self, file_name: impl Into<String>, hash: u64) {
+        self.entries.insert(file_name.into(), hash);
+    }
+
+    /// Forget a file, e.g. after its export failed.
+    pub fn remove(&mut self, file_name: &str) -> Option<u64> {
+        self.entries.remove(file_name)
+    }
+
+    /// Number of files in the manifest.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the manifest is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Statistics from an incremental export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IncrementalStats {
+    /// Number of records written because they were new or changed.
+    pub exported: usize,
+    /// Number of records skipped because their hash matched the manifest.
+    pub unchanged: usize,
+    /// Total number of records considered.
+    pub total: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_roundtrip() {
+        let mut manifest = ExportManifest::new();
+        manifest.insert("libs/items/sword.xml", 0xdead_beef);
+        manifest.insert("libs/a b.xml", u64::MAX);
+
+        let parsed = ExportManifest::parse(&manifest.to_text());
+        assert_eq!(parsed, manifest);
+        assert!(parsed.is_current("libs/items/sword.xml", 0xdead_beef));
+        assert!(!parsed.is_current("libs/items/sword.xml", 1));
+        assert_eq!(ExportManifest::parse("garbage\n").len(), 0);
+    }
+}
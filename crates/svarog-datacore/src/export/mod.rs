@@ -8,15 +8,42 @@
 //! With the `parallel` feature, exports can be parallelized using rayon for
 //! significant speedups on multi-core systems. The exporter uses thread-local
 //! buffers to minimize allocations and lock contention.
+//!
+//! With the `zip` feature, records can be streamed into a single ZIP archive
+//! instead of one file per record.
+//!
+//! Long-running exports can be driven through an [`ExportJob`] handle to
+//! pause, resume or cancel them and to observe progress and failures.
+//!
+//! Other formats plug in through the [`Exporter`] trait and are looked up by
+//! name in an [`ExporterRegistry`]; the XML exporter and [`JsonExporter`]
+//! are built on it.
+//!
+//! Incremental exports keep an [`ExportManifest`] of record content hashes in
+//! the output directory and only rewrite records whose content changed.
 
+mod exporter;
+mod job;
+mod manifest;
 mod walker;
 mod xml;
 
-pub use walker::RecordWalker;
+#[cfg(feature = "json-export")]
+pub use exporter::{CommandExporter, JsonExporter};
+pub use exporter::{Exporter, ExporterRegistry};
+pub use job::{ExportFailure, ExportJob, ExportProgress, ExportStats};
+pub use manifest::{ExportManifest, IncrementalStats};
+pub use walker::{RecordDependencies, RecordWalker};
+pub(crate) use xml::encode_xml_name;
 pub use xml::{ExportError, XmlExporter};
 
+#[cfg(feature = "zip")]
+mod archive;
 #[cfg(feature = "parallel")]
 mod parallel;
 
+#[cfg(feature = "zip")]
+pub use archive::{ZipCompression, ZipExportOptions};
+
 #[cfg(feature = "parallel")]
 pub use parallel::ParallelXmlExporter;
@@ -9,10 +9,11 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use parking_lot::Mutex;
 use rayon::prelude::*;
 
+use super::exporter::export_job_record;
 use super::xml::ExportError;
-use super::XmlExporter;
+use super::{ExportJob, ExportStats, XmlExporter};
 use crate::structs::DataCoreRecord;
-use crate::DataCoreDatabase;
+use crate::{DataCoreDatabase, LocaleResolver, Record};
 
 /// High-performance parallel XML exporter.
 ///
@@ -20,18 +21,58 @@ use crate::DataCoreDatabase;
 /// to minimize allocations and lock contention.
 pub struct ParallelXmlExporter<'a> {
     database: &'a DataCoreDatabase,
+    locale: Option<&'a LocaleResolver>,
+    canonical: bool,
+    weak_pointer_names: bool,
 }
 
 impl<'a> ParallelXmlExporter<'a> {
     /// Create a new parallel exporter.
     pub fn new(database: &'a DataCoreDatabase) -> Self {
-        Self { database }
+        Self {
+            database,
+            locale: None,
+            canonical: false,
+            weak_pointer_names: false,
+        }
+    }
+
+    /// Resolve `Locale` values to localized text instead of writing their keys.
+    pub fn with_locale(mut self, locale: &'a LocaleResolver) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Write canonical XML, see [`XmlExporter::canonical`].
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Name weak pointer targets, see [`XmlExporter::weak_pointer_names`].
+    pub fn weak_pointer_names(mut self, weak_pointer_names: bool) -> Self {
+        self.weak_pointer_names = weak_pointer_names;
+        self
+    }
+
+    fn exporter(&self) -> XmlExporter<'a> {
+        let exporter = XmlExporter::new(self.database)
+            .canonical(self.canonical)
+            .weak_pointer_names(self.weak_pointer_names);
+        match self.locale {
+            Some(locale) => exporter.with_locale(locale),
+            None => exporter,
+        }
     }
 
     /// Export all main records to a directory in parallel.
     ///
     /// Returns the number of successfully exported records.
     /// The progress callback receives (completed, total) counts.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_export", skip_all, fields(output = %output_dir.as_ref().display()))
+    )]
     pub fn export_all<P: AsRef<Path>, F>(
         &self,
         output_dir: P,
@@ -40,6 +81,8 @@ impl<'a> ParallelXmlExporter<'a> {
     where
         F: FnMut(usize, usize) + Send,
     {
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
         let output_dir = output_dir.as_ref();
         std::fs::create_dir_all(output_dir).map_err(|e| ExportError::Io(e.to_string()))?;
 
@@ -78,6 +121,14 @@ impl<'a> ParallelXmlExporter<'a> {
         // Final progress update
         progress.lock()(total, total);
 
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            exported = exported_count,
+            errors = error_count,
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "exported DataCore records"
+        );
+
         Ok(ExportStats {
             exported: exported_count,
             errors: error_count,
@@ -86,6 +137,10 @@ impl<'a> ParallelXmlExporter<'a> {
     }
 
     /// Export a batch of records by indices.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_export", skip_all, fields(records = indices.len()))
+    )]
     pub fn export_batch<P: AsRef<Path>>(
         &self,
         indices: &[usize],
@@ -122,12 +177,48 @@ impl<'a> ParallelXmlExporter<'a> {
         })
     }
 
+    /// Export records to a directory in parallel, controlled by a job handle.
+    ///
+    /// Workers stop picking up new records once the job is cancelled and
+    /// block while it is paused. Failures are collected in the job instead
+    /// of aborting the export.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "dcb_export", skip_all, fields(records = records.len()))
+    )]
+    pub fn export_with_job<P: AsRef<Path>>(
+        &self,
+        records: &[&DataCoreRecord],
+        output_dir: P,
+        job: &ExportJob,
+    ) -> ExportStats {
+        let output_dir = output_dir.as_ref();
+        let exporter = self.exporter();
+        job.start(records.len());
+
+        records.par_iter().for_each(|record| {
+            if job.checkpoint() {
+                export_job_record(&exporter, Record::new(self.database, record), output_dir, job);
+            }
+        });
+
+        let stats = job.finish();
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            exported = stats.exported,
+            errors = stats.errors,
+            cancelled = job.is_cancelled(),
+            "exported DataCore records"
+        );
+        stats
+    }
+
     /// Export records in parallel, returning XML strings.
     ///
     /// This is useful when you want to process the XML in memory
     /// rather than writing to disk.
     pub fn export_to_strings(&self, records: &[&DataCoreRecord]) -> Vec<Result<String, ExportError>> {
-        let exporter = XmlExporter::new(self.database);
+        let exporter = self.exporter();
 
         records
             .par_iter()
@@ -140,7 +231,7 @@ impl<'a> ParallelXmlExporter<'a> {
         record: &DataCoreRecord,
         output_dir: &Path,
     ) -> Result<(), ExportError> {
-        let exporter = XmlExporter::new(self.database);
+        let exporter = self.exporter();
 
         let file_name = self
             .database
@@ -163,21 +254,3 @@ impl<'a> ParallelXmlExporter<'a> {
         Ok(())
     }
 }
-
-/// Statistics from a parallel export operation.
-#[derive(Debug, Clone, Copy)]
-pub struct ExportStats {
-    /// Number of records successfully exported.
-    pub exported: usize,
-    /// Number of records that failed to export.
-    pub errors: usize,
-    /// Total number of records attempted.
-    pub total: usize,
-}
-
-impl ExportStats {
-    /// Check if all records were exported successfully.
-    pub fn is_complete(&self) -> bool {
-        self.errors == 0 && self.exported == self.total
-    }
-}
@@ -1,11 +1,11 @@
-//! Record walker for discovering weak pointers.
+//! Record walker for discovering weak pointers and record dependencies.
 //!
 //! This module walks a record's graph to find all weak pointers that will need
-//! identifiers for XML export.
+//! identifiers for XML export, and to collect the records a record depends on.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use svarog_common::BinaryReader;
+use svarog_common::{BinaryReader, CigGuid};
 
 use crate::structs::{DataCorePointer, DataCoreRecord, DataCoreReference};
 use crate::{DataCoreDatabase, DataType};
@@ -19,6 +19,21 @@ pub struct RecordWalker<'a> {
     database: &'a DataCoreDatabase,
     weak_pointers: HashMap<(i32, i32), usize>,
     self_file_name_offset: i32,
+    /// When set, references are collected here instead of being walked.
+    references: Option<Vec<CigGuid>>,
+}
+
+/// Records a root record depends on, as found by
+/// [`RecordWalker::collect_dependencies`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordDependencies {
+    /// The root record followed by every transitively referenced record, in
+    /// discovery order.
+    pub records: Vec<CigGuid>,
+    /// Records targeted by weak pointers that are not part of `records`.
+    ///
+    /// These are listed but not followed.
+    pub weak: Vec<CigGuid>,
 }
 
 impl<'a> RecordWalker<'a> {
@@ -28,6 +43,7 @@ impl<'a> RecordWalker<'a> {
             database,
             weak_pointers: HashMap::new(),
             self_file_name_offset: record.file_name_offset.id(),
+            references: None,
         };
 
         walker.walk_instance(record.struct_index, record.instance_index as usize);
@@ -35,6 +51,58 @@ impl<'a> RecordWalker<'a> {
         walker.weak_pointers
     }
 
+    /// Collect a record and every record it transitively references.
+    ///
+    /// Record references are followed across file boundaries. Weak pointers
+    /// that land on the root instance of another record are reported in
+    /// [`RecordDependencies::weak`] but not followed.
+    pub fn collect_dependencies(database: &'a DataCoreDatabase, record: &DataCoreRecord) -> RecordDependencies {
+        let mut dependencies = RecordDependencies::default();
+        let mut visited = HashSet::new();
+        let mut weak_targets = Vec::new();
+        let mut queue = VecDeque::from([record.id]);
+
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Some(current) = database.get_record(&id) else {
+                continue;
+            };
+            dependencies.records.push(id);
+
+            let mut walker = Self {
+                database,
+                weak_pointers: HashMap::new(),
+                self_file_name_offset: current.file_name_offset.id(),
+                references: Some(Vec::new()),
+            };
+            walker.walk_instance(current.struct_index, current.instance_index as usize);
+
+            queue.extend(walker.references.unwrap_or_default());
+            weak_targets.extend(walker.weak_pointers.into_keys());
+        }
+
+        if !weak_targets.is_empty() {
+            let roots: HashMap<(i32, i32), CigGuid> = database
+                .records()
+                .iter()
+                .map(|r| ((r.struct_index, r.instance_index as i32), r.id))
+                .collect();
+
+            let mut listed = HashSet::new();
+            for target in weak_targets {
+                if let Some(&id) = roots.get(&target) {
+                    if !visited.contains(&id) && listed.insert(id) {
+                        dependencies.weak.push(id);
+                    }
+                }
+            }
+        }
+
+        dependencies
+    }
+
     fn walk_instance(&mut self, struct_index: i32, instance_index: usize) {
         let mut reader = self.database.get_instance_reader(struct_index as usize, instance_index);
         self.walk_struct(struct_index, &mut reader);
@@ -122,8 +190,13 @@ impl<'a> RecordWalker<'a> {
             return;
         }
 
+        if let Some(references) = &mut self.references {
+            references.push(reference.record_id);
+            return;
+        }
+
         // Don't walk main records (they're separate files)
-        if self.database.is_main_record(&reference.record_id) {
+        if self.database.is_main_record(reference.record_id) {
             return;
         }
 
@@ -156,3 +229,40 @@ impl<'a> RecordWalker<'a> {
         self.weak_pointers.entry(key).or_insert(next_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    #[test]
+    fn test_collect_dependencies() {
+        let mut builder = DataCoreBuilder::new();
+        let node = builder.add_struct("Node", None);
+        builder.add_typed_property(node, "next", DataType::Reference, node);
+        builder.add_typed_property(node, "sibling", DataType::WeakPointer, node);
+
+        let root_id = CigGuid::random();
+        let child_id = CigGuid::random();
+        let leaf_id = CigGuid::random();
+        let other_id = CigGuid::random();
+
+        let root = builder.add_record_with_guid("Root", node, "libs/root.xml", root_id);
+        let child = builder.add_record_with_guid("Child", node, "libs/child.xml", child_id);
+        let leaf = builder.add_record_with_guid("Leaf", node, "libs/leaf.xml", leaf_id);
+        let other = builder.add_record_with_guid("Other", node, "libs/other.xml", other_id);
+
+        builder.set_reference(root, "next", child_id);
+        builder.set_weak_pointer(root, "sibling", Some(other));
+        builder.set_reference(child, "next", leaf_id);
+        // Cycles back to the root must not be followed twice
+        builder.set_reference(leaf, "next", root_id);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.get_record(&root_id).unwrap();
+        let deps = RecordWalker::collect_dependencies(&db, record);
+
+        assert_eq!(deps.records, vec![root_id, child_id, leaf_id]);
+        assert_eq!(deps.weak, vec![other_id]);
+    }
+}
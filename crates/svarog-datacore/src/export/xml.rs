@@ -2,27 +2,73 @@
 //!
 //! This module provides functionality to export DataCore records to XML format,
 //! similar to the .NET DataCoreBinaryXml class.
+//!
+//! In canonical mode ([`XmlExporter::canonical`]) the output of unchanged
+//! records is byte-identical between game versions: attributes are sorted by
+//! name, floats use a normalized shortest round-trip form and reference
+//! arrays are sorted by target, so exported trees diff line by line.
 
 use std::collections::HashMap;
 use std::io::Write;
 
+use quick_xml::events::attributes::Attribute;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
 use quick_xml::Writer;
 use svarog_common::BinaryReader;
 
-use super::RecordWalker;
+use super::{
+    ExportJob, ExportManifest, ExportStats, Exporter, IncrementalStats, RecordDependencies,
+    RecordWalker,
+};
 use crate::structs::{DataCorePointer, DataCoreRecord, DataCoreReference};
-use crate::{DataCoreDatabase, DataType};
+use crate::{ContentHasher, DataCoreDatabase, DataType, LocaleResolver};
 
 /// XML exporter for DataCore records.
+#[derive(Clone)]
 pub struct XmlExporter<'a> {
-    database: &'a DataCoreDatabase,
+    pub(super) database: &'a DataCoreDatabase,
+    locale: Option<&'a LocaleResolver>,
+    canonical: bool,
+    weak_pointer_names: bool,
 }
 
 impl<'a> XmlExporter<'a> {
     /// Create a new XML exporter.
     pub fn new(database: &'a DataCoreDatabase) -> Self {
-        Self { database }
+        Self {
+            database,
+            locale: None,
+            canonical: false,
+            weak_pointer_names: false,
+        }
+    }
+
+    /// Resolve `Locale` values to localized text instead of writing their keys.
+    pub fn with_locale(mut self, locale: &'a LocaleResolver) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Write canonical XML for line-by-line diffs between game versions.
+    ///
+    /// Attributes are sorted by name, floats are written in their shortest
+    /// round-trip form with an exponent for very large or small magnitudes
+    /// (`-0` is written as `0`), and arrays of references are sorted by the
+    /// referenced file, record name and GUID.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Write the name of each weak pointer's target in a `PointsToName`
+    /// attribute.
+    ///
+    /// `PointsTo` ids only link targets inside the exported file; the name
+    /// also identifies targets in other files. Targets that are the instance
+    /// of a record use the record's name, others `Type[index]`.
+    pub fn weak_pointer_names(mut self, weak_pointer_names: bool) -> Self {
+        self.weak_pointer_names = weak_pointer_names;
+        self
     }
 
     /// Export a record to XML string.
@@ -49,6 +95,10 @@ impl<'a> XmlExporter<'a> {
             writer: Writer::new_with_indent(writer, b' ', 2),
             pointers,
             file_path: file_path.to_string(),
+            locale: self.locale,
+            canonical: self.canonical,
+            weak_pointer_names: self.weak_pointer_names,
+            pending: None,
         };
 
         // Write XML declaration
@@ -67,22 +117,14 @@ impl<'a> XmlExporter<'a> {
         let encoded_name = encode_xml_name(record_name);
 
         // Write root element
-        let mut root = BytesStart::new(&encoded_name);
-        root.push_attribute(("RecordId", record.id.to_string().as_str()));
-
-        context
-            .writer
-            .write_event(Event::Start(root))
-            .map_err(|e| ExportError::Xml(e.to_string()))?;
+        context.start_element(&encoded_name)?;
+        context.write_attribute_str("RecordId", &record.id.to_string())?;
 
         // Write instance data
         context.write_instance(record.struct_index, record.instance_index as usize)?;
 
         // Close root element
-        context
-            .writer
-            .write_event(Event::End(BytesEnd::new(&encoded_name)))
-            .map_err(|e| ExportError::Xml(e.to_string()))?;
+        context.end_element(&encoded_name)?;
 
         Ok(())
     }
@@ -124,6 +166,134 @@ impl<'a> XmlExporter<'a> {
         progress(total, total);
         Ok(total)
     }
+
+    /// Export all main records to a directory, skipping unchanged records.
+    ///
+    /// Each record's [`ContentHasher`] hash, seeded with the exporter's
+    /// options, is compared with the [`ExportManifest`] stored in
+    /// `output_dir`. Records with a matching hash whose file still exists are
+    /// skipped; the manifest is updated and saved afterwards.
+    pub fn export_incremental<P: AsRef<std::path::Path>>(
+        &self,
+        output_dir: P,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<IncrementalStats, ExportError> {
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir).map_err(|e| ExportError::Io(e.to_string()))?;
+
+        let mut manifest =
+            ExportManifest::load(output_dir).map_err(|e| ExportError::Io(e.to_string()))?;
+        let hasher = self.content_hasher();
+
+        let main_records: Vec<_> = self.database.main_records().collect();
+        let mut stats = IncrementalStats {
+            total: main_records.len(),
+            ..Default::default()
+        };
+
+        for (i, record) in main_records.iter().enumerate() {
+            progress(i, stats.total);
+
+            let file_name = self
+                .database
+                .record_file_name(record)
+                .unwrap_or("unknown.xml");
+            let output_path = output_dir.join(file_name.replace('/', std::path::MAIN_SEPARATOR_STR));
+            let output_path = output_path.with_extension("xml");
+
+            let hash = hasher.hash_record(record);
+            if manifest.is_current(file_name, hash) && output_path.exists() {
+                stats.unchanged += 1;
+                continue;
+            }
+
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| ExportError::Io(e.to_string()))?;
+            }
+
+            let xml = self.export_record(record)?;
+            std::fs::write(&output_path, xml).map_err(|e| ExportError::Io(e.to_string()))?;
+            manifest.insert(file_name, hash);
+            stats.exported += 1;
+        }
+
+        manifest
+            .save(output_dir)
+            .map_err(|e| ExportError::Io(e.to_string()))?;
+
+        progress(stats.total, stats.total);
+        Ok(stats)
+    }
+
+    /// Content hasher whose hashes also change with this exporter's options.
+    pub fn content_hasher(&self) -> ContentHasher<'a> {
+        let options = [self.canonical as u8, self.weak_pointer_names as u8];
+        let hasher = ContentHasher::new(self.database).with_seed(&options);
+        match self.locale {
+            Some(locale) => hasher.with_locale(locale).with_seed(b"locale"),
+            None => hasher,
+        }
+    }
+
+    /// Export records to a directory, controlled by a job handle.
+    ///
+    /// The export stops before the next record once the job is cancelled
+    /// and blocks while it is paused. Failures are collected in the job
+    /// instead of aborting the export.
+    pub fn export_with_job<P: AsRef<std::path::Path>>(
+        &self,
+        records: &[&DataCoreRecord],
+        output_dir: P,
+        job: &ExportJob,
+    ) -> ExportStats {
+        self.export_to_dir(self.database, records, output_dir.as_ref(), job)
+    }
+
+    /// Export a record together with every record it transitively references.
+    ///
+    /// Each dependency is written as the main record of the file it lives in,
+    /// so the output directory mirrors the layout of [`export_all`](Self::export_all)
+    /// but only contains the files needed to resolve the root record.
+    pub fn export_with_dependencies<P: AsRef<std::path::Path>>(
+        &self,
+        record: &DataCoreRecord,
+        output_dir: P,
+    ) -> Result<RecordDependencies, ExportError> {
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir).map_err(|e| ExportError::Io(e.to_string()))?;
+
+        let dependencies = RecordWalker::collect_dependencies(self.database, record);
+
+        let mut written = std::collections::HashSet::new();
+        for id in &dependencies.records {
+            let Some(dependency) = self.database.get_record(id) else {
+                continue;
+            };
+            // Non-main records are written inline as part of their file's main record
+            let target = self.database.main_record_of(dependency).unwrap_or(dependency);
+
+            if !written.insert(target.id) {
+                continue;
+            }
+
+            let file_name = self
+                .database
+                .record_file_name(target)
+                .unwrap_or("unknown.xml");
+
+            let output_path = output_dir.join(file_name.replace('/', std::path::MAIN_SEPARATOR_STR));
+            let output_path = output_path.with_extension("xml");
+
+            if let Some(parent) = output_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| ExportError::Io(e.to_string()))?;
+            }
+
+            let xml = self.export_record(target)?;
+            std::fs::write(&output_path, xml).map_err(|e| ExportError::Io(e.to_string()))?;
+        }
+
+        Ok(dependencies)
+    }
 }
 
 /// Export context holding state during XML generation.
@@ -132,6 +302,11 @@ struct ExportContext<'a, W: Write> {
     writer: Writer<W>,
     pointers: HashMap<(i32, i32), usize>,
     file_path: String,
+    locale: Option<&'a LocaleResolver>,
+    canonical: bool,
+    weak_pointer_names: bool,
+    /// Start tag that is still accepting attributes.
+    pending: Option<BytesStart<'static>>,
 }
 
 impl<'a, W: Write> ExportContext<'a, W> {
@@ -204,11 +379,7 @@ impl<'a, W: Write> ExportContext<'a, W> {
                     .map_err(|e| ExportError::Read(e.to_string()))?;
 
                 self.start_element(&encoded_name)?;
-                if !pointer.is_null() {
-                    if let Some(&ptr_id) = self.pointers.get(&(pointer.struct_index, pointer.instance_index)) {
-                        self.write_attribute_str("PointsTo", &format!("ptr:{}", ptr_id))?;
-                    }
-                }
+                self.write_weak_pointer(&pointer)?;
                 self.end_element(&encoded_name)?;
             }
             DataType::StrongPointer => {
@@ -249,25 +420,21 @@ impl<'a, W: Write> ExportContext<'a, W> {
 
         let encoded_name = encode_xml_name(name);
 
-        let mut elem = BytesStart::new(&encoded_name);
+        self.start_element(&encoded_name)?;
         if let Some(type_name) = self.database.struct_name(struct_index as usize) {
-            elem.push_attribute(("Type", type_name));
+            self.write_attribute_str("Type", type_name)?;
         }
-        elem.push_attribute(("Count", count.to_string().as_str()));
-
-        self.writer
-            .write_event(Event::Start(elem))
-            .map_err(|e| ExportError::Xml(e.to_string()))?;
+        self.write_attribute_str("Count", &count.to_string())?;
 
-        for i in first_index..(first_index + count) {
-            self.write_array_element(data_type, struct_index, i as usize)?;
+        let mut indices: Vec<usize> = (first_index..first_index + count).map(|i| i as usize).collect();
+        if self.canonical && data_type == DataType::Reference {
+            indices.sort_by_cached_key(|&i| self.reference_sort_key(i));
+        }
+        for i in indices {
+            self.write_array_element(data_type, struct_index, i)?;
         }
 
-        self.writer
-            .write_event(Event::End(BytesEnd::new(&encoded_name)))
-            .map_err(|e| ExportError::Xml(e.to_string()))?;
-
-        Ok(())
+        self.end_element(&encoded_name)
     }
 
     fn write_array_element(
@@ -308,11 +475,7 @@ impl<'a, W: Write> ExportContext<'a, W> {
 
                     let encoded = encode_xml_name(type_name);
                     self.start_element(&encoded)?;
-                    if !pointer.is_null() {
-                        if let Some(&ptr_id) = self.pointers.get(&(pointer.struct_index, pointer.instance_index)) {
-                            self.write_attribute_str("PointsTo", &format!("ptr:{}", ptr_id))?;
-                        }
-                    }
+                    self.write_weak_pointer(&pointer)?;
                     self.end_element(&encoded)?;
                 }
             }
@@ -353,6 +516,57 @@ impl<'a, W: Write> ExportContext<'a, W> {
         Ok(())
     }
 
+    /// Sort key of a reference array element: file, record name and GUID of
+    /// the target, with null references first.
+    fn reference_sort_key(&self, index: usize) -> (String, String, String) {
+        let Some(reference) = self.database.reference_value(index).filter(|r| !r.is_null()) else {
+            return Default::default();
+        };
+        let record = self.database.get_record(&reference.record_id);
+        (
+            record
+                .and_then(|r| self.database.record_file_name(r))
+                .unwrap_or("")
+                .to_string(),
+            record
+                .and_then(|r| self.database.record_name(r))
+                .unwrap_or("")
+                .to_string(),
+            reference.record_id.to_string(),
+        )
+    }
+
+    fn write_weak_pointer(&mut self, pointer: &DataCorePointer) -> Result<(), ExportError> {
+        if pointer.is_null() {
+            return Ok(());
+        }
+
+        if let Some(&ptr_id) = self.pointers.get(&(pointer.struct_index, pointer.instance_index)) {
+            self.write_attribute_str("PointsTo", &format!("ptr:{}", ptr_id))?;
+        }
+
+        if self.weak_pointer_names {
+            let target = self
+                .database
+                .record_for_instance(pointer.struct_index as u32, pointer.instance_index as u32);
+            match target.and_then(|record| self.database.record_name(record)) {
+                Some(name) => self.write_attribute_str("PointsToName", name)?,
+                None => {
+                    let type_name = self
+                        .database
+                        .struct_name(pointer.struct_index as usize)
+                        .unwrap_or("Unknown");
+                    self.write_attribute_str(
+                        "PointsToName",
+                        &format!("{}[{}]", type_name, { pointer.instance_index }),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn write_reference(&mut self, reference: &DataCoreReference) -> Result<(), ExportError> {
         if reference.is_null() {
             return Ok(());
@@ -364,7 +578,7 @@ impl<'a, W: Write> ExportContext<'a, W> {
         };
 
         // If referencing a main record (full file), just reference it
-        if self.database.is_main_record(&reference.record_id) {
+        if self.database.is_main_record(reference.record_id) {
             let file_name = self.database.record_file_name(record).unwrap_or("");
             let relative_path = compute_relative_path(file_name, &self.file_path);
             self.write_attribute_str("ReferencedFile", &relative_path)?;
@@ -437,11 +651,11 @@ impl<'a, W: Write> ExportContext<'a, W> {
                 .map_err(|e| ExportError::Read(e.to_string()))?,
             DataType::Single => reader
                 .read_f32()
-                .map(|v| v.to_string())
+                .map(|v| self.format_float(v))
                 .map_err(|e| ExportError::Read(e.to_string()))?,
             DataType::Double => reader
                 .read_f64()
-                .map(|v| v.to_string())
+                .map(|v| self.format_float(v))
                 .map_err(|e| ExportError::Read(e.to_string()))?,
             DataType::Guid => {
                 let guid: svarog_common::CigGuid = reader
@@ -449,7 +663,7 @@ impl<'a, W: Write> ExportContext<'a, W> {
                     .map_err(|e| ExportError::Read(e.to_string()))?;
                 guid.to_string()
             }
-            DataType::String | DataType::Locale | DataType::EnumChoice => {
+            DataType::String | DataType::EnumChoice => {
                 let string_id: crate::structs::DataCoreStringId = reader
                     .read_struct()
                     .map_err(|e| ExportError::Read(e.to_string()))?;
@@ -458,6 +672,12 @@ impl<'a, W: Write> ExportContext<'a, W> {
                     .unwrap_or("")
                     .to_string()
             }
+            DataType::Locale => {
+                let string_id: crate::structs::DataCoreStringId = reader
+                    .read_struct()
+                    .map_err(|e| ExportError::Read(e.to_string()))?;
+                self.localize(self.database.get_string(&string_id).unwrap_or(""))
+            }
             _ => String::new(),
         };
 
@@ -514,12 +734,12 @@ impl<'a, W: Write> ExportContext<'a, W> {
             DataType::Single => self
                 .database
                 .float_value(index)
-                .map(|v| v.to_string())
+                .map(|v| self.format_float(v))
                 .unwrap_or_default(),
             DataType::Double => self
                 .database
                 .double_value(index)
-                .map(|v| v.to_string())
+                .map(|v| self.format_float(v))
                 .unwrap_or_default(),
             DataType::Guid => self
                 .database
@@ -532,12 +752,12 @@ impl<'a, W: Write> ExportContext<'a, W> {
                 .and_then(|id| self.database.get_string(&id))
                 .unwrap_or("")
                 .to_string(),
-            DataType::Locale => self
-                .database
-                .locale_value(index)
-                .and_then(|id| self.database.get_string(&id))
-                .unwrap_or("")
-                .to_string(),
+            DataType::Locale => self.localize(
+                self.database
+                    .locale_value(index)
+                    .and_then(|id| self.database.get_string(&id))
+                    .unwrap_or(""),
+            ),
             DataType::EnumChoice => self
                 .database
                 .enum_value(index)
@@ -550,45 +770,87 @@ impl<'a, W: Write> ExportContext<'a, W> {
         Ok(value)
     }
 
+    fn format_float<F>(&self, value: F) -> String
+    where
+        F: std::fmt::Display + std::fmt::Debug + Into<f64> + Copy,
+    {
+        if self.canonical {
+            canonical_float(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn localize(&self, key: &str) -> String {
+        match self.locale {
+            Some(locale) => locale.resolve(key).to_string(),
+            None => key.to_string(),
+        }
+    }
+
     // Helper methods for XML writing
 
-    fn start_element(&mut self, name: &str) -> Result<(), ExportError> {
+    /// Write an event, first emitting any start tag still collecting attributes.
+    fn write_event(&mut self, event: Event<'_>) -> Result<(), ExportError> {
+        self.flush_pending()?;
         self.writer
-            .write_event(Event::Start(BytesStart::new(name)))
+            .write_event(event)
             .map_err(|e| ExportError::Xml(e.to_string()))
     }
 
+    /// Write the start tag still collecting attributes, if any.
+    fn flush_pending(&mut self) -> Result<(), ExportError> {
+        if let Some(start) = self.pending.take() {
+            let start = if self.canonical {
+                sort_attributes(&start)?
+            } else {
+                start
+            };
+            self.writer
+                .write_event(Event::Start(start))
+                .map_err(|e| ExportError::Xml(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Open an element. Attributes can be added until its content is written.
+    fn start_element(&mut self, name: &str) -> Result<(), ExportError> {
+        self.flush_pending()?;
+        self.pending = Some(BytesStart::new(name.to_string()));
+        Ok(())
+    }
+
     fn end_element(&mut self, name: &str) -> Result<(), ExportError> {
-        self.writer
-            .write_event(Event::End(BytesEnd::new(name)))
-            .map_err(|e| ExportError::Xml(e.to_string()))
+        self.write_event(Event::End(BytesEnd::new(name)))
     }
 
     fn write_empty_element(&mut self, name: &str) -> Result<(), ExportError> {
-        self.writer
-            .write_event(Event::Empty(BytesStart::new(name)))
-            .map_err(|e| ExportError::Xml(e.to_string()))
+        self.write_event(Event::Empty(BytesStart::new(name)))
     }
 
     fn write_element(&mut self, name: &str, value: &str) -> Result<(), ExportError> {
         self.start_element(name)?;
-        self.writer
-            .write_event(Event::Text(quick_xml::events::BytesText::new(value)))
-            .map_err(|e| ExportError::Xml(e.to_string()))?;
+        self.write_event(Event::Text(quick_xml::events::BytesText::new(value)))?;
         self.end_element(name)
     }
 
-    fn write_attribute_str(&mut self, _name: &str, _value: &str) -> Result<(), ExportError> {
-        // This is a bit tricky with quick-xml's streaming API.
-        // We need to write attributes before the element content.
-        // For now, we'll write them as child elements (not ideal but functional).
-        // A proper implementation would buffer the element and write it all at once.
-        Ok(())
+    /// Add an attribute to the element opened last.
+    fn write_attribute_str(&mut self, name: &str, value: &str) -> Result<(), ExportError> {
+        match &mut self.pending {
+            Some(start) => {
+                start.push_attribute((name, value));
+                Ok(())
+            }
+            None => Err(ExportError::Xml(format!(
+                "attribute {} written after element content",
+                name
+            ))),
+        }
     }
 }
 
 /// Export errors.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExportError {
     /// XML writing error.
     Xml(String),
@@ -598,6 +860,8 @@ pub enum ExportError {
     Io(String),
     /// Read error.
     Read(String),
+    /// Error of a non-XML format, see [`Exporter`].
+    Format(String),
 }
 
 impl std::fmt::Display for ExportError {
@@ -607,6 +871,7 @@ impl std::fmt::Display for ExportError {
             Self::Utf8(e) => write!(f, "UTF-8 error: {}", e),
             Self::Io(e) => write!(f, "IO error: {}", e),
             Self::Read(e) => write!(f, "Read error: {}", e),
+            Self::Format(e) => write!(f, "Format error: {}", e),
         }
     }
 }
@@ -614,7 +879,7 @@ impl std::fmt::Display for ExportError {
 impl std::error::Error for ExportError {}
 
 /// Encode a string as a valid XML element name.
-fn encode_xml_name(name: &str) -> String {
+pub(crate) fn encode_xml_name(name: &str) -> String {
     // Replace invalid characters with underscores
     let mut result = String::with_capacity(name.len());
 
@@ -646,6 +911,37 @@ fn encode_xml_name(name: &str) -> String {
     result
 }
 
+/// Copy of a start tag with its attributes sorted by name.
+fn sort_attributes(start: &BytesStart<'_>) -> Result<BytesStart<'static>, ExportError> {
+    let mut attributes = start
+        .attributes()
+        .collect::<Result<Vec<Attribute<'_>>, _>>()
+        .map_err(|e| ExportError::Xml(e.to_string()))?;
+    attributes.sort_by(|a, b| a.key.as_ref().cmp(b.key.as_ref()));
+
+    let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+    let mut sorted = BytesStart::new(name);
+    sorted.extend_attributes(attributes);
+    Ok(sorted)
+}
+
+/// Shortest round-trip form of a float, e.g. `1`, `0.25`, `1e-7`, `3.4028235e38`.
+fn canonical_float<F>(value: F) -> String
+where
+    F: std::fmt::Debug + Into<f64> + Copy,
+{
+    if value.into() == 0.0 {
+        return "0".to_string();
+    }
+    // Debug formatting is shortest round-trip and switches to exponent
+    // notation for very large and small magnitudes
+    let formatted = format!("{:?}", value);
+    match formatted.strip_suffix(".0") {
+        Some(integer) => integer.to_string(),
+        None => formatted,
+    }
+}
+
 /// Compute a relative path from context to target.
 fn compute_relative_path(target_path: &str, context_path: &str) -> String {
     let slashes = context_path.chars().filter(|&c| c == '/').count();
@@ -658,3 +954,82 @@ fn compute_relative_path(target_path: &str, context_path: &str) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+
+    #[test]
+    fn test_canonical_float() {
+        assert_eq!(canonical_float(1.0f32), "1");
+        assert_eq!(canonical_float(0.1f32), "0.1");
+        assert_eq!(canonical_float(-0.0f32), "0");
+        assert_eq!(canonical_float(1e-7f32), "1e-7");
+        assert_eq!(canonical_float(f32::MAX), "3.4028235e38");
+        assert_eq!(canonical_float(0.1f64), "0.1");
+        assert_eq!(canonical_float(1e300f64), "1e300");
+    }
+
+    #[test]
+    fn test_canonical_export() {
+        let mut builder = DataCoreBuilder::new();
+        let target = builder.add_struct("Target", None);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "speed", DataType::Single);
+        builder.add_typed_array_property(item, "targets", DataType::Reference, target);
+
+        let record = builder.add_record("Item", item, "items/item.xml");
+        let zulu = svarog_common::CigGuid::random();
+        let alpha = svarog_common::CigGuid::random();
+        builder.add_record_with_guid("Zulu", target, "targets/zulu.xml", zulu);
+        builder.add_record_with_guid("Alpha", target, "targets/alpha.xml", alpha);
+        builder.set_float(record, "speed", 2.0);
+        builder.set_reference_array(record, "targets", &[zulu, alpha]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Item").unwrap().raw();
+
+        let plain = XmlExporter::new(&db).export_record(record).unwrap();
+        assert!(plain.contains(r#"<Item RecordId="#), "{}", plain);
+        assert!(plain.find("zulu").unwrap() < plain.find("alpha").unwrap());
+
+        let canonical = XmlExporter::new(&db).canonical(true).export_record(record).unwrap();
+        assert!(canonical.contains(r#"<Item RecordId=""#), "{}", canonical);
+        assert!(canonical.contains(r#"<targets Count="2" Type="Target">"#), "{}", canonical);
+        assert!(canonical.contains("<speed>2</speed>"), "{}", canonical);
+        assert!(canonical.find("alpha").unwrap() < canonical.find("zulu").unwrap());
+    }
+
+    #[test]
+    fn test_weak_pointer_names() {
+        let mut builder = DataCoreBuilder::new();
+        let node = builder.add_struct("Node", None);
+        builder.add_typed_property(node, "sibling", DataType::WeakPointer, node);
+        builder.add_typed_property(node, "next", DataType::Reference, node);
+
+        let other_id = svarog_common::CigGuid::random();
+        let root = builder.add_record("Root", node, "libs/root.xml");
+        let other = builder.add_record_with_guid("Other", node, "libs/other.xml", other_id);
+        builder.set_weak_pointer(root, "sibling", Some(other));
+        builder.set_reference(root, "next", other_id);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let root = db.record_by_name("Root").unwrap();
+        let other = db.record_by_name("Other").unwrap();
+
+        let sibling = db.resolve(&root.get("sibling").unwrap()).unwrap();
+        let next = db.resolve(&root.get("next").unwrap()).unwrap();
+        assert_eq!(sibling.instance_index(), other.raw().instance_index as u32);
+        assert_eq!(next.instance_index(), sibling.instance_index());
+        assert!(db.resolve(&other.get("sibling").unwrap()).is_none());
+
+        let plain = XmlExporter::new(&db).export_record(root.raw()).unwrap();
+        assert!(!plain.contains("PointsToName"), "{}", plain);
+        let named = XmlExporter::new(&db)
+            .weak_pointer_names(true)
+            .export_record(root.raw())
+            .unwrap();
+        assert!(named.contains(r#"PointsTo="ptr:0" PointsToName="Other""#), "{}", named);
+    }
+}
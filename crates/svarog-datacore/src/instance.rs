@@ -34,6 +34,12 @@ impl<'a> Instance<'a> {
         }
     }
 
+    /// Get the database this instance belongs to.
+    #[inline]
+    pub(crate) fn database(&self) -> &'a DataCoreDatabase {
+        self.database
+    }
+
     /// Get the struct type index.
     #[inline]
     pub fn struct_index(&self) -> u32 {
@@ -166,6 +172,135 @@ impl<'a> Instance<'a> {
         }
     }
 
+    /// Get a value by following a path of property names.
+    ///
+    /// Segments are separated by `/` or `.` and walk into inline classes,
+    /// pointers and record references. `name[N]` selects an array element,
+    /// and a segment naming a struct type selects the first element of an
+    /// array that is of that type or derived from it.
+    ///
+    /// ```no_run
+    /// # use svarog_datacore::DataCoreDatabase;
+    /// # let db = DataCoreDatabase::open("Game.dcb")?;
+    /// # let record = db.record_by_name("AEGS_Gladius").unwrap();
+    /// let max_health = record.get_path("Components/SHealthComponentParams/Health/maxHealth");
+    /// let first_port = record.get_path("ports[0].name");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Returns `None` if any segment cannot be resolved.
+    pub fn get_path(&self, path: &str) -> Option<Value<'a>> {
+        let mut cursor = PathCursor::Struct {
+            struct_index: self.struct_index as usize,
+            reader: self
+                .database
+                .get_instance_reader(self.struct_index as usize, self.instance_index as usize),
+            value: Value::Class(self.as_ref()),
+        };
+
+        for segment in path.split(['/', '.']).filter(|s| !s.is_empty()) {
+            let (name, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+            if !name.is_empty() {
+                cursor = self.path_field(cursor, name)?;
+            }
+
+            while !indices.is_empty() {
+                let rest = indices.strip_prefix('[')?;
+                let end = rest.find(']')?;
+                let index: usize = rest[..end].trim().parse().ok()?;
+                indices = &rest[end + 1..];
+
+                let PathCursor::Value(Value::Array(array)) = cursor else {
+                    return None;
+                };
+                if index >= array.count as usize {
+                    return None;
+                }
+                cursor = PathCursor::Value(ArrayIterator::new(self.database, array).nth(index)?);
+            }
+        }
+
+        Some(match cursor {
+            PathCursor::Struct { value, .. } => value,
+            PathCursor::Value(value) => value,
+        })
+    }
+
+    /// Resolve one named path segment relative to `cursor`.
+    fn path_field(&self, cursor: PathCursor<'a>, name: &str) -> Option<PathCursor<'a>> {
+        let (struct_index, mut reader) = match cursor {
+            PathCursor::Struct { struct_index, reader, .. } => (struct_index, reader),
+            PathCursor::Value(Value::Array(array)) => {
+                // Select an element by type, e.g. a component in a component list
+                return ArrayIterator::new(self.database, array)
+                    .find(|value| {
+                        self.path_target(value)
+                            .is_some_and(|target| self.is_type(target.struct_index, name))
+                    })
+                    .map(PathCursor::Value);
+            }
+            PathCursor::Value(value) => {
+                let target = self.path_target(&value)?;
+                let reader = self
+                    .database
+                    .get_instance_reader(target.struct_index as usize, target.instance_index as usize);
+                (target.struct_index as usize, reader)
+            }
+        };
+
+        for prop in self.database.get_struct_properties(struct_index) {
+            if self.database.property_name(prop) != Some(name) {
+                self.skip_property(prop, &mut reader);
+                continue;
+            }
+
+            let value = self.read_property_value(prop, &mut reader)?;
+            if prop.conversion_type == 0 && prop.data_type == DataType::Class as u16 {
+                // Inline class data follows in the same reader
+                return Some(PathCursor::Struct {
+                    struct_index: prop.struct_index as usize,
+                    reader,
+                    value,
+                });
+            }
+            return Some(PathCursor::Value(value));
+        }
+
+        None
+    }
+
+    /// The instance a pointer, reference or class array element refers to.
+    fn path_target(&self, value: &Value<'a>) -> Option<InstanceRef> {
+        match value {
+            Value::Class(r) | Value::StrongPointer(Some(r)) | Value::WeakPointer(Some(r)) => Some(*r),
+            Value::Reference(Some(r)) => {
+                let record = self.database.get_record(&r.guid)?;
+                Some(InstanceRef::new(record.struct_index as u32, record.instance_index as u32))
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether a struct type is `name` or derives from it.
+    fn is_type(&self, struct_index: u32, name: &str) -> bool {
+        let structs = self.database.struct_definitions();
+        let mut index = struct_index as i32;
+        // Bounded by the struct count in case of a malformed cycle
+        for _ in 0..structs.len() {
+            if index < 0 {
+                break;
+            }
+            if self.database.struct_name(index as usize) == Some(name) {
+                return true;
+            }
+            index = match structs.get(index as usize) {
+                Some(def) => def.parent_type_index,
+                None => break,
+            };
+        }
+        false
+    }
+
     fn read_property_value(
         &self,
         prop: &DataCorePropertyDefinition,
@@ -288,6 +423,18 @@ impl<'a> Instance<'a> {
     }
 }
 
+/// Position reached while resolving a path in [`Instance::get_path`].
+enum PathCursor<'a> {
+    /// Inside a struct whose data starts at the reader position.
+    Struct {
+        struct_index: usize,
+        reader: BinaryReader<'a>,
+        value: Value<'a>,
+    },
+    /// A property value or array element.
+    Value(Value<'a>),
+}
+
 impl std::fmt::Debug for Instance<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Instance")
@@ -314,6 +461,13 @@ impl<'a> Record<'a> {
         Self { database, record }
     }
 
+    /// Get the database this record belongs to.
+    #[cfg(feature = "models")]
+    #[inline]
+    pub(crate) fn database(&self) -> &'a DataCoreDatabase {
+        self.database
+    }
+
     /// Get the record's unique identifier.
     #[inline]
     pub fn id(&self) -> CigGuid {
@@ -353,7 +507,7 @@ impl<'a> Record<'a> {
     /// Check if this is a main record (one per file).
     #[inline]
     pub fn is_main(&self) -> bool {
-        self.database.is_main_record(&self.record.id)
+        self.database.is_main_record(self.record)
     }
 
     /// Get this record as an instance for property access.
@@ -443,6 +597,14 @@ impl<'a> Record<'a> {
     pub fn get_array(&self, name: &str) -> Option<ArrayIterator<'a>> {
         self.as_instance().get_array(name)
     }
+
+    /// Get a value by following a path of property names.
+    ///
+    /// See [`Instance::get_path`] for the path syntax.
+    #[inline]
+    pub fn get_path(&self, path: &str) -> Option<Value<'a>> {
+        self.as_instance().get_path(path)
+    }
 }
 
 impl std::fmt::Debug for Record<'_> {
@@ -529,7 +691,7 @@ pub struct ArrayIterator<'a> {
 }
 
 impl<'a> ArrayIterator<'a> {
-    fn new(database: &'a DataCoreDatabase, array: ArrayRef) -> Self {
+    pub(crate) fn new(database: &'a DataCoreDatabase, array: ArrayRef) -> Self {
         Self {
             database,
             array,
@@ -658,7 +820,7 @@ impl ExactSizeIterator for ArrayIterator<'_> {}
 
 // Helper functions
 
-fn read_single_value<'a>(
+pub(crate) fn read_single_value<'a>(
     database: &'a DataCoreDatabase,
     data_type: DataType,
     struct_index: u32,
@@ -727,7 +889,7 @@ fn read_single_value<'a>(
     })
 }
 
-fn data_type_to_array_element(data_type: DataType) -> ArrayElementType {
+pub(crate) fn data_type_to_array_element(data_type: DataType) -> ArrayElementType {
     match data_type {
         DataType::Boolean => ArrayElementType::Bool,
         DataType::SByte => ArrayElementType::Int8,
@@ -750,3 +912,57 @@ fn data_type_to_array_element(data_type: DataType) -> ArrayElementType {
         DataType::Reference => ArrayElementType::Reference,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::DataCoreBuilder;
+    use crate::{DataCoreDatabase, DataType, Value};
+
+    #[test]
+    fn test_get_path() {
+        let mut builder = DataCoreBuilder::new();
+        let health = builder.add_struct("Health", None);
+        builder.add_property(health, "maxHealth", DataType::Single);
+        let component = builder.add_struct("EntityComponentParams", None);
+        let health_params = builder.add_struct("SHealthComponentParams", Some(component));
+        builder.add_typed_property(health_params, "Health", DataType::Class, health);
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "name", DataType::String);
+        let entity = builder.add_struct("EntityClassDefinition", None);
+        builder.add_property(entity, "tag", DataType::Int32);
+        builder.add_typed_array_property(entity, "Components", DataType::StrongPointer, component);
+        builder.add_typed_array_property(entity, "items", DataType::Class, item);
+
+        let record = builder.add_record("Ship", entity, "entities/ship.xml");
+        builder.set_i32(record, "tag", 7);
+        let base = builder.add_instance(component);
+        let params = builder.add_instance(health_params);
+        let max_health = builder.add_instance(health);
+        builder.set_float(max_health, "maxHealth", 1500.0);
+        builder.set_class(params, "Health", max_health).unwrap();
+        builder.set_strong_pointer_array(record, "Components", &[base, params]);
+        let items: Vec<_> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| {
+                let item = builder.add_instance(item);
+                builder.set_string(item, "name", name);
+                item
+            })
+            .collect();
+        builder.set_class_array(record, "items", &items).unwrap();
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let record = db.record_by_name("Ship").unwrap();
+
+        assert_eq!(
+            record.get_path("Components/SHealthComponentParams/Health/maxHealth"),
+            Some(Value::Float(1500.0))
+        );
+        assert_eq!(record.get_path("Components[1].Health.maxHealth"), Some(Value::Float(1500.0)));
+        assert_eq!(record.get_path("items[3].name"), Some(Value::String("d")));
+        assert_eq!(record.get_path("tag"), Some(Value::Int32(7)));
+        assert_eq!(record.get_path("items[4].name"), None);
+        assert_eq!(record.get_path("Components/Missing/Health"), None);
+        assert_eq!(record.get_path("tag/value"), None);
+    }
+}
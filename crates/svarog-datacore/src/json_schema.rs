@@ -0,0 +1,207 @@
+//! JSON Schema generation for DataCore schema.
+//!
+//! This module emits one JSON Schema (draft 2020-12) document per struct
+//! definition. Documents refer to each other by file name (`<Struct>.schema.json`),
+//! so a directory produced by [`JsonSchemaExporter::export_all`] is
+//! self-contained.
+//!
+//! Inheritance is expressed with `allOf` pointing at the parent schema, record
+//! references are GUID strings and enums are inlined as string enumerations.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, JsonSchemaExporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let exporter = JsonSchemaExporter::new(&db);
+//!
+//! // Write one schema file per struct
+//! exporter.export_all("schemas")?;
+//!
+//! // Or build a single document in memory
+//! let schema = exporter.schema_for(0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use crate::structs::DataCorePropertyDefinition;
+use crate::{DataCoreDatabase, DataType, Result};
+
+/// JSON Schema dialect used for generated documents.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Exporter for generating JSON Schema documents from DataCore schema.
+pub struct JsonSchemaExporter<'a> {
+    db: &'a DataCoreDatabase,
+}
+
+impl<'a> JsonSchemaExporter<'a> {
+    /// Create a new JSON Schema exporter.
+    pub fn new(db: &'a DataCoreDatabase) -> Self {
+        Self { db }
+    }
+
+    /// File name of the schema document for a struct, e.g. `Item.schema.json`.
+    pub fn file_name(&self, struct_index: usize) -> String {
+        let name = self.db.struct_name(struct_index).unwrap_or("Unknown");
+        let name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.schema.json", name)
+    }
+
+    /// Build the schema document for a single struct.
+    pub fn schema_for(&self, struct_index: usize) -> Value {
+        let Some(def) = self.db.struct_definitions().get(struct_index) else {
+            return Value::Null;
+        };
+        let name = self.db.struct_name(struct_index).unwrap_or("Unknown");
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+
+        let start = def.first_attribute_index as usize;
+        let end = start + def.attribute_count as usize;
+        for prop in self
+            .db
+            .property_definitions()
+            .get(start..end)
+            .unwrap_or(&[])
+        {
+            let prop_name = self.db.property_name(prop).unwrap_or("unknown");
+            properties.insert(prop_name.to_string(), self.property_schema(prop));
+            required.push(Value::from(prop_name));
+        }
+
+        let mut schema = Map::new();
+        schema.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+        schema.insert("$id".into(), self.file_name(struct_index).into());
+        schema.insert("title".into(), name.into());
+        schema.insert("type".into(), "object".into());
+        if def.parent_type_index >= 0 {
+            let parent = self.file_name(def.parent_type_index as usize);
+            schema.insert("allOf".into(), json!([{ "$ref": parent }]));
+        }
+        schema.insert("properties".into(), Value::Object(properties));
+        schema.insert("required".into(), Value::Array(required));
+
+        Value::Object(schema)
+    }
+
+    /// Write a schema document for every struct into a directory.
+    ///
+    /// Returns the number of documents written.
+    pub fn export_all<P: AsRef<Path>>(&self, output_dir: P) -> Result<usize> {
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir)?;
+
+        let count = self.db.struct_definitions().len();
+        for idx in 0..count {
+            let schema = serde_json::to_string_pretty(&self.schema_for(idx))
+                .map_err(|e| crate::Error::Export(e.to_string()))?;
+            std::fs::write(output_dir.join(self.file_name(idx)), schema)?;
+        }
+
+        Ok(count)
+    }
+
+    /// Build the schema for a property, wrapping arrays.
+    fn property_schema(&self, prop: &DataCorePropertyDefinition) -> Value {
+        let element = self.element_schema(prop);
+        if prop.is_array() {
+            json!({ "type": "array", "items": element })
+        } else {
+            element
+        }
+    }
+
+    fn element_schema(&self, prop: &DataCorePropertyDefinition) -> Value {
+        let struct_idx = prop.struct_index as usize;
+        let Some(dt) = DataType::from_u16(prop.data_type) else {
+            return json!({});
+        };
+
+        match dt {
+            DataType::Boolean => json!({ "type": "boolean" }),
+            DataType::SByte => integer(i8::MIN as i64, i8::MAX as i64),
+            DataType::Int16 => integer(i16::MIN as i64, i16::MAX as i64),
+            DataType::Int32 => integer(i32::MIN as i64, i32::MAX as i64),
+            DataType::Byte => integer(0, u8::MAX as i64),
+            DataType::UInt16 => integer(0, u16::MAX as i64),
+            DataType::UInt32 => integer(0, u32::MAX as i64),
+            DataType::Int64 => json!({ "type": "integer" }),
+            DataType::UInt64 => json!({ "type": "integer", "minimum": 0 }),
+            DataType::Single | DataType::Double => json!({ "type": "number" }),
+            DataType::String | DataType::Locale => json!({ "type": "string" }),
+            DataType::Guid => json!({ "type": "string", "format": "uuid" }),
+            DataType::EnumChoice => match self.db.enum_definitions().get(struct_idx) {
+                Some(def) => json!({ "type": "string", "enum": self.db.enum_options(def) }),
+                None => json!({ "type": "string" }),
+            },
+            DataType::Class => json!({ "$ref": self.file_name(struct_idx) }),
+            DataType::StrongPointer => json!({
+                "oneOf": [{ "$ref": self.file_name(struct_idx) }, { "type": "null" }]
+            }),
+            DataType::WeakPointer => json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "struct_index": { "type": "integer", "minimum": 0 },
+                            "instance_index": { "type": "integer", "minimum": 0 }
+                        },
+                        "required": ["struct_index", "instance_index"]
+                    },
+                    { "type": "null" }
+                ]
+            }),
+            DataType::Reference => json!({ "type": ["string", "null"], "format": "uuid" }),
+        }
+    }
+}
+
+fn integer(minimum: i64, maximum: i64) -> Value {
+    json!({ "type": "integer", "minimum": minimum, "maximum": maximum })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    #[test]
+    fn test_schema_for_struct() {
+        let mut builder = DataCoreBuilder::new();
+        let base = builder.add_struct("Base", None);
+        builder.add_property(base, "name", DataType::String);
+        let item = builder.add_struct("Item", Some(base));
+        builder.add_property(item, "count", DataType::Byte);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder.add_array_property(item, "weights", DataType::Single);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let schema = JsonSchemaExporter::new(&db).schema_for(1);
+
+        assert_eq!(schema["$id"], "Item.schema.json");
+        assert_eq!(schema["allOf"][0]["$ref"], "Base.schema.json");
+        assert_eq!(schema["properties"]["count"]["maximum"], 255);
+        assert_eq!(
+            schema["properties"]["parent"]["type"],
+            json!(["string", "null"])
+        );
+        assert_eq!(schema["properties"]["weights"]["items"]["type"], "number");
+        assert_eq!(schema["required"], json!(["count", "parent", "weights"]));
+        assert!(schema["properties"].get("name").is_none());
+    }
+}
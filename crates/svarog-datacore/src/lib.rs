@@ -33,6 +33,7 @@
 //! - **Instances** (`Instance`): Views into struct data with property access
 //! - **Values** (`Value`): Type-safe property values
 //! - **Query** (`Query`): Fluent query builder for finding records
+//! - **Search** (`DataCoreDatabase::search`, `SearchIndex`): Substring search over names, types, files and values
 //!
 //! # Property Access
 //!
@@ -49,6 +50,9 @@
 //! let damage: Option<f32> = record.get_f32("baseDamage");
 //! let enabled: Option<bool> = record.get_bool("enabled");
 //!
+//! // Nested values through classes, pointers and arrays in one call
+//! let max_health = record.get_path("Components/SHealthComponentParams/Health/maxHealth");
+//!
 //! // Generic accessor returns Value enum
 //! if let Some(value) = record.get("someProperty") {
 //!     println!("{}", value); // Value implements Display
@@ -163,29 +167,74 @@
 
 mod builder;
 mod c_header;
+mod codegen;
+mod content_hash;
 mod database;
+#[cfg(feature = "serde")]
+mod de;
+mod diff;
+mod enums;
 mod error;
 mod instance;
+#[cfg(feature = "json-export")]
+mod json_schema;
+mod locale;
+mod matching;
+mod patch;
 mod query;
+mod search;
+mod stats;
+mod table;
 mod types;
+mod typescript;
 mod value;
 
 pub mod export;
+#[cfg(feature = "models")]
+pub mod models;
 pub mod structs;
 
 // Primary API
-pub use database::{DataCoreDatabase, PoolCounts, PoolType};
+pub use content_hash::ContentHasher;
+pub use database::{DataCoreDatabase, PoolCounts, PoolType, StructField};
+pub use diff::{
+    DataCoreDiff, EnumChange, PropertyChange, PropertyRetype, PropertySchema, RecordChange,
+    RecordSummary, SchemaDiff, StructChange,
+};
+pub use enums::Enum;
 pub use error::{Error, Result};
 pub use instance::{ArrayIterator, Instance, Property, PropertyIterator, Record};
+pub use locale::{LocaleResolver, Localized};
+pub use matching::{MatchKind, MatchOptions, RecordMatch, RecordMatching};
+pub use patch::{DataCorePatcher, PatchValue};
 pub use query::{Query, QueryIterator};
+pub use search::{SearchField, SearchHit, SearchIndex, SearchOptions};
+pub use stats::{ArrayStatistics, DatabaseStatistics, PoolStatistics, StringTableStatistics};
+pub use table::{Table, TableColumn, TableExporter};
 pub use value::{ArrayElementType, ArrayRef, InstanceRef, RecordRef, Value};
 
 // Builder API
-pub use builder::{DataCoreBuilder, EnumHandle, RecordHandle, StructHandle};
+#[cfg(feature = "xml-export")]
+pub use builder::XmlImporter;
+pub use builder::{
+    BuilderTarget, CompactStats, DataCoreBuilder, EnumHandle, InstanceHandle, MergeStats,
+    MergeStrategy, RecordHandle, RecordKey, StructHandle, ValidationIssue,
+};
 
 // Export types
-pub use c_header::{CHeaderExporter, C_HEADER_PREAMBLE};
-pub use export::{RecordWalker, XmlExporter};
+pub use c_header::{CHeaderExporter, CHeaderInheritance, C_HEADER_PREAMBLE};
+pub use codegen::{RustCodegen, RUST_PREAMBLE};
+#[cfg(feature = "json-export")]
+pub use export::{CommandExporter, JsonExporter};
+pub use export::{
+    ExportFailure, ExportJob, ExportManifest, ExportProgress, ExportStats, Exporter,
+    ExporterRegistry, IncrementalStats, RecordDependencies, RecordWalker, XmlExporter,
+};
+#[cfg(feature = "zip")]
+pub use export::{ZipCompression, ZipExportOptions};
+#[cfg(feature = "json-export")]
+pub use json_schema::{JsonSchemaExporter, JSON_SCHEMA_DIALECT};
+pub use typescript::{TypeScriptExporter, TS_PREAMBLE};
 
 // Low-level types
 pub use types::DataType;
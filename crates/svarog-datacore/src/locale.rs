@@ -0,0 +1,236 @@
+//! Localization support for DataCore `Locale` values.
+//!
+//! Locale properties only store a key such as `@item_NameGladius`. The actual
+//! text lives in `Data/Localization/<language>/global.ini` inside the P4K,
+//! one `key=value` pair per line. [`LocaleResolver`] loads such a file and
+//! maps keys to display strings.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, LocaleResolver, XmlExporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let locale = LocaleResolver::open("Data/Localization/english/global.ini")?;
+//!
+//! println!("{}", locale.resolve("@item_NameGladius"));
+//!
+//! let exporter = XmlExporter::new(&db).with_locale(&locale);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+
+use crate::table::write_csv_row;
+use crate::{Result, Value};
+
+/// Maps localization keys to strings loaded from a `global.ini` file.
+///
+/// Keys are matched case-insensitively, with or without the leading `@` used
+/// by DataCore.
+#[derive(Debug, Clone, Default)]
+pub struct LocaleResolver {
+    /// Keys as written in the file and their text, in file order.
+    entries: Vec<(String, String)>,
+    /// Lowercase key to index in `entries`.
+    index: HashMap<String, usize>,
+}
+
+impl LocaleResolver {
+    /// Load a `global.ini` file from disk.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_bytes(&std::fs::read(path)?))
+    }
+
+    /// Parse `global.ini` contents from raw bytes.
+    ///
+    /// A UTF-8 byte order mark is skipped and invalid UTF-8 is replaced.
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        Self::parse(&String::from_utf8_lossy(data))
+    }
+
+    /// Parse `global.ini` contents.
+    ///
+    /// Lines without `=` and comment lines starting with `;` or `#` are
+    /// ignored. Key suffixes such as `,P` are dropped.
+    pub fn parse(text: &str) -> Self {
+        let mut resolver = Self::default();
+
+        for line in text.lines() {
+            let line = line.trim_start_matches('\u{feff}');
+            if line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.split(',').next().unwrap_or(key).trim();
+            if key.is_empty() {
+                continue;
+            }
+            // A repeated key replaces the earlier text in place
+            let entry = (key.to_string(), value.to_string());
+            match resolver.index.get(&key.to_ascii_lowercase()) {
+                Some(&i) => resolver.entries[i] = entry,
+                None => {
+                    resolver
+                        .index
+                        .insert(key.to_ascii_lowercase(), resolver.entries.len());
+                    resolver.entries.push(entry);
+                }
+            }
+        }
+
+        resolver
+    }
+
+    /// Look up a localization key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = key.strip_prefix('@').unwrap_or(key);
+        self.index
+            .get(&key.to_ascii_lowercase())
+            .map(|&i| self.entries[i].1.as_str())
+    }
+
+    /// Resolve a localization key, falling back to the key itself.
+    pub fn resolve<'s>(&'s self, key: &'s str) -> &'s str {
+        self.get(key).unwrap_or(key)
+    }
+
+    /// Wrap a value so that `Locale` values display their resolved text.
+    ///
+    /// All other values display exactly as they would on their own.
+    pub fn display<'s>(&'s self, value: &'s Value<'s>) -> Localized<'s> {
+        Localized {
+            resolver: self,
+            value,
+        }
+    }
+
+    /// Number of loaded entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no entries were loaded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Keys and their text, in file order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Entries whose key or text contains `text`, ignoring case.
+    pub fn grep<'s>(&'s self, text: &str) -> impl Iterator<Item = (&'s str, &'s str)> {
+        let needle = text.to_lowercase();
+        self.iter().filter(move |(key, value)| {
+            key.to_lowercase().contains(&needle) || value.to_lowercase().contains(&needle)
+        })
+    }
+
+    /// Write all entries as CSV with a `key,value` header row.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_csv_row(writer, ["key", "value"].into_iter())?;
+        for (key, value) in self.iter() {
+            write_csv_row(writer, [key, value].into_iter())?;
+        }
+        Ok(())
+    }
+
+    /// Serialize all entries as a pretty-printed JSON object, in file order.
+    #[cfg(feature = "json-export")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        struct Entries<'a>(&'a LocaleResolver);
+
+        impl serde::Serialize for Entries<'_> {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter())
+            }
+        }
+
+        serde_json::to_string_pretty(&Entries(self))
+    }
+}
+
+/// A [`Value`] displayed through a [`LocaleResolver`].
+pub struct Localized<'a> {
+    resolver: &'a LocaleResolver,
+    value: &'a Value<'a>,
+}
+
+impl fmt::Display for Localized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Value::Locale(key) => f.write_str(self.resolver.resolve(key)),
+            value => value.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_global_ini() {
+        let data = b"\xEF\xBB\xBFitem_NameGladius=Gladius\r\nitem_DescGladius,P=Light fighter\\nAegis\n; comment\nbroken line\n";
+        let locale = LocaleResolver::from_bytes(data);
+
+        assert_eq!(locale.len(), 2);
+        assert_eq!(locale.get("@item_NameGladius"), Some("Gladius"));
+        assert_eq!(
+            locale.get("ITEM_DESCGLADIUS"),
+            Some("Light fighter\\nAegis")
+        );
+        assert_eq!(locale.resolve("@missing_key"), "@missing_key");
+
+        let keys: Vec<_> = locale.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["item_NameGladius", "item_DescGladius"]);
+        assert_eq!(locale.grep("aegis").count(), 1);
+        assert_eq!(locale.grep("GLADIUS").count(), 2);
+
+        let mut csv = Vec::new();
+        locale.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "key,value\r\nitem_NameGladius,Gladius\r\nitem_DescGladius,Light fighter\\nAegis\r\n"
+        );
+
+        let value = Value::Locale("@item_NameGladius");
+        assert_eq!(locale.display(&value).to_string(), "Gladius");
+        assert_eq!(locale.display(&Value::Int32(3)).to_string(), "3");
+    }
+
+    #[test]
+    fn test_repeated_keys() {
+        let locale = LocaleResolver::parse("b=1\na=2\nB=3\n");
+
+        assert_eq!(locale.len(), 2);
+        assert_eq!(locale.get("b"), Some("3"));
+        let entries: Vec<_> = locale.iter().collect();
+        assert_eq!(entries, [("B", "3"), ("a", "2")]);
+        assert_eq!(locale.grep("4").count(), 0);
+    }
+
+    #[cfg(feature = "json-export")]
+    #[test]
+    fn test_to_json() {
+        let locale = LocaleResolver::parse("zeta=Last\nalpha=\"Quoted\"\n");
+        let json = locale.to_json().unwrap();
+
+        // File order, not sorted
+        assert!(json.find("zeta").unwrap() < json.find("alpha").unwrap());
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["alpha"], "\"Quoted\"");
+        assert_eq!(LocaleResolver::default().to_json().unwrap(), "{}");
+    }
+}
@@ -0,0 +1,401 @@
+//! Record identity matching between two DataCore databases.
+//!
+//! Records usually keep their GUID across patches, but occasionally a record
+//! is recreated with a new GUID under the same name, or renamed and moved
+//! while keeping its GUID. [`RecordMatching`] pairs the records of two
+//! databases in three passes:
+//!
+//! 1. by GUID,
+//! 2. by struct type and record name, where the name is unique among the
+//!    records still unmatched on both sides,
+//! 3. by structural similarity: the share of identical property leaves
+//!    between records of the same struct type.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, RecordMatching};
+//!
+//! let old = DataCoreDatabase::open("Game_old.dcb")?;
+//! let new = DataCoreDatabase::open("Game_new.dcb")?;
+//!
+//! let matching = RecordMatching::compute(&old, &new);
+//! for m in matching.reidentified() {
+//!     println!("{} -> {} ({:?})", m.old_id, m.new_id, m.kind);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+use hashbrown::HashMap as FastHashMap;
+use rustc_hash::FxHasher;
+use svarog_common::CigGuid;
+
+use crate::diff::flatten_record;
+use crate::structs::DataCoreRecord;
+use crate::DataCoreDatabase;
+
+type FxHashMap<K, V> = FastHashMap<K, V, BuildHasherDefault<FxHasher>>;
+
+/// How a pair of records was matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub enum MatchKind {
+    /// Both records have the same GUID.
+    Guid,
+    /// Same struct type and record name.
+    Name,
+    /// Same struct type and similar contents.
+    Structure,
+}
+
+/// A record in the old database paired with a record in the new one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct RecordMatch {
+    /// The record GUID in the old database.
+    pub old_id: CigGuid,
+    /// The record GUID in the new database.
+    pub new_id: CigGuid,
+    /// Which pass matched the records.
+    pub kind: MatchKind,
+    /// Share of identical property leaves, for structural matches.
+    pub similarity: Option<f32>,
+}
+
+/// Options for [`RecordMatching::with_options`].
+#[derive(Debug, Clone, Copy)]
+pub struct MatchOptions {
+    /// Match remaining records by name.
+    pub by_name: bool,
+    /// Match remaining records by structural similarity.
+    pub by_structure: bool,
+    /// Minimum similarity (0.0 to 1.0) for a structural match.
+    pub min_similarity: f32,
+    /// Skip the structural pass for a struct type when its unmatched old and
+    /// new records would form more candidate pairs than this.
+    pub max_structure_pairs: usize,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            by_name: true,
+            by_structure: true,
+            min_similarity: 0.8,
+            max_structure_pairs: 1_000_000,
+        }
+    }
+}
+
+/// A mapping between the records of two databases.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct RecordMatching {
+    /// Matched record pairs, in new database record order.
+    pub matches: Vec<RecordMatch>,
+    /// Old records without a counterpart, in old database record order.
+    pub unmatched_old: Vec<CigGuid>,
+    /// New records without a counterpart, in new database record order.
+    pub unmatched_new: Vec<CigGuid>,
+    #[cfg_attr(feature = "json-export", serde(skip))]
+    by_old: FxHashMap<CigGuid, usize>,
+    #[cfg_attr(feature = "json-export", serde(skip))]
+    by_new: FxHashMap<CigGuid, usize>,
+}
+
+impl RecordMatching {
+    /// Match records using the default options.
+    pub fn compute(old: &DataCoreDatabase, new: &DataCoreDatabase) -> Self {
+        Self::with_options(old, new, &MatchOptions::default())
+    }
+
+    /// Match records using the given options.
+    pub fn with_options(
+        old: &DataCoreDatabase,
+        new: &DataCoreDatabase,
+        options: &MatchOptions,
+    ) -> Self {
+        let old_records = old.records();
+        let new_records = new.records();
+        // Old record index matched to each new record
+        let mut pairs: Vec<Option<(usize, MatchKind, Option<f32>)>> = vec![None; new_records.len()];
+        let mut old_matched = vec![false; old_records.len()];
+
+        for (new_index, record) in new_records.iter().enumerate() {
+            if let Some(old_index) = old.record_index(&record.id) {
+                pairs[new_index] = Some((old_index, MatchKind::Guid, None));
+                old_matched[old_index] = true;
+            }
+        }
+
+        if options.by_name {
+            let old_names = unmatched_by_name(old, old_records, |i| !old_matched[i]);
+            let new_names = unmatched_by_name(new, new_records, |i| pairs[i].is_none());
+            for (key, new_indices) in &new_names {
+                if let (Some([old_index]), [new_index]) = (
+                    old_names.get(key).map(Vec::as_slice),
+                    new_indices.as_slice(),
+                ) {
+                    pairs[*new_index] = Some((*old_index, MatchKind::Name, None));
+                    old_matched[*old_index] = true;
+                }
+            }
+        }
+
+        if options.by_structure {
+            for (old_index, new_index, similarity) in
+                match_by_structure(old, new, &old_matched, &pairs, options)
+            {
+                pairs[new_index] = Some((old_index, MatchKind::Structure, Some(similarity)));
+                old_matched[old_index] = true;
+            }
+        }
+
+        let mut matching = Self::default();
+        for (new_index, pair) in pairs.into_iter().enumerate() {
+            let new_id = new_records[new_index].id;
+            match pair {
+                Some((old_index, kind, similarity)) => {
+                    let old_id = old_records[old_index].id;
+                    matching.by_old.insert(old_id, matching.matches.len());
+                    matching.by_new.insert(new_id, matching.matches.len());
+                    matching.matches.push(RecordMatch {
+                        old_id,
+                        new_id,
+                        kind,
+                        similarity,
+                    });
+                }
+                None => matching.unmatched_new.push(new_id),
+            }
+        }
+        matching.unmatched_old = old_records
+            .iter()
+            .zip(&old_matched)
+            .filter(|(_, &matched)| !matched)
+            .map(|(record, _)| record.id)
+            .collect();
+
+        matching
+    }
+
+    /// The match for a record GUID from the old database.
+    pub fn for_old(&self, old_id: &CigGuid) -> Option<&RecordMatch> {
+        self.by_old.get(old_id).map(|&i| &self.matches[i])
+    }
+
+    /// The match for a record GUID from the new database.
+    pub fn for_new(&self, new_id: &CigGuid) -> Option<&RecordMatch> {
+        self.by_new.get(new_id).map(|&i| &self.matches[i])
+    }
+
+    /// Matches where the record GUID changed.
+    pub fn reidentified(&self) -> impl Iterator<Item = &RecordMatch> {
+        self.matches.iter().filter(|m| m.kind != MatchKind::Guid)
+    }
+}
+
+/// Group the records accepted by `unmatched` by struct type and name.
+fn unmatched_by_name<'a>(
+    db: &'a DataCoreDatabase,
+    records: &[DataCoreRecord],
+    unmatched: impl Fn(usize) -> bool,
+) -> FxHashMap<(&'a str, &'a str), Vec<usize>> {
+    let mut groups: FxHashMap<_, Vec<usize>> = FxHashMap::default();
+    for (i, record) in records.iter().enumerate() {
+        if !unmatched(i) {
+            continue;
+        }
+        let type_name = db.struct_name(record.struct_index as usize).unwrap_or("");
+        let name = db.record_name(record).unwrap_or("");
+        groups.entry((type_name, name)).or_default().push(i);
+    }
+    groups
+}
+
+/// Greedily pair unmatched records of the same struct type by similarity.
+///
+/// Returns `(old index, new index, similarity)` triples.
+fn match_by_structure(
+    old: &DataCoreDatabase,
+    new: &DataCoreDatabase,
+    old_matched: &[bool],
+    pairs: &[Option<(usize, MatchKind, Option<f32>)>],
+    options: &MatchOptions,
+) -> Vec<(usize, usize, f32)> {
+    let old_groups = unmatched_by_type(old, old.records(), |i| !old_matched[i]);
+    let new_groups = unmatched_by_type(new, new.records(), |i| pairs[i].is_none());
+    let mut result = Vec::new();
+
+    for (type_name, new_indices) in &new_groups {
+        let Some(old_indices) = old_groups.get(type_name) else {
+            continue;
+        };
+        if old_indices.len().saturating_mul(new_indices.len()) > options.max_structure_pairs {
+            continue;
+        }
+
+        let old_leaves: Vec<_> = old_indices
+            .iter()
+            .map(|&i| leaf_hashes(old, &old.records()[i]))
+            .collect();
+        let new_leaves: Vec<_> = new_indices
+            .iter()
+            .map(|&i| leaf_hashes(new, &new.records()[i]))
+            .collect();
+
+        let mut candidates = Vec::new();
+        for (o, old_hashes) in old_leaves.iter().enumerate() {
+            for (n, new_hashes) in new_leaves.iter().enumerate() {
+                let similarity = similarity(old_hashes, new_hashes);
+                if similarity >= options.min_similarity {
+                    candidates.push((similarity, o, n));
+                }
+            }
+        }
+        // Best pairs first, ties in record order
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+        let mut old_taken = vec![false; old_indices.len()];
+        let mut new_taken = vec![false; new_indices.len()];
+        for (similarity, o, n) in candidates {
+            if old_taken[o] || new_taken[n] {
+                continue;
+            }
+            old_taken[o] = true;
+            new_taken[n] = true;
+            result.push((old_indices[o], new_indices[n], similarity));
+        }
+    }
+
+    result
+}
+
+fn unmatched_by_type<'a>(
+    db: &'a DataCoreDatabase,
+    records: &[DataCoreRecord],
+    unmatched: impl Fn(usize) -> bool,
+) -> FxHashMap<&'a str, Vec<usize>> {
+    let mut groups: FxHashMap<_, Vec<usize>> = FxHashMap::default();
+    for (i, record) in records.iter().enumerate() {
+        if unmatched(i) {
+            let type_name = db.struct_name(record.struct_index as usize).unwrap_or("");
+            groups.entry(type_name).or_default().push(i);
+        }
+    }
+    groups
+}
+
+/// Sorted, deduplicated hashes of a record's `path = value` leaves, without
+/// the record metadata.
+fn leaf_hashes(db: &DataCoreDatabase, record: &DataCoreRecord) -> Vec<u64> {
+    let mut hashes: Vec<u64> = flatten_record(db, record)
+        .iter()
+        .filter(|(path, _)| !path.starts_with('@'))
+        .map(|leaf| {
+            let mut hasher = FxHasher::default();
+            leaf.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+    hashes
+}
+
+/// Jaccard similarity of two sorted hash sets.
+fn similarity(a: &[u64], b: &[u64]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    shared as f32 / (a.len() + b.len() - shared) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCoreBuilder, DataType};
+
+    fn build(records: &[(u8, &str, f32, i32)]) -> DataCoreDatabase {
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("Weapon", None);
+        builder.add_property(weapon, "damage", DataType::Single);
+        builder.add_property(weapon, "ammo", DataType::Int32);
+        builder.add_property(weapon, "size", DataType::Int32);
+        builder.add_property(weapon, "grade", DataType::Int32);
+        builder.add_property(weapon, "mass", DataType::Int32);
+
+        for &(id, name, damage, ammo) in records {
+            let guid = CigGuid::from_bytes([id; 16]);
+            let file = format!("weapons/{}.xml", name.to_lowercase());
+            let record = builder.add_record_with_guid(name, weapon, &file, guid);
+            builder.set_float(record, "damage", damage);
+            builder.set_i32(record, "ammo", ammo);
+            builder.set_i32(record, "size", 3);
+            builder.set_i32(record, "grade", 1);
+            builder.set_i32(record, "mass", 40);
+        }
+
+        DataCoreDatabase::parse(&builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_match_passes() {
+        let old = build(&[
+            (1, "Rifle", 10.0, 30),
+            (2, "Pistol", 5.0, 12),
+            (3, "Cannon", 90.0, 4),
+            (4, "Flare", 0.0, 1),
+        ]);
+        let new = build(&[
+            (1, "RifleMk2", 10.0, 30),
+            (5, "Pistol", 6.0, 12),
+            (6, "Railgun", 90.0, 4),
+            (7, "Mine", 300.0, 99),
+        ]);
+
+        let matching = RecordMatching::compute(&old, &new);
+        let guid = |id: u8| CigGuid::from_bytes([id; 16]);
+
+        assert_eq!(
+            matching.for_old(&guid(1)).map(|m| m.kind),
+            Some(MatchKind::Guid)
+        );
+        assert_eq!(
+            matching.for_new(&guid(5)).map(|m| (m.old_id, m.kind)),
+            Some((guid(2), MatchKind::Name))
+        );
+        let structural = matching.for_old(&guid(3)).unwrap();
+        assert_eq!(
+            (structural.new_id, structural.kind),
+            (guid(6), MatchKind::Structure)
+        );
+        assert_eq!(structural.similarity, Some(1.0));
+        assert_eq!(matching.reidentified().count(), 2);
+
+        assert_eq!(matching.unmatched_old, vec![guid(4)]);
+        assert_eq!(matching.unmatched_new, vec![guid(7)]);
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity(&[1, 2, 3], &[1, 2, 3]), 1.0);
+        assert_eq!(similarity(&[1, 2, 3], &[2, 3, 4]), 0.5);
+        assert_eq!(similarity(&[], &[5]), 0.0);
+    }
+}
@@ -0,0 +1,52 @@
+//! Mission broker entries.
+
+use super::Model;
+use crate::Record;
+
+/// A mission offered by the mission broker (`MissionBrokerEntry` records).
+#[derive(Debug, Clone, Copy)]
+pub struct Mission<'a> {
+    record: Record<'a>,
+}
+
+impl<'a> Model<'a> for Mission<'a> {
+    fn from_record(record: Record<'a>) -> Option<Self> {
+        (record.type_name() == Some("MissionBrokerEntry")).then_some(Self { record })
+    }
+
+    fn record(&self) -> Record<'a> {
+        self.record
+    }
+}
+
+impl<'a> Mission<'a> {
+    /// Localization key of the mission title.
+    pub fn title(&self) -> Option<&'a str> {
+        self.record.get_str("title")
+    }
+
+    /// Localization key of the mission description.
+    pub fn description(&self) -> Option<&'a str> {
+        self.record.get_str("description")
+    }
+
+    /// Localization key of the mission giver's name.
+    pub fn mission_giver(&self) -> Option<&'a str> {
+        self.record.get_str("missionGiver")
+    }
+
+    /// Base payout in aUEC.
+    pub fn reward(&self) -> Option<i32> {
+        self.record.get_path("missionReward/reward")?.as_i32()
+    }
+
+    /// Whether completing the mission is legal.
+    pub fn lawful(&self) -> Option<bool> {
+        self.record.get_bool("lawfulMission")
+    }
+
+    /// Maximum number of players that can share the mission.
+    pub fn max_players(&self) -> Option<i32> {
+        self.record.get_i32("maxPlayersPerInstance")
+    }
+}
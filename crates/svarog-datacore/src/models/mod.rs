@@ -0,0 +1,100 @@
+//! Typed views over common record families.
+//!
+//! Each model wraps a [`Record`] and exposes semantic getters computed from
+//! known property paths, so callers don't have to remember where e.g. a
+//! ship's top speed lives. The paths follow the current game data layout;
+//! getters return `None` when a record doesn't have the expected data, and
+//! the underlying record is always available through [`Model::record`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::models::{Model, Vehicle, Weapon};
+//! use svarog_datacore::DataCoreDatabase;
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//!
+//! for vehicle in db.models::<Vehicle>() {
+//!     println!("{:?}: {:?} m/s", vehicle.record().name(), vehicle.max_speed());
+//! }
+//! for weapon in db.models::<Weapon>() {
+//!     println!("{:?}: {:?} dps", weapon.record().name(), weapon.dps());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod mission;
+mod shop;
+mod vehicle;
+mod weapon;
+
+pub use mission::Mission;
+pub use shop::{Shop, ShopItem};
+pub use vehicle::Vehicle;
+pub use weapon::{Damage, Weapon};
+
+use crate::{DataCoreDatabase, Record};
+
+/// A typed view over a family of records.
+pub trait Model<'a>: Sized {
+    /// Wrap a record if it belongs to this model's family.
+    fn from_record(record: Record<'a>) -> Option<Self>;
+
+    /// The wrapped record.
+    fn record(&self) -> Record<'a>;
+}
+
+impl DataCoreDatabase {
+    /// Iterate over all records that match the model `M`.
+    pub fn models<'a, M: Model<'a> + 'a>(&'a self) -> impl Iterator<Item = M> + 'a {
+        self.all_records().filter_map(M::from_record)
+    }
+}
+
+/// Resolve a path to a float property.
+fn path_f32(record: &Record<'_>, path: &str) -> Option<f32> {
+    record.get_path(path)?.as_f32()
+}
+
+/// Resolve a path to a record reference and look up the target record.
+fn path_record<'a>(record: &Record<'a>, path: &str) -> Option<Record<'a>> {
+    let target = record.get_path(path)?.as_record_ref()?;
+    record.database().record(&target.guid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::DataType;
+
+    #[test]
+    fn test_models_filter_by_family() {
+        let mut builder = DataCoreBuilder::new();
+        let reward = builder.add_struct("MissionReward", None);
+        builder.add_property(reward, "reward", DataType::Int32);
+        let entry = builder.add_struct("MissionBrokerEntry", None);
+        builder.add_property(entry, "title", DataType::Locale);
+        builder.add_property(entry, "lawfulMission", DataType::Boolean);
+        builder.add_typed_property(entry, "missionReward", DataType::Class, reward);
+        let other = builder.add_struct("Tag", None);
+
+        let mission = builder.add_record("DeliverCargo", entry, "missions/deliver.xml");
+        builder.set_string(mission, "title", "@mission_Deliver");
+        builder.set_bool(mission, "lawfulMission", true);
+        let amount = builder.add_instance(reward);
+        builder.set_i32(amount, "reward", 15000);
+        builder.set_class(mission, "missionReward", amount).unwrap();
+        builder.add_record("Unrelated", other, "tags/unrelated.xml");
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let missions: Vec<Mission> = db.models().collect();
+
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].record().name(), Some("DeliverCargo"));
+        assert_eq!(missions[0].title(), Some("@mission_Deliver"));
+        assert_eq!(missions[0].reward(), Some(15000));
+        assert_eq!(missions[0].lawful(), Some(true));
+        assert_eq!(db.models::<Vehicle>().count(), 0);
+    }
+}
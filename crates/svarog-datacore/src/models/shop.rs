@@ -0,0 +1,119 @@
+//! Shops and their inventories.
+
+use svarog_common::CigGuid;
+
+use super::Model;
+use crate::{Instance, Record, Value};
+
+/// A shop layout (`ShopLayoutNode` records) listing the items it sells.
+#[derive(Debug, Clone, Copy)]
+pub struct Shop<'a> {
+    record: Record<'a>,
+}
+
+/// One entry of a shop's inventory.
+#[derive(Debug, Clone, Copy)]
+pub struct ShopItem<'a> {
+    instance: Instance<'a>,
+}
+
+impl<'a> Model<'a> for Shop<'a> {
+    fn from_record(record: Record<'a>) -> Option<Self> {
+        (record.type_name() == Some("ShopLayoutNode")).then_some(Self { record })
+    }
+
+    fn record(&self) -> Record<'a> {
+        self.record
+    }
+}
+
+impl<'a> Shop<'a> {
+    /// The shop's name.
+    pub fn name(&self) -> Option<&'a str> {
+        self.record.get_str("Name")
+    }
+
+    /// Items in the shop's inventory.
+    pub fn items(&self) -> impl Iterator<Item = ShopItem<'a>> + 'a {
+        let database = self.record.database();
+        self.record
+            .get_array("ShopInventoryNodes")
+            .into_iter()
+            .flatten()
+            .filter_map(move |value| {
+                let node = value.as_instance()?;
+                Some(ShopItem {
+                    instance: database.instance(node.struct_index, node.instance_index),
+                })
+            })
+    }
+}
+
+impl<'a> ShopItem<'a> {
+    /// GUID of the sold item's record.
+    pub fn item_id(&self) -> Option<CigGuid> {
+        match self.instance.get("InventoryID")? {
+            Value::Guid(guid) => Some(guid),
+            Value::Reference(Some(target)) => Some(target.guid),
+            _ => None,
+        }
+    }
+
+    /// The sold item's record.
+    pub fn item(&self) -> Option<Record<'a>> {
+        self.instance.database().record(&self.item_id()?)
+    }
+
+    /// Base price in aUEC, before the shop's markup.
+    pub fn base_price(&self) -> Option<f32> {
+        self.instance.get_f32("BasePrice")
+    }
+
+    /// Maximum number of units in stock.
+    pub fn max_inventory(&self) -> Option<f32> {
+        self.instance.get_f32("MaxInventory")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::{DataCoreDatabase, DataType};
+
+    #[test]
+    fn test_shop_items() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("EntityClassDefinition", None);
+        let node = builder.add_struct("ShopInventoryNode", None);
+        builder.add_property(node, "InventoryID", DataType::Guid);
+        builder.add_property(node, "BasePrice", DataType::Single);
+        let layout = builder.add_struct("ShopLayoutNode", None);
+        builder.add_property(layout, "Name", DataType::String);
+        builder.add_typed_array_property(layout, "ShopInventoryNodes", DataType::Class, node);
+
+        let rifle_guid = CigGuid::random();
+        builder.add_record_with_guid("rifle", item, "items/rifle.xml", rifle_guid);
+        let shop = builder.add_record("CenterMass", layout, "shops/center_mass.xml");
+        builder.set_string(shop, "Name", "Center Mass");
+        let rifle = builder.add_instance(node);
+        builder.set_guid(rifle, "InventoryID", rifle_guid);
+        builder.set_float(rifle, "BasePrice", 5200.0);
+        let missing = builder.add_instance(node);
+        builder.set_guid(missing, "InventoryID", CigGuid::random());
+        builder
+            .set_class_array(shop, "ShopInventoryNodes", &[rifle, missing])
+            .unwrap();
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let shops: Vec<Shop> = db.models().collect();
+
+        assert_eq!(shops.len(), 1);
+        assert_eq!(shops[0].name(), Some("Center Mass"));
+        let items: Vec<_> = shops[0].items().collect();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].item().and_then(|r| r.name()), Some("rifle"));
+        assert_eq!(items[0].base_price(), Some(5200.0));
+        assert!(items[1].item().is_none());
+    }
+}
@@ -0,0 +1,124 @@
+//! Ships and ground vehicles.
+
+use super::{path_f32, path_record, Model};
+use crate::Record;
+
+const VEHICLE_PARAMS: &str = "Components/VehicleComponentParams";
+const IFCS_PARAMS: &str = "Components/IFCSParams";
+
+/// A ship or ground vehicle: an entity with a `VehicleComponentParams` component.
+#[derive(Debug, Clone, Copy)]
+pub struct Vehicle<'a> {
+    record: Record<'a>,
+}
+
+impl<'a> Model<'a> for Vehicle<'a> {
+    fn from_record(record: Record<'a>) -> Option<Self> {
+        record.get_path(VEHICLE_PARAMS)?;
+        Some(Self { record })
+    }
+
+    fn record(&self) -> Record<'a> {
+        self.record
+    }
+}
+
+impl<'a> Vehicle<'a> {
+    fn param_str(&self, name: &str) -> Option<&'a str> {
+        self.record
+            .get_path(&format!("{VEHICLE_PARAMS}/{name}"))?
+            .as_str()
+    }
+
+    /// Localization key of the vehicle name.
+    pub fn display_name(&self) -> Option<&'a str> {
+        self.param_str("vehicleName")
+    }
+
+    /// Localization key of the vehicle description.
+    pub fn description(&self) -> Option<&'a str> {
+        self.param_str("vehicleDescription")
+    }
+
+    /// Localization key of the vehicle career, e.g. combat or industrial.
+    pub fn career(&self) -> Option<&'a str> {
+        self.param_str("vehicleCareer")
+    }
+
+    /// Localization key of the vehicle role within its career.
+    pub fn role(&self) -> Option<&'a str> {
+        self.param_str("vehicleRole")
+    }
+
+    /// Number of crew seats.
+    pub fn crew_size(&self) -> Option<i32> {
+        self.record
+            .get_path(&format!("{VEHICLE_PARAMS}/crewSize"))?
+            .as_i32()
+    }
+
+    /// The manufacturer record.
+    pub fn manufacturer(&self) -> Option<Record<'a>> {
+        path_record(&self.record, &format!("{VEHICLE_PARAMS}/manufacturer"))
+    }
+
+    /// Top speed in m/s, including boost.
+    pub fn max_speed(&self) -> Option<f32> {
+        path_f32(&self.record, &format!("{IFCS_PARAMS}/maxSpeed"))
+    }
+
+    /// Combat (SCM) speed in m/s.
+    pub fn scm_speed(&self) -> Option<f32> {
+        path_f32(&self.record, &format!("{IFCS_PARAMS}/scmSpeed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use svarog_common::CigGuid;
+
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::{DataCoreDatabase, DataType};
+
+    #[test]
+    fn test_vehicle_getters() {
+        let mut builder = DataCoreBuilder::new();
+        let manufacturer = builder.add_struct("SCItemManufacturer", None);
+        let component = builder.add_struct("EntityComponentParams", None);
+        let params = builder.add_struct("VehicleComponentParams", Some(component));
+        builder.add_property(params, "vehicleName", DataType::Locale);
+        builder.add_property(params, "crewSize", DataType::Int32);
+        builder.add_typed_property(params, "manufacturer", DataType::Reference, manufacturer);
+        let ifcs = builder.add_struct("IFCSParams", Some(component));
+        builder.add_property(ifcs, "maxSpeed", DataType::Single);
+        builder.add_property(ifcs, "scmSpeed", DataType::Single);
+        let entity = builder.add_struct("EntityClassDefinition", None);
+        builder.add_typed_array_property(entity, "Components", DataType::StrongPointer, component);
+
+        let aegis_guid = CigGuid::random();
+        builder.add_record_with_guid("AEGS", manufacturer, "manufacturers/aegs.xml", aegis_guid);
+        let ship = builder.add_record("AEGS_Gladius", entity, "entities/ships/aegs_gladius.xml");
+        let vehicle = builder.add_instance(params);
+        builder.set_string(vehicle, "vehicleName", "@vehicle_NameAEGS_Gladius");
+        builder.set_i32(vehicle, "crewSize", 1);
+        builder.set_reference(vehicle, "manufacturer", aegis_guid);
+        let flight = builder.add_instance(ifcs);
+        builder.set_float(flight, "maxSpeed", 1240.0);
+        builder.set_float(flight, "scmSpeed", 225.0);
+        builder.set_strong_pointer_array(ship, "Components", &[vehicle, flight]);
+        builder.add_record("Rock", entity, "entities/rock.xml");
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let vehicles: Vec<Vehicle> = db.models().collect();
+
+        assert_eq!(vehicles.len(), 1);
+        let gladius = vehicles[0];
+        assert_eq!(gladius.display_name(), Some("@vehicle_NameAEGS_Gladius"));
+        assert_eq!(gladius.crew_size(), Some(1));
+        assert_eq!(gladius.manufacturer().and_then(|r| r.name()), Some("AEGS"));
+        assert_eq!(gladius.max_speed(), Some(1240.0));
+        assert_eq!(gladius.scm_speed(), Some(225.0));
+        assert_eq!(gladius.career(), None);
+    }
+}
@@ -0,0 +1,255 @@
+//! Personal and ship weapons.
+
+use super::{path_f32, path_record, Model};
+use crate::{Instance, Record};
+
+const WEAPON_PARAMS: &str = "Components/SCItemWeaponComponentParams";
+const AMMO_CONTAINER_PARAMS: &str = "Components/SAmmoContainerComponentParams";
+
+/// A weapon: an entity with a `SCItemWeaponComponentParams` component.
+///
+/// Damage comes from the ammo the weapon's magazine or ammo container holds,
+/// and fire rate from its first fire action.
+#[derive(Debug, Clone, Copy)]
+pub struct Weapon<'a> {
+    record: Record<'a>,
+}
+
+/// Damage of a single projectile, by damage type.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Damage {
+    /// Physical (ballistic) damage.
+    pub physical: f32,
+    /// Energy damage.
+    pub energy: f32,
+    /// Distortion damage, which disables components instead of destroying them.
+    pub distortion: f32,
+    /// Thermal damage.
+    pub thermal: f32,
+    /// Biochemical damage.
+    pub biochemical: f32,
+    /// Stun damage.
+    pub stun: f32,
+}
+
+impl Damage {
+    fn from_instance(info: &Instance<'_>) -> Self {
+        let get = |name| info.get_f32(name).unwrap_or(0.0);
+        Self {
+            physical: get("DamagePhysical"),
+            energy: get("DamageEnergy"),
+            distortion: get("DamageDistortion"),
+            thermal: get("DamageThermal"),
+            biochemical: get("DamageBiochemical"),
+            stun: get("DamageStun"),
+        }
+    }
+
+    /// Sum of all damage types.
+    pub fn total(&self) -> f32 {
+        self.physical + self.energy + self.distortion + self.thermal + self.biochemical + self.stun
+    }
+}
+
+impl<'a> Model<'a> for Weapon<'a> {
+    fn from_record(record: Record<'a>) -> Option<Self> {
+        record.get_path(WEAPON_PARAMS)?;
+        Some(Self { record })
+    }
+
+    fn record(&self) -> Record<'a> {
+        self.record
+    }
+}
+
+impl<'a> Weapon<'a> {
+    /// Rounds per minute of the first fire action.
+    pub fn fire_rate(&self) -> Option<f32> {
+        path_f32(
+            &self.record,
+            &format!("{WEAPON_PARAMS}/fireActions[0]/fireRate"),
+        )
+    }
+
+    /// Projectiles per shot, e.g. for shotguns. Defaults to 1.
+    pub fn pellets_per_shot(&self) -> u32 {
+        self.record
+            .get_path(&format!(
+                "{WEAPON_PARAMS}/fireActions[0]/launchParams/pelletCount"
+            ))
+            .and_then(|v| v.as_i32())
+            .and_then(|count| u32::try_from(count).ok())
+            .filter(|&count| count > 0)
+            .unwrap_or(1)
+    }
+
+    /// The magazine or ammo container record.
+    pub fn ammo_container(&self) -> Option<Record<'a>> {
+        path_record(
+            &self.record,
+            &format!("{WEAPON_PARAMS}/ammoContainerRecord"),
+        )
+    }
+
+    /// The `AmmoParams` record of the fired ammo.
+    pub fn ammo(&self) -> Option<Record<'a>> {
+        path_record(
+            &self.ammo_container()?,
+            &format!("{AMMO_CONTAINER_PARAMS}/ammoParamsRecord"),
+        )
+    }
+
+    /// Ammo capacity of the magazine or container.
+    pub fn max_ammo(&self) -> Option<i32> {
+        self.ammo_container()?
+            .get_path(&format!("{AMMO_CONTAINER_PARAMS}/maxAmmoCount"))?
+            .as_i32()
+    }
+
+    /// Projectile speed in m/s.
+    pub fn projectile_speed(&self) -> Option<f32> {
+        self.ammo()?.get_f32("speed")
+    }
+
+    /// Damage of a single projectile.
+    pub fn damage_per_projectile(&self) -> Option<Damage> {
+        let ammo = self.ammo()?;
+        let info = ammo
+            .get_path("projectileParams/damage/DamageInfo")?
+            .as_instance()?;
+        let info = ammo
+            .database()
+            .instance(info.struct_index, info.instance_index);
+        Some(Damage::from_instance(&info))
+    }
+
+    /// Total damage per second, from projectile damage, pellets per shot
+    /// and fire rate.
+    pub fn dps(&self) -> Option<f32> {
+        let damage = self.damage_per_projectile()?.total();
+        let shots_per_second = self.fire_rate()? / 60.0;
+        Some(damage * self.pellets_per_shot() as f32 * shots_per_second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use svarog_common::CigGuid;
+
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+    use crate::{DataCoreDatabase, DataType};
+
+    #[test]
+    fn test_weapon_dps() {
+        let mut builder = DataCoreBuilder::new();
+        let component = builder.add_struct("EntityComponentParams", None);
+        let entity = builder.add_struct("EntityClassDefinition", None);
+        builder.add_typed_array_property(entity, "Components", DataType::StrongPointer, component);
+
+        let damage_info = builder.add_struct("DamageInfo", None);
+        builder.add_property(damage_info, "DamagePhysical", DataType::Single);
+        builder.add_property(damage_info, "DamageEnergy", DataType::Single);
+        let projectile = builder.add_struct("BulletProjectileParams", None);
+        builder.add_typed_array_property(
+            projectile,
+            "damage",
+            DataType::StrongPointer,
+            damage_info,
+        );
+        let ammo_params = builder.add_struct("AmmoParams", None);
+        builder.add_property(ammo_params, "speed", DataType::Single);
+        builder.add_typed_property(
+            ammo_params,
+            "projectileParams",
+            DataType::StrongPointer,
+            projectile,
+        );
+
+        let container_params = builder.add_struct("SAmmoContainerComponentParams", Some(component));
+        builder.add_property(container_params, "maxAmmoCount", DataType::Int32);
+        builder.add_typed_property(
+            container_params,
+            "ammoParamsRecord",
+            DataType::Reference,
+            ammo_params,
+        );
+
+        let launcher = builder.add_struct("SProjectileLauncher", None);
+        builder.add_property(launcher, "pelletCount", DataType::Int32);
+        let fire_action = builder.add_struct("SWeaponActionFireSingleParams", None);
+        builder.add_property(fire_action, "fireRate", DataType::Single);
+        builder.add_typed_property(
+            fire_action,
+            "launchParams",
+            DataType::StrongPointer,
+            launcher,
+        );
+        let weapon_params = builder.add_struct("SCItemWeaponComponentParams", Some(component));
+        builder.add_typed_array_property(
+            weapon_params,
+            "fireActions",
+            DataType::StrongPointer,
+            fire_action,
+        );
+        builder.add_typed_property(
+            weapon_params,
+            "ammoContainerRecord",
+            DataType::Reference,
+            entity,
+        );
+
+        let ammo_guid = CigGuid::random();
+        let ammo = builder.add_record_with_guid(
+            "shotgun_ammo",
+            ammo_params,
+            "ammo/shotgun.xml",
+            ammo_guid,
+        );
+        builder.set_float(ammo, "speed", 400.0);
+        let bullet = builder.add_instance(projectile);
+        let damage = builder.add_instance(damage_info);
+        builder.set_float(damage, "DamagePhysical", 10.0);
+        builder.set_float(damage, "DamageEnergy", 2.0);
+        builder.set_strong_pointer_array(bullet, "damage", &[damage]);
+        builder.set_strong_pointer_instance(ammo, "projectileParams", Some(bullet));
+
+        let magazine_guid = CigGuid::random();
+        let magazine = builder.add_record_with_guid(
+            "shotgun_mag",
+            entity,
+            "items/shotgun_mag.xml",
+            magazine_guid,
+        );
+        let container = builder.add_instance(container_params);
+        builder.set_i32(container, "maxAmmoCount", 8);
+        builder.set_reference(container, "ammoParamsRecord", ammo_guid);
+        builder.set_strong_pointer_array(magazine, "Components", &[container]);
+
+        let shotgun = builder.add_record("shotgun", entity, "items/shotgun.xml");
+        let params = builder.add_instance(weapon_params);
+        let action = builder.add_instance(fire_action);
+        builder.set_float(action, "fireRate", 120.0);
+        let launch = builder.add_instance(launcher);
+        builder.set_i32(launch, "pelletCount", 6);
+        builder.set_strong_pointer_instance(action, "launchParams", Some(launch));
+        builder.set_strong_pointer_array(params, "fireActions", &[action]);
+        builder.set_reference(params, "ammoContainerRecord", magazine_guid);
+        builder.set_strong_pointer_array(shotgun, "Components", &[params]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let weapons: Vec<Weapon> = db.models().collect();
+
+        assert_eq!(weapons.len(), 1);
+        let weapon = weapons[0];
+        assert_eq!(weapon.fire_rate(), Some(120.0));
+        assert_eq!(weapon.pellets_per_shot(), 6);
+        assert_eq!(weapon.max_ammo(), Some(8));
+        assert_eq!(weapon.projectile_speed(), Some(400.0));
+        assert_eq!(
+            weapon.damage_per_projectile().map(|d| d.total()),
+            Some(12.0)
+        );
+        assert_eq!(weapon.dps(), Some(144.0));
+    }
+}
@@ -0,0 +1,1336 @@
+//! In-place patching of DataCore databases.
+//!
+//! [`DataCorePatcher`] edits property values, strings and enum options in an
+//! existing DCB without rebuilding it. Fixed-size values are overwritten where
+//! they are stored, so the file keeps its exact byte layout. Only edits that
+//! need a string which is not yet in the string tables grow the file: the
+//! string is appended to the end of its table and the header length is updated.
+//!
+//! Edits are written to the file contents as they are made, but strings they
+//! add and the parsed [`DataCoreDatabase`] only catch up on
+//! [`commit`](DataCorePatcher::commit), so patching many values does not
+//! reparse the whole file for each of them.
+//!
+//! Property paths are dot-separated property names, with `[n]` to index into
+//! arrays. Paths follow inline classes and strong or weak pointers, so
+//! `Loadout.entries[2].itemName` is a valid path.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::DataCorePatcher;
+//!
+//! let mut patcher = DataCorePatcher::open("Game.dcb")?;
+//! let guid = "b3f2c3a4-0000-0000-0000-000000000000".parse()?;
+//!
+//! patcher.set_record_property(&guid, "Mass", 1250.0)?;
+//! patcher.set_record_property(&guid, "Components[0].name", "Shield")?;
+//! patcher.replace_string("libs/old.xml", "libs/new.xml")?;
+//!
+//! patcher.commit()?;
+//! assert!(patcher.database().record(&guid).is_some());
+//! patcher.write_to_file("Game.patched.dcb")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::mem::{offset_of, size_of, size_of_val};
+use std::path::Path;
+
+use svarog_common::CigGuid;
+
+use crate::structs::{
+    DataCoreEnumDefinition, DataCorePropertyDefinition, DataCoreRecord, DataCoreStructDefinition,
+};
+use crate::{DataCoreDatabase, DataType, Error, PoolType, Result};
+
+/// Header offset of the string table 1 length.
+const TEXT_LENGTH_1_OFFSET: usize = 112;
+/// Header offset of the string table 2 length.
+const TEXT_LENGTH_2_OFFSET: usize = 116;
+/// Size of the DCB header.
+const HEADER_SIZE: usize = 120;
+
+/// A value to write into a property.
+///
+/// Integer and float values are converted to the property's type, failing if
+/// the value does not fit. Strings are used for `String`, `Locale` and
+/// `EnumChoice` properties.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchValue {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    String(String),
+    Guid(CigGuid),
+    Reference(Option<CigGuid>),
+}
+
+impl PatchValue {
+    /// Parse a value from text for a property of the given type.
+    ///
+    /// References accept a GUID, or `null` / an empty string for no reference.
+    pub fn parse(data_type: DataType, text: &str) -> Result<Self> {
+        let invalid = || Error::Patch(format!("invalid {} value: {:?}", data_type.as_str(), text));
+
+        match data_type {
+            DataType::Boolean => match text {
+                "true" | "1" => Ok(Self::Bool(true)),
+                "false" | "0" => Ok(Self::Bool(false)),
+                _ => Err(invalid()),
+            },
+            DataType::SByte | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+                text.parse().map(Self::Int).map_err(|_| invalid())
+            }
+            DataType::Byte | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+                text.parse().map(Self::UInt).map_err(|_| invalid())
+            }
+            DataType::Single | DataType::Double => {
+                text.parse().map(Self::Float).map_err(|_| invalid())
+            }
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                Ok(Self::String(text.to_string()))
+            }
+            DataType::Guid => text.parse().map(Self::Guid).map_err(|_| invalid()),
+            DataType::Reference => match text {
+                "" | "null" => Ok(Self::Reference(None)),
+                _ => text
+                    .parse()
+                    .map(|guid| Self::Reference(Some(guid)))
+                    .map_err(|_| invalid()),
+            },
+            DataType::Class | DataType::StrongPointer | DataType::WeakPointer => Err(Error::Patch(
+                format!("{} properties cannot be patched", data_type.as_str()),
+            )),
+        }
+    }
+}
+
+impl From<bool> for PatchValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for PatchValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+impl From<i64> for PatchValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<u32> for PatchValue {
+    fn from(value: u32) -> Self {
+        Self::UInt(value as u64)
+    }
+}
+
+impl From<u64> for PatchValue {
+    fn from(value: u64) -> Self {
+        Self::UInt(value)
+    }
+}
+
+impl From<f32> for PatchValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value as f64)
+    }
+}
+
+impl From<f64> for PatchValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for PatchValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for PatchValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<CigGuid> for PatchValue {
+    fn from(value: CigGuid) -> Self {
+        Self::Guid(value)
+    }
+}
+
+/// String table selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Table {
+    /// Table 1: file names and string values.
+    Values,
+    /// Table 2: type, property, enum option and record names.
+    Names,
+}
+
+/// Location of a patchable value in the file.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    offset: usize,
+    data_type: DataType,
+    struct_index: usize,
+}
+
+/// Location of a table 1 string id in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct StringSlot {
+    offset: usize,
+    /// Enum index for `EnumChoice` values.
+    enum_index: Option<usize>,
+}
+
+/// Where a path segment leads to.
+enum Target {
+    Value(usize),
+    Instance(usize, usize),
+    Inline(usize, usize),
+}
+
+/// Editor for patching a DCB file in place.
+pub struct DataCorePatcher {
+    data: Vec<u8>,
+    /// The database as of the last commit; its layout matches `data` until
+    /// staged strings are appended.
+    db: DataCoreDatabase,
+    /// Strings added to tables 1 and 2 since the last commit.
+    appended: [Vec<u8>; 2],
+    /// Locations of table 1 string ids by the id they hold, collected on
+    /// first use and kept up to date by writes.
+    string_uses: Option<BTreeMap<i32, Vec<StringSlot>>>,
+    /// Whether anything changed since the last commit.
+    dirty: bool,
+}
+
+impl DataCorePatcher {
+    /// Create a patcher over the raw bytes of a DCB file.
+    pub fn new(data: Vec<u8>) -> Result<Self> {
+        let db = DataCoreDatabase::parse(&data)?;
+        Ok(Self {
+            data,
+            db,
+            appended: Default::default(),
+            string_uses: None,
+            dirty: false,
+        })
+    }
+
+    /// Load a DCB file from disk for patching.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(std::fs::read(path)?)
+    }
+
+    /// The database as of the last [`commit`](Self::commit).
+    pub fn database(&self) -> &DataCoreDatabase {
+        &self.db
+    }
+
+    /// The patched file contents.
+    ///
+    /// Strings added since the last [`commit`](Self::commit) are not part of
+    /// them yet.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consume the patcher, returning the file contents with all edits.
+    pub fn into_bytes(mut self) -> Result<Vec<u8>> {
+        self.append_strings()?;
+        Ok(self.data)
+    }
+
+    /// Commit all edits and write the patched file to disk.
+    pub fn write_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        self.commit()?;
+        std::fs::write(path, &self.data)?;
+        Ok(())
+    }
+
+    /// Append the strings added by edits to their tables and reparse the
+    /// database, so that [`database`](Self::database) reflects all edits.
+    ///
+    /// Does nothing if there were no edits since the last commit.
+    pub fn commit(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.append_strings()?;
+        self.db = DataCoreDatabase::parse(&self.data)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Set a property of a record.
+    ///
+    /// `path` is a dot-separated property path such as `stats.health` or
+    /// `entries[2].name`. Enum values are checked against the enum's options.
+    pub fn set_record_property(
+        &mut self,
+        record: &CigGuid,
+        path: &str,
+        value: impl Into<PatchValue>,
+    ) -> Result<()> {
+        let value = value.into();
+        let slot = self.resolve(record, path)?;
+        let bytes = self.encode(slot, &value)?;
+
+        match slot.data_type {
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                let mut id = [0; 4];
+                id.copy_from_slice(&bytes);
+                self.write_string_id(slot.offset, i32::from_le_bytes(id));
+            }
+            _ => {
+                self.data[slot.offset..slot.offset + bytes.len()].copy_from_slice(&bytes);
+                self.dirty = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a property of a record from its text form.
+    ///
+    /// The text is parsed according to the property's type, see
+    /// [`PatchValue::parse`].
+    pub fn set_record_property_str(
+        &mut self,
+        record: &CigGuid,
+        path: &str,
+        text: &str,
+    ) -> Result<()> {
+        let slot = self.resolve(record, path)?;
+        let value = PatchValue::parse(slot.data_type, text)?;
+        self.set_record_property(record, path, value)
+    }
+
+    /// Replace every use of a string value with another string.
+    ///
+    /// This affects file names and `String`, `Locale` and `EnumChoice` values.
+    /// The string is rewritten in place when the new one fits, otherwise uses
+    /// are repointed to the new string. Returns the number of updated values.
+    pub fn replace_string(&mut self, old: &str, new: &str) -> Result<usize> {
+        let old_id = self
+            .find_string(Table::Values, old)
+            .ok_or_else(|| Error::Patch(format!("string not found: {:?}", old)))?;
+        if old == new {
+            return Ok(0);
+        }
+
+        let uses = self.string_uses();
+        let used = uses.get(&old_id).map_or(0, Vec::len);
+
+        // Rewriting in place is only safe if nothing points into the middle of the string
+        let end = old_id + old.len() as i32;
+        let shared = uses.range(old_id + 1..=end).next().is_some();
+        if new.len() <= old.len() && !shared && self.find_string(Table::Values, new).is_none() {
+            let bytes = self.string_bytes_mut(Table::Values, old_id as usize, old.len());
+            bytes[..new.len()].copy_from_slice(new.as_bytes());
+            bytes[new.len()..].fill(0);
+            self.dirty = true;
+            return Ok(used);
+        }
+
+        let new_id = self.intern(Table::Values, new)?;
+        let slots = self.string_uses().remove(&old_id).unwrap_or_default();
+        for slot in &slots {
+            self.write_i32(slot.offset, new_id);
+        }
+        let updated = slots.len();
+        self.string_uses().entry(new_id).or_default().extend(slots);
+        Ok(updated)
+    }
+
+    /// Rename an option of an enum.
+    ///
+    /// Updates the enum definition and every value of that enum which used the
+    /// old option. Returns the number of updated values.
+    pub fn rename_enum_option(&mut self, enum_name: &str, old: &str, new: &str) -> Result<usize> {
+        let enum_index = (0..self.db.enum_definitions().len())
+            .find(|&i| self.db.enum_name(i) == Some(enum_name))
+            .ok_or_else(|| Error::Patch(format!("enum not found: {}", enum_name)))?;
+        let def = self.db.enum_definitions()[enum_index];
+        let options = self.enum_options(enum_index);
+        let position = options
+            .iter()
+            .position(|o| *o == old)
+            .ok_or_else(|| Error::Patch(format!("{} has no option {:?}", enum_name, old)))?;
+        if options.contains(&new) {
+            return Err(Error::Patch(format!(
+                "{} already has an option {:?}",
+                enum_name, new
+            )));
+        }
+
+        let old_id = self.find_string(Table::Values, old);
+        let name_id = self.intern(Table::Names, new)?;
+        let value_id = self.intern(Table::Values, new)?;
+
+        let (pool_offset, _, elem_size) = self.db.pool_layout(PoolType::EnumOption);
+        let option_index = def.first_value_index as usize + position;
+        self.write_string_id(pool_offset + option_index * elem_size, name_id);
+
+        let Some(old_id) = old_id else {
+            return Ok(0);
+        };
+        let uses = self.string_uses();
+        let (renamed, kept): (Vec<_>, Vec<_>) = uses
+            .remove(&old_id)
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|s| s.enum_index == Some(enum_index));
+        if !kept.is_empty() {
+            uses.insert(old_id, kept);
+        }
+        for slot in &renamed {
+            self.write_i32(slot.offset, value_id);
+        }
+        let updated = renamed.len();
+        self.string_uses()
+            .entry(value_id)
+            .or_default()
+            .extend(renamed);
+        Ok(updated)
+    }
+
+    // Path resolution
+
+    /// Resolve a record property path to the location of its value.
+    fn resolve(&self, record: &CigGuid, path: &str) -> Result<Slot> {
+        let record = self
+            .db
+            .get_record(record)
+            .ok_or_else(|| Error::RecordNotFound(record.to_string()))?;
+
+        let mut struct_index = record.struct_index as usize;
+        let mut base = self
+            .db
+            .instance_offset(struct_index, record.instance_index as usize);
+
+        let segments: Vec<&str> = path.split('.').collect();
+        for (i, segment) in segments.iter().enumerate() {
+            let (name, index) = parse_segment(segment)?;
+            let (prop, offset) = self.locate_field(struct_index, base, name)?;
+            let data_type =
+                DataType::from_u16(prop.data_type).ok_or(Error::InvalidDataType(prop.data_type))?;
+            let target_struct = prop.struct_index as usize;
+
+            let target = match (prop.is_array(), index) {
+                (true, Some(index)) => {
+                    let count = self.read_i32(offset).max(0) as usize;
+                    let first = self.read_i32(offset + 4).max(0) as usize;
+                    if index >= count {
+                        return Err(Error::Patch(format!(
+                            "index {} out of bounds for {} (length {})",
+                            index, name, count
+                        )));
+                    }
+                    match data_type {
+                        DataType::Class => Target::Instance(target_struct, first + index),
+                        DataType::StrongPointer | DataType::WeakPointer => {
+                            let pool =
+                                PoolType::for_data_type(data_type).unwrap_or(PoolType::Strong);
+                            let (pool_offset, _, elem_size) = self.db.pool_layout(pool);
+                            self.pointer_target(pool_offset + (first + index) * elem_size)?
+                        }
+                        _ => {
+                            let pool = PoolType::for_data_type(data_type)
+                                .ok_or(Error::InvalidDataType(prop.data_type))?;
+                            let (pool_offset, _, elem_size) = self.db.pool_layout(pool);
+                            Target::Value(pool_offset + (first + index) * elem_size)
+                        }
+                    }
+                }
+                (true, None) => {
+                    return Err(Error::Patch(format!(
+                        "{} is an array and needs an index",
+                        name
+                    )));
+                }
+                (false, Some(_)) => {
+                    return Err(Error::Patch(format!("{} is not an array", name)));
+                }
+                (false, None) => match data_type {
+                    DataType::Class => Target::Inline(target_struct, offset),
+                    DataType::StrongPointer | DataType::WeakPointer => {
+                        self.pointer_target(offset)?
+                    }
+                    _ => Target::Value(offset),
+                },
+            };
+
+            let last = i + 1 == segments.len();
+            match (target, last) {
+                (Target::Value(offset), true) => {
+                    return Ok(Slot {
+                        offset,
+                        data_type,
+                        struct_index: target_struct,
+                    });
+                }
+                (Target::Value(_), false) => {
+                    return Err(Error::Patch(format!("{} has no properties", name)));
+                }
+                (_, true) => {
+                    return Err(Error::Patch(format!("{} is a struct, not a value", name)));
+                }
+                (Target::Instance(s, instance), false) => {
+                    self.check_instance(s, instance)?;
+                    struct_index = s;
+                    base = self.db.instance_offset(s, instance);
+                }
+                (Target::Inline(s, offset), false) => {
+                    struct_index = s;
+                    base = offset;
+                }
+            }
+        }
+
+        Err(Error::Patch("empty property path".to_string()))
+    }
+
+    /// Find a property of a struct instance and its absolute offset.
+    fn locate_field(
+        &self,
+        struct_index: usize,
+        base: usize,
+        name: &str,
+    ) -> Result<(DataCorePropertyDefinition, usize)> {
+        let mut offset = base;
+        for prop in self.db.get_struct_properties(struct_index) {
+            if self.db.property_name(prop) == Some(name) {
+                return Ok((*prop, offset));
+            }
+            offset += self.field_size(prop);
+        }
+
+        let struct_name = self.db.struct_name(struct_index).unwrap_or("Unknown");
+        Err(Error::Patch(format!(
+            "{} has no property {}",
+            struct_name, name
+        )))
+    }
+
+    /// Inline size of a property, including inline classes.
+    fn field_size(&self, prop: &DataCorePropertyDefinition) -> usize {
+        if prop.is_array() {
+            return 8;
+        }
+        match DataType::from_u16(prop.data_type) {
+            Some(DataType::Class) => self
+                .db
+                .get_struct_properties(prop.struct_index as usize)
+                .into_iter()
+                .map(|p| self.field_size(p))
+                .sum(),
+            Some(data_type) => data_type.inline_size(),
+            None => 0,
+        }
+    }
+
+    fn pointer_target(&self, offset: usize) -> Result<Target> {
+        let struct_index = self.read_i32(offset);
+        let instance_index = self.read_i32(offset + 4);
+        if struct_index < 0 || instance_index < 0 {
+            return Err(Error::Patch("cannot follow a null pointer".to_string()));
+        }
+        Ok(Target::Instance(
+            struct_index as usize,
+            instance_index as usize,
+        ))
+    }
+
+    fn check_instance(&self, struct_index: usize, instance_index: usize) -> Result<()> {
+        let count = self
+            .db
+            .data_mappings()
+            .iter()
+            .find(|m| m.struct_index as usize == struct_index)
+            .map_or(0, |m| m.struct_count as usize);
+        if instance_index >= count {
+            return Err(Error::Patch(format!(
+                "instance {} of struct {} does not exist",
+                instance_index, struct_index
+            )));
+        }
+        Ok(())
+    }
+
+    // Value encoding
+
+    /// Encode a value for a slot, interning strings as needed.
+    fn encode(&mut self, slot: Slot, value: &PatchValue) -> Result<Vec<u8>> {
+        let mismatch = || {
+            Error::Patch(format!(
+                "cannot store {:?} in a {} property",
+                value,
+                slot.data_type.as_str()
+            ))
+        };
+
+        let bytes = match slot.data_type {
+            DataType::String | DataType::Locale | DataType::EnumChoice => {
+                let PatchValue::String(text) = value else {
+                    return Err(mismatch());
+                };
+                if slot.data_type == DataType::EnumChoice {
+                    self.check_enum_option(slot.struct_index, text)?;
+                }
+                self.intern(Table::Values, text)?.to_le_bytes().to_vec()
+            }
+            DataType::Reference => {
+                let target = match *value {
+                    PatchValue::Reference(target) => target,
+                    PatchValue::Guid(guid) => Some(guid),
+                    _ => return Err(mismatch()),
+                };
+                let guid = target.unwrap_or(CigGuid::EMPTY);
+                let instance_index = target
+                    .and_then(|g| self.db.get_record(&g))
+                    .map_or(0, |r| r.instance_index as i32);
+                let mut bytes = guid.as_bytes().to_vec();
+                bytes.extend_from_slice(&instance_index.to_le_bytes());
+                bytes
+            }
+            DataType::Class | DataType::StrongPointer | DataType::WeakPointer => {
+                return Err(mismatch());
+            }
+            _ => encode_scalar(slot.data_type, value)?,
+        };
+
+        Ok(bytes)
+    }
+
+    fn check_enum_option(&self, enum_index: usize, value: &str) -> Result<()> {
+        if enum_index >= self.db.enum_definitions().len() {
+            return Ok(());
+        }
+        if self.enum_options(enum_index).contains(&value) {
+            Ok(())
+        } else {
+            let name = self.db.enum_name(enum_index).unwrap_or("Unknown");
+            Err(Error::Patch(format!(
+                "{:?} is not an option of {}",
+                value, name
+            )))
+        }
+    }
+
+    /// Options of an enum, including renames since the last commit.
+    fn enum_options(&self, enum_index: usize) -> Vec<&str> {
+        let def = self.db.enum_definitions()[enum_index];
+        let (pool_offset, _, elem_size) = self.db.pool_layout(PoolType::EnumOption);
+        let first = def.first_value_index as usize;
+        (first..first + def.value_count as usize)
+            .map(|i| {
+                let id = self.read_i32(pool_offset + i * elem_size);
+                self.string_at(Table::Names, id).unwrap_or_default()
+            })
+            .collect()
+    }
+
+    // String tables
+
+    /// Index of the staged strings of a table; version 5 files share one
+    /// table between names and values.
+    fn table_index(&self, table: Table) -> usize {
+        match table {
+            Table::Names if self.db.version() >= 6 => 1,
+            _ => 0,
+        }
+    }
+
+    /// Offset and length of a table as of the last commit.
+    fn table_range(&self, table: Table) -> (usize, usize) {
+        let (table_1, table_2) = self.db.string_table_layout();
+        if self.table_index(table) == 1 {
+            table_2
+        } else {
+            table_1
+        }
+    }
+
+    /// Find the id of a string in a table, including strings added since the
+    /// last commit.
+    fn find_string(&self, table: Table, value: &str) -> Option<i32> {
+        let (offset, len) = self.table_range(table);
+        let appended = &self.appended[self.table_index(table)];
+        find_in_table(&self.data[offset..offset + len], value)
+            .or_else(|| find_in_table(appended, value).map(|start| len + start))
+            .map(|start| start as i32)
+    }
+
+    /// The string with an id in a table.
+    fn string_at(&self, table: Table, id: i32) -> Option<&str> {
+        let (offset, len) = self.table_range(table);
+        let id = usize::try_from(id).ok()?;
+        let bytes = if id < len {
+            &self.data[offset + id..offset + len]
+        } else {
+            self.appended[self.table_index(table)].get(id - len..)?
+        };
+        let end = memchr::memchr(0, bytes).unwrap_or(bytes.len());
+        std::str::from_utf8(&bytes[..end]).ok()
+    }
+
+    /// The `len` bytes of the string with an id in a table.
+    fn string_bytes_mut(&mut self, table: Table, id: usize, len: usize) -> &mut [u8] {
+        let (offset, table_len) = self.table_range(table);
+        if id < table_len {
+            &mut self.data[offset + id..offset + id + len]
+        } else {
+            let index = self.table_index(table);
+            &mut self.appended[index][id - table_len..id - table_len + len]
+        }
+    }
+
+    /// Get the id of a string, adding it to the table if missing.
+    ///
+    /// New strings are staged and appended to the table on commit.
+    fn intern(&mut self, table: Table, value: &str) -> Result<i32> {
+        if value.contains('\0') {
+            return Err(Error::Patch(
+                "strings cannot contain NUL characters".to_string(),
+            ));
+        }
+        if let Some(id) = self.find_string(table, value) {
+            return Ok(id);
+        }
+
+        let (_, len) = self.table_range(table);
+        let index = self.table_index(table);
+        let appended = &mut self.appended[index];
+        let id = i32::try_from(len + appended.len())
+            .map_err(|_| Error::Patch("string table too large".to_string()))?;
+        appended.extend_from_slice(value.as_bytes());
+        appended.push(0);
+        self.dirty = true;
+        Ok(id)
+    }
+
+    /// Append staged strings to the end of their tables and update the
+    /// header lengths.
+    ///
+    /// Everything after the tables moves, so the layout of the database is
+    /// stale until it is parsed again.
+    fn append_strings(&mut self) -> Result<()> {
+        let (table_1, table_2) = self.db.string_table_layout();
+        // Table 2 follows table 1, so growing it first keeps table 1 in place
+        let tables = [
+            (1, table_2, TEXT_LENGTH_2_OFFSET),
+            (0, table_1, TEXT_LENGTH_1_OFFSET),
+        ];
+        for (index, (offset, len), header) in tables {
+            if self.appended[index].is_empty() {
+                continue;
+            }
+            let new_len = u32::try_from(len + self.appended[index].len())
+                .map_err(|_| Error::Patch("string table too large".to_string()))?;
+            let bytes = std::mem::take(&mut self.appended[index]);
+            self.data.splice(offset + len..offset + len, bytes);
+            self.data[header..header + 4].copy_from_slice(&new_len.to_le_bytes());
+            self.string_uses = None;
+        }
+        Ok(())
+    }
+
+    /// Locations of table 1 string ids by the id they hold.
+    fn string_uses(&mut self) -> &mut BTreeMap<i32, Vec<StringSlot>> {
+        if self.string_uses.is_none() {
+            let mut uses: BTreeMap<i32, Vec<StringSlot>> = BTreeMap::new();
+            for slot in self.string_slots() {
+                uses.entry(self.read_i32(slot.offset))
+                    .or_default()
+                    .push(slot);
+            }
+            self.string_uses = Some(uses);
+        }
+        self.string_uses.get_or_insert_with(BTreeMap::new)
+    }
+
+    /// Write a table 1 string id, keeping the string uses up to date.
+    fn write_string_id(&mut self, offset: usize, id: i32) {
+        let old_id = self.read_i32(offset);
+        if let Some(uses) = &mut self.string_uses {
+            if let Some(slots) = uses.get_mut(&old_id) {
+                if let Some(i) = slots.iter().position(|s| s.offset == offset) {
+                    let slot = slots.swap_remove(i);
+                    if slots.is_empty() {
+                        uses.remove(&old_id);
+                    }
+                    uses.entry(id).or_default().push(slot);
+                }
+            }
+        }
+        self.write_i32(offset, id);
+    }
+
+    /// Collect every location holding a table 1 string id.
+    fn string_slots(&self) -> Vec<StringSlot> {
+        let mut slots = Vec::new();
+
+        let records_offset = self.db.records_offset();
+        for i in 0..self.db.records().len() {
+            let offset = records_offset + i * size_of::<DataCoreRecord>();
+            slots.push(StringSlot {
+                offset: offset + offset_of!(DataCoreRecord, file_name_offset),
+                enum_index: None,
+            });
+        }
+
+        for mapping in self.db.data_mappings() {
+            let struct_index = mapping.struct_index as usize;
+            for instance in 0..mapping.struct_count as usize {
+                let base = self.db.instance_offset(struct_index, instance);
+                self.collect_instance_strings(struct_index, base, &mut slots);
+            }
+        }
+
+        // Version 5 files share one table between names and values
+        if self.db.version() < 6 {
+            self.collect_name_slots(&mut slots);
+        }
+
+        slots.sort();
+        slots.dedup();
+        slots
+    }
+
+    fn collect_instance_strings(
+        &self,
+        struct_index: usize,
+        base: usize,
+        slots: &mut Vec<StringSlot>,
+    ) {
+        let mut offset = base;
+        for prop in self.db.get_struct_properties(struct_index) {
+            let size = self.field_size(prop);
+            let data_type = DataType::from_u16(prop.data_type);
+            let enum_index =
+                (data_type == Some(DataType::EnumChoice)).then_some(prop.struct_index as usize);
+
+            match data_type {
+                Some(DataType::String | DataType::Locale | DataType::EnumChoice)
+                    if prop.is_array() =>
+                {
+                    let pool = PoolType::for_data_type(data_type.unwrap_or(DataType::String))
+                        .unwrap_or(PoolType::StringId);
+                    let (pool_offset, pool_count, elem_size) = self.db.pool_layout(pool);
+                    let count = self.read_i32(offset).max(0) as usize;
+                    let first = self.read_i32(offset + 4).max(0) as usize;
+                    for index in first..(first + count).min(pool_count) {
+                        slots.push(StringSlot {
+                            offset: pool_offset + index * elem_size,
+                            enum_index,
+                        });
+                    }
+                }
+                Some(DataType::String | DataType::Locale | DataType::EnumChoice) => {
+                    slots.push(StringSlot { offset, enum_index });
+                }
+                Some(DataType::Class) if !prop.is_array() => {
+                    self.collect_instance_strings(prop.struct_index as usize, offset, slots);
+                }
+                _ => {}
+            }
+
+            offset += size;
+        }
+    }
+
+    fn collect_name_slots(&self, slots: &mut Vec<StringSlot>) {
+        let structs = HEADER_SIZE;
+        let properties = structs + size_of_val(self.db.struct_definitions());
+        let enums = properties + size_of_val(self.db.property_definitions());
+
+        let tables = [
+            (
+                structs,
+                self.db.struct_definitions().len(),
+                size_of::<DataCoreStructDefinition>(),
+                offset_of!(DataCoreStructDefinition, name_offset),
+            ),
+            (
+                properties,
+                self.db.property_definitions().len(),
+                size_of::<DataCorePropertyDefinition>(),
+                offset_of!(DataCorePropertyDefinition, name_offset),
+            ),
+            (
+                enums,
+                self.db.enum_definitions().len(),
+                size_of::<DataCoreEnumDefinition>(),
+                offset_of!(DataCoreEnumDefinition, name_offset),
+            ),
+            (
+                self.db.records_offset(),
+                self.db.records().len(),
+                size_of::<DataCoreRecord>(),
+                offset_of!(DataCoreRecord, name_offset),
+            ),
+        ];
+        for (start, count, size, field) in tables {
+            for i in 0..count {
+                slots.push(StringSlot {
+                    offset: start + i * size + field,
+                    enum_index: None,
+                });
+            }
+        }
+
+        let (pool_offset, pool_count, elem_size) = self.db.pool_layout(PoolType::EnumOption);
+        for i in 0..pool_count {
+            slots.push(StringSlot {
+                offset: pool_offset + i * elem_size,
+                enum_index: None,
+            });
+        }
+    }
+
+    // Raw access
+
+    fn read_i32(&self, offset: usize) -> i32 {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&self.data[offset..offset + 4]);
+        i32::from_le_bytes(buf)
+    }
+
+    fn write_i32(&mut self, offset: usize, value: i32) {
+        self.data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        self.dirty = true;
+    }
+}
+
+/// Find the start of a string in NUL-terminated table data.
+fn find_in_table(data: &[u8], value: &str) -> Option<usize> {
+    let mut start = 0;
+    while start < data.len() {
+        let end = memchr::memchr(0, &data[start..]).map_or(data.len(), |p| start + p);
+        if &data[start..end] == value.as_bytes() {
+            return Some(start);
+        }
+        start = end + 1;
+    }
+    None
+}
+
+/// Encode a bool, number or GUID value as the little-endian bytes of a property.
+pub(crate) fn encode_scalar(data_type: DataType, value: &PatchValue) -> Result<Vec<u8>> {
+    let mismatch = || {
+        Error::Patch(format!(
+            "cannot store {:?} in a {} property",
+            value,
+            data_type.as_str()
+        ))
+    };
+    let integer = match *value {
+        PatchValue::Int(v) => Some(v as i128),
+        PatchValue::UInt(v) => Some(v as i128),
+        _ => None,
+    };
+    let out_of_range = || {
+        Error::Patch(format!(
+            "{:?} is out of range for {}",
+            value,
+            data_type.as_str()
+        ))
+    };
+
+    macro_rules! int {
+        ($ty:ty) => {{
+            let v = integer.ok_or_else(mismatch)?;
+            <$ty>::try_from(v)
+                .map_err(|_| out_of_range())?
+                .to_le_bytes()
+                .to_vec()
+        }};
+    }
+
+    let bytes = match data_type {
+        DataType::Boolean => match value {
+            PatchValue::Bool(v) => vec![*v as u8],
+            _ => return Err(mismatch()),
+        },
+        DataType::SByte => int!(i8),
+        DataType::Int16 => int!(i16),
+        DataType::Int32 => int!(i32),
+        DataType::Int64 => int!(i64),
+        DataType::Byte => int!(u8),
+        DataType::UInt16 => int!(u16),
+        DataType::UInt32 => int!(u32),
+        DataType::UInt64 => int!(u64),
+        DataType::Single | DataType::Double => {
+            let v = match *value {
+                PatchValue::Float(v) => v,
+                _ => integer.ok_or_else(mismatch)? as f64,
+            };
+            if data_type == DataType::Single {
+                (v as f32).to_le_bytes().to_vec()
+            } else {
+                v.to_le_bytes().to_vec()
+            }
+        }
+        DataType::Guid => match value {
+            PatchValue::Guid(guid) => guid.as_bytes().to_vec(),
+            _ => return Err(mismatch()),
+        },
+        _ => return Err(mismatch()),
+    };
+
+    Ok(bytes)
+}
+
+/// Split a path segment like `entries[2]` into name and index.
+fn parse_segment(segment: &str) -> Result<(&str, Option<usize>)> {
+    let invalid = || Error::Patch(format!("invalid path segment: {:?}", segment));
+
+    match segment.split_once('[') {
+        Some((name, rest)) => {
+            let index = rest
+                .strip_suffix(']')
+                .and_then(|i| i.parse().ok())
+                .ok_or_else(invalid)?;
+            Ok((name, Some(index)))
+        }
+        None if segment.is_empty() => Err(invalid()),
+        None => Ok((segment, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    fn build() -> (Vec<u8>, CigGuid) {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_property(item, "name", DataType::String);
+        builder.add_property(item, "count", DataType::Int32);
+        builder.add_property(item, "mass", DataType::Single);
+        builder.add_array_property(item, "weights", DataType::Single);
+        builder.add_array_property(item, "tags", DataType::String);
+        builder.add_typed_property(item, "child", DataType::StrongPointer, item);
+
+        let root = builder.add_record("Root", item, "libs/root.xml");
+        let child = builder.add_record("Child", item, "libs/child.xml");
+        builder.set_string(root, "name", "root");
+        builder.set_i32(root, "count", 3);
+        builder.set_float_array(root, "weights", &[1.0, 2.0]);
+        builder.set_string_array(root, "tags", &["alpha", "beta"]);
+        builder.set_strong_pointer(root, "child", Some(child));
+        builder.set_string(child, "name", "child");
+        builder.set_strong_pointer(child, "child", None);
+
+        let data = builder.build().unwrap();
+        let guid = DataCoreDatabase::parse(&data).unwrap().records()[0].id;
+        (data, guid)
+    }
+
+    #[test]
+    fn test_set_record_property() {
+        let (data, guid) = build();
+        let len = data.len();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+
+        patcher.set_record_property(&guid, "count", 42).unwrap();
+        patcher
+            .set_record_property(&guid, "weights[1]", 7.5)
+            .unwrap();
+        patcher
+            .set_record_property_str(&guid, "mass", "12.5")
+            .unwrap();
+        assert_eq!(patcher.as_bytes().len(), len);
+
+        patcher
+            .set_record_property(&guid, "child.name", "renamed")
+            .unwrap();
+        assert_eq!(patcher.as_bytes().len(), len);
+        patcher.commit().unwrap();
+        assert!(patcher.as_bytes().len() > len);
+
+        assert!(patcher
+            .set_record_property(&guid, "weights[2]", 1.0)
+            .is_err());
+        assert!(patcher
+            .set_record_property(&guid, "count", u64::MAX)
+            .is_err());
+        assert!(patcher.set_record_property(&guid, "missing", 1).is_err());
+
+        let db = DataCoreDatabase::parse(&patcher.into_bytes().unwrap()).unwrap();
+        let root = db.record(&guid).unwrap();
+        assert_eq!(root.get_i32("count"), Some(42));
+        assert_eq!(root.get_f32("mass"), Some(12.5));
+        assert_eq!(
+            root.get_instance("child").unwrap().get_str("name"),
+            Some("renamed")
+        );
+        assert_eq!(root.get_str("name"), Some("root"));
+    }
+
+    #[test]
+    fn test_replace_string() {
+        let (data, guid) = build();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+
+        assert_eq!(patcher.replace_string("beta", "b").unwrap(), 1);
+        assert_eq!(
+            patcher
+                .replace_string("libs/root.xml", "libs/items/root.xml")
+                .unwrap(),
+            1
+        );
+        assert!(patcher.replace_string("missing", "x").is_err());
+
+        patcher.commit().unwrap();
+        let db = patcher.database();
+        let record = db.get_record(&guid).unwrap();
+        assert_eq!(db.record_file_name(record), Some("libs/items/root.xml"));
+
+        let tag = db.string_id_value(1).unwrap();
+        assert_eq!(db.get_string(&tag), Some("b"));
+    }
+
+    #[test]
+    fn test_path_errors() {
+        let (data, guid) = build();
+        let mut patcher = DataCorePatcher::new(data.clone()).unwrap();
+
+        let cases = [
+            ("", "invalid path segment"),
+            ("child..name", "invalid path segment"),
+            ("weights[", "invalid path segment"),
+            ("weights[x]", "invalid path segment"),
+            ("weights", "weights is an array and needs an index"),
+            ("weights[5]", "index 5 out of bounds for weights (length 2)"),
+            ("count[0]", "count is not an array"),
+            ("count.value", "count has no properties"),
+            ("child", "child is a struct, not a value"),
+            ("child.child.name", "cannot follow a null pointer"),
+            ("child.missing", "Item has no property missing"),
+        ];
+        for (path, message) in cases {
+            let error = patcher.set_record_property(&guid, path, 1).unwrap_err();
+            assert!(error.to_string().contains(message), "{}: {}", path, error);
+        }
+
+        assert!(matches!(
+            patcher.set_record_property(&CigGuid::random(), "count", 1),
+            Err(Error::RecordNotFound(_))
+        ));
+        let error = patcher.set_record_property(&guid, "name", 1).unwrap_err();
+        assert!(error.to_string().contains("cannot store"), "{}", error);
+        assert!(patcher
+            .set_record_property_str(&guid, "count", "many")
+            .is_err());
+
+        // Failed edits leave the file alone
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes(), data);
+    }
+
+    #[test]
+    fn test_string_writes() {
+        let (data, guid) = build();
+        let len = data.len();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+
+        // Shorter strings are rewritten in place, existing ones are reused
+        assert_eq!(patcher.replace_string("alpha", "alp").unwrap(), 1);
+        patcher
+            .set_record_property(&guid, "tags[1]", "root")
+            .unwrap();
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes().len(), len);
+
+        // New strings are appended once on commit, however often they are used
+        patcher.set_record_property(&guid, "name", "gamma").unwrap();
+        patcher
+            .set_record_property(&guid, "tags[0]", "gamma")
+            .unwrap();
+        assert_eq!(patcher.replace_string("gamma", "gam").unwrap(), 2);
+        assert_eq!(patcher.as_bytes().len(), len);
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes().len(), len + "gamma\0".len());
+
+        let root = patcher.database().record(&guid).unwrap();
+        assert_eq!(root.get_str("name"), Some("gam"));
+        let tags: Vec<_> = root
+            .get_array("tags")
+            .unwrap()
+            .map(|v| v.as_str())
+            .collect();
+        assert_eq!(tags, [Some("gam"), Some("root")]);
+    }
+
+    #[test]
+    fn test_replace_shared_string() {
+        let (data, guid) = build();
+        let len = data.len();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+
+        // A value pointing into the middle of a string keeps it from being
+        // rewritten in place
+        let alpha = patcher.find_string(Table::Values, "alpha").unwrap();
+        let slot = patcher.resolve(&guid, "tags[1]").unwrap();
+        patcher.write_string_id(slot.offset, alpha + 2);
+
+        assert_eq!(patcher.replace_string("alpha", "xy").unwrap(), 1);
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes().len(), len + "xy\0".len());
+        assert!(patcher.find_string(Table::Values, "alpha").is_some());
+        let root = patcher.database().record(&guid).unwrap();
+        let tag = root.get_array("tags").unwrap().next().unwrap();
+        assert_eq!(tag.as_str(), Some("xy"));
+    }
+
+    /// Three items whose grade, tier and label all start out as `Common`.
+    fn build_graded() -> (Vec<u8>, [CigGuid; 3]) {
+        let mut builder = DataCoreBuilder::new();
+        let grade = builder.add_enum("Grade", &["Common", "Rare"]);
+        let tier = builder.add_enum("Tier", &["Common", "High"]);
+        let item = builder.add_struct("Item", None);
+        builder.add_enum_property(item, "grade", grade);
+        builder.add_enum_property(item, "tier", tier);
+        builder.add_property(item, "label", DataType::String);
+
+        let guids = [CigGuid::random(), CigGuid::random(), CigGuid::random()];
+        for (i, guid) in guids.iter().enumerate() {
+            let name = format!("Item{}", i + 1);
+            let file_name = format!("libs/{}.xml", name);
+            let record = builder.add_record_with_guid(&name, item, &file_name, *guid);
+            builder.set_enum(record, "grade", "Common").unwrap();
+            builder.set_enum(record, "tier", "Common").unwrap();
+            builder.set_string(record, "label", "Common");
+        }
+        (builder.build().unwrap(), guids)
+    }
+
+    #[test]
+    fn test_rename_enum_option() {
+        let (data, [first, second, third]) = build_graded();
+        let len = data.len();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+
+        patcher
+            .set_record_property(&third, "grade", "Rare")
+            .unwrap();
+        assert_eq!(
+            patcher
+                .rename_enum_option("Grade", "Common", "Standard")
+                .unwrap(),
+            2
+        );
+
+        // Renamed options apply before the database is parsed again
+        patcher
+            .set_record_property(&third, "grade", "Standard")
+            .unwrap();
+        assert!(patcher
+            .set_record_property(&third, "grade", "Common")
+            .is_err());
+
+        assert!(patcher
+            .rename_enum_option("Grade", "Common", "Basic")
+            .is_err());
+        assert!(patcher
+            .rename_enum_option("Grade", "Rare", "Standard")
+            .is_err());
+        assert!(patcher
+            .rename_enum_option("Missing", "Common", "Basic")
+            .is_err());
+
+        // The new option is added to both the name and the value table, next
+        // to the first value of the other option
+        patcher.commit().unwrap();
+        let added = "Rare\0".len() + 2 * "Standard\0".len();
+        assert_eq!(patcher.as_bytes().len(), len + added);
+
+        let db = patcher.database();
+        let options = |i: usize| db.enum_options(&db.enum_definitions()[i]);
+        assert_eq!(options(0), ["Standard", "Rare"]);
+        assert_eq!(options(1), ["Common", "High"]);
+        for guid in [first, second, third] {
+            let record = db.record(&guid).unwrap();
+            assert_eq!(record.get_str("grade"), Some("Standard"));
+            assert_eq!(record.get_str("tier"), Some("Common"));
+            assert_eq!(record.get_str("label"), Some("Common"));
+        }
+    }
+
+    /// Turn a version 6 file into a version 5 one, whose names share string
+    /// table 1 with the values.
+    fn to_v5(data: Vec<u8>) -> Vec<u8> {
+        let patcher = DataCorePatcher::new(data).unwrap();
+        let (_, table_1_len) = patcher.db.string_table_layout().0;
+        let (_, table_2_len) = patcher.db.string_table_layout().1;
+        let mut slots = Vec::new();
+        patcher.collect_name_slots(&mut slots);
+
+        // Table 2 directly follows table 1, so only the ids move
+        let mut patcher = patcher;
+        for slot in slots {
+            let id = patcher.read_i32(slot.offset);
+            patcher.write_i32(slot.offset, id + table_1_len as i32);
+        }
+        let mut data = patcher.data;
+        data[4..8].copy_from_slice(&5u32.to_le_bytes());
+        let text_length = (table_1_len + table_2_len) as u32;
+        data[TEXT_LENGTH_1_OFFSET..TEXT_LENGTH_1_OFFSET + 4]
+            .copy_from_slice(&text_length.to_le_bytes());
+        data[TEXT_LENGTH_2_OFFSET..TEXT_LENGTH_2_OFFSET + 4].fill(0);
+        data
+    }
+
+    #[test]
+    fn test_v5_shared_string_table() {
+        let (data, [first, second, _]) = build_graded();
+        let data = to_v5(data);
+        let len = data.len();
+        let mut patcher = DataCorePatcher::new(data).unwrap();
+        assert_eq!(patcher.database().version(), 5);
+
+        // The new option is added once for both its name and its values
+        assert_eq!(
+            patcher
+                .rename_enum_option("Grade", "Common", "Standard")
+                .unwrap(),
+            3
+        );
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes().len(), len + "Standard\0".len());
+
+        // Values reuse names, so replacing a string renames the struct too
+        patcher
+            .set_record_property(&first, "label", "Item")
+            .unwrap();
+        assert_eq!(patcher.replace_string("Item", "Gear").unwrap(), 2);
+        patcher.commit().unwrap();
+        assert_eq!(patcher.as_bytes().len(), len + "Standard\0".len());
+
+        let db = patcher.database();
+        assert_eq!(db.version(), 5);
+        assert_eq!(
+            db.enum_options(&db.enum_definitions()[0]),
+            ["Standard", "Rare"]
+        );
+        let record = db.record(&first).unwrap();
+        assert_eq!(record.type_name(), Some("Gear"));
+        assert_eq!(record.get_str("label"), Some("Gear"));
+        assert_eq!(record.get_str("grade"), Some("Standard"));
+        let record = db.record(&second).unwrap();
+        assert_eq!(record.get_str("label"), Some("Common"));
+        assert_eq!(record.get_str("tier"), Some("Common"));
+    }
+}
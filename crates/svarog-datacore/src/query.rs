@@ -11,7 +11,7 @@ use svarog_common::CigGuid;
 
 use crate::instance::{Instance, Record};
 use crate::structs::DataCoreRecord;
-use crate::DataCoreDatabase;
+use crate::{DataCoreDatabase, Value};
 
 type FxHashMap<K, V> = FastHashMap<K, V, BuildHasherDefault<FxHasher>>;
 
@@ -44,6 +44,16 @@ impl DataCoreDatabase {
         self.get_record(guid).map(|r| Record::new(self, r))
     }
 
+    /// Get a record by its GUID string, e.g. `"04b6b41e-2d4b-4a62-a52f-2c8bd6f0c1e7"`.
+    ///
+    /// See [`get_record_by_guid_str`](Self::get_record_by_guid_str) for the
+    /// accepted forms.
+    #[inline]
+    pub fn record_by_guid_str(&self, guid: &str) -> Option<Record<'_>> {
+        self.get_record_by_guid_str(guid)
+            .map(|r| Record::new(self, r))
+    }
+
     /// Get an instance by struct and instance index.
     ///
     /// This is useful when you have an `InstanceRef` from a property value.
@@ -52,6 +62,38 @@ impl DataCoreDatabase {
         Instance::new(self, struct_index, instance_index)
     }
 
+    /// Resolve a property value to the instance it points at.
+    ///
+    /// Inline classes and strong and weak pointers resolve to their target
+    /// instance, record references to the referenced record's instance.
+    /// Returns `None` for null pointers, unknown records and all other values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use svarog_datacore::DataCoreDatabase;
+    ///
+    /// let db = DataCoreDatabase::open("Game.dcb")?;
+    ///
+    /// if let Some(record) = db.record_by_name("MyRecord") {
+    ///     for prop in record.properties() {
+    ///         if let Some(target) = db.resolve(&prop.value) {
+    ///             println!("{} -> {}", prop.name, target.type_name().unwrap_or("?"));
+    ///         }
+    ///     }
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn resolve(&self, value: &Value<'_>) -> Option<Instance<'_>> {
+        match value {
+            Value::Reference(Some(target)) => {
+                let record = self.get_record(&target.guid)?;
+                Some(self.instance(record.struct_index as u32, record.instance_index as u32))
+            }
+            _ => Some(value.as_instance()?.resolve(self)),
+        }
+    }
+
     /// Iterate over all records in the database.
     ///
     /// # Example
@@ -159,14 +201,8 @@ impl DataCoreDatabase {
 
     /// Find records by file name/path.
     pub fn records_by_file<'a>(&'a self, file_name: &'a str) -> impl Iterator<Item = Record<'a>> {
-        self.records().iter().filter_map(move |r| {
-            let record_file = self.record_file_name(r)?;
-            if record_file == file_name {
-                Some(Record::new(self, r))
-            } else {
-                None
-            }
-        })
+        self.records_in_file(file_name)
+            .map(move |r| Record::new(self, r))
     }
 
     /// Get a list of all unique struct type names in the database.
@@ -337,7 +373,7 @@ impl<'a> Iterator for QueryIterator<'a> {
             let record = self.records.next()?;
 
             // Main record filter
-            if self.main_only && !self.database.is_main_record(&record.id) {
+            if self.main_only && !self.database.is_main_record(record) {
                 continue;
             }
 
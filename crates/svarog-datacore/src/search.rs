@@ -0,0 +1,298 @@
+//! Substring search over records.
+//!
+//! Matches a query against record names, struct type names and file paths,
+//! and optionally against every property value of a record, flattened the
+//! same way as for [`DataCoreDiff`](crate::DataCoreDiff). A [`SearchIndex`]
+//! keeps that text around for interactive use, where the same database is
+//! searched again for every keystroke.
+
+use crate::diff::flatten_record;
+use crate::{DataCoreDatabase, RecordSummary};
+
+/// Options for [`DataCoreDatabase::search`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    /// Also match property values. Every record is flattened, so this is
+    /// much slower than searching names alone.
+    pub properties: bool,
+    /// Match letter case exactly.
+    pub case_sensitive: bool,
+}
+
+/// The part of a record a query matched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub enum SearchField {
+    /// The record name.
+    Name,
+    /// The struct type name.
+    Type,
+    /// The file the record belongs to.
+    File,
+    /// A property value, with its dotted path, e.g. `ports[2].name`.
+    Property(String),
+}
+
+/// A record matching a search query.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct SearchHit {
+    /// The matching record.
+    pub record: RecordSummary,
+    /// Where the query matched.
+    pub field: SearchField,
+    /// The matching text.
+    pub value: String,
+}
+
+impl DataCoreDatabase {
+    /// Find records whose name, type, file or (optionally) property values
+    /// contain `query`.
+    ///
+    /// Hits are returned in record order, one per matching field.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use svarog_datacore::{DataCoreDatabase, SearchOptions};
+    ///
+    /// let db = DataCoreDatabase::open("Game.dcb")?;
+    /// for hit in db.search("FS-9", &SearchOptions::default()) {
+    ///     println!("{} ({:?})", hit.record, hit.field);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Vec<SearchHit> {
+        let needle = if options.case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        let matches = |haystack: &str| {
+            if options.case_sensitive {
+                haystack.contains(&needle)
+            } else {
+                haystack.to_lowercase().contains(&needle)
+            }
+        };
+
+        let mut hits = Vec::new();
+        for record in self.records() {
+            let summary = RecordSummary::new(self, record);
+            let mut fields = Vec::new();
+            if matches(&summary.name) {
+                fields.push((SearchField::Name, summary.name.clone()));
+            }
+            if matches(&summary.type_name) {
+                fields.push((SearchField::Type, summary.type_name.clone()));
+            }
+            if matches(&summary.file_name) {
+                fields.push((SearchField::File, summary.file_name.clone()));
+            }
+            if options.properties {
+                // Record metadata (`@name`) and array bookkeeping
+                // (`tags.@count`) are not property values
+                let leaves = flatten_record(self, record)
+                    .into_iter()
+                    .filter(|(path, value)| {
+                        !path.starts_with('@') && !path.contains(".@") && matches(value)
+                    });
+                fields.extend(leaves.map(|(path, value)| (SearchField::Property(path), value)));
+            }
+
+            hits.extend(fields.into_iter().map(|(field, value)| SearchHit {
+                record: summary.clone(),
+                field,
+                value,
+            }));
+        }
+        hits
+    }
+}
+
+/// Record text prepared for repeated searches.
+///
+/// Building flattens every record once, so each query only scans memory
+/// instead of walking the database again. With
+/// [`properties`](SearchOptions::properties) set, numeric and boolean values
+/// are left out: they make up most leaves of a record and are rarely what a
+/// text search is after.
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    case_sensitive: bool,
+    records: Vec<RecordSummary>,
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexEntry {
+    record: usize,
+    field: SearchField,
+    value: String,
+    /// Lowercase `value` for case-insensitive indexes, when it differs
+    folded: Option<String>,
+}
+
+impl SearchIndex {
+    /// Index the records of `database`.
+    pub fn build(database: &DataCoreDatabase, options: &SearchOptions) -> Self {
+        let mut records = Vec::with_capacity(database.records().len());
+        let mut entries = Vec::new();
+        for record in database.records() {
+            let summary = RecordSummary::new(database, record);
+            let mut fields = vec![
+                (SearchField::Name, summary.name.clone()),
+                (SearchField::Type, summary.type_name.clone()),
+                (SearchField::File, summary.file_name.clone()),
+            ];
+            if options.properties {
+                // Metadata and array counts are skipped, as in `search`
+                for (path, value) in flatten_record(database, record) {
+                    if !path.starts_with('@') && !path.contains(".@") && is_text(&value) {
+                        fields.push((SearchField::Property(path), value));
+                    }
+                }
+            }
+
+            let index = records.len();
+            entries.extend(fields.into_iter().map(|(field, value)| {
+                let folded = if options.case_sensitive {
+                    None
+                } else {
+                    Some(value.to_lowercase()).filter(|folded| *folded != value)
+                };
+                IndexEntry {
+                    record: index,
+                    field,
+                    value,
+                    folded,
+                }
+            }));
+            records.push(summary);
+        }
+
+        Self {
+            case_sensitive: options.case_sensitive,
+            records,
+            entries,
+        }
+    }
+
+    /// Find records whose indexed text contains `query`, returning at most
+    /// `limit` hits in record order, one per matching field.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let needle = if self.case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .folded
+                    .as_deref()
+                    .unwrap_or(&entry.value)
+                    .contains(&needle)
+            })
+            .take(limit)
+            .map(|entry| SearchHit {
+                record: self.records[entry.record].clone(),
+                field: entry.field.clone(),
+                value: entry.value.clone(),
+            })
+            .collect()
+    }
+
+    /// Number of indexed records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the index holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+/// Whether a flattened value is worth indexing for text search.
+fn is_text(value: &str) -> bool {
+    !value.is_empty() && value != "true" && value != "false" && value.parse::<f64>().is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCoreBuilder, DataType};
+
+    #[test]
+    fn test_search() {
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("WeaponGun", None);
+        builder.add_property(weapon, "displayName", DataType::String);
+        let record = builder.add_record("KLWE_LaserRepeater_S3", weapon, "weapons/klwe_s3.xml");
+        builder.set_string(record, "displayName", "Attrition-3 Repeater");
+        let record = builder.add_record("GATS_BallisticGatling_S3", weapon, "weapons/gats_s3.xml");
+        builder.set_string(record, "displayName", "FS-9 LMG");
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let hits = db.search("klwe", &SearchOptions::default());
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].field, SearchField::Name);
+        assert_eq!(hits[1].field, SearchField::File);
+
+        assert!(db.search("FS-9", &SearchOptions::default()).is_empty());
+        let options = SearchOptions {
+            properties: true,
+            ..SearchOptions::default()
+        };
+        let hits = db.search("fs-9", &options);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].record.name, "GATS_BallisticGatling_S3");
+        assert_eq!(
+            hits[0].field,
+            SearchField::Property("displayName".to_string())
+        );
+        assert_eq!(hits[0].value, "FS-9 LMG");
+
+        let options = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        assert_eq!(db.search("weapongun", &options).len(), 0);
+        assert_eq!(db.search("WeaponGun", &options).len(), 2);
+    }
+
+    #[test]
+    fn test_search_index() {
+        let mut builder = DataCoreBuilder::new();
+        let weapon = builder.add_struct("WeaponGun", None);
+        builder.add_property(weapon, "displayName", DataType::String);
+        builder.add_property(weapon, "size", DataType::Int32);
+        let record = builder.add_record("GATS_BallisticGatling_S3", weapon, "weapons/gats_s3.xml");
+        builder.set_string(record, "displayName", "FS-9 LMG");
+        builder.set_i32(record, "size", 3);
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+
+        let options = SearchOptions {
+            properties: true,
+            ..SearchOptions::default()
+        };
+        let index = SearchIndex::build(&db, &options);
+        assert_eq!(index.len(), 1);
+
+        let hits = index.search("fs-9", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].field,
+            SearchField::Property("displayName".to_string())
+        );
+        assert_eq!(hits[0].value, "FS-9 LMG");
+
+        // "g" is in the name, type and file, of which the limit keeps two
+        assert_eq!(index.search("g", 2).len(), 2);
+        // Numbers are not indexed
+        let size = SearchField::Property("size".to_string());
+        assert!(index.search("3", 10).iter().all(|hit| hit.field != size));
+    }
+}
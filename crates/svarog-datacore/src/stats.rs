@@ -0,0 +1,453 @@
+//! Database statistics for reverse engineering and tuning.
+//!
+//! [`DataCoreDatabase::statistics`] summarizes the size of every section of a
+//! database: value pools, instance data and string tables, together with
+//! record counts per type, the deepest inheritance chains and the largest
+//! arrays.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::DataCoreDatabase;
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let stats = db.statistics();
+//! println!("{} records in {} bytes", stats.record_count, stats.file_bytes);
+//! print!("{}", stats);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::structs::{DataCorePropertyDefinition, DataCoreStringId, DataCoreStringId2};
+use crate::{DataCoreDatabase, DataType, PoolType};
+
+/// Number of entries kept in the ranked lists of [`DatabaseStatistics`].
+const TOP_COUNT: usize = 10;
+
+/// Summary of a database's contents and memory use.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct DatabaseStatistics {
+    /// DCB format version.
+    pub version: u32,
+    /// Size of the database file.
+    pub file_bytes: usize,
+    pub struct_count: usize,
+    pub property_count: usize,
+    pub enum_count: usize,
+    pub record_count: usize,
+    /// Records that are the first record of their file.
+    pub main_record_count: usize,
+    /// Struct instances in the data section.
+    pub instance_count: usize,
+    /// Size of the data section.
+    pub instance_bytes: usize,
+    /// Value pools, in file order.
+    pub pools: Vec<PoolStatistics>,
+    /// String tables 1 (values and file names) and 2 (names).
+    ///
+    /// Version 5 databases have a single table; its usage is reported as
+    /// table 1 and table 2 is empty.
+    pub string_tables: [StringTableStatistics; 2],
+    /// Heap bytes used by interned strings after parsing.
+    pub string_cache_bytes: usize,
+    /// Record count per struct type, largest first.
+    pub records_by_type: Vec<(String, usize)>,
+    /// Deepest inheritance chains, each from the derived type up to its root.
+    pub inheritance_chains: Vec<Vec<String>>,
+    /// Arrays with the most elements, largest first.
+    pub largest_arrays: Vec<ArrayStatistics>,
+}
+
+/// Size of a value pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct PoolStatistics {
+    pub pool: PoolType,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+/// Occupancy of a string table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct StringTableStatistics {
+    /// Size of the table.
+    pub bytes: usize,
+    /// Strings stored in the table.
+    pub strings: usize,
+    /// Distinct strings referenced by the database.
+    pub referenced: usize,
+    /// Bytes taken by referenced strings, including terminators.
+    pub referenced_bytes: usize,
+}
+
+/// An array property value of one instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
+pub struct ArrayStatistics {
+    /// Struct type of the instance holding the array.
+    pub struct_name: String,
+    pub instance_index: usize,
+    pub property: String,
+    pub element_type: DataType,
+    pub count: usize,
+}
+
+impl DatabaseStatistics {
+    /// Serialize the statistics as pretty-printed JSON.
+    #[cfg(feature = "json-export")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl DataCoreDatabase {
+    /// Collect statistics about the database.
+    ///
+    /// This reads every instance once, so it takes about as long as a full
+    /// walk of the data section.
+    pub fn statistics(&self) -> DatabaseStatistics {
+        let structs = self.struct_definitions();
+
+        let mut stats = DatabaseStatistics {
+            version: self.version(),
+            file_bytes: self.data_len(),
+            struct_count: structs.len(),
+            property_count: self.property_definitions().len(),
+            enum_count: self.enum_definitions().len(),
+            record_count: self.records().len(),
+            main_record_count: self.main_record_count(),
+            ..Default::default()
+        };
+
+        stats.pools = PoolType::ALL
+            .iter()
+            .map(|&pool| {
+                let (_, count, elem_size) = self.pool_layout(pool);
+                PoolStatistics {
+                    pool,
+                    count,
+                    bytes: count * elem_size,
+                }
+            })
+            .collect();
+
+        // Record counts per type
+        let mut counts = vec![0usize; structs.len()];
+        for record in self.records() {
+            if let Some(count) = counts.get_mut(record.struct_index as usize) {
+                *count += 1;
+            }
+        }
+        stats.records_by_type = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(i, &count)| (self.struct_name(i).unwrap_or("").to_string(), count))
+            .collect();
+        stats
+            .records_by_type
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        stats.inheritance_chains = self.inheritance_chains();
+
+        // Instances: array sizes and string references
+        let mut values = HashSet::new();
+        for mapping in self.data_mappings() {
+            let struct_index = mapping.struct_index as usize;
+            let Some(def) = structs.get(struct_index) else {
+                continue;
+            };
+            stats.instance_count += mapping.struct_count as usize;
+            stats.instance_bytes += mapping.struct_count as usize * def.struct_size as usize;
+
+            let mut fields = Vec::new();
+            self.collect_fields(struct_index, &mut fields);
+
+            for instance_index in 0..mapping.struct_count as usize {
+                let mut reader = self.get_instance_reader(struct_index, instance_index);
+                for &(prop, data_type, is_array) in &fields {
+                    if is_array {
+                        let count = reader.read_i32().unwrap_or(0).max(0) as usize;
+                        reader.advance(4);
+                        let arrays = &mut stats.largest_arrays;
+                        if count > 0
+                            && (arrays.len() < TOP_COUNT || count > arrays[TOP_COUNT - 1].count)
+                        {
+                            arrays.push(ArrayStatistics {
+                                struct_name: self
+                                    .struct_name(struct_index)
+                                    .unwrap_or("")
+                                    .to_string(),
+                                instance_index,
+                                property: self.property_name(prop).unwrap_or("").to_string(),
+                                element_type: data_type,
+                                count,
+                            });
+                            arrays.sort_by_key(|a| Reverse(a.count));
+                            arrays.truncate(TOP_COUNT);
+                        }
+                    } else if matches!(
+                        data_type,
+                        DataType::String | DataType::Locale | DataType::EnumChoice
+                    ) {
+                        values.insert(reader.read_i32().unwrap_or(-1));
+                    } else {
+                        reader.advance(data_type.inline_size());
+                    }
+                }
+            }
+        }
+
+        // String references outside of instances
+        for record in self.records() {
+            values.insert(record.file_name_offset.id());
+        }
+        for (pool, value) in [
+            (
+                PoolType::StringId,
+                Self::string_id_value as fn(&Self, usize) -> Option<DataCoreStringId>,
+            ),
+            (PoolType::Locale, Self::locale_value),
+            (PoolType::EnumValue, Self::enum_value),
+        ] {
+            let (_, count, _) = self.pool_layout(pool);
+            values.extend((0..count).filter_map(|i| value(self, i)).map(|id| id.id()));
+        }
+
+        let mut names: HashSet<i32> = HashSet::new();
+        names.extend(structs.iter().map(|s| s.name_offset.id()));
+        names.extend(
+            self.property_definitions()
+                .iter()
+                .map(|p| p.name_offset.id()),
+        );
+        names.extend(self.enum_definitions().iter().map(|e| e.name_offset.id()));
+        names.extend(self.records().iter().map(|r| r.name_offset.id()));
+        let (_, option_count, _) = self.pool_layout(PoolType::EnumOption);
+        names.extend(
+            (0..option_count)
+                .filter_map(|i| self.enum_option_value(i))
+                .map(|id| id.id()),
+        );
+
+        let (string_cache_bytes, strings_1, strings_2) = self.string_cache_usage();
+        let ((_, table_1_len), (_, table_2_len)) = self.string_table_layout();
+        stats.string_cache_bytes = string_cache_bytes;
+
+        let value_bytes = |ids: &HashSet<i32>| -> (usize, usize) {
+            let bytes = ids
+                .iter()
+                .filter_map(|&id| self.get_string(&DataCoreStringId::new(id)))
+                .map(|s| s.len() + 1);
+            (ids.len(), bytes.sum())
+        };
+        let name_bytes = |ids: &HashSet<i32>| -> (usize, usize) {
+            let bytes = ids
+                .iter()
+                .filter_map(|&id| self.get_string2(&DataCoreStringId2::new(id)))
+                .map(|s| s.len() + 1);
+            (ids.len(), bytes.sum())
+        };
+
+        if self.version() >= 6 {
+            let (referenced, referenced_bytes) = value_bytes(&values);
+            stats.string_tables[0] = StringTableStatistics {
+                bytes: table_1_len,
+                strings: strings_1,
+                referenced,
+                referenced_bytes,
+            };
+            let (referenced, referenced_bytes) = name_bytes(&names);
+            stats.string_tables[1] = StringTableStatistics {
+                bytes: table_2_len,
+                strings: strings_2,
+                referenced,
+                referenced_bytes,
+            };
+        } else {
+            values.extend(names);
+            let (referenced, referenced_bytes) = value_bytes(&values);
+            stats.string_tables[0] = StringTableStatistics {
+                bytes: table_1_len,
+                strings: strings_1,
+                referenced,
+                referenced_bytes,
+            };
+        }
+
+        stats
+    }
+
+    /// Properties of a struct in instance order, with inline classes
+    /// flattened into their properties.
+    fn collect_fields<'a>(
+        &'a self,
+        struct_index: usize,
+        fields: &mut Vec<(&'a DataCorePropertyDefinition, DataType, bool)>,
+    ) {
+        for prop in self.get_struct_properties(struct_index) {
+            let Some(data_type) = DataType::from_u16(prop.data_type) else {
+                continue;
+            };
+            let is_array = prop.conversion_type != 0;
+            if data_type == DataType::Class && !is_array {
+                self.collect_fields(prop.struct_index as usize, fields);
+            } else {
+                fields.push((prop, data_type, is_array));
+            }
+        }
+    }
+
+    /// The deepest inheritance chains, from the derived type to its root.
+    fn inheritance_chains(&self) -> Vec<Vec<String>> {
+        let structs = self.struct_definitions();
+        let chain = |mut index: i32| {
+            let mut chain = Vec::new();
+            // Bounded by the struct count in case of a malformed cycle
+            while index >= 0 && chain.len() <= structs.len() {
+                let Some(def) = structs.get(index as usize) else {
+                    break;
+                };
+                chain.push(index as usize);
+                index = def.parent_type_index;
+            }
+            chain
+        };
+
+        let mut depths: Vec<(usize, usize)> = (0..structs.len())
+            .map(|i| (chain(i as i32).len(), i))
+            .collect();
+        depths.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        depths
+            .into_iter()
+            .take(TOP_COUNT)
+            .map(|(_, i)| {
+                chain(i as i32)
+                    .into_iter()
+                    .map(|s| self.struct_name(s).unwrap_or("").to_string())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for DatabaseStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version:    {}", self.version)?;
+        writeln!(f, "File size:  {} bytes", self.file_bytes)?;
+        writeln!(
+            f,
+            "Structs:    {} ({} properties), {} enums",
+            self.struct_count, self.property_count, self.enum_count
+        )?;
+        writeln!(
+            f,
+            "Records:    {} ({} main)",
+            self.record_count, self.main_record_count
+        )?;
+        writeln!(
+            f,
+            "Instances:  {} ({} bytes)",
+            self.instance_count, self.instance_bytes
+        )?;
+
+        writeln!(f, "\nPools:")?;
+        for pool in self.pools.iter().filter(|p| p.count > 0) {
+            writeln!(
+                f,
+                "  {:<12} {:>10} values {:>12} bytes",
+                format!("{:?}", pool.pool),
+                pool.count,
+                pool.bytes
+            )?;
+        }
+
+        writeln!(f, "\nString tables:")?;
+        for (i, table) in self.string_tables.iter().enumerate() {
+            if table.bytes == 0 && table.strings == 0 {
+                continue;
+            }
+            let occupancy = if table.bytes > 0 {
+                table.referenced_bytes as f64 * 100.0 / table.bytes as f64
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "  Table {}: {} bytes, {} strings, {} referenced ({:.1}% of bytes)",
+                i + 1,
+                table.bytes,
+                table.strings,
+                table.referenced,
+                occupancy
+            )?;
+        }
+        writeln!(f, "  Interned: {} bytes", self.string_cache_bytes)?;
+
+        writeln!(f, "\nRecords by type:")?;
+        for (name, count) in self.records_by_type.iter().take(TOP_COUNT) {
+            writeln!(f, "  {:>8}  {}", count, name)?;
+        }
+        if self.records_by_type.len() > TOP_COUNT {
+            writeln!(
+                f,
+                "  ... {} more types",
+                self.records_by_type.len() - TOP_COUNT
+            )?;
+        }
+
+        writeln!(f, "\nDeepest inheritance chains:")?;
+        for chain in &self.inheritance_chains {
+            writeln!(f, "  {:>3}  {}", chain.len(), chain.join(" -> "))?;
+        }
+
+        writeln!(f, "\nLargest arrays:")?;
+        for array in &self.largest_arrays {
+            writeln!(
+                f,
+                "  {:>8}  {}[{}].{} ({})",
+                array.count,
+                array.struct_name,
+                array.instance_index,
+                array.property,
+                array.element_type.as_str()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::DataCoreBuilder;
+
+    #[test]
+    fn test_statistics() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_array_property(item, "names", DataType::String);
+        let derived = builder.add_struct("Weapon", Some(item));
+        builder.add_record("weapon", derived, "weapons/rifle.xml");
+        let record = builder.add_record("item", item, "items/item.xml");
+        builder.set_string_array(record, "names", &["a", "b", "c"]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let stats = db.statistics();
+
+        assert_eq!(stats.record_count, 2);
+        assert_eq!(stats.main_record_count, 2);
+        assert_eq!(stats.pools.len(), PoolType::ALL.len());
+        assert_eq!(stats.inheritance_chains[0], vec!["Weapon", "Item"]);
+        assert_eq!(stats.largest_arrays.len(), 1);
+        assert_eq!(stats.largest_arrays[0].property, "names");
+        assert_eq!(stats.largest_arrays[0].count, 3);
+        assert!(stats.string_tables[0].referenced >= 5);
+        assert!(format!("{}", stats).contains("Weapon -> Item"));
+    }
+}
@@ -26,6 +26,13 @@ pub struct DataCoreRecord {
     pub struct_size: u16,
 }
 
+impl AsRef<CigGuid> for DataCoreRecord {
+    #[inline]
+    fn as_ref(&self) -> &CigGuid {
+        &self.id
+    }
+}
+
 /// Mapping of struct type to data location.
 ///
 /// This tells us where the data for instances of a struct type is stored.
@@ -0,0 +1,497 @@
+//! Tabular export of DataCore records.
+//!
+//! [`TableExporter`] flattens every record of a struct type into one row of a
+//! [`Table`], with one column per scalar property. Inline classes are expanded
+//! into dotted column names (`stats.damage`); arrays and pointers are left
+//! out. Records of derived types are included with the columns of the
+//! requested type.
+//!
+//! Tables can be written as CSV, or as Parquet with the `parquet` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, TableExporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let table = TableExporter::new(&db).table_by_name("AmmoParams").unwrap();
+//!
+//! let mut file = std::fs::File::create("ammo.csv")?;
+//! table.write_csv(&mut file)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::io::Write;
+
+use svarog_common::BinaryReader;
+
+use crate::instance::read_single_value;
+use crate::{DataCoreDatabase, DataType, LocaleResolver, Result, Value};
+
+/// A column of a [`Table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableColumn {
+    /// Property path, with inline classes separated by dots.
+    pub name: String,
+    /// Type of the values in the column.
+    pub data_type: DataType,
+}
+
+/// Records of one struct type flattened into rows.
+///
+/// The first two columns are `RecordName` and `RecordId`. Every row has one
+/// value per column; references are `Value::Reference` and everything else is
+/// a scalar value.
+#[derive(Debug, Clone)]
+pub struct Table<'a> {
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<Vec<Value<'a>>>,
+}
+
+/// Exporter flattening records of a struct type into a [`Table`].
+pub struct TableExporter<'a> {
+    database: &'a DataCoreDatabase,
+    locale: Option<&'a LocaleResolver>,
+}
+
+impl<'a> TableExporter<'a> {
+    /// Create a new table exporter.
+    pub fn new(database: &'a DataCoreDatabase) -> Self {
+        Self {
+            database,
+            locale: None,
+        }
+    }
+
+    /// Resolve `Locale` values to localized text instead of their keys.
+    pub fn with_locale(mut self, locale: &'a LocaleResolver) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// Columns of the table for a struct type.
+    pub fn columns(&self, struct_index: usize) -> Vec<TableColumn> {
+        let mut columns = vec![
+            TableColumn {
+                name: "RecordName".to_string(),
+                data_type: DataType::String,
+            },
+            TableColumn {
+                name: "RecordId".to_string(),
+                data_type: DataType::Guid,
+            },
+        ];
+        self.collect_columns(struct_index, "", &mut columns);
+        columns
+    }
+
+    /// Build the table of all records whose type is, or derives from, a struct.
+    pub fn table(&self, struct_index: usize) -> Table<'a> {
+        let columns = self.columns(struct_index);
+
+        let rows = self
+            .database
+            .records()
+            .iter()
+            .filter(|r| self.derives_from(r.struct_index, struct_index))
+            .map(|record| {
+                let mut row = Vec::with_capacity(columns.len());
+                row.push(Value::String(
+                    self.database.record_name(record).unwrap_or(""),
+                ));
+                row.push(Value::Guid(record.id));
+
+                let mut reader = self.database.get_instance_reader(
+                    record.struct_index as usize,
+                    record.instance_index as usize,
+                );
+                self.read_row(struct_index, &mut reader, &mut row);
+                row
+            })
+            .collect();
+
+        Table { columns, rows }
+    }
+
+    /// Build the table for a struct type by name.
+    pub fn table_by_name(&self, type_name: &str) -> Option<Table<'a>> {
+        let struct_index = (0..self.database.struct_definitions().len())
+            .find(|&i| self.database.struct_name(i) == Some(type_name))?;
+        Some(self.table(struct_index))
+    }
+
+    fn derives_from(&self, mut struct_index: i32, ancestor: usize) -> bool {
+        while struct_index >= 0 {
+            if struct_index as usize == ancestor {
+                return true;
+            }
+            struct_index = match self
+                .database
+                .struct_definitions()
+                .get(struct_index as usize)
+            {
+                Some(def) => def.parent_type_index,
+                None => return false,
+            };
+        }
+        false
+    }
+
+    fn collect_columns(&self, struct_index: usize, prefix: &str, columns: &mut Vec<TableColumn>) {
+        for prop in self.database.get_struct_properties(struct_index) {
+            let Some(data_type) = DataType::from_u16(prop.data_type) else {
+                continue;
+            };
+            if prop.conversion_type != 0 {
+                continue;
+            }
+
+            let name = format!(
+                "{}{}",
+                prefix,
+                self.database.property_name(prop).unwrap_or("unknown")
+            );
+            match data_type {
+                DataType::Class => {
+                    self.collect_columns(
+                        prop.struct_index as usize,
+                        &format!("{}.", name),
+                        columns,
+                    );
+                }
+                DataType::StrongPointer | DataType::WeakPointer => {}
+                _ => columns.push(TableColumn { name, data_type }),
+            }
+        }
+    }
+
+    /// Read the column values of an instance, in the order of [`collect_columns`](Self::collect_columns).
+    fn read_row(
+        &self,
+        struct_index: usize,
+        reader: &mut BinaryReader<'_>,
+        row: &mut Vec<Value<'a>>,
+    ) {
+        for prop in self.database.get_struct_properties(struct_index) {
+            let Some(data_type) = DataType::from_u16(prop.data_type) else {
+                continue;
+            };
+
+            if prop.conversion_type != 0 {
+                // Array header
+                reader.advance(8);
+                continue;
+            }
+
+            match data_type {
+                DataType::Class => self.read_row(prop.struct_index as usize, reader, row),
+                DataType::StrongPointer | DataType::WeakPointer => {
+                    reader.advance(data_type.inline_size())
+                }
+                _ => {
+                    let value = read_single_value(
+                        self.database,
+                        data_type,
+                        prop.struct_index as u32,
+                        reader,
+                    )
+                    .unwrap_or(Value::Null);
+                    row.push(match (value, self.locale) {
+                        (Value::Locale(key), Some(locale)) => Value::Locale(locale.resolve(key)),
+                        (value, _) => value,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Table<'_> {
+    /// Write the table as CSV with a header row.
+    ///
+    /// References are written as GUIDs and null references as empty cells.
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let header: Vec<&str> = self.columns.iter().map(|c| c.name.as_str()).collect();
+        write_csv_row(writer, header.iter().copied())?;
+
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(cell_text).collect();
+            write_csv_row(writer, cells.iter().map(String::as_str))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the table as a Parquet file with one row group.
+    ///
+    /// Integers keep their width and signedness, floats map to `FLOAT` and
+    /// `DOUBLE`, and strings, enums, GUIDs and references are UTF-8 strings.
+    /// References are the only nullable columns.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: Write + Send>(&self, writer: W) -> Result<()> {
+        parquet_writer::write(self, writer).map_err(|e| crate::Error::Export(e.to_string()))
+    }
+}
+
+fn cell_text(value: &Value<'_>) -> String {
+    match value {
+        Value::Reference(Some(r)) => r.guid.to_string(),
+        Value::Reference(None) | Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+pub(crate) fn write_csv_row<'s, W: Write>(
+    writer: &mut W,
+    cells: impl Iterator<Item = &'s str>,
+) -> Result<()> {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")?;
+    Ok(())
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_writer {
+    use std::io::Write;
+    use std::sync::Arc;
+
+    use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType};
+    use parquet::data_type::{
+        BoolType, ByteArray, ByteArrayType, DoubleType, FloatType, Int32Type, Int64Type,
+    };
+    use parquet::errors::Result;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::types::Type;
+
+    use super::{cell_text, Table};
+    use crate::{DataType, Value};
+
+    fn physical_type(data_type: DataType) -> (PhysicalType, Option<LogicalType>) {
+        let integer = |bit_width, is_signed| {
+            Some(LogicalType::Integer {
+                bit_width,
+                is_signed,
+            })
+        };
+        match data_type {
+            DataType::Boolean => (PhysicalType::BOOLEAN, None),
+            DataType::SByte => (PhysicalType::INT32, integer(8, true)),
+            DataType::Int16 => (PhysicalType::INT32, integer(16, true)),
+            DataType::Int32 => (PhysicalType::INT32, integer(32, true)),
+            DataType::Byte => (PhysicalType::INT32, integer(8, false)),
+            DataType::UInt16 => (PhysicalType::INT32, integer(16, false)),
+            DataType::UInt32 => (PhysicalType::INT32, integer(32, false)),
+            DataType::Int64 => (PhysicalType::INT64, integer(64, true)),
+            DataType::UInt64 => (PhysicalType::INT64, integer(64, false)),
+            DataType::Single => (PhysicalType::FLOAT, None),
+            DataType::Double => (PhysicalType::DOUBLE, None),
+            _ => (PhysicalType::BYTE_ARRAY, Some(LogicalType::String)),
+        }
+    }
+
+    pub(super) fn write<W: Write + Send>(table: &Table<'_>, writer: W) -> Result<()> {
+        let fields = table
+            .columns
+            .iter()
+            .map(|column| {
+                let (physical, logical) = physical_type(column.data_type);
+                let repetition = if column.data_type == DataType::Reference {
+                    Repetition::OPTIONAL
+                } else {
+                    Repetition::REQUIRED
+                };
+                Type::primitive_type_builder(&column.name, physical)
+                    .with_logical_type(logical)
+                    .with_repetition(repetition)
+                    .build()
+                    .map(Arc::new)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let schema = Type::group_type_builder("schema")
+            .with_fields(fields)
+            .build()?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(Default::default()))
+            .build();
+
+        let mut file = SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(properties))?;
+        let mut row_group = file.next_row_group()?;
+
+        for (index, column) in table.columns.iter().enumerate() {
+            let Some(mut writer) = row_group.next_column()? else {
+                break;
+            };
+            let cells = table.rows.iter().map(|row| &row[index]);
+
+            match physical_type(column.data_type).0 {
+                PhysicalType::BOOLEAN => {
+                    let values: Vec<bool> = cells.map(|v| matches!(v, Value::Bool(true))).collect();
+                    writer
+                        .typed::<BoolType>()
+                        .write_batch(&values, None, None)?;
+                }
+                PhysicalType::INT32 => {
+                    let values: Vec<i32> = cells
+                        .map(|v| match *v {
+                            Value::Int8(v) => v as i32,
+                            Value::Int16(v) => v as i32,
+                            Value::Int32(v) => v,
+                            Value::UInt8(v) => v as i32,
+                            Value::UInt16(v) => v as i32,
+                            // Stored as the bit pattern of the unsigned value
+                            Value::UInt32(v) => v as i32,
+                            _ => 0,
+                        })
+                        .collect();
+                    writer
+                        .typed::<Int32Type>()
+                        .write_batch(&values, None, None)?;
+                }
+                PhysicalType::INT64 => {
+                    let values: Vec<i64> = cells
+                        .map(|v| match *v {
+                            Value::Int64(v) => v,
+                            Value::UInt64(v) => v as i64,
+                            _ => 0,
+                        })
+                        .collect();
+                    writer
+                        .typed::<Int64Type>()
+                        .write_batch(&values, None, None)?;
+                }
+                PhysicalType::FLOAT => {
+                    let values: Vec<f32> = cells.map(|v| v.as_f32().unwrap_or_default()).collect();
+                    writer
+                        .typed::<FloatType>()
+                        .write_batch(&values, None, None)?;
+                }
+                PhysicalType::DOUBLE => {
+                    let values: Vec<f64> = cells.map(|v| v.as_f64().unwrap_or_default()).collect();
+                    writer
+                        .typed::<DoubleType>()
+                        .write_batch(&values, None, None)?;
+                }
+                _ if column.data_type == DataType::Reference => {
+                    let mut levels = Vec::with_capacity(table.rows.len());
+                    let mut values = Vec::new();
+                    for cell in cells {
+                        let present = matches!(cell, Value::Reference(Some(_)));
+                        levels.push(present as i16);
+                        if present {
+                            values.push(ByteArray::from(cell_text(cell).as_str()));
+                        }
+                    }
+                    writer
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, Some(&levels), None)?;
+                }
+                _ => {
+                    let values: Vec<ByteArray> = cells
+                        .map(|v| ByteArray::from(cell_text(v).as_str()))
+                        .collect();
+                    writer
+                        .typed::<ByteArrayType>()
+                        .write_batch(&values, None, None)?;
+                }
+            }
+
+            writer.close()?;
+        }
+
+        row_group.close()?;
+        file.close()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+    use svarog_common::CigGuid;
+
+    fn build() -> Vec<u8> {
+        let mut builder = DataCoreBuilder::new();
+        let stats = builder.add_struct("Stats", None);
+        builder.add_property(stats, "damage", DataType::Single);
+        let weapon = builder.add_struct("Weapon", None);
+        builder.add_property(weapon, "name", DataType::String);
+        builder.add_typed_property(weapon, "stats", DataType::Class, stats);
+        builder.add_array_property(weapon, "tags", DataType::String);
+        builder.add_typed_property(weapon, "ammo", DataType::Reference, weapon);
+        let rifle = builder.add_struct("Rifle", Some(weapon));
+        builder.add_property(rifle, "scoped", DataType::Boolean);
+
+        let ammo_id = CigGuid::random();
+        let pistol = builder.add_record_with_guid("Pistol", weapon, "weapons/pistol.xml", ammo_id);
+        builder.set_string(pistol, "name", "P4, \"Arrowhead\"");
+        builder.set_string_array(pistol, "tags", &["sidearm"]);
+        let hit = builder.add_instance(stats);
+        builder.set_float(hit, "damage", 12.5);
+        builder.set_class(pistol, "stats", hit).unwrap();
+
+        let sniper = builder.add_record("Sniper", rifle, "weapons/sniper.xml");
+        builder.set_string(sniper, "name", "Sniper");
+        builder.set_reference(sniper, "ammo", ammo_id);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_table_csv() {
+        let db = DataCoreDatabase::parse(&build()).unwrap();
+        let table = TableExporter::new(&db).table_by_name("Weapon").unwrap();
+
+        let names: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["RecordName", "RecordId", "name", "stats.damage", "ammo"]
+        );
+        assert_eq!(table.rows.len(), 2);
+
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        let pistol_id = db.record_by_name("Pistol").unwrap().id();
+        assert_eq!(lines[0], "RecordName,RecordId,name,stats.damage,ammo");
+        assert_eq!(
+            lines[1],
+            format!("Pistol,{},\"P4, \"\"Arrowhead\"\"\",12.5,", pistol_id)
+        );
+        assert!(
+            lines[2].starts_with("Sniper,")
+                && lines[2].ends_with(&format!(",Sniper,0,{}", pistol_id))
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_table_parquet() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let db = DataCoreDatabase::parse(&build()).unwrap();
+        let table = TableExporter::new(&db).table_by_name("Weapon").unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("svarog-table-{}.parquet", std::process::id()));
+        table
+            .write_parquet(std::fs::File::create(&path).unwrap())
+            .unwrap();
+
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        assert_eq!(metadata.file_metadata().schema_descr().num_columns(), 5);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -4,6 +4,7 @@
 ///
 /// The values are the actual binary values from the DCB file format.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json-export", derive(serde::Serialize))]
 #[repr(u16)]
 pub enum DataType {
     /// Boolean value.
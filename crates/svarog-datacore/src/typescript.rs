@@ -0,0 +1,296 @@
+//! TypeScript definition export for DataCore schema.
+//!
+//! This module generates a `.d.ts` file with an interface per DataCore struct
+//! and a string-literal union per enum, describing the JSON shape of exported
+//! records. Struct inheritance maps directly onto `interface ... extends ...`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_datacore::{DataCoreDatabase, TypeScriptExporter};
+//!
+//! let db = DataCoreDatabase::open("Game.dcb")?;
+//! let exporter = TypeScriptExporter::new(&db);
+//!
+//! std::fs::write("datacore.d.ts", exporter.export_all())?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use crate::structs::DataCorePropertyDefinition;
+use crate::{DataCoreDatabase, DataType};
+
+/// TypeScript preamble with the DataCore helper types.
+pub const TS_PREAMBLE: &str = r#"/*
+ * DataCore Schema Export
+ * Auto-generated TypeScript definitions for Star Citizen game data structures
+ */
+
+/** DataCore GUID in its canonical string form. */
+export type Guid = string;
+
+/** Reference to another record by GUID. */
+export type Reference<T> = Guid & { readonly __target?: T };
+
+/** Non-owning pointer to a struct instance. */
+export interface WeakPointer<T> {
+  struct_index: number;
+  instance_index: number;
+  readonly __target?: T;
+}
+"#;
+
+/// Exporter for generating TypeScript definitions from DataCore schema.
+pub struct TypeScriptExporter<'a> {
+    db: &'a DataCoreDatabase,
+}
+
+impl<'a> TypeScriptExporter<'a> {
+    /// Create a new TypeScript exporter.
+    pub fn new(db: &'a DataCoreDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Export all structs and enums to a `.d.ts` string.
+    pub fn export_all(&self) -> String {
+        let structs = (0..self.db.struct_definitions().len()).collect();
+        let enums = (0..self.db.enum_definitions().len()).collect();
+        self.render(&structs, &enums)
+    }
+
+    /// Export specific structs (and every type they refer to) to a `.d.ts` string.
+    pub fn export_structs(&self, struct_indices: &[usize]) -> String {
+        let (structs, enums) = self.collect_types(struct_indices);
+        self.render(&structs, &enums)
+    }
+
+    fn render(&self, structs: &BTreeSet<usize>, enums: &BTreeSet<usize>) -> String {
+        let mut buf = String::new();
+        buf.push_str(TS_PREAMBLE);
+
+        for &idx in enums {
+            buf.push('\n');
+            buf.push_str(&self.generate_enum(idx));
+        }
+
+        for &idx in structs {
+            buf.push('\n');
+            buf.push_str(&self.generate_interface(idx));
+        }
+
+        buf
+    }
+
+    /// Collect the given structs plus their parents and all types they use.
+    fn collect_types(&self, roots: &[usize]) -> (BTreeSet<usize>, BTreeSet<usize>) {
+        let mut structs = BTreeSet::new();
+        let mut enums = BTreeSet::new();
+        let mut stack: Vec<usize> = roots.to_vec();
+
+        while let Some(idx) = stack.pop() {
+            let Some(def) = self.db.struct_definitions().get(idx) else {
+                continue;
+            };
+            if !structs.insert(idx) {
+                continue;
+            }
+            if def.parent_type_index >= 0 {
+                stack.push(def.parent_type_index as usize);
+            }
+
+            for prop in self.db.get_struct_properties(idx) {
+                match DataType::from_u16(prop.data_type) {
+                    Some(
+                        DataType::Class
+                        | DataType::StrongPointer
+                        | DataType::WeakPointer
+                        | DataType::Reference,
+                    ) => stack.push(prop.struct_index as usize),
+                    Some(DataType::EnumChoice)
+                        if self.db.enum_name(prop.struct_index as usize).is_some() =>
+                    {
+                        enums.insert(prop.struct_index as usize);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        (structs, enums)
+    }
+
+    /// Generate a string-literal union for an enum.
+    fn generate_enum(&self, enum_index: usize) -> String {
+        let Some(def) = self.db.enum_definitions().get(enum_index) else {
+            return String::new();
+        };
+        let name = self.db.enum_name(enum_index).unwrap_or("Unknown");
+        let values = self.db.enum_options(def);
+
+        let union = if values.is_empty() {
+            "never".to_string()
+        } else {
+            values
+                .iter()
+                .map(|v| quote(v))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+
+        format!("export type {} = {};\n", ts_ident(name), union)
+    }
+
+    /// Generate an interface with the struct's own properties.
+    fn generate_interface(&self, struct_index: usize) -> String {
+        let mut out = String::new();
+        let Some(def) = self.db.struct_definitions().get(struct_index) else {
+            return out;
+        };
+        let name = ts_ident(self.db.struct_name(struct_index).unwrap_or("Unknown"));
+
+        let extends = if def.parent_type_index >= 0 {
+            let parent = self
+                .db
+                .struct_name(def.parent_type_index as usize)
+                .unwrap_or("Unknown");
+            format!(" extends {}", ts_ident(parent))
+        } else {
+            String::new()
+        };
+
+        let _ = writeln!(out, "export interface {}{} {{", name, extends);
+
+        let start = def.first_attribute_index as usize;
+        let end = start + def.attribute_count as usize;
+        for prop in self
+            .db
+            .property_definitions()
+            .get(start..end)
+            .unwrap_or(&[])
+        {
+            let raw_name = self.db.property_name(prop).unwrap_or("unknown");
+            let _ = writeln!(
+                out,
+                "  {}: {};",
+                property_key(raw_name),
+                self.describe_type(prop)
+            );
+        }
+
+        let _ = writeln!(out, "}}");
+
+        out
+    }
+
+    /// Describe a property's TypeScript type.
+    fn describe_type(&self, prop: &DataCorePropertyDefinition) -> String {
+        let struct_idx = prop.struct_index as usize;
+        let Some(dt) = DataType::from_u16(prop.data_type) else {
+            return "unknown".to_string();
+        };
+        let target = || ts_ident(self.db.struct_name(struct_idx).unwrap_or("Unknown"));
+
+        let element = match dt {
+            DataType::Boolean => "boolean".to_string(),
+            DataType::SByte
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Byte
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Single
+            | DataType::Double => "number".to_string(),
+            DataType::String | DataType::Locale => "string".to_string(),
+            DataType::Guid => "Guid".to_string(),
+            DataType::EnumChoice => match self.db.enum_name(struct_idx) {
+                Some(name) => ts_ident(name),
+                None => "string".to_string(),
+            },
+            DataType::Class => target(),
+            DataType::StrongPointer => format!("{} | null", target()),
+            DataType::WeakPointer => format!("WeakPointer<{}> | null", target()),
+            DataType::Reference => format!("Reference<{}> | null", target()),
+        };
+
+        match (prop.is_array(), element.contains(' ')) {
+            (true, true) => format!("({})[]", element),
+            (true, false) => format!("{}[]", element),
+            (false, _) => element,
+        }
+    }
+}
+
+/// Sanitize a name into a valid TypeScript type identifier.
+fn ts_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+/// Use the property name as-is when it is a valid identifier, quoted otherwise.
+fn property_key(name: &str) -> String {
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataCoreBuilder;
+
+    #[test]
+    fn test_generate_interfaces() {
+        let mut builder = DataCoreBuilder::new();
+        let base = builder.add_struct("Base", None);
+        builder.add_property(base, "name", DataType::String);
+        let item = builder.add_struct("Item", Some(base));
+        builder.add_property(item, "mass", DataType::Single);
+        builder.add_property(item, "item-id", DataType::Guid);
+        builder.add_typed_property(item, "parent", DataType::Reference, item);
+        builder.add_array_property(item, "tags", DataType::String);
+        builder.add_enum("Grade", &["A", "B"]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let exporter = TypeScriptExporter::new(&db);
+        let ts = exporter.export_structs(&[1]);
+
+        assert!(ts.contains("export interface Base {\n  name: string;\n}"));
+        assert!(ts.contains("export interface Item extends Base {"));
+        assert!(ts.contains("  mass: number;"));
+        assert!(ts.contains("  \"item-id\": Guid;"));
+        assert!(ts.contains("  parent: Reference<Item> | null;"));
+        assert!(ts.contains("  tags: string[];"));
+        assert!(!ts.contains("Grade"));
+
+        assert!(exporter
+            .export_all()
+            .contains("export type Grade = \"A\" | \"B\";"));
+    }
+}
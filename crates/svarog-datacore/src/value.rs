@@ -5,11 +5,14 @@
 
 use svarog_common::CigGuid;
 
+use crate::{ArrayIterator, DataCoreDatabase, Instance};
+
 /// A type-safe value from the DataCore database.
 ///
 /// This enum represents all possible values that can be stored in DataCore properties,
 /// providing safe access to the underlying data without exposing raw binary readers.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value<'a> {
     /// Boolean value.
     Bool(bool),
@@ -59,6 +62,7 @@ pub enum Value<'a> {
 ///
 /// This is a lightweight handle that can be used to access the actual instance data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InstanceRef {
     /// Index of the struct type.
     pub struct_index: u32,
@@ -72,10 +76,17 @@ impl InstanceRef {
     pub fn new(struct_index: u32, instance_index: u32) -> Self {
         Self { struct_index, instance_index }
     }
+
+    /// Get the referenced instance from the database.
+    #[inline]
+    pub fn resolve(self, database: &DataCoreDatabase) -> Instance<'_> {
+        database.instance(self.struct_index, self.instance_index)
+    }
 }
 
 /// Reference to a record by GUID.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RecordRef {
     /// The GUID of the referenced record.
     pub guid: CigGuid,
@@ -93,6 +104,7 @@ impl RecordRef {
 ///
 /// Arrays in DataCore are stored in value pools with a count and first index.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayRef {
     /// The data type of array elements.
     pub element_type: ArrayElementType,
@@ -104,8 +116,17 @@ pub struct ArrayRef {
     pub first_index: u32,
 }
 
+impl ArrayRef {
+    /// Iterate over the array's elements.
+    #[inline]
+    pub fn iter(self, database: &DataCoreDatabase) -> ArrayIterator<'_> {
+        ArrayIterator::new(database, self)
+    }
+}
+
 /// Element type for arrays.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ArrayElementType {
     Bool,
     Int8,
@@ -227,6 +248,13 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Try to get this value as a UUID.
+    #[cfg(feature = "uuid")]
+    #[inline]
+    pub fn as_uuid(&self) -> Option<uuid::Uuid> {
+        self.as_guid().map(uuid::Uuid::from)
+    }
+
     /// Try to get this value as an instance reference (for Class types).
     #[inline]
     pub fn as_instance(&self) -> Option<InstanceRef> {
@@ -256,6 +284,19 @@ impl<'a> Value<'a> {
     }
 }
 
+impl From<CigGuid> for Value<'_> {
+    fn from(guid: CigGuid) -> Self {
+        Value::Guid(guid)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Value<'_> {
+    fn from(uuid: uuid::Uuid) -> Self {
+        Value::Guid(uuid.into())
+    }
+}
+
 impl std::fmt::Display for Value<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -284,3 +325,34 @@ impl std::fmt::Display for Value<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataCoreBuilder, DataType};
+
+    #[test]
+    fn test_array_iter() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_array_property(item, "weights", DataType::Single);
+        let record = builder.add_record("Root", item, "libs/root.xml");
+        builder.set_float_array(record, "weights", &[1.0, 2.5]);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let root = db.record(&db.records()[0].id).unwrap();
+        let Some(Value::Array(array)) = root.get("weights") else {
+            panic!("expected an array");
+        };
+        let weights: Vec<_> = array.iter(&db).collect();
+        assert_eq!(weights, [Value::Float(1.0), Value::Float(2.5)]);
+
+        // Elements past the end of the value pool read as defaults
+        let outside = ArrayRef {
+            first_index: 100,
+            ..array
+        };
+        assert_eq!(outside.iter(&db).len(), 2);
+        assert!(outside.iter(&db).all(|value| value == Value::Float(0.0)));
+    }
+}
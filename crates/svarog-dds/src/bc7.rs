@@ -0,0 +1,331 @@
+//! BC7 block decoding.
+//!
+//! Implements all eight BC7 modes as described in the Direct3D 11 block
+//! compression specification. Reserved blocks (mode byte zero) decode to
+//! transparent black.
+
+use crate::bcn::Block;
+
+/// Per-mode layout.
+struct Mode {
+    /// Number of subsets.
+    subsets: usize,
+    /// Partition selector bits.
+    partition_bits: u32,
+    /// Rotation bits.
+    rotation_bits: u32,
+    /// Index selection bits.
+    index_selection_bits: u32,
+    /// Color endpoint bits per channel.
+    color_bits: u32,
+    /// Alpha endpoint bits (0 if alpha is always 255).
+    alpha_bits: u32,
+    /// One p-bit per endpoint.
+    endpoint_pbits: bool,
+    /// One p-bit per subset, shared by both endpoints.
+    shared_pbits: bool,
+    /// Primary index bits.
+    index_bits: u32,
+    /// Secondary index bits (modes 4 and 5).
+    index2_bits: u32,
+}
+
+#[rustfmt::skip]
+const MODES: [Mode; 8] = [
+    Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 3, index2_bits: 0 },
+    Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_pbits: false, shared_pbits: true, index_bits: 3, index2_bits: 0 },
+    Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+    Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+    Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 3 },
+    Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 2 },
+    Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_pbits: true, shared_pbits: false, index_bits: 4, index2_bits: 0 },
+    Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+];
+
+/// Two-subset partitions; bit `i` set means pixel `i` is in subset 1.
+#[rustfmt::skip]
+const PARTITIONS_2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80,
+    0xC800, 0xFFEC, 0xFE80, 0xE800, 0xFFE8, 0xFF00, 0xFFF0, 0xF000,
+    0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE,
+    0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C,
+    0xAAAA, 0xF0F0, 0x5A5A, 0x33CC, 0x3C3C, 0x55AA, 0x9696, 0xA55A,
+    0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C,
+    0x9336, 0x9CC6, 0x817E, 0xE718, 0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// Three-subset partitions, two bits per pixel (pixel 0 in the low bits).
+#[rustfmt::skip]
+const PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+/// Anchor index of subset 1 in two-subset partitions.
+#[rustfmt::skip]
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15,  2,  8,  2,  2,  8,  8, 15,  2,  8,  2,  2,  8,  8,  2,  2,
+    15, 15,  6,  8,  2,  8, 15, 15,  2,  8,  2,  2,  2, 15, 15,  6,
+     6,  2,  6,  8, 15, 15,  2,  2, 15, 15, 15, 15, 15,  2,  2, 15,
+];
+
+/// Anchor index of subset 1 in three-subset partitions.
+#[rustfmt::skip]
+const ANCHORS_3_1: [u8; 64] = [
+     3,  3, 15, 15,  8,  3, 15, 15,  8,  8,  6,  6,  6,  5,  3,  3,
+     3,  3,  8, 15,  3,  3,  6, 10,  5,  8,  8,  6,  8,  5, 15, 15,
+     8, 15,  3,  5,  6, 10,  8, 15, 15,  3, 15,  5, 15, 15, 15, 15,
+     3, 15,  5,  5,  5,  8,  5, 10,  5, 10,  8, 13, 15, 12,  3,  3,
+];
+
+/// Anchor index of subset 2 in three-subset partitions.
+#[rustfmt::skip]
+const ANCHORS_3_2: [u8; 64] = [
+    15,  8,  8,  3, 15, 15,  3,  8, 15, 15, 15, 15, 15, 15, 15,  8,
+    15,  8, 15,  3, 15,  8, 15,  8,  3, 15,  6, 10, 15, 15, 10,  8,
+    15,  3, 15, 10, 10,  8,  9, 10,  6, 15,  8, 15,  3,  6,  6,  8,
+    15,  3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,  3, 15, 15,  8,
+];
+
+const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Little-endian bit reader over a 16-byte block.
+struct Bits(u128, u32);
+
+impl Bits {
+    fn read(&mut self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        let value = (self.0 >> self.1) as u32 & ((1u32 << count) - 1);
+        self.1 += count;
+        value
+    }
+}
+
+fn subset_of(subsets: usize, partition: usize, pixel: usize) -> usize {
+    match subsets {
+        2 => ((PARTITIONS_2[partition] >> pixel) & 1) as usize,
+        3 => ((PARTITIONS_3[partition] >> (2 * pixel)) & 3) as usize,
+        _ => 0,
+    }
+}
+
+fn is_anchor(subsets: usize, partition: usize, pixel: usize) -> bool {
+    pixel == 0
+        || match subsets {
+            2 => pixel == ANCHORS_2[partition] as usize,
+            3 => {
+                pixel == ANCHORS_3_1[partition] as usize || pixel == ANCHORS_3_2[partition] as usize
+            }
+            _ => false,
+        }
+}
+
+/// Expand an endpoint of `bits` bits (4 to 8) to 8 bits.
+fn unquantize(value: u32, bits: u32) -> u8 {
+    let value = value << (8 - bits);
+    (value | (value >> bits)) as u8
+}
+
+fn interpolate(e0: u8, e1: u8, index: u32, index_bits: u32) -> u8 {
+    let weight = match index_bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    };
+    (((64 - weight) * e0 as u32 + weight * e1 as u32 + 32) >> 6) as u8
+}
+
+/// Decode one 16-byte BC7 block.
+pub(crate) fn decode_bc7(block: &[u8]) -> Block {
+    let mut bits = Bits(u128::from_le_bytes(block[..16].try_into().unwrap()), 0);
+
+    let mode_index = block[0].trailing_zeros() as usize;
+    if mode_index >= MODES.len() {
+        return [[0; 4]; 16];
+    }
+    let mode = &MODES[mode_index];
+    bits.read(mode_index as u32 + 1);
+
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    // Endpoints: [subset * 2 + endpoint][channel], raw values
+    let endpoint_count = mode.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+    for channel in 0..3 {
+        for endpoint in endpoints.iter_mut().take(endpoint_count) {
+            endpoint[channel] = bits.read(mode.color_bits);
+        }
+    }
+    for endpoint in endpoints.iter_mut().take(endpoint_count) {
+        endpoint[3] = bits.read(mode.alpha_bits);
+    }
+
+    // P-bits extend every channel by one low bit
+    let mut color_bits = mode.color_bits;
+    let mut alpha_bits = mode.alpha_bits;
+    if mode.endpoint_pbits || mode.shared_pbits {
+        let mut pbits = [0u32; 6];
+        if mode.endpoint_pbits {
+            for pbit in pbits.iter_mut().take(endpoint_count) {
+                *pbit = bits.read(1);
+            }
+        } else {
+            for subset in 0..mode.subsets {
+                let pbit = bits.read(1);
+                pbits[subset * 2] = pbit;
+                pbits[subset * 2 + 1] = pbit;
+            }
+        }
+        for (endpoint, pbit) in endpoints.iter_mut().zip(pbits).take(endpoint_count) {
+            for (channel, value) in endpoint.iter_mut().enumerate() {
+                if channel < 3 || mode.alpha_bits > 0 {
+                    *value = (*value << 1) | pbit;
+                }
+            }
+        }
+        color_bits += 1;
+        if alpha_bits > 0 {
+            alpha_bits += 1;
+        }
+    }
+
+    let mut colors = [[0u8; 4]; 6];
+    for (color, endpoint) in colors.iter_mut().zip(&endpoints).take(endpoint_count) {
+        for channel in 0..3 {
+            color[channel] = unquantize(endpoint[channel], color_bits);
+        }
+        color[3] = if alpha_bits > 0 {
+            unquantize(endpoint[3], alpha_bits)
+        } else {
+            255
+        };
+    }
+
+    // Indices; the anchor of each subset has an implicit leading zero bit
+    let mut indices = [0u32; 16];
+    for (pixel, index) in indices.iter_mut().enumerate() {
+        let anchor = is_anchor(mode.subsets, partition, pixel);
+        *index = bits.read(mode.index_bits - anchor as u32);
+    }
+    let mut indices2 = [0u32; 16];
+    if mode.index2_bits > 0 {
+        for (pixel, index) in indices2.iter_mut().enumerate() {
+            *index = bits.read(mode.index2_bits - (pixel == 0) as u32);
+        }
+    }
+
+    let mut out = [[0u8; 4]; 16];
+    for (pixel, out) in out.iter_mut().enumerate() {
+        let subset = subset_of(mode.subsets, partition, pixel);
+        let (e0, e1) = (colors[subset * 2], colors[subset * 2 + 1]);
+
+        let (color_index, color_index_bits, alpha_index, alpha_index_bits) =
+            if mode.index2_bits == 0 {
+                (
+                    indices[pixel],
+                    mode.index_bits,
+                    indices[pixel],
+                    mode.index_bits,
+                )
+            } else if index_selection == 0 {
+                (
+                    indices[pixel],
+                    mode.index_bits,
+                    indices2[pixel],
+                    mode.index2_bits,
+                )
+            } else {
+                (
+                    indices2[pixel],
+                    mode.index2_bits,
+                    indices[pixel],
+                    mode.index_bits,
+                )
+            };
+
+        for channel in 0..3 {
+            out[channel] = interpolate(e0[channel], e1[channel], color_index, color_index_bits);
+        }
+        out[3] = interpolate(e0[3], e1[3], alpha_index, alpha_index_bits);
+
+        match rotation {
+            1 => out.swap(0, 3),
+            2 => out.swap(1, 3),
+            3 => out.swap(2, 3),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pack `(value, bit count)` fields into a block, LSB first.
+    fn pack(fields: &[(u32, u32)]) -> [u8; 16] {
+        let mut bits = 0u128;
+        let mut offset = 0;
+        for &(value, count) in fields {
+            bits |= (value as u128) << offset;
+            offset += count;
+        }
+        assert_eq!(offset, 128);
+        bits.to_le_bytes()
+    }
+
+    #[test]
+    fn test_partition_tables_match_anchors() {
+        for p in 0..64 {
+            assert_eq!(subset_of(2, p, ANCHORS_2[p] as usize), 1);
+            assert_eq!(subset_of(3, p, ANCHORS_3_1[p] as usize), 1);
+            assert_eq!(subset_of(3, p, ANCHORS_3_2[p] as usize), 2);
+        }
+    }
+
+    #[test]
+    fn test_mode6_solid() {
+        // Mode 6: all endpoints 127 with p-bit 1, i.e. 255
+        let mut fields = vec![(1 << 6, 7)];
+        fields.extend([(127, 7), (127, 7)]); // R
+        fields.extend([(127, 7), (127, 7)]); // G
+        fields.extend([(127, 7), (127, 7)]); // B
+        fields.extend([(127, 7), (127, 7)]); // A
+        fields.extend([(1, 1), (1, 1)]); // p-bits
+        fields.push((7, 3)); // anchor index
+        fields.extend([(15, 4); 15]);
+        let out = decode_bc7(&pack(&fields));
+        assert!(out.iter().all(|p| *p == [255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_mode5_rotation() {
+        // Mode 5, rotation 1 (swap R and A): red endpoints 0, alpha 255
+        let mut fields = vec![(1 << 5, 6), (1, 2)];
+        fields.extend([(0, 7), (0, 7), (0, 7), (0, 7), (0, 7), (0, 7)]);
+        fields.extend([(255, 8), (255, 8)]);
+        fields.push((0, 31));
+        fields.push((0, 31));
+        let out = decode_bc7(&pack(&fields));
+        assert!(out.iter().all(|p| *p == [255, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_reserved_mode() {
+        assert_eq!(decode_bc7(&[0; 16]), [[0; 4]; 16]);
+    }
+}
@@ -0,0 +1,202 @@
+//! BC1-BC5 block decoding.
+//!
+//! Each function decodes one 4x4 block into 16 RGBA pixels in row-major
+//! order.
+
+/// Decoded 4x4 block, row-major RGBA.
+pub(crate) type Block = [[u8; 4]; 16];
+
+/// Expand a 5:6:5 color to 8 bits per channel.
+pub(crate) fn rgb565(c: u16) -> [u8; 3] {
+    let r = ((c >> 11) & 0x1F) as u8;
+    let g = ((c >> 5) & 0x3F) as u8;
+    let b = (c & 0x1F) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+/// The four colors a BC1 color block can select from.
+///
+/// `allow_alpha` enables the 3-color mode with transparent black, which
+/// only BC1 itself uses; BC2 and BC3 always use 4 colors.
+pub(crate) fn color_palette(c0: u16, c1: u16, allow_alpha: bool) -> [[u8; 4]; 4] {
+    let (e0, e1) = (rgb565(c0), rgb565(c1));
+
+    let mut palette = [[0u8; 4]; 4];
+    palette[0] = [e0[0], e0[1], e0[2], 255];
+    palette[1] = [e1[0], e1[1], e1[2], 255];
+    if c0 > c1 || !allow_alpha {
+        for i in 0..3 {
+            palette[2][i] = ((2 * e0[i] as u16 + e1[i] as u16) / 3) as u8;
+            palette[3][i] = ((e0[i] as u16 + 2 * e1[i] as u16) / 3) as u8;
+        }
+        palette[2][3] = 255;
+        palette[3][3] = 255;
+    } else {
+        for i in 0..3 {
+            palette[2][i] = ((e0[i] as u16 + e1[i] as u16) / 2) as u8;
+        }
+        palette[2][3] = 255;
+        palette[3] = [0, 0, 0, 0];
+    }
+    palette
+}
+
+/// Decode a BC1 color block into `out`.
+fn decode_color(block: &[u8], allow_alpha: bool, out: &mut Block) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let palette = color_palette(c0, c1, allow_alpha);
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = palette[((indices >> (2 * i)) & 0x3) as usize];
+    }
+}
+
+/// Decode an 8-byte BC3/BC4 interpolated channel block into 16 values.
+fn decode_channel(block: &[u8], signed: bool) -> [u8; 16] {
+    let mut bits = 0u64;
+    for (i, &b) in block[2..8].iter().enumerate() {
+        bits |= (b as u64) << (8 * i);
+    }
+
+    let palette: [i32; 8] = if signed {
+        let a0 = (block[0] as i8).max(-127) as i32;
+        let a1 = (block[1] as i8).max(-127) as i32;
+        interpolate_channel(a0, a1, -127, 127)
+    } else {
+        interpolate_channel(block[0] as i32, block[1] as i32, 0, 255)
+    };
+
+    let mut values = [0u8; 16];
+    for (i, value) in values.iter_mut().enumerate() {
+        let v = palette[((bits >> (3 * i)) & 0x7) as usize];
+        *value = if signed {
+            // Map -127..=127 to 0..=255
+            ((v + 127) * 255 / 254) as u8
+        } else {
+            v as u8
+        };
+    }
+    values
+}
+
+/// The eight values a BC3/BC4 channel block can select from.
+pub(crate) fn interpolate_channel(a0: i32, a1: i32, min: i32, max: i32) -> [i32; 8] {
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for (k, value) in (2i32..).zip(&mut palette[2..8]) {
+            *value = ((8 - k) * a0 + (k - 1) * a1) / 7;
+        }
+    } else {
+        for (k, value) in (2i32..).zip(&mut palette[2..6]) {
+            *value = ((6 - k) * a0 + (k - 1) * a1) / 5;
+        }
+        palette[6] = min;
+        palette[7] = max;
+    }
+    palette
+}
+
+/// BC1 (DXT1): 8 bytes, RGB with optional 1-bit alpha.
+pub(crate) fn decode_bc1(block: &[u8]) -> Block {
+    let mut out = [[0; 4]; 16];
+    decode_color(block, true, &mut out);
+    out
+}
+
+/// BC2 (DXT3): 8 bytes of explicit 4-bit alpha, then a color block.
+pub(crate) fn decode_bc2(block: &[u8]) -> Block {
+    let mut out = [[0; 4]; 16];
+    decode_color(&block[8..16], false, &mut out);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        let nibble = (block[i / 2] >> (4 * (i % 2))) & 0xF;
+        pixel[3] = nibble * 17;
+    }
+    out
+}
+
+/// BC3 (DXT5): an interpolated alpha block, then a color block.
+pub(crate) fn decode_bc3(block: &[u8]) -> Block {
+    let mut out = [[0; 4]; 16];
+    decode_color(&block[8..16], false, &mut out);
+    let alpha = decode_channel(&block[..8], false);
+    for (pixel, a) in out.iter_mut().zip(alpha) {
+        pixel[3] = a;
+    }
+    out
+}
+
+/// BC4: a single channel, decoded as grayscale.
+pub(crate) fn decode_bc4(block: &[u8], signed: bool) -> Block {
+    let values = decode_channel(&block[..8], signed);
+    let mut out = [[0; 4]; 16];
+    for (pixel, v) in out.iter_mut().zip(values) {
+        *pixel = [v, v, v, 255];
+    }
+    out
+}
+
+/// BC5: two channels, decoded as a tangent-space normal map with the blue
+/// channel reconstructed from red and green.
+pub(crate) fn decode_bc5(block: &[u8], signed: bool) -> Block {
+    let red = decode_channel(&block[..8], signed);
+    let green = decode_channel(&block[8..16], signed);
+    let mut out = [[0; 4]; 16];
+    for (i, pixel) in out.iter_mut().enumerate() {
+        let x = red[i] as f32 / 127.5 - 1.0;
+        let y = green[i] as f32 / 127.5 - 1.0;
+        let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+        let blue = ((z + 1.0) * 127.5).round() as u8;
+        *pixel = [red[i], green[i], blue, 255];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bc1_four_colors() {
+        // White and black endpoints, indices 0, 1, 2, 3 repeated per row
+        let block = [0xFF, 0xFF, 0x00, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let out = decode_bc1(&block);
+        assert_eq!(out[0], [255, 255, 255, 255]);
+        assert_eq!(out[1], [0, 0, 0, 255]);
+        assert_eq!(out[2], [170, 170, 170, 255]);
+        assert_eq!(out[3], [85, 85, 85, 255]);
+    }
+
+    #[test]
+    fn test_bc1_transparent() {
+        // c0 <= c1 selects 3-color mode; index 3 is transparent black
+        let block = [0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let out = decode_bc1(&block);
+        assert!(out.iter().all(|p| *p == [0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_bc3_alpha() {
+        // Alpha endpoints 255 and 0, all indices 1 (alpha 0); color white
+        let mut block = [0u8; 16];
+        block[0] = 255;
+        block[2..8].copy_from_slice(&[0x49, 0x92, 0x24, 0x49, 0x92, 0x24]);
+        block[8..10].copy_from_slice(&[0xFF, 0xFF]);
+        let out = decode_bc3(&block);
+        assert!(out.iter().all(|p| *p == [255, 255, 255, 0]));
+    }
+
+    #[test]
+    fn test_bc4_signed() {
+        // Endpoints -127 and 127 with index 0 and 1 alternating per row
+        let block = [0x81, 0x7F, 0x08, 0x82, 0x20, 0x08, 0x82, 0x20];
+        let out = decode_bc4(&block, true);
+        assert_eq!(out[0], [0, 0, 0, 255]);
+        assert_eq!(out[1], [255, 255, 255, 255]);
+    }
+}
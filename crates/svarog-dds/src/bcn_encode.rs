@@ -0,0 +1,320 @@
+//! BC1-BC5 and BC7 block encoding.
+//!
+//! Endpoints are fitted along the principal axis of each block's colors and
+//! every pixel takes the nearest palette entry. This is fast and good
+//! enough for edited textures, but no match for an offline compressor.
+//! BC7 blocks always use mode 6.
+
+use crate::bcn::{color_palette, interpolate_channel, Block};
+
+/// Fit a line through the first `channels` channels of `pixels` and return
+/// its two ends, clamped to 0..=255.
+fn fit_endpoints(pixels: &[[u8; 4]], channels: usize) -> ([f32; 4], [f32; 4]) {
+    let n = pixels.len() as f32;
+    let mut mean = [0f32; 4];
+    for p in pixels {
+        for c in 0..channels {
+            mean[c] += p[c] as f32 / n;
+        }
+    }
+
+    let mut cov = [[0f32; 4]; 4];
+    for p in pixels {
+        for i in 0..channels {
+            for j in 0..channels {
+                cov[i][j] += (p[i] as f32 - mean[i]) * (p[j] as f32 - mean[j]);
+            }
+        }
+    }
+
+    // Power iteration, starting from the channel with the largest variance
+    let start = (0..channels)
+        .max_by(|&a, &b| cov[a][a].total_cmp(&cov[b][b]))
+        .unwrap_or(0);
+    let mut axis = cov[start];
+    for _ in 0..8 {
+        let mut next = [0f32; 4];
+        for i in 0..channels {
+            for j in 0..channels {
+                next[i] += cov[i][j] * axis[j];
+            }
+        }
+        let len = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if len < 1e-6 {
+            return (mean, mean);
+        }
+        axis = next.map(|v| v / len);
+    }
+
+    let project = |p: &[u8; 4]| {
+        (0..channels)
+            .map(|c| (p[c] as f32 - mean[c]) * axis[c])
+            .sum::<f32>()
+    };
+    let (mut t_min, mut t_max) = (f32::MAX, f32::MIN);
+    for p in pixels {
+        let t = project(p);
+        t_min = t_min.min(t);
+        t_max = t_max.max(t);
+    }
+
+    let point = |t: f32| {
+        let mut out = [0f32; 4];
+        for c in 0..channels {
+            out[c] = (mean[c] + axis[c] * t).clamp(0.0, 255.0);
+        }
+        out
+    };
+    (point(t_min), point(t_max))
+}
+
+/// Squared distance over the first `channels` channels.
+fn distance(a: &[u8; 4], b: &[u8; 4], channels: usize) -> u32 {
+    (0..channels)
+        .map(|c| {
+            let d = a[c] as i32 - b[c] as i32;
+            (d * d) as u32
+        })
+        .sum()
+}
+
+/// Index of the palette entry nearest to `pixel`.
+fn nearest(pixel: &[u8; 4], palette: &[[u8; 4]], channels: usize) -> usize {
+    (0..palette.len())
+        .min_by_key(|&i| distance(pixel, &palette[i], channels))
+        .unwrap_or(0)
+}
+
+fn to_rgb565(c: [f32; 4]) -> u16 {
+    let r = (c[0] * 31.0 / 255.0).round() as u16;
+    let g = (c[1] * 63.0 / 255.0).round() as u16;
+    let b = (c[2] * 31.0 / 255.0).round() as u16;
+    (r << 11) | (g << 5) | b
+}
+
+/// Encode a BC1 color block.
+///
+/// With `allow_alpha`, pixels with alpha below 128 switch the block to the
+/// 3-color mode and become transparent black.
+fn encode_color(pixels: &Block, allow_alpha: bool) -> [u8; 8] {
+    let transparent = allow_alpha && pixels.iter().any(|p| p[3] < 128);
+    let opaque: Vec<[u8; 4]> = pixels
+        .iter()
+        .filter(|p| !transparent || p[3] >= 128)
+        .copied()
+        .collect();
+    if opaque.is_empty() {
+        // 3-color mode, every pixel index 3
+        return [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+    }
+
+    let (lo, hi) = fit_endpoints(&opaque, 3);
+    let (mut c0, mut c1) = (to_rgb565(hi), to_rgb565(lo));
+    // c0 > c1 selects the 4-color mode, c0 <= c1 the 3-color mode
+    if (c0 < c1) != transparent && c0 != c1 {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+
+    let palette = color_palette(c0, c1, allow_alpha);
+    let choices = if c0 > c1 || !allow_alpha { 4 } else { 3 };
+    let mut indices = 0u32;
+    for (i, p) in pixels.iter().enumerate() {
+        let index = if transparent && p[3] < 128 {
+            3
+        } else {
+            nearest(p, &palette[..choices], 3)
+        };
+        indices |= (index as u32) << (2 * i);
+    }
+
+    let mut out = [0u8; 8];
+    out[..2].copy_from_slice(&c0.to_le_bytes());
+    out[2..4].copy_from_slice(&c1.to_le_bytes());
+    out[4..].copy_from_slice(&indices.to_le_bytes());
+    out
+}
+
+/// Encode 16 values as a BC3 alpha / BC4 channel block.
+fn encode_channel(values: &[u8; 16]) -> [u8; 8] {
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+    // a0 > a1 selects 8 interpolated values; equal endpoints need none
+    let palette = interpolate_channel(max as i32, min as i32, 0, 255);
+
+    let mut bits = 0u64;
+    for (i, &v) in values.iter().enumerate() {
+        let index = (0..8)
+            .min_by_key(|&k| (palette[k] - v as i32).abs())
+            .unwrap_or(0);
+        bits |= (index as u64) << (3 * i);
+    }
+
+    let mut out = [0u8; 8];
+    out[0] = max;
+    out[1] = min;
+    out[2..].copy_from_slice(&bits.to_le_bytes()[..6]);
+    out
+}
+
+fn channel(pixels: &Block, c: usize) -> [u8; 16] {
+    pixels.map(|p| p[c])
+}
+
+/// BC1 (DXT1), with 1-bit alpha.
+pub(crate) fn encode_bc1(pixels: &Block) -> [u8; 8] {
+    encode_color(pixels, true)
+}
+
+/// BC3 (DXT5).
+pub(crate) fn encode_bc3(pixels: &Block) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    out[..8].copy_from_slice(&encode_channel(&channel(pixels, 3)));
+    out[8..].copy_from_slice(&encode_color(pixels, false));
+    out
+}
+
+/// BC4 (unsigned), from the red channel.
+pub(crate) fn encode_bc4(pixels: &Block) -> [u8; 8] {
+    encode_channel(&channel(pixels, 0))
+}
+
+/// BC5 (unsigned), from the red and green channels.
+pub(crate) fn encode_bc5(pixels: &Block) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    out[..8].copy_from_slice(&encode_channel(&channel(pixels, 0)));
+    out[8..].copy_from_slice(&encode_channel(&channel(pixels, 1)));
+    out
+}
+
+/// Mode 6 interpolation weights (4-bit indices).
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Quantize an endpoint to 7 bits per channel plus a shared p-bit.
+fn quantize_mode6(e: [f32; 4]) -> ([u8; 4], u8) {
+    let quantize = |p: u8| {
+        let q = e.map(|v| ((v - p as f32) / 2.0).round().clamp(0.0, 127.0) as u8);
+        let err: f32 = (0..4)
+            .map(|c| {
+                let d = (q[c] * 2 + p) as f32 - e[c];
+                d * d
+            })
+            .sum();
+        (q, err)
+    };
+    let (q0, err0) = quantize(0);
+    let (q1, err1) = quantize(1);
+    if err0 <= err1 {
+        (q0, 0)
+    } else {
+        (q1, 1)
+    }
+}
+
+/// BC7, always as mode 6 (one subset, RGBA endpoints, 4-bit indices).
+pub(crate) fn encode_bc7(pixels: &Block) -> [u8; 16] {
+    let (lo, hi) = fit_endpoints(pixels, 4);
+    let (mut q0, mut p0) = quantize_mode6(lo);
+    let (mut q1, mut p1) = quantize_mode6(hi);
+
+    let palette = |q0: [u8; 4], p0: u8, q1: [u8; 4], p1: u8| {
+        let e0 = q0.map(|v| (v * 2 + p0) as u32);
+        let e1 = q1.map(|v| (v * 2 + p1) as u32);
+        WEIGHTS_4.map(|w| {
+            let mut c = [0u8; 4];
+            for i in 0..4 {
+                c[i] = (((64 - w) * e0[i] + w * e1[i] + 32) >> 6) as u8;
+            }
+            c
+        })
+    };
+    let colors = palette(q0, p0, q1, p1);
+    let mut indices = pixels.map(|p| nearest(&p, &colors, 4));
+
+    // The anchor index drops its top bit, so it must be below 8
+    if indices[0] >= 8 {
+        std::mem::swap(&mut q0, &mut q1);
+        std::mem::swap(&mut p0, &mut p1);
+        indices = indices.map(|i| 15 - i);
+    }
+
+    let mut bits = 0u128;
+    let mut pos = 0;
+    let mut put = |value: u128, count: u32| {
+        bits |= value << pos;
+        pos += count;
+    };
+    put(1 << 6, 7);
+    for c in 0..4 {
+        put(q0[c] as u128, 7);
+        put(q1[c] as u128, 7);
+    }
+    put(p0 as u128, 1);
+    put(p1 as u128, 1);
+    for (i, &index) in indices.iter().enumerate() {
+        put(index as u128, if i == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bc7::decode_bc7;
+    use crate::bcn::{decode_bc1, decode_bc3, decode_bc5};
+
+    fn gradient() -> Block {
+        let mut pixels = [[0u8; 4]; 16];
+        for (i, p) in pixels.iter_mut().enumerate() {
+            let v = (i * 17) as u8;
+            *p = [v, 255 - v, v / 2, 255 - v / 3];
+        }
+        pixels
+    }
+
+    fn max_error(a: &Block, b: &Block, channels: usize) -> i32 {
+        a.iter()
+            .zip(b)
+            .flat_map(|(p, q)| (0..channels).map(move |c| (p[c] as i32 - q[c] as i32).abs()))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bc1_roundtrip() {
+        let pixels = gradient();
+        let decoded = decode_bc1(&encode_bc1(&pixels));
+        assert!(max_error(&pixels, &decoded, 3) <= 48);
+
+        let mut cutout = pixels;
+        cutout[5][3] = 0;
+        let decoded = decode_bc1(&encode_bc1(&cutout));
+        assert_eq!(decoded[5], [0, 0, 0, 0]);
+        assert_eq!(decoded[6][3], 255);
+    }
+
+    #[test]
+    fn test_bc3_bc5_roundtrip() {
+        let pixels = gradient();
+        let decoded = decode_bc3(&encode_bc3(&pixels));
+        assert!(max_error(&pixels, &decoded, 3) <= 48);
+        assert!(pixels
+            .iter()
+            .zip(&decoded)
+            .all(|(p, q)| (p[3] as i32 - q[3] as i32).abs() <= 6));
+
+        // 8 levels over the full range are ~36 apart
+        let decoded = decode_bc5(&encode_bc5(&pixels), false);
+        assert!(max_error(&pixels, &decoded, 2) <= 20);
+    }
+
+    #[test]
+    fn test_bc7_roundtrip() {
+        let pixels = gradient();
+        let decoded = decode_bc7(&encode_bc7(&pixels));
+        assert!(max_error(&pixels, &decoded, 4) <= 24);
+
+        let solid = [[200, 100, 50, 255]; 16];
+        let decoded = decode_bc7(&encode_bc7(&solid));
+        assert!(max_error(&solid, &decoded, 4) <= 1);
+    }
+}
@@ -0,0 +1,188 @@
+//! DDS decoding to RGBA images.
+
+use image::{Rgba, RgbaImage};
+
+use crate::bc7::decode_bc7;
+use crate::bcn::{decode_bc1, decode_bc2, decode_bc3, decode_bc4, decode_bc5, Block};
+use crate::format::DdsFormat;
+use crate::header::read_header;
+use crate::{Error, Result};
+
+/// Decode the top mipmap of a DDS file to an RGBA image.
+///
+/// For cubemaps, arrays and volume textures only the first surface is
+/// decoded. Split files must be merged with [`merge_dds`](crate::merge_dds)
+/// first, otherwise the full-size mipmap is missing.
+pub fn decode_to_rgba(data: &[u8]) -> Result<RgbaImage> {
+    let (header, dx10_header, header_size) = read_header(data)?;
+    let format = DdsFormat::from_header(&header, dx10_header.as_ref())?;
+
+    let (width, height) = (header.width, header.height);
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidHeader(format!(
+            "invalid dimensions {width}x{height}"
+        )));
+    }
+
+    let pixels = &data[header_size..];
+    let expected = format.surface_size(width, height);
+    if pixels.len() < expected {
+        return Err(Error::MipmapSizeMismatch {
+            expected,
+            actual: pixels.len(),
+        });
+    }
+
+    let mut image = RgbaImage::new(width, height);
+    match format {
+        DdsFormat::Uncompressed {
+            bit_count,
+            r_mask,
+            g_mask,
+            b_mask,
+            a_mask,
+        } => {
+            let bytes_per_pixel = bit_count as usize / 8;
+            for (i, chunk) in pixels[..expected].chunks_exact(bytes_per_pixel).enumerate() {
+                let mut raw = [0u8; 4];
+                raw[..bytes_per_pixel].copy_from_slice(chunk);
+                let value = u32::from_le_bytes(raw);
+                let pixel = [
+                    extract_channel(value, r_mask, 0),
+                    extract_channel(value, g_mask, 0),
+                    extract_channel(value, b_mask, 0),
+                    extract_channel(value, a_mask, 255),
+                ];
+                let (x, y) = (i as u32 % width, i as u32 / width);
+                image.put_pixel(x, y, Rgba(pixel));
+            }
+        }
+        _ => {
+            let blocks_x = width.div_ceil(4);
+            let block_bytes = format.block_bytes();
+            for (i, block) in pixels[..expected].chunks_exact(block_bytes).enumerate() {
+                let decoded = decode_block(format, block);
+                let (bx, by) = (i as u32 % blocks_x * 4, i as u32 / blocks_x * 4);
+                for (j, pixel) in decoded.iter().enumerate() {
+                    let (x, y) = (bx + j as u32 % 4, by + j as u32 / 4);
+                    if x < width && y < height {
+                        image.put_pixel(x, y, Rgba(*pixel));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+/// Decode one 4x4 block of a block-compressed format.
+fn decode_block(format: DdsFormat, block: &[u8]) -> Block {
+    match format {
+        DdsFormat::Bc1 => decode_bc1(block),
+        DdsFormat::Bc2 => decode_bc2(block),
+        DdsFormat::Bc3 => decode_bc3(block),
+        DdsFormat::Bc4 { signed } => decode_bc4(block, signed),
+        DdsFormat::Bc5 { signed } => decode_bc5(block, signed),
+        DdsFormat::Bc7 => decode_bc7(block),
+        DdsFormat::Uncompressed { .. } => unreachable!("not a block format"),
+    }
+}
+
+/// Extract a masked channel and scale it to 8 bits.
+fn extract_channel(value: u32, mask: u32, default: u8) -> u8 {
+    if mask == 0 {
+        return default;
+    }
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    let v = ((value & mask) >> mask.trailing_zeros()) as u64;
+    (v * 255 / max) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::DDPF_FOURCC;
+    use crate::header::{DdsHeader, FourCC};
+    use zerocopy::{FromZeros, IntoBytes};
+
+    fn dds(pixel_format: impl FnOnce(&mut DdsHeader), width: u32, height: u32) -> Vec<u8> {
+        let mut header = DdsHeader::new_zeroed();
+        header.size = DdsHeader::SIZE;
+        header.width = width;
+        header.height = height;
+        header.mipmap_count = 1;
+        header.pixel_format.size = 32;
+        pixel_format(&mut header);
+
+        let mut data = crate::DDS_MAGIC.to_vec();
+        data.extend_from_slice(header.as_bytes());
+        data
+    }
+
+    #[test]
+    fn test_decode_uncompressed() {
+        let mut data = dds(
+            |h| {
+                h.pixel_format.flags = 0x41;
+                h.pixel_format.rgb_bit_count = 32;
+                h.pixel_format.r_bit_mask = 0xFF_0000;
+                h.pixel_format.g_bit_mask = 0xFF00;
+                h.pixel_format.b_bit_mask = 0xFF;
+                h.pixel_format.a_bit_mask = 0xFF00_0000;
+            },
+            2,
+            1,
+        );
+        // BGRA byte order
+        data.extend_from_slice(&[0x30, 0x20, 0x10, 0xFF, 0x00, 0x00, 0xFF, 0x80]);
+
+        let image = decode_to_rgba(&data).unwrap();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [0x10, 0x20, 0x30, 0xFF]);
+        assert_eq!(image.get_pixel(1, 0).0, [0xFF, 0x00, 0x00, 0x80]);
+    }
+
+    #[test]
+    fn test_decode_bc1_partial_block() {
+        let mut data = dds(
+            |h| {
+                h.pixel_format.flags = DDPF_FOURCC;
+                h.pixel_format.four_cc = FourCC::DXT1;
+            },
+            2,
+            2,
+        );
+        // Solid white block
+        data.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]);
+
+        let image = decode_to_rgba(&data).unwrap();
+        assert_eq!(image.dimensions(), (2, 2));
+        assert!(image.pixels().all(|p| p.0 == [255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut data = dds(
+            |h| {
+                h.pixel_format.flags = DDPF_FOURCC;
+                h.pixel_format.four_cc = FourCC::DXT5;
+            },
+            8,
+            8,
+        );
+        data.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            decode_to_rgba(&data),
+            Err(Error::MipmapSizeMismatch {
+                expected: 64,
+                actual: 16
+            })
+        ));
+
+        assert!(matches!(
+            DdsFormat::from_dxgi(95),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+//! Encoding of RGBA images to DDS.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_dds::{encode_dds, split_dds, DdsFormat, EncodeOptions, MipFilter};
+//!
+//! let image = image::open("edited.png")?.to_rgba8();
+//! let options = EncodeOptions::new(DdsFormat::Bc7).filter(MipFilter::Box);
+//! let dds = encode_dds(&image, &options)?;
+//!
+//! // Lay it out like the game files: texture.dds plus texture.dds.1 .. .3
+//! let (base, parts) = split_dds(&dds, 3)?;
+//! std::fs::write("texture.dds", base)?;
+//! for (index, part) in parts {
+//!     std::fs::write(format!("texture.dds.{index}"), part)?;
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use image::RgbaImage;
+use zerocopy::{FromZeros, IntoBytes};
+
+use crate::bcn::Block;
+use crate::bcn_encode::{encode_bc1, encode_bc3, encode_bc4, encode_bc5, encode_bc7};
+use crate::format::{DdsFormat, DDPF_FOURCC};
+use crate::header::{DdsHeader, DdsHeaderDxt10, FourCC};
+use crate::mips::{generate_mips, MipFilter};
+use crate::{repair_header, Error, Result, DDS_MAGIC};
+
+/// Header flags required in every file: caps, height, width, pixel format.
+const DDSD_REQUIRED: u32 = 0x1 | 0x2 | 0x4 | 0x1000;
+/// Caps flag: the file holds a texture.
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+/// Pixel format flags for uncompressed RGB with alpha.
+const DDPF_RGBA: u32 = 0x40 | 0x1;
+/// DXGI format of BC7 UNORM.
+const DXGI_BC7_UNORM: u32 = 98;
+/// DX10 resource dimension of 2D textures.
+const DIMENSION_TEXTURE2D: u32 = 3;
+
+/// Options for [`encode_dds`].
+#[derive(Debug, Clone)]
+pub struct EncodeOptions {
+    format: DdsFormat,
+    filter: MipFilter,
+    mipmaps: bool,
+}
+
+impl EncodeOptions {
+    /// Encode to `format` with a full, box-filtered mipmap chain.
+    ///
+    /// Supported formats are BC1, BC3, unsigned BC4 and BC5, BC7 and
+    /// [`DdsFormat::RGBA8`].
+    pub fn new(format: DdsFormat) -> Self {
+        Self {
+            format,
+            filter: MipFilter::default(),
+            mipmaps: true,
+        }
+    }
+
+    /// Filter used to downsample mipmaps.
+    pub fn filter(mut self, filter: MipFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Generate mipmaps; with `false` only the image itself is stored.
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+}
+
+/// Encode an image, and its mipmaps, to a DDS file.
+pub fn encode_dds(image: &RgbaImage, options: &EncodeOptions) -> Result<Vec<u8>> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidHeader(format!(
+            "invalid dimensions {width}x{height}"
+        )));
+    }
+
+    let mut header = DdsHeader::new_zeroed();
+    header.size = DdsHeader::SIZE;
+    header.flags = DDSD_REQUIRED;
+    header.width = width;
+    header.height = height;
+    header.caps = DDSCAPS_TEXTURE;
+    header.pixel_format.size = 32;
+
+    let mut dx10 = None;
+    match options.format {
+        DdsFormat::Bc1 => set_four_cc(&mut header, FourCC::DXT1),
+        DdsFormat::Bc3 => set_four_cc(&mut header, FourCC::DXT5),
+        DdsFormat::Bc4 { signed: false } => set_four_cc(&mut header, FourCC::BC4U),
+        DdsFormat::Bc5 { signed: false } => set_four_cc(&mut header, FourCC::BC5U),
+        DdsFormat::Bc7 => {
+            set_four_cc(&mut header, FourCC::DX10);
+            dx10 = Some(DdsHeaderDxt10 {
+                dxgi_format: DXGI_BC7_UNORM,
+                resource_dimension: DIMENSION_TEXTURE2D,
+                misc_flag: 0,
+                array_size: 1,
+                misc_flags2: 0,
+            });
+        }
+        DdsFormat::RGBA8 => {
+            header.pixel_format.flags = DDPF_RGBA;
+            header.pixel_format.rgb_bit_count = 32;
+            header.pixel_format.r_bit_mask = 0xFF;
+            header.pixel_format.g_bit_mask = 0xFF00;
+            header.pixel_format.b_bit_mask = 0xFF_0000;
+            header.pixel_format.a_bit_mask = 0xFF00_0000;
+        }
+        format => return Err(Error::UnsupportedFormat(format!("cannot encode {format}"))),
+    }
+
+    let mips = if options.mipmaps {
+        generate_mips(image, options.filter)
+    } else {
+        vec![image.clone()]
+    };
+    header.mipmap_count = mips.len() as u32;
+
+    let mut data = DDS_MAGIC.to_vec();
+    data.extend_from_slice(header.as_bytes());
+    if let Some(dx10) = &dx10 {
+        data.extend_from_slice(dx10.as_bytes());
+    }
+    for mip in &mips {
+        encode_surface(mip, options.format, &mut data);
+    }
+
+    // Fills in the mipmap and linear size flags
+    repair_header(&mut data)?;
+    Ok(data)
+}
+
+fn set_four_cc(header: &mut DdsHeader, four_cc: FourCC) {
+    header.pixel_format.flags = DDPF_FOURCC;
+    header.pixel_format.four_cc = four_cc;
+}
+
+fn encode_surface(image: &RgbaImage, format: DdsFormat, out: &mut Vec<u8>) {
+    if let DdsFormat::Uncompressed { .. } = format {
+        out.extend_from_slice(image.as_raw());
+        return;
+    }
+
+    let (width, height) = image.dimensions();
+    for by in (0..height).step_by(4) {
+        for bx in (0..width).step_by(4) {
+            // Blocks past the edge repeat the last row and column
+            let mut block: Block = [[0; 4]; 16];
+            for (i, pixel) in block.iter_mut().enumerate() {
+                let x = (bx + i as u32 % 4).min(width - 1);
+                let y = (by + i as u32 / 4).min(height - 1);
+                *pixel = image.get_pixel(x, y).0;
+            }
+            match format {
+                DdsFormat::Bc1 => out.extend_from_slice(&encode_bc1(&block)),
+                DdsFormat::Bc3 => out.extend_from_slice(&encode_bc3(&block)),
+                DdsFormat::Bc4 { .. } => out.extend_from_slice(&encode_bc4(&block)),
+                DdsFormat::Bc5 { .. } => out.extend_from_slice(&encode_bc5(&block)),
+                _ => out.extend_from_slice(&encode_bc7(&block)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_to_rgba, merge::merge_dds_data, split_dds, DdsInfo};
+    use image::Rgba;
+
+    fn sample() -> RgbaImage {
+        RgbaImage::from_fn(16, 8, |x, y| Rgba([x as u8 * 16, y as u8 * 32, 128, 255]))
+    }
+
+    #[test]
+    fn test_encode_formats() {
+        for format in [
+            DdsFormat::Bc1,
+            DdsFormat::Bc3,
+            DdsFormat::Bc4 { signed: false },
+            DdsFormat::Bc5 { signed: false },
+            DdsFormat::Bc7,
+            DdsFormat::RGBA8,
+        ] {
+            let dds = encode_dds(&sample(), &EncodeOptions::new(format)).unwrap();
+            let info = DdsInfo::parse(&dds).unwrap();
+            assert_eq!(info.format(), Some(format));
+            assert_eq!(info.mip_count(), 5);
+            assert_eq!(info.expected_data_size(), Some(info.data_size()));
+
+            let decoded = decode_to_rgba(&dds).unwrap();
+            assert_eq!(decoded.dimensions(), (16, 8));
+            let (expected, actual) = (sample().get_pixel(9, 5).0, decoded.get_pixel(9, 5).0);
+            assert!((expected[0] as i32 - actual[0] as i32).abs() <= 16);
+        }
+
+        let options = EncodeOptions::new(DdsFormat::Bc2);
+        assert!(matches!(
+            encode_dds(&sample(), &options),
+            Err(Error::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_roundtrip() {
+        let options = EncodeOptions::new(DdsFormat::Bc1).filter(MipFilter::Triangle);
+        let dds = encode_dds(&sample(), &options).unwrap();
+
+        let (base, parts) = split_dds(&dds, 2).unwrap();
+        assert_eq!(parts.iter().map(|p| p.0).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(parts[0].1.len(), 64);
+        assert_eq!(merge_dds_data(&base, &parts).unwrap(), dds);
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for DDS handling.
 
+use svarog_common::ErrorLocation;
 use thiserror::Error;
 
 /// Errors that can occur when working with DDS files.
@@ -24,6 +25,19 @@ pub enum Error {
     /// Mipmap size mismatch.
     #[error("mipmap size mismatch: expected {expected}, got {actual}")]
     MipmapSizeMismatch { expected: usize, actual: usize },
+
+    /// Pixel format that cannot be decoded.
+    #[error("unsupported pixel format: {0}")]
+    UnsupportedFormat(String),
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// Result type for DDS operations.
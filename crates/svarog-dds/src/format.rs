@@ -0,0 +1,195 @@
+//! DDS pixel formats.
+
+use std::fmt;
+
+use crate::header::{DdsHeader, DdsHeaderDxt10, FourCC};
+use crate::{Error, Result};
+
+/// Pixel format flag: the four-CC code is valid.
+pub(crate) const DDPF_FOURCC: u32 = 0x4;
+/// Pixel format flag: single-channel luminance in the red mask.
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+/// Pixel format of a DDS texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DdsFormat {
+    /// BC1 (DXT1).
+    Bc1,
+    /// BC2 (DXT3).
+    Bc2,
+    /// BC3 (DXT5).
+    Bc3,
+    /// BC4, decoded as grayscale.
+    Bc4 {
+        /// Signed (SNORM) values.
+        signed: bool,
+    },
+    /// BC5, decoded as a normal map.
+    Bc5 {
+        /// Signed (SNORM) values.
+        signed: bool,
+    },
+    /// BC7.
+    Bc7,
+    /// Uncompressed pixels described by channel bit masks.
+    Uncompressed {
+        /// Bits per pixel (8, 16, 24 or 32).
+        bit_count: u32,
+        /// Red bit mask.
+        r_mask: u32,
+        /// Green bit mask.
+        g_mask: u32,
+        /// Blue bit mask.
+        b_mask: u32,
+        /// Alpha bit mask.
+        a_mask: u32,
+    },
+}
+
+impl DdsFormat {
+    /// 32-bit RGBA in byte order.
+    pub const RGBA8: Self = Self::Uncompressed {
+        bit_count: 32,
+        r_mask: 0xFF,
+        g_mask: 0xFF00,
+        b_mask: 0xFF_0000,
+        a_mask: 0xFF00_0000,
+    };
+
+    /// Determine the format from the DDS headers.
+    pub fn from_header(header: &DdsHeader, dx10: Option<&DdsHeaderDxt10>) -> Result<Self> {
+        if let Some(dx10) = dx10 {
+            return Self::from_dxgi(dx10.dxgi_format);
+        }
+
+        let pf = header.pixel_format;
+        let flags = pf.flags;
+        if flags & DDPF_FOURCC != 0 {
+            let four_cc = pf.four_cc;
+            return match four_cc {
+                FourCC::DXT1 => Ok(Self::Bc1),
+                FourCC::DXT3 => Ok(Self::Bc2),
+                FourCC::DXT5 => Ok(Self::Bc3),
+                FourCC::BC4U | FourCC::ATI1 => Ok(Self::Bc4 { signed: false }),
+                FourCC::BC4S => Ok(Self::Bc4 { signed: true }),
+                FourCC::BC5U | FourCC::ATI2 => Ok(Self::Bc5 { signed: false }),
+                FourCC::BC5S => Ok(Self::Bc5 { signed: true }),
+                FourCC(code) => Err(Error::UnsupportedFormat(format!(
+                    "four-CC {:?}",
+                    String::from_utf8_lossy(&code)
+                ))),
+            };
+        }
+
+        let bit_count = pf.rgb_bit_count;
+        if !matches!(bit_count, 8 | 16 | 24 | 32) {
+            return Err(Error::UnsupportedFormat(format!(
+                "{bit_count} bits per pixel"
+            )));
+        }
+        let r_mask = pf.r_bit_mask;
+        let (g_mask, b_mask) = if flags & DDPF_LUMINANCE != 0 {
+            (r_mask, r_mask)
+        } else {
+            (pf.g_bit_mask, pf.b_bit_mask)
+        };
+        Ok(Self::Uncompressed {
+            bit_count,
+            r_mask,
+            g_mask,
+            b_mask,
+            a_mask: pf.a_bit_mask,
+        })
+    }
+
+    /// Determine the format from a DXGI format code.
+    pub fn from_dxgi(format: u32) -> Result<Self> {
+        let rgba = |r, g, b, a| Self::Uncompressed {
+            bit_count: 32,
+            r_mask: r,
+            g_mask: g,
+            b_mask: b,
+            a_mask: a,
+        };
+        Ok(match format {
+            71 | 72 => Self::Bc1,
+            74 | 75 => Self::Bc2,
+            77 | 78 => Self::Bc3,
+            DdsHeaderDxt10::BC4_UNORM => Self::Bc4 { signed: false },
+            DdsHeaderDxt10::BC4_SNORM => Self::Bc4 { signed: true },
+            83 => Self::Bc5 { signed: false },
+            84 => Self::Bc5 { signed: true },
+            98 | 99 => Self::Bc7,
+            // R8G8B8A8
+            28 | 29 => Self::RGBA8,
+            // B8G8R8A8
+            87 | 91 => rgba(0xFF_0000, 0xFF00, 0xFF, 0xFF00_0000),
+            // B8G8R8X8
+            88 | 93 => rgba(0xFF_0000, 0xFF00, 0xFF, 0),
+            // R8G8
+            49 => Self::Uncompressed {
+                bit_count: 16,
+                r_mask: 0xFF,
+                g_mask: 0xFF00,
+                b_mask: 0,
+                a_mask: 0,
+            },
+            // R8, decoded as grayscale
+            61 => Self::Uncompressed {
+                bit_count: 8,
+                r_mask: 0xFF,
+                g_mask: 0xFF,
+                b_mask: 0xFF,
+                a_mask: 0,
+            },
+            _ => {
+                return Err(Error::UnsupportedFormat(format!("DXGI format {format}")));
+            }
+        })
+    }
+
+    /// Size in bytes of a surface of the given dimensions.
+    pub fn surface_size(&self, width: u32, height: u32) -> usize {
+        match self {
+            Self::Uncompressed { bit_count, .. } => {
+                width as usize * height as usize * (*bit_count as usize / 8)
+            }
+            _ => {
+                let blocks = (width as usize).div_ceil(4) * (height as usize).div_ceil(4);
+                blocks * self.block_bytes()
+            }
+        }
+    }
+
+    pub(crate) fn block_bytes(&self) -> usize {
+        match self {
+            Self::Bc1 | Self::Bc4 { .. } => 8,
+            _ => 16,
+        }
+    }
+}
+
+impl fmt::Display for DdsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let signed = |signed: bool| if signed { " SNORM" } else { "" };
+        match *self {
+            Self::Bc1 => write!(f, "BC1"),
+            Self::Bc2 => write!(f, "BC2"),
+            Self::Bc3 => write!(f, "BC3"),
+            Self::Bc4 { signed: s } => write!(f, "BC4{}", signed(s)),
+            Self::Bc5 { signed: s } => write!(f, "BC5{}", signed(s)),
+            Self::Bc7 => write!(f, "BC7"),
+            Self::Uncompressed {
+                bit_count,
+                r_mask,
+                g_mask,
+                b_mask,
+                a_mask,
+            } => write!(
+                f,
+                "{bit_count}-bit (R {r_mask:#x}, G {g_mask:#x}, B {b_mask:#x}, A {a_mask:#x})"
+            ),
+        }
+    }
+}
@@ -1,9 +1,13 @@
 //! DDS header structures.
 
+use svarog_common::{BinaryReader, ResultExt};
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout};
 
+use crate::{Error, Result, DDS_MAGIC};
+
 /// DDS file header.
 #[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C, packed)]
 pub struct DdsHeader {
     /// Header size (should be 124).
@@ -48,6 +52,7 @@ impl DdsHeader {
 
 /// DDS pixel format.
 #[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C, packed)]
 pub struct DdsPixelFormat {
     /// Structure size (should be 32).
@@ -90,10 +95,63 @@ impl FourCC {
     pub const BC5U: Self = Self(*b"BC5U");
     /// BC5S compression.
     pub const BC5S: Self = Self(*b"BC5S");
+    /// BC4 compression (legacy ATI name).
+    pub const ATI1: Self = Self(*b"ATI1");
+    /// BC5 compression (legacy ATI name).
+    pub const ATI2: Self = Self(*b"ATI2");
+}
+
+/// Printable codes serialize as their four characters, anything else (such
+/// as the zero code of uncompressed textures) as its little-endian value.
+#[cfg(feature = "serde")]
+impl serde::Serialize for FourCC {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match std::str::from_utf8(&self.0) {
+            Ok(s) if self.0.iter().all(|b| b.is_ascii_graphic()) => serializer.serialize_str(s),
+            _ => serializer.serialize_u32(u32::from_le_bytes(self.0)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FourCC {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct FourCCVisitor;
+
+        impl serde::de::Visitor<'_> for FourCCVisitor {
+            type Value = FourCC;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a four-character code or a u32")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> std::result::Result<FourCC, E> {
+                v.as_bytes()
+                    .try_into()
+                    .map(FourCC)
+                    .map_err(|_| E::invalid_length(v.len(), &self))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> std::result::Result<FourCC, E> {
+                u32::try_from(v)
+                    .map(|v| FourCC(v.to_le_bytes()))
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+        }
+
+        deserializer.deserialize_any(FourCCVisitor)
+    }
 }
 
 /// DX10 extended header.
 #[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C, packed)]
 pub struct DdsHeaderDxt10 {
     /// DXGI format.
@@ -121,7 +179,7 @@ impl DdsHeaderDxt10 {
 pub fn block_size(four_cc: FourCC, dx10_format: Option<u32>) -> usize {
     // BC4 and BC1 use 8 bytes per block, others use 16
     match four_cc {
-        FourCC::DXT1 | FourCC::BC4U | FourCC::BC4S => 8,
+        FourCC::DXT1 | FourCC::BC4U | FourCC::BC4S | FourCC::ATI1 => 8,
         _ => {
             if let Some(fmt) = dx10_format {
                 if fmt == DdsHeaderDxt10::BC4_UNORM || fmt == DdsHeaderDxt10::BC4_SNORM {
@@ -135,7 +193,76 @@ pub fn block_size(four_cc: FourCC, dx10_format: Option<u32>) -> usize {
 
 /// Calculate the size in bytes of a mipmap level.
 pub fn mipmap_size(width: u32, height: u32, block_size: usize) -> usize {
-    let blocks_x = ((width as usize) + 3) / 4;
-    let blocks_y = ((height as usize) + 3) / 4;
+    let blocks_x = (width as usize).div_ceil(4);
+    let blocks_y = (height as usize).div_ceil(4);
     blocks_x.max(1) * blocks_y.max(1) * block_size
 }
+
+/// Parse the magic and headers at the start of a DDS file.
+///
+/// Returns the header, the DX10 header if present, and the offset of the
+/// pixel data.
+pub(crate) fn read_header(data: &[u8]) -> Result<(DdsHeader, Option<DdsHeaderDxt10>, usize)> {
+    if data.len() < 4 {
+        return Err(Error::InvalidHeader("file too small".into()));
+    }
+
+    // Verify magic
+    let magic: [u8; 4] = data[..4].try_into().unwrap();
+    if &magic != DDS_MAGIC {
+        return Err(Error::InvalidMagic(magic));
+    }
+
+    let mut reader = BinaryReader::new_at(data, 4);
+    let header: DdsHeader = reader.read_struct().in_section("header", 4)?;
+
+    let dx10_header: Option<DdsHeaderDxt10> = if header.is_dx10() {
+        let offset = reader.position();
+        Some(reader.read_struct().in_section("DX10 header", offset)?)
+    } else {
+        None
+    };
+
+    let header_size = 4
+        + std::mem::size_of::<DdsHeader>()
+        + if dx10_header.is_some() {
+            std::mem::size_of::<DdsHeaderDxt10>()
+        } else {
+            0
+        };
+
+    Ok((header, dx10_header, header_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_header_errors() {
+        assert!(matches!(read_header(b"DD"), Err(Error::InvalidHeader(_))));
+        assert!(matches!(
+            read_header(b"KTX 0000"),
+            Err(Error::InvalidMagic(magic)) if &magic == b"KTX "
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_four_cc_serde() {
+        assert_eq!(serde_json::to_string(&FourCC::DXT5).unwrap(), "\"DXT5\"");
+        assert_eq!(serde_json::to_string(&FourCC([0; 4])).unwrap(), "0");
+        assert_eq!(
+            serde_json::to_string(&FourCC([113, 0, 0, 0])).unwrap(),
+            "113"
+        );
+
+        let code: FourCC = serde_json::from_str("\"ATI2\"").unwrap();
+        assert_eq!(code, FourCC::ATI2);
+        let code: FourCC = serde_json::from_str("113").unwrap();
+        assert_eq!(code, FourCC([113, 0, 0, 0]));
+
+        assert!(serde_json::from_str::<FourCC>("\"DXT\"").is_err());
+        assert!(serde_json::from_str::<FourCC>("4294967296").is_err());
+    }
+}
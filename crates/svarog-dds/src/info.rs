@@ -0,0 +1,264 @@
+//! DDS header inspection and repair.
+
+use zerocopy::IntoBytes;
+
+use crate::format::DdsFormat;
+use crate::header::{read_header, DdsHeader, DdsHeaderDxt10, FourCC};
+use crate::{Error, Result};
+
+/// Header flag: `pitch_or_linear_size` is the row pitch.
+const DDSD_PITCH: u32 = 0x8;
+/// Header flag: `mipmap_count` is valid.
+const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+/// Header flag: `pitch_or_linear_size` is the top mipmap size.
+const DDSD_LINEARSIZE: u32 = 0x8_0000;
+/// Caps flag: more than one surface.
+const DDSCAPS_COMPLEX: u32 = 0x8;
+/// Caps flag: the texture has mipmaps.
+const DDSCAPS_MIPMAP: u32 = 0x40_0000;
+/// Caps2 flag: cubemap.
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+/// Caps2 flag: volume texture.
+const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+
+/// Summary of a DDS file's headers.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_dds::DdsInfo;
+///
+/// let data = std::fs::read("texture.dds")?;
+/// let info = DdsInfo::parse(&data)?;
+/// let (width, height) = info.dimensions();
+/// println!("{width}x{height}, {} mipmaps", info.mip_count());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct DdsInfo {
+    header: DdsHeader,
+    dx10: Option<DdsHeaderDxt10>,
+    header_size: usize,
+    data_size: usize,
+}
+
+impl DdsInfo {
+    /// Parse the headers of a DDS file.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let (header, dx10, header_size) = read_header(data)?;
+        Ok(Self {
+            header,
+            dx10,
+            header_size,
+            data_size: data.len() - header_size,
+        })
+    }
+
+    /// The main header.
+    pub fn header(&self) -> &DdsHeader {
+        &self.header
+    }
+
+    /// The DX10 extended header, if present.
+    pub fn dx10(&self) -> Option<&DdsHeaderDxt10> {
+        self.dx10.as_ref()
+    }
+
+    /// Width and height of the top mipmap.
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.header.width, self.header.height)
+    }
+
+    /// Depth of a volume texture, 1 otherwise.
+    pub fn depth(&self) -> u32 {
+        if self.is_volume() {
+            self.header.depth.max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Number of mipmap levels, including the top one.
+    pub fn mip_count(&self) -> u32 {
+        self.header.mipmap_count.max(1)
+    }
+
+    /// The four-CC code of the pixel format.
+    pub fn four_cc(&self) -> FourCC {
+        self.header.pixel_format.four_cc
+    }
+
+    /// The pixel format, or `None` if it is not supported.
+    pub fn format(&self) -> Option<DdsFormat> {
+        DdsFormat::from_header(&self.header, self.dx10.as_ref()).ok()
+    }
+
+    /// Whether this is a cubemap.
+    pub fn is_cubemap(&self) -> bool {
+        self.header.caps2 & DDSCAPS2_CUBEMAP != 0
+    }
+
+    /// Whether this is a volume texture.
+    pub fn is_volume(&self) -> bool {
+        self.header.caps2 & DDSCAPS2_VOLUME != 0
+    }
+
+    /// Number of surfaces, counting cubemap faces and array slices.
+    pub fn surface_count(&self) -> u32 {
+        let faces = if self.is_cubemap() { 6 } else { 1 };
+        let array_size = self.dx10.map_or(1, |dx10| dx10.array_size.max(1));
+        faces * array_size
+    }
+
+    /// Size of the headers, including the magic.
+    pub fn header_size(&self) -> usize {
+        self.header_size
+    }
+
+    /// Number of bytes after the headers.
+    pub fn data_size(&self) -> usize {
+        self.data_size
+    }
+
+    /// Number of bytes a complete file holds after the headers, or `None`
+    /// if the format is not supported.
+    pub fn expected_data_size(&self) -> Option<usize> {
+        let format = self.format()?;
+        let mips: usize = (0..self.mip_count())
+            .map(|level| self.level_size(format, level))
+            .sum();
+        Some(mips * self.surface_count() as usize)
+    }
+
+    /// Whether the file holds every mipmap its header declares; `false` for
+    /// unmerged split files.
+    pub fn is_complete(&self) -> bool {
+        self.expected_data_size()
+            .is_some_and(|size| self.data_size >= size)
+    }
+
+    pub(crate) fn level_size(&self, format: DdsFormat, level: u32) -> usize {
+        let (width, height) = self.dimensions();
+        let depth = (self.depth() >> level).max(1);
+        format.surface_size((width >> level).max(1), (height >> level).max(1)) * depth as usize
+    }
+}
+
+/// Fix the mipmap count, pitch/linear size and related flags of a DDS
+/// file in place.
+///
+/// The mipmap count is lowered to the number of levels the data actually
+/// holds, e.g. after merging only some split parts. Returns whether the
+/// header was changed.
+pub fn repair_header(data: &mut [u8]) -> Result<bool> {
+    let info = DdsInfo::parse(data)?;
+    let format = DdsFormat::from_header(&info.header, info.dx10.as_ref())?;
+    let mut header = info.header;
+
+    // Count the levels that fit, stopping at the 1x1 level
+    let (width, height) = info.dimensions();
+    let max_levels = 32 - width.max(height).max(1).leading_zeros();
+    let surfaces = info.surface_count() as usize;
+    let mut mip_count = 0;
+    let mut total = 0;
+    while mip_count < max_levels.min(info.mip_count()) {
+        total += info.level_size(format, mip_count) * surfaces;
+        if total > info.data_size {
+            break;
+        }
+        mip_count += 1;
+    }
+    if mip_count == 0 {
+        return Err(Error::MipmapSizeMismatch {
+            expected: info.level_size(format, 0) * surfaces,
+            actual: info.data_size,
+        });
+    }
+
+    header.size = DdsHeader::SIZE;
+    header.pixel_format.size = 32;
+    header.mipmap_count = mip_count;
+    if mip_count > 1 {
+        header.flags |= DDSD_MIPMAPCOUNT;
+        header.caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+    } else {
+        header.flags &= !DDSD_MIPMAPCOUNT;
+        header.caps &= !DDSCAPS_MIPMAP;
+    }
+
+    match format {
+        DdsFormat::Uncompressed { bit_count, .. } => {
+            header.pitch_or_linear_size = (width * bit_count).div_ceil(8);
+            header.flags = (header.flags | DDSD_PITCH) & !DDSD_LINEARSIZE;
+        }
+        _ => {
+            header.pitch_or_linear_size = format.surface_size(width, height) as u32;
+            header.flags = (header.flags | DDSD_LINEARSIZE) & !DDSD_PITCH;
+        }
+    }
+
+    let bytes = &mut data[4..4 + std::mem::size_of::<DdsHeader>()];
+    let changed = bytes != header.as_bytes();
+    bytes.copy_from_slice(header.as_bytes());
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::DDPF_FOURCC;
+    use zerocopy::FromZeros;
+
+    fn dds(width: u32, height: u32, mipmap_count: u32, data_size: usize) -> Vec<u8> {
+        let mut header = DdsHeader::new_zeroed();
+        header.size = DdsHeader::SIZE;
+        header.width = width;
+        header.height = height;
+        header.mipmap_count = mipmap_count;
+        header.pixel_format.size = 32;
+        header.pixel_format.flags = DDPF_FOURCC;
+        header.pixel_format.four_cc = FourCC::DXT5;
+
+        let mut data = crate::DDS_MAGIC.to_vec();
+        data.extend_from_slice(header.as_bytes());
+        data.resize(data.len() + data_size, 0);
+        data
+    }
+
+    #[test]
+    fn test_info() {
+        // 16x16 BC3 with all 5 levels: 256 + 64 + 16 + 16 + 16 bytes
+        let data = dds(16, 16, 5, 368);
+        let info = DdsInfo::parse(&data).unwrap();
+        assert_eq!(info.dimensions(), (16, 16));
+        assert_eq!(info.mip_count(), 5);
+        assert_eq!(info.format(), Some(DdsFormat::Bc3));
+        assert!(info.dx10().is_none());
+        assert_eq!(info.expected_data_size(), Some(368));
+        assert!(info.is_complete());
+
+        let info = DdsInfo::parse(&data[..data.len() - 1]).unwrap();
+        assert!(!info.is_complete());
+    }
+
+    #[test]
+    fn test_repair_header() {
+        // Declares 5 levels but only holds the top two
+        let mut data = dds(16, 16, 5, 320);
+        assert!(repair_header(&mut data).unwrap());
+
+        let info = DdsInfo::parse(&data).unwrap();
+        assert_eq!(info.mip_count(), 2);
+        assert_eq!({ info.header().pitch_or_linear_size }, 256);
+        assert_ne!({ info.header().flags } & DDSD_LINEARSIZE, 0);
+        assert!(info.is_complete());
+
+        assert!(!repair_header(&mut data).unwrap());
+
+        let mut data = dds(16, 16, 5, 100);
+        assert!(matches!(
+            repair_header(&mut data),
+            Err(Error::MipmapSizeMismatch { .. })
+        ));
+    }
+}
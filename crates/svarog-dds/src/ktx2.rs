@@ -0,0 +1,414 @@
+//! Conversion of merged DDS textures to KTX2.
+//!
+//! Block-compressed and 32-bit RGBA data is copied unchanged, only the
+//! container is rewritten. Levels can optionally be supercompressed with
+//! Zstandard. Transcoding to Basis Universal (BasisLZ/UASTC) is not
+//! supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_dds::{dds_to_ktx2, merge_dds, Ktx2Options, Supercompression};
+//!
+//! let merged = merge_dds("texture.dds")?;
+//! let options = Ktx2Options::new().supercompression(Supercompression::Zstd(19));
+//! std::fs::write("texture.ktx2", dds_to_ktx2(&merged, &options)?)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::format::DdsFormat;
+use crate::info::DdsInfo;
+use crate::{Error, Result};
+
+/// KTX2 file identifier ("«KTX 20»\r\n\x1A\n").
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+/// Size of the identifier, header and index.
+const HEADER_SIZE: usize = 80;
+/// Supercompression scheme: Zstandard.
+const SCHEME_ZSTD: u32 = 2;
+
+/// Data format descriptor color models.
+const MODEL_RGBSDA: u8 = 1;
+const MODEL_BC1A: u8 = 128;
+const MODEL_BC2: u8 = 129;
+const MODEL_BC3: u8 = 130;
+const MODEL_BC4: u8 = 131;
+const MODEL_BC5: u8 = 132;
+const MODEL_BC7: u8 = 134;
+/// Data format descriptor primaries: BT.709.
+const PRIMARIES_BT709: u8 = 1;
+/// Data format descriptor transfer functions.
+const TRANSFER_LINEAR: u8 = 1;
+const TRANSFER_SRGB: u8 = 2;
+/// Sample qualifiers, or'ed into the channel type.
+const QUALIFIER_LINEAR: u8 = 0x10;
+const QUALIFIER_SIGNED: u8 = 0x40;
+/// Channel of the alpha in RGBSDA, BC2 and BC3.
+const CHANNEL_ALPHA: u8 = 15;
+
+/// DXGI formats stored as sRGB (BC1, BC2, BC3, R8G8B8A8, B8G8R8A8, BC7).
+const DXGI_SRGB: [u32; 6] = [72, 75, 78, 29, 91, 99];
+
+/// Supercompression applied to each mipmap level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Supercompression {
+    /// Store the levels as is.
+    #[default]
+    None,
+    /// Zstandard, at the given level (1-22).
+    Zstd(i32),
+}
+
+/// Options for [`dds_to_ktx2`].
+#[derive(Debug, Clone, Default)]
+pub struct Ktx2Options {
+    supercompression: Supercompression,
+    srgb: Option<bool>,
+}
+
+impl Ktx2Options {
+    /// No supercompression, transfer function taken from the DDS.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Supercompression of the mipmap levels.
+    pub fn supercompression(mut self, supercompression: Supercompression) -> Self {
+        self.supercompression = supercompression;
+        self
+    }
+
+    /// Mark the texture as sRGB or linear.
+    ///
+    /// By default it is sRGB if the DX10 header names an sRGB format and
+    /// linear otherwise, as legacy headers do not say.
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = Some(srgb);
+        self
+    }
+}
+
+/// One sample of a data format descriptor: channel, bit offset, bit
+/// length.
+type Sample = (u8, u16, u8);
+
+/// Convert a complete (merged) DDS file to KTX2.
+///
+/// BC1-BC5, BC7 and 32-bit RGBA/BGRA textures are supported, including
+/// cubemaps, arrays and volumes.
+pub fn dds_to_ktx2(data: &[u8], options: &Ktx2Options) -> Result<Vec<u8>> {
+    let info = DdsInfo::parse(data)?;
+    let format = DdsFormat::from_header(info.header(), info.dx10())?;
+    if let Some(expected) = info
+        .expected_data_size()
+        .filter(|&size| info.data_size() < size)
+    {
+        return Err(Error::MipmapSizeMismatch {
+            expected,
+            actual: info.data_size(),
+        });
+    }
+
+    let srgb = options.srgb.unwrap_or_else(|| {
+        info.dx10()
+            .is_some_and(|dx10| DXGI_SRGB.contains(&{ dx10.dxgi_format }))
+    });
+    let (vk_format, model, samples) = describe(format, srgb)?;
+    let texel_bytes = match format {
+        DdsFormat::Uncompressed { .. } => 4,
+        _ => format.block_bytes(),
+    };
+
+    // DDS stores each surface with its full mipmap chain, KTX2 each level
+    // with all of its surfaces
+    let mip_count = info.mip_count() as usize;
+    let level_sizes: Vec<usize> = (0..info.mip_count())
+        .map(|level| info.level_size(format, level))
+        .collect();
+    let chain_size: usize = level_sizes.iter().sum();
+    let pixels = &data[info.header_size()..];
+    let mut levels = vec![Vec::new(); mip_count];
+    for surface in pixels
+        .chunks_exact(chain_size)
+        .take(info.surface_count() as usize)
+    {
+        let mut offset = 0;
+        for (level, &size) in level_sizes.iter().enumerate() {
+            levels[level].extend_from_slice(&surface[offset..offset + size]);
+            offset += size;
+        }
+    }
+
+    let (scheme, levels) = match options.supercompression {
+        Supercompression::None => (0, levels.into_iter().map(|l| (l.len(), l)).collect()),
+        Supercompression::Zstd(level) => {
+            let compressed = levels
+                .into_iter()
+                .map(|l| Ok((l.len(), zstd::bulk::compress(&l, level)?)))
+                .collect::<Result<Vec<_>>>()?;
+            (SCHEME_ZSTD, compressed)
+        }
+    };
+
+    let transfer = if srgb { TRANSFER_SRGB } else { TRANSFER_LINEAR };
+    let dfd = data_format_descriptor(format, model, transfer, &samples, scheme != 0);
+    let kvd = key_value_data();
+
+    let (width, height) = info.dimensions();
+    let faces = if info.is_cubemap() { 6 } else { 1 };
+    let layers = info.surface_count() / faces;
+    let dfd_offset = HEADER_SIZE + 24 * mip_count;
+    let kvd_offset = dfd_offset + dfd.len();
+
+    let mut out = Vec::with_capacity(kvd_offset + kvd.len() + pixels.len());
+    out.extend_from_slice(&KTX2_MAGIC);
+    for value in [
+        vk_format,
+        1, // typeSize
+        width,
+        height,
+        if info.is_volume() { info.depth() } else { 0 },
+        if layers > 1 { layers } else { 0 },
+        faces,
+        mip_count as u32,
+        scheme,
+        dfd_offset as u32,
+        dfd.len() as u32,
+        kvd_offset as u32,
+        kvd.len() as u32,
+    ] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    // No supercompression global data
+    out.extend_from_slice(&[0; 16]);
+
+    // Levels are stored smallest first; uncompressed ones aligned to a
+    // texel block
+    let alignment = if scheme == 0 { texel_bytes.max(4) } else { 1 };
+    let mut offset = kvd_offset + kvd.len();
+    let mut index = vec![(0, 0, 0); mip_count];
+    for (level, (uncompressed, bytes)) in levels.iter().enumerate().rev() {
+        offset = offset.next_multiple_of(alignment);
+        index[level] = (offset, bytes.len(), *uncompressed);
+        offset += bytes.len();
+    }
+    for (offset, length, uncompressed) in index.iter().copied() {
+        out.extend_from_slice(&(offset as u64).to_le_bytes());
+        out.extend_from_slice(&(length as u64).to_le_bytes());
+        out.extend_from_slice(&(uncompressed as u64).to_le_bytes());
+    }
+
+    out.extend_from_slice(&dfd);
+    out.extend_from_slice(&kvd);
+    for (level, (_, bytes)) in levels.iter().enumerate().rev() {
+        out.resize(index[level].0, 0);
+        out.extend_from_slice(bytes);
+    }
+    Ok(out)
+}
+
+/// Vulkan format, color model and samples of `format`.
+fn describe(format: DdsFormat, srgb: bool) -> Result<(u32, u8, Vec<Sample>)> {
+    let srgb_format = |unorm: u32, srgb_id: u32| if srgb { srgb_id } else { unorm };
+    let signed = |signed: bool| if signed { QUALIFIER_SIGNED } else { 0 };
+    Ok(match format {
+        // BC1 with the 1-bit alpha mode
+        DdsFormat::Bc1 => (srgb_format(133, 134), MODEL_BC1A, vec![(1, 0, 64)]),
+        DdsFormat::Bc2 => (
+            srgb_format(135, 136),
+            MODEL_BC2,
+            vec![(CHANNEL_ALPHA, 0, 64), (0, 64, 64)],
+        ),
+        DdsFormat::Bc3 => (
+            srgb_format(137, 138),
+            MODEL_BC3,
+            vec![(CHANNEL_ALPHA, 0, 64), (0, 64, 64)],
+        ),
+        DdsFormat::Bc4 { signed: s } => (
+            if s { 140 } else { 139 },
+            MODEL_BC4,
+            vec![(signed(s), 0, 64)],
+        ),
+        DdsFormat::Bc5 { signed: s } => (
+            if s { 142 } else { 141 },
+            MODEL_BC5,
+            vec![(signed(s), 0, 64), (1 | signed(s), 64, 64)],
+        ),
+        DdsFormat::Bc7 => (srgb_format(145, 146), MODEL_BC7, vec![(0, 0, 128)]),
+        DdsFormat::RGBA8 => (
+            srgb_format(37, 43),
+            MODEL_RGBSDA,
+            vec![(0, 0, 8), (1, 8, 8), (2, 16, 8), (CHANNEL_ALPHA, 24, 8)],
+        ),
+        DdsFormat::Uncompressed {
+            bit_count: 32,
+            r_mask: 0xFF_0000,
+            g_mask: 0xFF00,
+            b_mask: 0xFF,
+            a_mask: 0xFF00_0000,
+        } => (
+            srgb_format(44, 50),
+            MODEL_RGBSDA,
+            vec![(2, 0, 8), (1, 8, 8), (0, 16, 8), (CHANNEL_ALPHA, 24, 8)],
+        ),
+        format => {
+            return Err(Error::UnsupportedFormat(format!(
+                "{format} has no KTX2 equivalent"
+            )))
+        }
+    })
+}
+
+/// Build a basic data format descriptor, including its total size.
+fn data_format_descriptor(
+    format: DdsFormat,
+    model: u8,
+    transfer: u8,
+    samples: &[Sample],
+    supercompressed: bool,
+) -> Vec<u8> {
+    let block_size = 24 + 16 * samples.len();
+    let (block_dimension, bytes_plane) = match format {
+        DdsFormat::Uncompressed { .. } => (0, 4),
+        _ => (3, format.block_bytes() as u8),
+    };
+
+    let mut dfd = Vec::with_capacity(4 + block_size);
+    dfd.extend_from_slice(&(4 + block_size as u32).to_le_bytes());
+    // Khronos vendor, basic descriptor type
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    dfd.extend_from_slice(&2u16.to_le_bytes());
+    dfd.extend_from_slice(&(block_size as u16).to_le_bytes());
+    dfd.extend_from_slice(&[model, PRIMARIES_BT709, transfer, 0]);
+    dfd.extend_from_slice(&[block_dimension, block_dimension, 0, 0]);
+    // Supercompressed data has no fixed plane size
+    let bytes_plane = if supercompressed { 0 } else { bytes_plane };
+    dfd.extend_from_slice(&[bytes_plane, 0, 0, 0, 0, 0, 0, 0]);
+
+    for &(channel, bit_offset, bit_length) in samples {
+        // Alpha stays linear in sRGB textures
+        let channel = if transfer == TRANSFER_SRGB && channel & 0xF == CHANNEL_ALPHA {
+            channel | QUALIFIER_LINEAR
+        } else {
+            channel
+        };
+        let (lower, upper) = match (channel & QUALIFIER_SIGNED != 0, bit_length) {
+            (true, _) => (0x8000_0000u32, 0x7FFF_FFFFu32),
+            (false, 8) => (0, 0xFF),
+            (false, _) => (0, u32::MAX),
+        };
+        dfd.extend_from_slice(&bit_offset.to_le_bytes());
+        dfd.extend_from_slice(&[bit_length - 1, channel]);
+        dfd.extend_from_slice(&[0; 4]);
+        dfd.extend_from_slice(&lower.to_le_bytes());
+        dfd.extend_from_slice(&upper.to_le_bytes());
+    }
+    dfd
+}
+
+/// Key/value data naming the writer.
+fn key_value_data() -> Vec<u8> {
+    let entry = concat!("KTXwriter\0svarog-dds ", env!("CARGO_PKG_VERSION"), "\0");
+    let mut kvd = (entry.len() as u32).to_le_bytes().to_vec();
+    kvd.extend_from_slice(entry.as_bytes());
+    kvd.resize(kvd.len().next_multiple_of(4), 0);
+    kvd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::DDPF_FOURCC;
+    use crate::header::{DdsHeader, FourCC};
+    use zerocopy::{FromZeros, IntoBytes};
+
+    fn read_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_u64(data: &[u8], offset: usize) -> u64 {
+        u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+    }
+
+    /// A 16x8 BC1 texture whose bytes are numbered by mipmap level.
+    fn dds(caps2: u32, surfaces: usize) -> Vec<u8> {
+        let mut header = DdsHeader::new_zeroed();
+        header.size = DdsHeader::SIZE;
+        header.width = 16;
+        header.height = 8;
+        header.mipmap_count = 5;
+        header.caps2 = caps2;
+        header.pixel_format.size = 32;
+        header.pixel_format.flags = DDPF_FOURCC;
+        header.pixel_format.four_cc = FourCC::DXT1;
+
+        let mut data = crate::DDS_MAGIC.to_vec();
+        data.extend_from_slice(header.as_bytes());
+        for _ in 0..surfaces {
+            // 64 + 16 + 8 + 8 + 8 bytes
+            for (level, size) in [64, 16, 8, 8, 8].into_iter().enumerate() {
+                data.resize(data.len() + size, level as u8);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_dds_to_ktx2() {
+        let ktx = dds_to_ktx2(&dds(0, 1), &Ktx2Options::new()).unwrap();
+        assert_eq!(ktx[..12], KTX2_MAGIC);
+        assert_eq!(read_u32(&ktx, 12), 133);
+        assert_eq!(read_u32(&ktx, 20), 16);
+        assert_eq!(read_u32(&ktx, 24), 8);
+        assert_eq!(read_u32(&ktx, 36), 1);
+        assert_eq!(read_u32(&ktx, 40), 5);
+        assert_eq!(read_u32(&ktx, 44), 0);
+
+        // Level 0 is indexed first but stored last, at the end of the file
+        let (offset, length) = (read_u64(&ktx, 80) as usize, read_u64(&ktx, 88) as usize);
+        assert_eq!(length, 64);
+        assert_eq!(offset + length, ktx.len());
+        assert!(ktx[offset..].iter().all(|&b| b == 0));
+        let offset = read_u64(&ktx, 80 + 24 * 4) as usize;
+        assert_eq!(offset % 8, 0);
+        assert_eq!(ktx[offset..offset + 8], [4; 8]);
+
+        let dfd_offset = read_u32(&ktx, 48) as usize;
+        assert_eq!(read_u32(&ktx, dfd_offset), read_u32(&ktx, 52));
+        assert_eq!(ktx[dfd_offset + 12], MODEL_BC1A);
+    }
+
+    #[test]
+    fn test_cubemap_levels() {
+        // Cubemap with all faces
+        let ktx = dds_to_ktx2(&dds(0xFE00, 6), &Ktx2Options::new().srgb(true)).unwrap();
+        assert_eq!(read_u32(&ktx, 12), 134);
+        assert_eq!(read_u32(&ktx, 36), 6);
+
+        // Each level holds the same mipmap of all six faces
+        let offset = read_u64(&ktx, 80 + 24) as usize;
+        assert_eq!(read_u64(&ktx, 80 + 32), 96);
+        assert!(ktx[offset..offset + 96].iter().all(|&b| b == 1));
+
+        let mut truncated = dds(0xFE00, 6);
+        truncated.pop();
+        assert!(matches!(
+            dds_to_ktx2(&truncated, &Ktx2Options::new()),
+            Err(Error::MipmapSizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_zstd_levels() {
+        let options = Ktx2Options::new().supercompression(Supercompression::Zstd(3));
+        let ktx = dds_to_ktx2(&dds(0, 1), &options).unwrap();
+        assert_eq!(read_u32(&ktx, 44), SCHEME_ZSTD);
+
+        let (offset, length) = (read_u64(&ktx, 80) as usize, read_u64(&ktx, 88) as usize);
+        assert_eq!(read_u64(&ktx, 96), 64);
+        let level = zstd::bulk::decompress(&ktx[offset..offset + length], 64).unwrap();
+        assert_eq!(level, [0; 64]);
+    }
+}
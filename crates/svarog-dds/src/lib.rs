@@ -7,27 +7,105 @@
 //! - ...down to...
 //! - `texture.dds.0` - Smallest split mipmap
 //!
+//! Some textures also carry a glossmap stream, `texture.dds.a`, split the
+//! same way into `texture.dds.8a` ... `texture.dds.0a`.
+//!
 //! This crate provides utilities to merge these split files back into
-//! a complete DDS file.
+//! complete DDS files.
 //!
 //! # Example
 //!
 //! ```no_run
-//! use svarog_dds::merge_dds;
+//! use svarog_dds::{merge_dds, merge_glossmap};
 //!
 //! // Merge a split DDS file
 //! let merged = merge_dds("path/to/texture.dds")?;
 //! std::fs::write("merged.dds", &merged)?;
+//!
+//! // Merge its glossmap stream, if there is one
+//! if let Some(gloss) = merge_glossmap("path/to/texture.dds")? {
+//!     std::fs::write("merged.dds.a", &gloss)?;
+//! }
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
+//!
+//! For textures that aren't on disk, e.g. entries read from a P4K,
+//! [`split_path`] names the parts to look for and [`merge_dds_data`] merges
+//! them in memory.
+//!
+//! With the `parallel` feature, [`merge_dds_tree`] merges every split
+//! texture below an extracted directory in place.
+//!
+//! [`DdsInfo`] summarizes a file's headers (dimensions, format, mipmaps,
+//! DX10 details) and [`repair_header`] fixes the mipmap count and linear
+//! size of a merged file whose header does not match its data.
+//!
+//! # Decoding
+//!
+//! With the `decode` feature, [`decode_to_rgba`] turns a (merged) DDS into
+//! an [`image::RgbaImage`]. BC1-BC5, BC7 and common uncompressed formats
+//! are supported; BC6H is not.
+//!
+//! ```ignore
+//! let merged = svarog_dds::merge_dds("path/to/texture.dds")?;
+//! svarog_dds::decode_to_rgba(&merged)?.save("texture.png")?;
+//! ```
+//!
+//! # Encoding
+//!
+//! The `encode` feature goes the other way: [`generate_mips`] builds a
+//! mipmap chain and [`encode_dds`] compresses an edited image to BC1, BC3,
+//! BC4, BC5, BC7 or RGBA. [`split_dds`] then lays the result out as the
+//! game's split files.
+//!
+//! # KTX2
+//!
+//! With the `ktx2` feature, [`dds_to_ktx2`] rewraps a merged DDS as KTX2
+//! for web viewers and modern engines, optionally with Zstandard
+//! supercompression. The compressed blocks are kept as they are.
 
+#[cfg(feature = "decode")]
+mod bc7;
+#[cfg(feature = "decode")]
+mod bcn;
+#[cfg(feature = "encode")]
+mod bcn_encode;
+#[cfg(feature = "decode")]
+mod decode;
+#[cfg(feature = "encode")]
+mod encode;
 mod error;
+mod format;
 mod header;
+mod info;
+#[cfg(feature = "ktx2")]
+mod ktx2;
 mod merge;
+#[cfg(feature = "encode")]
+mod mips;
+#[cfg(feature = "parallel")]
+mod tree;
 
+#[cfg(feature = "decode")]
+pub use decode::decode_to_rgba;
+#[cfg(feature = "encode")]
+pub use encode::{encode_dds, EncodeOptions};
 pub use error::{Error, Result};
-pub use header::{DdsHeader, DdsHeaderDxt10, DdsPixelFormat};
-pub use merge::merge_dds;
+pub use format::DdsFormat;
+pub use header::{DdsHeader, DdsHeaderDxt10, DdsPixelFormat, FourCC};
+pub use info::{repair_header, DdsInfo};
+#[cfg(feature = "ktx2")]
+pub use ktx2::{dds_to_ktx2, Ktx2Options, Supercompression};
+pub use merge::{
+    glossmap_path, merge_dds, merge_dds_data, merge_glossmap, split_dds, split_path, SplitDds,
+};
+#[cfg(feature = "encode")]
+pub use mips::{generate_mips, MipFilter};
+#[cfg(feature = "parallel")]
+pub use tree::{
+    merge_dds_tree, merge_dds_tree_with, MergeStatus, MergeTreeOptions, MergeTreeReport,
+    MergeTreeResult,
+};
 
 /// DDS file magic bytes ("DDS ").
 pub const DDS_MAGIC: &[u8; 4] = b"DDS ";
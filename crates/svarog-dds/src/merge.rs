@@ -1,12 +1,10 @@
 //! DDS mipmap merging.
 
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use svarog_common::BinaryReader;
-
-use crate::header::{block_size, mipmap_size, DdsHeader, DdsHeaderDxt10};
-use crate::{Error, Result, DDS_MAGIC};
+use crate::header::{block_size, mipmap_size, read_header, DdsHeader, DdsHeaderDxt10};
+use crate::{Error, Result};
 
 /// Merge a split DDS file into a complete DDS.
 ///
@@ -17,12 +15,14 @@ use crate::{Error, Result, DDS_MAGIC};
 ///
 /// * `path` - Path to the base DDS file (without the `.N` suffix)
 ///
+/// Glossmap streams are merged the same way when `path` is the `.dds.a`
+/// file itself; their splits are named `.dds.1a`, `.dds.2a`, etc.
+///
 /// # Returns
 ///
 /// The merged DDS file as a byte vector.
 pub fn merge_dds<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
     let path = path.as_ref();
-    let base_path = path.to_string_lossy();
 
     // Read the base file
     let base_data = fs::read(path)?;
@@ -31,8 +31,7 @@ pub fn merge_dds<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
     let mut split_files: Vec<(u8, Vec<u8>)> = Vec::new();
 
     for i in 0..=9 {
-        let split_path = format!("{}.{}", base_path, i);
-        if let Ok(data) = fs::read(&split_path) {
+        if let Ok(data) = fs::read(split_path(path, i)) {
             split_files.push((i, data));
         }
     }
@@ -43,40 +42,49 @@ pub fn merge_dds<P: AsRef<Path>>(path: P) -> Result<Vec<u8>> {
     }
 
     // Sort by number descending (largest mipmap first)
-    split_files.sort_by(|a, b| b.0.cmp(&a.0));
+    split_files.sort_by_key(|f| std::cmp::Reverse(f.0));
 
     merge_dds_data(&base_data, &split_files)
 }
 
-/// Merge DDS data from base file and split mipmap files.
-pub fn merge_dds_data(base_data: &[u8], split_files: &[(u8, Vec<u8>)]) -> Result<Vec<u8>> {
-    if base_data.len() < 4 {
-        return Err(Error::InvalidHeader("file too small".into()));
-    }
-
-    // Verify magic
-    let magic: [u8; 4] = base_data[..4].try_into().unwrap();
-    if &magic != DDS_MAGIC {
-        return Err(Error::InvalidMagic(magic));
+/// Merge the glossmap (`.dds.a`) stream that accompanies a DDS file.
+///
+/// Star Citizen stores the gloss/alpha channel of some textures (usually
+/// `_ddna` normal maps) as a separate DDS next to the color stream, with
+/// its own split mipmaps. `path` is the base color file; returns `None`
+/// if it has no glossmap.
+pub fn merge_glossmap<P: AsRef<Path>>(path: P) -> Result<Option<Vec<u8>>> {
+    let gloss_path = glossmap_path(path.as_ref());
+    if !gloss_path.is_file() {
+        return Ok(None);
     }
+    merge_dds(gloss_path).map(Some)
+}
 
-    // Parse header
-    let mut reader = BinaryReader::new(&base_data[4..]);
-    let header: DdsHeader = reader.read_struct()?;
+/// Path of the glossmap stream for a base DDS file (`texture.dds.a`).
+pub fn glossmap_path(path: &Path) -> PathBuf {
+    let mut gloss = path.as_os_str().to_owned();
+    gloss.push(".a");
+    PathBuf::from(gloss)
+}
 
-    let has_dx10 = header.is_dx10();
-    let dx10_header: Option<DdsHeaderDxt10> = if has_dx10 {
-        Some(reader.read_struct()?)
-    } else {
-        None
-    };
+/// Path of split mipmap `index` for a base file.
+///
+/// Color streams use `texture.dds.N`, glossmap streams `texture.dds.Na`.
+pub fn split_path(path: &Path, index: u8) -> PathBuf {
+    let base_path = path.to_string_lossy();
+    match base_path.strip_suffix(".a") {
+        Some(stem) => PathBuf::from(format!("{}.{}a", stem, index)),
+        None => PathBuf::from(format!("{}.{}", base_path, index)),
+    }
+}
 
-    let header_size = 4 + std::mem::size_of::<DdsHeader>()
-        + if has_dx10 {
-            std::mem::size_of::<DdsHeaderDxt10>()
-        } else {
-            0
-        };
+/// Merge DDS data from base file and split mipmap files.
+///
+/// `split_files` holds each part's number and contents, largest mipmap
+/// (highest number) first, as [`merge_dds`] collects them from disk.
+pub fn merge_dds_data(base_data: &[u8], split_files: &[(u8, Vec<u8>)]) -> Result<Vec<u8>> {
+    let (header, dx10_header, header_size) = read_header(base_data)?;
 
     // Get the small mipmaps from the base file
     let small_mipmaps = &base_data[header_size..];
@@ -131,6 +139,63 @@ pub fn merge_dds_data(base_data: &[u8], split_files: &[(u8, Vec<u8>)]) -> Result
     Ok(output)
 }
 
+/// A base file and its numbered split parts, as returned by [`split_dds`].
+pub type SplitDds = (Vec<u8>, Vec<(u8, Vec<u8>)>);
+
+/// Split a complete DDS file into the game's streaming layout.
+///
+/// The `split_count` largest mipmaps move to parts numbered from
+/// `split_count` (the largest) down to 1, to be written as
+/// `texture.dds.N`; the returned base file keeps the headers and the
+/// remaining mipmaps. This is the inverse of [`merge_dds_data`].
+pub fn split_dds(data: &[u8], split_count: u8) -> Result<SplitDds> {
+    let (header, dx10_header, header_size) = read_header(data)?;
+    let mip_sizes = calculate_mipmap_sizes(&header, dx10_header.as_ref());
+
+    // Keep at least one mipmap in the base file
+    let split_count = (split_count.min(9) as usize).min(mip_sizes.len().saturating_sub(1));
+    let face_size: usize = mip_sizes.iter().sum();
+    let pixels = &data[header_size..];
+    if face_size == 0 || pixels.is_empty() || pixels.len() % face_size != 0 {
+        return Err(Error::MipmapSizeMismatch {
+            expected: face_size,
+            actual: pixels.len(),
+        });
+    }
+
+    let mut base = data[..header_size].to_vec();
+    let mut parts: Vec<(u8, Vec<u8>)> = (0..split_count)
+        .map(|level| ((split_count - level) as u8, Vec::new()))
+        .collect();
+
+    // Faces are stored one after another, each with its full mipmap chain
+    for face in pixels.chunks_exact(face_size) {
+        let mut offset = 0;
+        for (level, &size) in mip_sizes.iter().enumerate() {
+            let mip = &face[offset..offset + size];
+            if level < split_count {
+                parts[level].1.extend_from_slice(mip);
+            } else {
+                base.extend_from_slice(mip);
+            }
+            offset += size;
+        }
+    }
+
+    Ok((base, parts))
+}
+
+/// Check whether a base file already holds every mipmap of its first face,
+/// i.e. was merged before.
+#[cfg(feature = "parallel")]
+pub(crate) fn is_complete(base_data: &[u8]) -> Result<bool> {
+    let (header, dx10_header, header_size) = read_header(base_data)?;
+    let full_size: usize = calculate_mipmap_sizes(&header, dx10_header.as_ref())
+        .iter()
+        .sum();
+    Ok(base_data.len() - header_size >= full_size)
+}
+
 /// Calculate the sizes of each mipmap level.
 fn calculate_mipmap_sizes(header: &DdsHeader, dx10: Option<&DdsHeaderDxt10>) -> Vec<usize> {
     let mut sizes = Vec::with_capacity(header.mipmap_count as usize);
@@ -157,4 +222,41 @@ mod tests {
         assert_eq!(mipmap_size(8, 8, 16), 64);
         assert_eq!(mipmap_size(1024, 1024, 16), 1024 * 1024);
     }
+
+    #[test]
+    fn test_split_paths() {
+        let base = Path::new("tex_ddna.dds");
+        assert_eq!(split_path(base, 3), PathBuf::from("tex_ddna.dds.3"));
+
+        let gloss = glossmap_path(base);
+        assert_eq!(gloss, PathBuf::from("tex_ddna.dds.a"));
+        assert_eq!(split_path(&gloss, 3), PathBuf::from("tex_ddna.dds.3a"));
+    }
+
+    #[test]
+    fn test_merge_dds_data() {
+        use crate::header::FourCC;
+        use zerocopy::{FromZeros, IntoBytes};
+
+        // 16x16 DXT1 with 128 + 32 + 8 + 8 + 8 bytes of mipmaps
+        let mut header = DdsHeader::new_zeroed();
+        header.size = DdsHeader::SIZE;
+        header.width = 16;
+        header.height = 16;
+        header.mipmap_count = 5;
+        header.pixel_format.size = 32;
+        header.pixel_format.flags = 0x4;
+        header.pixel_format.four_cc = FourCC::DXT1;
+        let mut dds = crate::DDS_MAGIC.to_vec();
+        dds.extend_from_slice(header.as_bytes());
+        for (level, size) in [128, 32, 8, 8, 8].into_iter().enumerate() {
+            dds.resize(dds.len() + size, level as u8);
+        }
+
+        let (base, parts) = split_dds(&dds, 2).unwrap();
+        assert_eq!(parts.iter().map(|(i, _)| *i).collect::<Vec<_>>(), [2, 1]);
+        assert_eq!(merge_dds_data(&base, &parts).unwrap(), dds);
+
+        assert!(merge_dds_data(b"garbage", &parts).is_err());
+    }
 }
@@ -0,0 +1,74 @@
+//! Mipmap chain generation.
+
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+/// Downsampling filter for [`generate_mips`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MipFilter {
+    /// Average of each 2x2 block; fast and what most tools use.
+    #[default]
+    Box,
+    /// Bilinear.
+    Triangle,
+    /// Bicubic (Catmull-Rom).
+    CatmullRom,
+    /// Lanczos with a window of 3; sharpest, may ring.
+    Lanczos3,
+}
+
+/// Build the full mipmap chain of `image`, from the image itself down to
+/// 1x1.
+pub fn generate_mips(image: &RgbaImage, filter: MipFilter) -> Vec<RgbaImage> {
+    let mut mips = vec![image.clone()];
+    loop {
+        let last = mips.last().unwrap();
+        let (width, height) = last.dimensions();
+        if width <= 1 && height <= 1 {
+            break;
+        }
+        let (width, height) = ((width / 2).max(1), (height / 2).max(1));
+        let next = match filter {
+            MipFilter::Box => downsample_box(last, width, height),
+            MipFilter::Triangle => imageops::resize(last, width, height, FilterType::Triangle),
+            MipFilter::CatmullRom => imageops::resize(last, width, height, FilterType::CatmullRom),
+            MipFilter::Lanczos3 => imageops::resize(last, width, height, FilterType::Lanczos3),
+        };
+        mips.push(next);
+    }
+    mips
+}
+
+fn downsample_box(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    let (src_width, src_height) = image.dimensions();
+    RgbaImage::from_fn(width, height, |x, y| {
+        let xs = [2 * x, (2 * x + 1).min(src_width - 1)];
+        let ys = [2 * y, (2 * y + 1).min(src_height - 1)];
+        let mut sum = [0u32; 4];
+        for &sy in &ys {
+            for &sx in &xs {
+                let p = image.get_pixel(sx, sy).0;
+                for c in 0..4 {
+                    sum[c] += p[c] as u32;
+                }
+            }
+        }
+        Rgba(sum.map(|v| ((v + 2) / 4) as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_mips() {
+        let image = RgbaImage::from_fn(8, 2, |x, _| Rgba([x as u8 * 10, 0, 0, 255]));
+        let mips = generate_mips(&image, MipFilter::Box);
+
+        let dims: Vec<_> = mips.iter().map(|m| m.dimensions()).collect();
+        assert_eq!(dims, [(8, 2), (4, 1), (2, 1), (1, 1)]);
+        assert_eq!(mips[1].get_pixel(0, 0).0, [5, 0, 0, 255]);
+        assert_eq!(mips[3].get_pixel(0, 0).0, [35, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Parallel merging of split DDS files below a directory.
+//!
+//! [`merge_dds_tree`] finds every base `.dds` (and glossmap `.dds.a`) that
+//! has split siblings, merges them on the rayon thread pool and writes the
+//! result over the base file. Every merged texture gets a
+//! [`MergeTreeResult`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_dds::{merge_dds_tree, MergeTreeOptions};
+//!
+//! let options = MergeTreeOptions::new().delete_parts(true);
+//! let report = merge_dds_tree("extracted/", &options)?;
+//! println!("{} merged, {} failed", report.merged(), report.failed());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::merge::{is_complete, split_path};
+use crate::{merge_dds, Result};
+
+/// Options for [`merge_dds_tree`].
+#[derive(Debug, Clone, Default)]
+pub struct MergeTreeOptions {
+    delete_parts: bool,
+}
+
+impl MergeTreeOptions {
+    /// Create options that merge in place and keep the split parts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delete the `.N` parts once their base file is merged.
+    pub fn delete_parts(mut self, delete: bool) -> Self {
+        self.delete_parts = delete;
+        self
+    }
+}
+
+/// What happened to a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStatus {
+    /// The parts were merged into the base file.
+    Merged,
+    /// The base file already held every mipmap and was left alone.
+    AlreadyMerged,
+}
+
+/// Outcome of one texture.
+#[derive(Debug)]
+pub struct MergeTreeResult {
+    /// Base file.
+    pub path: PathBuf,
+    /// Split parts found next to it.
+    pub parts: Vec<PathBuf>,
+    /// Status, or the error that stopped the merge.
+    pub status: Result<MergeStatus>,
+}
+
+/// Results of a [`merge_dds_tree`] run, in no particular order.
+#[derive(Debug, Default)]
+pub struct MergeTreeReport {
+    /// One entry per base file with split parts.
+    pub results: Vec<MergeTreeResult>,
+}
+
+impl MergeTreeReport {
+    /// Number of textures merged.
+    pub fn merged(&self) -> usize {
+        self.count(|s| matches!(s, Ok(MergeStatus::Merged)))
+    }
+
+    /// Number of textures that were already merged.
+    pub fn already_merged(&self) -> usize {
+        self.count(|s| matches!(s, Ok(MergeStatus::AlreadyMerged)))
+    }
+
+    /// Number of textures that failed to merge.
+    pub fn failed(&self) -> usize {
+        self.count(|s| s.is_err())
+    }
+
+    /// Total number of split parts of successfully handled textures.
+    pub fn parts(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.status.is_ok())
+            .map(|r| r.parts.len())
+            .sum()
+    }
+
+    /// Iterate over the failed textures.
+    pub fn failures(&self) -> impl Iterator<Item = &MergeTreeResult> {
+        self.results.iter().filter(|r| r.status.is_err())
+    }
+
+    fn count(&self, f: impl Fn(&Result<MergeStatus>) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.status)).count()
+    }
+}
+
+/// Merge all split DDS files below `dir` in parallel.
+pub fn merge_dds_tree(
+    dir: impl AsRef<Path>,
+    options: &MergeTreeOptions,
+) -> Result<MergeTreeReport> {
+    merge_dds_tree_with(dir, options, |_| {})
+}
+
+/// Like [`merge_dds_tree`], calling `on_file` from the worker threads as
+/// each texture finishes, e.g. to advance a progress bar.
+pub fn merge_dds_tree_with<F>(
+    dir: impl AsRef<Path>,
+    options: &MergeTreeOptions,
+    on_file: F,
+) -> Result<MergeTreeReport>
+where
+    F: Fn(&MergeTreeResult) + Sync,
+{
+    let mut bases = Vec::new();
+    collect_bases(dir.as_ref(), &mut bases)?;
+
+    let results = bases
+        .into_par_iter()
+        .map(|(path, parts)| {
+            let status = merge_in_place(&path, &parts, options);
+            let result = MergeTreeResult {
+                path,
+                parts,
+                status,
+            };
+            on_file(&result);
+            result
+        })
+        .collect();
+
+    Ok(MergeTreeReport { results })
+}
+
+fn merge_in_place(
+    path: &Path,
+    parts: &[PathBuf],
+    options: &MergeTreeOptions,
+) -> Result<MergeStatus> {
+    // Merging an already merged file again would duplicate its mipmaps
+    let status = if is_complete(&fs::read(path)?)? {
+        MergeStatus::AlreadyMerged
+    } else {
+        let merged = merge_dds(path)?;
+        fs::write(path, merged)?;
+        MergeStatus::Merged
+    };
+
+    if options.delete_parts {
+        for part in parts {
+            fs::remove_file(part)?;
+        }
+    }
+    Ok(status)
+}
+
+/// Whether a file name is a base color (`.dds`) or glossmap (`.dds.a`)
+/// stream.
+fn is_base(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    name.ends_with(".dds") || name.ends_with(".dds.a")
+}
+
+fn collect_bases(dir: &Path, out: &mut Vec<(PathBuf, Vec<PathBuf>)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_bases(&path, out)?;
+        } else if file_type.is_file() && is_base(&path) {
+            let parts: Vec<PathBuf> = (0..=9)
+                .map(|i| split_path(&path, i))
+                .filter(|p| p.is_file())
+                .collect();
+            if !parts.is_empty() {
+                out.push((path, parts));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::{DdsHeader, FourCC};
+    use zerocopy::{FromZeros, IntoBytes};
+
+    /// 8x8 DXT1 base file holding only its 4x4 mipmap.
+    fn split_base() -> Vec<u8> {
+        let mut header = DdsHeader::new_zeroed();
+        header.size = DdsHeader::SIZE;
+        header.width = 8;
+        header.height = 8;
+        header.mipmap_count = 2;
+        header.pixel_format.size = 32;
+        header.pixel_format.flags = 0x4;
+        header.pixel_format.four_cc = FourCC::DXT1;
+
+        let mut data = crate::DDS_MAGIC.to_vec();
+        data.extend_from_slice(header.as_bytes());
+        data.extend_from_slice(&[1; 8]);
+        data
+    }
+
+    #[test]
+    fn test_merge_dds_tree() {
+        let root = std::env::temp_dir().join(format!("svarog-dds-tree-{}", std::process::id()));
+        fs::create_dir_all(root.join("textures")).unwrap();
+
+        let base = root.join("textures/hull.dds");
+        fs::write(&base, split_base()).unwrap();
+        fs::write(root.join("textures/hull.dds.1"), [2; 32]).unwrap();
+        fs::write(root.join("plain.dds"), split_base()).unwrap();
+        fs::write(root.join("broken.dds"), b"garbage").unwrap();
+        fs::write(root.join("broken.dds.1"), [2; 32]).unwrap();
+
+        let report = merge_dds_tree(&root, &MergeTreeOptions::new()).unwrap();
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.merged(), 1);
+        assert_eq!(report.failed(), 1);
+        assert_eq!(report.parts(), 1);
+
+        let merged = fs::read(&base).unwrap();
+        let header_size = 4 + std::mem::size_of::<DdsHeader>();
+        assert_eq!(merged.len(), header_size + 40);
+        assert_eq!(&merged[header_size..header_size + 32], &[2; 32]);
+        assert_eq!(&merged[header_size + 32..], &[1; 8]);
+
+        // A second run must not merge again, but may clean up the parts
+        let options = MergeTreeOptions::new().delete_parts(true);
+        let report = merge_dds_tree(&root, &options).unwrap();
+        assert_eq!(report.already_merged(), 1);
+        assert_eq!(fs::read(&base).unwrap(), merged);
+        assert!(!root.join("textures/hull.dds.1").exists());
+        assert!(root.join("broken.dds.1").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,8 +1,15 @@
 //! Main application
 
+use std::path::PathBuf;
+
 use eframe::egui::{self, RichText};
 
-use crate::panels::{DataCoreBrowserPanel, ExtractionDialog, P4kBrowserPanel};
+use crate::open::FileKind;
+use crate::panels::{
+    BookmarksMenu, DataCoreBrowserPanel, ExtractionDialog, GlobalSearchPanel, LogPanel,
+    P4kBrowserPanel, P4kDiffPanel,
+};
+use crate::session::{self, Session};
 use crate::state::{ActiveTab, AppState};
 use crate::widgets::error_toast;
 
@@ -11,10 +18,26 @@ pub struct SvarogApp {
 }
 
 impl SvarogApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self {
-            state: AppState::new(),
+    /// `paths` are opened after the saved session is restored, replacing
+    /// its P4K or DCB if they include one
+    pub fn new(cc: &eframe::CreationContext<'_>, paths: Vec<PathBuf>) -> Self {
+        let mut state = AppState::new();
+        state.log.install();
+        if let Some(mut session) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Session>(storage, session::STORAGE_KEY))
+        {
+            let kinds: Vec<FileKind> = paths.iter().map(|p| FileKind::of(p)).collect();
+            if kinds.contains(&FileKind::P4k) {
+                session.p4k_path = None;
+            }
+            if kinds.contains(&FileKind::Dcb) {
+                session.dcb_path = None;
+            }
+            state.restore_session(session);
         }
+        state.queue_open(paths);
+        Self { state }
     }
 }
 
@@ -22,12 +45,28 @@ impl eframe::App for SvarogApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Process background worker messages
         self.state.process_messages();
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|f| f.path.clone())
+                .collect()
+        });
+        self.state.queue_open(dropped);
+        self.state.process_open_queue();
+        if let Some(entry) = self.state.reload_entry.take() {
+            DataCoreBrowserPanel::load_entry_without_history(&mut self.state, entry);
+        }
 
         // Request repaint if we have active operations
         if self.state.p4k_loading
             || self.state.datacore_loading
             || self.state.extracting
             || self.state.preview_loading
+            || self.state.diff_loading
+            || self.state.diff_content_loading
+            || self.state.global_search_pending()
+            || !self.state.open_queue.is_empty()
         {
             ctx.request_repaint();
         }
@@ -41,9 +80,7 @@ impl eframe::App for SvarogApp {
                             .add_filter("P4K Archive", &["p4k"])
                             .pick_file()
                         {
-                            self.state.p4k_loading = true;
-                            self.state.p4k_path = Some(path.clone());
-                            crate::worker::load_p4k(path, self.state.worker_sender.clone());
+                            self.state.open_p4k(path);
                         }
                         ui.close_menu();
                     }
@@ -53,15 +90,50 @@ impl eframe::App for SvarogApp {
                             .add_filter("DataCore Database", &["dcb"])
                             .pick_file()
                         {
-                            match std::fs::read(&path) {
-                                Ok(data) => {
-                                    self.state.datacore_loading = true;
-                                    crate::worker::load_datacore(data, self.state.worker_sender.clone());
-                                }
-                                Err(e) => {
-                                    self.state.show_error(format!("Failed to read file: {}", e));
-                                }
-                            }
+                            self.state.open_dcb(path);
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .button("Open Locale...")
+                        .on_hover_text("global.ini used to show the text of Locale values")
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Localization", &["ini"])
+                            .pick_file()
+                        {
+                            self.state.locale_path = Some(path.clone());
+                            crate::worker::load_locale(path, self.state.worker_sender.clone());
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .button("Open Vorbis Codebooks...")
+                        .on_hover_text(
+                            "Packed codebook library, such as packed_codebooks_aoTuV_603.bin \
+                             from ww2ogg, used to decode Wwise Vorbis audio",
+                        )
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Codebook library", &["bin"])
+                            .pick_file()
+                        {
+                            self.state.open_codebooks(path);
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .button("Open File...")
+                        .on_hover_text("P4K, DCB, or any file to preview, such as CHF or DDS")
+                        .clicked()
+                    {
+                        if let Some(paths) = rfd::FileDialog::new().pick_files() {
+                            self.state.queue_open(paths);
                         }
                         ui.close_menu();
                     }
@@ -73,6 +145,16 @@ impl eframe::App for SvarogApp {
                     }
                 });
 
+                ui.menu_button("View", |ui| {
+                    if ui.checkbox(&mut self.state.log_open, "Log").clicked() {
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Bookmarks", |ui| {
+                    BookmarksMenu::show(ui, &mut self.state);
+                });
+
                 ui.menu_button("Help", |ui| {
                     if ui.button("About").clicked() {
                         self.state.about_open = true;
@@ -95,6 +177,14 @@ impl eframe::App for SvarogApp {
                     ActiveTab::DataCoreBrowser,
                     RichText::new("[DCB] DataCore").size(14.0),
                 );
+                ui.selectable_value(
+                    &mut self.state.active_tab,
+                    ActiveTab::P4kDiff,
+                    RichText::new("[DIFF] Compare").size(14.0),
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    GlobalSearchPanel::search_box(ui, &mut self.state);
+                });
             });
         });
 
@@ -135,11 +225,15 @@ impl eframe::App for SvarogApp {
             });
         });
 
+        // Log panel, above the status bar
+        LogPanel::show(ctx, &mut self.state);
+
         // Main content area
         egui::CentralPanel::default().show(ctx, |ui| {
             match self.state.active_tab {
                 ActiveTab::P4kBrowser => P4kBrowserPanel::show(ui, &mut self.state),
                 ActiveTab::DataCoreBrowser => DataCoreBrowserPanel::show(ui, &mut self.state),
+                ActiveTab::P4kDiff => P4kDiffPanel::show(ui, &mut self.state),
             }
         });
 
@@ -169,7 +263,43 @@ impl eframe::App for SvarogApp {
                 });
         }
 
+        // Global search results
+        GlobalSearchPanel::show(ctx, &mut self.state);
+
         // Extraction dialog
         ExtractionDialog::show(ctx, &mut self.state);
+
+        drop_overlay(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, session::STORAGE_KEY, &self.state.session());
     }
 }
+
+/// Dim the window while files are dragged over it
+fn drop_overlay(ctx: &egui::Context) {
+    let count = ctx.input(|i| i.raw.hovered_files.len());
+    if count == 0 {
+        return;
+    }
+
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("drop_overlay"),
+    ));
+    let rect = ctx.screen_rect();
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(192));
+    let text = if count == 1 {
+        "Drop to open".to_string()
+    } else {
+        format!("Drop to open {} files", count)
+    };
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(24.0),
+        egui::Color32::WHITE,
+    );
+}
@@ -0,0 +1,195 @@
+//! Tracing output collected for the log panel
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Maximum number of lines kept before the oldest are dropped.
+const MAX_LINES: usize = 2000;
+
+/// One line of the log panel.
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Shared buffer of recent log lines.
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+}
+
+impl LogBuffer {
+    /// Install a global subscriber writing into this buffer.
+    ///
+    /// The filter is read from `SVAROG_LOG` and defaults to `info`.
+    pub fn install(&self) {
+        let filter =
+            EnvFilter::try_from_env("SVAROG_LOG").unwrap_or_else(|_| EnvFilter::new("info"));
+        let _ = tracing_subscriber::registry()
+            .with(filter)
+            .with(LogLayer {
+                buffer: self.clone(),
+            })
+            .try_init();
+    }
+
+    /// Copy of the buffered lines, oldest first.
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.lines.lock().iter().cloned().collect()
+    }
+
+    /// Drop all buffered lines.
+    pub fn clear(&self) {
+        self.lines.lock().clear();
+    }
+
+    fn push(&self, line: LogLine) {
+        let mut lines = self.lines.lock();
+        if lines.len() == MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// Fields of a span, and when it was entered for the first time.
+struct SpanTiming {
+    fields: String,
+    start: Instant,
+}
+
+struct LogLayer {
+    buffer: LogBuffer,
+}
+
+impl<S> Layer<S> for LogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                fields: visitor.fields,
+                start: Instant::now(),
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.buffer.push(LogLine {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.finish(),
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        self.buffer.push(LogLine {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: format!(
+                "{} finished in {:.1} ms{}",
+                metadata.name(),
+                timing.start.elapsed().as_secs_f64() * 1000.0,
+                timing.fields
+            ),
+        });
+    }
+}
+
+/// Formats the message and ` key=value` pairs of an event or span.
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+
+impl FieldVisitor {
+    fn finish(self) -> String {
+        self.message + &self.fields
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(f: impl FnOnce()) -> Vec<LogLine> {
+        let buffer = LogBuffer::default();
+        let subscriber = tracing_subscriber::registry().with(LogLayer {
+            buffer: buffer.clone(),
+        });
+        tracing::subscriber::with_default(subscriber, f);
+        buffer.lines()
+    }
+
+    #[test]
+    fn test_events_and_spans() {
+        let lines = capture(|| {
+            let span = tracing::info_span!("dcb_parse", size = 42);
+            span.in_scope(|| tracing::warn!(records = 3, "parsed"));
+        });
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].level, Level::WARN);
+        assert_eq!(lines[0].message, "parsed records=3");
+        assert!(lines[1].message.starts_with("dcb_parse finished in "));
+        assert!(lines[1].message.ends_with(" ms size=42"));
+    }
+
+    #[test]
+    fn test_oldest_lines_dropped() {
+        let lines = capture(|| {
+            for i in 0..MAX_LINES + 5 {
+                tracing::info!(i);
+            }
+        });
+
+        assert_eq!(lines.len(), MAX_LINES);
+        assert_eq!(lines[0].message, " i=5");
+    }
+}
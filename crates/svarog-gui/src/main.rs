@@ -1,20 +1,29 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod log;
+mod open;
 mod panels;
 mod preview;
+mod session;
 mod state;
 mod widgets;
 mod worker;
 
+use std::path::PathBuf;
+
 use app::SvarogApp;
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
+    // Files to open, as passed by the OS for an associated file type
+    let paths: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1400.0, 900.0])
             .with_min_inner_size([800.0, 600.0])
+            .with_app_id("svarog-gui")
             .with_icon(load_icon()),
         ..Default::default()
     };
@@ -22,7 +31,7 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Svarog - Star Citizen File Browser",
         options,
-        Box::new(|cc| Ok(Box::new(SvarogApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(SvarogApp::new(cc, paths)))),
     )
 }
 
@@ -30,3 +39,29 @@ fn load_icon() -> egui::IconData {
     // Simple default icon - could be replaced with actual icon
     egui::IconData::default()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Write an archive of `files`, stored uncompressed, to the temporary
+    /// directory.
+    pub(crate) fn write_p4k(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("svarog-gui-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+}
@@ -0,0 +1,160 @@
+//! Opening files picked in a dialog, dropped onto the window or passed on
+//! the command line, as when the OS launches the application for a file
+//! associated with it
+//!
+//! Files are opened in the panel for their type: P4K archives in the P4K
+//! browser, DataCore databases in the DataCore browser, and anything else,
+//! such as `.chf` characters or `.dds` textures, in the file preview.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::state::{ActiveTab, AppState, PreviewData};
+
+/// How a file is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    P4k,
+    Dcb,
+    /// Shown in the file preview, as if it were a P4K entry
+    Loose,
+}
+
+impl FileKind {
+    pub fn of(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "p4k" => FileKind::P4k,
+            "dcb" => FileKind::Dcb,
+            _ => FileKind::Loose,
+        }
+    }
+}
+
+impl AppState {
+    /// Queue files to be opened one after another
+    pub fn queue_open(&mut self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.open_queue.extend(paths);
+    }
+
+    /// Open the next queued file once nothing else is loading, so a P4K and
+    /// a DCB dropped together do not replace each other half-loaded
+    pub fn process_open_queue(&mut self) {
+        if self.p4k_loading || self.datacore_loading || self.preview_loading {
+            return;
+        }
+        if let Some(path) = self.open_queue.pop_front() {
+            self.open_path(path);
+        }
+    }
+
+    /// Open a file in the panel for its type and switch to that panel
+    pub fn open_path(&mut self, path: PathBuf) {
+        match FileKind::of(&path) {
+            FileKind::P4k => {
+                self.active_tab = ActiveTab::P4kBrowser;
+                self.open_p4k(path);
+            }
+            FileKind::Dcb => {
+                self.active_tab = ActiveTab::DataCoreBrowser;
+                self.open_dcb(path);
+            }
+            FileKind::Loose => {
+                self.active_tab = ActiveTab::P4kBrowser;
+                self.open_loose_file(path);
+            }
+        }
+    }
+
+    /// Load a P4K archive in the background
+    pub fn open_p4k(&mut self, path: PathBuf) {
+        self.p4k_loading = true;
+        self.p4k_path = Some(path.clone());
+        crate::worker::load_p4k(path, self.worker_sender.clone());
+    }
+
+    /// Load a standalone DCB file in the background
+    pub fn open_dcb(&mut self, path: PathBuf) {
+        match std::fs::read(&path) {
+            Ok(data) => {
+                self.datacore_loading = true;
+                self.dcb_path = Some(path);
+                crate::worker::load_datacore(data, self.worker_sender.clone());
+            }
+            Err(e) => self.show_error(format!("Failed to read file: {}", e)),
+        }
+    }
+
+    /// Preview a file from disk, next to the P4K tree if one is loaded
+    pub fn open_loose_file(&mut self, path: PathBuf) {
+        self.selected_file = None;
+        self.preview = PreviewData::None;
+        self.preview_loading = true;
+        self.loose_file = Some(path.clone());
+        crate::worker::load_file_preview(path, self.codebooks.clone(), self.worker_sender.clone());
+    }
+
+    /// Load the Vorbis codebook library that Wwise Vorbis audio refers to
+    pub fn open_codebooks(&mut self, path: PathBuf) {
+        match svarog::wwise::CodebookLibrary::open(&path) {
+            Ok(library) => {
+                self.codebooks = Some(Arc::new(library));
+                self.codebooks_path = Some(path);
+            }
+            Err(e) => self.show_error(format!("Failed to load codebooks: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::WorkerMessage;
+
+    #[test]
+    fn test_file_kind() {
+        assert_eq!(FileKind::of(Path::new("C:/Games/Data.P4K")), FileKind::P4k);
+        assert_eq!(FileKind::of(Path::new("Game.dcb")), FileKind::Dcb);
+        assert_eq!(FileKind::of(Path::new("head.chf")), FileKind::Loose);
+        assert_eq!(FileKind::of(Path::new("p4k")), FileKind::Loose);
+    }
+
+    #[test]
+    fn test_open_queue() {
+        let dir = std::env::temp_dir().join(format!("svarog-gui-open-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let text = dir.join("notes.txt");
+        std::fs::write(&text, "hello").unwrap();
+
+        let mut state = AppState::new();
+        state.queue_open([dir.join("missing.dcb"), text.clone()]);
+
+        state.process_open_queue();
+        assert_eq!(state.active_tab, ActiveTab::DataCoreBrowser);
+        assert!(!state.datacore_loading);
+        assert!(state
+            .error_message
+            .as_deref()
+            .is_some_and(|e| e.starts_with("Failed to read file")));
+
+        state.process_open_queue();
+        assert_eq!(state.active_tab, ActiveTab::P4kBrowser);
+        assert_eq!(state.loose_file, Some(text));
+        assert!(state.open_queue.is_empty());
+
+        // Nothing more is opened until the preview has loaded
+        state.queue_open([dir.join("Data.p4k")]);
+        state.process_open_queue();
+        assert_eq!(state.open_queue.len(), 1);
+        let message = state.worker_receiver.recv().unwrap();
+        assert!(matches!(
+            message,
+            WorkerMessage::FilePreviewReady(PreviewData::Text(text)) if text == "hello"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+//! Bookmarks menu and toggles
+
+use eframe::egui::{Color32, RichText, Ui};
+
+use crate::panels::{DataCoreBrowserPanel, P4kBrowserPanel};
+use crate::session::Location;
+use crate::state::{ActiveTab, AppState};
+
+pub struct BookmarksMenu;
+
+impl BookmarksMenu {
+    /// Contents of the "Bookmarks" menu
+    pub fn show(ui: &mut Ui, state: &mut AppState) {
+        if state.bookmarks.is_empty() {
+            ui.label(
+                RichText::new("No bookmarks yet. Use 'Bookmark' next to a record, type or file.")
+                    .color(Color32::from_gray(120)),
+            );
+            return;
+        }
+
+        let mut open = None;
+        let mut remove = None;
+        for (i, location) in state.bookmarks.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .small_button("x")
+                    .on_hover_text("Remove bookmark")
+                    .clicked()
+                {
+                    remove = Some(i);
+                }
+                if ui.button(location.label()).clicked() {
+                    open = Some(location.clone());
+                }
+            });
+        }
+
+        if let Some(i) = remove {
+            state.bookmarks.remove(i);
+        }
+        if let Some(location) = open {
+            ui.close_menu();
+            jump(state, &location);
+        }
+    }
+}
+
+/// Button adding or removing `location` from the bookmarks
+pub fn bookmark_toggle(ui: &mut Ui, bookmarks: &mut Vec<Location>, location: Location) {
+    let position = bookmarks.iter().position(|b| *b == location);
+    let label = if position.is_some() {
+        "Bookmarked"
+    } else {
+        "Bookmark"
+    };
+    if ui.selectable_label(position.is_some(), label).clicked() {
+        match position {
+            Some(i) => {
+                bookmarks.remove(i);
+            }
+            None => bookmarks.push(location),
+        }
+    }
+}
+
+/// Show a bookmarked location in its browser
+pub fn jump(state: &mut AppState, location: &Location) {
+    if let Location::File { path } = location {
+        match state.file_entry(path) {
+            Some(idx) => {
+                state.active_tab = ActiveTab::P4kBrowser;
+                P4kBrowserPanel::select_entry(state, idx, path);
+            }
+            None => state.show_error(format!("{} is not in the loaded P4K", path)),
+        }
+        return;
+    }
+
+    match state.navigation_entry(location) {
+        Some(entry) => {
+            state.active_tab = ActiveTab::DataCoreBrowser;
+            DataCoreBrowserPanel::navigate_to(state, entry);
+        }
+        None if state.datacore.is_none() => state.show_error("Load a DataCore database first"),
+        None => state.show_error(format!(
+            "{} is not in the loaded DataCore",
+            location.label()
+        )),
+    }
+}
@@ -2,11 +2,15 @@
 
 use eframe::egui::{self, Color32, RichText, ScrollArea, Ui, Sense, Vec2, Key, CursorIcon};
 use std::sync::Arc;
+use svarog::datacore::{Exporter, InstanceRef};
 
-use crate::state::{AppState, DataCorePage, DataCoreRecordNode, DataCoreTypeNode, IncomingReference, NavigationEntry, RecordReference, ReferenceIndex, ReferenceType, StructRefTarget, StructReferenceIndex, StructTypeReference};
-use crate::widgets::{progress_bar, search_box};
+use crate::state::{AppState, DataCorePage, DataCoreRecordNode, DataCoreTab, DataCoreTypeNode, IncomingReference, NavigationEntry, RecordReference, RecordView, ReferenceIndex, ReferenceType, StructRefTarget, StructReferenceIndex, StructTypeReference, StructView};
+use crate::session::Location;
+use crate::widgets::{progress_bar, search_box, tab_strip, TabAction};
 use crate::worker;
 
+use super::{bookmark_toggle, record_table, struct_hierarchy};
+
 pub struct DataCoreBrowserPanel;
 
 impl DataCoreBrowserPanel {
@@ -26,13 +30,7 @@ impl DataCoreBrowserPanel {
                     .add_filter("DataCore Database", &["dcb"])
                     .pick_file()
                 {
-                    match std::fs::read(&path) {
-                        Ok(data) => {
-                            state.datacore_loading = true;
-                            worker::load_datacore(data, state.worker_sender.clone());
-                        }
-                        Err(e) => state.show_error(format!("Failed to read file: {}", e)),
-                    }
+                    state.open_dcb(path);
                 }
             }
 
@@ -63,6 +61,29 @@ impl DataCoreBrowserPanel {
                     }
                 }
 
+                if state.datacore_page == DataCorePage::Records {
+                    let mut formats = vec!["xml".to_string(), "json".to_string()];
+                    for command in &state.export_commands {
+                        if !formats.iter().any(|f| f == command.name()) {
+                            formats.push(command.name().to_string());
+                        }
+                    }
+                    egui::ComboBox::from_id_salt("datacore_export_format")
+                        .selected_text(state.datacore_export_format.as_str())
+                        .show_ui(ui, |ui| {
+                            for format in formats {
+                                let label = format.clone();
+                                ui.selectable_value(
+                                    &mut state.datacore_export_format,
+                                    format,
+                                    label,
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Format of Export All");
+                }
+
                 ui.separator();
 
                 let can_go_back = state.navigation_index > 0;
@@ -158,6 +179,16 @@ impl DataCoreBrowserPanel {
 
         ui.separator();
 
+        if state.datacore_tabs.len() > 1 {
+            Self::show_tabs(ui, state);
+            ui.separator();
+        }
+
+        if state.datacore_export.is_some() {
+            Self::show_export_progress(ui, state);
+            ui.separator();
+        }
+
         // Loading state
         if state.datacore_loading {
             ui.vertical_centered(|ui| {
@@ -203,6 +234,7 @@ impl DataCoreBrowserPanel {
                                     let db = state.datacore.clone();
                                     let mut new_type_filter: Option<String> = None;
                                     let mut navigate_to: Option<usize> = None;
+                                    let mut open_in_tab: Option<usize> = None;
 
                                     if !search.is_empty() || type_filter.is_some() {
                                         for child in &mut tree.children {
@@ -216,6 +248,7 @@ impl DataCoreBrowserPanel {
                                             ui, child, &search, type_filter.as_deref(),
                                             selected, record_xml, record_refs, db.clone(),
                                             0, &mut row_index, &mut new_type_filter, &mut navigate_to,
+                                            &mut open_in_tab,
                                         );
                                     }
 
@@ -225,20 +258,39 @@ impl DataCoreBrowserPanel {
                                     if let Some(idx) = navigate_to {
                                         Self::navigate_to_record(state, idx);
                                     }
+                                    if let Some(idx) = open_in_tab {
+                                        Self::open_in_new_tab(state, NavigationEntry::Record(idx));
+                                    }
                                 }
                             });
 
                         columns[1].vertical(|ui| {
                             if let Some(record_idx) = state.selected_record {
                                 if let Some(db) = &state.datacore {
-                                    let records: Vec<_> = db.main_records().collect();
-                                    if let Some(record) = records.get(record_idx) {
+                                    if let Some(record) = db.main_record(record_idx) {
                                         let name = db.record_name(record).unwrap_or("Unknown");
                                         let type_name = db.struct_name(record.struct_index as usize).unwrap_or("Unknown");
                                         ui.horizontal(|ui| {
                                             ui.label(RichText::new("[R]").strong().color(Color32::from_rgb(100, 180, 255)));
                                             ui.label(RichText::new(name).monospace().color(Color32::from_rgb(100, 180, 255)));
                                             ui.label(RichText::new(format!("({})", type_name)).color(Color32::from_gray(120)).small());
+                                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                                let location = Location::Record { id: record.id.to_string(), name: name.to_string() };
+                                                bookmark_toggle(ui, &mut state.bookmarks, location);
+                                                ui.separator();
+                                                ui.selectable_value(&mut state.record_view, RecordView::Table, "Table")
+                                                    .on_hover_text("Properties as an expandable table");
+                                                ui.selectable_value(&mut state.record_view, RecordView::Xml, "XML");
+                                                if state.locale.is_some() {
+                                                    ui.separator();
+                                                    if ui.toggle_value(&mut state.show_locale_keys, "Locale keys")
+                                                        .on_hover_text("Show Locale values as keys instead of their global.ini text")
+                                                        .changed()
+                                                    {
+                                                        state.reload_entry = Some(NavigationEntry::Record(record_idx));
+                                                    }
+                                                }
+                                            });
                                         });
                                         ui.separator();
                                     }
@@ -251,6 +303,9 @@ impl DataCoreBrowserPanel {
                             let has_incoming = incoming_count > 0;
                             let refs_panel_height = 120.0;
                             let content_height = (panel_height - refs_panel_height - 60.0).max(100.0);
+                            let mut navigate_to_idx: Option<usize> = None;
+                            let beside = Self::beside_record(state);
+                            let mut close_beside = false;
 
                             egui::Frame::none()
                                 .fill(Color32::from_gray(25))
@@ -258,18 +313,60 @@ impl DataCoreBrowserPanel {
                                     ui.set_min_height(content_height);
                                     ui.set_max_height(content_height);
 
-                                    if state.record_xml.is_empty() {
-                                        ui.centered_and_justified(|ui| {
-                                            ui.label(RichText::new("Select a record to view its contents").color(Color32::from_gray(100)));
-                                        });
-                                    } else {
-                                        render_text_with_line_numbers(ui, &state.record_xml, "dcb_xml_scroll");
+                                    let mut show_record = |ui: &mut Ui| {
+                                        let record = state.selected_record.zip(state.datacore.clone());
+                                        match (state.record_view, record) {
+                                            (_, None) => {
+                                                ui.centered_and_justified(|ui| {
+                                                    ui.label(RichText::new("Select a record to view its contents").color(Color32::from_gray(100)));
+                                                });
+                                            }
+                                            (RecordView::Table, Some((idx, db))) => {
+                                                if let Some(record) = db.main_record(idx) {
+                                                    let instance = InstanceRef::new(record.struct_index as u32, record.instance_index as u32);
+                                                    record_table::show(
+                                                        ui,
+                                                        &db,
+                                                        instance,
+                                                        state.reference_index.as_deref(),
+                                                        state.locale.as_deref(),
+                                                        state.show_locale_keys,
+                                                        &mut state.record_table_expanded,
+                                                        &mut navigate_to_idx,
+                                                    );
+                                                }
+                                            }
+                                            (RecordView::Xml, Some(_)) => {
+                                                render_text_with_line_numbers(ui, &state.record_xml, "dcb_xml_scroll");
+                                            }
+                                        }
+                                    };
+
+                                    // Another tab's record side by side with this one
+                                    match &beside {
+                                        Some((tab, title)) => {
+                                            ui.columns(2, |columns| {
+                                                show_record(&mut columns[0]);
+                                                let ui = &mut columns[1];
+                                                ui.horizontal(|ui| {
+                                                    ui.label(RichText::new(title).monospace().color(Color32::from_rgb(100, 180, 255)));
+                                                    if ui.small_button("x").on_hover_text("Stop showing beside").clicked() {
+                                                        close_beside = true;
+                                                    }
+                                                });
+                                                ui.separator();
+                                                render_text_with_line_numbers(ui, &state.datacore_tabs[*tab].record_xml, "dcb_beside_scroll");
+                                            });
+                                        }
+                                        None => show_record(ui),
                                     }
                                 });
 
-                            ui.add_space(8.0);
+                            if close_beside {
+                                state.datacore_beside = None;
+                            }
 
-                            let mut navigate_to_idx: Option<usize> = None;
+                            ui.add_space(8.0);
 
                             ui.horizontal(|ui| {
                                 let half_width = (ui.available_width() / 2.0 - 8.0).max(100.0);
@@ -479,6 +576,15 @@ impl DataCoreBrowserPanel {
                                     ui.horizontal(|ui| {
                                         ui.label(RichText::new("[S]").strong().color(Color32::from_rgb(180, 220, 140)));
                                         ui.label(RichText::new(name).monospace().color(Color32::from_rgb(180, 220, 140)));
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            bookmark_toggle(ui, &mut state.bookmarks, Location::Struct { name: name.to_string() });
+                                            ui.separator();
+                                            ui.selectable_value(&mut state.struct_view, StructView::Inheritance, "Inheritance")
+                                                .on_hover_text("Ancestors and structs deriving from this one");
+                                            ui.selectable_value(&mut state.struct_view, StructView::Fields, "Fields")
+                                                .on_hover_text("All fields with their offsets and declaring ancestor");
+                                            ui.selectable_value(&mut state.struct_view, StructView::Layout, "Layout");
+                                        });
                                     });
                                     ui.separator();
                                 }
@@ -497,15 +603,25 @@ impl DataCoreBrowserPanel {
                                     ui.set_min_height(content_height);
                                     ui.set_max_height(content_height);
 
-                                    if state.type_preview.is_empty() {
-                                        ui.centered_and_justified(|ui| {
-                                            ui.label(
-                                                RichText::new("Select a struct to view its layout")
-                                                    .color(Color32::from_gray(100)),
-                                            );
-                                        });
-                                    } else {
-                                        render_text_with_line_numbers(ui, &state.type_preview, "dcb_type_preview_scroll");
+                                    let selected = state.selected_type.zip(state.datacore.as_deref());
+                                    match (state.struct_view, selected) {
+                                        (_, None) => {
+                                            ui.centered_and_justified(|ui| {
+                                                ui.label(
+                                                    RichText::new("Select a struct to view its layout")
+                                                        .color(Color32::from_gray(100)),
+                                                );
+                                            });
+                                        }
+                                        (StructView::Layout, Some(_)) => {
+                                            render_text_with_line_numbers(ui, &state.type_preview, "dcb_type_preview_scroll");
+                                        }
+                                        (StructView::Fields, Some((idx, db))) => {
+                                            struct_hierarchy::show_fields(ui, db, idx, &mut navigate_to_struct);
+                                        }
+                                        (StructView::Inheritance, Some((idx, db))) => {
+                                            struct_hierarchy::show_inheritance(ui, db, idx, &mut navigate_to_struct);
+                                        }
                                     }
                                 });
 
@@ -742,6 +858,9 @@ impl DataCoreBrowserPanel {
                                     ui.horizontal(|ui| {
                                         ui.label(RichText::new("[E]").strong().color(Color32::from_rgb(220, 180, 120)));
                                         ui.label(RichText::new(name).monospace().color(Color32::from_rgb(220, 180, 120)));
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            bookmark_toggle(ui, &mut state.bookmarks, Location::Enum { name: name.to_string() });
+                                        });
                                     });
                                     ui.separator();
                                 }
@@ -879,7 +998,7 @@ impl DataCoreBrowserPanel {
         }
     }
 
-    fn navigate_to(state: &mut AppState, entry: NavigationEntry) {
+    pub fn navigate_to(state: &mut AppState, entry: NavigationEntry) {
         // Don't add duplicate if it's the same as current
         if let Some(&current) = state.navigation_history.get(state.navigation_index) {
             if current == entry {
@@ -899,19 +1018,125 @@ impl DataCoreBrowserPanel {
         Self::load_entry_without_history(state, entry);
     }
 
-    fn navigate_to_record(state: &mut AppState, idx: usize) {
+    /// Open `entry` in a new tab with its own navigation history
+    pub fn open_in_new_tab(state: &mut AppState, entry: NavigationEntry) {
+        state.datacore_tabs.push(DataCoreTab::default());
+        Self::switch_tab(state, state.datacore_tabs.len() - 1);
+        Self::navigate_to(state, entry);
+    }
+
+    fn switch_tab(state: &mut AppState, index: usize) {
+        if index == state.datacore_tab || index >= state.datacore_tabs.len() {
+            return;
+        }
+
+        // Park the active tab and bring in the new one
+        let active = &mut state.datacore_tabs[state.datacore_tab];
+        active.navigation_history = std::mem::take(&mut state.navigation_history);
+        active.navigation_index = state.navigation_index;
+        active.record_xml = std::mem::take(&mut state.record_xml);
+
+        let tab = std::mem::take(&mut state.datacore_tabs[index]);
+        state.navigation_history = tab.navigation_history;
+        state.navigation_index = tab.navigation_index;
+        state.datacore_tab = index;
+        if state.datacore_beside == Some(index) {
+            state.datacore_beside = None;
+        }
+
+        match state
+            .navigation_history
+            .get(state.navigation_index)
+            .copied()
+        {
+            Some(entry) => Self::load_entry_without_history(state, entry),
+            None => {
+                state.selected_record = None;
+                state.record_references.clear();
+                state.incoming_references.clear();
+            }
+        }
+    }
+
+    fn close_tab(state: &mut AppState, index: usize) {
+        if state.datacore_tabs.len() < 2 || index >= state.datacore_tabs.len() {
+            return;
+        }
+        if index == state.datacore_tab {
+            Self::switch_tab(state, if index > 0 { index - 1 } else { 1 });
+        }
+        state.datacore_tabs.remove(index);
+        if state.datacore_tab > index {
+            state.datacore_tab -= 1;
+        }
+        state.datacore_beside = match state.datacore_beside {
+            Some(beside) if beside == index => None,
+            Some(beside) if beside > index => Some(beside - 1),
+            beside => beside,
+        };
+    }
+
+    fn show_tabs(ui: &mut Ui, state: &mut AppState) {
+        let titles: Vec<String> = (0..state.datacore_tabs.len())
+            .map(|i| {
+                let entry = if i == state.datacore_tab {
+                    state
+                        .navigation_history
+                        .get(state.navigation_index)
+                        .copied()
+                } else {
+                    state.datacore_tabs[i].current()
+                };
+                entry
+                    .and_then(|entry| state.location_of(entry))
+                    .map_or_else(|| "New tab".to_string(), |location| location.label())
+            })
+            .collect();
+
+        let active = state.datacore_tab;
+        let mut beside = None;
+        let action = tab_strip(ui, &titles, active, |ui, i| {
+            if i != active && ui.button("Show beside current").clicked() {
+                beside = Some(i);
+                ui.close_menu();
+            }
+        });
+
+        if let Some(i) = beside {
+            state.datacore_beside = Some(i);
+            state.datacore_page = DataCorePage::Records;
+        }
+        match action {
+            Some(TabAction::Select(i)) => Self::switch_tab(state, i),
+            Some(TabAction::Close(i)) => Self::close_tab(state, i),
+            None => {}
+        }
+    }
+
+    /// Background tab to show beside the active one, with its title, when
+    /// it shows a record
+    fn beside_record(state: &AppState) -> Option<(usize, String)> {
+        let index = state.datacore_beside.filter(|&i| i != state.datacore_tab)?;
+        let entry = state.datacore_tabs.get(index)?.current()?;
+        if !matches!(entry, NavigationEntry::Record(_)) {
+            return None;
+        }
+        Some((index, state.location_of(entry)?.label()))
+    }
+
+    pub fn navigate_to_record(state: &mut AppState, idx: usize) {
         Self::navigate_to(state, NavigationEntry::Record(idx));
     }
 
-    fn navigate_to_struct(state: &mut AppState, idx: usize) {
+    pub fn navigate_to_struct(state: &mut AppState, idx: usize) {
         Self::navigate_to(state, NavigationEntry::Struct(idx));
     }
 
-    fn navigate_to_enum(state: &mut AppState, idx: usize) {
+    pub fn navigate_to_enum(state: &mut AppState, idx: usize) {
         Self::navigate_to(state, NavigationEntry::Enum(idx));
     }
 
-    fn load_entry_without_history(state: &mut AppState, entry: NavigationEntry) {
+    pub fn load_entry_without_history(state: &mut AppState, entry: NavigationEntry) {
         match entry {
             NavigationEntry::Record(idx) => {
                 state.datacore_page = DataCorePage::Records;
@@ -929,14 +1154,20 @@ impl DataCoreBrowserPanel {
     }
 
     fn load_record_without_history(state: &mut AppState, idx: usize) {
+        if state.selected_record != Some(idx) {
+            state.record_table_expanded.clear();
+        }
         state.selected_record = Some(idx);
         state.selected_line = None;
 
         if let Some(db) = &state.datacore {
-            let records: Vec<_> = db.main_records().collect();
-            if let Some(record) = records.get(idx) {
+            if let Some(record) = db.main_record(idx) {
                 // Generate XML with 4-space indentation
-                match svarog::datacore::XmlExporter::new(db).export_record(record) {
+                let mut exporter = svarog::datacore::XmlExporter::new(db);
+                if let Some(locale) = state.locale.as_deref().filter(|_| !state.show_locale_keys) {
+                    exporter = exporter.with_locale(locale);
+                }
+                match exporter.export_record(record) {
                     Ok(xml) => {
                         // Convert 2-space to 4-space indentation
                         state.record_xml = xml.lines()
@@ -953,7 +1184,7 @@ impl DataCoreBrowserPanel {
                 state.record_references = extract_references(db, record, &state.reference_index);
 
                 // Extract incoming references from the index
-                state.incoming_references = extract_incoming_references(db, idx, &state.reference_index, &records);
+                state.incoming_references = extract_incoming_references(db, idx, &state.reference_index);
             }
         }
     }
@@ -985,6 +1216,66 @@ impl DataCoreBrowserPanel {
         }
     }
 
+    /// Progress bar and controls for a running "Export All" of records.
+    fn show_export_progress(ui: &mut Ui, state: &mut AppState) {
+        let Some(job) = state.datacore_export.clone() else {
+            return;
+        };
+        let progress = job.progress();
+        let finished = job.is_finished();
+
+        ui.horizontal(|ui| {
+            let label = if finished {
+                format!(
+                    "Exported {} of {} records ({} failed) in {:.1?}",
+                    progress.exported, progress.total, progress.failed, progress.elapsed
+                )
+            } else {
+                format!(
+                    "Exporting {}/{} records, {:.0} records/s, {}/s",
+                    progress.completed(),
+                    progress.total,
+                    progress.records_per_second(),
+                    crate::widgets::format_size(progress.bytes_per_second() as u64)
+                )
+            };
+            progress_bar(ui, progress.completed(), progress.total, &label);
+
+            if finished {
+                if ui.button("Dismiss").clicked() {
+                    state.datacore_export = None;
+                }
+            } else {
+                if job.is_paused() {
+                    if ui.button("Resume").clicked() {
+                        job.resume();
+                    }
+                } else if ui.button("Pause").clicked() {
+                    job.pause();
+                }
+                if ui.add_enabled(!job.is_cancelled(), egui::Button::new("Cancel")).clicked() {
+                    job.cancel();
+                }
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+        });
+
+        if finished && progress.failed > 0 {
+            egui::CollapsingHeader::new(format!("{} failed records", progress.failed))
+                .id_salt("datacore_export_failures")
+                .show(ui, |ui| {
+                    ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                        for failure in job.failures() {
+                            ui.label(
+                                RichText::new(format!("{}: {}", failure.path.display(), failure.error))
+                                    .color(Color32::from_rgb(220, 100, 100)),
+                            );
+                        }
+                    });
+                });
+        }
+    }
+
     fn load_datacore_from_p4k(state: &mut AppState) {
         if let Some(archive) = &state.p4k_archive {
             let dcb_names = ["Data/Game.dcb", "Data/Game2.dcb", "Game.dcb", "Game2.dcb"];
@@ -1004,6 +1295,7 @@ impl DataCoreBrowserPanel {
 
             if let Some(data) = found {
                 state.datacore_loading = true;
+                state.dcb_path = None;
                 worker::load_datacore(data, state.worker_sender.clone());
             }
         }
@@ -1097,6 +1389,7 @@ fn type_node_matches(node: &DataCoreTypeNode, search: &str) -> bool {
         || node.children.iter().any(|c| type_node_matches(c, search))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_type_tree(
     ui: &mut Ui,
     node: &mut DataCoreTypeNode,
@@ -1119,7 +1412,7 @@ fn render_type_tree(
     }
 
     let is_selected = node.struct_index.is_some()
-        && selected.map_or(false, |idx| Some(idx) == node.struct_index);
+        && selected.is_some_and(|idx| Some(idx) == node.struct_index);
 
     // Alternating background
     let row_bg = if *row_index % 2 == 1 {
@@ -1535,7 +1828,6 @@ fn extract_incoming_references(
     db: &Arc<svarog::datacore::DataCoreDatabase>,
     target_idx: usize,
     reference_index: &Option<std::sync::Arc<ReferenceIndex>>,
-    records: &[&svarog::datacore::structs::DataCoreRecord],
 ) -> Vec<IncomingReference> {
     let mut incoming = Vec::new();
 
@@ -1543,7 +1835,7 @@ fn extract_incoming_references(
 
     if let Some(refs) = index.incoming.get(&target_idx) {
         for (source_idx, property_name, ref_type) in refs {
-            if let Some(source_record) = records.get(*source_idx) {
+            if let Some(source_record) = db.main_record(*source_idx) {
                 let source_name = db.record_name(source_record).unwrap_or("Unknown").to_string();
                 let source_type = db.struct_name(source_record.struct_index as usize).unwrap_or("Unknown").to_string();
 
@@ -1682,7 +1974,7 @@ fn matches_filters(node: &DataCoreRecordNode, search: &str, type_filter: Option<
         || node.id.to_lowercase().contains(search)
 }
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::only_used_in_recursion)]
 fn render_record_tree(
     ui: &mut Ui,
     node: &mut DataCoreRecordNode,
@@ -1696,6 +1988,7 @@ fn render_record_tree(
     row_index: &mut usize,
     new_type_filter: &mut Option<String>,
     navigate_to: &mut Option<usize>,
+    open_in_tab: &mut Option<usize>,
 ) {
     let show_node = if search.is_empty() && type_filter.is_none() {
         true
@@ -1710,7 +2003,7 @@ fn render_record_tree(
 
     let is_selected = !node.is_folder
         && node.record_index.is_some()
-        && selected.as_ref().map_or(false, |s| Some(*s) == node.record_index);
+        && selected.as_ref().is_some_and(|s| Some(*s) == node.record_index);
 
     // Alternating background
     let row_bg = if *row_index % 2 == 1 {
@@ -1804,6 +2097,18 @@ fn render_record_tree(
                     }
                 }
 
+                if let Some(idx) = node.record_index.filter(|_| !node.is_folder) {
+                    if name_response.middle_clicked() {
+                        *open_in_tab = Some(idx);
+                    }
+                    name_response.context_menu(|ui| {
+                        if ui.button("Open in new tab").clicked() {
+                            *open_in_tab = Some(idx);
+                            ui.close_menu();
+                        }
+                    });
+                }
+
                 // Type for records (right-aligned, clickable)
                 if !node.is_folder {
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -1843,6 +2148,7 @@ fn render_record_tree(
                 row_index,
                 new_type_filter,
                 navigate_to,
+                open_in_tab,
             );
         }
     }
@@ -1879,10 +2185,9 @@ fn export_current(db: &Arc<svarog::datacore::DataCoreDatabase>, state: &mut AppS
         }
         DataCorePage::Records => {
             if let Some(record_idx) = state.selected_record {
-                let records: Vec<_> = db.main_records().collect();
-                if let Some(record) = records.get(record_idx) {
+                if let Some(record) = db.main_record(record_idx) {
                     let file_name = db.record_file_name(record).unwrap_or("record.xml");
-                    let suggested = file_name.replace('/', "_").replace('\\', "_");
+                    let suggested = file_name.replace(['/', '\\'], "_");
                     let xml = svarog::datacore::XmlExporter::new(db)
                         .export_record(record)
                         .map_err(|e| e.to_string())?;
@@ -1925,25 +2230,71 @@ fn export_all(db: &Arc<svarog::datacore::DataCoreDatabase>, state: &mut AppState
             }
         }
         DataCorePage::Records => {
-            let exporter = svarog::datacore::XmlExporter::new(db);
+            if state.datacore_export.as_ref().is_some_and(|job| !job.is_finished()) {
+                return Err("An export is already running".into());
+            }
             if let Some(dir) = rfd::FileDialog::new().set_directory(".").pick_folder() {
-                let records: Vec<_> = db.main_records().collect();
-                for record in records {
-                    let file_name = db
-                        .record_file_name(record)
-                        .unwrap_or("record.xml")
-                        .replace('/', std::path::MAIN_SEPARATOR_STR);
-                    let path = dir.join(file_name).with_extension("xml");
-                    if let Some(parent) = path.parent() {
-                        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                let job = svarog::datacore::ExportJob::new();
+                let handle = job.clone();
+                let db = db.clone();
+                let format = state.datacore_export_format.clone();
+                let commands = state.export_commands.clone();
+                std::thread::spawn(move || {
+                    let mut registry = svarog::datacore::ExporterRegistry::with_builtin(&db);
+                    for command in commands {
+                        if registry.get(command.name()).is_none() {
+                            registry.register(command);
+                        }
                     }
-                    let xml = exporter.export_record(record).map_err(|e| e.to_string())?;
-                    std::fs::write(&path, xml).map_err(|e| e.to_string())?;
-                }
-                Ok(())
-            } else {
-                Ok(())
+                    if let Some(exporter) = registry.get(&format) {
+                        let records: Vec<_> = db.main_records().collect();
+                        exporter.export_to_dir(&db, &records, &dir, &handle);
+                    }
+                });
+                state.datacore_export = Some(job);
             }
+            Ok(())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tabs() {
+        let mut state = AppState::new();
+        DataCoreBrowserPanel::navigate_to_record(&mut state, 1);
+        DataCoreBrowserPanel::navigate_to_record(&mut state, 2);
+        DataCoreBrowserPanel::open_in_new_tab(&mut state, NavigationEntry::Enum(3));
+
+        assert_eq!(state.datacore_tab, 1);
+        assert_eq!(state.navigation_history, [NavigationEntry::Enum(3)]);
+        let parked = &state.datacore_tabs[0];
+        assert_eq!(parked.navigation_history.len(), 2);
+        assert_eq!(parked.current(), Some(NavigationEntry::Record(2)));
+
+        // Switching back restores the tab's history and record
+        state.datacore_beside = Some(1);
+        DataCoreBrowserPanel::switch_tab(&mut state, 0);
+        assert_eq!(state.datacore_tab, 0);
+        assert_eq!(state.navigation_index, 1);
+        assert_eq!(state.selected_record, Some(2));
+        assert_eq!(
+            state.datacore_tabs[1].current(),
+            Some(NavigationEntry::Enum(3))
+        );
+
+        DataCoreBrowserPanel::close_tab(&mut state, 1);
+        assert_eq!(state.datacore_tabs.len(), 1);
+        assert_eq!(state.datacore_beside, None);
+
+        // The last tab can't be closed, nor a missing one selected
+        DataCoreBrowserPanel::close_tab(&mut state, 0);
+        DataCoreBrowserPanel::switch_tab(&mut state, 5);
+        assert_eq!(state.datacore_tabs.len(), 1);
+        assert_eq!(state.datacore_tab, 0);
+        assert_eq!(state.selected_record, Some(2));
+    }
+}
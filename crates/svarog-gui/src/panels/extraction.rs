@@ -1,8 +1,10 @@
 //! Extraction dialog
 
+use std::path::PathBuf;
+
 use eframe::egui::{self, Color32, RichText, Ui};
 
-use crate::state::AppState;
+use crate::state::{AppState, ExtractionProfile};
 use crate::widgets::progress_bar;
 
 pub struct ExtractionDialog;
@@ -32,7 +34,29 @@ impl ExtractionDialog {
         state.extraction_dialog_open = open;
     }
 
+    /// Start extracting with a saved profile, showing the progress
+    pub fn run_profile(state: &mut AppState, index: usize) {
+        let Some(profile) = state.extraction_profiles.get(index) else {
+            return;
+        };
+        state.extraction_options = profile.options.clone();
+        state.extraction_profile_name = profile.name.clone();
+        state.extraction_dialog_open = true;
+        if state.extraction_options.output_path.as_os_str().is_empty() {
+            // Leave the dialog open to pick one
+            state.show_error(format!(
+                "Profile '{}' has no output directory",
+                profile.name
+            ));
+            return;
+        }
+        start_extraction(state);
+    }
+
     fn show_options(ui: &mut Ui, state: &mut AppState) {
+        Self::show_profiles(ui, state);
+        ui.separator();
+
         ui.heading("Extraction Options");
         ui.add_space(10.0);
 
@@ -71,6 +95,14 @@ impl ExtractionDialog {
                     ui.checkbox(&mut state.extraction_options.incremental, "Incremental (skip existing)");
                     ui.checkbox(&mut state.extraction_options.expand_socpak, "Expand SOCPAK archives");
                     ui.checkbox(&mut state.extraction_options.extract_dcb, "Extract DataCore to XML");
+                    ui.checkbox(
+                        &mut state.extraction_options.decode_cryxml,
+                        "Decode CryXML to XML",
+                    );
+                    ui.checkbox(
+                        &mut state.extraction_options.merge_dds,
+                        "Merge split DDS textures",
+                    );
                 });
                 ui.end_row();
 
@@ -108,6 +140,76 @@ impl ExtractionDialog {
         });
     }
 
+    /// Pick, save and delete named profiles of the options below
+    fn show_profiles(ui: &mut Ui, state: &mut AppState) {
+        let selected = state
+            .extraction_profiles
+            .iter()
+            .position(|p| p.name == state.extraction_profile_name);
+
+        ui.horizontal(|ui| {
+            ui.label("Profile:");
+            let selected_text = match selected {
+                Some(i) => state.extraction_profiles[i].name.as_str(),
+                None => "(unsaved)",
+            };
+            egui::ComboBox::from_id_salt("extraction_profile")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for profile in &state.extraction_profiles {
+                        let is_selected = profile.name == state.extraction_profile_name;
+                        if ui.selectable_label(is_selected, &profile.name).clicked() {
+                            state.extraction_options = profile.options.clone();
+                            state.extraction_profile_name = profile.name.clone();
+                        }
+                    }
+                });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut state.extraction_profile_name)
+                    .hint_text("Profile name")
+                    .desired_width(140.0),
+            );
+
+            let name = state.extraction_profile_name.trim().to_string();
+            let modified = selected
+                .is_some_and(|i| state.extraction_profiles[i].options != state.extraction_options);
+            let save_label = if selected.is_some() { "Update" } else { "Save" };
+            if ui
+                .add_enabled(
+                    !name.is_empty() && (selected.is_none() || modified),
+                    egui::Button::new(save_label),
+                )
+                .clicked()
+            {
+                let profile = ExtractionProfile {
+                    name: name.clone(),
+                    options: state.extraction_options.clone(),
+                };
+                match state
+                    .extraction_profiles
+                    .iter_mut()
+                    .find(|p| p.name == name)
+                {
+                    Some(existing) => *existing = profile,
+                    None => state.extraction_profiles.push(profile),
+                }
+                state.extraction_profile_name = name;
+            }
+            if ui
+                .add_enabled(selected.is_some(), egui::Button::new("Delete"))
+                .clicked()
+            {
+                if let Some(i) = selected {
+                    state.extraction_profiles.remove(i);
+                }
+            }
+            if modified {
+                ui.label(RichText::new("modified").color(Color32::YELLOW));
+            }
+        });
+    }
+
     fn show_progress(ui: &mut Ui, state: &mut AppState) {
         ui.heading("Extracting...");
         ui.add_space(20.0);
@@ -132,10 +234,8 @@ impl ExtractionDialog {
         // Cancel button would go here - but extraction is typically fast enough
         // that cancellation isn't usually needed
         ui.horizontal(|ui| {
-            if ui.button("Close").clicked() {
-                if !state.extracting {
-                    state.extraction_dialog_open = false;
-                }
+            if ui.button("Close").clicked() && !state.extracting {
+                state.extraction_dialog_open = false;
             }
         });
     }
@@ -193,6 +293,23 @@ fn start_extraction(state: &mut AppState) {
             }
         };
 
+        // Conversions applied to each entry on its way to disk
+        let mut processors = svarog::process::Pipeline::new();
+        if options.expand_socpak {
+            processors.push(Box::new(svarog::process::SocpakProcessor));
+        }
+        if options.decode_cryxml {
+            processors.push(Box::new(svarog::process::CryXmlProcessor));
+        }
+        if options.extract_dcb {
+            processors.push(Box::new(
+                svarog::process::DataCoreProcessor::new().root(output_path),
+            ));
+        }
+        if options.merge_dds {
+            processors.push(Box::new(svarog::process::DdsMergeProcessor::new()));
+        }
+
         let mut extracted = 0;
         let mut errors = Vec::new();
 
@@ -218,54 +335,40 @@ fn start_extraction(state: &mut AppState) {
 
             let file_path = output_path.join(&name);
 
-            // Skip if incremental and file exists with same size
-            if options.incremental {
-                if let Ok(meta) = std::fs::metadata(&file_path) {
-                    if meta.len() == entry.uncompressed_size {
-                        extracted += 1;
-                        continue;
-                    }
-                }
-            }
-
-            // Create parent directory
-            if let Some(parent) = file_path.parent() {
-                if let Err(e) = std::fs::create_dir_all(parent) {
-                    errors.push(format!("Failed to create directory for {}: {}", name, e));
-                    continue;
-                }
+            // SOCPAKs are expanded into a directory named after them, and
+            // are up to date once it has any files
+            let up_to_date = options.incremental
+                && if options.expand_socpak && name.to_lowercase().ends_with(".socpak") {
+                    std::fs::read_dir(file_path.with_extension("")).is_ok_and(|mut d| d.next().is_some())
+                } else {
+                    std::fs::metadata(&file_path).is_ok_and(|meta| meta.len() == entry.uncompressed_size)
+                };
+            if up_to_date {
+                extracted += 1;
+                continue;
             }
 
-            // Read and write file
             match archive.read_index(idx) {
-                Ok(data) => {
-                    // Check for CryXML and decode
-                    let final_data = if svarog::cryxml::CryXml::is_cryxml(&data) {
-                        match svarog::cryxml::CryXml::parse(&data) {
-                            Ok(xml) => {
-                                match xml.to_xml_string() {
-                                    Ok(text) => text.into_bytes(),
-                                    Err(_) => data,
-                                }
-                            }
-                            Err(_) => data,
-                        }
-                    } else {
-                        data
-                    };
-
-                    if let Err(e) = std::fs::write(&file_path, &final_data) {
-                        errors.push(format!("Failed to write {}: {}", name, e));
-                    }
-                }
-                Err(e) => {
-                    errors.push(format!("Failed to read {}: {}", name, e));
-                }
+                Ok(data) => processors.process(file_path, data, &mut DiskOutput { errors: &mut errors }),
+                Err(e) => errors.push(format!("Failed to read {}: {}", name, e)),
             }
 
             extracted += 1;
         }
 
+        // Steps over the whole output tree, such as merging split DDS textures
+        for processor in processors.processors() {
+            sender.send(crate::state::WorkerMessage::ExtractionProgress {
+                current: extracted,
+                total,
+                current_file: format!("Finishing {}...", processor.name()),
+            }).ok();
+            match processor.finish(output_path) {
+                Ok(report) => errors.extend(report.into_iter().flat_map(|r| r.warnings)),
+                Err(e) => errors.push(format!("Failed to run {}: {}", processor.name(), e)),
+            }
+        }
+
         sender.send(crate::state::WorkerMessage::ExtractionProgress {
             current: extracted,
             total,
@@ -282,7 +385,107 @@ fn start_extraction(state: &mut AppState) {
     });
 }
 
+/// Writes the files of the processing pipeline below the output directory
+struct DiskOutput<'a> {
+    errors: &'a mut Vec<String>,
+}
+
+impl svarog::process::Output for DiskOutput<'_> {
+    fn write(&mut self, path: PathBuf, data: Vec<u8>) {
+        if let Some(parent) = path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                self.errors.push(format!("Failed to create directory for {}: {}", path.display(), e));
+                return;
+            }
+        }
+        if let Err(e) = std::fs::write(&path, data) {
+            self.errors.push(format!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+
+    fn warn(&mut self, message: String) {
+        self.errors.push(message);
+    }
+}
+
 enum FilterType {
     Glob(glob::Pattern),
     Regex(regex::Regex),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ExtractionOptions, WorkerMessage};
+
+    /// A SOCPAK holding one text file
+    fn socpak() -> Vec<u8> {
+        use std::io::Write;
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("inner.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"inner").unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_run_profile() {
+        let cryxml = svarog::cryxml::builder::CryXmlBuilder::from_xml(r#"<Ammo Speed="900"/>"#)
+            .unwrap()
+            .build()
+            .unwrap();
+        let socpak = socpak();
+        let p4k = crate::tests::write_p4k(
+            "profile.p4k",
+            &[("Data/ammo.xml", &cryxml), ("Data/ship.socpak", &socpak)],
+        );
+        let output = p4k.with_extension("out");
+
+        let mut state = AppState::new();
+        state.p4k_archive = Some(std::sync::Arc::new(
+            svarog::p4k::P4kArchive::open(&p4k).unwrap(),
+        ));
+        state.extraction_profiles = vec![
+            ExtractionProfile {
+                name: "Unset".into(),
+                options: ExtractionOptions::default(),
+            },
+            ExtractionProfile {
+                name: "Ships".into(),
+                options: ExtractionOptions {
+                    output_path: output.clone(),
+                    extract_dcb: false,
+                    ..ExtractionOptions::default()
+                },
+            },
+        ];
+
+        // Profiles without an output directory only open the dialog
+        ExtractionDialog::run_profile(&mut state, 0);
+        assert!(state.extraction_dialog_open && !state.extracting);
+        assert_eq!(
+            state.error_message.as_deref(),
+            Some("Profile 'Unset' has no output directory")
+        );
+        ExtractionDialog::run_profile(&mut state, 2);
+        assert_eq!(state.extraction_profile_name, "Unset");
+
+        ExtractionDialog::run_profile(&mut state, 1);
+        assert!(state.extracting);
+        assert_eq!(state.extraction_options.output_path, output);
+        loop {
+            match state.worker_receiver.recv().unwrap() {
+                WorkerMessage::ExtractionComplete(result) => break result.unwrap(),
+                WorkerMessage::ExtractionProgress { .. } => {}
+                _ => panic!("unexpected message"),
+            }
+        }
+        let xml = std::fs::read_to_string(output.join("Data/ammo.xml")).unwrap();
+        assert!(xml.contains("<Ammo"));
+        let inner = std::fs::read(output.join("Data/ship/inner.txt")).unwrap();
+        assert_eq!(inner, b"inner");
+
+        std::fs::remove_dir_all(&output).unwrap();
+    }
+}
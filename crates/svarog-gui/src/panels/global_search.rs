@@ -0,0 +1,196 @@
+//! Global search over DataCore records, structs, enums and P4K entries
+
+use eframe::egui::{self, Color32, Key, RichText, ScrollArea, Ui};
+
+use crate::panels::{DataCoreBrowserPanel, P4kBrowserPanel};
+use crate::state::{ActiveTab, AppState, GlobalSearchResults};
+use crate::widgets::search_box;
+use crate::worker;
+
+/// Shorter queries match too much to be useful
+const MIN_QUERY_LEN: usize = 2;
+
+/// A search result to jump to
+enum Target {
+    Record(usize),
+    Struct(usize),
+    Enum(usize),
+    File(usize, String),
+}
+
+pub struct GlobalSearchPanel;
+
+impl GlobalSearchPanel {
+    /// Search box for the tab bar
+    pub fn search_box(ui: &mut Ui, state: &mut AppState) {
+        let response = search_box(ui, &mut state.global_search, "Search everything...");
+        if response.changed() || response.gained_focus() {
+            state.global_search_open = true;
+        }
+    }
+
+    /// Run the current query and show its results below the tab bar
+    pub fn show(ctx: &egui::Context, state: &mut AppState) {
+        let query = state.global_search.trim().to_string();
+        if query.len() < MIN_QUERY_LEN {
+            state.global_search_requested = None;
+            state.global_search_results = None;
+            return;
+        }
+        if state.global_search_requested.as_deref() != Some(query.as_str()) {
+            state.global_search_requested = Some(query.clone());
+            worker::global_search(
+                query.clone(),
+                state.datacore.clone(),
+                state.record_search_index.clone(),
+                state.p4k_archive.clone(),
+                state.file_search_index.clone(),
+                state.worker_sender.clone(),
+            );
+        }
+
+        if !state.global_search_open {
+            return;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            state.global_search_open = false;
+            return;
+        }
+
+        let mut open = true;
+        let mut target = None;
+        egui::Window::new("Search")
+            .open(&mut open)
+            .collapsible(false)
+            .anchor(egui::Align2::RIGHT_TOP, [-8.0, 64.0])
+            .default_size([520.0, 480.0])
+            .show(ctx, |ui| {
+                if state.p4k_archive.is_some() && state.file_search_index.is_none() {
+                    indexing_note(ui, "Indexing P4K entries...");
+                }
+                if state.datacore.is_some() && state.record_search_index.is_none() {
+                    indexing_note(ui, "Indexing DataCore records...");
+                }
+
+                match &state.global_search_results {
+                    Some(results) if results.query == query => {
+                        target = render_results(ui, results);
+                    }
+                    _ => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Searching...");
+                        });
+                    }
+                }
+            });
+
+        state.global_search_open = open;
+        if let Some(target) = target {
+            state.global_search_open = false;
+            jump_to(state, target);
+        }
+    }
+}
+
+fn indexing_note(ui: &mut Ui, text: &str) {
+    ui.horizontal(|ui| {
+        ui.spinner();
+        ui.label(RichText::new(text).color(Color32::from_gray(140)));
+    });
+}
+
+fn render_results(ui: &mut Ui, results: &GlobalSearchResults) -> Option<Target> {
+    if results.records.is_empty()
+        && results.structs.is_empty()
+        && results.enums.is_empty()
+        && results.files.is_empty()
+    {
+        ui.label(RichText::new("No results").color(Color32::from_gray(120)));
+        return None;
+    }
+
+    let mut target = None;
+    ScrollArea::vertical()
+        .id_salt("global_search_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            group(ui, "Records", results.records.len(), |ui| {
+                for record in &results.records {
+                    ui.horizontal(|ui| {
+                        let name =
+                            RichText::new(&record.name).color(Color32::from_rgb(100, 180, 255));
+                        if ui.selectable_label(false, name).clicked() {
+                            target = Some(Target::Record(record.record_index));
+                        }
+                        if record.field != "name" {
+                            ui.label(
+                                RichText::new(format!("{}: {}", record.field, record.value))
+                                    .color(Color32::from_gray(120))
+                                    .small(),
+                            );
+                        }
+                    });
+                }
+            });
+            group(ui, "Structs", results.structs.len(), |ui| {
+                for (idx, name) in &results.structs {
+                    let name = RichText::new(name).color(Color32::from_rgb(180, 220, 140));
+                    if ui.selectable_label(false, name).clicked() {
+                        target = Some(Target::Struct(*idx));
+                    }
+                }
+            });
+            group(ui, "Enums", results.enums.len(), |ui| {
+                for (idx, name) in &results.enums {
+                    let name = RichText::new(name).color(Color32::from_rgb(255, 200, 100));
+                    if ui.selectable_label(false, name).clicked() {
+                        target = Some(Target::Enum(*idx));
+                    }
+                }
+            });
+            group(ui, "Files", results.files.len(), |ui| {
+                for (idx, path) in &results.files {
+                    let name = RichText::new(path)
+                        .monospace()
+                        .color(Color32::from_gray(220));
+                    if ui.selectable_label(false, name).clicked() {
+                        target = Some(Target::File(*idx, path.clone()));
+                    }
+                }
+            });
+        });
+    target
+}
+
+/// Collapsible result group, left out when empty
+fn group(ui: &mut Ui, title: &str, count: usize, add_contents: impl FnOnce(&mut Ui)) {
+    if count == 0 {
+        return;
+    }
+    egui::CollapsingHeader::new(RichText::new(format!("{} ({})", title, count)).strong())
+        .id_salt(("global_search", title))
+        .default_open(true)
+        .show(ui, add_contents);
+}
+
+fn jump_to(state: &mut AppState, target: Target) {
+    match target {
+        Target::Record(idx) => {
+            state.active_tab = ActiveTab::DataCoreBrowser;
+            DataCoreBrowserPanel::navigate_to_record(state, idx);
+        }
+        Target::Struct(idx) => {
+            state.active_tab = ActiveTab::DataCoreBrowser;
+            DataCoreBrowserPanel::navigate_to_struct(state, idx);
+        }
+        Target::Enum(idx) => {
+            state.active_tab = ActiveTab::DataCoreBrowser;
+            DataCoreBrowserPanel::navigate_to_enum(state, idx);
+        }
+        Target::File(idx, path) => {
+            state.active_tab = ActiveTab::P4kBrowser;
+            P4kBrowserPanel::select_entry(state, idx, &path);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Log panel
+
+use eframe::egui::{self, Color32, RichText};
+use tracing::Level;
+
+use crate::state::AppState;
+
+pub struct LogPanel;
+
+impl LogPanel {
+    pub fn show(ctx: &egui::Context, state: &mut AppState) {
+        if !state.log_open {
+            return;
+        }
+
+        egui::TopBottomPanel::bottom("log_panel")
+            .resizable(true)
+            .default_height(180.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("Log");
+                    if ui.small_button("Clear").clicked() {
+                        state.log.clear();
+                    }
+                    if ui.small_button("Close").clicked() {
+                        state.log_open = false;
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in state.log.lines() {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(format!("{:<5}", line.level.as_str()))
+                                        .monospace()
+                                        .color(level_color(line.level)),
+                                );
+                                ui.label(RichText::new(&line.target).monospace().weak());
+                                ui.label(RichText::new(&line.message).monospace());
+                            });
+                        }
+                    });
+            });
+    }
+}
+
+fn level_color(level: Level) -> Color32 {
+    match level {
+        Level::ERROR => Color32::from_rgb(230, 80, 80),
+        Level::WARN => Color32::from_rgb(230, 180, 60),
+        Level::INFO => Color32::from_rgb(100, 180, 230),
+        _ => Color32::GRAY,
+    }
+}
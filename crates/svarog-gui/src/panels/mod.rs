@@ -1,9 +1,19 @@
 //! UI panels
 
+mod bookmarks;
 mod p4k_browser;
+mod p4k_diff;
 mod datacore_browser;
 mod extraction;
+mod global_search;
+mod log;
+mod record_table;
+mod struct_hierarchy;
 
+pub use bookmarks::{bookmark_toggle, BookmarksMenu};
 pub use p4k_browser::P4kBrowserPanel;
+pub use p4k_diff::P4kDiffPanel;
 pub use datacore_browser::DataCoreBrowserPanel;
 pub use extraction::ExtractionDialog;
+pub use global_search::GlobalSearchPanel;
+pub use log::LogPanel;
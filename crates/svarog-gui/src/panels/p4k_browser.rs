@@ -3,9 +3,11 @@
 use eframe::egui::{self, Color32, RichText, ScrollArea, Ui, Sense, Vec2};
 use std::sync::Arc;
 
+use crate::panels::{bookmark_toggle, ExtractionDialog};
 use crate::preview::render_preview;
-use crate::state::{AppState, FileTreeNode};
-use crate::widgets::{format_size, progress_bar, search_box};
+use crate::session::Location;
+use crate::state::{AppState, FileTreeNode, PreviewData};
+use crate::widgets::{format_size, progress_bar, search_box, tab_strip, TabAction};
 use crate::worker;
 
 /// Text-based file type icon
@@ -39,9 +41,7 @@ impl P4kBrowserPanel {
                     .add_filter("P4K Archive", &["p4k"])
                     .pick_file()
                 {
-                    state.p4k_loading = true;
-                    state.p4k_path = Some(path.clone());
-                    worker::load_p4k(path, state.worker_sender.clone());
+                    state.open_p4k(path);
                 }
             }
 
@@ -51,6 +51,26 @@ impl P4kBrowserPanel {
                 if ui.button("Extract...").clicked() {
                     state.extraction_dialog_open = true;
                 }
+                if !state.extraction_profiles.is_empty() && !state.extracting {
+                    ui.menu_button("Run Profile", |ui| {
+                        let mut run = None;
+                        for (i, profile) in state.extraction_profiles.iter().enumerate() {
+                            let filter = &profile.options.filter_pattern;
+                            let hover = if filter.is_empty() {
+                                "All files"
+                            } else {
+                                filter
+                            };
+                            if ui.button(&profile.name).on_hover_text(hover).clicked() {
+                                run = Some(i);
+                            }
+                        }
+                        if let Some(i) = run {
+                            ExtractionDialog::run_profile(state, i);
+                            ui.close_menu();
+                        }
+                    });
+                }
 
                 ui.separator();
 
@@ -92,6 +112,11 @@ impl P4kBrowserPanel {
             return;
         }
 
+        if state.file_tabs.len() > 1 {
+            Self::show_tabs(ui, state);
+            ui.separator();
+        }
+
         // Main content area with split view
         if state.file_tree.is_some() {
             ui.columns(2, |columns| {
@@ -104,7 +129,9 @@ impl P4kBrowserPanel {
                             let selected = &mut state.selected_file;
                             let archive = state.p4k_archive.clone();
                             let sender = state.worker_sender.clone();
+                            let codebooks = state.codebooks.clone();
                             let preview_loading = &mut state.preview_loading;
+                            let mut open_in_tab = None;
 
                             // Auto-expand matching paths when filtering
                             if !filter.is_empty() {
@@ -123,36 +150,26 @@ impl P4kBrowserPanel {
                                     selected,
                                     archive.clone(),
                                     sender.clone(),
+                                    codebooks.clone(),
                                     preview_loading,
                                     0,
                                     &mut row_index,
+                                    &mut open_in_tab,
                                 );
                             }
+
+                            if let Some((idx, path)) = open_in_tab {
+                                Self::open_in_new_tab(state, idx, &path);
+                            }
                         }
                     });
 
                 // Right panel: Preview
-                columns[1].vertical(|ui| {
-                    // Preview header
-                    if let Some(selected) = &state.selected_file {
-                        ui.horizontal(|ui| {
-                            ui.label(
-                                RichText::new(text_file_icon(selected))
-                                    .monospace()
-                                    .color(Color32::from_gray(150))
-                            );
-                            ui.label(
-                                RichText::new(selected)
-                                    .monospace()
-                                    .color(Color32::LIGHT_BLUE)
-                            );
-                        });
-                        ui.separator();
-                    }
-
-                    render_preview(ui, &state.preview, state.preview_loading);
-                });
+                columns[1].vertical(|ui| Self::show_preview(ui, state));
             });
+        } else if state.loose_file.is_some() {
+            // A file opened from disk without an archive
+            Self::show_preview(ui, state);
         } else {
             // Empty state
             ui.centered_and_justified(|ui| {
@@ -162,11 +179,145 @@ impl P4kBrowserPanel {
                     ui.add_space(20.0);
                     ui.label(RichText::new("No P4K archive loaded").size(20.0));
                     ui.add_space(10.0);
-                    ui.label("Click 'Open P4K' to browse a Star Citizen archive");
+                    ui.label(
+                        "Click 'Open P4K' to browse a Star Citizen archive, or drop files here",
+                    );
                 });
             });
         }
     }
+
+    fn show_preview(ui: &mut Ui, state: &mut AppState) {
+        // Preview header
+        if let Some(selected) = &state.selected_file {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(text_file_icon(selected))
+                        .monospace()
+                        .color(Color32::from_gray(150))
+                );
+                ui.label(
+                    RichText::new(selected)
+                        .monospace()
+                        .color(Color32::LIGHT_BLUE)
+                );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let location = Location::File { path: selected.clone() };
+                    bookmark_toggle(ui, &mut state.bookmarks, location);
+                });
+            });
+            ui.separator();
+        } else if let Some(path) = &state.loose_file {
+            ui.horizontal(|ui| {
+                let name = path.to_string_lossy();
+                ui.label(
+                    RichText::new(text_file_icon(&name))
+                        .monospace()
+                        .color(Color32::from_gray(150)),
+                );
+                ui.label(RichText::new(name).monospace().color(Color32::LIGHT_BLUE))
+                    .on_hover_text("Opened from disk");
+            });
+            ui.separator();
+        }
+
+        render_preview(
+            ui,
+            &state.preview,
+            &mut state.texture_view,
+            &mut state.audio_view,
+            state.preview_loading,
+        );
+    }
+
+    /// Select an entry, expanding the directories above it, and preview it
+    pub fn select_entry(state: &mut AppState, entry_index: usize, path: &str) {
+        if let Some(tree) = &mut state.file_tree {
+            expand_to(tree, path);
+        }
+        state.selected_file = Some(path.to_string());
+        state.loose_file = None;
+        if let Some(archive) = &state.p4k_archive {
+            state.preview_loading = true;
+            worker::load_preview(
+                archive.clone(),
+                entry_index,
+                state.codebooks.clone(),
+                state.worker_sender.clone(),
+            );
+        }
+    }
+
+    /// Open an entry in a new tab
+    pub fn open_in_new_tab(state: &mut AppState, entry_index: usize, path: &str) {
+        state.file_tabs.push(None);
+        Self::switch_tab(state, state.file_tabs.len() - 1);
+        Self::select_entry(state, entry_index, path);
+    }
+
+    fn switch_tab(state: &mut AppState, index: usize) {
+        if index == state.file_tab || index >= state.file_tabs.len() {
+            return;
+        }
+        state.file_tabs[state.file_tab] = state.selected_file.take();
+        state.file_tab = index;
+        state.preview = PreviewData::None;
+        state.loose_file = None;
+        if let Some(path) = state.file_tabs[index].take() {
+            if let Some(entry_index) = state.file_entry(&path) {
+                Self::select_entry(state, entry_index, &path);
+            }
+        }
+    }
+
+    fn close_tab(state: &mut AppState, index: usize) {
+        if state.file_tabs.len() < 2 || index >= state.file_tabs.len() {
+            return;
+        }
+        if index == state.file_tab {
+            Self::switch_tab(state, if index > 0 { index - 1 } else { 1 });
+        }
+        state.file_tabs.remove(index);
+        if state.file_tab > index {
+            state.file_tab -= 1;
+        }
+    }
+
+    fn show_tabs(ui: &mut Ui, state: &mut AppState) {
+        let titles: Vec<String> = state
+            .file_tabs
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let path = if i == state.file_tab {
+                    &state.selected_file
+                } else {
+                    path
+                };
+                path.as_deref()
+                    .map_or("New tab", |p| p.rsplit('/').next().unwrap_or(p))
+                    .to_string()
+            })
+            .collect();
+
+        match tab_strip(ui, &titles, state.file_tab, |_, _| {}) {
+            Some(TabAction::Select(i)) => Self::switch_tab(state, i),
+            Some(TabAction::Close(i)) => Self::close_tab(state, i),
+            None => {}
+        }
+    }
+}
+
+/// Expand the directories on the way to `path`
+fn expand_to(node: &mut FileTreeNode, path: &str) {
+    for child in &mut node.children {
+        let prefix = path.strip_prefix(child.path.as_str());
+        if child.is_directory && prefix.is_some_and(|rest| rest.starts_with('/')) {
+            child.expanded = true;
+            expand_to(child, path);
+            return;
+        }
+    }
 }
 
 /// Check if node or any children match filter, and auto-expand if needed
@@ -192,6 +343,7 @@ fn check_and_expand_for_filter(node: &mut FileTreeNode, filter: &str) -> bool {
     self_matches || any_child_matches
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_tree_node(
     ui: &mut Ui,
     node: &mut FileTreeNode,
@@ -199,9 +351,11 @@ fn render_tree_node(
     selected: &mut Option<String>,
     archive: Option<Arc<svarog::p4k::P4kArchive>>,
     sender: crossbeam_channel::Sender<crate::state::WorkerMessage>,
+    codebooks: Option<Arc<svarog::wwise::CodebookLibrary>>,
     preview_loading: &mut bool,
     depth: usize,
     row_index: &mut usize,
+    open_in_tab: &mut Option<(usize, String)>,
 ) {
     // Filter check - skip non-matching nodes
     if !filter.is_empty() {
@@ -212,7 +366,7 @@ fn render_tree_node(
         }
     }
 
-    let is_selected = selected.as_ref().map_or(false, |s| s == &node.path);
+    let is_selected = selected.as_ref() == Some(&node.path);
 
     // Alternating row background (very subtle)
     let row_bg = if *row_index % 2 == 0 {
@@ -307,11 +461,28 @@ fn render_tree_node(
                         *selected = Some(node.path.clone());
                         if let (Some(archive), Some(idx)) = (&archive, node.entry_index) {
                             *preview_loading = true;
-                            worker::load_preview(archive.clone(), idx, sender.clone());
+                            worker::load_preview(
+                                archive.clone(),
+                                idx,
+                                codebooks.clone(),
+                                sender.clone(),
+                            );
                         }
                     }
                 }
 
+                if let Some(idx) = node.entry_index.filter(|_| !node.is_directory) {
+                    if name_response.middle_clicked() {
+                        *open_in_tab = Some((idx, node.path.clone()));
+                    }
+                    name_response.context_menu(|ui| {
+                        if ui.button("Open in new tab").clicked() {
+                            *open_in_tab = Some((idx, node.path.clone()));
+                            ui.close_menu();
+                        }
+                    });
+                }
+
                 // Size for files (right-aligned)
                 if !node.is_directory {
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -338,9 +509,11 @@ fn render_tree_node(
                 selected,
                 archive.clone(),
                 sender.clone(),
+                codebooks.clone(),
                 preview_loading,
                 depth + 1,
                 row_index,
+                open_in_tab,
             );
         }
     }
@@ -352,3 +525,45 @@ fn node_matches_filter(node: &FileTreeNode, filter: &str) -> bool {
     }
     node.children.iter().any(|c| node_matches_filter(c, filter))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tabs() {
+        let mut data = FileTreeNode::new_directory("Data".into(), "Data".into());
+        for (i, name) in ["a.txt", "b.txt"].into_iter().enumerate() {
+            let path = format!("Data/{}", name);
+            data.children
+                .push(FileTreeNode::new_file(name.into(), path, 1, 1, false, i));
+        }
+        let mut root = FileTreeNode::new_directory(String::new(), String::new());
+        root.children.push(data);
+
+        let mut state = AppState::new();
+        state.file_tree = Some(root);
+        P4kBrowserPanel::select_entry(&mut state, 0, "Data/a.txt");
+        P4kBrowserPanel::open_in_new_tab(&mut state, 1, "Data/b.txt");
+        assert_eq!(state.file_tab, 1);
+        assert_eq!(state.file_tabs, [Some("Data/a.txt".to_string()), None]);
+        assert_eq!(state.selected_file.as_deref(), Some("Data/b.txt"));
+
+        P4kBrowserPanel::switch_tab(&mut state, 0);
+        assert_eq!(state.selected_file.as_deref(), Some("Data/a.txt"));
+        assert_eq!(state.file_tabs[1].as_deref(), Some("Data/b.txt"));
+
+        // Closing the active tab moves to its neighbour
+        P4kBrowserPanel::close_tab(&mut state, 0);
+        assert_eq!((state.file_tab, state.file_tabs.len()), (0, 1));
+        assert_eq!(state.selected_file.as_deref(), Some("Data/b.txt"));
+
+        // A tab whose file is gone from the archive opens empty
+        state.file_tabs.push(Some("Data/missing.txt".into()));
+        P4kBrowserPanel::switch_tab(&mut state, 1);
+        assert_eq!(state.selected_file, None);
+        P4kBrowserPanel::close_tab(&mut state, 1);
+        P4kBrowserPanel::close_tab(&mut state, 0);
+        assert_eq!(state.file_tabs.len(), 1);
+    }
+}
@@ -0,0 +1,355 @@
+//! P4K version diff panel
+
+use eframe::egui::{self, Color32, RichText, ScrollArea, Ui};
+
+use svarog::cryxml::CryXmlChange;
+use svarog::p4k::{P4kChange, P4kChangeKind};
+
+use crate::state::{AppState, ContentDiff, DiffLine, DiffSource, DiffTreeNode, P4kDiffResult};
+use crate::widgets::{format_size, search_box, tree_node};
+use crate::worker;
+
+const ADDED: Color32 = Color32::from_rgb(120, 220, 120);
+const REMOVED: Color32 = Color32::from_rgb(240, 110, 110);
+const MODIFIED: Color32 = Color32::from_rgb(240, 200, 90);
+
+/// Change kinds in the order of `AppState::diff_show`
+const KINDS: [P4kChangeKind; 3] = [
+    P4kChangeKind::Added,
+    P4kChangeKind::Removed,
+    P4kChangeKind::Modified,
+];
+
+fn kind_color(kind: P4kChangeKind) -> Color32 {
+    match kind {
+        P4kChangeKind::Added => ADDED,
+        P4kChangeKind::Removed => REMOVED,
+        P4kChangeKind::Modified => MODIFIED,
+    }
+}
+
+fn kind_label(kind: P4kChangeKind) -> &'static str {
+    match kind {
+        P4kChangeKind::Added => "added",
+        P4kChangeKind::Removed => "removed",
+        P4kChangeKind::Modified => "modified",
+    }
+}
+
+fn kind_symbol(kind: P4kChangeKind) -> &'static str {
+    match kind {
+        P4kChangeKind::Added => "+",
+        P4kChangeKind::Removed => "-",
+        P4kChangeKind::Modified => "~",
+    }
+}
+
+pub struct P4kDiffPanel;
+
+impl P4kDiffPanel {
+    pub fn show(ui: &mut Ui, state: &mut AppState) {
+        // Top toolbar: pick both sides
+        ui.horizontal(|ui| {
+            ui.label("Old:");
+            if ui.button("P4K...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("P4K Archive", &["p4k"])
+                    .pick_file()
+                {
+                    state.diff_old = Some(DiffSource::P4k(path));
+                }
+            }
+            if ui
+                .button("Manifest...")
+                .on_hover_text("JSON manifest written by `svarog p4k-manifest`")
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("P4K Manifest", &["json"])
+                    .pick_file()
+                {
+                    state.diff_old = Some(DiffSource::Manifest(path));
+                }
+            }
+            source_label(ui, state.diff_old.as_ref().map(|s| s.path()));
+
+            ui.separator();
+
+            ui.label("New:");
+            if ui.button("P4K...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("P4K Archive", &["p4k"])
+                    .pick_file()
+                {
+                    state.diff_new = Some(path);
+                }
+            }
+            source_label(ui, state.diff_new.as_deref());
+
+            ui.separator();
+
+            let ready = state.diff_old.is_some() && state.diff_new.is_some() && !state.diff_loading;
+            if ui
+                .add_enabled(ready, egui::Button::new("Compare"))
+                .clicked()
+            {
+                if let (Some(old), Some(new)) = (&state.diff_old, &state.diff_new) {
+                    state.diff_loading = true;
+                    worker::compare_p4k(old.clone(), new.clone(), state.worker_sender.clone());
+                }
+            }
+        });
+
+        ui.separator();
+
+        if state.diff_loading {
+            ui.vertical_centered(|ui| {
+                ui.add_space(50.0);
+                ui.spinner();
+                ui.label("Comparing archives...");
+            });
+            return;
+        }
+
+        let Some(result) = state.diff_result.clone() else {
+            ui.centered_and_justified(|ui| {
+                ui.label("Pick an old P4K or manifest and a new P4K, then click 'Compare'");
+            });
+            return;
+        };
+
+        // Summary, change kind toggles and filter
+        ui.horizontal(|ui| {
+            for (show, kind) in state.diff_show.iter_mut().zip(KINDS) {
+                let text = format!(
+                    "{} {} {}",
+                    kind_symbol(kind),
+                    result.diff.count(kind),
+                    kind_label(kind)
+                );
+                ui.toggle_value(show, RichText::new(text).color(kind_color(kind)));
+            }
+            ui.label(
+                RichText::new(format!("{} unchanged", result.diff.unchanged()))
+                    .color(Color32::GRAY),
+            );
+            ui.separator();
+            search_box(ui, &mut state.diff_filter, "Filter changes...");
+        });
+
+        ui.separator();
+
+        if result.diff.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label("No differences");
+            });
+            return;
+        }
+
+        ui.columns(2, |columns| {
+            // Left panel: tree of changes
+            ScrollArea::vertical()
+                .id_salt("p4k_diff_tree")
+                .auto_shrink([false, false])
+                .show(&mut columns[0], |ui| {
+                    let filter = state.diff_filter.to_lowercase();
+                    let show = state.diff_show;
+                    let mut clicked = None;
+                    if let Some(tree) = &mut state.diff_tree {
+                        for child in &mut tree.children {
+                            render_diff_node(
+                                ui,
+                                child,
+                                &result,
+                                &filter,
+                                show,
+                                state.diff_selected,
+                                &mut clicked,
+                            );
+                        }
+                    }
+                    if let Some(idx) = clicked {
+                        select_change(state, &result, idx);
+                    }
+                });
+
+            // Right panel: the selected change
+            columns[1].vertical(|ui| {
+                let Some(change) = state.diff_selected.map(|i| &result.diff.changes()[i]) else {
+                    ui.centered_and_justified(|ui| {
+                        ui.label("Select a changed file");
+                    });
+                    return;
+                };
+                render_change_header(ui, change);
+                ui.separator();
+
+                if state.diff_content_loading {
+                    ui.spinner();
+                } else if let Some(content) = &state.diff_content {
+                    render_content_diff(ui, content);
+                }
+            });
+        });
+    }
+}
+
+fn source_label(ui: &mut Ui, path: Option<&std::path::Path>) {
+    let text = path.map_or("(none)".into(), |p| {
+        p.file_name().unwrap_or_default().to_string_lossy()
+    });
+    ui.label(RichText::new(text).color(Color32::LIGHT_BLUE));
+}
+
+fn select_change(state: &mut AppState, result: &std::sync::Arc<P4kDiffResult>, idx: usize) {
+    state.diff_selected = Some(idx);
+    state.diff_content = None;
+    state.diff_content_loading = false;
+    if result.diff.changes()[idx].kind == P4kChangeKind::Modified {
+        state.diff_content_loading = true;
+        worker::load_content_diff(result.clone(), idx, state.worker_sender.clone());
+    }
+}
+
+/// Whether a node or any entry below it passes the filter and kind toggles
+fn node_visible(
+    node: &DiffTreeNode,
+    result: &P4kDiffResult,
+    filter: &str,
+    show: [bool; 3],
+) -> bool {
+    match node.change {
+        Some(idx) => {
+            let kind = result.diff.changes()[idx].kind;
+            let shown = KINDS.iter().zip(show).any(|(k, s)| s && *k == kind);
+            shown && (filter.is_empty() || node.path.to_lowercase().contains(filter))
+        }
+        None => node
+            .children
+            .iter()
+            .any(|c| node_visible(c, result, filter, show)),
+    }
+}
+
+fn render_diff_node(
+    ui: &mut Ui,
+    node: &mut DiffTreeNode,
+    result: &P4kDiffResult,
+    filter: &str,
+    show: [bool; 3],
+    selected: Option<usize>,
+    clicked: &mut Option<usize>,
+) {
+    if !node_visible(node, result, filter, show) {
+        return;
+    }
+
+    match node.change {
+        Some(idx) => {
+            let kind = result.diff.changes()[idx].kind;
+            ui.horizontal(|ui| {
+                ui.add_space(18.0);
+                ui.label(
+                    RichText::new(kind_symbol(kind))
+                        .monospace()
+                        .color(kind_color(kind)),
+                );
+                let text = RichText::new(&node.name).color(kind_color(kind));
+                if ui.selectable_label(selected == Some(idx), text).clicked() {
+                    *clicked = Some(idx);
+                }
+            });
+        }
+        None => {
+            // Expand everything while filtering, like the P4K browser
+            if !filter.is_empty() {
+                node.expanded = true;
+            }
+            let label =
+                RichText::new(format!("[D] {}", node.name)).color(Color32::from_rgb(255, 200, 100));
+            let mut expanded = node.expanded;
+            tree_node(ui, &node.path, label, &mut expanded, false, |ui| {
+                for child in &mut node.children {
+                    render_diff_node(ui, child, result, filter, show, selected, clicked);
+                }
+            });
+            node.expanded = expanded;
+        }
+    }
+}
+
+fn render_change_header(ui: &mut Ui, change: &P4kChange) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(kind_symbol(change.kind))
+                .monospace()
+                .color(kind_color(change.kind)),
+        );
+        ui.label(
+            RichText::new(change.name())
+                .monospace()
+                .color(Color32::LIGHT_BLUE),
+        );
+    });
+    let side = |entry: &Option<svarog::p4k::EntrySummary>| {
+        entry.as_ref().map_or("-".to_string(), |e| {
+            format!("{} (CRC32 {:08x})", format_size(e.size), e.crc32)
+        })
+    };
+    ui.label(
+        RichText::new(format!(
+            "Old: {}    New: {}",
+            side(&change.old),
+            side(&change.new)
+        ))
+        .color(Color32::GRAY),
+    );
+}
+
+fn render_content_diff(ui: &mut Ui, content: &ContentDiff) {
+    match content {
+        ContentDiff::Unavailable(reason) => {
+            ui.label(RichText::new(reason).color(Color32::GRAY));
+        }
+        ContentDiff::CryXml(changes) if changes.is_empty() => {
+            ui.label("Structurally equal, only attribute or sibling order differs");
+        }
+        ContentDiff::CryXml(changes) => {
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            ScrollArea::both()
+                .id_salt("p4k_diff_content")
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, changes.len(), |ui, rows| {
+                    for change in &changes[rows] {
+                        let color = match change {
+                            CryXmlChange::NodeAdded { .. }
+                            | CryXmlChange::AttributeAdded { .. } => ADDED,
+                            CryXmlChange::NodeRemoved { .. }
+                            | CryXmlChange::AttributeRemoved { .. } => REMOVED,
+                            _ => MODIFIED,
+                        };
+                        ui.label(RichText::new(change.to_string()).monospace().color(color));
+                    }
+                });
+        }
+        ContentDiff::Text(lines) => {
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            ScrollArea::both()
+                .id_salt("p4k_diff_content")
+                .auto_shrink([false, false])
+                .show_rows(ui, row_height, lines.len(), |ui, rows| {
+                    for line in &lines[rows] {
+                        let (text, color) = match line {
+                            DiffLine::Same(l) => (format!("  {}", l), Color32::from_gray(200)),
+                            DiffLine::Added(l) => (format!("+ {}", l), ADDED),
+                            DiffLine::Removed(l) => (format!("- {}", l), REMOVED),
+                            DiffLine::Skipped(n) => {
+                                (format!("@@ {} unchanged lines @@", n), Color32::LIGHT_BLUE)
+                            }
+                        };
+                        ui.label(RichText::new(text).monospace().color(color));
+                    }
+                });
+        }
+    }
+}
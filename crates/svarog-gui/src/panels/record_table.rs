@@ -0,0 +1,434 @@
+//! Property table view of DataCore records
+
+use std::collections::HashSet;
+
+use eframe::egui::{self, Color32, RichText, ScrollArea, Sense, Ui};
+use svarog::datacore::{ArrayElementType, DataCoreDatabase, InstanceRef, LocaleResolver, Value};
+
+use crate::state::ReferenceIndex;
+
+const NAME_WIDTH: f32 = 280.0;
+const TYPE_WIDTH: f32 = 180.0;
+const INDENT: f32 = 16.0;
+/// Array elements listed before the rest is summarized
+const MAX_ARRAY_ROWS: usize = 1000;
+
+const RECORD_COLOR: Color32 = Color32::from_rgb(100, 180, 255);
+const STRUCT_COLOR: Color32 = Color32::from_rgb(180, 220, 140);
+const ENUM_COLOR: Color32 = Color32::from_rgb(255, 200, 100);
+const LOCALE_COLOR: Color32 = Color32::from_rgb(200, 170, 230);
+
+/// Shared state of one table render
+struct TableContext<'a> {
+    db: &'a DataCoreDatabase,
+    references: Option<&'a ReferenceIndex>,
+    locale: Option<&'a LocaleResolver>,
+    /// Show `Locale` values as keys, with their text as a tooltip
+    show_locale_keys: bool,
+    /// Paths of expanded properties, e.g. `Components[1].Health`
+    expanded: &'a mut HashSet<String>,
+    navigate_to: &'a mut Option<usize>,
+    row_index: usize,
+}
+
+/// Render the properties of `instance` as an expandable name/type/value
+/// table. Clicking a record reference sets `navigate_to` to its main record
+/// index.
+#[allow(clippy::too_many_arguments)]
+pub fn show(
+    ui: &mut Ui,
+    db: &DataCoreDatabase,
+    instance: InstanceRef,
+    references: Option<&ReferenceIndex>,
+    locale: Option<&LocaleResolver>,
+    show_locale_keys: bool,
+    expanded: &mut HashSet<String>,
+    navigate_to: &mut Option<usize>,
+) {
+    ui.horizontal(|ui| {
+        ui.add_space(4.0);
+        let start = ui.cursor().min.x;
+        ui.label(
+            RichText::new("Name")
+                .strong()
+                .color(Color32::from_gray(150)),
+        );
+        pad_to(ui, start + NAME_WIDTH);
+        ui.label(
+            RichText::new("Type")
+                .strong()
+                .color(Color32::from_gray(150)),
+        );
+        pad_to(ui, start + NAME_WIDTH + TYPE_WIDTH);
+        ui.label(
+            RichText::new("Value")
+                .strong()
+                .color(Color32::from_gray(150)),
+        );
+    });
+    ui.separator();
+
+    let mut ctx = TableContext {
+        db,
+        references,
+        locale,
+        show_locale_keys,
+        expanded,
+        navigate_to,
+        row_index: 0,
+    };
+
+    ScrollArea::both()
+        .id_salt("dcb_table_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            render_instance(ui, &mut ctx, instance, "", 0);
+        });
+}
+
+fn render_instance(
+    ui: &mut Ui,
+    ctx: &mut TableContext,
+    instance: InstanceRef,
+    path: &str,
+    depth: usize,
+) {
+    let db = ctx.db;
+    for property in instance.resolve(db).properties() {
+        let child_path = if path.is_empty() {
+            property.name.to_string()
+        } else {
+            format!("{}.{}", path, property.name)
+        };
+        render_value(ui, ctx, property.name, &property.value, &child_path, depth);
+    }
+}
+
+fn render_value(
+    ui: &mut Ui,
+    ctx: &mut TableContext,
+    name: &str,
+    value: &Value,
+    path: &str,
+    depth: usize,
+) {
+    let db = ctx.db;
+    let type_name = type_label(db, value);
+
+    match value {
+        Value::Class(instance) | Value::StrongPointer(Some(instance)) => {
+            let struct_name = db
+                .struct_name(instance.struct_index as usize)
+                .unwrap_or("Unknown");
+            let open = row(ui, ctx, name, &type_name, path, depth, true, |ui, _| {
+                ui.label(RichText::new(struct_name).color(STRUCT_COLOR));
+            });
+            if open {
+                render_instance(ui, ctx, *instance, path, depth + 1);
+            }
+        }
+        Value::Array(array) => {
+            let count = array.count as usize;
+            let open = row(
+                ui,
+                ctx,
+                name,
+                &type_name,
+                path,
+                depth,
+                count > 0,
+                |ui, _| {
+                    ui.label(RichText::new(format!("{} elements", count)).color(Color32::GRAY));
+                },
+            );
+            if open {
+                for (i, element) in array.iter(db).take(MAX_ARRAY_ROWS).enumerate() {
+                    let element_path = format!("{}[{}]", path, i);
+                    render_value(
+                        ui,
+                        ctx,
+                        &format!("[{}]", i),
+                        &element,
+                        &element_path,
+                        depth + 1,
+                    );
+                }
+                if count > MAX_ARRAY_ROWS {
+                    row(ui, ctx, "...", "", path, depth + 1, false, |ui, _| {
+                        ui.label(
+                            RichText::new(format!("{} more elements", count - MAX_ARRAY_ROWS))
+                                .color(Color32::GRAY)
+                                .italics(),
+                        );
+                    });
+                }
+            }
+        }
+        Value::Reference(Some(reference)) => {
+            let target = db.get_record(&reference.guid);
+            let index = ctx
+                .references
+                .and_then(|r| r.guid_to_index.get(&reference.guid.to_string()).copied());
+            let label = target
+                .and_then(|record| db.record_name(record))
+                .map_or_else(|| reference.guid.to_string(), str::to_string);
+            let guid = reference.guid.to_string();
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, ctx| {
+                record_link(ui, ctx, &label, &guid, index);
+            });
+        }
+        Value::WeakPointer(Some(instance)) => {
+            // Weak pointers may point back up the tree, so they are not
+            // expanded; those that point at a record link to it
+            let struct_name = db
+                .struct_name(instance.struct_index as usize)
+                .unwrap_or("Unknown");
+            let record = db.record_for_instance(instance.struct_index, instance.instance_index);
+            let index = record.and_then(|record| {
+                ctx.references
+                    .and_then(|r| r.guid_to_index.get(&record.id.to_string()).copied())
+            });
+            let label = match record.and_then(|record| db.record_name(record)) {
+                Some(record_name) => record_name.to_string(),
+                None => format!("{}[{}]", struct_name, instance.instance_index),
+            };
+            let hover = format!("{} instance {}", struct_name, instance.instance_index);
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, ctx| {
+                record_link(ui, ctx, &label, &hover, index);
+            });
+        }
+        Value::StrongPointer(None)
+        | Value::WeakPointer(None)
+        | Value::Reference(None)
+        | Value::Null => {
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, _| {
+                ui.label(
+                    RichText::new("null")
+                        .color(Color32::from_gray(100))
+                        .italics(),
+                );
+            });
+        }
+        Value::Enum(choice) => {
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, _| {
+                ui.label(RichText::new(*choice).color(ENUM_COLOR));
+            });
+        }
+        Value::Locale(key) => {
+            let text = ctx.locale.and_then(|locale| locale.get(key));
+            let translated = text.is_some() && !ctx.show_locale_keys;
+            let (shown, hover) = match text {
+                Some(text) if translated => (text, Some(*key)),
+                Some(text) => (*key, Some(text)),
+                None => (*key, None),
+            };
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, _| {
+                let label = ui.label(RichText::new(shown).color(LOCALE_COLOR));
+                if let Some(hover) = hover {
+                    label.on_hover_text(hover);
+                }
+                // Translated text keeps its key next to it
+                if translated {
+                    ui.label(RichText::new(*key).color(Color32::from_gray(110)).small());
+                }
+            });
+        }
+        _ => {
+            let text = value.to_string();
+            row(ui, ctx, name, &type_name, path, depth, false, |ui, _| {
+                ui.label(
+                    RichText::new(text)
+                        .monospace()
+                        .color(Color32::from_gray(210)),
+                );
+            });
+        }
+    }
+}
+
+/// A clickable record name, or plain text when the target can't be opened
+fn record_link(
+    ui: &mut Ui,
+    ctx: &mut TableContext,
+    label: &str,
+    hover: &str,
+    index: Option<usize>,
+) {
+    match index {
+        Some(index) => {
+            let response = ui
+                .add(
+                    egui::Label::new(RichText::new(label).color(RECORD_COLOR))
+                        .sense(Sense::click()),
+                )
+                .on_hover_text(hover);
+            if response.hovered() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+            if response.clicked() {
+                *ctx.navigate_to = Some(index);
+            }
+        }
+        None => {
+            ui.label(RichText::new(label).color(Color32::from_gray(140)))
+                .on_hover_text(hover);
+        }
+    }
+}
+
+/// Render one table row and return whether it is expanded.
+#[allow(clippy::too_many_arguments)]
+fn row(
+    ui: &mut Ui,
+    ctx: &mut TableContext,
+    name: &str,
+    type_name: &str,
+    path: &str,
+    depth: usize,
+    expandable: bool,
+    add_value: impl FnOnce(&mut Ui, &mut TableContext),
+) -> bool {
+    // Alternating background, like the record tree
+    if ctx.row_index % 2 == 1 {
+        let rect = ui.available_rect_before_wrap();
+        let rect = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), 20.0));
+        ui.painter()
+            .rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(255, 255, 255, 1));
+    }
+    ctx.row_index += 1;
+
+    let mut open = expandable && ctx.expanded.contains(path);
+    ui.horizontal(|ui| {
+        ui.add_space(4.0);
+        let start = ui.cursor().min.x;
+        ui.add_space(depth as f32 * INDENT);
+        if expandable {
+            let symbol = if open { "▼" } else { "▶" };
+            if ui.small_button(symbol).clicked() {
+                open = !open;
+                if open {
+                    ctx.expanded.insert(path.to_string());
+                } else {
+                    ctx.expanded.remove(path);
+                }
+            }
+        } else {
+            ui.add_space(18.0);
+        }
+        ui.label(RichText::new(name).color(Color32::from_gray(220)));
+
+        pad_to(ui, start + NAME_WIDTH);
+        ui.label(
+            RichText::new(type_name)
+                .color(Color32::from_gray(120))
+                .small(),
+        );
+
+        pad_to(ui, start + NAME_WIDTH + TYPE_WIDTH);
+        add_value(ui, ctx);
+    });
+    open
+}
+
+/// Advance the cursor to `x`, keeping a small gap after overlong cells
+fn pad_to(ui: &mut Ui, x: f32) {
+    ui.add_space((x - ui.cursor().min.x).max(8.0));
+}
+
+fn type_label(db: &DataCoreDatabase, value: &Value) -> String {
+    let struct_name = |index: u32| db.struct_name(index as usize).unwrap_or("Unknown");
+    match value {
+        Value::Bool(_) => "bool".into(),
+        Value::Int8(_) => "i8".into(),
+        Value::Int16(_) => "i16".into(),
+        Value::Int32(_) => "i32".into(),
+        Value::Int64(_) => "i64".into(),
+        Value::UInt8(_) => "u8".into(),
+        Value::UInt16(_) => "u16".into(),
+        Value::UInt32(_) => "u32".into(),
+        Value::UInt64(_) => "u64".into(),
+        Value::Float(_) => "f32".into(),
+        Value::Double(_) => "f64".into(),
+        Value::String(_) => "string".into(),
+        Value::Locale(_) => "locale".into(),
+        Value::Enum(_) => "enum".into(),
+        Value::Guid(_) => "guid".into(),
+        Value::Class(instance) => struct_name(instance.struct_index).into(),
+        Value::StrongPointer(Some(instance)) => format!("{}*", struct_name(instance.struct_index)),
+        Value::StrongPointer(None) => "pointer".into(),
+        Value::WeakPointer(Some(instance)) => format!("&{}", struct_name(instance.struct_index)),
+        Value::WeakPointer(None) => "weak pointer".into(),
+        Value::Reference(Some(reference)) => db
+            .get_record(&reference.guid)
+            .and_then(|record| db.struct_name(record.struct_index as usize))
+            .map_or_else(|| "reference".into(), |name| format!("ref {}", name)),
+        Value::Reference(None) => "reference".into(),
+        Value::Array(array) => {
+            let element = match array.element_type {
+                ArrayElementType::Bool => "bool",
+                ArrayElementType::Int8 => "i8",
+                ArrayElementType::Int16 => "i16",
+                ArrayElementType::Int32 => "i32",
+                ArrayElementType::Int64 => "i64",
+                ArrayElementType::UInt8 => "u8",
+                ArrayElementType::UInt16 => "u16",
+                ArrayElementType::UInt32 => "u32",
+                ArrayElementType::UInt64 => "u64",
+                ArrayElementType::Float => "f32",
+                ArrayElementType::Double => "f64",
+                ArrayElementType::String => "string",
+                ArrayElementType::Locale => "locale",
+                ArrayElementType::Enum => "enum",
+                ArrayElementType::Guid => "guid",
+                ArrayElementType::Reference => "reference",
+                ArrayElementType::Class => struct_name(array.struct_index),
+                ArrayElementType::StrongPointer => {
+                    return format!("{}*[]", struct_name(array.struct_index))
+                }
+                ArrayElementType::WeakPointer => {
+                    return format!("&{}[]", struct_name(array.struct_index))
+                }
+            };
+            format!("{}[]", element)
+        }
+        Value::Null => "null".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svarog::common::CigGuid;
+    use svarog::datacore::{DataCoreBuilder, DataType, InstanceRef, RecordRef};
+
+    #[test]
+    fn test_type_label() {
+        let mut builder = DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_array_property(item, "tags", DataType::String);
+        builder.add_typed_property(item, "child", DataType::StrongPointer, item);
+        builder.add_typed_array_property(item, "parts", DataType::WeakPointer, item);
+        builder.add_property(item, "target", DataType::Reference);
+        let target = CigGuid::new_v4();
+        let root = builder.add_record("Root", item, "libs/root.xml");
+        let child = builder.add_record_with_guid("Child", item, "libs/child.xml", target);
+        builder.set_string_array(root, "tags", &["a"]);
+        builder.set_strong_pointer(root, "child", Some(child));
+        builder.set_reference(root, "target", target);
+
+        let db = DataCoreDatabase::parse(&builder.build().unwrap()).unwrap();
+        let root = db.record(&db.records()[0].id).unwrap();
+        let label = |name: &str| type_label(&db, &root.get(name).unwrap());
+        assert_eq!(label("tags"), "string[]");
+        assert_eq!(label("child"), "Item*");
+        assert_eq!(label("parts"), "&Item[]");
+        assert_eq!(label("target"), "ref Item");
+
+        // Missing structs and records still get a label
+        let missing = Value::Class(InstanceRef::new(999, 0));
+        assert_eq!(type_label(&db, &missing), "Unknown");
+        let missing = Value::Reference(Some(RecordRef::new(CigGuid::new_v4())));
+        assert_eq!(type_label(&db, &missing), "reference");
+        assert_eq!(type_label(&db, &Value::StrongPointer(None)), "pointer");
+    }
+}
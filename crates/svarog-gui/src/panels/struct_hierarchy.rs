@@ -0,0 +1,215 @@
+//! Inheritance and flattened field views of DataCore structs
+
+use eframe::egui::{self, Color32, CursorIcon, RichText, ScrollArea, Sense, Ui};
+use svarog::datacore::structs::DataCorePropertyDefinition;
+use svarog::datacore::{DataCoreDatabase, DataType};
+
+const STRUCT_COLOR: Color32 = Color32::from_rgb(180, 220, 140);
+const LINK_COLOR: Color32 = Color32::from_rgb(100, 180, 255);
+const INDENT: f32 = 16.0;
+
+/// Render every property of a struct, inherited ones first, with its byte
+/// offset in an instance and the ancestor declaring it. Clicking an ancestor
+/// sets `navigate_to` to its struct index.
+pub fn show_fields(
+    ui: &mut Ui,
+    db: &DataCoreDatabase,
+    struct_index: usize,
+    navigate_to: &mut Option<usize>,
+) {
+    let fields = db.struct_fields(struct_index);
+    if fields.is_empty() {
+        ui.label(
+            RichText::new("No fields")
+                .color(Color32::from_gray(100))
+                .italics(),
+        );
+        return;
+    }
+
+    ScrollArea::both()
+        .id_salt("dcb_struct_fields_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("dcb_struct_fields")
+                .striped(true)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    for header in ["Offset", "Size", "Name", "Type", "Declared By"] {
+                        ui.label(
+                            RichText::new(header)
+                                .strong()
+                                .color(Color32::from_gray(150)),
+                        );
+                    }
+                    ui.end_row();
+
+                    for field in &fields {
+                        ui.label(
+                            RichText::new(format!("0x{:04X}", field.offset))
+                                .monospace()
+                                .color(Color32::from_gray(140)),
+                        );
+                        ui.label(
+                            RichText::new(field.size.to_string())
+                                .monospace()
+                                .color(Color32::from_gray(140)),
+                        );
+                        ui.label(
+                            RichText::new(db.property_name(field.property).unwrap_or("Unknown"))
+                                .color(Color32::from_gray(220)),
+                        );
+                        ui.label(
+                            RichText::new(type_label(db, field.property))
+                                .color(Color32::from_gray(120))
+                                .small(),
+                        );
+
+                        let declared_by = db.struct_name(field.declared_by).unwrap_or("Unknown");
+                        if field.declared_by == struct_index {
+                            ui.label(RichText::new(declared_by).color(Color32::from_gray(100)));
+                        } else if struct_link(ui, declared_by, LINK_COLOR).clicked() {
+                            *navigate_to = Some(field.declared_by);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Render the ancestor chain of a struct, from the root down, followed by
+/// the structs deriving directly from it. Clicking a struct sets
+/// `navigate_to` to its index.
+pub fn show_inheritance(
+    ui: &mut Ui,
+    db: &DataCoreDatabase,
+    struct_index: usize,
+    navigate_to: &mut Option<usize>,
+) {
+    let mut chain = db.struct_ancestors(struct_index);
+    chain.reverse();
+    let depth = chain.len();
+    let children = db.struct_children(struct_index);
+
+    // One pass over all structs instead of one per child
+    let mut child_counts = vec![0usize; db.struct_definitions().len()];
+    for def in db.struct_definitions() {
+        if let Some(count) = usize::try_from(def.parent_type_index)
+            .ok()
+            .and_then(|parent| child_counts.get_mut(parent))
+        {
+            *count += 1;
+        }
+    }
+
+    ScrollArea::vertical()
+        .id_salt("dcb_struct_inheritance_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new(format!("Ancestors ({})", chain.len()))
+                    .strong()
+                    .color(Color32::from_gray(150)),
+            );
+            for (level, &ancestor) in chain.iter().enumerate() {
+                if hierarchy_row(ui, db, ancestor, level, child_counts[ancestor], false) {
+                    *navigate_to = Some(ancestor);
+                }
+            }
+            hierarchy_row(
+                ui,
+                db,
+                struct_index,
+                depth,
+                child_counts[struct_index],
+                true,
+            );
+
+            ui.add_space(8.0);
+            ui.label(
+                RichText::new(format!("Children ({})", children.len()))
+                    .strong()
+                    .color(Color32::from_gray(150)),
+            );
+            if children.is_empty() {
+                ui.label(
+                    RichText::new("none")
+                        .color(Color32::from_gray(60))
+                        .italics(),
+                );
+            }
+            for &child in &children {
+                if hierarchy_row(ui, db, child, depth + 1, child_counts[child], false) {
+                    *navigate_to = Some(child);
+                }
+            }
+        });
+}
+
+/// One struct in the inheritance view; returns whether it was clicked
+fn hierarchy_row(
+    ui: &mut Ui,
+    db: &DataCoreDatabase,
+    struct_index: usize,
+    level: usize,
+    child_count: usize,
+    selected: bool,
+) -> bool {
+    let name = db.struct_name(struct_index).unwrap_or("Unknown");
+    let def = &db.struct_definitions()[struct_index];
+    let mut clicked = false;
+
+    ui.horizontal(|ui| {
+        ui.add_space(level as f32 * INDENT);
+        ui.label(RichText::new("[S]").color(STRUCT_COLOR).monospace().small());
+        if selected {
+            ui.label(RichText::new(name).strong().color(STRUCT_COLOR));
+        } else {
+            clicked = struct_link(ui, name, LINK_COLOR).clicked();
+        }
+
+        let (fields, size) = (def.attribute_count, def.struct_size);
+        let mut details = format!("{} fields, {} bytes", fields, size);
+        if child_count > 0 {
+            details.push_str(&format!(", {} children", child_count));
+        }
+        ui.label(
+            RichText::new(details)
+                .color(Color32::from_gray(100))
+                .small(),
+        );
+    });
+    clicked
+}
+
+fn struct_link(ui: &mut Ui, name: &str, color: Color32) -> egui::Response {
+    let resp = ui.add(egui::Label::new(RichText::new(name).color(color)).sense(Sense::click()));
+    if resp.hovered() {
+        ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+    }
+    resp
+}
+
+fn type_label(db: &DataCoreDatabase, prop: &DataCorePropertyDefinition) -> String {
+    let struct_name = || {
+        db.struct_name(prop.struct_index as usize)
+            .unwrap_or("Unknown")
+    };
+    let element = match DataType::from_u16(prop.data_type) {
+        Some(DataType::Class) => struct_name().to_string(),
+        Some(DataType::StrongPointer) => format!("{}*", struct_name()),
+        Some(DataType::WeakPointer) => format!("&{}", struct_name()),
+        Some(DataType::Reference) => format!("ref {}", struct_name()),
+        Some(DataType::EnumChoice) => db
+            .enum_name(prop.struct_index as usize)
+            .unwrap_or("enum")
+            .to_string(),
+        Some(dt) => dt.as_str().to_string(),
+        None => format!("unknown ({})", { prop.data_type }),
+    };
+    if prop.is_array() {
+        format!("{}[]", element)
+    } else {
+        element
+    }
+}
@@ -1,11 +1,78 @@
 //! File preview rendering
 
-use eframe::egui::{self, Color32, RichText, ScrollArea, TextEdit, TextStyle, Ui};
+use eframe::egui::{self, Color32, RichText, ScrollArea, Sense, TextEdit, TextStyle, Ui};
 
-use crate::state::PreviewData;
+use crate::state::{AudioPreview, PreviewData, TexturePreview};
+
+/// Zoom limits of the texture preview
+const MIN_ZOOM: f32 = 1.0 / 64.0;
+const MAX_ZOOM: f32 = 32.0;
+
+/// Zoom and channel settings of the texture preview
+pub struct TextureView {
+    /// Scale factor, or `None` to fit the texture into the pane
+    pub zoom: Option<f32>,
+    /// Shown channels: red, green, blue and alpha
+    pub channels: [bool; 4],
+    /// Texture uploaded for the current preview, with the channels it shows
+    texture: Option<([bool; 4], egui::TextureHandle)>,
+}
+
+impl Default for TextureView {
+    fn default() -> Self {
+        Self {
+            zoom: None,
+            // Alpha mostly holds gloss or masks rather than transparency
+            channels: [true, true, true, false],
+            texture: None,
+        }
+    }
+}
+
+impl TextureView {
+    /// Forget the uploaded texture and fit the next one, keeping the channels
+    pub fn reset(&mut self) {
+        self.zoom = None;
+        self.texture = None;
+    }
+}
+
+/// Track selection and playback of the audio preview
+#[derive(Default)]
+pub struct AudioView {
+    /// Shown track of a soundbank
+    pub track: usize,
+    /// Result of the last WAV export
+    export_error: Option<String>,
+    #[cfg(feature = "audio")]
+    player: Option<Player>,
+}
+
+/// A track being played on the default output device
+#[cfg(feature = "audio")]
+struct Player {
+    track: usize,
+    started: std::time::Instant,
+    sink: rodio::Sink,
+    // Playback stops when the stream is dropped
+    _stream: rodio::OutputStream,
+}
+
+impl AudioView {
+    /// Stop playback and show the first track
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
 
 /// Render a file preview
-pub fn render_preview(ui: &mut Ui, preview: &PreviewData, loading: bool) {
+pub fn render_preview(
+    ui: &mut Ui,
+    preview: &PreviewData,
+    texture_view: &mut TextureView,
+    audio_view: &mut AudioView,
+    loading: bool,
+) {
     if loading {
         ui.centered_and_justified(|ui| {
             ui.spinner();
@@ -29,6 +96,12 @@ pub fn render_preview(ui: &mut Ui, preview: &PreviewData, loading: bool) {
         PreviewData::Image(data) => {
             render_image_preview(ui, data);
         }
+        PreviewData::Texture(texture) => {
+            render_texture_preview(ui, texture, texture_view);
+        }
+        PreviewData::Audio(audio) => {
+            render_audio_preview(ui, audio, audio_view);
+        }
     }
 }
 
@@ -143,3 +216,321 @@ fn render_image_preview(ui: &mut Ui, data: &[u8]) {
         }
     }
 }
+
+fn render_texture_preview(ui: &mut Ui, texture: &TexturePreview, view: &mut TextureView) {
+    let size = egui::vec2(texture.width as f32, texture.height as f32);
+    let available = ui.available_size();
+    let fit = (available.x / size.x).min(available.y / size.y).min(1.0);
+    let scale = view.zoom.unwrap_or(fit);
+
+    ui.horizontal(|ui| {
+        let mut info = format!(
+            "{}x{} {}, {} mips",
+            texture.width, texture.height, texture.format, texture.mip_count
+        );
+        if texture.merged_parts > 0 {
+            info.push_str(&format!(", {} split parts merged", texture.merged_parts));
+        }
+        ui.label(RichText::new(info).color(Color32::GRAY));
+
+        ui.separator();
+        for (enabled, name) in view.channels.iter_mut().zip(["R", "G", "B", "A"]) {
+            ui.toggle_value(enabled, name);
+        }
+
+        ui.separator();
+        if ui.selectable_label(view.zoom.is_none(), "Fit").clicked() {
+            view.zoom = None;
+        }
+        if ui.selectable_label(view.zoom == Some(1.0), "1:1").clicked() {
+            view.zoom = Some(1.0);
+        }
+        if ui.button("-").clicked() {
+            view.zoom = Some((scale / 2.0).max(MIN_ZOOM));
+        }
+        if ui.button("+").clicked() {
+            view.zoom = Some((scale * 2.0).min(MAX_ZOOM));
+        }
+        ui.label(format!("{:.0}%", scale * 100.0));
+    });
+    ui.separator();
+
+    // Re-upload only when the shown channels change
+    let handle = match &view.texture {
+        Some((channels, handle)) if *channels == view.channels => handle.clone(),
+        _ => {
+            let pixels = apply_channels(&texture.pixels, view.channels);
+            let handle = ui.ctx().load_texture(
+                "preview_texture",
+                egui::ColorImage::from_rgba_unmultiplied(
+                    [texture.width as usize, texture.height as usize],
+                    &pixels,
+                ),
+                // Keep texels sharp when zoomed in
+                egui::TextureOptions {
+                    magnification: egui::TextureFilter::Nearest,
+                    ..egui::TextureOptions::LINEAR
+                },
+            );
+            view.texture = Some((view.channels, handle.clone()));
+            handle
+        }
+    };
+
+    ScrollArea::both()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            let response = ui.add(
+                egui::Image::new((handle.id(), size * scale))
+                    .fit_to_exact_size(size * scale)
+                    .sense(Sense::hover()),
+            );
+            // Ctrl+scroll or pinch over the texture zooms
+            if response.hovered() {
+                let delta = ui.input(|i| i.zoom_delta());
+                if delta != 1.0 {
+                    view.zoom = Some((scale * delta).clamp(MIN_ZOOM, MAX_ZOOM));
+                }
+            }
+        });
+}
+
+fn render_audio_preview(ui: &mut Ui, preview: &AudioPreview, view: &mut AudioView) {
+    if preview.tracks.is_empty() {
+        ui.centered_and_justified(|ui| {
+            ui.label("Soundbank has no embedded media");
+        });
+        return;
+    }
+    view.track = view.track.min(preview.tracks.len() - 1);
+
+    if preview.tracks.len() > 1 {
+        let title = |i: usize| match preview.tracks[i].id {
+            Some(id) => format!("{}: {}", i + 1, id),
+            None => format!("{}", i + 1),
+        };
+        egui::ComboBox::from_id_salt("audio_track")
+            .selected_text(format!("Media {}", title(view.track)))
+            .show_ui(ui, |ui| {
+                for i in 0..preview.tracks.len() {
+                    ui.selectable_value(&mut view.track, i, title(i));
+                }
+            });
+    }
+    let track = &preview.tracks[view.track];
+
+    let audio = match &track.audio {
+        Ok(audio) => audio,
+        Err(e) => {
+            if !track.summary.is_empty() {
+                ui.label(RichText::new(&track.summary).color(Color32::GRAY));
+            }
+            ui.label(RichText::new(format!("Cannot decode: {}", e)).color(Color32::YELLOW));
+            if track.ogg.is_some() && ui.button("Export OGG...").clicked() {
+                view.export_error = export_ogg(preview, view.track).err();
+            }
+            if let Some(e) = &view.export_error {
+                ui.label(RichText::new(format!("Export failed: {}", e)).color(Color32::RED));
+            }
+            return;
+        }
+    };
+
+    ui.horizontal(|ui| {
+        let duration = audio.duration().as_secs_f32();
+        ui.label(
+            RichText::new(format!("{}, {:.2} s", track.summary, duration)).color(Color32::GRAY),
+        );
+        ui.separator();
+        render_playback_controls(ui, audio, view);
+        if ui.button("Export WAV...").clicked() {
+            view.export_error = export_wav(preview, view.track).err();
+        }
+        if track.ogg.is_some() && ui.button("Export OGG...").clicked() {
+            view.export_error = export_ogg(preview, view.track).err();
+        }
+    });
+    if let Some(e) = &view.export_error {
+        ui.label(RichText::new(format!("Export failed: {}", e)).color(Color32::RED));
+    }
+    ui.separator();
+
+    let height = ui.available_height().clamp(64.0, 240.0);
+    let (rect, _) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), height), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let center = rect.center().y;
+    let scale = rect.height() / 2.0 / f32::from(i16::MAX);
+    let column_width = rect.width() / track.peaks.len().max(1) as f32;
+    let stroke = egui::Stroke::new(column_width.max(1.0), Color32::LIGHT_BLUE);
+    for (i, &(min, max)) in track.peaks.iter().enumerate() {
+        let x = rect.left() + (i as f32 + 0.5) * column_width;
+        let top = center - f32::from(max) * scale;
+        let bottom = center - f32::from(min) * scale;
+        painter.vline(x, egui::Rangef::new(top, bottom + 1.0), stroke);
+    }
+
+    if let Some(progress) = playback_progress(view, audio) {
+        let x = rect.left() + rect.width() * progress;
+        painter.vline(x, rect.y_range(), egui::Stroke::new(1.5, Color32::YELLOW));
+        ui.ctx().request_repaint();
+    }
+}
+
+#[cfg(feature = "audio")]
+fn render_playback_controls(ui: &mut Ui, audio: &svarog::wwise::Audio, view: &mut AudioView) {
+    let playing = view
+        .player
+        .as_ref()
+        .is_some_and(|p| p.track == view.track && !p.sink.empty());
+    if playing {
+        if ui.button("Stop").clicked() {
+            view.player = None;
+        }
+    } else if ui.button("Play").clicked() {
+        match play(audio) {
+            Ok((stream, sink)) => {
+                view.player = Some(Player {
+                    track: view.track,
+                    started: std::time::Instant::now(),
+                    sink,
+                    _stream: stream,
+                });
+            }
+            Err(e) => tracing::error!("Failed to play audio: {}", e),
+        }
+    }
+}
+
+/// Without the `audio` feature there is no output device to play on
+#[cfg(not(feature = "audio"))]
+fn render_playback_controls(ui: &mut Ui, _audio: &svarog::wwise::Audio, _view: &mut AudioView) {
+    ui.add_enabled(false, egui::Button::new("Play"))
+        .on_disabled_hover_text("Build with --features audio to enable playback");
+}
+
+#[cfg(feature = "audio")]
+fn play(audio: &svarog::wwise::Audio) -> Result<(rodio::OutputStream, rodio::Sink), String> {
+    let (stream, handle) = rodio::OutputStream::try_default().map_err(|e| e.to_string())?;
+    let sink = rodio::Sink::try_new(&handle).map_err(|e| e.to_string())?;
+    sink.append(rodio::buffer::SamplesBuffer::new(
+        audio.channels,
+        audio.sample_rate,
+        audio.samples.clone(),
+    ));
+    Ok((stream, sink))
+}
+
+/// Fraction of the shown track played so far, while it plays
+#[cfg(feature = "audio")]
+fn playback_progress(view: &AudioView, audio: &svarog::wwise::Audio) -> Option<f32> {
+    let player = view
+        .player
+        .as_ref()
+        .filter(|p| p.track == view.track && !p.sink.empty())?;
+    let duration = audio.duration().as_secs_f32().max(f32::EPSILON);
+    Some((player.started.elapsed().as_secs_f32() / duration).min(1.0))
+}
+
+#[cfg(not(feature = "audio"))]
+fn playback_progress(_view: &AudioView, _audio: &svarog::wwise::Audio) -> Option<f32> {
+    None
+}
+
+/// Path chosen in a save dialog for a track, named after the file and the
+/// track's media ID
+fn export_path(
+    preview: &AudioPreview,
+    track: usize,
+    filter: &str,
+    extension: &str,
+) -> Option<std::path::PathBuf> {
+    let file_name = match preview.tracks[track].id {
+        Some(id) => format!("{}_{}.{}", preview.name, id, extension),
+        None => format!("{}.{}", preview.name, extension),
+    };
+    rfd::FileDialog::new()
+        .add_filter(filter, &[extension])
+        .set_file_name(&file_name)
+        .save_file()
+}
+
+/// Save a decoded track as a WAV file chosen in a dialog
+fn export_wav(preview: &AudioPreview, track: usize) -> Result<(), String> {
+    let audio = preview.tracks[track]
+        .audio
+        .as_ref()
+        .map_err(|e| e.clone())?;
+    let Some(path) = export_path(preview, track, "WAV audio", "wav") else {
+        return Ok(());
+    };
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(&path).map_err(|e| e.to_string())?);
+    audio
+        .write_wav(&mut writer)
+        .and_then(|()| std::io::Write::flush(&mut writer))
+        .map_err(|e| e.to_string())
+}
+
+/// Save a Wwise Vorbis track, rebuilt as Ogg Vorbis, to a file chosen in a
+/// dialog
+fn export_ogg(preview: &AudioPreview, track: usize) -> Result<(), String> {
+    let ogg = preview.tracks[track]
+        .ogg
+        .as_ref()
+        .ok_or("Track is not Wwise Vorbis")?;
+    let Some(path) = export_path(preview, track, "Ogg Vorbis audio", "ogg") else {
+        return Ok(());
+    };
+    std::fs::write(path, ogg.as_slice()).map_err(|e| e.to_string())
+}
+
+/// RGBA pixels showing only the enabled channels. A single channel is shown
+/// as grayscale; alpha is only used as transparency next to color channels.
+fn apply_channels(pixels: &[u8], channels: [bool; 4]) -> Vec<u8> {
+    let enabled: Vec<usize> = (0..4).filter(|&c| channels[c]).collect();
+    let mut output = Vec::with_capacity(pixels.len());
+    for pixel in pixels.chunks_exact(4) {
+        if let [single] = enabled[..] {
+            let value = pixel[single];
+            output.extend_from_slice(&[value, value, value, 255]);
+        } else {
+            let channel = |c: usize| if channels[c] { pixel[c] } else { 0 };
+            let alpha = if channels[3] { pixel[3] } else { 255 };
+            output.extend_from_slice(&[channel(0), channel(1), channel(2), alpha]);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_channels() {
+        let pixels = [10, 20, 30, 40, 50, 60, 70, 80];
+
+        assert_eq!(
+            apply_channels(&pixels, [true, true, true, false]),
+            [10, 20, 30, 255, 50, 60, 70, 255]
+        );
+        assert_eq!(
+            apply_channels(&pixels, [true, false, true, true]),
+            [10, 0, 30, 40, 50, 0, 70, 80]
+        );
+        // A single channel, alpha included, shows as opaque grayscale
+        assert_eq!(
+            apply_channels(&pixels, [false, false, false, true]),
+            [40, 40, 40, 255, 80, 80, 80, 255]
+        );
+        assert_eq!(
+            apply_channels(&pixels, [false; 4]),
+            [0, 0, 0, 255, 0, 0, 0, 255]
+        );
+        // A trailing partial pixel is dropped
+        assert_eq!(apply_channels(&pixels[..6], [true; 4]).len(), 4);
+    }
+}
@@ -0,0 +1,401 @@
+//! Bookmarks and session state kept between runs
+//!
+//! The session is stored through eframe's persistence in the per-user data
+//! directory, next to egui's own memory of window and panel layout. Records,
+//! structs, enums and files are saved by GUID, name or path rather than by
+//! index, so they still resolve after the game is patched.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::{
+    ActiveTab, AppState, DataCoreRecordNode, DataCoreTypeNode, ExtractionProfile, FileTreeNode,
+    NavigationEntry, RecordView, StructView,
+};
+
+/// Key of the session in eframe's storage
+pub const STORAGE_KEY: &str = "svarog-session";
+
+/// Something the browsers can show, identified independently of the loaded
+/// archive or database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Location {
+    /// A main record, by GUID
+    Record {
+        id: String,
+        name: String,
+    },
+    Struct {
+        name: String,
+    },
+    Enum {
+        name: String,
+    },
+    /// A P4K entry, by path with `/` separators
+    File {
+        path: String,
+    },
+}
+
+impl Location {
+    pub fn label(&self) -> String {
+        match self {
+            Location::Record { name, .. } => format!("[R] {}", name),
+            Location::Struct { name } => format!("[S] {}", name),
+            Location::Enum { name } => format!("[E] {}", name),
+            Location::File { path } => format!("[F] {}", path),
+        }
+    }
+}
+
+/// Everything restored on startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub p4k_path: Option<PathBuf>,
+    /// Standalone DCB file; `None` when DataCore came from the P4K
+    pub dcb_path: Option<PathBuf>,
+    pub active_tab: ActiveTab,
+    pub record_view: RecordView,
+    pub struct_view: StructView,
+    pub log_open: bool,
+    pub locale_path: Option<PathBuf>,
+    pub show_locale_keys: bool,
+    /// Vorbis codebook library for Wwise audio
+    pub codebooks_path: Option<PathBuf>,
+    pub bookmarks: Vec<Location>,
+    pub extraction_profiles: Vec<ExtractionProfile>,
+    pub navigation_history: Vec<Location>,
+    pub navigation_index: usize,
+    /// Expanded tree nodes, as `/`-joined names from the root
+    pub expanded_files: Vec<String>,
+    pub expanded_records: Vec<String>,
+    pub expanded_types: Vec<String>,
+}
+
+impl AppState {
+    /// Restore a saved session, reopening its P4K and DCB in the background.
+    /// Tree and navigation state is applied once they have loaded.
+    pub fn restore_session(&mut self, session: Session) {
+        self.active_tab = session.active_tab;
+        self.record_view = session.record_view;
+        self.struct_view = session.struct_view;
+        self.log_open = session.log_open;
+        self.show_locale_keys = session.show_locale_keys;
+        self.bookmarks = session.bookmarks.clone();
+        self.extraction_profiles = session.extraction_profiles.clone();
+
+        // Before the P4K, which would otherwise load its own locale
+        if let Some(path) = &session.locale_path {
+            self.locale_path = Some(path.clone());
+            crate::worker::load_locale(path.clone(), self.worker_sender.clone());
+        }
+
+        if let Some(path) = &session.codebooks_path {
+            self.open_codebooks(path.clone());
+        }
+
+        if let Some(path) = session.p4k_path.clone().filter(|p| p.exists()) {
+            self.p4k_loading = true;
+            self.p4k_path = Some(path.clone());
+            crate::worker::load_p4k(path, self.worker_sender.clone());
+        }
+        if let Some(path) = &session.dcb_path {
+            match std::fs::read(path) {
+                Ok(data) => {
+                    self.datacore_loading = true;
+                    self.dcb_path = Some(path.clone());
+                    crate::worker::load_datacore(data, self.worker_sender.clone());
+                }
+                Err(e) => tracing::warn!("Failed to reopen {}: {}", path.display(), e),
+            }
+        }
+
+        self.restored_session = Some(session);
+    }
+
+    /// The current session, for saving
+    pub fn session(&self) -> Session {
+        // Parts not applied yet, because their data is still loading, are
+        // carried over unchanged
+        let mut session = self.restored_session.clone().unwrap_or_default();
+        session.p4k_path = self.p4k_path.clone();
+        session.dcb_path = self.dcb_path.clone();
+        session.active_tab = self.active_tab;
+        session.record_view = self.record_view;
+        session.struct_view = self.struct_view;
+        session.log_open = self.log_open;
+        session.locale_path = self.locale_path.clone();
+        session.show_locale_keys = self.show_locale_keys;
+        session.codebooks_path = self.codebooks_path.clone();
+        session.bookmarks = self.bookmarks.clone();
+        session.extraction_profiles = self.extraction_profiles.clone();
+
+        if let Some(tree) = &self.file_tree {
+            session.expanded_files = collect_expanded(tree);
+        }
+        if let Some(tree) = &self.datacore_tree {
+            session.expanded_records = collect_expanded(tree);
+        }
+        if let Some(tree) = &self.datacore_type_tree {
+            session.expanded_types = collect_expanded(tree);
+        }
+        if self.datacore.is_some() {
+            session.navigation_history = Vec::new();
+            session.navigation_index = 0;
+            for (i, entry) in self.navigation_history.iter().enumerate() {
+                if let Some(location) = self.location_of(*entry) {
+                    session.navigation_history.push(location);
+                    if i <= self.navigation_index {
+                        session.navigation_index = session.navigation_history.len() - 1;
+                    }
+                }
+            }
+        }
+        session
+    }
+
+    /// Expand the restored P4K tree nodes, after the file tree is built
+    pub(crate) fn apply_restored_files(&mut self) {
+        let Some(session) = &mut self.restored_session else {
+            return;
+        };
+        let expanded = std::mem::take(&mut session.expanded_files);
+        if let Some(tree) = &mut self.file_tree {
+            apply_expanded(tree, &expanded);
+        }
+    }
+
+    /// Expand the restored DataCore tree nodes and resolve the navigation
+    /// history, after the DataCore trees are built
+    pub(crate) fn apply_restored_datacore(&mut self) {
+        let Some(session) = &mut self.restored_session else {
+            return;
+        };
+        let records = std::mem::take(&mut session.expanded_records);
+        let types = std::mem::take(&mut session.expanded_types);
+        let history = std::mem::take(&mut session.navigation_history);
+        let index = session.navigation_index;
+
+        if let Some(tree) = &mut self.datacore_tree {
+            apply_expanded(tree, &records);
+        }
+        if let Some(tree) = &mut self.datacore_type_tree {
+            apply_expanded(tree, &types);
+        }
+
+        self.navigation_history.clear();
+        self.navigation_index = 0;
+        for (i, location) in history.iter().enumerate() {
+            if let Some(entry) = self.navigation_entry(location) {
+                self.navigation_history.push(entry);
+                if i <= index {
+                    self.navigation_index = self.navigation_history.len() - 1;
+                }
+            }
+        }
+        self.reload_current_entry();
+    }
+
+    /// Stable location of a DataCore navigation entry
+    pub fn location_of(&self, entry: NavigationEntry) -> Option<Location> {
+        let db = self.datacore.as_ref()?;
+        Some(match entry {
+            NavigationEntry::Record(idx) => {
+                let record = db.main_record(idx)?;
+                Location::Record {
+                    id: record.id.to_string(),
+                    name: db.record_name(record).unwrap_or("Unknown").to_string(),
+                }
+            }
+            NavigationEntry::Struct(idx) => Location::Struct {
+                name: db.struct_name(idx)?.to_string(),
+            },
+            NavigationEntry::Enum(idx) => Location::Enum {
+                name: db.enum_name(idx)?.to_string(),
+            },
+        })
+    }
+
+    /// Navigation entry of a DataCore location in the loaded database
+    pub fn navigation_entry(&self, location: &Location) -> Option<NavigationEntry> {
+        let db = self.datacore.as_ref()?;
+        match location {
+            Location::Record { id, .. } => {
+                let idx = match &self.reference_index {
+                    Some(index) => index.guid_to_index.get(id).copied(),
+                    None => db.main_records().position(|r| r.id.to_string() == *id),
+                };
+                idx.map(NavigationEntry::Record)
+            }
+            Location::Struct { name } => (0..db.struct_definitions().len())
+                .position(|i| db.struct_name(i) == Some(name.as_str()))
+                .map(NavigationEntry::Struct),
+            Location::Enum { name } => (0..db.enum_definitions().len())
+                .position(|i| db.enum_name(i) == Some(name.as_str()))
+                .map(NavigationEntry::Enum),
+            Location::File { .. } => None,
+        }
+    }
+
+    /// P4K entry index of a file in the loaded archive
+    pub fn file_entry(&self, path: &str) -> Option<usize> {
+        fn find(node: &FileTreeNode, path: &str) -> Option<usize> {
+            for child in &node.children {
+                if child.path == path {
+                    return child.entry_index;
+                }
+                if child.is_directory && path.starts_with(&format!("{}/", child.path)) {
+                    return find(child, path);
+                }
+            }
+            None
+        }
+        find(self.file_tree.as_ref()?, path)
+    }
+}
+
+/// Tree nodes whose expanded state is saved
+trait ExpandableNode: Sized {
+    fn name(&self) -> &str;
+    fn is_expanded(&self) -> bool;
+    fn expand(&mut self);
+    fn children(&self) -> &[Self];
+    fn children_mut(&mut self) -> &mut [Self];
+}
+
+macro_rules! impl_expandable_node {
+    ($($node:ty),*) => {$(
+        impl ExpandableNode for $node {
+            fn name(&self) -> &str {
+                &self.name
+            }
+            fn is_expanded(&self) -> bool {
+                self.expanded
+            }
+            fn expand(&mut self) {
+                self.expanded = true;
+            }
+            fn children(&self) -> &[Self] {
+                &self.children
+            }
+            fn children_mut(&mut self) -> &mut [Self] {
+                &mut self.children
+            }
+        }
+    )*};
+}
+
+impl_expandable_node!(FileTreeNode, DataCoreRecordNode, DataCoreTypeNode);
+
+/// Paths of the expanded nodes below `root`
+fn collect_expanded<N: ExpandableNode>(root: &N) -> Vec<String> {
+    fn walk<N: ExpandableNode>(node: &N, prefix: &str, paths: &mut Vec<String>) {
+        for child in node.children() {
+            if child.is_expanded() {
+                let path = format!("{}{}", prefix, child.name());
+                walk(child, &format!("{}/", path), paths);
+                paths.push(path);
+            }
+        }
+    }
+    let mut paths = Vec::new();
+    walk(root, "", &mut paths);
+    paths
+}
+
+/// Expand the nodes below `root` at the given paths
+fn apply_expanded<N: ExpandableNode>(root: &mut N, paths: &[String]) {
+    let paths: std::collections::HashSet<&str> = paths.iter().map(String::as_str).collect();
+    fn walk<N: ExpandableNode>(
+        node: &mut N,
+        prefix: &str,
+        paths: &std::collections::HashSet<&str>,
+    ) {
+        for child in node.children_mut() {
+            let path = format!("{}{}", prefix, child.name());
+            if paths.contains(path.as_str()) {
+                child.expand();
+                walk(child, &format!("{}/", path), paths);
+            }
+        }
+    }
+    walk(root, "", &paths);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> FileTreeNode {
+        let mut data = FileTreeNode::new_directory("Data".into(), "Data".into());
+        let mut scripts = FileTreeNode::new_directory("Scripts".into(), "Data/Scripts".into());
+        scripts.children.push(FileTreeNode::new_directory(
+            "Entities".into(),
+            "Data/Scripts/Entities".into(),
+        ));
+        data.children.push(scripts);
+        data.children.push(FileTreeNode::new_directory(
+            "Textures".into(),
+            "Data/Textures".into(),
+        ));
+        let mut root = FileTreeNode::new_directory(String::new(), String::new());
+        root.children.push(data);
+        root
+    }
+
+    #[test]
+    fn test_expanded_paths() {
+        let mut expanded = tree();
+        expanded.children[0].expanded = true;
+        expanded.children[0].children[0].expanded = true;
+        let paths = collect_expanded(&expanded);
+        assert_eq!(paths, ["Data/Scripts", "Data"]);
+
+        let mut restored = tree();
+        let mut saved = paths.clone();
+        saved.push("Data/Missing".into());
+        apply_expanded(&mut restored, &saved);
+        assert_eq!(collect_expanded(&restored), paths);
+
+        // Nodes below a collapsed parent stay collapsed
+        let mut restored = tree();
+        apply_expanded(&mut restored, &["Data/Scripts".to_string()]);
+        assert!(collect_expanded(&restored).is_empty());
+    }
+
+    #[test]
+    fn test_session_serde() {
+        let session = Session {
+            p4k_path: Some(PathBuf::from("Data.p4k")),
+            bookmarks: vec![
+                Location::Record {
+                    id: "04b6b41e-2d4b-4a62-a52f-2c8bd6f0c1e7".into(),
+                    name: "Gladius".into(),
+                },
+                Location::File {
+                    path: "Data/Game.dcb".into(),
+                },
+            ],
+            navigation_index: 1,
+            ..Session::default()
+        };
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains(r#""kind":"record""#));
+
+        let restored: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.p4k_path, session.p4k_path);
+        assert_eq!(restored.bookmarks, session.bookmarks);
+        assert_eq!(restored.navigation_index, 1);
+        assert_eq!(restored.bookmarks[0].label(), "[R] Gladius");
+        assert_eq!(restored.bookmarks[1].label(), "[F] Data/Game.dcb");
+
+        // Sessions saved by older versions lack fields
+        let restored: Session = serde_json::from_str(r#"{"log_open":true}"#).unwrap();
+        assert!(restored.log_open);
+        assert!(restored.bookmarks.is_empty());
+        assert!(serde_json::from_str::<Location>(r#"{"kind":"mesh","path":"a"}"#).is_err());
+    }
+}
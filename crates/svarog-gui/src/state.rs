@@ -3,11 +3,17 @@
 #![allow(dead_code)]
 
 use crossbeam_channel::{Receiver, Sender};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use svarog::datacore::DataCoreDatabase;
-use svarog::p4k::P4kArchive;
+use svarog::cryxml::CryXmlChange;
+use svarog::common::CigGuid;
+use svarog::datacore::{CommandExporter, DataCoreDatabase, ExportJob, LocaleResolver, SearchIndex};
+use svarog::p4k::{P4kArchive, P4kDiff};
+
+use crate::log::LogBuffer;
+use crate::session::{Location, Session};
 
 /// Messages from background workers to UI
 #[derive(Debug)]
@@ -21,6 +27,12 @@ pub enum WorkerMessage {
     ExtractionProgress { current: usize, total: usize, current_file: String },
     ExtractionComplete(Result<(), String>),
     FilePreviewReady(PreviewData),
+    P4kDiffReady(Result<Arc<P4kDiffResult>, String>),
+    ContentDiffReady { change: usize, content: ContentDiff },
+    RecordSearchIndexReady(Arc<RecordSearchIndex>),
+    FileSearchIndexReady(Arc<Vec<String>>),
+    GlobalSearchReady(GlobalSearchResults),
+    LocaleLoaded(Result<Arc<LocaleResolver>, String>),
     Error(String),
 }
 
@@ -30,9 +42,156 @@ pub enum PreviewData {
     Text(String),
     Hex { data: Vec<u8>, offset: usize },
     Image(Vec<u8>), // PNG bytes
+    Texture(TexturePreview),
+    Audio(AudioPreview),
     None,
 }
 
+/// A DDS texture decoded for preview, with its split mipmaps merged
+#[derive(Debug, Clone)]
+pub struct TexturePreview {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8 pixels of the top mipmap
+    pub pixels: Vec<u8>,
+    /// Block or pixel format, e.g. "BC7"
+    pub format: String,
+    pub mip_count: u32,
+    /// Number of split mipmap entries merged into the base file
+    pub merged_parts: usize,
+}
+
+/// Wwise audio decoded for preview: one track for a `.wem` file, one per
+/// embedded media file for a `.bnk` soundbank
+#[derive(Debug, Clone)]
+pub struct AudioPreview {
+    /// File name without its extension, for naming exported files
+    pub name: String,
+    pub tracks: Vec<AudioTrack>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioTrack {
+    /// Media ID inside a soundbank
+    pub id: Option<u32>,
+    /// Codec, channels and sample rate, e.g. "PCM, 2 ch, 48000 Hz"
+    pub summary: String,
+    /// Decoded audio, or why it could not be decoded
+    pub audio: Result<Arc<svarog::wwise::Audio>, String>,
+    /// Wwise Vorbis rebuilt as an Ogg Vorbis file, for export
+    pub ogg: Option<Arc<Vec<u8>>>,
+    /// Minimum and maximum sample per waveform column
+    pub peaks: Vec<(i16, i16)>,
+}
+
+/// Old side of a P4K comparison
+#[derive(Debug, Clone)]
+pub enum DiffSource {
+    P4k(PathBuf),
+    /// JSON manifest written by `svarog p4k-manifest`
+    Manifest(PathBuf),
+}
+
+impl DiffSource {
+    pub fn path(&self) -> &std::path::Path {
+        match self {
+            DiffSource::P4k(path) | DiffSource::Manifest(path) => path,
+        }
+    }
+}
+
+/// Two compared P4K archives
+#[derive(Debug)]
+pub struct P4kDiffResult {
+    pub diff: P4kDiff,
+    /// Old archive to read contents from, `None` when it was a manifest
+    pub old_archive: Option<Arc<P4kArchive>>,
+    pub new_archive: Arc<P4kArchive>,
+}
+
+/// Contents of a modified entry compared between the archives
+#[derive(Debug, Clone)]
+pub enum ContentDiff {
+    /// Structural changes of a CryXml document
+    CryXml(Vec<CryXmlChange>),
+    /// Line diff of a text file
+    Text(Vec<DiffLine>),
+    /// Why the contents can't be compared
+    Unavailable(String),
+}
+
+/// A line of a text diff
+#[derive(Debug, Clone)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+    /// Run of unchanged lines left out between changes
+    Skipped(usize),
+}
+
+/// Node of the P4K diff tree
+#[derive(Debug, Clone)]
+pub struct DiffTreeNode {
+    pub name: String,
+    pub path: String,
+    pub children: Vec<DiffTreeNode>,
+    pub expanded: bool,
+    /// Index into the diff's changes, for entries
+    pub change: Option<usize>,
+}
+
+impl DiffTreeNode {
+    fn new(name: String, path: String, change: Option<usize>) -> Self {
+        Self {
+            name,
+            path,
+            children: Vec::new(),
+            expanded: false,
+            change,
+        }
+    }
+
+    /// Sort children: directories first, then alphabetically
+    fn sort_children(&mut self) {
+        self.children.sort_by_cached_key(|c| (c.change.is_some(), c.name.to_lowercase()));
+        for child in &mut self.children {
+            child.sort_children();
+        }
+    }
+}
+
+/// DataCore text index behind the global search
+#[derive(Debug)]
+pub struct RecordSearchIndex {
+    pub index: SearchIndex,
+    /// Maps the GUID of every record to the index of its main record
+    pub main_records: std::collections::HashMap<CigGuid, usize>,
+}
+
+/// A DataCore record found by the global search
+#[derive(Debug, Clone)]
+pub struct RecordSearchResult {
+    pub record_index: usize,
+    pub name: String,
+    /// Where the query matched: `name`, `type`, `file` or a property path
+    pub field: String,
+    pub value: String,
+}
+
+/// Results of the global search, grouped by kind
+#[derive(Debug, Clone, Default)]
+pub struct GlobalSearchResults {
+    pub query: String,
+    pub records: Vec<RecordSearchResult>,
+    /// (struct index, name)
+    pub structs: Vec<(usize, String)>,
+    /// (enum index, name)
+    pub enums: Vec<(usize, String)>,
+    /// (P4K entry index, path)
+    pub files: Vec<(usize, String)>,
+}
+
 /// Represents a node in the P4K file tree
 #[derive(Debug, Clone)]
 pub struct FileTreeNode {
@@ -165,7 +324,7 @@ impl DataCoreTypeNode {
     }
 
     pub fn sort_children(&mut self) {
-        self.children.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        self.children.sort_by_key(|a| a.name.to_lowercase());
         for child in &mut self.children {
             child.sort_children();
         }
@@ -253,7 +412,8 @@ impl std::fmt::Display for ReferenceType {
 }
 
 /// Extraction options
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct ExtractionOptions {
     pub output_path: PathBuf,
     pub filter_pattern: String,
@@ -261,6 +421,10 @@ pub struct ExtractionOptions {
     pub incremental: bool,
     pub expand_socpak: bool,
     pub extract_dcb: bool,
+    /// Decode CryXmlB files to text XML
+    pub decode_cryxml: bool,
+    /// Merge split DDS textures into their base files after extraction
+    pub merge_dds: bool,
     pub parallel_workers: usize,
 }
 
@@ -273,17 +437,27 @@ impl Default for ExtractionOptions {
             incremental: true,
             expand_socpak: true,
             extract_dcb: true,
+            decode_cryxml: true,
+            merge_dds: false,
             parallel_workers: 0, // auto
         }
     }
 }
 
+/// Named extraction options, saved with the session
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExtractionProfile {
+    pub name: String,
+    pub options: ExtractionOptions,
+}
+
 /// Current active tab
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum ActiveTab {
     #[default]
     P4kBrowser,
     DataCoreBrowser,
+    P4kDiff,
 }
 
 /// A navigation entry for the DataCore browser history
@@ -294,6 +468,23 @@ pub enum NavigationEntry {
     Enum(usize),
 }
 
+/// A DataCore viewer tab. The active tab lives in the flat `AppState`
+/// fields; background tabs keep their own navigation history here.
+#[derive(Debug, Clone, Default)]
+pub struct DataCoreTab {
+    pub navigation_history: Vec<NavigationEntry>,
+    pub navigation_index: usize,
+    /// XML of the shown record, for viewing beside the active tab
+    pub record_xml: String,
+}
+
+impl DataCoreTab {
+    /// The entry the tab shows
+    pub fn current(&self) -> Option<NavigationEntry> {
+        self.navigation_history.get(self.navigation_index).copied()
+    }
+}
+
 /// Active page within the DataCore browser
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DataCorePage {
@@ -303,6 +494,26 @@ pub enum DataCorePage {
     Enums,
 }
 
+/// How the selected record's contents are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum RecordView {
+    #[default]
+    Xml,
+    Table,
+}
+
+/// How the selected struct is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum StructView {
+    /// C header style layout
+    #[default]
+    Layout,
+    /// All fields, inherited ones included, with their offsets
+    Fields,
+    /// Ancestors and direct children
+    Inheritance,
+}
+
 /// Main application state
 pub struct AppState {
     // Current tab
@@ -315,14 +526,23 @@ pub struct AppState {
     pub p4k_load_progress: (usize, usize, String),
     pub file_tree: Option<FileTreeNode>,
     pub selected_file: Option<String>,
+    /// Selected file of each P4K viewer tab; the active tab's is `selected_file`
+    pub file_tabs: Vec<Option<String>>,
+    pub file_tab: usize,
     pub file_filter: String,
+    /// File opened from disk rather than from the P4K, shown in the preview
+    pub loose_file: Option<PathBuf>,
 
     // Preview state
     pub preview: PreviewData,
     pub preview_loading: bool,
+    pub texture_view: crate::preview::TextureView,
+    pub audio_view: crate::preview::AudioView,
 
     // DataCore state
     pub datacore: Option<Arc<DataCoreDatabase>>,
+    /// DCB file the database was opened from, unless it came from the P4K
+    pub dcb_path: Option<PathBuf>,
     pub datacore_loading: bool,
     pub datacore_progress: (usize, usize),
     pub datacore_tree: Option<DataCoreRecordNode>,
@@ -332,6 +552,19 @@ pub struct AppState {
     pub selected_enum: Option<usize>,
     pub selected_type: Option<usize>,
     pub record_xml: String,
+    pub record_view: RecordView,
+    pub struct_view: StructView,
+    /// Localization text for `Locale` values
+    pub locale: Option<Arc<LocaleResolver>>,
+    /// `global.ini` the locale was opened from, unless it came from the P4K
+    pub locale_path: Option<PathBuf>,
+    /// Show `Locale` values as raw keys instead of their text
+    pub show_locale_keys: bool,
+    /// Codebooks that Wwise Vorbis audio refers to, for its preview
+    pub codebooks: Option<Arc<svarog::wwise::CodebookLibrary>>,
+    pub codebooks_path: Option<PathBuf>,
+    /// Expanded property paths in the record table
+    pub record_table_expanded: std::collections::HashSet<String>,
     pub enum_preview: String,
     pub type_preview: String,
     pub type_filter: Option<String>,
@@ -346,12 +579,63 @@ pub struct AppState {
     pub incoming_expanded: bool,
     pub navigation_history: Vec<NavigationEntry>,
     pub navigation_index: usize,
+    /// DataCore viewer tabs; the one at `datacore_tab` is a placeholder for
+    /// the active tab, whose state is in the fields above
+    pub datacore_tabs: Vec<DataCoreTab>,
+    pub datacore_tab: usize,
+    /// Background tab whose record is shown beside the active one
+    pub datacore_beside: Option<usize>,
+    /// Entry for the DataCore browser to load on the next frame, after a
+    /// session restore or a locale change
+    pub reload_entry: Option<NavigationEntry>,
     pub selected_line: Option<usize>,
     pub datacore_page: DataCorePage,
+    pub datacore_export: Option<ExportJob>,
+    /// Format of "Export All" on the records page
+    pub datacore_export_format: String,
+    /// `svarog-export-*` programs found on the PATH at startup
+    pub export_commands: Vec<CommandExporter>,
     pub about_open: bool,
 
+    // Bookmarks and session restore
+    pub bookmarks: Vec<Location>,
+    /// Saved session parts still waiting for their data to load
+    pub restored_session: Option<Session>,
+    /// Dropped or command-line files waiting for the previous one to load
+    pub open_queue: VecDeque<PathBuf>,
+
+    // P4K diff state
+    pub diff_old: Option<DiffSource>,
+    pub diff_new: Option<PathBuf>,
+    pub diff_loading: bool,
+    pub diff_result: Option<Arc<P4kDiffResult>>,
+    pub diff_tree: Option<DiffTreeNode>,
+    pub diff_filter: String,
+    /// Shown change kinds: added, removed, modified
+    pub diff_show: [bool; 3],
+    pub diff_selected: Option<usize>,
+    pub diff_content: Option<ContentDiff>,
+    pub diff_content_loading: bool,
+
+    // Global search state
+    pub global_search: String,
+    pub global_search_open: bool,
+    /// Query of the last search handed to the worker
+    pub global_search_requested: Option<String>,
+    pub global_search_results: Option<GlobalSearchResults>,
+    pub record_search_index: Option<Arc<RecordSearchIndex>>,
+    /// Lowercase P4K entry names, by entry index
+    pub file_search_index: Option<Arc<Vec<String>>>,
+
+    // Log panel
+    pub log: LogBuffer,
+    pub log_open: bool,
+
     // Extraction state
     pub extraction_options: ExtractionOptions,
+    pub extraction_profiles: Vec<ExtractionProfile>,
+    /// Name of the profile being edited, or to save the options under
+    pub extraction_profile_name: String,
     pub extraction_dialog_open: bool,
     pub extracting: bool,
     pub extraction_progress: (usize, usize, String),
@@ -376,10 +660,16 @@ impl AppState {
             p4k_load_progress: (0, 0, String::new()),
             file_tree: None,
             selected_file: None,
+            file_tabs: vec![None],
+            file_tab: 0,
             file_filter: String::new(),
+            loose_file: None,
             preview: PreviewData::None,
             preview_loading: false,
+            texture_view: crate::preview::TextureView::default(),
+            audio_view: crate::preview::AudioView::default(),
             datacore: None,
+            dcb_path: None,
             datacore_loading: false,
             datacore_progress: (0, 0),
             datacore_tree: None,
@@ -389,6 +679,14 @@ impl AppState {
             selected_enum: None,
             selected_type: None,
             record_xml: String::new(),
+            record_view: RecordView::default(),
+            struct_view: StructView::default(),
+            locale: None,
+            locale_path: None,
+            show_locale_keys: false,
+            codebooks: None,
+            codebooks_path: None,
+            record_table_expanded: std::collections::HashSet::new(),
             enum_preview: String::new(),
             type_preview: String::new(),
             type_filter: None,
@@ -403,10 +701,40 @@ impl AppState {
             incoming_expanded: true,
             navigation_history: Vec::new(),
             navigation_index: 0,
+            datacore_tabs: vec![DataCoreTab::default()],
+            datacore_tab: 0,
+            datacore_beside: None,
+            reload_entry: None,
             selected_line: None,
             datacore_page: DataCorePage::default(),
+            datacore_export: None,
+            datacore_export_format: "xml".to_string(),
+            export_commands: CommandExporter::discover(),
             about_open: false,
+            bookmarks: Vec::new(),
+            restored_session: None,
+            open_queue: VecDeque::new(),
+            diff_old: None,
+            diff_new: None,
+            diff_loading: false,
+            diff_result: None,
+            diff_tree: None,
+            diff_filter: String::new(),
+            diff_show: [true; 3],
+            diff_selected: None,
+            diff_content: None,
+            diff_content_loading: false,
+            global_search: String::new(),
+            global_search_open: false,
+            global_search_requested: None,
+            global_search_results: None,
+            record_search_index: None,
+            file_search_index: None,
+            log: LogBuffer::default(),
+            log_open: false,
             extraction_options: ExtractionOptions::default(),
+            extraction_profiles: Vec::new(),
+            extraction_profile_name: String::new(),
             extraction_dialog_open: false,
             extracting: false,
             extraction_progress: (0, 0, String::new()),
@@ -427,6 +755,18 @@ impl AppState {
         self.error_dismiss_time = None;
     }
 
+    /// Load the shown DataCore entry again on the next frame, e.g. after the
+    /// way `Locale` values are displayed changed
+    pub fn reload_current_entry(&mut self) {
+        self.reload_entry = self.navigation_history.get(self.navigation_index).copied();
+    }
+
+    /// Whether a global search is running in the background
+    pub fn global_search_pending(&self) -> bool {
+        self.global_search_requested.is_some()
+            && self.global_search_results.as_ref().map(|r| &r.query) != self.global_search_requested.as_ref()
+    }
+
     /// Process messages from workers
     pub fn process_messages(&mut self) {
         while let Ok(msg) = self.worker_receiver.try_recv() {
@@ -435,8 +775,17 @@ impl AppState {
                     self.p4k_loading = false;
                     match result {
                         Ok(archive) => {
+                            self.file_search_index = None;
+                            self.global_search_requested = None;
+                            crate::worker::build_file_search_index(archive.clone(), self.worker_sender.clone());
+                            if self.locale_path.is_none() {
+                                crate::worker::load_locale_from_p4k(archive.clone(), self.worker_sender.clone());
+                            }
                             self.p4k_archive = Some(archive);
                         self.build_file_tree();
+                            self.file_tabs = vec![None];
+                            self.file_tab = 0;
+                            self.apply_restored_files();
                         }
                         Err(e) => self.show_error(format!("Failed to load P4K: {}", e)),
                     }
@@ -453,11 +802,19 @@ impl AppState {
                             self.selected_type = None;
                             self.selected_enum = None;
                             self.record_xml.clear();
+                            self.record_table_expanded.clear();
                             self.type_preview.clear();
                             self.enum_preview.clear();
                             self.datacore_page = DataCorePage::Records;
+                            self.datacore_tabs = vec![DataCoreTab::default()];
+                            self.datacore_tab = 0;
+                            self.datacore_beside = None;
                             self.build_datacore_tree();
                             self.build_datacore_type_tree();
+                            self.apply_restored_datacore();
+                            self.record_search_index = None;
+                            self.global_search_requested = None;
+                            crate::worker::build_record_search_index(db.clone(), self.worker_sender.clone());
                             // Build reference index in background
                             crate::worker::build_reference_index(db, self.worker_sender.clone());
                         }
@@ -470,6 +827,27 @@ impl AppState {
                 WorkerMessage::StructReferenceIndexReady(index) => {
                     self.struct_reference_index = Some(index);
                 }
+                WorkerMessage::RecordSearchIndexReady(index) => {
+                    self.record_search_index = Some(index);
+                    // Search again with the new index
+                    self.global_search_requested = None;
+                }
+                WorkerMessage::FileSearchIndexReady(index) => {
+                    self.file_search_index = Some(index);
+                    self.global_search_requested = None;
+                }
+                WorkerMessage::GlobalSearchReady(results) => {
+                    if self.global_search_requested.as_ref() == Some(&results.query) {
+                        self.global_search_results = Some(results);
+                    }
+                }
+                WorkerMessage::LocaleLoaded(result) => match result {
+                    Ok(locale) => {
+                        self.locale = Some(locale);
+                        self.reload_current_entry();
+                    }
+                    Err(e) => self.show_error(format!("Failed to load locale: {}", e)),
+                },
                 WorkerMessage::DataCoreProgress { current, total } => {
                     self.datacore_progress = (current, total);
                 }
@@ -485,6 +863,27 @@ impl AppState {
                 WorkerMessage::FilePreviewReady(data) => {
                     self.preview = data;
                     self.preview_loading = false;
+                    self.texture_view.reset();
+                    self.audio_view.reset();
+                }
+                WorkerMessage::P4kDiffReady(result) => {
+                    self.diff_loading = false;
+                    self.diff_selected = None;
+                    self.diff_content = None;
+                    match result {
+                        Ok(result) => {
+                            self.diff_result = Some(result);
+                            self.build_diff_tree();
+                        }
+                        Err(e) => self.show_error(format!("Failed to compare: {}", e)),
+                    }
+                }
+                WorkerMessage::ContentDiffReady { change, content } => {
+                    // Ignore answers for an entry that is no longer selected
+                    if self.diff_selected == Some(change) {
+                        self.diff_content = Some(content);
+                        self.diff_content_loading = false;
+                    }
                 }
                 WorkerMessage::Error(e) => {
                     self.show_error(e);
@@ -552,6 +951,36 @@ impl AppState {
         self.file_tree = Some(root);
     }
 
+    /// Build the tree of changed entries from the P4K diff
+    fn build_diff_tree(&mut self) {
+        let Some(result) = &self.diff_result else { return };
+
+        let mut root = DiffTreeNode::new("root".to_string(), String::new(), None);
+        for (idx, change) in result.diff.changes().iter().enumerate() {
+            let parts: Vec<&str> = change.name().split('/').collect();
+            let mut current = &mut root;
+            for (i, part) in parts.iter().enumerate() {
+                let path = parts[..=i].join("/");
+                if i == parts.len() - 1 {
+                    current.children.push(DiffTreeNode::new(part.to_string(), path, Some(idx)));
+                } else {
+                    // Changes are sorted by name, so an existing directory is the last child
+                    let exists = current
+                        .children
+                        .last()
+                        .is_some_and(|c| c.change.is_none() && c.name.eq_ignore_ascii_case(part));
+                    if !exists {
+                        current.children.push(DiffTreeNode::new(part.to_string(), path, None));
+                    }
+                    current = current.children.last_mut().unwrap();
+                }
+            }
+        }
+        root.sort_children();
+
+        self.diff_tree = Some(root);
+    }
+
     /// Build DataCore record tree
     fn build_datacore_tree(&mut self) {
         use svarog::datacore::{Value, ArrayElementType};
@@ -561,7 +990,7 @@ impl AppState {
         let mut root = DataCoreRecordNode::new_folder("DataCore".to_string());
 
         for (idx, record) in db.main_records().enumerate() {
-            let file_name = db.record_file_name(&record).unwrap_or_default();
+            let file_name = db.record_file_name(record).unwrap_or_default();
             let path = file_name.replace('\\', "/");
             let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
 
@@ -731,51 +1160,51 @@ impl AppState {
                                 .push((source_idx, prop.name.to_string(), ReferenceType::WeakPointer));
                         }
                     }
-                    Value::Array(array_ref) => {
-                        if array_ref.count > 0 && array_ref.count < 1_000_000 {
-                            match array_ref.element_type {
-                                ArrayElementType::Reference => {
-                                    for i in 0..array_ref.count.min(100) {
-                                        let idx = array_ref.first_index as usize + i as usize;
-                                        if let Some(ref_val) = db.reference_value(idx) {
-                                            let guid_str = format!("{}", ref_val.record_id);
-                                            if let Some(&target_idx) = guid_to_index.get(&guid_str) {
-                                                incoming
-                                                    .entry(target_idx)
-                                                    .or_default()
-                                                    .push((source_idx, format!("{}[{}]", prop.name, i), ReferenceType::Reference));
-                                            }
+                    Value::Array(array_ref)
+                        if array_ref.count > 0 && array_ref.count < 1_000_000 =>
+                    {
+                        match array_ref.element_type {
+                            ArrayElementType::Reference => {
+                                for i in 0..array_ref.count.min(100) {
+                                    let idx = array_ref.first_index as usize + i as usize;
+                                    if let Some(ref_val) = db.reference_value(idx) {
+                                        let guid_str = format!("{}", ref_val.record_id);
+                                        if let Some(&target_idx) = guid_to_index.get(&guid_str) {
+                                            incoming
+                                                .entry(target_idx)
+                                                .or_default()
+                                                .push((source_idx, format!("{}[{}]", prop.name, i), ReferenceType::Reference));
                                         }
                                     }
                                 }
-                                ArrayElementType::StrongPointer | ArrayElementType::WeakPointer => {
-                                    let ref_type = if array_ref.element_type == ArrayElementType::StrongPointer {
-                                        ReferenceType::StrongPointer
-                                    } else {
-                                        ReferenceType::WeakPointer
+                            }
+                            ArrayElementType::StrongPointer | ArrayElementType::WeakPointer => {
+                                let ref_type = if array_ref.element_type == ArrayElementType::StrongPointer {
+                                    ReferenceType::StrongPointer
+                                } else {
+                                    ReferenceType::WeakPointer
+                                };
+
+                                for i in 0..array_ref.count.min(100) {
+                                    let idx = array_ref.first_index as usize + i as usize;
+                                    let ptr = match array_ref.element_type {
+                                        ArrayElementType::StrongPointer => db.strong_value(idx),
+                                        ArrayElementType::WeakPointer => db.weak_value(idx),
+                                        _ => None,
                                     };
 
-                                    for i in 0..array_ref.count.min(100) {
-                                        let idx = array_ref.first_index as usize + i as usize;
-                                        let ptr = match array_ref.element_type {
-                                            ArrayElementType::StrongPointer => db.strong_value(idx),
-                                            ArrayElementType::WeakPointer => db.weak_value(idx),
-                                            _ => None,
-                                        };
-
-                                        if let Some(ptr) = ptr {
-                                            let key = (ptr.struct_index as u32, ptr.instance_index as u32);
-                                            if let Some(&target_idx) = instance_to_index.get(&key) {
-                                                incoming
-                                                    .entry(target_idx)
-                                                    .or_default()
-                                                    .push((source_idx, format!("{}[{}]", prop.name, i), ref_type));
-                                            }
+                                    if let Some(ptr) = ptr {
+                                        let key = (ptr.struct_index as u32, ptr.instance_index as u32);
+                                        if let Some(&target_idx) = instance_to_index.get(&key) {
+                                            incoming
+                                                .entry(target_idx)
+                                                .or_default()
+                                                .push((source_idx, format!("{}[{}]", prop.name, i), ref_type));
                                         }
                                     }
                                 }
-                                _ => {}
                             }
+                            _ => {}
                         }
                     }
                     _ => {}
@@ -786,3 +1215,30 @@ impl AppState {
         self.reference_index = Some(std::sync::Arc::new(ReferenceIndex { incoming, guid_to_index }));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_loaded() {
+        let mut state = AppState::new();
+        state.navigation_history = vec![NavigationEntry::Record(4)];
+
+        let locale = LocaleResolver::parse("item_NameGladius=Gladius\n");
+        let message = WorkerMessage::LocaleLoaded(Ok(Arc::new(locale)));
+        state.worker_sender.send(message).unwrap();
+        state.process_messages();
+        assert_eq!(state.locale.as_ref().unwrap().len(), 1);
+        // The shown record is loaded again with the new text
+        assert_eq!(state.reload_entry, Some(NavigationEntry::Record(4)));
+
+        let message = WorkerMessage::LocaleLoaded(Err("not found".into()));
+        state.worker_sender.send(message).unwrap();
+        state.process_messages();
+        assert_eq!(
+            state.error_message.as_deref(),
+            Some("Failed to load locale: not found")
+        );
+    }
+}
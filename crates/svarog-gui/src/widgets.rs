@@ -87,6 +87,46 @@ pub fn tree_node<R>(
     }
 }
 
+/// What was done to a tab in a [`tab_strip`]
+pub enum TabAction {
+    Select(usize),
+    Close(usize),
+}
+
+/// Render a row of closable tabs. Middle-click closes a tab; `context_menu`
+/// adds entries to a tab's right-click menu.
+pub fn tab_strip(
+    ui: &mut Ui,
+    titles: &[String],
+    active: usize,
+    mut context_menu: impl FnMut(&mut Ui, usize),
+) -> Option<TabAction> {
+    let mut action = None;
+    ui.horizontal_wrapped(|ui| {
+        for (i, title) in titles.iter().enumerate() {
+            let response = ui.selectable_label(i == active, title);
+            if response.clicked() {
+                action = Some(TabAction::Select(i));
+            }
+            if response.middle_clicked() {
+                action = Some(TabAction::Close(i));
+            }
+            response.context_menu(|ui| {
+                context_menu(ui, i);
+                if ui.button("Close tab").clicked() {
+                    action = Some(TabAction::Close(i));
+                    ui.close_menu();
+                }
+            });
+            if ui.small_button("x").on_hover_text("Close tab").clicked() {
+                action = Some(TabAction::Close(i));
+            }
+            ui.separator();
+        }
+    });
+    action
+}
+
 /// Icon for file/folder (text-based)
 pub fn file_icon(is_directory: bool, is_encrypted: bool) -> &'static str {
     if is_directory {
@@ -4,11 +4,16 @@ use crossbeam_channel::Sender;
 use std::path::Path;
 use std::sync::Arc;
 
-use svarog::cryxml::CryXml;
-use svarog::datacore::DataCoreDatabase;
-use svarog::p4k::P4kArchive;
+use svarog::cryxml::{CryXml, CryXmlDiff};
+use svarog::datacore::{DataCoreDatabase, LocaleResolver, SearchField, SearchIndex, SearchOptions};
+use svarog::p4k::{DecompressionLimits, EntrySummary, P4kArchive, P4kDiff};
+use svarog::wwise::{CodebookLibrary, Codebooks, Codec, SoundBank, Wem};
 
-use crate::state::{IncomingStructReference, PreviewData, ReferenceIndex, ReferenceType, StructReferenceIndex, WorkerMessage};
+use crate::state::{AudioPreview, AudioTrack, ContentDiff, DiffLine, DiffSource, GlobalSearchResults, IncomingStructReference, P4kDiffResult, PreviewData, RecordSearchIndex, RecordSearchResult, ReferenceIndex, ReferenceType, StructReferenceIndex, TexturePreview, WorkerMessage};
+
+/// Largest entry the browser decompresses; P4K files stay well below it,
+/// while corrupt or malicious archives can declare any size.
+const MAX_ENTRY_SIZE: u64 = 4 << 30;
 
 /// Load a P4K archive in a background thread
 pub fn load_p4k(path: impl AsRef<Path>, sender: Sender<WorkerMessage>) {
@@ -22,6 +27,7 @@ pub fn load_p4k(path: impl AsRef<Path>, sender: Sender<WorkerMessage>) {
 
         match P4kArchive::open(&path) {
             Ok(archive) => {
+                let archive = archive.with_limits(DecompressionLimits::new().max_size(MAX_ENTRY_SIZE));
                 let count = archive.entry_count();
                 sender.send(WorkerMessage::P4kProgress {
                     current: count,
@@ -61,8 +67,40 @@ pub fn load_datacore(data: Vec<u8>, sender: Sender<WorkerMessage>) {
     });
 }
 
+/// Localization shown for `Locale` values when a P4K is opened
+const LOCALE_PATH: &str = "Data/Localization/english/global.ini";
+
+/// Load a `global.ini` in a background thread
+pub fn load_locale(path: std::path::PathBuf, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let result = LocaleResolver::open(&path)
+            .map(Arc::new)
+            .map_err(|e| e.to_string());
+        sender.send(WorkerMessage::LocaleLoaded(result)).ok();
+    });
+}
+
+/// Load the P4K's `global.ini` in a background thread, if it has one
+pub fn load_locale_from_p4k(archive: Arc<P4kArchive>, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let Some(entry) = archive.find(LOCALE_PATH) else {
+            return;
+        };
+        let result = archive
+            .read(&entry)
+            .map(|data| Arc::new(LocaleResolver::from_bytes(&data)))
+            .map_err(|e| e.to_string());
+        sender.send(WorkerMessage::LocaleLoaded(result)).ok();
+    });
+}
+
 /// Load file preview in a background thread
-pub fn load_preview(archive: Arc<P4kArchive>, entry_index: usize, sender: Sender<WorkerMessage>) {
+pub fn load_preview(
+    archive: Arc<P4kArchive>,
+    entry_index: usize,
+    codebooks: Option<Arc<CodebookLibrary>>,
+    sender: Sender<WorkerMessage>,
+) {
     std::thread::spawn(move || {
         let entry = match archive.get(entry_index) {
             Some(e) => e,
@@ -82,23 +120,216 @@ pub fn load_preview(archive: Arc<P4kArchive>, entry_index: usize, sender: Sender
             }
         };
 
-        let name_lower = entry.name.to_lowercase();
-        let preview = determine_preview(&data, &name_lower);
+        let preview = file_preview(entry.name, &data, codebooks.as_deref(), || {
+            archive_split_parts(&archive, entry.name)
+        });
         sender.send(WorkerMessage::FilePreviewReady(preview)).ok();
     });
 }
 
+/// Load a file from disk in a background thread, previewing it like a P4K
+/// entry
+pub fn load_file_preview(
+    path: std::path::PathBuf,
+    codebooks: Option<Arc<CodebookLibrary>>,
+    sender: Sender<WorkerMessage>,
+) {
+    std::thread::spawn(move || {
+        let data = match std::fs::read(&path) {
+            Ok(d) => d,
+            Err(e) => {
+                sender.send(WorkerMessage::Error(format!("Failed to read file: {}", e))).ok();
+                sender.send(WorkerMessage::FilePreviewReady(PreviewData::None)).ok();
+                return;
+            }
+        };
+
+        let name = path.to_string_lossy();
+        let preview = file_preview(&name, &data, codebooks.as_deref(), || {
+            disk_split_parts(&path)
+        });
+        sender.send(WorkerMessage::FilePreviewReady(preview)).ok();
+    });
+}
+
+/// Preview of a file by its name. `codebooks` decode Wwise Vorbis audio;
+/// `split_parts` reads the split mipmaps of a DDS texture, by part number.
+fn file_preview(
+    name: &str,
+    data: &[u8],
+    codebooks: Option<&CodebookLibrary>,
+    split_parts: impl FnOnce() -> anyhow::Result<Vec<(u8, Vec<u8>)>>,
+) -> PreviewData {
+    let name_lower = name.to_lowercase();
+    if name_lower.ends_with(".dds") || name_lower.ends_with(".dds.a") {
+        split_parts()
+            .and_then(|parts| load_texture(data, parts))
+            .unwrap_or_else(|e| {
+                // Formats without a decoder, like BC6H, fall back to hex
+                tracing::warn!("Failed to decode {}: {:#}", name, e);
+                hex_preview(data)
+            })
+    } else if name_lower.ends_with(".wem") || name_lower.ends_with(".bnk") {
+        load_audio(name, data, codebooks).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse {}: {:#}", name, e);
+            hex_preview(data)
+        })
+    } else if name_lower.ends_with(".chf") {
+        load_character(data).unwrap_or_else(|e| {
+            tracing::warn!("Failed to parse {}: {:#}", name, e);
+            hex_preview(data)
+        })
+    } else {
+        determine_preview(data, &name_lower)
+    }
+}
+
+/// Split mipmaps of a DDS entry, from the archive
+fn archive_split_parts(archive: &P4kArchive, name: &str) -> anyhow::Result<Vec<(u8, Vec<u8>)>> {
+    let part_names: Vec<(u8, String)> = (0..=9)
+        .map(|i| {
+            let part = svarog::dds::split_path(Path::new(name), i);
+            (i, part.to_string_lossy().into_owned())
+        })
+        .collect();
+
+    // One pass over the archive rather than a lookup per part
+    let mut parts = Vec::new();
+    for entry in archive.iter() {
+        if let Some((i, _)) = part_names
+            .iter()
+            .find(|(_, part)| part.eq_ignore_ascii_case(entry.name))
+        {
+            parts.push((*i, archive.read(&entry)?));
+        }
+    }
+    Ok(parts)
+}
+
+/// Split mipmaps of a DDS file, from the files next to it
+fn disk_split_parts(path: &Path) -> anyhow::Result<Vec<(u8, Vec<u8>)>> {
+    let mut parts = Vec::new();
+    for i in 0..=9 {
+        let part = svarog::dds::split_path(path, i);
+        if part.is_file() {
+            parts.push((i, std::fs::read(part)?));
+        }
+    }
+    Ok(parts)
+}
+
+/// Merge a DDS file with its split mipmaps and decode the top mipmap
+fn load_texture(base: &[u8], mut parts: Vec<(u8, Vec<u8>)>) -> anyhow::Result<PreviewData> {
+    parts.sort_by_key(|(i, _)| std::cmp::Reverse(*i));
+
+    let merged = if parts.is_empty() {
+        base.to_vec()
+    } else {
+        svarog::dds::merge_dds_data(base, &parts)?
+    };
+    let info = svarog::dds::DdsInfo::parse(&merged)?;
+    let image = svarog::dds::decode_to_rgba(&merged)?;
+
+    Ok(PreviewData::Texture(TexturePreview {
+        width: image.width(),
+        height: image.height(),
+        format: info.format().map(|f| f.to_string()).unwrap_or_default(),
+        mip_count: info.mip_count(),
+        merged_parts: parts.len(),
+        pixels: image.into_raw(),
+    }))
+}
+
+/// Decode a character head file and show its data as JSON
+fn load_character(data: &[u8]) -> anyhow::Result<PreviewData> {
+    // Lenient, so files with unexpected trailers still show
+    let file = svarog::chf::ChfFile::parse_with(data, &svarog::chf::ParseOptions::lenient())?;
+    let character = svarog::chf::ChfData::parse(file.data())?;
+    Ok(PreviewData::Text(character.to_json()?))
+}
+
+/// Columns of the audio preview waveform
+const WAVEFORM_COLUMNS: usize = 1024;
+
+/// Decode a WEM file, or each WEM file embedded in a soundbank
+fn load_audio(
+    name: &str,
+    data: &[u8],
+    codebooks: Option<&CodebookLibrary>,
+) -> anyhow::Result<PreviewData> {
+    let tracks = if name.to_lowercase().ends_with(".bnk") {
+        let bank = SoundBank::parse(data)?;
+        bank.media()
+            .iter()
+            .map(|entry| {
+                let mut track = match bank.wem(entry).map(Wem::parse) {
+                    Some(Ok(wem)) => audio_track(&wem, codebooks),
+                    Some(Err(e)) => failed_track(e.to_string()),
+                    None => failed_track("Media lies outside the DATA section".to_string()),
+                };
+                track.id = Some(entry.id);
+                track
+            })
+            .collect()
+    } else {
+        vec![audio_track(&Wem::parse(data)?, codebooks)]
+    };
+
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    Ok(PreviewData::Audio(AudioPreview {
+        name: file_name.rsplit_once('.').map_or(file_name, |(stem, _)| stem).to_string(),
+        tracks,
+    }))
+}
+
+fn audio_track(wem: &Wem, library: Option<&CodebookLibrary>) -> AudioTrack {
+    // Without a library, try codebooks packed into the file itself
+    let codebooks = library.map_or(Codebooks::Inline, Codebooks::Library);
+    let hint = if wem.codec() == Codec::Vorbis && library.is_none() {
+        " (open Vorbis codebooks from the File menu)"
+    } else {
+        ""
+    };
+    let audio = wem
+        .decode_with(codebooks)
+        .map(Arc::new)
+        .map_err(|e| format!("{}{}", e, hint));
+    // Only Wwise Vorbis rebuilds as Ogg
+    let ogg = wem.to_ogg(codebooks).ok().map(Arc::new);
+    AudioTrack {
+        id: None,
+        summary: format!(
+            "{}, {} ch, {} Hz",
+            wem.codec(),
+            wem.channels(),
+            wem.sample_rate()
+        ),
+        peaks: audio
+            .as_ref()
+            .map(|a| a.peaks(WAVEFORM_COLUMNS))
+            .unwrap_or_default(),
+        audio,
+        ogg,
+    }
+}
+
+fn failed_track(error: String) -> AudioTrack {
+    AudioTrack {
+        id: None,
+        summary: String::new(),
+        audio: Err(error),
+        ogg: None,
+        peaks: Vec::new(),
+    }
+}
+
 fn determine_preview(data: &[u8], name_lower: &str) -> PreviewData {
     // Check for CryXML binary
     if CryXml::is_cryxml(data) {
-        match CryXml::parse(data) {
-            Ok(xml) => {
-                match xml.to_xml_string() {
-                    Ok(text) => return PreviewData::Text(text),
-                    Err(_) => {}
-                }
+        if let Ok(xml) = CryXml::parse(data) {
+            if let Ok(text) = xml.to_xml_string() {
+                return PreviewData::Text(text);
             }
-            Err(_) => {}
         }
     }
 
@@ -130,16 +361,10 @@ fn determine_preview(data: &[u8], name_lower: &str) -> PreviewData {
         }
     }
 
-    // Check for DDS - convert to PNG
-    if name_lower.ends_with(".dds") {
-        // For now, show as hex - DDS conversion requires additional libraries
-        // In a full implementation, we'd use the ddsfile crate or similar
-        return PreviewData::Hex {
-            data: data.to_vec(),
-            offset: 0,
-        };
-    }
+    hex_preview(data)
+}
 
+fn hex_preview(data: &[u8]) -> PreviewData {
     // Default to hex view for small files, or truncated hex for large
     let max_hex_size = 1024 * 1024; // 1MB
     let display_data = if data.len() > max_hex_size {
@@ -154,6 +379,200 @@ fn determine_preview(data: &[u8], name_lower: &str) -> PreviewData {
     }
 }
 
+/// Compare an old P4K archive or manifest with a new archive in a background thread
+pub fn compare_p4k(old: DiffSource, new: std::path::PathBuf, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let result = compare(old, &new).map(Arc::new).map_err(|e| format!("{:#}", e));
+        sender.send(WorkerMessage::P4kDiffReady(result)).ok();
+    });
+}
+
+fn compare(old: DiffSource, new: &Path) -> anyhow::Result<P4kDiffResult> {
+    use anyhow::Context;
+
+    let new_archive = P4kArchive::open(new)
+        .with_context(|| format!("Failed to open {}", new.display()))?;
+    let (old_entries, old_archive) = match old {
+        DiffSource::P4k(path) => {
+            let archive = P4kArchive::open(&path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            let entries: Vec<_> = archive.iter().map(|e| EntrySummary::from(&e)).collect();
+            (entries, Some(Arc::new(archive)))
+        }
+        DiffSource::Manifest(path) => (read_manifest(&path)?, None),
+    };
+    let diff = P4kDiff::compare_entries(
+        old_entries,
+        new_archive.iter().map(|e| EntrySummary::from(&e)),
+    );
+
+    Ok(P4kDiffResult {
+        diff,
+        old_archive,
+        new_archive: Arc::new(new_archive),
+    })
+}
+
+/// Entries of a JSON manifest written by `svarog p4k-manifest`
+fn read_manifest(path: &Path) -> anyhow::Result<Vec<EntrySummary>> {
+    use anyhow::Context;
+
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let manifest: serde_json::Value = serde_json::from_slice(&data)
+        .with_context(|| format!("{} is not a JSON manifest", path.display()))?;
+    let files = manifest["files"]
+        .as_array()
+        .with_context(|| format!("{} has no file list", path.display()))?;
+    files
+        .iter()
+        .map(|file| {
+            let name = file["name"].as_str()?;
+            let size = file["size"].as_u64()?;
+            let crc32 = u32::from_str_radix(file["crc32"].as_str()?, 16).ok()?;
+            Some(EntrySummary::new(name, size, crc32))
+        })
+        .collect::<Option<Vec<_>>>()
+        .with_context(|| format!("{} has malformed entries", path.display()))
+}
+
+/// Compare the contents of a modified entry in a background thread
+pub fn load_content_diff(result: Arc<P4kDiffResult>, change_index: usize, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let change = &result.diff.changes()[change_index];
+        let content = match (&result.old_archive, &change.old, &change.new) {
+            (None, _, _) => ContentDiff::Unavailable(
+                "The old side is a manifest, which holds no file contents".to_string(),
+            ),
+            (Some(old_archive), Some(old), Some(new)) => {
+                let read = |archive: &P4kArchive, name: &str| {
+                    archive
+                        .find(name)
+                        .ok_or_else(|| format!("{} not found", name))
+                        .and_then(|entry| archive.read(&entry).map_err(|e| e.to_string()))
+                };
+                match (read(old_archive, &old.name), read(&result.new_archive, &new.name)) {
+                    (Ok(old), Ok(new)) => content_diff(&old, &new),
+                    (Err(e), _) | (_, Err(e)) => ContentDiff::Unavailable(format!("Failed to read: {}", e)),
+                }
+            }
+            _ => ContentDiff::Unavailable("Only modified entries are compared".to_string()),
+        };
+        sender
+            .send(WorkerMessage::ContentDiffReady {
+                change: change_index,
+                content,
+            })
+            .ok();
+    });
+}
+
+fn content_diff(old: &[u8], new: &[u8]) -> ContentDiff {
+    if CryXml::is_cryxml(old) || CryXml::is_cryxml(new) {
+        return match CryXmlDiff::compare_bytes(old, new) {
+            Ok(diff) => ContentDiff::CryXml(diff.changes),
+            Err(e) => ContentDiff::Unavailable(format!("Failed to parse CryXml: {}", e)),
+        };
+    }
+    match (std::str::from_utf8(old), std::str::from_utf8(new)) {
+        (Ok(old), Ok(new)) if !old.contains('\0') && !new.contains('\0') => {
+            ContentDiff::Text(line_diff(old, new))
+        }
+        _ => ContentDiff::Unavailable("Binary files differ".to_string()),
+    }
+}
+
+/// Unchanged lines shown around each change of a text diff
+const DIFF_CONTEXT: usize = 3;
+
+/// Largest product of differing line counts diffed line by line; beyond it
+/// the differing block is shown as replaced as a whole
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line diff of two texts, with long unchanged runs collapsed
+fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Op {
+        Same,
+        Added,
+        Removed,
+    }
+
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|l| (Op::Same, *l)).collect();
+    if a.len() * b.len() > MAX_DIFF_CELLS {
+        ops.extend(a.iter().map(|l| (Op::Removed, *l)));
+        ops.extend(b.iter().map(|l| (Op::Added, *l)));
+    } else {
+        // Longest common subsequence lengths of the suffixes a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push((Op::Same, a[i]));
+                i += 1;
+                j += 1;
+            } else if i < a.len() && (j == b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+                // Removals first, so replaced lines read old then new
+                ops.push((Op::Removed, a[i]));
+                i += 1;
+            } else {
+                ops.push((Op::Added, b[j]));
+                j += 1;
+            }
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (Op::Same, *l)));
+
+    // Keep only the context around changes of unchanged runs
+    let mut lines = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        let start = i;
+        while i < ops.len() && ops[i].0 == Op::Same {
+            i += 1;
+        }
+        if i > start {
+            let run = &ops[start..i];
+            let head = if start == 0 { 0 } else { DIFF_CONTEXT.min(run.len()) };
+            let tail = if i == ops.len() { 0 } else { DIFF_CONTEXT.min(run.len() - head) };
+            let hidden = run.len() - head - tail;
+            lines.extend(run[..head].iter().map(|(_, l)| DiffLine::Same(l.to_string())));
+            if hidden > 0 {
+                lines.push(DiffLine::Skipped(hidden));
+            }
+            lines.extend(run[run.len() - tail..].iter().map(|(_, l)| DiffLine::Same(l.to_string())));
+            continue;
+        }
+        lines.push(match ops[i] {
+            (Op::Added, l) => DiffLine::Added(l.to_string()),
+            (_, l) => DiffLine::Removed(l.to_string()),
+        });
+        i += 1;
+    }
+    lines
+}
+
 /// Build reference index in a background thread
 pub fn build_reference_index(db: Arc<DataCoreDatabase>, sender: Sender<WorkerMessage>) {
     let sender2 = sender.clone();
@@ -213,51 +632,51 @@ pub fn build_reference_index(db: Arc<DataCoreDatabase>, sender: Sender<WorkerMes
                                 .push((source_idx, prop.name.to_string(), ReferenceType::WeakPointer));
                         }
                     }
-                    Value::Array(array_ref) => {
-                        if array_ref.count > 0 && array_ref.count < 1_000_000 {
-                            match array_ref.element_type {
-                                ArrayElementType::Reference => {
-                                    for i in 0..array_ref.count.min(100) {
-                                        let idx = array_ref.first_index as usize + i as usize;
-                                        if let Some(ref_val) = db.reference_value(idx) {
-                                            let guid_str = format!("{}", ref_val.record_id);
-                                            if let Some(&target_idx) = guid_to_index.get(&guid_str) {
-                                                incoming
-                                                    .entry(target_idx)
-                                                    .or_default()
-                                                    .push((source_idx, format!("{}[{}]", prop.name, i), ReferenceType::Reference));
-                                            }
+                    Value::Array(array_ref)
+                        if array_ref.count > 0 && array_ref.count < 1_000_000 =>
+                    {
+                        match array_ref.element_type {
+                            ArrayElementType::Reference => {
+                                for i in 0..array_ref.count.min(100) {
+                                    let idx = array_ref.first_index as usize + i as usize;
+                                    if let Some(ref_val) = db.reference_value(idx) {
+                                        let guid_str = format!("{}", ref_val.record_id);
+                                        if let Some(&target_idx) = guid_to_index.get(&guid_str) {
+                                            incoming
+                                                .entry(target_idx)
+                                                .or_default()
+                                                .push((source_idx, format!("{}[{}]", prop.name, i), ReferenceType::Reference));
                                         }
                                     }
                                 }
-                                ArrayElementType::StrongPointer | ArrayElementType::WeakPointer => {
-                                    let ref_type = if array_ref.element_type == ArrayElementType::StrongPointer {
-                                        ReferenceType::StrongPointer
-                                    } else {
-                                        ReferenceType::WeakPointer
+                            }
+                            ArrayElementType::StrongPointer | ArrayElementType::WeakPointer => {
+                                let ref_type = if array_ref.element_type == ArrayElementType::StrongPointer {
+                                    ReferenceType::StrongPointer
+                                } else {
+                                    ReferenceType::WeakPointer
+                                };
+
+                                for i in 0..array_ref.count.min(100) {
+                                    let idx = array_ref.first_index as usize + i as usize;
+                                    let ptr = match array_ref.element_type {
+                                        ArrayElementType::StrongPointer => db.strong_value(idx),
+                                        ArrayElementType::WeakPointer => db.weak_value(idx),
+                                        _ => None,
                                     };
 
-                                    for i in 0..array_ref.count.min(100) {
-                                        let idx = array_ref.first_index as usize + i as usize;
-                                        let ptr = match array_ref.element_type {
-                                            ArrayElementType::StrongPointer => db.strong_value(idx),
-                                            ArrayElementType::WeakPointer => db.weak_value(idx),
-                                            _ => None,
-                                        };
-
-                                        if let Some(ptr) = ptr {
-                                            let key = (ptr.struct_index as u32, ptr.instance_index as u32);
-                                            if let Some(&target_idx) = instance_to_index.get(&key) {
-                                                incoming
-                                                    .entry(target_idx)
-                                                    .or_default()
-                                                    .push((source_idx, format!("{}[{}]", prop.name, i), ref_type));
-                                            }
+                                    if let Some(ptr) = ptr {
+                                        let key = (ptr.struct_index as u32, ptr.instance_index as u32);
+                                        if let Some(&target_idx) = instance_to_index.get(&key) {
+                                            incoming
+                                                .entry(target_idx)
+                                                .or_default()
+                                                .push((source_idx, format!("{}[{}]", prop.name, i), ref_type));
                                         }
                                     }
                                 }
-                                _ => {}
                             }
+                            _ => {}
                         }
                     }
                     _ => {}
@@ -375,3 +794,178 @@ fn build_struct_reference_index(db: Arc<DataCoreDatabase>, sender: Sender<Worker
         enum_incoming,
     }))).ok();
 }
+
+/// Results listed per group of the global search
+const GLOBAL_SEARCH_LIMIT: usize = 200;
+
+/// Build the DataCore text index of the global search in a background thread
+pub fn build_record_search_index(db: Arc<DataCoreDatabase>, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let start = std::time::Instant::now();
+        let options = SearchOptions {
+            properties: true,
+            ..SearchOptions::default()
+        };
+        let index = SearchIndex::build(&db, &options);
+
+        let main_index: std::collections::HashMap<_, _> = db
+            .main_records()
+            .enumerate()
+            .map(|(idx, record)| (record.id, idx))
+            .collect();
+        let main_records = db
+            .records()
+            .iter()
+            .filter_map(|record| {
+                let main = db.main_record_of(record)?;
+                Some((record.id, *main_index.get(&main.id)?))
+            })
+            .collect();
+
+        tracing::info!("Indexed {} records for search in {:.1?}", index.len(), start.elapsed());
+        sender
+            .send(WorkerMessage::RecordSearchIndexReady(Arc::new(RecordSearchIndex {
+                index,
+                main_records,
+            })))
+            .ok();
+    });
+}
+
+/// Build the lowercase entry names of the global search in a background thread
+pub fn build_file_search_index(archive: Arc<P4kArchive>, sender: Sender<WorkerMessage>) {
+    std::thread::spawn(move || {
+        let names = archive
+            .iter()
+            .map(|entry| entry.name.replace('\\', "/").to_lowercase())
+            .collect();
+        sender.send(WorkerMessage::FileSearchIndexReady(Arc::new(names))).ok();
+    });
+}
+
+/// Search records, structs, enums and P4K entries in a background thread
+pub fn global_search(
+    query: String,
+    db: Option<Arc<DataCoreDatabase>>,
+    records: Option<Arc<RecordSearchIndex>>,
+    archive: Option<Arc<P4kArchive>>,
+    files: Option<Arc<Vec<String>>>,
+    sender: Sender<WorkerMessage>,
+) {
+    std::thread::spawn(move || {
+        let needle = query.to_lowercase();
+        let mut results = GlobalSearchResults::default();
+
+        if let Some(records) = &records {
+            // One row per record, for the first field that matched
+            let mut seen = std::collections::HashSet::new();
+            for hit in records.index.search(&query, GLOBAL_SEARCH_LIMIT * 4) {
+                let Some(&record_index) = records.main_records.get(&hit.record.id) else {
+                    continue;
+                };
+                if !seen.insert(record_index) {
+                    continue;
+                }
+                let field = match hit.field {
+                    SearchField::Name => "name".to_string(),
+                    SearchField::Type => "type".to_string(),
+                    SearchField::File => "file".to_string(),
+                    SearchField::Property(path) => path,
+                };
+                results.records.push(RecordSearchResult {
+                    record_index,
+                    name: hit.record.name,
+                    field,
+                    value: hit.value,
+                });
+                if results.records.len() == GLOBAL_SEARCH_LIMIT {
+                    break;
+                }
+            }
+        }
+
+        if let Some(db) = &db {
+            results.structs = (0..db.struct_definitions().len())
+                .filter_map(|idx| Some((idx, db.struct_name(idx)?.to_string())))
+                .filter(|(_, name)| name.to_lowercase().contains(&needle))
+                .take(GLOBAL_SEARCH_LIMIT)
+                .collect();
+            results.enums = (0..db.enum_definitions().len())
+                .filter_map(|idx| Some((idx, db.enum_name(idx)?.to_string())))
+                .filter(|(_, name)| name.to_lowercase().contains(&needle))
+                .take(GLOBAL_SEARCH_LIMIT)
+                .collect();
+        }
+
+        if let (Some(archive), Some(files)) = (&archive, &files) {
+            results.files = files
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| name.contains(&needle))
+                .filter_map(|(idx, _)| Some((idx, archive.get(idx)?.name.replace('\\', "/"))))
+                .take(GLOBAL_SEARCH_LIMIT)
+                .collect();
+        }
+
+        results.query = query;
+        sender.send(WorkerMessage::GlobalSearchReady(results)).ok();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svarog::dds::{encode_dds, split_dds, DdsFormat, EncodeOptions};
+
+    /// 8x8 BC1 texture with its mipmaps, each pixel `color`
+    fn texture(color: [u8; 4]) -> Vec<u8> {
+        let image = image::RgbaImage::from_pixel(8, 8, image::Rgba(color));
+        encode_dds(&image, &EncodeOptions::new(DdsFormat::Bc1)).unwrap()
+    }
+
+    #[test]
+    fn test_texture_preview() {
+        let (base, parts) = split_dds(&texture([255, 0, 0, 255]), 2).unwrap();
+
+        let preview = file_preview("Data/hull.DDS", &base, None, || Ok(parts));
+        let PreviewData::Texture(texture) = preview else {
+            panic!("expected a texture, got {:?}", preview);
+        };
+        assert_eq!((texture.width, texture.height), (8, 8));
+        assert_eq!(texture.mip_count, 4);
+        assert_eq!(texture.merged_parts, 2);
+        assert_eq!(texture.pixels[..4], [255, 0, 0, 255]);
+
+        // Undecodable data and failing part lookups fall back to hex
+        let preview = file_preview("hull.dds", b"garbage", None, || Ok(Vec::new()));
+        assert!(matches!(preview, PreviewData::Hex { data, .. } if data == b"garbage"));
+        let preview = file_preview("hull.dds", &base, None, || anyhow::bail!("unreadable"));
+        assert!(matches!(preview, PreviewData::Hex { .. }));
+    }
+
+    #[test]
+    fn test_load_locale() {
+        let p4k = crate::tests::write_p4k(
+            "locale.p4k",
+            &[(LOCALE_PATH, b"item_NameGladius=Gladius\r\n")],
+        );
+        let archive = Arc::new(P4kArchive::open(&p4k).unwrap());
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        load_locale_from_p4k(archive, sender.clone());
+        let Ok(WorkerMessage::LocaleLoaded(Ok(locale))) = receiver.recv() else {
+            panic!("expected a locale");
+        };
+        assert_eq!(locale.get("@item_NameGladius"), Some("Gladius"));
+
+        load_locale(p4k.with_extension("ini"), sender);
+        let Ok(WorkerMessage::LocaleLoaded(Err(_))) = receiver.recv() else {
+            panic!("expected an error");
+        };
+
+        // Archives without a locale send nothing
+        let p4k = crate::tests::write_p4k("no-locale.p4k", &[("Data/a.txt", b"a")]);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        load_locale_from_p4k(Arc::new(P4kArchive::open(&p4k).unwrap()), sender);
+        assert!(receiver.recv().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+/* tslint:disable */
+/* eslint-disable */
+
+/* auto-generated by NAPI-RS */
+
+/** Metadata of one archive entry. */
+export interface ArchiveEntry {
+  /** Position in the archive, accepted by `readIndex`. */
+  index: number
+  /** Path inside the archive, with `\` separators. */
+  name: string
+  compressedSize: number
+  uncompressedSize: number
+  crc32: number
+  encrypted: boolean
+}
+/** Identity of one record. */
+export interface RecordInfo {
+  guid: string
+  name?: string
+  /** Struct type, e.g. `AmmoParams`. */
+  typeName?: string
+  /** File the record belongs to. */
+  fileName?: string
+  /** Main record of its file rather than a sub-record. */
+  isMain: boolean
+}
+/** Filters of `DataCore.query`; every given filter must match. */
+export interface RecordQuery {
+  /** Struct type, ignoring case. */
+  typeName?: string
+  /** Text the record name contains, ignoring case. */
+  name?: string
+  /** Prefix of the record's file path, ignoring case and separator style. */
+  fileName?: string
+  /** Only main records. */
+  mainOnly?: boolean
+  /** Return at most this many records. */
+  limit?: number
+}
+/** Svarog version. */
+export function version(): string
+/** Whether `data` starts with the CryXmlB magic. */
+export function isCryxml(data: Buffer): boolean
+/** Convert CryXmlB to XML text. */
+export function cryxmlToXml(data: Buffer): string
+/** Convert XML text to CryXmlB. */
+export function xmlToCryxml(xml: string): Buffer
+/** An open P4K archive. */
+export class P4kArchive {
+  /** Open an archive. */
+  static open(path: string): Promise<P4kArchive>
+  /** File name of the archive. */
+  get name(): string
+  get entryCount(): number
+  /**
+   * Entries whose path matches `pattern`, where `*` matches any run of
+   * characters, ignoring case and separator style. All entries without
+   * a pattern.
+   */
+  entries(pattern?: string | undefined | null): Array<ArchiveEntry>
+  /** Find an entry by path, ignoring case and accepting `/` or `\`. */
+  find(name: string): ArchiveEntry | null
+  /** Read, decrypt and decompress an entry by path. */
+  read(name: string): Promise<Buffer>
+  /** Read an entry by its position in the archive. */
+  readIndex(index: number): Promise<Buffer>
+  /** Read an entry by path as text, decoding CryXmlB to XML. */
+  readText(name: string): Promise<string>
+}
+/** A parsed DataCore database. */
+export class DataCore {
+  /** Open a `.dcb` file. */
+  static open(path: string): Promise<DataCore>
+  /** Parse a database from its bytes, copying them. */
+  static parse(data: Buffer): Promise<DataCore>
+  /** Read and parse the database of a P4K archive (`Data\Game.dcb`). */
+  static fromArchive(archive: P4kArchive): Promise<DataCore>
+  get recordCount(): number
+  /** Look a record up by GUID, then by name. */
+  record(key: string): RecordInfo | null
+  /** Records matching every filter of `query`, in database order. */
+  query(query?: RecordQuery | undefined | null): Array<RecordInfo>
+  /**
+   * A record's properties as a plain object, by GUID or name.
+   *
+   * Record references become GUID strings and weak pointers
+   * `{ struct_index, instance_index }` objects.
+   */
+  recordJson(key: string): Promise<Record<string, any>>
+  /** A record as XML, with all references resolved, by GUID or name. */
+  recordXml(key: string): Promise<string>
+}
@@ -0,0 +1,22 @@
+// Loads the addon that `npm run build` wrote for this platform, named
+// svarog.<platform>-<arch>[-<abi>].node as napi-rs names it.
+const { existsSync } = require('fs')
+const { join } = require('path')
+
+function abi() {
+  switch (process.platform) {
+    case 'win32':
+      return '-msvc'
+    case 'linux':
+      // Only glibc reports its version
+      return process.report.getReport().header.glibcVersionRuntime ? '-gnu' : '-musl'
+    default:
+      return ''
+  }
+}
+
+const file = join(__dirname, `svarog.${process.platform}-${process.arch}${abi()}.node`)
+if (!existsSync(file)) {
+  throw new Error(`svarog-node is not built for ${process.platform}-${process.arch}, run npm run build`)
+}
+module.exports = require(file)
@@ -0,0 +1,275 @@
+//! P4K archives.
+
+use std::sync::Arc;
+
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Task};
+use napi_derive::napi;
+use svarog::cryxml::CryXml;
+use svarog::p4k::{P4kArchive, P4kEntryRef};
+
+use crate::error;
+
+/// An open P4K archive.
+#[napi(js_name = "P4kArchive")]
+pub struct Archive {
+    pub(crate) archive: Arc<P4kArchive>,
+}
+
+/// Metadata of one archive entry.
+#[napi(object)]
+pub struct ArchiveEntry {
+    /// Position in the archive, accepted by `readIndex`.
+    pub index: u32,
+    /// Path inside the archive, with `\` separators.
+    pub name: String,
+    pub compressed_size: f64,
+    pub uncompressed_size: f64,
+    pub crc32: u32,
+    pub encrypted: bool,
+}
+
+impl ArchiveEntry {
+    fn new(index: usize, entry: &P4kEntryRef<'_>) -> Self {
+        Self {
+            index: index as u32,
+            name: entry.name.to_string(),
+            compressed_size: entry.compressed_size as f64,
+            uncompressed_size: entry.uncompressed_size as f64,
+            crc32: entry.crc32,
+            encrypted: entry.is_encrypted,
+        }
+    }
+}
+
+#[napi]
+impl Archive {
+    /// Open an archive.
+    #[napi]
+    pub fn open(path: String) -> AsyncTask<OpenArchive> {
+        AsyncTask::new(OpenArchive { path })
+    }
+
+    /// File name of the archive.
+    #[napi(getter)]
+    pub fn name(&self) -> String {
+        self.archive.name().to_string()
+    }
+
+    #[napi(getter)]
+    pub fn entry_count(&self) -> u32 {
+        self.archive.entry_count() as u32
+    }
+
+    /// Entries whose path matches `pattern`, where `*` matches any run of
+    /// characters, ignoring case and separator style. All entries without
+    /// a pattern.
+    #[napi]
+    pub fn entries(&self, pattern: Option<String>) -> Vec<ArchiveEntry> {
+        let pattern = pattern.map(|p| p.replace('/', "\\").to_lowercase());
+        self.archive
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                pattern
+                    .as_deref()
+                    .map_or(true, |p| wildcard_match(p, &entry.name.to_lowercase()))
+            })
+            .map(|(index, entry)| ArchiveEntry::new(index, &entry))
+            .collect()
+    }
+
+    /// Find an entry by path, ignoring case and accepting `/` or `\`.
+    #[napi]
+    pub fn find(&self, name: String) -> Option<ArchiveEntry> {
+        let index = self.archive.index_of(&name)?;
+        self.archive
+            .get(index)
+            .map(|entry| ArchiveEntry::new(index, &entry))
+    }
+
+    /// Read, decrypt and decompress an entry by path.
+    #[napi]
+    pub fn read(&self, name: String) -> napi::Result<AsyncTask<ReadEntry>> {
+        let index = self
+            .archive
+            .index_of(&name)
+            .ok_or_else(|| error(format!("No entry {}", name)))?;
+        Ok(self.read_index_task(index))
+    }
+
+    /// Read an entry by its position in the archive.
+    #[napi]
+    pub fn read_index(&self, index: u32) -> napi::Result<AsyncTask<ReadEntry>> {
+        let index = index as usize;
+        if index >= self.archive.entry_count() {
+            return Err(error(format!("No entry {}", index)));
+        }
+        Ok(self.read_index_task(index))
+    }
+
+    /// Read an entry by path as text, decoding CryXmlB to XML.
+    #[napi]
+    pub fn read_text(&self, name: String) -> napi::Result<AsyncTask<ReadText>> {
+        let index = self
+            .archive
+            .index_of(&name)
+            .ok_or_else(|| error(format!("No entry {}", name)))?;
+        Ok(AsyncTask::new(ReadText {
+            archive: self.archive.clone(),
+            index,
+        }))
+    }
+}
+
+impl Archive {
+    fn read_index_task(&self, index: usize) -> AsyncTask<ReadEntry> {
+        AsyncTask::new(ReadEntry {
+            archive: self.archive.clone(),
+            index,
+        })
+    }
+}
+
+/// `*` wildcard match of lowercase strings, anchored at both ends.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = parts.next().and_then(|first| name.strip_prefix(first)) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(found) => rest = &rest[found + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+pub struct OpenArchive {
+    path: String,
+}
+
+impl Task for OpenArchive {
+    type Output = P4kArchive;
+    type JsValue = Archive;
+
+    fn compute(&mut self) -> napi::Result<P4kArchive> {
+        P4kArchive::open(&self.path).map_err(error)
+    }
+
+    fn resolve(&mut self, _env: Env, archive: P4kArchive) -> napi::Result<Archive> {
+        Ok(Archive {
+            archive: Arc::new(archive),
+        })
+    }
+}
+
+pub struct ReadEntry {
+    archive: Arc<P4kArchive>,
+    index: usize,
+}
+
+impl Task for ReadEntry {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> napi::Result<Vec<u8>> {
+        self.archive.read_index(self.index).map_err(error)
+    }
+
+    fn resolve(&mut self, _env: Env, data: Vec<u8>) -> napi::Result<Buffer> {
+        Ok(data.into())
+    }
+}
+
+pub struct ReadText {
+    archive: Arc<P4kArchive>,
+    index: usize,
+}
+
+impl Task for ReadText {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<String> {
+        let data = self.archive.read_index(self.index).map_err(error)?;
+        if CryXml::is_cryxml(&data) {
+            let cryxml = CryXml::parse(&data).map_err(error)?;
+            cryxml.to_xml_string().map_err(error)
+        } else {
+            String::from_utf8(data).map_err(|_| error("Entry is not UTF-8 text"))
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, text: String) -> napi::Result<String> {
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::write_p4k;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("data\\*.xml", "data\\libs\\ammo.xml"));
+        assert!(wildcard_match("*ammo*", "data\\ammo.xml"));
+        assert!(wildcard_match("data\\ammo.xml", "data\\ammo.xml"));
+        assert!(!wildcard_match("data\\*.xml", "data\\ammo.xml.bak"));
+        assert!(!wildcard_match("*.xml", "ammo.dds"));
+        assert!(!wildcard_match("data", "data\\ammo.xml"));
+    }
+
+    #[test]
+    fn test_archive() {
+        let cryxml = svarog::cryxml::builder::CryXmlBuilder::from_xml(r#"<Ammo Speed="900"/>"#)
+            .unwrap()
+            .build()
+            .unwrap();
+        let path = write_p4k(
+            "archive.p4k",
+            &[("Data\\a.txt", b"hello"), ("Data\\Libs\\ammo.xml", &cryxml)],
+        );
+
+        let mut open = OpenArchive {
+            path: path.to_string_lossy().into_owned(),
+        };
+        let archive = Archive {
+            archive: Arc::new(open.compute().unwrap()),
+        };
+        assert_eq!(archive.entry_count(), 2);
+        assert_eq!(archive.entries(None).len(), 2);
+        let entries = archive.entries(Some("data/libs/*.XML".into()));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Data\\Libs\\ammo.xml");
+        assert_eq!(archive.find("data/a.txt".into()).unwrap().index, 0);
+
+        let mut read = ReadEntry {
+            archive: archive.archive.clone(),
+            index: 0,
+        };
+        assert_eq!(read.compute().unwrap(), b"hello");
+        let mut text = ReadText {
+            archive: archive.archive.clone(),
+            index: 1,
+        };
+        assert!(text.compute().unwrap().contains("<Ammo"));
+
+        assert!(archive.find("Data/missing.txt".into()).is_none());
+        assert!(archive.read("Data/missing.txt".into()).is_err());
+        assert!(archive.read_index(2).is_err());
+        let mut open = OpenArchive {
+            path: path
+                .with_extension("missing")
+                .to_string_lossy()
+                .into_owned(),
+        };
+        assert!(open.compute().is_err());
+    }
+}
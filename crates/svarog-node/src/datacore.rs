@@ -0,0 +1,323 @@
+//! DataCore databases.
+
+use std::sync::Arc;
+
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, JsUnknown, Task};
+use napi_derive::napi;
+use svarog::datacore::{DataCoreDatabase, Record, XmlExporter};
+use svarog::p4k::P4kArchive;
+
+use crate::{error, Archive};
+
+/// A parsed DataCore database.
+#[napi]
+pub struct DataCore {
+    database: Arc<DataCoreDatabase>,
+}
+
+/// Identity of one record.
+#[napi(object)]
+pub struct RecordInfo {
+    pub guid: String,
+    pub name: Option<String>,
+    /// Struct type, e.g. `AmmoParams`.
+    pub type_name: Option<String>,
+    /// File the record belongs to.
+    pub file_name: Option<String>,
+    /// Main record of its file rather than a sub-record.
+    pub is_main: bool,
+}
+
+impl From<Record<'_>> for RecordInfo {
+    fn from(record: Record<'_>) -> Self {
+        Self {
+            guid: record.id().to_string(),
+            name: record.name().map(String::from),
+            type_name: record.type_name().map(String::from),
+            file_name: record.file_name().map(String::from),
+            is_main: record.is_main(),
+        }
+    }
+}
+
+/// Filters of `DataCore.query`; every given filter must match.
+#[napi(object)]
+#[derive(Default)]
+pub struct RecordQuery {
+    /// Struct type, ignoring case.
+    pub type_name: Option<String>,
+    /// Text the record name contains, ignoring case.
+    pub name: Option<String>,
+    /// Prefix of the record's file path, ignoring case and separator style.
+    pub file_name: Option<String>,
+    /// Only main records.
+    pub main_only: Option<bool>,
+    /// Return at most this many records.
+    pub limit: Option<u32>,
+}
+
+#[napi]
+impl DataCore {
+    /// Open a `.dcb` file.
+    #[napi]
+    pub fn open(path: String) -> AsyncTask<LoadDataCore> {
+        AsyncTask::new(LoadDataCore {
+            source: Source::Path(path),
+        })
+    }
+
+    /// Parse a database from its bytes, copying them.
+    #[napi]
+    pub fn parse(data: Buffer) -> AsyncTask<LoadDataCore> {
+        AsyncTask::new(LoadDataCore {
+            source: Source::Bytes(data.to_vec()),
+        })
+    }
+
+    /// Read and parse the database of a P4K archive (`Data\Game.dcb`).
+    #[napi]
+    pub fn from_archive(archive: &Archive) -> AsyncTask<LoadDataCore> {
+        AsyncTask::new(LoadDataCore {
+            source: Source::Archive(archive.archive.clone()),
+        })
+    }
+
+    #[napi(getter)]
+    pub fn record_count(&self) -> u32 {
+        self.database.records().len() as u32
+    }
+
+    /// Look a record up by GUID, then by name.
+    #[napi]
+    pub fn record(&self, key: String) -> Option<RecordInfo> {
+        find_record(&self.database, &key).map(RecordInfo::from)
+    }
+
+    /// Records matching every filter of `query`, in database order.
+    #[napi]
+    pub fn query(&self, query: Option<RecordQuery>) -> Vec<RecordInfo> {
+        let query = query.unwrap_or_default();
+        let name = query.name.map(|n| n.to_lowercase());
+        let file_name = query.file_name.map(|f| normalize_path(&f));
+        let main_only = query.main_only.unwrap_or(false);
+
+        self.database
+            .all_records()
+            .filter(|record| {
+                query.type_name.as_deref().map_or(true, |t| {
+                    record
+                        .type_name()
+                        .is_some_and(|r| r.eq_ignore_ascii_case(t))
+                }) && name.as_deref().map_or(true, |n| {
+                    record.name().is_some_and(|r| r.to_lowercase().contains(n))
+                }) && file_name.as_deref().map_or(true, |f| {
+                    record
+                        .file_name()
+                        .is_some_and(|r| normalize_path(r).starts_with(f))
+                }) && (!main_only || record.is_main())
+            })
+            .take(query.limit.map_or(usize::MAX, |limit| limit as usize))
+            .map(RecordInfo::from)
+            .collect()
+    }
+
+    /// A record's properties as a plain object, by GUID or name.
+    ///
+    /// Record references become GUID strings and weak pointers
+    /// `{ struct_index, instance_index }` objects.
+    #[napi(ts_return_type = "Promise<Record<string, any>>")]
+    pub fn record_json(&self, key: String) -> AsyncTask<RecordJson> {
+        AsyncTask::new(RecordJson {
+            database: self.database.clone(),
+            key,
+        })
+    }
+
+    /// A record as XML, with all references resolved, by GUID or name.
+    #[napi]
+    pub fn record_xml(&self, key: String) -> AsyncTask<RecordXml> {
+        AsyncTask::new(RecordXml {
+            database: self.database.clone(),
+            key,
+        })
+    }
+}
+
+fn find_record<'a>(database: &'a DataCoreDatabase, key: &str) -> Option<Record<'a>> {
+    database
+        .record_by_guid_str(key)
+        .or_else(|| database.record_by_name(key))
+}
+
+/// Lowercase with `/` separators, for prefix matching of file paths.
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+enum Source {
+    Path(String),
+    Bytes(Vec<u8>),
+    Archive(Arc<P4kArchive>),
+}
+
+pub struct LoadDataCore {
+    source: Source,
+}
+
+impl Task for LoadDataCore {
+    type Output = DataCoreDatabase;
+    type JsValue = DataCore;
+
+    fn compute(&mut self) -> napi::Result<DataCoreDatabase> {
+        match &self.source {
+            Source::Path(path) => DataCoreDatabase::open(path).map_err(error),
+            Source::Bytes(data) => DataCoreDatabase::parse(data).map_err(error),
+            Source::Archive(archive) => {
+                let entry = archive
+                    .iter()
+                    .find(|e| e.name.to_lowercase().ends_with(".dcb"))
+                    .ok_or_else(|| error("No DataCore database in this archive"))?;
+                let data = archive.read(&entry).map_err(error)?;
+                DataCoreDatabase::parse(&data).map_err(error)
+            }
+        }
+    }
+
+    fn resolve(&mut self, _env: Env, database: DataCoreDatabase) -> napi::Result<DataCore> {
+        Ok(DataCore {
+            database: Arc::new(database),
+        })
+    }
+}
+
+pub struct RecordJson {
+    database: Arc<DataCoreDatabase>,
+    key: String,
+}
+
+impl Task for RecordJson {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<serde_json::Value> {
+        let record = find_record(&self.database, &self.key)
+            .ok_or_else(|| error(format!("No record {}", self.key)))?;
+        record.deserialize().map_err(error)
+    }
+
+    fn resolve(&mut self, env: Env, value: serde_json::Value) -> napi::Result<JsUnknown> {
+        env.to_js_value(&value)
+    }
+}
+
+pub struct RecordXml {
+    database: Arc<DataCoreDatabase>,
+    key: String,
+}
+
+impl Task for RecordXml {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<String> {
+        let record = find_record(&self.database, &self.key)
+            .ok_or_else(|| error(format!("No record {}", self.key)))?;
+        XmlExporter::new(&self.database)
+            .export_record(record.raw())
+            .map_err(error)
+    }
+
+    fn resolve(&mut self, _env: Env, xml: String) -> napi::Result<String> {
+        Ok(xml)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svarog::datacore::{DataCoreBuilder, DataType};
+
+    fn database() -> DataCore {
+        let mut builder = DataCoreBuilder::new();
+        let ammo = builder.add_struct("AmmoParams", None);
+        builder.add_property(ammo, "speed", DataType::Single);
+        let bullet = builder.add_record("Bullet", ammo, "libs/ammo/bullet.xml");
+        builder.set_float(bullet, "speed", 900.0);
+        builder.add_record("Rocket", ammo, "libs/missiles/rocket.xml");
+
+        let mut load = LoadDataCore {
+            source: Source::Bytes(builder.build().unwrap()),
+        };
+        DataCore {
+            database: Arc::new(load.compute().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_records() {
+        let db = database();
+        assert_eq!(db.record_count(), 2);
+
+        let bullet = db.record("Bullet".into()).unwrap();
+        assert_eq!(bullet.type_name.as_deref(), Some("AmmoParams"));
+        let by_guid = db.record(bullet.guid.clone()).unwrap();
+        assert_eq!(by_guid.name, bullet.name);
+        assert!(db.record("Missing".into()).is_none());
+
+        let query = |query: RecordQuery| -> Vec<Option<String>> {
+            db.query(Some(query)).into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(db.query(None).len(), 2);
+        let names = query(RecordQuery {
+            file_name: Some("LIBS\\Missiles".into()),
+            ..RecordQuery::default()
+        });
+        assert_eq!(names, [Some("Rocket".to_string())]);
+        let names = query(RecordQuery {
+            type_name: Some("ammoparams".into()),
+            limit: Some(1),
+            ..RecordQuery::default()
+        });
+        assert_eq!(names.len(), 1);
+        assert!(query(RecordQuery {
+            name: Some("laser".into()),
+            ..RecordQuery::default()
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn test_record_tasks() {
+        let db = database();
+        let mut json = RecordJson {
+            database: db.database.clone(),
+            key: "Bullet".into(),
+        };
+        assert_eq!(json.compute().unwrap()["speed"], 900.0);
+        let mut xml = RecordXml {
+            database: db.database.clone(),
+            key: "Bullet".into(),
+        };
+        assert!(xml.compute().unwrap().contains("900"));
+
+        let mut json = RecordJson {
+            database: db.database.clone(),
+            key: "Missing".into(),
+        };
+        assert_eq!(json.compute().unwrap_err().reason, "No record Missing");
+        let mut load = LoadDataCore {
+            source: Source::Bytes(b"garbage".to_vec()),
+        };
+        assert!(load.compute().is_err());
+
+        let path = crate::tests::write_p4k("no-dcb.p4k", &[("Data\\a.txt", b"a")]);
+        let mut load = LoadDataCore {
+            source: Source::Archive(Arc::new(P4kArchive::open(path).unwrap())),
+        };
+        assert_eq!(
+            load.compute().unwrap_err().reason,
+            "No DataCore database in this archive"
+        );
+    }
+}
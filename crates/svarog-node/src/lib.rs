@@ -0,0 +1,102 @@
+//! Node.js bindings for Svarog.
+//!
+//! Exposes P4K archives, DataCore queries and CryXmlB conversion to
+//! JavaScript and TypeScript through [napi-rs](https://napi.rs). Reading
+//! entries, parsing databases and exporting records run on the libuv thread
+//! pool and return promises; metadata lookups are synchronous. The typings
+//! are in `index.d.ts`, regenerated by `npm run build`.
+//!
+//! # Example
+//!
+//! ```js
+//! const { P4kArchive, DataCore } = require('svarog-node')
+//!
+//! const archive = await P4kArchive.open('Data.p4k')
+//! const dcb = await DataCore.fromArchive(archive)
+//! for (const record of dcb.query({ typeName: 'AmmoParams', limit: 10 })) {
+//!   const ammo = await dcb.recordJson(record.guid)
+//!   console.log(record.name, ammo.speed)
+//! }
+//! ```
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+use svarog::cryxml::{CryXml, CryXmlDocument};
+
+mod archive;
+mod datacore;
+
+pub use archive::{Archive, ArchiveEntry};
+pub use datacore::{DataCore, RecordInfo, RecordQuery};
+
+/// Svarog version.
+#[napi]
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Whether `data` starts with the CryXmlB magic.
+#[napi]
+pub fn is_cryxml(data: Buffer) -> bool {
+    CryXml::is_cryxml(&data)
+}
+
+/// Convert CryXmlB to XML text.
+#[napi]
+pub fn cryxml_to_xml(data: Buffer) -> napi::Result<String> {
+    let cryxml = CryXml::parse(&data).map_err(error)?;
+    cryxml.to_xml_string().map_err(error)
+}
+
+/// Convert XML text to CryXmlB.
+#[napi]
+pub fn xml_to_cryxml(xml: String) -> napi::Result<Buffer> {
+    let document = CryXmlDocument::from_xml(&xml).map_err(error)?;
+    document.to_cryxml().map(Buffer::from).map_err(error)
+}
+
+/// Report an error to JavaScript as an `Error` with its message.
+pub(crate) fn error(err: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Write an archive of `files`, stored uncompressed, to the temporary
+    /// directory.
+    pub(crate) fn write_p4k(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("svarog-node-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_cryxml_roundtrip() {
+        let cryxml = xml_to_cryxml(r#"<Ammo Speed="900"/>"#.into()).unwrap();
+        assert!(is_cryxml(cryxml.to_vec().into()));
+
+        let xml = cryxml_to_xml(cryxml).unwrap();
+        assert!(xml.contains(r#"Speed="900""#));
+
+        assert!(!is_cryxml(b"<Ammo/>".to_vec().into()));
+        assert!(cryxml_to_xml(b"<Ammo/>".to_vec().into()).is_err());
+        assert!(xml_to_cryxml("<Ammo".into()).is_err());
+    }
+}
@@ -5,16 +5,23 @@
 //! - Zero-copy entry storage with arena-allocated names
 //! - Parallel central directory parsing
 //! - Parallel extraction with worker pool
-//! - Thread-local decompressors to avoid allocation
+//! - Thread-local Zstandard contexts, decoding in one pass into the entry's size
 
+use std::borrow::Cow;
 use std::fs::File;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use memmap2::Mmap;
-use svarog_common::BinaryReader;
+use svarog_common::arena::{ArenaOptions, ArenaStats, ArenaStr, StringArena};
+use svarog_common::{BinaryReader, ResultExt};
 
 use crate::crypto;
-use crate::decompress;
+use crate::decompress::{self, ZstdFrameInfo, ZstdOptions};
+use crate::index::{DirTree, NameIndex, P4kDir};
+use crate::limits::DecompressionLimits;
+use crate::read_ahead::{RawEntry, ReadAhead, ReadAheadOptions};
 use crate::simd;
 use crate::zip::central_dir::extra_field;
 use crate::zip::{
@@ -23,6 +30,10 @@ use crate::zip::{
 };
 use crate::{Error, Result};
 
+/// Room left for the extra fields of a local header, which the central
+/// directory does not give, when reading an entry in one request.
+const LOCAL_EXTRA_SLACK: usize = 4096;
+
 /// A P4K entry with zero-copy name storage.
 ///
 /// The name is stored as a reference into an arena allocator,
@@ -53,15 +64,27 @@ pub struct P4kArchive {
     mmap: Mmap,
     /// Archive file name
     name: String,
+    /// Path the archive was opened from
+    path: PathBuf,
     /// Entry metadata
     entries: Vec<P4kEntryCompact>,
+    /// Entry names
+    names: StringArena,
+    /// Zstandard decoding settings
+    zstd: ZstdOptions,
+    /// Limits checked before decoding an entry
+    limits: DecompressionLimits,
+    /// Entry indices by name, built on first lookup
+    name_index: OnceLock<NameIndex>,
+    /// Directory tree, built on first listing
+    dir_tree: OnceLock<DirTree>,
 }
 
 /// Compact entry metadata (names stored separately)
 #[derive(Debug, Clone)]
 struct P4kEntryCompact {
-    /// File name (owned string)
-    name: String,
+    /// File name (handle into the name arena)
+    name: ArenaStr,
     /// Compressed size
     compressed_size: u64,
     /// Uncompressed size
@@ -79,6 +102,21 @@ struct P4kEntryCompact {
 impl P4kArchive {
     /// Open a P4K archive with maximum performance optimizations.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_arena(path, ArenaOptions::default())
+    }
+
+    /// Open a P4K archive, storing entry names with the given arena options.
+    ///
+    /// A budget caps the memory taken by names on machines with little RAM;
+    /// with [`Overflow::Fail`](svarog_common::arena::Overflow::Fail), opening
+    /// fails once it is used up.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "p4k_open", skip_all, fields(path = %path.as_ref().display()))
+    )]
+    pub fn open_with_arena<P: AsRef<Path>>(path: P, options: ArenaOptions) -> Result<Self> {
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
         let path = path.as_ref();
         let file = File::open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
@@ -89,21 +127,76 @@ impl P4kArchive {
             .unwrap_or("unknown")
             .to_string();
 
-        let entries = Self::parse_entries_optimized(&mmap)?;
+        let mut names = StringArena::with_options(options);
+        let entries = Self::parse_entries_optimized(&mmap, &mut names)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            entries = entries.len(),
+            size = mmap.len(),
+            name_bytes = names.stats().total_bytes(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "opened P4K archive"
+        );
 
         Ok(Self {
             mmap,
             name,
+            path: path.to_path_buf(),
             entries,
+            names,
+            zstd: ZstdOptions::default(),
+            limits: DecompressionLimits::default(),
+            name_index: OnceLock::new(),
+            dir_tree: OnceLock::new(),
         })
     }
 
+    /// Decode Zstandard entries with the given settings, e.g. a window
+    /// limit or a dictionary.
+    pub fn with_zstd_options(mut self, options: ZstdOptions) -> Self {
+        self.zstd = options;
+        self
+    }
+
+    /// Settings used to decode Zstandard entries.
+    #[inline]
+    pub fn zstd_options(&self) -> &ZstdOptions {
+        &self.zstd
+    }
+
+    /// Check entries against the given limits before decoding them; reads
+    /// of entries over a limit fail with [`Error::SizeLimitExceeded`] or
+    /// [`Error::RatioLimitExceeded`].
+    pub fn with_limits(mut self, limits: DecompressionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Limits checked before decoding an entry.
+    #[inline]
+    pub fn limits(&self) -> &DecompressionLimits {
+        &self.limits
+    }
+
     /// Get the archive name.
     #[inline]
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Path the archive was opened from.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Memory used by entry names.
+    #[inline]
+    pub fn name_arena_stats(&self) -> ArenaStats {
+        self.names.stats()
+    }
+
     /// Get the number of entries.
     #[inline]
     pub fn entry_count(&self) -> usize {
@@ -122,21 +215,46 @@ impl P4kArchive {
         self.entries.get(index).map(|e| self.entry_ref(e))
     }
 
-    /// Find an entry by name (case-insensitive).
+    /// Find an entry by name (case-insensitive, `/` or `\\` separated).
     pub fn find(&self, name: &str) -> Option<P4kEntryRef<'_>> {
-        let normalized = name.replace('/', "\\");
-        self.entries
-            .iter()
-            .find(|e| {
-                let entry_name = self.get_name(e);
-                entry_name.eq_ignore_ascii_case(&normalized)
-            })
-            .map(|e| self.entry_ref(e))
+        self.index_of(name).and_then(|index| self.get(index))
+    }
+
+    /// Index of the entry with the given name (case-insensitive, `/` or
+    /// `\\` separated).
+    ///
+    /// The first lookup hashes every name; later lookups take constant time.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.name_index
+            .get_or_init(|| NameIndex::build(self.entries.len(), |i| self.name_at(i as usize)))
+            .get(name, |i| self.name_at(i as usize))
+    }
+
+    /// Whether an entry with the given name exists.
+    pub fn exists(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    /// The directory at `path` (case-insensitive, `/` or `\\` separated), or
+    /// the root for an empty path.
+    ///
+    /// The first call sorts all entries into a directory tree; later
+    /// listings take time proportional to the path and the listing.
+    pub fn dir(&self, path: &str) -> Option<P4kDir<'_>> {
+        let tree = self
+            .dir_tree
+            .get_or_init(|| DirTree::build(self.entries.len(), |i| self.name_at(i as usize)));
+        P4kDir::find(self, tree, path)
     }
 
     /// Read entry contents - handles decryption and decompression.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "p4k_read", level = "trace", skip_all, fields(entry = entry.name))
+    )]
     pub fn read(&self, entry: &P4kEntryRef<'_>) -> Result<Vec<u8>> {
         self.read_by_offset(
+            entry.name,
             entry.local_header_offset,
             entry.compressed_size,
             entry.uncompressed_size,
@@ -155,15 +273,77 @@ impl P4kArchive {
         })?;
 
         self.read_by_offset(
+            self.get_name(entry),
             entry.local_header_offset,
             entry.compressed_size,
             entry.uncompressed_size,
             CompressionMethod::try_from(entry.compression_method as u16)
-                .map_err(|m| Error::UnsupportedCompression(m))?,
+                .map_err(Error::UnsupportedCompression)?,
             entry.flags & 1 != 0,
         )
     }
 
+    /// Read the entries named by `items` ahead of their use.
+    ///
+    /// `index_of` gives the entry index of an item, or `None` to pass it
+    /// through. Up to the configured depth of items are queued, their reads
+    /// in flight, before the first is returned; pass the items in archive
+    /// order for the fewest seeks. [`decode`](Self::decode) turns the
+    /// fetched entries into their contents, on any thread.
+    pub fn read_ahead<I, F>(
+        &self,
+        items: I,
+        index_of: F,
+        options: ReadAheadOptions,
+    ) -> Result<ReadAhead<'_, I, F>>
+    where
+        I: Iterator,
+        F: FnMut(&I::Item) -> Option<usize>,
+    {
+        ReadAhead::new(self, items, index_of, options)
+    }
+
+    /// Decrypt and decompress an entry fetched by
+    /// [`read_ahead`](Self::read_ahead).
+    pub fn decode(&self, raw: RawEntry) -> Result<Vec<u8>> {
+        let Some((buffer, range)) = raw.stored else {
+            return self.read_index(raw.index);
+        };
+        let entry = self.entry_at(raw.index)?;
+        if entry.uncompressed_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        self.decode_stored(
+            self.get_name(entry),
+            &buffer[range],
+            CompressionMethod::try_from(entry.compression_method as u16)
+                .map_err(Error::UnsupportedCompression)?,
+            entry.uncompressed_size,
+            entry.flags & 1 != 0,
+        )
+    }
+
+    /// Frame parameters of a Zstandard-compressed entry, or `None` for
+    /// entries compressed otherwise.
+    pub fn zstd_frame_info(&self, entry: &P4kEntryRef<'_>) -> Result<Option<ZstdFrameInfo>> {
+        if entry.compression_method != CompressionMethod::Zstd || entry.uncompressed_size == 0 {
+            return Ok(None);
+        }
+        let range = Self::stored_range(
+            &self.mmap,
+            entry.local_header_offset as usize,
+            entry.compressed_size,
+        )?;
+        let stored = &self.mmap[range];
+        if entry.is_encrypted {
+            let data = crypto::decrypt(stored).map_err(|e| Error::Decryption(e.to_string()))?;
+            ZstdFrameInfo::parse(&data).map(Some)
+        } else {
+            ZstdFrameInfo::parse(stored).map(Some)
+        }
+    }
+
     /// Parallel extraction of multiple entries.
     #[cfg(feature = "parallel")]
     pub fn read_parallel<'a>(
@@ -180,6 +360,10 @@ impl P4kArchive {
 
     /// Parallel extraction with callback for streaming.
     #[cfg(feature = "parallel")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "p4k_extract", skip_all, fields(entries = indices.len()))
+    )]
     pub fn extract_parallel<F>(&self, indices: &[usize], mut callback: F) -> Result<()>
     where
         F: FnMut(usize, &str, Result<Vec<u8>>) + Send,
@@ -187,9 +371,11 @@ impl P4kArchive {
         use rayon::prelude::*;
         use std::sync::Mutex;
 
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
         let callback = Mutex::new(&mut callback);
 
-        indices.par_iter().try_for_each(|&idx| {
+        let result = indices.par_iter().try_for_each(|&idx| {
             let entry = self.entries.get(idx).ok_or_else(|| {
                 Error::Io(std::io::Error::new(
                     std::io::ErrorKind::NotFound,
@@ -199,6 +385,7 @@ impl P4kArchive {
 
             let name = self.get_name(entry);
             let result = self.read_by_offset(
+                name,
                 entry.local_header_offset,
                 entry.compressed_size,
                 entry.uncompressed_size,
@@ -209,7 +396,16 @@ impl P4kArchive {
 
             callback.lock().unwrap()(idx, name, result);
             Ok(())
-        })
+        });
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            ok = result.is_ok(),
+            "extracted P4K entries"
+        );
+
+        result
     }
 
     // Internal methods
@@ -217,7 +413,7 @@ impl P4kArchive {
     #[inline]
     fn entry_ref<'a>(&'a self, entry: &'a P4kEntryCompact) -> P4kEntryRef<'a> {
         P4kEntryRef {
-            name: &entry.name,
+            name: self.names.get(entry.name),
             compressed_size: entry.compressed_size,
             uncompressed_size: entry.uncompressed_size,
             compression_method: CompressionMethod::try_from(entry.compression_method as u16)
@@ -230,11 +426,56 @@ impl P4kArchive {
 
     #[inline]
     fn get_name<'a>(&'a self, entry: &'a P4kEntryCompact) -> &'a str {
-        &entry.name
+        self.names.get(entry.name)
+    }
+
+    /// Name of the entry at an index known to be in bounds.
+    #[inline]
+    pub(crate) fn name_at(&self, index: usize) -> &str {
+        self.names.get(self.entries[index].name)
+    }
+
+    fn entry_at(&self, index: usize) -> Result<&P4kEntryCompact> {
+        self.entries.get(index).ok_or_else(|| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "entry index out of bounds",
+            ))
+        })
+    }
+
+    #[inline]
+    pub(crate) fn mmap(&self) -> &Mmap {
+        &self.mmap
+    }
+
+    /// Offset and length of one read covering the local header and stored
+    /// bytes of entry `index`, or `None` if it stores nothing.
+    pub(crate) fn read_span(&self, index: usize) -> Option<(u64, usize)> {
+        let entry = self.entries.get(index)?;
+        if entry.uncompressed_size == 0 {
+            return None;
+        }
+        let offset = entry.local_header_offset as usize;
+        let header = 4
+            + std::mem::size_of::<LocalFileHeader>()
+            + self.get_name(entry).len()
+            + LOCAL_EXTRA_SLACK;
+        let len =
+            (header + entry.compressed_size as usize).min(self.mmap.len().checked_sub(offset)?);
+        Some((offset as u64, len))
+    }
+
+    /// Location of the stored bytes of entry `index` in `buffer`, which
+    /// starts at its local header.
+    pub(crate) fn locate_stored(&self, index: usize, buffer: &[u8]) -> Result<Range<usize>> {
+        let entry = self.entry_at(index)?;
+        Self::stored_range(buffer, 0, entry.compressed_size)
     }
 
     fn read_by_offset(
         &self,
+        name: &str,
         local_header_offset: u64,
         compressed_size: u64,
         uncompressed_size: u64,
@@ -245,10 +486,61 @@ impl P4kArchive {
             return Ok(Vec::new());
         }
 
-        let offset = local_header_offset as usize;
+        let range = Self::stored_range(&self.mmap, local_header_offset as usize, compressed_size)?;
+        self.decode_stored(
+            name,
+            &self.mmap[range],
+            compression_method,
+            uncompressed_size,
+            is_encrypted,
+        )
+    }
+
+    /// Check the limits, then decrypt and decompress the stored bytes of an
+    /// entry.
+    fn decode_stored(
+        &self,
+        name: &str,
+        stored: &[u8],
+        compression_method: CompressionMethod,
+        uncompressed_size: u64,
+        is_encrypted: bool,
+    ) -> Result<Vec<u8>> {
+        self.limits.check(name, stored.len() as u64, uncompressed_size)?;
+
+        // Decrypt if needed
+        let data = if is_encrypted {
+            Cow::Owned(crypto::decrypt(stored).map_err(|e| Error::Decryption(e.to_string()))?)
+        } else {
+            Cow::Borrowed(stored)
+        };
+
+        // Decompress
+        match compression_method {
+            CompressionMethod::Store => {
+                if data.len() != uncompressed_size as usize {
+                    return Err(Error::Decompression(format!(
+                        "stored entry size mismatch: expected {}, got {}",
+                        uncompressed_size,
+                        data.len()
+                    )));
+                }
+                Ok(data.into_owned())
+            }
+            CompressionMethod::Deflate => {
+                decompress::decompress_deflate_sized(&data, uncompressed_size as usize)
+            }
+            CompressionMethod::Zstd => {
+                decompress::decompress_zstd(&data, uncompressed_size as usize, &self.zstd)
+            }
+        }
+    }
 
+    /// Location in `data` of the stored bytes of the entry whose local
+    /// header starts at `offset`.
+    fn stored_range(data: &[u8], offset: usize, compressed_size: u64) -> Result<Range<usize>> {
         // Validate and read local header
-        if offset + 4 > self.mmap.len() {
+        if offset + 4 > data.len() {
             return Err(Error::Io(std::io::Error::new(
                 std::io::ErrorKind::UnexpectedEof,
                 "local header offset out of bounds",
@@ -257,14 +549,15 @@ impl P4kArchive {
 
         // Read signature using direct byte access (faster than BinaryReader for small reads)
         let sig = u32::from_le_bytes([
-            self.mmap[offset],
-            self.mmap[offset + 1],
-            self.mmap[offset + 2],
-            self.mmap[offset + 3],
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
         ]);
 
         if sig != LocalFileHeader::SIGNATURE && sig != LocalFileHeader::SIGNATURE_EXTENDED {
             return Err(Error::InvalidSignature {
+                offset,
                 expected: LocalFileHeader::SIGNATURE,
                 actual: sig,
             });
@@ -274,68 +567,48 @@ impl P4kArchive {
         let header_start = offset + 4;
         let header_size = std::mem::size_of::<LocalFileHeader>();
 
-        if header_start + header_size > self.mmap.len() {
+        if header_start + header_size > data.len() {
             return Err(Error::Io(std::io::Error::new(
                 std::io::ErrorKind::UnexpectedEof,
                 "local header out of bounds",
             )));
         }
 
-        let mut reader = BinaryReader::new(&self.mmap[header_start..]);
-        let local_header: LocalFileHeader = reader.read_struct()?;
+        let mut reader = BinaryReader::new_at(data, header_start);
+        let local_header: LocalFileHeader = reader
+            .read_struct()
+            .in_section("local file header", offset)?;
 
         // Calculate data location
         let data_offset = header_start + header_size + local_header.variable_data_size();
         let data_end = data_offset + compressed_size as usize;
 
-        if data_end > self.mmap.len() {
+        if data_end > data.len() {
             return Err(Error::Io(std::io::Error::new(
                 std::io::ErrorKind::UnexpectedEof,
                 "entry data out of bounds",
             )));
         }
 
-        let compressed_data = &self.mmap[data_offset..data_end];
-
-        // Decrypt if needed
-        let decrypted = if is_encrypted {
-            crypto::decrypt(compressed_data).map_err(|e| Error::Decryption(e.to_string()))?
-        } else {
-            compressed_data.to_vec()
-        };
-
-        // Decompress
-        match compression_method {
-            CompressionMethod::Store => {
-                if decrypted.len() != uncompressed_size as usize {
-                    return Err(Error::Decompression(format!(
-                        "stored entry size mismatch: expected {}, got {}",
-                        uncompressed_size,
-                        decrypted.len()
-                    )));
-                }
-                Ok(decrypted)
-            }
-            CompressionMethod::Deflate => {
-                decompress::decompress_deflate_sized(&decrypted, uncompressed_size as usize)
-            }
-            CompressionMethod::Zstd => {
-                decompress::decompress_zstd_sized(&decrypted, uncompressed_size as usize)
-            }
-        }
+        Ok(data_offset..data_end)
     }
 
     /// Parse entries with SIMD-accelerated operations.
-    fn parse_entries_optimized(data: &[u8]) -> Result<Vec<P4kEntryCompact>> {
+    fn parse_entries_optimized(
+        data: &[u8],
+        names: &mut StringArena,
+    ) -> Result<Vec<P4kEntryCompact>> {
         // Use SIMD to find actual content end (skip null padding)
         let actual_end = simd::find_content_end(data);
 
         // Find EOCD record
         let eocd_offset = Self::find_eocd_optimized(data, actual_end)?;
-        let mut reader = BinaryReader::new(&data[eocd_offset..]);
+        let mut reader = BinaryReader::new_at(data, eocd_offset);
 
         reader.advance(4); // Skip signature
-        let eocd: EocdRecord = reader.read_struct()?;
+        let eocd: EocdRecord = reader
+            .read_struct()
+            .in_section("end of central directory", eocd_offset)?;
 
         // Get ZIP64 values if needed
         let (total_entries, central_dir_offset) = if eocd.is_zip64() {
@@ -347,32 +620,46 @@ impl P4kArchive {
             )
         };
 
+        let central_dir_offset = central_dir_offset as usize;
+        if central_dir_offset > data.len() {
+            let err = svarog_common::Error::UnexpectedEof {
+                offset: central_dir_offset,
+                needed: 4,
+                available: 0,
+            };
+            return Err(err
+                .in_section("central directory", central_dir_offset)
+                .into());
+        }
+
         // Pre-allocate entry vector
         let mut entries = Vec::with_capacity(total_entries as usize);
 
         // Parse central directory
-        let cd_data = &data[central_dir_offset as usize..];
-
         Self::parse_entries_sequential(
-            cd_data,
+            data,
+            central_dir_offset,
             total_entries as usize,
             eocd.is_zip64(),
             &mut entries,
+            names,
         )?;
 
         Ok(entries)
     }
 
     fn parse_entries_sequential(
-        cd_data: &[u8],
+        data: &[u8],
+        central_dir_offset: usize,
         count: usize,
         is_zip64: bool,
         entries: &mut Vec<P4kEntryCompact>,
+        names: &mut StringArena,
     ) -> Result<()> {
-        let mut reader = BinaryReader::new(cd_data);
+        let mut reader = BinaryReader::new_at(data, central_dir_offset);
 
         for _ in 0..count {
-            let entry = Self::read_cd_entry_compact(&mut reader, is_zip64)?;
+            let entry = Self::read_cd_entry_compact(data, &mut reader, is_zip64, names)?;
             entries.push(entry);
         }
 
@@ -380,13 +667,17 @@ impl P4kArchive {
     }
 
     fn read_cd_entry_compact(
+        data: &[u8],
         reader: &mut BinaryReader,
         is_zip64: bool,
+        names: &mut StringArena,
     ) -> Result<P4kEntryCompact> {
         // Read signature
+        let offset = reader.position();
         let sig = reader.read_u32()?;
         if sig != CentralDirectoryHeader::SIGNATURE {
             return Err(Error::InvalidSignature {
+                offset,
                 expected: CentralDirectoryHeader::SIGNATURE,
                 actual: sig,
             });
@@ -397,7 +688,11 @@ impl P4kArchive {
         // Read name
         let name_bytes = reader.read_bytes(header.file_name_length as usize)?;
         let name_str = String::from_utf8_lossy(name_bytes);
-        let name = name_str.replace('/', "\\");
+        let name = if name_str.contains('/') {
+            names.alloc(&name_str.replace('/', "\\"))?
+        } else {
+            names.alloc(&name_str)?
+        };
 
         // Initialize values from header (may be overridden by ZIP64)
         let mut compressed_size = header.compressed_size as u64;
@@ -406,14 +701,18 @@ impl P4kArchive {
         let mut is_encrypted = false;
 
         // Parse extra fields
-        let extra_data = reader.read_bytes(header.extra_field_length as usize)?;
-        let mut extra_reader = BinaryReader::new(extra_data);
+        // The extra field reader keeps file offsets for error reporting
+        let extra_offset = reader.position();
+        let extra_len = reader.read_bytes(header.extra_field_length as usize)?.len();
+        let mut extra_reader =
+            BinaryReader::new_at(&data[..extra_offset + extra_len], extra_offset);
 
         if is_zip64 {
             // ZIP64 extra field
             let zip64_id = extra_reader.read_u16()?;
             if zip64_id != extra_field::ZIP64 {
                 return Err(Error::InvalidExtraFieldId {
+                    offset: extra_reader.position() - 2,
                     expected: extra_field::ZIP64,
                     actual: zip64_id,
                 });
@@ -438,6 +737,7 @@ impl P4kArchive {
             let field_5000_id = extra_reader.read_u16()?;
             if field_5000_id != extra_field::P4K_5000 {
                 return Err(Error::InvalidExtraFieldId {
+                    offset: extra_reader.position() - 2,
                     expected: extra_field::P4K_5000,
                     actual: field_5000_id,
                 });
@@ -449,6 +749,7 @@ impl P4kArchive {
             let field_5002_id = extra_reader.read_u16()?;
             if field_5002_id != extra_field::P4K_5002 {
                 return Err(Error::InvalidExtraFieldId {
+                    offset: extra_reader.position() - 2,
                     expected: extra_field::P4K_5002,
                     actual: field_5002_id,
                 });
@@ -456,6 +757,7 @@ impl P4kArchive {
             let field_5002_size = extra_reader.read_u16()?;
             if field_5002_size != 6 {
                 return Err(Error::InvalidExtraFieldId {
+                    offset: extra_reader.position() - 2,
                     expected: 6,
                     actual: field_5002_size,
                 });
@@ -466,6 +768,7 @@ impl P4kArchive {
             let field_5003_id = extra_reader.read_u16()?;
             if field_5003_id != extra_field::P4K_5003 {
                 return Err(Error::InvalidExtraFieldId {
+                    offset: extra_reader.position() - 2,
                     expected: extra_field::P4K_5003,
                     actual: field_5003_id,
                 });
@@ -480,7 +783,7 @@ impl P4kArchive {
         }
 
         let compression_method = CompressionMethod::try_from(header.compression_method)
-            .map_err(|m| Error::UnsupportedCompression(m))?;
+            .map_err(Error::UnsupportedCompression)?;
 
         Ok(P4kEntryCompact {
             name,
@@ -518,10 +821,12 @@ impl P4kArchive {
         }
 
         let locator_offset = locator_offset.ok_or(Error::Zip64EocdNotFound)?;
-        let mut reader = BinaryReader::new(&data[locator_offset..]);
+        let mut reader = BinaryReader::new_at(data, locator_offset);
 
         reader.advance(4);
-        let locator: Eocd64Locator = reader.read_struct()?;
+        let locator: Eocd64Locator = reader
+            .read_struct()
+            .in_section("ZIP64 end of central directory locator", locator_offset)?;
 
         // Read ZIP64 EOCD
         let eocd64_offset = locator.zip64_eocd_offset as usize;
@@ -538,13 +843,16 @@ impl P4kArchive {
 
         if sig != Eocd64Record::SIGNATURE {
             return Err(Error::InvalidSignature {
+                offset: eocd64_offset,
                 expected: Eocd64Record::SIGNATURE,
                 actual: sig,
             });
         }
 
-        let mut reader = BinaryReader::new(&data[eocd64_offset + 4..]);
-        let eocd64: Eocd64Record = reader.read_struct()?;
+        let mut reader = BinaryReader::new_at(data, eocd64_offset + 4);
+        let eocd64: Eocd64Record = reader
+            .read_struct()
+            .in_section("ZIP64 end of central directory", eocd64_offset)?;
 
         Ok((eocd64.central_dir_count_total, eocd64.central_dir_offset))
     }
@@ -585,6 +893,7 @@ impl P4kArchive {
     /// Legacy read method.
     pub fn read_entry(&self, entry: &P4kEntry) -> Result<Vec<u8>> {
         self.read_by_offset(
+            entry.name(),
             entry.local_header_offset(),
             entry.compressed_size(),
             entry.uncompressed_size(),
@@ -1,77 +1,228 @@
 //! Decompression utilities for P4K archives.
 
+use std::cell::RefCell;
 use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 use flate2::read::DeflateDecoder;
 
 use crate::{Error, Result};
 
-/// Decompress Zstandard-compressed data.
-pub fn decompress_zstd(data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-    let mut decoder = zstd::Decoder::new(data).map_err(|e| Error::Decompression(e.to_string()))?;
+/// Magic number at the start of every Zstandard frame.
+const ZSTD_MAGIC: u32 = 0xFD2F_B528;
 
-    output.clear();
-    decoder
-        .read_to_end(output)
-        .map_err(|e| Error::Decompression(e.to_string()))?;
+/// Source of [`ZstdOptions`] identities; 0 is the default settings.
+static NEXT_OPTIONS_ID: AtomicU64 = AtomicU64::new(1);
 
-    Ok(())
+thread_local! {
+    /// Decompression context of this thread, with the identity of the
+    /// settings it was created for.
+    static ZSTD_CONTEXT: RefCell<Option<(u64, zstd::bulk::Decompressor<'static>)>> =
+        const { RefCell::new(None) };
 }
 
-/// Decompress Zstandard-compressed data with known output size.
-pub fn decompress_zstd_sized(data: &[u8], expected_size: usize) -> Result<Vec<u8>> {
-    let mut output = Vec::with_capacity(expected_size);
-    decompress_zstd(data, &mut output)?;
-    Ok(output)
+/// Zstandard decoding settings of an archive.
+///
+/// Entries are decoded in one pass straight into a buffer of their
+/// uncompressed size, so no window buffer is allocated however long the
+/// window of a frame is. Each thread keeps its decompression context (and
+/// the loaded dictionary) between entries.
+#[derive(Clone, Default)]
+pub struct ZstdOptions {
+    /// Identity of these settings for the per-thread context.
+    id: u64,
+    window_log_max: Option<u32>,
+    dictionary: Option<Arc<[u8]>>,
 }
 
-/// Decompress DEFLATE-compressed data.
-pub fn decompress_deflate(data: &[u8], output: &mut Vec<u8>) -> Result<()> {
-    let mut decoder = DeflateDecoder::new(data);
+impl ZstdOptions {
+    /// Default settings: no window limit and no dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    output.clear();
-    decoder
-        .read_to_end(output)
-        .map_err(|e| Error::Decompression(e.to_string()))?;
+    /// Reject frames whose window is larger than `2^log` bytes, as
+    /// `zstd --memory` does, instead of decoding them.
+    pub fn window_log_max(mut self, log: u32) -> Self {
+        self.window_log_max = Some(log);
+        self.id = NEXT_OPTIONS_ID.fetch_add(1, Ordering::Relaxed);
+        self
+    }
 
-    Ok(())
+    /// Decode with a dictionary, either one trained with `zstd --train` or
+    /// raw content.
+    pub fn dictionary(mut self, dictionary: impl Into<Vec<u8>>) -> Self {
+        self.dictionary = Some(dictionary.into().into());
+        self.id = NEXT_OPTIONS_ID.fetch_add(1, Ordering::Relaxed);
+        self
+    }
+
+    /// The window limit, as a power of two.
+    pub fn window_log_limit(&self) -> Option<u32> {
+        self.window_log_max
+    }
+
+    /// The dictionary, if any.
+    pub fn dictionary_data(&self) -> Option<&[u8]> {
+        self.dictionary.as_deref()
+    }
+
+    fn decompressor(&self) -> Result<zstd::bulk::Decompressor<'static>> {
+        let decompressor = match &self.dictionary {
+            Some(dictionary) => zstd::bulk::Decompressor::with_dictionary(dictionary),
+            None => zstd::bulk::Decompressor::new(),
+        };
+        decompressor.map_err(|e| Error::Decompression(e.to_string()))
+    }
 }
 
-/// Decompress DEFLATE-compressed data with known output size.
-pub fn decompress_deflate_sized(data: &[u8], expected_size: usize) -> Result<Vec<u8>> {
-    let mut output = Vec::with_capacity(expected_size);
-    decompress_deflate(data, &mut output)?;
-    Ok(output)
+impl std::fmt::Debug for ZstdOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZstdOptions")
+            .field("window_log_max", &self.window_log_max)
+            .field("dictionary_len", &self.dictionary.as_ref().map(|d| d.len()))
+            .finish()
+    }
 }
 
-/// Thread-local Zstandard decompressor for efficiency.
-///
-/// Creating a Zstd decoder has some overhead, so we reuse decoders
-/// within each thread. Currently not used but available for parallel extraction.
-#[allow(dead_code)]
-pub struct ZstdDecompressor {
-    // The zstd crate doesn't expose a stateful decompressor directly,
-    // but we could use raw FFI if needed for performance.
-    // For now, we just create new decoders per call.
+/// Parameters from the header of a Zstandard frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZstdFrameInfo {
+    /// Bytes of history the decoder keeps when streaming.
+    pub window_size: u64,
+    /// Decompressed size, when the header records it.
+    pub content_size: Option<u64>,
+    /// ID of the dictionary the frame was compressed with.
+    pub dictionary_id: Option<u32>,
+    /// Whether the frame ends with a checksum of its contents.
+    pub has_checksum: bool,
 }
 
-#[allow(dead_code)]
-impl ZstdDecompressor {
-    /// Create a new thread-local decompressor.
-    pub fn new() -> Self {
-        Self {}
+impl ZstdFrameInfo {
+    /// Read the header of the frame at the start of `data`.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let truncated = || Error::Decompression("truncated Zstandard frame header".to_string());
+
+        let magic = data.get(..4).ok_or_else(truncated)?;
+        if u32::from_le_bytes([magic[0], magic[1], magic[2], magic[3]]) != ZSTD_MAGIC {
+            return Err(Error::Decompression("not a Zstandard frame".to_string()));
+        }
+        let descriptor = *data.get(4).ok_or_else(truncated)?;
+        let single_segment = descriptor & 0x20 != 0;
+        let has_checksum = descriptor & 0x04 != 0;
+        let dictionary_id_size = [0, 1, 2, 4][usize::from(descriptor & 0x03)];
+        let content_size_size = match descriptor >> 6 {
+            0 => usize::from(single_segment),
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+
+        let mut pos = 5;
+        let window_descriptor = if single_segment {
+            None
+        } else {
+            pos += 1;
+            Some(*data.get(5).ok_or_else(truncated)?)
+        };
+        let dictionary_id = read_le(data, pos, dictionary_id_size).ok_or_else(truncated)?;
+        pos += dictionary_id_size;
+        let content_size = read_le(data, pos, content_size_size).ok_or_else(truncated)?;
+
+        let content_size = match content_size_size {
+            0 => None,
+            2 => Some(content_size + 256),
+            _ => Some(content_size),
+        };
+        let window_size = match window_descriptor {
+            Some(byte) => {
+                let base = 1u64 << (10 + (byte >> 3));
+                base + (base / 8) * u64::from(byte & 0x07)
+            }
+            None => content_size.unwrap_or(0),
+        };
+
+        Ok(Self {
+            window_size,
+            content_size,
+            dictionary_id: (dictionary_id != 0).then_some(dictionary_id as u32),
+            has_checksum,
+        })
     }
 
-    /// Decompress data.
-    pub fn decompress(&mut self, data: &[u8], expected_size: usize) -> Result<Vec<u8>> {
-        decompress_zstd_sized(data, expected_size)
+    /// Base-2 logarithm of the window size, rounded up.
+    pub fn window_log(&self) -> u32 {
+        64 - (self.window_size.max(1) - 1).leading_zeros()
+    }
+}
+
+/// Little-endian integer of `size` bytes at `pos`.
+fn read_le(data: &[u8], pos: usize, size: usize) -> Option<u64> {
+    let bytes = data.get(pos..pos + size)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0u64, |value, &b| (value << 8) | u64::from(b)),
+    )
+}
+
+/// Decompress Zstandard-compressed data of known output size.
+pub fn decompress_zstd(
+    data: &[u8],
+    expected_size: usize,
+    options: &ZstdOptions,
+) -> Result<Vec<u8>> {
+    if let Ok(frame) = ZstdFrameInfo::parse(data) {
+        if let Some(log) = options.window_log_max {
+            if frame.window_size > 1u64 << log.min(63) {
+                return Err(Error::Decompression(format!(
+                    "Zstandard window of {} bytes exceeds the limit of 2^{}",
+                    frame.window_size, log
+                )));
+            }
+        }
+        if let (Some(id), None) = (frame.dictionary_id, &options.dictionary) {
+            return Err(Error::Decompression(format!(
+                "Zstandard frame needs dictionary {}",
+                id
+            )));
+        }
     }
+
+    ZSTD_CONTEXT.with(|context| -> Result<Vec<u8>> {
+        let mut context = context.borrow_mut();
+        let decompressor = match &mut *context {
+            Some((id, decompressor)) if *id == options.id => decompressor,
+            slot => &mut slot.insert((options.id, options.decompressor()?)).1,
+        };
+
+        let mut output = Vec::with_capacity(expected_size);
+        decompressor
+            .decompress_to_buffer(data, &mut output)
+            .map_err(|e| Error::Decompression(e.to_string()))?;
+        Ok(output)
+    })
 }
 
-impl Default for ZstdDecompressor {
-    fn default() -> Self {
-        Self::new()
+/// Decompress DEFLATE-compressed data with known output size.
+///
+/// Fails rather than decoding past `expected_size`, so a stream cannot
+/// expand beyond the size its entry declares.
+pub fn decompress_deflate_sized(data: &[u8], expected_size: usize) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(expected_size);
+    DeflateDecoder::new(data)
+        .take(expected_size as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(|e| Error::Decompression(e.to_string()))?;
+    if output.len() > expected_size {
+        return Err(Error::Decompression(format!(
+            "DEFLATE stream expands beyond the declared {} bytes",
+            expected_size
+        )));
     }
+    Ok(output)
 }
 
 #[cfg(test)]
@@ -86,11 +237,70 @@ mod tests {
         let compressed = zstd::encode_all(&original[..], 3).unwrap();
 
         // Decompress
-        let decompressed = decompress_zstd_sized(&compressed, original.len()).unwrap();
+        let decompressed =
+            decompress_zstd(&compressed, original.len(), &ZstdOptions::default()).unwrap();
 
         assert_eq!(decompressed, original);
     }
 
+    #[test]
+    fn test_zstd_dictionary() {
+        let dictionary = b"<Entity Name=\"\" Class=\"\"><Components/></Entity>".repeat(4);
+        let original = b"<Entity Name=\"ship\" Class=\"Vehicle\"><Components/></Entity>";
+
+        let compressed = zstd::bulk::Compressor::with_dictionary(3, &dictionary)
+            .unwrap()
+            .compress(original)
+            .unwrap();
+
+        let options = ZstdOptions::new().dictionary(dictionary);
+        let decompressed = decompress_zstd(&compressed, original.len(), &options).unwrap();
+        assert_eq!(decompressed, original);
+
+        // The context of the dictionary is not used for other settings
+        let plain = zstd::bulk::compress(original, 3).unwrap();
+        let decompressed = decompress_zstd(&plain, original.len(), &ZstdOptions::new()).unwrap();
+        assert_eq!(decompressed, original);
+    }
+
+    #[test]
+    fn test_zstd_frame_info() {
+        let original = vec![7u8; 5000];
+        let compressed = zstd::bulk::compress(&original, 3).unwrap();
+
+        let frame = ZstdFrameInfo::parse(&compressed).unwrap();
+        assert_eq!(frame.content_size, Some(5000));
+        assert_eq!(frame.dictionary_id, None);
+        assert!(frame.window_size >= 5000);
+
+        // Window descriptor of 2 MiB, no content size
+        let header = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x58];
+        let frame = ZstdFrameInfo::parse(&header).unwrap();
+        assert_eq!(frame.window_size, 2 << 20);
+        assert_eq!(frame.window_log(), 21);
+        assert_eq!(frame.content_size, None);
+        assert!(!frame.has_checksum);
+
+        // 2-byte content size is offset by 256, dictionary ID follows the window
+        let header = [0x28, 0xB5, 0x2F, 0xFD, 0x45, 0x00, 0x2A, 0x10, 0x00];
+        let frame = ZstdFrameInfo::parse(&header).unwrap();
+        assert_eq!(frame.window_size, 1024);
+        assert_eq!(frame.dictionary_id, Some(42));
+        assert_eq!(frame.content_size, Some(272));
+        assert!(frame.has_checksum);
+
+        assert!(ZstdFrameInfo::parse(&header[..7]).is_err());
+        assert!(ZstdFrameInfo::parse(b"PK\x03\x04\x00").is_err());
+    }
+
+    #[test]
+    fn test_zstd_window_limit() {
+        let header = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x58];
+        let options = ZstdOptions::new().window_log_max(20);
+        let err = decompress_zstd(&header, 100, &options).unwrap_err();
+        assert!(err.to_string().contains("exceeds the limit"));
+    }
+
     #[test]
     fn test_deflate_roundtrip() {
         use flate2::write::DeflateEncoder;
@@ -108,5 +318,9 @@ mod tests {
         let decompressed = decompress_deflate_sized(&compressed, original.len()).unwrap();
 
         assert_eq!(decompressed, original);
+
+        // Streams longer than declared are cut off with an error
+        let err = decompress_deflate_sized(&compressed, original.len() - 1).unwrap_err();
+        assert!(err.to_string().contains("beyond the declared"));
     }
 }
@@ -0,0 +1,218 @@
+//! Entry-level comparison of two P4K archives.
+//!
+//! Entries are paired by name, ignoring case and separator style, and
+//! compared by uncompressed size and the CRC32 stored in the archive. Nothing
+//! is decompressed, so two full game archives compare in about the time it
+//! takes to open them. Entries listed elsewhere, e.g. in a manifest saved
+//! from an earlier build, go through [`P4kDiff::compare_entries`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_p4k::{P4kArchive, P4kDiff};
+//!
+//! let old = P4kArchive::open("old/Data.p4k")?;
+//! let new = P4kArchive::open("new/Data.p4k")?;
+//! let diff = P4kDiff::compare(&old, &new);
+//! print!("{}", diff);
+//! # Ok::<(), svarog_p4k::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{P4kArchive, P4kEntryRef};
+
+/// Name and content identity of one archive entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrySummary {
+    /// Entry name with `/` separators.
+    pub name: String,
+    /// Uncompressed size in bytes.
+    pub size: u64,
+    /// CRC32 of the uncompressed contents.
+    pub crc32: u32,
+}
+
+impl EntrySummary {
+    /// Summarize an entry, normalizing `\` separators in `name` to `/`.
+    pub fn new(name: &str, size: u64, crc32: u32) -> Self {
+        Self {
+            name: name.replace('\\', "/"),
+            size,
+            crc32,
+        }
+    }
+}
+
+impl From<&P4kEntryRef<'_>> for EntrySummary {
+    fn from(entry: &P4kEntryRef<'_>) -> Self {
+        Self::new(entry.name, entry.uncompressed_size, entry.crc32)
+    }
+}
+
+/// How an entry differs between the archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum P4kChangeKind {
+    /// Only in the new archive.
+    Added,
+    /// Only in the old archive.
+    Removed,
+    /// In both, with a different size or CRC32.
+    Modified,
+}
+
+/// One entry that differs between the archives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct P4kChange {
+    /// How the entry differs.
+    pub kind: P4kChangeKind,
+    /// The entry in the old archive, unless added.
+    pub old: Option<EntrySummary>,
+    /// The entry in the new archive, unless removed.
+    pub new: Option<EntrySummary>,
+}
+
+impl P4kChange {
+    /// Name of the entry, as spelled in the new archive when it is in both.
+    pub fn name(&self) -> &str {
+        self.new
+            .as_ref()
+            .or(self.old.as_ref())
+            .map_or("", |entry| &entry.name)
+    }
+}
+
+impl fmt::Display for P4kChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => {
+                write!(f, "~ {} ({} -> {} bytes)", new.name, old.size, new.size)
+            }
+            (None, Some(new)) => write!(f, "+ {} ({} bytes)", new.name, new.size),
+            (Some(old), None) => write!(f, "- {} ({} bytes)", old.name, old.size),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Differences between the entries of two archives, sorted by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct P4kDiff {
+    changes: Vec<P4kChange>,
+    unchanged: usize,
+}
+
+impl P4kDiff {
+    /// Compare the entries of two archives.
+    pub fn compare(old: &P4kArchive, new: &P4kArchive) -> Self {
+        Self::compare_entries(
+            old.iter().map(|e| EntrySummary::from(&e)),
+            new.iter().map(|e| EntrySummary::from(&e)),
+        )
+    }
+
+    /// Compare two lists of entries, from archives or saved listings.
+    pub fn compare_entries(
+        old: impl IntoIterator<Item = EntrySummary>,
+        new: impl IntoIterator<Item = EntrySummary>,
+    ) -> Self {
+        let mut old: HashMap<String, EntrySummary> = old
+            .into_iter()
+            .map(|entry| (entry.name.to_lowercase(), entry))
+            .collect();
+
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+        for entry in new {
+            match old.remove(&entry.name.to_lowercase()) {
+                Some(previous) if previous.size == entry.size && previous.crc32 == entry.crc32 => {
+                    unchanged += 1;
+                }
+                Some(previous) => changes.push(P4kChange {
+                    kind: P4kChangeKind::Modified,
+                    old: Some(previous),
+                    new: Some(entry),
+                }),
+                None => changes.push(P4kChange {
+                    kind: P4kChangeKind::Added,
+                    old: None,
+                    new: Some(entry),
+                }),
+            }
+        }
+        changes.extend(old.into_values().map(|entry| P4kChange {
+            kind: P4kChangeKind::Removed,
+            old: Some(entry),
+            new: None,
+        }));
+        changes.sort_by_cached_key(|change| change.name().to_lowercase());
+
+        Self { changes, unchanged }
+    }
+
+    /// All changed entries, sorted by name.
+    pub fn changes(&self) -> &[P4kChange] {
+        &self.changes
+    }
+
+    /// Number of entries of the given kind.
+    pub fn count(&self, kind: P4kChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// Number of entries in both archives with the same contents.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Whether the archives hold the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for P4kDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_entries() {
+        let old = vec![
+            EntrySummary::new("Data\\Scripts\\main.lua", 10, 1),
+            EntrySummary::new("Data\\Objects\\ship.mtl", 20, 2),
+            EntrySummary::new("Data\\old.txt", 5, 3),
+        ];
+        let new = vec![
+            EntrySummary::new("Data/scripts/main.lua", 10, 1),
+            EntrySummary::new("Data/Objects/ship.mtl", 22, 4),
+            EntrySummary::new("Data/new.txt", 7, 5),
+        ];
+        let diff = P4kDiff::compare_entries(old, new);
+
+        assert_eq!(diff.unchanged(), 1);
+        let changes: Vec<_> = diff.changes().iter().map(|c| (c.kind, c.name())).collect();
+        assert_eq!(
+            changes,
+            [
+                (P4kChangeKind::Added, "Data/new.txt"),
+                (P4kChangeKind::Modified, "Data/Objects/ship.mtl"),
+                (P4kChangeKind::Removed, "Data/old.txt"),
+            ]
+        );
+        assert_eq!(diff.count(P4kChangeKind::Modified), 1);
+        assert_eq!(
+            diff.to_string(),
+            "+ Data/new.txt (7 bytes)\n~ Data/Objects/ship.mtl (20 -> 22 bytes)\n- Data/old.txt (5 bytes)\n"
+        );
+    }
+}
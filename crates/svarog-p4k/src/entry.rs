@@ -133,14 +133,19 @@ impl P4kEntry {
 /// - Date: bits 16-20 = day, bits 21-24 = month, bits 25-31 = year-1980
 fn dos_datetime_to_system_time(datetime: u32) -> Option<SystemTime> {
     let year = 1980 + ((datetime >> 25) & 0x7F) as i32;
-    let month = ((datetime >> 21) & 0x0F) as u32;
-    let day = ((datetime >> 16) & 0x1F) as u32;
-    let hour = ((datetime >> 11) & 0x1F) as u32;
-    let minute = ((datetime >> 5) & 0x3F) as u32;
-    let second = ((datetime & 0x1F) * 2) as u32;
+    let month = (datetime >> 21) & 0x0F;
+    let day = (datetime >> 16) & 0x1F;
+    let hour = (datetime >> 11) & 0x1F;
+    let minute = (datetime >> 5) & 0x3F;
+    let second = (datetime & 0x1F) * 2;
 
     // Basic validation
-    if month < 1 || month > 12 || day < 1 || day > 31 || hour > 23 || minute > 59 || second > 59 {
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
         return None;
     }
 
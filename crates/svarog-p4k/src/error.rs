@@ -1,5 +1,6 @@
 //! Error types for the P4K crate.
 
+use svarog_common::ErrorLocation;
 use thiserror::Error;
 
 /// Errors that can occur when working with P4K archives.
@@ -14,8 +15,12 @@ pub enum Error {
     Common(#[from] svarog_common::Error),
 
     /// Invalid ZIP magic bytes.
-    #[error("invalid ZIP signature: expected {expected:#010x}, got {actual:#010x}")]
-    InvalidSignature { expected: u32, actual: u32 },
+    #[error("invalid ZIP signature at offset {offset:#x}: expected {expected:#010x}, got {actual:#010x}")]
+    InvalidSignature {
+        offset: usize,
+        expected: u32,
+        actual: u32,
+    },
 
     /// Could not find the end of central directory record.
     #[error("could not find end of central directory record")]
@@ -26,8 +31,14 @@ pub enum Error {
     Zip64EocdNotFound,
 
     /// Invalid extra field ID.
-    #[error("invalid extra field ID: expected {expected:#06x}, got {actual:#06x}")]
-    InvalidExtraFieldId { expected: u16, actual: u16 },
+    #[error(
+        "invalid extra field ID at offset {offset:#x}: expected {expected:#06x}, got {actual:#06x}"
+    )]
+    InvalidExtraFieldId {
+        offset: usize,
+        expected: u16,
+        actual: u16,
+    },
 
     /// Unsupported compression method.
     #[error("unsupported compression method: {0}")]
@@ -48,6 +59,36 @@ pub enum Error {
     /// Entry not found.
     #[error("entry not found: {0}")]
     EntryNotFound(String),
+
+    /// Entry larger uncompressed than the configured limit.
+    #[error("{name}: {size} bytes uncompressed exceeds the limit of {limit} bytes")]
+    SizeLimitExceeded { name: String, size: u64, limit: u64 },
+
+    /// Entry compressed more than the configured ratio limit allows.
+    #[error("{name}: compression ratio of {ratio}:1 exceeds the limit of {limit}:1")]
+    RatioLimitExceeded {
+        name: String,
+        ratio: u64,
+        limit: u64,
+    },
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn own_offset(&self) -> Option<usize> {
+        match self {
+            Error::InvalidSignature { offset, .. } | Error::InvalidExtraFieldId { offset, .. } => {
+                Some(*offset)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Result type for P4K operations.
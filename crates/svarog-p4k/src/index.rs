@@ -0,0 +1,338 @@
+//! Lookup structures over entry names.
+//!
+//! Both are built on first use, so archives opened only to be extracted in
+//! full do not pay for them. Names are compared as P4K paths: ASCII case is
+//! ignored, `/` and `\` are the same separator, and empty components (from
+//! doubled, leading or trailing separators) are skipped.
+
+use std::hash::Hasher;
+
+use hashbrown::hash_table::{Entry, HashTable};
+use rustc_hash::FxHasher;
+
+use crate::{P4kArchive, P4kEntryRef};
+
+/// Separator entry names are stored with.
+const SEPARATOR: u8 = b'\\';
+
+/// Node of the archive root in a [`DirTree`].
+const ROOT: u32 = 0;
+
+#[inline]
+fn is_separator(b: u8) -> bool {
+    b == b'\\' || b == b'/'
+}
+
+/// Non-empty components of a path.
+fn components(path: &str) -> impl Iterator<Item = &[u8]> {
+    path.as_bytes()
+        .split(|&b| is_separator(b))
+        .filter(|c| !c.is_empty())
+}
+
+/// Hash of a path, equal for all paths that [`same_path`] equates.
+///
+/// Each component is hashed lowercased and followed by a separator, so the
+/// hash of a directory is a prefix state of the hashes of its entries.
+fn hash_path(path: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    for component in components(path) {
+        for &b in component {
+            hasher.write_u8(b.to_ascii_lowercase());
+        }
+        hasher.write_u8(SEPARATOR);
+    }
+    hasher.finish()
+}
+
+/// Whether two paths name the same entry or directory.
+pub(crate) fn same_path(a: &str, b: &str) -> bool {
+    let mut a = components(a);
+    let mut b = components(b);
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) if x.eq_ignore_ascii_case(y) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Entry indices by name.
+pub(crate) struct NameIndex {
+    table: HashTable<u32>,
+}
+
+impl NameIndex {
+    /// Index `count` entries named by `name`; of entries with the same name,
+    /// the first is kept.
+    pub(crate) fn build<'a>(count: usize, name: impl Fn(u32) -> &'a str) -> Self {
+        let mut table = HashTable::with_capacity(count);
+        for index in 0..count as u32 {
+            let path = name(index);
+            let hash = hash_path(path);
+            if let Entry::Vacant(slot) = table.entry(
+                hash,
+                |&other| same_path(name(other), path),
+                |&other| hash_path(name(other)),
+            ) {
+                slot.insert(index);
+            }
+        }
+        Self { table }
+    }
+
+    /// Index of the entry named `path`.
+    pub(crate) fn get<'a>(&self, path: &str, name: impl Fn(u32) -> &'a str) -> Option<usize> {
+        self.table
+            .find(hash_path(path), |&index| same_path(name(index), path))
+            .map(|&index| index as usize)
+    }
+}
+
+/// One directory: a prefix of the name of one of its entries.
+struct DirNode {
+    /// Entry whose name starts with the path of the directory
+    entry: u32,
+    /// Length of the path in that name
+    len: u32,
+    /// Subdirectories, in order of first appearance
+    dirs: Vec<u32>,
+    /// Entries directly inside, in archive order
+    files: Vec<u32>,
+}
+
+/// Directories of an archive with their contents.
+pub(crate) struct DirTree {
+    nodes: Vec<DirNode>,
+    /// Nodes by path
+    table: HashTable<u32>,
+}
+
+impl DirTree {
+    /// Sort `count` entries named by `name` into directories.
+    ///
+    /// Paths are hashed once, component by component, with the hasher
+    /// state at each separator giving the hash of that directory.
+    pub(crate) fn build<'a>(count: usize, name: impl Fn(u32) -> &'a str) -> Self {
+        let mut nodes = vec![DirNode {
+            entry: 0,
+            len: 0,
+            dirs: Vec::new(),
+            files: Vec::new(),
+        }];
+        let mut table = HashTable::new();
+        let path_of = |node: &DirNode| &name(node.entry)[..node.len as usize];
+
+        for index in 0..count as u32 {
+            let bytes = name(index).as_bytes();
+            let mut hasher = FxHasher::default();
+            let mut parent = ROOT;
+            let mut start = 0;
+            for (pos, &b) in bytes.iter().enumerate() {
+                if !is_separator(b) {
+                    hasher.write_u8(b.to_ascii_lowercase());
+                    continue;
+                }
+                if pos > start {
+                    hasher.write_u8(SEPARATOR);
+                    let path = &name(index)[..pos];
+                    let entry = table.entry(
+                        hasher.finish(),
+                        |&node| same_path(path_of(&nodes[node as usize]), path),
+                        |&node| hash_path(path_of(&nodes[node as usize])),
+                    );
+                    parent = match entry {
+                        Entry::Occupied(slot) => *slot.get(),
+                        Entry::Vacant(slot) => {
+                            let node = nodes.len() as u32;
+                            slot.insert(node);
+                            nodes.push(DirNode {
+                                entry: index,
+                                len: pos as u32,
+                                dirs: Vec::new(),
+                                files: Vec::new(),
+                            });
+                            nodes[parent as usize].dirs.push(node);
+                            node
+                        }
+                    };
+                }
+                start = pos + 1;
+            }
+            // Names ending in a separator only mark a directory
+            if bytes.len() > start {
+                nodes[parent as usize].files.push(index);
+            }
+        }
+        Self { nodes, table }
+    }
+
+    /// Node of the directory at `path`; empty paths are the root.
+    fn get<'a>(&self, path: &str, name: impl Fn(u32) -> &'a str) -> Option<u32> {
+        if components(path).next().is_none() {
+            return Some(ROOT);
+        }
+        self.table
+            .find(hash_path(path), |&node| {
+                let node = &self.nodes[node as usize];
+                same_path(&name(node.entry)[..node.len as usize], path)
+            })
+            .copied()
+    }
+}
+
+/// A directory of a P4K archive, from [`P4kArchive::dir`].
+#[derive(Clone, Copy)]
+pub struct P4kDir<'a> {
+    archive: &'a P4kArchive,
+    tree: &'a DirTree,
+    node: u32,
+}
+
+impl<'a> P4kDir<'a> {
+    pub(crate) fn find(archive: &'a P4kArchive, tree: &'a DirTree, path: &str) -> Option<Self> {
+        let node = tree.get(path, |index| archive.name_at(index as usize))?;
+        Some(Self {
+            archive,
+            tree,
+            node,
+        })
+    }
+
+    fn node(&self) -> &'a DirNode {
+        &self.tree.nodes[self.node as usize]
+    }
+
+    /// Path of the directory as stored in the archive, without a trailing
+    /// separator; empty for the root.
+    pub fn path(&self) -> &'a str {
+        match self.node() {
+            node if node.len == 0 => "",
+            node => &self.archive.name_at(node.entry as usize)[..node.len as usize],
+        }
+    }
+
+    /// Last component of the path; empty for the root.
+    pub fn name(&self) -> &'a str {
+        let path = self.path();
+        path.rfind(['\\', '/']).map_or(path, |pos| &path[pos + 1..])
+    }
+
+    /// Whether this is the root of the archive.
+    pub fn is_root(&self) -> bool {
+        self.node == ROOT
+    }
+
+    /// Subdirectories, in order of first appearance in the archive.
+    pub fn dirs(&self) -> impl Iterator<Item = P4kDir<'a>> + 'a {
+        let (archive, tree) = (self.archive, self.tree);
+        self.node().dirs.iter().map(move |&node| P4kDir {
+            archive,
+            tree,
+            node,
+        })
+    }
+
+    /// Indices of the entries directly inside, in archive order.
+    pub fn file_indices(&self) -> impl Iterator<Item = usize> + 'a {
+        self.node().files.iter().map(|&index| index as usize)
+    }
+
+    /// Entries directly inside, in archive order.
+    pub fn files(&self) -> impl Iterator<Item = P4kEntryRef<'a>> + 'a {
+        let archive = self.archive;
+        self.file_indices()
+            .filter_map(move |index| archive.get(index))
+    }
+}
+
+impl std::fmt::Debug for P4kDir<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let node = self.node();
+        f.debug_struct("P4kDir")
+            .field("path", &self.path())
+            .field("dirs", &node.dirs.len())
+            .field("files", &node.files.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: &[&str] = &[
+        "Data\\Objects\\Ships\\AEGS\\gladius.cgf",
+        "Data\\Objects\\Ships\\AEGS\\gladius.mtl",
+        "Data\\Objects\\ship.mtl",
+        "Data\\Game2.dcb",
+        "Data\\Objects\\Ships\\ANVL\\",
+        "Data\\game2.DCB",
+        "readme.txt",
+    ];
+
+    fn name(index: u32) -> &'static str {
+        NAMES[index as usize]
+    }
+
+    #[test]
+    fn test_same_path() {
+        assert!(same_path("Data\\Objects", "data/objects/"));
+        assert!(same_path("\\Data\\\\Objects", "DATA/OBJECTS"));
+        assert!(!same_path("Data\\Objects", "Data\\Object"));
+        assert!(!same_path("Data\\Objects", "Data"));
+        assert_eq!(hash_path("Data\\Objects"), hash_path("data//objects\\"));
+        assert_ne!(hash_path("ab\\c"), hash_path("a\\bc"));
+    }
+
+    #[test]
+    fn test_name_index() {
+        let index = NameIndex::build(NAMES.len(), name);
+        assert_eq!(
+            index.get("data/objects/ships/aegs/GLADIUS.MTL", name),
+            Some(1)
+        );
+        // The first of entries with the same name wins
+        assert_eq!(index.get("Data/Game2.dcb", name), Some(3));
+        assert_eq!(index.get("readme.txt", name), Some(6));
+        assert_eq!(index.get("Data\\Objects", name), None);
+        assert_eq!(index.get("missing.txt", name), None);
+    }
+
+    #[test]
+    fn test_dir_tree() {
+        let tree = DirTree::build(NAMES.len(), name);
+        let files = |node: u32| tree.nodes[node as usize].files.clone();
+        let dirs = |node: u32| -> Vec<&str> {
+            tree.nodes[node as usize]
+                .dirs
+                .iter()
+                .map(|&dir| {
+                    let dir = &tree.nodes[dir as usize];
+                    &name(dir.entry)[..dir.len as usize]
+                })
+                .collect()
+        };
+
+        assert_eq!(tree.get("", name), Some(ROOT));
+        assert_eq!(files(ROOT), [6]);
+        assert_eq!(dirs(ROOT), ["Data"]);
+
+        let objects = tree.get("data/objects/", name).unwrap();
+        assert_eq!(files(objects), [2]);
+        assert_eq!(dirs(objects), ["Data\\Objects\\Ships"]);
+
+        let ships = tree.get("Data\\Objects\\Ships", name).unwrap();
+        assert_eq!(
+            dirs(ships),
+            ["Data\\Objects\\Ships\\AEGS", "Data\\Objects\\Ships\\ANVL"]
+        );
+        let anvl = tree.get("Data\\Objects\\Ships\\ANVL", name).unwrap();
+        assert!(files(anvl).is_empty());
+
+        assert_eq!(files(tree.get("Data", name).unwrap()), [3, 5]);
+        assert_eq!(tree.get("Data\\Game2.dcb", name), None);
+        assert_eq!(tree.get("Data\\Missing", name), None);
+    }
+}
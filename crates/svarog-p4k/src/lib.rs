@@ -17,6 +17,15 @@
 //! - Parallel extraction with rayon (with `parallel` feature)
 //! - Zero-copy memory-mapped file access
 //! - Optimized AES decryption with AES-NI when available
+//! - Per-thread Zstandard contexts decoding each entry in one pass, with
+//!   optional dictionaries and window limits ([`ZstdOptions`])
+//! - Reads queued ahead of decompression for seek-bound disks
+//!   ([`P4kArchive::read_ahead`]), through io_uring on Linux or overlapped
+//!   I/O on Windows with the `queued-io` feature
+//! - Case-insensitive name index and directory tree, built on first use, for
+//!   constant-time [`P4kArchive::find`] and [`P4kArchive::dir`] listings
+//! - Size and compression ratio limits checked before decoding
+//!   ([`DecompressionLimits`]), guarding against zip bombs
 //!
 //! # Example
 //!
@@ -40,11 +49,20 @@
 mod archive;
 mod crypto;
 mod decompress;
+mod diff;
 mod entry;
 mod error;
+mod index;
+mod limits;
+mod read_ahead;
 mod simd;
 pub mod zip;
 
 pub use archive::{P4kArchive, P4kEntryRef};
+pub use decompress::{ZstdFrameInfo, ZstdOptions};
+pub use diff::{EntrySummary, P4kChange, P4kChangeKind, P4kDiff};
 pub use entry::P4kEntry;
 pub use error::{Error, Result};
+pub use index::P4kDir;
+pub use limits::DecompressionLimits;
+pub use read_ahead::{IoBackend, RawEntry, ReadAhead, ReadAheadOptions};
@@ -0,0 +1,141 @@
+//! Limits on decompressed entries.
+
+use crate::index::same_path;
+use crate::{Error, Result};
+
+/// Limits checked before an entry is decrypted or decompressed.
+///
+/// Malformed or malicious archives ("zip bombs") can declare sizes far
+/// beyond available memory, or expand a few stored bytes into gigabytes.
+/// Entries are decoded into a buffer of their declared size and never past
+/// it, so bounding the declared size bounds the memory an entry can take.
+/// Services and viewers should set limits; nothing is limited by default.
+#[derive(Debug, Clone, Default)]
+pub struct DecompressionLimits {
+    max_size: Option<u64>,
+    max_ratio: Option<u64>,
+    /// Entries exempt from the limits
+    allowed: Vec<String>,
+}
+
+impl DecompressionLimits {
+    /// No limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject entries larger than `bytes` uncompressed.
+    pub fn max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// Reject entries more than `ratio` times larger uncompressed than
+    /// stored.
+    pub fn max_ratio(mut self, ratio: u64) -> Self {
+        self.max_ratio = Some(ratio);
+        self
+    }
+
+    /// Exempt the entry `name` (case-insensitive, `/` or `\` separated) from
+    /// the limits, e.g. a known-large file.
+    pub fn allow(mut self, name: impl Into<String>) -> Self {
+        self.allowed.push(name.into());
+        self
+    }
+
+    /// The size limit in bytes.
+    pub fn size_limit(&self) -> Option<u64> {
+        self.max_size
+    }
+
+    /// The compression ratio limit.
+    pub fn ratio_limit(&self) -> Option<u64> {
+        self.max_ratio
+    }
+
+    /// Whether the entry `name` is exempt from the limits.
+    pub fn is_allowed(&self, name: &str) -> bool {
+        self.allowed.iter().any(|allowed| same_path(allowed, name))
+    }
+
+    /// Check an entry against the limits.
+    pub fn check(&self, name: &str, compressed_size: u64, uncompressed_size: u64) -> Result<()> {
+        if let Some(limit) = self.max_size {
+            if uncompressed_size > limit && !self.is_allowed(name) {
+                return Err(Error::SizeLimitExceeded {
+                    name: name.to_string(),
+                    size: uncompressed_size,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = self.max_ratio {
+            let ratio = uncompressed_size / compressed_size.max(1);
+            if ratio > limit && !self.is_allowed(name) {
+                return Err(Error::RatioLimitExceeded {
+                    name: name.to_string(),
+                    ratio,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limits() {
+        let limits = DecompressionLimits::new();
+        assert!(limits.check("Data\\big.dds", 1, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let limits = DecompressionLimits::new().max_size(1000);
+        assert!(limits.check("Data\\a.xml", 500, 1000).is_ok());
+
+        let err = limits.check("Data\\a.xml", 500, 1001).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::SizeLimitExceeded {
+                size: 1001,
+                limit: 1000,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("Data\\a.xml"));
+    }
+
+    #[test]
+    fn test_ratio_limit() {
+        let limits = DecompressionLimits::new().max_ratio(100);
+        assert!(limits.check("a.bin", 10, 1000).is_ok());
+        assert!(limits.check("empty.bin", 0, 100).is_ok());
+
+        let err = limits.check("bomb.bin", 10, 1_000_000).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::RatioLimitExceeded {
+                ratio: 100_000,
+                limit: 100,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_allowed_entries() {
+        let limits = DecompressionLimits::new()
+            .max_size(10)
+            .max_ratio(2)
+            .allow("Data/Game2.dcb");
+        assert!(limits.is_allowed("data\\game2.DCB"));
+        assert!(limits.check("Data\\Game2.dcb", 1, 1 << 30).is_ok());
+        assert!(limits.check("Data\\Game.dcb", 1, 1 << 30).is_err());
+    }
+}
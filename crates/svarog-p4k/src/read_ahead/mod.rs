@@ -0,0 +1,375 @@
+//! Reading entries ahead of their decoding.
+//!
+//! Extraction from a hard disk is bound by seeks: decoder threads faulting
+//! pages of the memory map in one at a time leave the disk idle between
+//! requests. A [`ReadAhead`] keeps many reads in flight instead, in archive
+//! order, so the disk (or the kernel's elevator) can serve them back to back
+//! while earlier entries are decompressed.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use memmap2::Mmap;
+
+use crate::{Error, P4kArchive, Result};
+
+#[cfg(all(windows, feature = "queued-io"))]
+mod overlapped;
+#[cfg(all(target_os = "linux", feature = "queued-io"))]
+mod uring;
+
+/// Entries read ahead by default.
+const DEFAULT_DEPTH: usize = 64;
+
+/// Bytes read ahead by default.
+const DEFAULT_MAX_BYTES: usize = 256 << 20;
+
+/// How a [`ReadAhead`] fetches entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoBackend {
+    /// Decoders read from the memory map, with the kernel asked to page
+    /// queued entries in ahead of them.
+    #[default]
+    Mmap,
+    /// Linux io_uring (`queued-io` feature).
+    IoUring,
+    /// Windows overlapped I/O (`queued-io` feature).
+    Overlapped,
+}
+
+impl IoBackend {
+    /// The queued backend of this platform when built in, otherwise
+    /// [`Mmap`](Self::Mmap).
+    pub fn native() -> Self {
+        [Self::IoUring, Self::Overlapped]
+            .into_iter()
+            .find(|backend| backend.is_available())
+            .unwrap_or(Self::Mmap)
+    }
+
+    /// Whether this build supports the backend.
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Mmap => true,
+            Self::IoUring => cfg!(all(target_os = "linux", feature = "queued-io")),
+            Self::Overlapped => cfg!(all(windows, feature = "queued-io")),
+        }
+    }
+
+    /// Short name, e.g. `io_uring`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mmap => "mmap",
+            Self::IoUring => "io_uring",
+            Self::Overlapped => "overlapped",
+        }
+    }
+}
+
+impl fmt::Display for IoBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Settings of [`P4kArchive::read_ahead`].
+#[derive(Debug, Clone, Copy)]
+pub struct ReadAheadOptions {
+    backend: IoBackend,
+    depth: usize,
+    max_bytes: usize,
+}
+
+impl Default for ReadAheadOptions {
+    fn default() -> Self {
+        Self {
+            backend: IoBackend::default(),
+            depth: DEFAULT_DEPTH,
+            max_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+impl ReadAheadOptions {
+    /// Memory map backend, 64 entries or 256 MiB ahead.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch entries through `backend`.
+    pub fn backend(mut self, backend: IoBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Items to queue ahead of the one being consumed.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Stop queueing once this many bytes are in flight; a single larger
+    /// entry is still read.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+}
+
+/// An entry fetched by a [`ReadAhead`], ready for [`P4kArchive::decode`].
+#[derive(Debug)]
+pub struct RawEntry {
+    pub(crate) index: usize,
+    /// The local header and stored bytes of the entry, and where the stored
+    /// bytes are; `None` when decoding reads them from the memory map.
+    pub(crate) stored: Option<(Vec<u8>, Range<usize>)>,
+}
+
+impl RawEntry {
+    /// Index of the entry in the archive.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// Iterator over items with the entries they name read ahead, created by
+/// [`P4kArchive::read_ahead`].
+///
+/// Yields each item with its entry, or `None` for items naming no entry, in
+/// the order of the items.
+pub struct ReadAhead<'a, I: Iterator, F> {
+    archive: &'a P4kArchive,
+    items: I,
+    index_of: F,
+    options: ReadAheadOptions,
+    reader: Box<dyn Reader + 'a>,
+    queue: VecDeque<(I::Item, Option<Slot>)>,
+    queued_bytes: usize,
+    #[cfg(all(feature = "queued-io", any(target_os = "linux", windows)))]
+    next_key: u64,
+}
+
+impl<'a, I, F> ReadAhead<'a, I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> Option<usize>,
+{
+    pub(crate) fn new(
+        archive: &'a P4kArchive,
+        items: I,
+        index_of: F,
+        options: ReadAheadOptions,
+    ) -> Result<Self> {
+        let reader: Box<dyn Reader + 'a> = match options.backend {
+            IoBackend::Mmap => Box::new(MapReader {
+                mmap: archive.mmap(),
+            }),
+            #[cfg(all(target_os = "linux", feature = "queued-io"))]
+            IoBackend::IoUring => Box::new(uring::UringReader::new(archive.path(), options.depth)?),
+            #[cfg(all(windows, feature = "queued-io"))]
+            IoBackend::Overlapped => Box::new(overlapped::OverlappedReader::new(archive.path())?),
+            backend => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{} reads are not available in this build", backend),
+                )))
+            }
+        };
+
+        Ok(Self {
+            archive,
+            items,
+            index_of,
+            options,
+            reader,
+            queue: VecDeque::new(),
+            queued_bytes: 0,
+            #[cfg(all(feature = "queued-io", any(target_os = "linux", windows)))]
+            next_key: 0,
+        })
+    }
+
+    /// The backend reading the entries.
+    pub fn backend(&self) -> IoBackend {
+        self.options.backend
+    }
+
+    /// Queue items until the depth or byte limit is reached.
+    fn fill(&mut self) {
+        while self.queue.len() < self.options.depth
+            && (self.queued_bytes == 0 || self.queued_bytes < self.options.max_bytes)
+        {
+            let Some(item) = self.items.next() else {
+                break;
+            };
+            let slot = (self.index_of)(&item).map(|index| self.submit(index));
+            self.queue.push_back((item, slot));
+        }
+    }
+
+    fn submit(&mut self, index: usize) -> Slot {
+        let mut slot = Slot {
+            index,
+            #[cfg(all(feature = "queued-io", any(target_os = "linux", windows)))]
+            key: self.next_key,
+            offset: 0,
+            len: 0,
+            buffer: Vec::new(),
+            submitted: None,
+        };
+        #[cfg(all(feature = "queued-io", any(target_os = "linux", windows)))]
+        {
+            self.next_key += 1;
+        }
+        if let Some((offset, len)) = self.archive.read_span(index) {
+            slot.offset = offset;
+            slot.len = len;
+            self.queued_bytes += len;
+            slot.submitted = Some(self.reader.submit(&mut slot));
+        }
+        slot
+    }
+
+    fn finish(&mut self, mut slot: Slot) -> Result<RawEntry> {
+        self.queued_bytes -= slot.len;
+        if let Some(submitted) = slot.submitted.take() {
+            if let Err(e) = submitted.and_then(|()| self.reader.complete(&mut slot)) {
+                release(self.reader.as_mut(), slot);
+                return Err(e.into());
+            }
+        }
+        if slot.buffer.is_empty() {
+            return Ok(RawEntry {
+                index: slot.index,
+                stored: None,
+            });
+        }
+
+        // Extra fields longer than the slack leave the data short of the
+        // buffer; those entries are read from the memory map instead
+        let stored = self
+            .archive
+            .locate_stored(slot.index, &slot.buffer)
+            .ok()
+            .map(|range| (slot.buffer, range));
+        Ok(RawEntry {
+            index: slot.index,
+            stored,
+        })
+    }
+}
+
+impl<I, F> Iterator for ReadAhead<'_, I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> Option<usize>,
+{
+    type Item = (I::Item, Option<Result<RawEntry>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.fill();
+        let (item, slot) = self.queue.pop_front()?;
+        let raw = slot.map(|slot| self.finish(slot));
+        Some((item, raw))
+    }
+}
+
+impl<I: Iterator, F> Drop for ReadAhead<'_, I, F> {
+    fn drop(&mut self) {
+        // Reads still in flight write into buffers owned by the queue
+        let queue = std::mem::take(&mut self.queue);
+        for slot in queue.into_iter().filter_map(|(_, slot)| slot) {
+            release(self.reader.as_mut(), slot);
+        }
+    }
+}
+
+/// Drop a slot whose read may not have finished.
+///
+/// The read is cancelled first; if the backend cannot make sure it is over,
+/// the buffer is leaked rather than freed under the kernel.
+fn release(reader: &mut dyn Reader, mut slot: Slot) {
+    if reader.cancel(&mut slot).is_err() {
+        std::mem::forget(std::mem::take(&mut slot.buffer));
+    }
+}
+
+/// One entry being read.
+struct Slot {
+    index: usize,
+    /// Identifies the read to the queued backends
+    #[cfg(all(feature = "queued-io", any(target_os = "linux", windows)))]
+    key: u64,
+    offset: u64,
+    len: usize,
+    /// Filled by backends that read into memory; left empty by the map
+    buffer: Vec<u8>,
+    /// Outcome of submitting the read; `None` if there is nothing to read
+    submitted: Option<io::Result<()>>,
+}
+
+/// An I/O backend.
+trait Reader: Send {
+    /// Start reading `slot.len` bytes at `slot.offset`.
+    fn submit(&mut self, slot: &mut Slot) -> io::Result<()>;
+
+    /// Wait for a submitted read to finish.
+    ///
+    /// On error the read may still be in flight, and the slot has to go
+    /// through [`cancel`](Self::cancel) before its buffer is dropped.
+    fn complete(&mut self, slot: &mut Slot) -> io::Result<()>;
+
+    /// Stop a read that may still be in flight and wait until it no longer
+    /// writes to the slot's buffer; reads already over need nothing.
+    ///
+    /// On error the buffer may still be written to.
+    fn cancel(&mut self, slot: &mut Slot) -> io::Result<()>;
+}
+
+/// Leaves reading to the decoders, hinting the kernel to page entries in.
+struct MapReader<'a> {
+    mmap: &'a Mmap,
+}
+
+impl Reader for MapReader<'_> {
+    fn submit(&mut self, slot: &mut Slot) -> io::Result<()> {
+        #[cfg(unix)]
+        let _ = self
+            .mmap
+            .advise_range(memmap2::Advice::WillNeed, slot.offset as usize, slot.len);
+        #[cfg(not(unix))]
+        let _ = (&self.mmap, slot);
+        Ok(())
+    }
+
+    fn complete(&mut self, _slot: &mut Slot) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel(&mut self, _slot: &mut Slot) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_availability() {
+        assert!(IoBackend::Mmap.is_available());
+        assert!(IoBackend::native().is_available());
+        assert_eq!(IoBackend::default(), IoBackend::Mmap);
+        assert_eq!(IoBackend::IoUring.to_string(), "io_uring");
+    }
+
+    #[test]
+    fn test_options() {
+        let options = ReadAheadOptions::new().depth(0).max_bytes(1024);
+        assert_eq!(options.depth, 1);
+        assert_eq!(options.max_bytes, 1024);
+        assert_eq!(options.backend, IoBackend::Mmap);
+    }
+}
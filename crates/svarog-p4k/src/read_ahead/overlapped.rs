@@ -0,0 +1,161 @@
+//! Reads through Windows overlapped I/O.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::windows::fs::OpenOptionsExt;
+use std::os::windows::io::AsRawHandle;
+use std::path::Path;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{
+    CloseHandle, ERROR_IO_PENDING, FALSE, HANDLE, STATUS_PENDING, TRUE,
+};
+use windows_sys::Win32::Storage::FileSystem::{ReadFile, FILE_FLAG_OVERLAPPED};
+use windows_sys::Win32::System::Threading::CreateEventW;
+use windows_sys::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
+
+use super::{Reader, Slot};
+
+/// Reads issued on a file opened for overlapped I/O, each signalling its
+/// own event.
+pub(super) struct OverlappedReader {
+    file: File,
+    /// Reads in flight by slot key; the kernel writes to the `OVERLAPPED`
+    /// until the read finishes, so it is boxed
+    pending: HashMap<u64, Box<OVERLAPPED>>,
+}
+
+// SAFETY: the events and `OVERLAPPED`s are only touched through `&mut self`
+// and belong to reads on the reader's own file
+unsafe impl Send for OverlappedReader {}
+
+impl OverlappedReader {
+    pub(super) fn new(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .custom_flags(FILE_FLAG_OVERLAPPED)
+            .open(path)?;
+        Ok(Self {
+            file,
+            pending: HashMap::new(),
+        })
+    }
+
+    fn handle(&self) -> HANDLE {
+        self.file.as_raw_handle()
+    }
+
+    /// Start reading into `buffer` at `offset`.
+    ///
+    /// # Safety
+    ///
+    /// `buffer` must stay valid until a [`wait`](Self::wait) has seen the
+    /// read finish.
+    unsafe fn start(&self, buffer: &mut [u8], offset: u64) -> io::Result<Box<OVERLAPPED>> {
+        let mut overlapped: Box<OVERLAPPED> = Box::new(std::mem::zeroed());
+        overlapped.Anonymous.Anonymous.Offset = offset as u32;
+        overlapped.Anonymous.Anonymous.OffsetHigh = (offset >> 32) as u32;
+        overlapped.hEvent = CreateEventW(ptr::null(), TRUE, FALSE, ptr::null());
+        if overlapped.hEvent.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let len = buffer.len().min(u32::MAX as usize) as u32;
+        let ok = ReadFile(
+            self.handle(),
+            buffer.as_mut_ptr(),
+            len,
+            ptr::null_mut(),
+            &mut *overlapped,
+        );
+        if ok == FALSE {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(ERROR_IO_PENDING as i32) {
+                CloseHandle(overlapped.hEvent);
+                return Err(err);
+            }
+        }
+        Ok(overlapped)
+    }
+
+    /// Wait for the pending read of `key` to finish, returning the bytes
+    /// read.
+    ///
+    /// The read stays pending if the wait fails before it is over.
+    fn wait(&mut self, key: u64) -> io::Result<usize> {
+        let Some(overlapped) = self.pending.get(&key) else {
+            return Err(io::Error::other("no read in flight"));
+        };
+        let mut read = 0u32;
+        // SAFETY: `overlapped` comes from `start` on this file
+        let ok = unsafe { GetOverlappedResult(self.handle(), &**overlapped, &mut read, TRUE) };
+        let result = if ok == FALSE {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(read as usize)
+        };
+        if is_finished(overlapped) {
+            let overlapped = self.pending.remove(&key).unwrap();
+            // SAFETY: the read is over, so nothing refers to the event any more
+            unsafe { CloseHandle(overlapped.hEvent) };
+        }
+        result
+    }
+}
+
+/// Whether the kernel is done with a read, as `HasOverlappedIoCompleted`.
+fn is_finished(overlapped: &OVERLAPPED) -> bool {
+    // SAFETY: the kernel updates the status while the read is in flight
+    unsafe { ptr::read_volatile(&overlapped.Internal) != STATUS_PENDING as usize }
+}
+
+impl Reader for OverlappedReader {
+    fn submit(&mut self, slot: &mut Slot) -> io::Result<()> {
+        slot.buffer = vec![0; slot.len];
+        // SAFETY: the buffer is neither dropped nor reallocated before
+        // `complete` or `cancel` has seen this read finish
+        let overlapped = unsafe { self.start(&mut slot.buffer, slot.offset)? };
+        self.pending.insert(slot.key, overlapped);
+        Ok(())
+    }
+
+    fn complete(&mut self, slot: &mut Slot) -> io::Result<()> {
+        if !self.pending.contains_key(&slot.key) {
+            return Ok(());
+        }
+        let mut pos = self.wait(slot.key)?;
+
+        // Short reads are finished one read at a time
+        while pos < slot.len {
+            // SAFETY: as for `submit`
+            let overlapped =
+                unsafe { self.start(&mut slot.buffer[pos..], slot.offset + pos as u64)? };
+            self.pending.insert(slot.key, overlapped);
+            match self.wait(slot.key)? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                read => pos += read,
+            }
+        }
+        Ok(())
+    }
+
+    fn cancel(&mut self, slot: &mut Slot) -> io::Result<()> {
+        let Some(overlapped) = self.pending.get(&slot.key) else {
+            return Ok(());
+        };
+        // Fails if the read is already over, which the wait sees as well
+        // SAFETY: `overlapped` belongs to a read on this file
+        unsafe { CancelIoEx(self.handle(), &**overlapped) };
+        let result = self.wait(slot.key);
+
+        if let Some(overlapped) = self.pending.remove(&slot.key) {
+            // The kernel may still write to both
+            std::mem::forget(overlapped);
+            return Err(result
+                .err()
+                .unwrap_or_else(|| io::Error::other("read could not be cancelled")));
+        }
+        Ok(())
+    }
+}
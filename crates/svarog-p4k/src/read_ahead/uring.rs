@@ -0,0 +1,125 @@
+//! Reads through Linux io_uring.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use io_uring::{opcode, types, IoUring};
+
+use super::{Reader, Slot};
+
+/// Largest ring the kernel accepts.
+const MAX_ENTRIES: usize = 4096;
+
+/// User data of cancel requests; slot keys count up from zero.
+const CANCEL_KEY: u64 = u64::MAX;
+
+/// Reads submitted to one ring; the kernel finishes them in any order.
+pub(super) struct UringReader {
+    ring: IoUring,
+    file: File,
+    /// Keys of reads queued to the kernel that have not finished
+    in_flight: HashSet<u64>,
+    /// Results of finished reads not yet waited for, by slot key
+    finished: HashMap<u64, i32>,
+}
+
+impl UringReader {
+    pub(super) fn new(path: &Path, depth: usize) -> io::Result<Self> {
+        let entries = depth.clamp(1, MAX_ENTRIES).next_power_of_two();
+        Ok(Self {
+            ring: IoUring::new(entries as u32)?,
+            file: File::open(path)?,
+            in_flight: HashSet::new(),
+            finished: HashMap::new(),
+        })
+    }
+
+    /// Wait for at least one request to finish and collect the results.
+    fn reap(&mut self) -> io::Result<()> {
+        match self.ring.submit_and_wait(1) {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+        for entry in self.ring.completion() {
+            let key = entry.user_data();
+            if key != CANCEL_KEY {
+                self.in_flight.remove(&key);
+                self.finished.insert(key, entry.result());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Reader for UringReader {
+    fn submit(&mut self, slot: &mut Slot) -> io::Result<()> {
+        slot.buffer = vec![0; slot.len];
+        // Longer reads are finished in `complete`
+        let len = slot.len.min(u32::MAX as usize) as u32;
+        let read = opcode::Read::new(
+            types::Fd(self.file.as_raw_fd()),
+            slot.buffer.as_mut_ptr(),
+            len,
+        )
+        .offset(slot.offset)
+        .build()
+        .user_data(slot.key);
+
+        // SAFETY: the buffer is neither dropped nor reallocated before
+        // `complete` or `cancel` has seen this read finish
+        unsafe { self.ring.submission().push(&read) }
+            .map_err(|_| io::Error::other("io_uring submission queue is full"))?;
+        // Queued even if submitting fails, to go out with the next request
+        self.in_flight.insert(slot.key);
+        self.ring.submit()?;
+        Ok(())
+    }
+
+    fn complete(&mut self, slot: &mut Slot) -> io::Result<()> {
+        let result = loop {
+            if let Some(result) = self.finished.remove(&slot.key) {
+                break result;
+            }
+            if !self.in_flight.contains(&slot.key) {
+                return Err(io::Error::other("no read in flight"));
+            }
+            self.reap()?;
+        };
+        if result < 0 {
+            return Err(io::Error::from_raw_os_error(-result));
+        }
+
+        // Short reads are finished synchronously
+        let read = result as usize;
+        if read < slot.len {
+            self.file
+                .read_exact_at(&mut slot.buffer[read..], slot.offset + read as u64)?;
+        }
+        Ok(())
+    }
+
+    fn cancel(&mut self, slot: &mut Slot) -> io::Result<()> {
+        if self.in_flight.contains(&slot.key) {
+            let cancel = opcode::AsyncCancel::new(slot.key)
+                .build()
+                .user_data(CANCEL_KEY);
+            // Make room in case the queue is full of unsubmitted reads
+            self.ring.submit()?;
+            // SAFETY: cancel requests refer to no memory
+            unsafe { self.ring.submission().push(&cancel) }
+                .map_err(|_| io::Error::other("io_uring submission queue is full"))?;
+
+            // The read finishes either way, cancelled or not
+            while self.in_flight.contains(&slot.key) {
+                self.reap()?;
+            }
+        }
+        self.finished.remove(&slot.key);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Decoded audio.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Decoded audio as interleaved 16-bit samples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audio {
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Samples of all channels, interleaved frame by frame.
+    pub samples: Vec<i16>,
+}
+
+impl Audio {
+    /// Number of frames, i.e. samples per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / usize::from(self.channels.max(1))
+    }
+
+    /// Playing time.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / f64::from(self.sample_rate.max(1)))
+    }
+
+    /// Minimum and maximum sample of each of `buckets` equal stretches of
+    /// frames, over all channels, for drawing a waveform.
+    pub fn peaks(&self, buckets: usize) -> Vec<(i16, i16)> {
+        let channels = usize::from(self.channels.max(1));
+        let frames = self.frames();
+        if frames == 0 || buckets == 0 {
+            return Vec::new();
+        }
+
+        (0..buckets.min(frames))
+            .map(|bucket| {
+                let start = bucket * frames / buckets.min(frames);
+                let end = (bucket + 1) * frames / buckets.min(frames);
+                self.samples[start * channels..end * channels]
+                    .iter()
+                    .fold((i16::MAX, i16::MIN), |(min, max), &s| {
+                        (min.min(s), max.max(s))
+                    })
+            })
+            .collect()
+    }
+
+    /// Write as a 16-bit PCM WAV file.
+    pub fn write_wav<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let data_size = (self.samples.len() * 2) as u32;
+        let block_align = self.channels * 2;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_size).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&self.channels.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate * u32::from(block_align)).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_size.to_le_bytes())?;
+        for sample in &self.samples {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Wem;
+
+    #[test]
+    fn test_wav_round_trip() {
+        let audio = Audio {
+            channels: 2,
+            sample_rate: 22050,
+            samples: vec![1, -1, 300, -300, i16::MAX, i16::MIN],
+        };
+        let mut wav = Vec::new();
+        audio.write_wav(&mut wav).unwrap();
+        assert_eq!(wav.len(), 44 + 12);
+
+        let wem = Wem::parse(&wav).unwrap();
+        assert_eq!(wem.decode().unwrap(), audio);
+        assert_eq!(audio.duration(), Duration::from_secs_f64(3.0 / 22050.0));
+    }
+
+    #[test]
+    fn test_peaks() {
+        let audio = Audio {
+            channels: 1,
+            sample_rate: 8000,
+            samples: vec![0, 5, -3, 2, 9, -9, 1, 1],
+        };
+        assert_eq!(audio.peaks(4), [(0, 5), (-3, 2), (-9, 9), (1, 1)]);
+        assert_eq!(audio.peaks(100).len(), 8);
+        assert_eq!(audio.peaks(1), [(-9, 9)]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Bit-level reading and writing, least significant bit first, as Vorbis
+//! packs its packets.
+
+use crate::{Error, Result};
+
+/// Reads values of up to 32 bits from a byte slice.
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Read `bits` bits, at most 32.
+    pub(crate) fn read(&mut self, bits: u32) -> Result<u32> {
+        debug_assert!(bits <= 32);
+        if self.pos + bits as usize > self.data.len() * 8 {
+            return Err(Error::InvalidVorbis(format!(
+                "packet of {} bytes ends within a field",
+                self.data.len()
+            )));
+        }
+        let mut value = 0u32;
+        for i in 0..bits {
+            let byte = self.data[self.pos / 8];
+            value |= u32::from((byte >> (self.pos % 8)) & 1) << i;
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    /// Bits read so far.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+/// Writes values of up to 32 bits into a byte vector.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bytes: Vec<u8>,
+    /// Bits not yet written to `bytes`
+    acc: u64,
+    pending: u32,
+}
+
+impl BitWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the low `bits` bits of `value`, at most 32.
+    pub(crate) fn write(&mut self, value: u32, bits: u32) {
+        debug_assert!(bits <= 32);
+        let mask = (1u64 << bits) - 1;
+        self.acc |= (u64::from(value) & mask) << self.pending;
+        self.pending += bits;
+        while self.pending >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.pending -= 8;
+        }
+    }
+
+    /// Write a single bit.
+    pub(crate) fn flag(&mut self, flag: bool) {
+        self.write(u32::from(flag), 1);
+    }
+
+    /// Write whole bytes, at the current bit position.
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.pending == 0 {
+            self.bytes.extend_from_slice(bytes);
+        } else {
+            for &b in bytes {
+                self.write(u32::from(b), 8);
+            }
+        }
+    }
+
+    /// The written bytes, the last one padded with zero bits.
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        if self.pending > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Number of bits needed to store `v`, as Vorbis' `ilog`.
+pub(crate) fn ilog(v: u32) -> u32 {
+    32 - v.leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut writer = BitWriter::new();
+        writer.write(0b101, 3);
+        writer.flag(true);
+        writer.write(0x564342, 24);
+        writer.write_bytes(&[0xAB, 0xCD]);
+        writer.write(u32::MAX, 32);
+        let bytes = writer.finish();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(bytes[0] & 0x0F, 0b1101);
+
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read(3).unwrap(), 0b101);
+        assert_eq!(reader.read(1).unwrap(), 1);
+        assert_eq!(reader.read(24).unwrap(), 0x564342);
+        assert_eq!(reader.read(8).unwrap(), 0xAB);
+        assert_eq!(reader.read(8).unwrap(), 0xCD);
+        assert_eq!(reader.read(32).unwrap(), u32::MAX);
+        assert_eq!(reader.position(), 76);
+        assert!(reader.read(5).is_err());
+    }
+
+    #[test]
+    fn test_ilog() {
+        assert_eq!(ilog(0), 0);
+        assert_eq!(ilog(1), 1);
+        assert_eq!(ilog(7), 3);
+        assert_eq!(ilog(8), 4);
+    }
+}
@@ -0,0 +1,169 @@
+//! Wwise soundbanks.
+
+use svarog_common::{BinaryReader, ResultExt};
+
+use crate::{Error, Result};
+
+/// A WEM file embedded in a soundbank, from its `DIDX` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaEntry {
+    /// Media ID, also used as the file name of loose `.wem` files.
+    pub id: u32,
+    /// Offset in the `DATA` section.
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// A parsed soundbank (`.bnk`) and the WEM files embedded in it.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_wwise::{SoundBank, Wem};
+///
+/// let data = std::fs::read("weapons.bnk")?;
+/// let bank = SoundBank::parse(&data)?;
+/// for entry in bank.media() {
+///     if let Some(wem) = bank.wem(entry) {
+///         println!("{}: {}", entry.id, Wem::parse(wem)?.codec());
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SoundBank<'a> {
+    version: u32,
+    id: u32,
+    media: Vec<MediaEntry>,
+    data: &'a [u8],
+}
+
+impl<'a> SoundBank<'a> {
+    /// Parse the `BKHD`, `DIDX` and `DATA` sections of a soundbank.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut reader = BinaryReader::new(data);
+        let magic = reader.peek_bytes(4).in_section("BKHD", 0)?;
+        if magic != b"BKHD" {
+            return Err(Error::InvalidMagic {
+                expected: "BKHD",
+                actual: magic.try_into().unwrap_or_default(),
+            });
+        }
+
+        let mut header = None;
+        let mut index = None;
+        let mut media_data = None;
+        while reader.remaining() >= 8 {
+            let offset = reader.position();
+            let tag = reader.read_bytes(4).in_section("section header", offset)?;
+            let size = reader.read_u32().in_section("section header", offset)? as usize;
+            let body = reader.read_bytes(size).in_section("section", offset)?;
+            match tag {
+                b"BKHD" => header = Some(body),
+                b"DIDX" => index = Some(body),
+                b"DATA" => media_data = Some(body),
+                _ => {}
+            }
+        }
+
+        let mut reader = BinaryReader::new(header.ok_or(Error::MissingChunk("BKHD"))?);
+        let version = reader.read_u32().in_section("BKHD", 0)?;
+        let id = reader.read_u32().in_section("BKHD", 4)?;
+
+        let mut media = Vec::new();
+        if let Some(index) = index {
+            let mut reader = BinaryReader::new(index);
+            while reader.remaining() >= 12 {
+                let offset = reader.position();
+                media.push(MediaEntry {
+                    id: reader.read_u32().in_section("DIDX", offset)?,
+                    offset: reader.read_u32().in_section("DIDX", offset)?,
+                    size: reader.read_u32().in_section("DIDX", offset)?,
+                });
+            }
+        }
+
+        Ok(Self {
+            version,
+            id,
+            media,
+            data: media_data.unwrap_or_default(),
+        })
+    }
+
+    /// Soundbank format version.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Soundbank ID.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Embedded WEM files, in index order.
+    pub fn media(&self) -> &[MediaEntry] {
+        &self.media
+    }
+
+    /// Contents of an embedded WEM file, unless it lies outside the `DATA`
+    /// section.
+    pub fn wem(&self, entry: &MediaEntry) -> Option<&'a [u8]> {
+        let start = entry.offset as usize;
+        self.data
+            .get(start..start.checked_add(entry.size as usize)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wem::tests::build_wem;
+    use crate::Wem;
+
+    fn section(tag: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = tag.to_vec();
+        data.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn test_parse_soundbank() {
+        let first = build_wem(1, 1, 8000, 2, 16, &[1, 0, 2, 0]);
+        let second = build_wem(1, 1, 8000, 2, 16, &[3, 0]);
+
+        let mut didx = Vec::new();
+        let mut blob = Vec::new();
+        for (id, wem) in [(100u32, &first), (200, &second)] {
+            didx.extend_from_slice(&id.to_le_bytes());
+            didx.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+            didx.extend_from_slice(&(wem.len() as u32).to_le_bytes());
+            blob.extend_from_slice(wem);
+        }
+
+        let mut bkhd = 113u32.to_le_bytes().to_vec();
+        bkhd.extend_from_slice(&0xABCDu32.to_le_bytes());
+        let mut data = section(b"BKHD", &bkhd);
+        data.extend(section(b"DIDX", &didx));
+        data.extend(section(b"DATA", &blob));
+        data.extend(section(b"HIRC", &[0; 4]));
+
+        let bank = SoundBank::parse(&data).unwrap();
+        assert_eq!(bank.version(), 113);
+        assert_eq!(bank.id(), 0xABCD);
+        assert_eq!(bank.media().len(), 2);
+        assert_eq!(bank.media()[1].id, 200);
+
+        let wem = Wem::parse(bank.wem(&bank.media()[1]).unwrap()).unwrap();
+        assert_eq!(wem.decode().unwrap().samples, [3]);
+
+        let outside = MediaEntry {
+            id: 0,
+            offset: blob.len() as u32,
+            size: 1,
+        };
+        assert_eq!(bank.wem(&outside), None);
+        assert!(SoundBank::parse(&first).is_err());
+    }
+}
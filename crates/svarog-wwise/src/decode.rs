@@ -0,0 +1,142 @@
+//! PCM and IMA ADPCM decoding.
+
+use crate::{Error, Result};
+
+/// IMA ADPCM quantizer step sizes.
+const STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+/// IMA ADPCM step index changes, by the low three bits of a nibble.
+const INDEX_TABLE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+
+/// Bytes of the per-channel header in an IMA ADPCM block.
+const IMA_HEADER_SIZE: usize = 4;
+
+/// Decode little-endian PCM to 16-bit samples.
+pub(crate) fn pcm(data: &[u8], bits_per_sample: u16) -> Result<Vec<i16>> {
+    Ok(match bits_per_sample {
+        8 => data.iter().map(|&b| (i16::from(b) - 128) << 8).collect(),
+        16 => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect(),
+        // Keep the most significant bytes
+        24 => data
+            .chunks_exact(3)
+            .map(|b| i16::from_le_bytes([b[1], b[2]]))
+            .collect(),
+        bits => {
+            return Err(Error::InvalidFormat(format!(
+                "{}-bit PCM is not supported",
+                bits
+            )))
+        }
+    })
+}
+
+/// Samples per channel in one IMA ADPCM block: the header sample plus two
+/// per data byte.
+pub(crate) fn ima_samples_per_block(block_align: usize, channels: u16) -> usize {
+    let channel_size = block_align / usize::from(channels.max(1));
+    channel_size.saturating_sub(IMA_HEADER_SIZE) * 2 + 1
+}
+
+/// Decode Wwise IMA ADPCM to interleaved 16-bit samples.
+///
+/// Unlike Microsoft IMA ADPCM, each channel's part of a block is stored in
+/// one piece: a 4-byte header (initial sample, step index, reserved byte)
+/// followed by its nibbles, low nibble first.
+pub(crate) fn ima_adpcm(data: &[u8], block_align: usize, channels: u16) -> Result<Vec<i16>> {
+    let channel_count = usize::from(channels);
+    if channel_count == 0 || block_align < channel_count * (IMA_HEADER_SIZE + 1) {
+        return Err(Error::InvalidFormat(format!(
+            "IMA ADPCM block of {} bytes for {} channels",
+            block_align, channels
+        )));
+    }
+    let channel_size = block_align / channel_count;
+    let per_block = ima_samples_per_block(block_align, channels);
+
+    let blocks = data.len() / block_align;
+    let mut samples = vec![0i16; blocks * per_block * channel_count];
+    for (block_index, block) in data.chunks_exact(block_align).enumerate() {
+        let first = block_index * per_block * channel_count;
+        for channel in 0..channel_count {
+            let part = &block[channel * channel_size..(channel + 1) * channel_size];
+            let mut predictor = i32::from(i16::from_le_bytes([part[0], part[1]]));
+            let mut index = i32::from(part[2]).min(88);
+
+            let mut out = first + channel;
+            samples[out] = predictor as i16;
+            for &byte in &part[IMA_HEADER_SIZE..] {
+                for nibble in [byte & 0x0F, byte >> 4] {
+                    out += channel_count;
+                    expand_nibble(nibble, &mut predictor, &mut index);
+                    samples[out] = predictor as i16;
+                }
+            }
+        }
+    }
+    Ok(samples)
+}
+
+fn expand_nibble(nibble: u8, predictor: &mut i32, index: &mut i32) {
+    let step = STEP_TABLE[*index as usize];
+    let mut diff = step >> 3;
+    if nibble & 1 != 0 {
+        diff += step >> 2;
+    }
+    if nibble & 2 != 0 {
+        diff += step >> 1;
+    }
+    if nibble & 4 != 0 {
+        diff += step;
+    }
+    if nibble & 8 != 0 {
+        diff = -diff;
+    }
+    *predictor = (*predictor + diff).clamp(i32::from(i16::MIN), i32::from(i16::MAX));
+    *index = (*index + INDEX_TABLE[usize::from(nibble & 7)]).clamp(0, 88);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm_widths() {
+        assert_eq!(pcm(&[0x00, 0x80, 0xFF], 8).unwrap(), [-32768, 0, 32512]);
+        assert_eq!(pcm(&[0x34, 0x12, 0xFF], 16).unwrap(), [0x1234]);
+        assert_eq!(pcm(&[0xAA, 0x34, 0x12], 24).unwrap(), [0x1234]);
+        assert!(pcm(&[0; 4], 32).is_err());
+    }
+
+    #[test]
+    fn test_ima_adpcm() {
+        // Stereo blocks of 8 bytes per channel: 1 + 4 * 2 samples each
+        let block = [
+            0x10, 0x00, 0x00, 0x00, 0x77, 0x00, 0x00, 0x00, // left
+            0xF0, 0xFF, 0x58, 0x00, 0x88, 0x00, 0x00, 0x00, // right
+        ];
+        assert_eq!(ima_samples_per_block(block.len(), 2), 9);
+
+        let samples = ima_adpcm(&block, block.len(), 2).unwrap();
+        assert_eq!(samples.len(), 18);
+        let left: Vec<i16> = samples.iter().step_by(2).copied().collect();
+        let right: Vec<i16> = samples.iter().skip(1).step_by(2).copied().collect();
+
+        // Nibble 7 adds the whole step plus its halves: 7 -> 11, then 16 -> 30
+        assert_eq!(&left[..3], [16, 27, 57]);
+        // Zero nibbles add an eighth of the step while the step shrinks
+        assert!(left[3..].windows(2).all(|w| w[1] >= w[0]));
+        // Nibble 8 subtracts an eighth of the step: 32767 -> 4095, 29794 -> 3724
+        assert_eq!(&right[..3], [-16, -4111, -7835]);
+
+        assert!(ima_adpcm(&block, 4, 2).is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Error types for Wwise audio handling.
+
+use svarog_common::ErrorLocation;
+use thiserror::Error;
+
+use crate::Codec;
+
+/// Errors that can occur when working with Wwise audio.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Common library error.
+    #[error("{0}")]
+    Common(#[from] svarog_common::Error),
+
+    /// Invalid file magic.
+    #[error("invalid magic: expected {expected:?}, got {actual:?}")]
+    InvalidMagic {
+        expected: &'static str,
+        actual: [u8; 4],
+    },
+
+    /// A required chunk or section is missing.
+    #[error("missing '{0}' chunk")]
+    MissingChunk(&'static str),
+
+    /// Invalid `fmt ` chunk contents.
+    #[error("invalid format: {0}")]
+    InvalidFormat(String),
+
+    /// Malformed Wwise Vorbis packets or codebooks.
+    #[error("invalid Vorbis data: {0}")]
+    InvalidVorbis(String),
+
+    /// A setup header refers to a codebook missing from the library.
+    #[error("codebook {0} is missing from the library")]
+    MissingCodebook(u32),
+
+    /// Codec that cannot be decoded.
+    #[error("unsupported codec: {0}")]
+    UnsupportedCodec(Codec),
+}
+
+impl ErrorLocation for Error {
+    fn common(&self) -> Option<&svarog_common::Error> {
+        match self {
+            Error::Common(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Result type for Wwise audio operations.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,43 @@
+//! Wwise audio handling for Star Citizen.
+//!
+//! Game audio is stored as Wwise media: loose `.wem` files, named by media
+//! ID, and soundbanks (`.bnk`) that embed more of them.
+//!
+//! - [`Wem`] reads the RIFF header of a WEM file and decodes PCM and IMA
+//!   ADPCM audio to 16-bit samples
+//! - [`SoundBank`] lists and slices out the WEM files embedded in a bank
+//! - [`Audio`] holds decoded samples and writes them as a WAV file
+//!
+//! Wwise Vorbis, the codec of most game audio, is a Vorbis stream stripped
+//! of its headers. [`Wem::to_ogg`] rebuilds it as a standard Ogg Vorbis file,
+//! given the [`CodebookLibrary`] its setup header refers to, and with the
+//! `vorbis` feature [`Wem::decode_with`] decodes it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use svarog_wwise::Wem;
+//!
+//! let data = std::fs::read("sound.wem")?;
+//! let wem = Wem::parse(&data)?;
+//! if wem.codec().can_decode() {
+//!     let mut file = std::fs::File::create("sound.wav")?;
+//!     wem.decode()?.write_wav(&mut file)?;
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod audio;
+mod bits;
+mod bnk;
+mod decode;
+mod error;
+mod ogg;
+mod vorbis;
+mod wem;
+
+pub use audio::Audio;
+pub use bnk::{MediaEntry, SoundBank};
+pub use error::{Error, Result};
+pub use vorbis::{CodebookLibrary, Codebooks};
+pub use wem::{Codec, Wem};
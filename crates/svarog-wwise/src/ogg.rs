@@ -0,0 +1,140 @@
+//! Ogg pages around Vorbis packets.
+
+/// Serial number of the single logical stream.
+const SERIAL: u32 = 1;
+
+/// Most segments in one page.
+const MAX_SEGMENTS: usize = 255;
+
+/// Granule position of pages on which no packet ends.
+const NO_GRANULE: u64 = u64::MAX;
+
+/// Page header flags.
+const CONTINUED: u8 = 0x01;
+const FIRST: u8 = 0x02;
+const LAST: u8 = 0x04;
+
+/// CRC-32 of Ogg pages: polynomial 0x04C11DB7, no reflection, no final XOR.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, &b| {
+        (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ b) as usize]
+    })
+}
+
+/// Writes each packet on pages of its own.
+#[derive(Default)]
+pub(crate) struct OggWriter {
+    out: Vec<u8>,
+    sequence: u32,
+}
+
+impl OggWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write a packet ending at `granule`, on as many pages as it needs.
+    pub(crate) fn packet(&mut self, mut packet: &[u8], granule: u64, last: bool) {
+        let mut flags = 0;
+        loop {
+            // A packet ends on the first segment shorter than 255 bytes, so
+            // one of exactly 255 * N bytes needs an empty segment
+            let full = packet.len() / 255;
+            if full < MAX_SEGMENTS {
+                let mut lacing = vec![255u8; full];
+                lacing.push((packet.len() % 255) as u8);
+                if last {
+                    flags |= LAST;
+                }
+                self.page(flags, granule, &lacing, packet);
+                return;
+            }
+
+            let (head, rest) = packet.split_at(MAX_SEGMENTS * 255);
+            self.page(flags, NO_GRANULE, &[255; MAX_SEGMENTS], head);
+            packet = rest;
+            flags = CONTINUED;
+        }
+    }
+
+    fn page(&mut self, mut flags: u8, granule: u64, lacing: &[u8], payload: &[u8]) {
+        if self.sequence == 0 {
+            flags |= FIRST;
+        }
+        let start = self.out.len();
+        self.out.extend_from_slice(b"OggS");
+        self.out.push(0);
+        self.out.push(flags);
+        self.out.extend_from_slice(&granule.to_le_bytes());
+        self.out.extend_from_slice(&SERIAL.to_le_bytes());
+        self.out.extend_from_slice(&self.sequence.to_le_bytes());
+        // Checksum, filled in below
+        self.out.extend_from_slice(&[0; 4]);
+        self.out.push(lacing.len() as u8);
+        self.out.extend_from_slice(lacing);
+        self.out.extend_from_slice(payload);
+
+        let crc = crc32(&self.out[start..]);
+        self.out[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+        self.sequence += 1;
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc() {
+        assert_eq!(crc32(b"123456789"), 0x89A1_897F);
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut writer = OggWriter::new();
+        writer.packet(&[1; 10], 0, false);
+        writer.packet(&[2; 255 * 255], 100, true);
+        let out = writer.finish();
+
+        // First page: one packet of 10 bytes
+        assert_eq!(&out[..4], b"OggS");
+        assert_eq!(out[5], FIRST);
+        assert_eq!(out[26], 1);
+        assert_eq!(out[27], 10);
+        let second = 28 + 10;
+
+        // A packet filling 255 segments continues with an empty one
+        assert_eq!(out[second + 5], 0);
+        assert_eq!(&out[second + 6..second + 14], &NO_GRANULE.to_le_bytes());
+        assert_eq!(out[second + 26], 255);
+        let third = second + 27 + 255 + 255 * 255;
+        assert_eq!(out[third + 5], CONTINUED | LAST);
+        assert_eq!(&out[third + 6..third + 14], &100u64.to_le_bytes());
+        assert_eq!(&out[third + 18..third + 22], &2u32.to_le_bytes());
+        assert_eq!(&out[third + 26..], &[1, 0]);
+    }
+}
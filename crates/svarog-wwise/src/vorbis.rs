@@ -0,0 +1,839 @@
+//! Wwise Vorbis to Ogg Vorbis.
+//!
+//! Wwise strips a Vorbis stream down before storing it: the identification
+//! and comment headers are replaced by fields of the `fmt ` chunk, the setup
+//! header loses its fixed fields and packs its codebooks (or refers to them
+//! by id in a library shared by all files), and audio packets lose their
+//! packet type bit and window flags. The rebuild below restores a standard
+//! stream, following ww2ogg, and recomputes granule positions as revorb does.
+
+use std::fmt;
+use std::path::Path;
+
+use crate::bits::{ilog, BitReader, BitWriter};
+use crate::ogg::OggWriter;
+#[cfg(feature = "vorbis")]
+use crate::Audio;
+use crate::{Error, Result, Wem};
+
+/// Vendor string of the rebuilt comment header.
+const VENDOR: &str = "converted from Audiokinetic Wwise by svarog";
+
+/// Sync pattern that starts every Vorbis codebook.
+const CODEBOOK_SYNC: u32 = 0x56_4342;
+
+/// Vorbis header packet types.
+const IDENTIFICATION: u8 = 1;
+const COMMENT: u8 = 3;
+const SETUP: u8 = 5;
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::InvalidVorbis(message.into())
+}
+
+/// Packed codebooks that Wwise setup headers refer to by id, such as
+/// `packed_codebooks_aoTuV_603.bin` distributed with ww2ogg.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_wwise::{CodebookLibrary, Codebooks, Wem};
+///
+/// let codebooks = CodebookLibrary::open("packed_codebooks_aoTuV_603.bin")?;
+/// let data = std::fs::read("sound.wem")?;
+/// let ogg = Wem::parse(&data)?.to_ogg(Codebooks::Library(&codebooks))?;
+/// std::fs::write("sound.ogg", ogg)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct CodebookLibrary {
+    data: Vec<u8>,
+    /// Start of each codebook, then the end of the last one
+    offsets: Vec<usize>,
+}
+
+impl CodebookLibrary {
+    /// Parse a library: the codebooks back to back, then the offset of each
+    /// as a little-endian `u32`, ending with the offset of that table.
+    pub fn parse(mut data: Vec<u8>) -> Result<Self> {
+        let malformed = || invalid("malformed codebook library");
+        let len = data.len();
+        if len < 4 {
+            return Err(malformed());
+        }
+        let table = u32::from_le_bytes(data[len - 4..].try_into().unwrap()) as usize;
+        if table > len - 4 || (len - table) % 4 != 0 {
+            return Err(malformed());
+        }
+
+        let offsets: Vec<usize> = data[table..]
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+            .collect();
+        if offsets.windows(2).any(|pair| pair[0] > pair[1]) || offsets.last() != Some(&table) {
+            return Err(malformed());
+        }
+
+        data.truncate(table);
+        Ok(Self { data, offsets })
+    }
+
+    /// Read and parse a library file.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = std::fs::read(path).map_err(svarog_common::Error::from)?;
+        Self::parse(data)
+    }
+
+    /// Number of codebooks.
+    pub fn len(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// Whether the library holds no codebooks.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The packed codebook with the given id.
+    pub fn get(&self, id: u32) -> Option<&[u8]> {
+        let id = id as usize;
+        let start = *self.offsets.get(id)?;
+        let end = *self.offsets.get(id + 1)?;
+        self.data.get(start..end)
+    }
+}
+
+impl fmt::Debug for CodebookLibrary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodebookLibrary")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Where the codebooks of a Wwise Vorbis setup header come from.
+#[derive(Debug, Clone, Copy)]
+pub enum Codebooks<'a> {
+    /// Ids into a codebook library, as most Wwise versions write them.
+    Library(&'a CodebookLibrary),
+    /// Packed codebooks in the setup header itself.
+    Inline,
+}
+
+/// Vorbis fields that Wwise keeps in the `fmt ` chunk or a `vorb` chunk.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VorbisInfo {
+    pub(crate) sample_count: u32,
+    /// Audio packets lack their packet type bit and window flags
+    mod_packets: bool,
+    /// Packet headers carry a granule position after the size
+    granule_headers: bool,
+    setup_offset: usize,
+    audio_offset: usize,
+    blocksize_pows: [u8; 2],
+}
+
+impl VorbisInfo {
+    /// Parse the `vorb` chunk, or the `vorb` data embedded in an extended
+    /// `fmt ` chunk when there is none.
+    pub(crate) fn parse(format: &[u8], vorb: Option<&[u8]>) -> Result<Self> {
+        let (vorb, embedded) = match vorb {
+            Some(vorb) => (vorb, false),
+            None if format.len() == 0x42 => (&format[0x18..], true),
+            None => return Err(Error::MissingChunk("vorb")),
+        };
+        let truncated = || Error::InvalidFormat(format!("vorb data of {} bytes", vorb.len()));
+        let u32_at = |offset: usize| {
+            vorb.get(offset..offset + 4)
+                .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                .ok_or_else(truncated)
+        };
+        let u8_at = |offset: usize| vorb.get(offset).copied().ok_or_else(truncated);
+
+        let info = match (embedded, vorb.len()) {
+            (true, _) | (false, 0x2A) => Self {
+                sample_count: u32_at(0x00)?,
+                mod_packets: !matches!(u32_at(0x04)?, 0x4A | 0x4B | 0x69 | 0x70),
+                granule_headers: false,
+                setup_offset: u32_at(0x10)? as usize,
+                audio_offset: u32_at(0x14)? as usize,
+                blocksize_pows: [u8_at(0x28)?, u8_at(0x29)?],
+            },
+            (false, 0x32 | 0x34) => Self {
+                sample_count: u32_at(0x00)?,
+                mod_packets: false,
+                granule_headers: true,
+                setup_offset: u32_at(0x18)? as usize,
+                audio_offset: u32_at(0x1C)? as usize,
+                blocksize_pows: [u8_at(0x30)?, u8_at(0x31)?],
+            },
+            (false, size) => {
+                return Err(Error::InvalidFormat(format!(
+                    "unsupported vorb chunk of {} bytes",
+                    size
+                )))
+            }
+        };
+
+        let [short, long] = info.blocksize_pows;
+        if !(6..=13).contains(&short) || !(6..=13).contains(&long) || short > long {
+            return Err(invalid(format!("block sizes 2^{} and 2^{}", short, long)));
+        }
+        Ok(info)
+    }
+}
+
+/// A packet of the `data` chunk, behind its Wwise packet header.
+struct Packet<'a> {
+    payload: &'a [u8],
+    /// Offset of the packet after this one
+    next: usize,
+}
+
+impl<'a> Packet<'a> {
+    fn read(data: &'a [u8], offset: usize, info: &VorbisInfo) -> Result<Self> {
+        let header = if info.granule_headers { 6 } else { 2 };
+        let size = data
+            .get(offset..offset + 2)
+            .map(|b| usize::from(u16::from_le_bytes([b[0], b[1]])))
+            .ok_or_else(|| invalid(format!("packet header at {} is truncated", offset)))?;
+        let start = offset + header;
+        let payload = data
+            .get(start..start + size)
+            .ok_or_else(|| invalid(format!("packet at {} is truncated", offset)))?;
+        Ok(Self {
+            payload,
+            next: start + size,
+        })
+    }
+}
+
+/// The rebuilt setup header, and the window of each mode.
+struct Setup {
+    packet: Vec<u8>,
+    mode_blockflags: Vec<bool>,
+    mode_bits: u32,
+}
+
+/// Write the common header of a Vorbis header packet.
+fn header(w: &mut BitWriter, kind: u8) {
+    w.write(u32::from(kind), 8);
+    w.write_bytes(b"vorbis");
+}
+
+/// Copy a field of `bits` bits, returning its value.
+fn copy(r: &mut BitReader<'_>, w: &mut BitWriter, bits: u32) -> Result<u32> {
+    let value = r.read(bits)?;
+    w.write(value, bits);
+    Ok(value)
+}
+
+fn identification(wem: &Wem<'_>, info: &VorbisInfo) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    header(&mut w, IDENTIFICATION);
+    // Version
+    w.write(0, 32);
+    w.write(u32::from(wem.channels()), 8);
+    w.write(wem.sample_rate(), 32);
+    // Maximum, nominal and minimum bitrate
+    w.write(0, 32);
+    w.write(wem.avg_bytes_per_second().wrapping_mul(8), 32);
+    w.write(0, 32);
+    w.write(u32::from(info.blocksize_pows[0]), 4);
+    w.write(u32::from(info.blocksize_pows[1]), 4);
+    // Framing
+    w.flag(true);
+    w.finish()
+}
+
+fn comment() -> Vec<u8> {
+    let mut w = BitWriter::new();
+    header(&mut w, COMMENT);
+    w.write(VENDOR.len() as u32, 32);
+    w.write_bytes(VENDOR.as_bytes());
+    // No user comments
+    w.write(0, 32);
+    w.flag(true);
+    w.finish()
+}
+
+/// Rebuild a Wwise setup header, stripped of its fixed fields and with
+/// packed codebooks.
+fn setup(packet: &[u8], channels: u16, codebooks: Codebooks<'_>) -> Result<Setup> {
+    let mut r = BitReader::new(packet);
+    let mut w = BitWriter::new();
+    header(&mut w, SETUP);
+
+    let codebook_count = copy(&mut r, &mut w, 8)? + 1;
+    for _ in 0..codebook_count {
+        match codebooks {
+            Codebooks::Inline => codebook(&mut r, &mut w)?,
+            Codebooks::Library(library) => {
+                let id = r.read(10)?;
+                let packed = library.get(id).ok_or(Error::MissingCodebook(id))?;
+                let mut cr = BitReader::new(packed);
+                codebook(&mut cr, &mut w)?;
+                if cr.position() / 8 + 1 != packed.len() {
+                    return Err(invalid(format!(
+                        "codebook {} of {} bytes does not match the library",
+                        id,
+                        packed.len()
+                    )));
+                }
+            }
+        }
+    }
+
+    // A single placeholder time domain transform
+    w.write(0, 6);
+    w.write(0, 16);
+
+    let floor_count = copy(&mut r, &mut w, 6)? + 1;
+    for _ in 0..floor_count {
+        floor(&mut r, &mut w, codebook_count)?;
+    }
+
+    let residue_count = copy(&mut r, &mut w, 6)? + 1;
+    for _ in 0..residue_count {
+        residue(&mut r, &mut w, codebook_count)?;
+    }
+
+    let mapping_count = copy(&mut r, &mut w, 6)? + 1;
+    for _ in 0..mapping_count {
+        mapping(&mut r, &mut w, channels, floor_count, residue_count)?;
+    }
+
+    let mode_count = copy(&mut r, &mut w, 6)? + 1;
+    let mut mode_blockflags = Vec::with_capacity(mode_count as usize);
+    for _ in 0..mode_count {
+        mode_blockflags.push(copy(&mut r, &mut w, 1)? != 0);
+        // Window and transform types
+        w.write(0, 16);
+        w.write(0, 16);
+        if copy(&mut r, &mut w, 8)? >= mapping_count {
+            return Err(invalid("mode refers to a missing mapping"));
+        }
+    }
+    // Framing
+    w.flag(true);
+
+    if r.position().div_ceil(8) != packet.len() {
+        return Err(invalid(format!(
+            "setup header of {} bytes has {} bits of trailing data",
+            packet.len(),
+            packet.len() * 8 - r.position()
+        )));
+    }
+
+    Ok(Setup {
+        packet: w.finish(),
+        mode_blockflags,
+        mode_bits: ilog(mode_count - 1),
+    })
+}
+
+/// Unpack a codebook to its Vorbis form.
+fn codebook(r: &mut BitReader<'_>, w: &mut BitWriter) -> Result<()> {
+    let dimensions = r.read(4)?;
+    let entries = r.read(14)?;
+    w.write(CODEBOOK_SYNC, 24);
+    w.write(dimensions, 16);
+    w.write(entries, 24);
+
+    if copy(r, w, 1)? != 0 {
+        // Ordered: runs of entries per codeword length
+        copy(r, w, 5)?;
+        let mut entry = 0;
+        while entry < entries {
+            entry += copy(r, w, ilog(entries - entry))?;
+        }
+        if entry > entries {
+            return Err(invalid("codebook lengths overrun its entries"));
+        }
+    } else {
+        let length_bits = r.read(3)?;
+        if length_bits == 0 || length_bits > 5 {
+            return Err(invalid(format!("codeword lengths of {} bits", length_bits)));
+        }
+        let sparse = copy(r, w, 1)? != 0;
+        for _ in 0..entries {
+            if !sparse || copy(r, w, 1)? != 0 {
+                w.write(r.read(length_bits)?, 5);
+            }
+        }
+    }
+
+    let lookup_type = r.read(1)?;
+    w.write(lookup_type, 4);
+    if lookup_type == 1 {
+        // Minimum and delta value
+        copy(r, w, 32)?;
+        copy(r, w, 32)?;
+        let value_bits = copy(r, w, 4)? + 1;
+        // Sequence flag
+        copy(r, w, 1)?;
+        for _ in 0..quantvals(entries, dimensions)? {
+            copy(r, w, value_bits)?;
+        }
+    }
+    Ok(())
+}
+
+/// Number of values of a lookup type 1 codebook: the largest integer whose
+/// `dimensions`th power does not exceed `entries`.
+fn quantvals(entries: u32, dimensions: u32) -> Result<u32> {
+    if entries == 0 || dimensions == 0 {
+        return Err(invalid("lookup codebook without entries"));
+    }
+    let power =
+        |base: u32| (0..dimensions).fold(1u64, |acc, _| acc.saturating_mul(u64::from(base)));
+    let bits = ilog(entries);
+    let mut vals = entries >> ((bits - 1) * (dimensions - 1) / dimensions);
+    loop {
+        if power(vals) <= u64::from(entries) && power(vals + 1) > u64::from(entries) {
+            return Ok(vals);
+        }
+        if power(vals) > u64::from(entries) {
+            vals -= 1;
+        } else {
+            vals += 1;
+        }
+    }
+}
+
+fn check_book(book: u32, codebook_count: u32) -> Result<()> {
+    if book >= codebook_count {
+        return Err(invalid(format!("reference to missing codebook {}", book)));
+    }
+    Ok(())
+}
+
+fn floor(r: &mut BitReader<'_>, w: &mut BitWriter, codebook_count: u32) -> Result<()> {
+    // Only floor type 1 is used
+    w.write(1, 16);
+
+    let partitions = copy(r, w, 5)?;
+    let mut partition_classes = Vec::with_capacity(partitions as usize);
+    for _ in 0..partitions {
+        partition_classes.push(copy(r, w, 4)? as usize);
+    }
+
+    let class_count = partition_classes.iter().max().map_or(0, |max| max + 1);
+    let mut class_dimensions = Vec::with_capacity(class_count);
+    for _ in 0..class_count {
+        class_dimensions.push(copy(r, w, 3)? + 1);
+        let subclasses = copy(r, w, 2)?;
+        if subclasses != 0 {
+            check_book(copy(r, w, 8)?, codebook_count)?;
+        }
+        for _ in 0..1 << subclasses {
+            let book = copy(r, w, 8)?;
+            if book != 0 {
+                check_book(book - 1, codebook_count)?;
+            }
+        }
+    }
+
+    // Multiplier
+    copy(r, w, 2)?;
+    let range_bits = copy(r, w, 4)?;
+    for class in partition_classes {
+        for _ in 0..class_dimensions[class] {
+            copy(r, w, range_bits)?;
+        }
+    }
+    Ok(())
+}
+
+fn residue(r: &mut BitReader<'_>, w: &mut BitWriter, codebook_count: u32) -> Result<()> {
+    let residue_type = r.read(2)?;
+    if residue_type > 2 {
+        return Err(invalid(format!("residue type {}", residue_type)));
+    }
+    w.write(residue_type, 16);
+
+    // Begin, end and partition size
+    copy(r, w, 24)?;
+    copy(r, w, 24)?;
+    copy(r, w, 24)?;
+    let classifications = copy(r, w, 6)? + 1;
+    check_book(copy(r, w, 8)?, codebook_count)?;
+
+    let mut cascades = Vec::with_capacity(classifications as usize);
+    for _ in 0..classifications {
+        let low = copy(r, w, 3)?;
+        let high = if copy(r, w, 1)? != 0 {
+            copy(r, w, 5)?
+        } else {
+            0
+        };
+        cascades.push(high << 3 | low);
+    }
+    for cascade in cascades {
+        for bit in 0..8 {
+            if cascade & (1 << bit) != 0 {
+                check_book(copy(r, w, 8)?, codebook_count)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn mapping(
+    r: &mut BitReader<'_>,
+    w: &mut BitWriter,
+    channels: u16,
+    floor_count: u32,
+    residue_count: u32,
+) -> Result<()> {
+    let channels = u32::from(channels);
+    // Only mapping type 0 is used
+    w.write(0, 16);
+
+    let submaps = if copy(r, w, 1)? != 0 {
+        copy(r, w, 4)? + 1
+    } else {
+        1
+    };
+
+    if copy(r, w, 1)? != 0 {
+        let coupling_steps = copy(r, w, 8)? + 1;
+        let bits = ilog(channels - 1);
+        for _ in 0..coupling_steps {
+            let magnitude = copy(r, w, bits)?;
+            let angle = copy(r, w, bits)?;
+            if magnitude == angle || magnitude >= channels || angle >= channels {
+                return Err(invalid("invalid channel coupling"));
+            }
+        }
+    }
+
+    if copy(r, w, 2)? != 0 {
+        return Err(invalid("mapping reserved field is set"));
+    }
+    if submaps > 1 {
+        for _ in 0..channels {
+            if copy(r, w, 4)? >= submaps {
+                return Err(invalid("channel refers to a missing submap"));
+            }
+        }
+    }
+    for _ in 0..submaps {
+        // Time configuration
+        copy(r, w, 8)?;
+        if copy(r, w, 8)? >= floor_count {
+            return Err(invalid("submap refers to a missing floor"));
+        }
+        if copy(r, w, 8)? >= residue_count {
+            return Err(invalid("submap refers to a missing residue"));
+        }
+    }
+    Ok(())
+}
+
+/// Mode of an audio packet, from its first byte; `mod_packets` packets
+/// lack the leading packet type bit.
+fn packet_mode(payload: &[u8], setup: &Setup, mod_packets: bool) -> Result<usize> {
+    let mut r = BitReader::new(payload);
+    if !mod_packets && r.read(1)? != 0 {
+        return Err(invalid("header packet among audio packets"));
+    }
+    let mode = r.read(setup.mode_bits)? as usize;
+    if mode >= setup.mode_blockflags.len() {
+        return Err(invalid(format!("audio packet in missing mode {}", mode)));
+    }
+    Ok(mode)
+}
+
+/// Rebuild a Wwise Vorbis stream as Ogg Vorbis.
+pub(crate) fn to_ogg(wem: &Wem<'_>, codebooks: Codebooks<'_>) -> Result<Vec<u8>> {
+    let info = VorbisInfo::parse(wem.format(), wem.vorb())?;
+    let data = wem.data();
+
+    let mut ogg = OggWriter::new();
+    ogg.packet(&identification(wem, &info), 0, false);
+    ogg.packet(&comment(), 0, false);
+
+    let setup_packet = Packet::read(data, info.setup_offset, &info)?;
+    if setup_packet.next != info.audio_offset {
+        return Err(invalid("audio does not start after the setup header"));
+    }
+    let setup = setup(setup_packet.payload, wem.channels(), codebooks)?;
+    ogg.packet(&setup.packet, 0, false);
+
+    let blocksizes = info.blocksize_pows.map(|pow| 1u64 << pow);
+    let mut offset = info.audio_offset;
+    let mut previous_long = false;
+    let mut previous_size = None;
+    let mut granule = 0;
+    // Each packet is written once the next one is known, to flag the last
+    let mut pending: Option<Vec<u8>> = None;
+    while offset < data.len() {
+        let packet = Packet::read(data, offset, &info)?;
+        offset = packet.next;
+        if packet.payload.is_empty() {
+            continue;
+        }
+
+        let mode = packet_mode(packet.payload, &setup, info.mod_packets)?;
+        let long = setup.mode_blockflags[mode];
+        let rebuilt = if info.mod_packets {
+            let next_long = Packet::read(data, packet.next, &info)
+                .ok()
+                .filter(|next| !next.payload.is_empty())
+                .map(|next| packet_mode(next.payload, &setup, true))
+                .transpose()?
+                .is_some_and(|next| setup.mode_blockflags[next]);
+
+            let mut w = BitWriter::new();
+            // Audio packet type
+            w.flag(false);
+            w.write(mode as u32, setup.mode_bits);
+            if long {
+                w.flag(previous_long);
+                w.flag(next_long);
+            }
+            w.write(
+                u32::from(packet.payload[0]) >> setup.mode_bits,
+                8 - setup.mode_bits,
+            );
+            w.write_bytes(&packet.payload[1..]);
+            w.finish()
+        } else {
+            packet.payload.to_vec()
+        };
+
+        // A packet completes the second half of the previous window and the
+        // first half of its own; the first one completes no samples
+        let size = blocksizes[usize::from(long)];
+        if let Some(previous) = previous_size {
+            if let Some(pending) = pending.replace(rebuilt) {
+                ogg.packet(&pending, granule, false);
+            }
+            granule += previous / 4 + size / 4;
+        } else {
+            pending = Some(rebuilt);
+        }
+        previous_size = Some(size);
+        previous_long = long;
+    }
+
+    let pending = pending.ok_or_else(|| invalid("no audio packets"))?;
+    // The last window is cut at the end of the audio
+    ogg.packet(&pending, granule.min(u64::from(info.sample_count)), true);
+    Ok(ogg.finish())
+}
+
+/// Decode an Ogg Vorbis stream to interleaved 16-bit samples.
+#[cfg(feature = "vorbis")]
+pub(crate) fn decode(ogg: &[u8]) -> Result<Audio> {
+    use lewton::inside_ogg::OggStreamReader;
+
+    let mut reader =
+        OggStreamReader::new(std::io::Cursor::new(ogg)).map_err(|e| invalid(e.to_string()))?;
+    let channels = u16::from(reader.ident_hdr.audio_channels);
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    let mut samples = Vec::new();
+    while let Some(packet) = reader
+        .read_dec_packet_itl()
+        .map_err(|e| invalid(e.to_string()))?
+    {
+        samples.extend(packet);
+    }
+    Ok(Audio {
+        channels,
+        sample_rate,
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wem::tests::build_wem_chunks;
+
+    /// A Wwise setup header with one codebook, either packed inline or by
+    /// library id 0, two modes (short and long) and one of everything else.
+    fn wwise_setup(inline: bool) -> Vec<u8> {
+        let mut w = BitWriter::new();
+        // One codebook
+        w.write(0, 8);
+        if inline {
+            write_packed_codebook(&mut w);
+        } else {
+            w.write(0, 10);
+        }
+        // One floor with one partition of class 0
+        w.write(0, 6);
+        w.write(1, 5);
+        w.write(0, 4);
+        w.write(0, 3);
+        w.write(0, 2);
+        w.write(0, 8);
+        w.write(1, 2);
+        w.write(4, 4);
+        w.write(9, 4);
+        // One residue
+        w.write(0, 6);
+        w.write(0, 2);
+        w.write(0, 24);
+        w.write(0, 24);
+        w.write(0, 24);
+        w.write(0, 6);
+        w.write(0, 8);
+        w.write(1, 3);
+        w.write(0, 1);
+        w.write(0, 8);
+        // One mapping
+        w.write(0, 6);
+        w.write(0, 1);
+        w.write(0, 1);
+        w.write(0, 2);
+        w.write(0, 8);
+        w.write(0, 8);
+        w.write(0, 8);
+        // Two modes, short and long
+        w.write(1, 6);
+        w.write(0, 1);
+        w.write(0, 8);
+        w.write(1, 1);
+        w.write(0, 8);
+        w.finish()
+    }
+
+    /// A packed codebook: one dimension, two entries of length 1.
+    fn write_packed_codebook(w: &mut BitWriter) {
+        w.write(1, 4);
+        w.write(2, 14);
+        w.write(0, 1);
+        w.write(1, 3);
+        w.write(0, 1);
+        w.write(0, 1);
+        w.write(0, 1);
+        w.write(0, 1);
+    }
+
+    fn packed_codebook() -> Vec<u8> {
+        let mut w = BitWriter::new();
+        write_packed_codebook(&mut w);
+        w.finish()
+    }
+
+    /// A WEM file with a `vorb` chunk, the setup header and three audio
+    /// packets: long, short, long.
+    fn wwise_vorbis(inline: bool) -> Vec<u8> {
+        let setup = wwise_setup(inline);
+        let mut data = Vec::new();
+        for packet in [&setup[..], &[0x01, 0xAA], &[0x00], &[0xFF, 0x55]] {
+            data.extend_from_slice(&(packet.len() as u16).to_le_bytes());
+            data.extend_from_slice(packet);
+        }
+
+        let mut vorb = vec![0; 0x2A];
+        vorb[0x00..0x04].copy_from_slice(&1000u32.to_le_bytes());
+        vorb[0x14..0x18].copy_from_slice(&(setup.len() as u32 + 2).to_le_bytes());
+        vorb[0x28] = 8;
+        vorb[0x29] = 11;
+
+        let mut fmt = vec![0; 0x18];
+        fmt[0..2].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        fmt[2..4].copy_from_slice(&1u16.to_le_bytes());
+        fmt[4..8].copy_from_slice(&48000u32.to_le_bytes());
+        fmt[8..12].copy_from_slice(&16000u32.to_le_bytes());
+        build_wem_chunks(&[(b"fmt ", &fmt), (b"vorb", &vorb), (b"data", &data)])
+    }
+
+    /// Packets of an Ogg stream with the granule position of their page.
+    fn ogg_packets(mut ogg: &[u8]) -> Vec<(Vec<u8>, u64)> {
+        let mut packets = Vec::new();
+        let mut packet = Vec::new();
+        while !ogg.is_empty() {
+            assert_eq!(&ogg[..4], b"OggS");
+            let granule = u64::from_le_bytes(ogg[6..14].try_into().unwrap());
+            let segments = usize::from(ogg[26]);
+            let lacing = &ogg[27..27 + segments];
+            let mut body = &ogg[27 + segments..];
+            for &len in lacing {
+                packet.extend_from_slice(&body[..usize::from(len)]);
+                body = &body[usize::from(len)..];
+                if len < 255 {
+                    packets.push((std::mem::take(&mut packet), granule));
+                }
+            }
+            ogg = body;
+        }
+        packets
+    }
+
+    #[test]
+    fn test_to_ogg() {
+        let data = wwise_vorbis(true);
+        let wem = Wem::parse(&data).unwrap();
+        assert_eq!(wem.sample_count(), Some(1000));
+
+        let ogg = wem.to_ogg(Codebooks::Inline).unwrap();
+        let packets = ogg_packets(&ogg);
+        assert_eq!(packets.len(), 6);
+
+        let (identification, _) = &packets[0];
+        assert_eq!(&identification[..7], b"\x01vorbis");
+        assert_eq!(identification[11], 1);
+        assert_eq!(&identification[12..16], &48000u32.to_le_bytes());
+        assert_eq!(&identification[20..24], &128_000u32.to_le_bytes());
+        assert_eq!(identification[28], 0xB8);
+        assert!(packets[1]
+            .0
+            .windows(VENDOR.len())
+            .any(|w| w == VENDOR.as_bytes()));
+
+        let mut r = BitReader::new(&packets[2].0);
+        assert_eq!(r.read(8).unwrap(), u32::from(SETUP));
+        for _ in 0..6 {
+            r.read(8).unwrap();
+        }
+        assert_eq!(r.read(8).unwrap(), 0);
+        assert_eq!(r.read(24).unwrap(), CODEBOOK_SYNC);
+        assert_eq!(r.read(16).unwrap(), 1);
+        assert_eq!(r.read(24).unwrap(), 2);
+
+        // Each packet gains a type bit, and long ones the window flags of
+        // their neighbours: none here, as the first is followed by a short
+        // packet and the last preceded by one
+        assert_eq!(packets[3], (vec![0x02, 0x50, 0x05], 0));
+        assert_eq!(packets[4], (vec![0x00, 0x00], 576));
+        // The last is cut to the sample count
+        assert_eq!(packets[5], (vec![0xF2, 0xAF, 0x02], 1000));
+    }
+
+    #[test]
+    fn test_codebook_library() {
+        let codebook = packed_codebook();
+        let mut data = codebook.clone();
+        data.extend_from_slice(&[0xEE; 3]);
+        let table = data.len() as u32;
+        for offset in [0, codebook.len() as u32, table] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+
+        let library = CodebookLibrary::parse(data).unwrap();
+        assert_eq!(library.len(), 2);
+        assert_eq!(library.get(0), Some(&codebook[..]));
+        assert_eq!(library.get(1), Some(&[0xEE; 3][..]));
+        assert_eq!(library.get(2), None);
+        assert!(CodebookLibrary::parse(vec![0xFF; 8]).is_err());
+
+        let inline = Wem::parse(&wwise_vorbis(true))
+            .unwrap()
+            .to_ogg(Codebooks::Inline)
+            .unwrap();
+        let data = wwise_vorbis(false);
+        let wem = Wem::parse(&data).unwrap();
+        assert_eq!(wem.to_ogg(Codebooks::Library(&library)).unwrap(), inline);
+
+        let empty = CodebookLibrary::parse(0u32.to_le_bytes().to_vec()).unwrap();
+        assert!(empty.is_empty());
+        assert!(matches!(
+            wem.to_ogg(Codebooks::Library(&empty)),
+            Err(Error::MissingCodebook(0))
+        ));
+    }
+}
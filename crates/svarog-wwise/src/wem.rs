@@ -0,0 +1,363 @@
+//! WEM audio files.
+
+use std::fmt;
+use std::time::Duration;
+
+use svarog_common::{BinaryReader, ResultExt};
+
+use crate::vorbis::{self, Codebooks, VorbisInfo};
+use crate::{decode, Audio, Error, Result};
+
+/// `fmt ` format tags used by Wwise.
+const TAG_PCM: u16 = 0x0001;
+const TAG_IMA_ADPCM: u16 = 0x0002;
+const TAG_EXTENSIBLE: u16 = 0xFFFE;
+const TAG_VORBIS: u16 = 0xFFFF;
+
+/// Audio codec of a WEM file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Uncompressed little-endian PCM.
+    Pcm,
+    /// Wwise's IMA ADPCM variant.
+    ImaAdpcm,
+    /// Wwise Vorbis, with stripped headers and packed codebooks.
+    Vorbis,
+    /// Any other format tag.
+    Other(u16),
+}
+
+impl Codec {
+    fn from_tag(tag: u16) -> Self {
+        match tag {
+            TAG_PCM | TAG_EXTENSIBLE => Codec::Pcm,
+            TAG_IMA_ADPCM => Codec::ImaAdpcm,
+            TAG_VORBIS => Codec::Vorbis,
+            tag => Codec::Other(tag),
+        }
+    }
+
+    /// Whether [`Wem::decode`] supports the codec.
+    pub fn can_decode(self) -> bool {
+        matches!(self, Codec::Pcm | Codec::ImaAdpcm)
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Codec::Pcm => f.write_str("PCM"),
+            Codec::ImaAdpcm => f.write_str("IMA ADPCM"),
+            Codec::Vorbis => f.write_str("Wwise Vorbis"),
+            Codec::Other(tag) => write!(f, "format 0x{:04X}", tag),
+        }
+    }
+}
+
+/// A parsed WEM file: a RIFF/WAVE container holding Wwise audio.
+///
+/// # Example
+///
+/// ```no_run
+/// use svarog_wwise::Wem;
+///
+/// let data = std::fs::read("sound.wem")?;
+/// let wem = Wem::parse(&data)?;
+/// println!("{}, {} Hz, {} channels", wem.codec(), wem.sample_rate(), wem.channels());
+///
+/// let mut wav = Vec::new();
+/// wem.decode()?.write_wav(&mut wav)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Wem<'a> {
+    codec: Codec,
+    channels: u16,
+    sample_rate: u32,
+    avg_bytes_per_second: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+    format: &'a [u8],
+    vorb: Option<&'a [u8]>,
+    data: &'a [u8],
+}
+
+impl<'a> Wem<'a> {
+    /// Parse the `fmt `, `vorb` and `data` chunks of a WEM file.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut reader = BinaryReader::new(data);
+        let magic = reader.read_bytes(4).in_section("RIFF header", 0)?;
+        if magic != b"RIFF" {
+            return Err(Error::InvalidMagic {
+                expected: "RIFF",
+                actual: magic.try_into().unwrap_or_default(),
+            });
+        }
+        reader.read_u32().in_section("RIFF header", 4)?;
+        let form = reader.read_bytes(4).in_section("RIFF header", 8)?;
+        if form != b"WAVE" {
+            return Err(Error::InvalidMagic {
+                expected: "WAVE",
+                actual: form.try_into().unwrap_or_default(),
+            });
+        }
+
+        let mut format = None;
+        let mut vorb = None;
+        let mut samples = None;
+        while reader.remaining() >= 8 {
+            let offset = reader.position();
+            let id = reader.read_bytes(4).in_section("chunk header", offset)?;
+            let size = reader.read_u32().in_section("chunk header", offset)? as usize;
+            // The last chunk may be cut short of its declared size
+            let body = reader.read_bytes(size.min(reader.remaining()))?;
+            match id {
+                b"fmt " => format = Some(body),
+                b"vorb" => vorb = Some(body),
+                b"data" => samples = Some(body),
+                _ => {}
+            }
+            // Chunks are padded to an even size
+            reader.advance(size % 2);
+        }
+
+        let format = format.ok_or(Error::MissingChunk("fmt "))?;
+        let data = samples.ok_or(Error::MissingChunk("data"))?;
+
+        let mut reader = BinaryReader::new(format);
+        let tag = reader.read_u16().in_section("fmt", 0)?;
+        let channels = reader.read_u16().in_section("fmt", 2)?;
+        let sample_rate = reader.read_u32().in_section("fmt", 4)?;
+        let avg_bytes_per_second = reader.read_u32().in_section("fmt", 8)?;
+        let block_align = reader.read_u16().in_section("fmt", 12)?;
+        let bits_per_sample = reader.read_u16().in_section("fmt", 14)?;
+
+        if channels == 0 || sample_rate == 0 {
+            return Err(Error::InvalidFormat(format!(
+                "{} channels at {} Hz",
+                channels, sample_rate
+            )));
+        }
+
+        Ok(Self {
+            codec: Codec::from_tag(tag),
+            channels,
+            sample_rate,
+            avg_bytes_per_second,
+            block_align,
+            bits_per_sample,
+            format,
+            vorb,
+            data,
+        })
+    }
+
+    /// Audio codec.
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    /// Number of channels.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Average bytes per second, as stored in the `fmt ` chunk.
+    pub fn avg_bytes_per_second(&self) -> u32 {
+        self.avg_bytes_per_second
+    }
+
+    /// Bytes per block of all channels.
+    pub fn block_align(&self) -> u16 {
+        self.block_align
+    }
+
+    /// Bits per sample, as stored in the `fmt ` chunk.
+    pub fn bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+
+    /// Contents of the `fmt ` chunk.
+    pub fn format(&self) -> &'a [u8] {
+        self.format
+    }
+
+    /// Contents of the `vorb` chunk, which older Wwise Vorbis files have.
+    pub fn vorb(&self) -> Option<&'a [u8]> {
+        self.vorb
+    }
+
+    /// Contents of the `data` chunk.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Number of samples per channel, for codecs that can be decoded.
+    pub fn sample_count(&self) -> Option<u64> {
+        if self.codec == Codec::Vorbis {
+            let info = VorbisInfo::parse(self.format, self.vorb).ok()?;
+            return Some(u64::from(info.sample_count));
+        }
+        let block_align = usize::from(self.block_align);
+        if block_align == 0 {
+            return None;
+        }
+        let blocks = (self.data.len() / block_align) as u64;
+        match self.codec {
+            Codec::Pcm => Some(blocks),
+            Codec::ImaAdpcm => {
+                Some(blocks * decode::ima_samples_per_block(block_align, self.channels) as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Playing time, for codecs that can be decoded.
+    pub fn duration(&self) -> Option<Duration> {
+        let samples = self.sample_count()?;
+        Some(Duration::from_secs_f64(
+            samples as f64 / f64::from(self.sample_rate),
+        ))
+    }
+
+    /// Decode to interleaved 16-bit samples.
+    ///
+    /// PCM and IMA ADPCM are supported. Wwise Vorbis is not, as its setup
+    /// header usually refers to codebooks outside the file; see
+    /// [`Wem::decode_with`].
+    pub fn decode(&self) -> Result<Audio> {
+        let samples = match self.codec {
+            Codec::Pcm => decode::pcm(self.data, self.bits_per_sample)?,
+            Codec::ImaAdpcm => {
+                decode::ima_adpcm(self.data, usize::from(self.block_align), self.channels)?
+            }
+            codec => return Err(Error::UnsupportedCodec(codec)),
+        };
+        Ok(Audio {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples,
+        })
+    }
+
+    /// Decode to interleaved 16-bit samples, rebuilding Wwise Vorbis with
+    /// `codebooks` first.
+    ///
+    /// Wwise Vorbis needs the `vorbis` feature; other codecs decode as with
+    /// [`Wem::decode`].
+    pub fn decode_with(&self, codebooks: Codebooks<'_>) -> Result<Audio> {
+        match self.codec {
+            #[cfg(feature = "vorbis")]
+            Codec::Vorbis => vorbis::decode(&self.to_ogg(codebooks)?),
+            _ => {
+                let _ = codebooks;
+                self.decode()
+            }
+        }
+    }
+
+    /// Rebuild Wwise Vorbis as a standard Ogg Vorbis file.
+    ///
+    /// Wwise packs the codebooks of the setup header, most versions into a
+    /// library shared by all files; `codebooks` says where to find them.
+    pub fn to_ogg(&self, codebooks: Codebooks<'_>) -> Result<Vec<u8>> {
+        if self.codec != Codec::Vorbis {
+            return Err(Error::UnsupportedCodec(self.codec));
+        }
+        vorbis::to_ogg(self, codebooks)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A WEM file with the given `fmt ` fields and `data` chunk.
+    pub(crate) fn build_wem(
+        tag: u16,
+        channels: u16,
+        sample_rate: u32,
+        block_align: u16,
+        bits: u16,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&tag.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&(sample_rate * u32::from(block_align)).to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+        build_wem_chunks(&[(b"fmt ", &fmt), (b"data", data)])
+    }
+
+    /// A WEM file with the given chunks.
+    pub(crate) fn build_wem_chunks(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let mut body = b"WAVE".to_vec();
+        for &(id, chunk) in chunks {
+            body.extend_from_slice(id);
+            body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            body.extend_from_slice(chunk);
+            if chunk.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        file.extend_from_slice(&body);
+        file
+    }
+
+    #[test]
+    fn test_parse_pcm() {
+        let samples: Vec<u8> = [0i16, 1000, -1000, i16::MAX, i16::MIN, 5]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let data = build_wem(TAG_EXTENSIBLE, 2, 48000, 4, 16, &samples);
+
+        let wem = Wem::parse(&data).unwrap();
+        assert_eq!(wem.codec(), Codec::Pcm);
+        assert_eq!(wem.channels(), 2);
+        assert_eq!(wem.sample_rate(), 48000);
+        assert_eq!(wem.sample_count(), Some(3));
+
+        let audio = wem.decode().unwrap();
+        assert_eq!(audio.samples, [0, 1000, -1000, i16::MAX, i16::MIN, 5]);
+        assert_eq!(audio.frames(), 3);
+    }
+
+    #[test]
+    fn test_vorbis_without_vorb() {
+        let data = build_wem(TAG_VORBIS, 1, 48000, 0, 0, &[0; 16]);
+        let wem = Wem::parse(&data).unwrap();
+        assert_eq!(wem.codec(), Codec::Vorbis);
+        assert_eq!(wem.sample_count(), None);
+        assert!(matches!(
+            wem.decode(),
+            Err(Error::UnsupportedCodec(Codec::Vorbis))
+        ));
+        assert!(matches!(
+            wem.to_ogg(Codebooks::Inline),
+            Err(Error::MissingChunk("vorb"))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_wem() {
+        assert!(matches!(
+            Wem::parse(b"RIFX\0\0\0\0WAVE"),
+            Err(Error::InvalidMagic { .. })
+        ));
+        assert!(matches!(
+            Wem::parse(b"RIFF\x04\0\0\0WAVE"),
+            Err(Error::MissingChunk("fmt "))
+        ));
+    }
+}
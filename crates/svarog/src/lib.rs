@@ -10,7 +10,11 @@
 //! - [`svarog_cryxml`] - CryXmlB binary XML parsing
 //! - [`svarog_datacore`] - DataCore database (`.dcb`) parsing
 //! - [`svarog_chf`] - Character head file (`.chf`) handling
-//! - [`svarog_dds`] - DDS texture mipmap merging
+//! - [`svarog_dds`] - DDS texture mipmap merging, decoding and encoding
+//! - [`svarog_wwise`] - Wwise audio (`.wem`/`.bnk`) decoding
+//!
+//! The [`process`] module converts extracted files on their way to disk:
+//! CryXmlB decoding, SOCPAK expansion, DataCore export and DDS merging.
 //!
 //! # Example
 //!
@@ -38,11 +42,15 @@ pub use svarog_cryxml as cryxml;
 pub use svarog_datacore as datacore;
 pub use svarog_dds as dds;
 pub use svarog_p4k as p4k;
+pub use svarog_wwise as wwise;
+
+#[cfg(feature = "full")]
+pub mod process;
 
 /// Prelude module for convenient imports.
 pub mod prelude {
     pub use svarog_chf::{ChfData, ChfFile, Dna, FacePart, ItemPort, Material, NameHash};
-    pub use svarog_common::{crc, BinaryReader, CigGuid};
+    pub use svarog_common::{crc, BinaryReader, CigGuid, ErrorLocation};
     pub use svarog_cryxml::CryXml;
     pub use svarog_datacore::{DataCoreDatabase, XmlExporter};
     pub use svarog_dds::merge_dds;
@@ -0,0 +1,662 @@
+//! Post-processing of extracted files.
+//!
+//! A [`Pipeline`] turns the raw contents of archive entries into the files
+//! written to disk. Each [`Processor`] claims files by extension or magic
+//! bytes and replaces them with converted ones: CryXmlB becomes text XML,
+//! SOCPAKs expand into a directory, DataCore databases export one file per
+//! record. Files a processor writes go through the pipeline again, so the
+//! CryXmlB inside a SOCPAK is decoded too.
+//!
+//! Steps that need the whole output tree, such as merging split DDS
+//! textures, run once every file is written, through
+//! [`Pipeline::finish`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::path::{Path, PathBuf};
+//!
+//! use svarog::p4k::P4kArchive;
+//! use svarog::process::{CryXmlProcessor, Output, Pipeline, SocpakProcessor};
+//!
+//! struct Disk;
+//!
+//! impl Output for Disk {
+//!     fn write(&mut self, path: PathBuf, data: Vec<u8>) {
+//!         std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+//!         std::fs::write(path, data).unwrap();
+//!     }
+//!
+//!     fn warn(&mut self, message: String) {
+//!         eprintln!("{}", message);
+//!     }
+//! }
+//!
+//! let pipeline = Pipeline::new()
+//!     .with(SocpakProcessor)
+//!     .with(CryXmlProcessor);
+//!
+//! let archive = P4kArchive::open("Data.p4k")?;
+//! let entry = archive.find("Data\\Objects\\Spaceships\\Ships\\AEGS\\Gladius.socpak").unwrap();
+//! let path = Path::new("out").join(entry.name.replace('\\', "/"));
+//! pipeline.process(path, archive.read(&entry)?, &mut Disk);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use svarog_cryxml::CryXml;
+use svarog_datacore::{
+    ContentHasher, DataCoreDatabase, ExportManifest, ExporterRegistry, XmlExporter,
+};
+
+/// Error of a processor.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Nesting depth after which files are written unprocessed, against
+/// archives that contain themselves.
+const MAX_DEPTH: usize = 8;
+
+/// Receives the files and warnings of a [`Pipeline`].
+pub trait Output {
+    /// Write a finished file.
+    fn write(&mut self, path: PathBuf, data: Vec<u8>);
+
+    /// Report a problem that did not stop processing.
+    fn warn(&mut self, message: String);
+
+    /// Called after `processor` converted the file at `path`, once the
+    /// files it wrote have been passed on.
+    fn processed(&mut self, processor: &str, path: &Path) {
+        let _ = (processor, path);
+    }
+}
+
+/// A conversion applied to extracted files.
+pub trait Processor: Send + Sync {
+    /// Short name, e.g. `cryxml`.
+    fn name(&self) -> &str;
+
+    /// Extensions of the files this processor handles, lowercase without
+    /// the dot.
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Leading bytes of the files this processor handles, or empty.
+    fn magic(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Whether this processor handles a file, by default if its extension
+    /// or magic bytes match.
+    fn matches(&self, path: &Path, data: &[u8]) -> bool {
+        let extension_matches = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions().iter().any(|x| x.eq_ignore_ascii_case(e)));
+        let magic = self.magic();
+        extension_matches || (!magic.is_empty() && data.starts_with(magic))
+    }
+
+    /// Write the files that replace the file at `path` to `output`.
+    ///
+    /// Files written at `path` itself are passed on unprocessed; all others
+    /// go through the pipeline again.
+    ///
+    /// On error, the pipeline writes the original file instead, after any
+    /// files this processor already wrote.
+    fn process(&self, path: &Path, data: &[u8], output: &mut dyn Output) -> Result<(), Error> {
+        let _ = (path, data, output);
+        Ok(())
+    }
+
+    /// Run once all files below `output_dir` are written.
+    fn finish(&self, output_dir: &Path) -> Result<Option<FinishReport>, Error> {
+        let _ = output_dir;
+        Ok(None)
+    }
+}
+
+/// Outcome of [`Processor::finish`].
+#[derive(Debug, Clone, Default)]
+pub struct FinishReport {
+    /// One line describing what was done.
+    pub summary: String,
+    /// Named counts, e.g. `("merged", 12)`.
+    pub counts: Vec<(&'static str, u64)>,
+    /// Problems with individual files.
+    pub warnings: Vec<String>,
+}
+
+/// An ordered list of processors; the first one matching a file handles it.
+#[derive(Default)]
+pub struct Pipeline {
+    processors: Vec<Box<dyn Processor>>,
+}
+
+impl Pipeline {
+    /// Create a pipeline that writes files unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a processor after the existing ones.
+    pub fn with(mut self, processor: impl Processor + 'static) -> Self {
+        self.push(Box::new(processor));
+        self
+    }
+
+    /// Add a boxed processor after the existing ones.
+    pub fn push(&mut self, processor: Box<dyn Processor>) {
+        self.processors.push(processor);
+    }
+
+    /// The processors, in order.
+    pub fn processors(&self) -> impl Iterator<Item = &dyn Processor> {
+        self.processors.iter().map(|p| p.as_ref())
+    }
+
+    /// Whether no processor is configured.
+    pub fn is_empty(&self) -> bool {
+        self.processors.is_empty()
+    }
+
+    /// Process the file at `path` and write the results to `output`.
+    pub fn process(&self, path: PathBuf, data: Vec<u8>, output: &mut dyn Output) {
+        self.process_at(path, data, 0, output);
+    }
+
+    fn process_at(&self, path: PathBuf, data: Vec<u8>, depth: usize, output: &mut dyn Output) {
+        let processor = self
+            .processors
+            .iter()
+            .find(|p| depth < MAX_DEPTH && p.matches(&path, &data));
+        let Some(processor) = processor else {
+            output.write(path, data);
+            return;
+        };
+
+        let mut nested = Nested {
+            pipeline: self,
+            source: &path,
+            depth: depth + 1,
+            output: &mut *output,
+        };
+        match processor.process(&path, &data, &mut nested) {
+            Ok(()) => output.processed(processor.name(), &path),
+            Err(e) => {
+                output.warn(format!(
+                    "Failed to process {} ({}): {}",
+                    path.display(),
+                    processor.name(),
+                    e
+                ));
+                output.write(path, data);
+            }
+        }
+    }
+
+    /// Run the [`finish`](Processor::finish) step of every processor on
+    /// `output_dir`, returning each processor's name and result.
+    pub fn finish(&self, output_dir: &Path) -> Vec<(&str, Result<Option<FinishReport>, Error>)> {
+        self.processors
+            .iter()
+            .map(|p| (p.name(), p.finish(output_dir)))
+            .collect()
+    }
+}
+
+/// Feeds the files a processor writes back into the pipeline.
+struct Nested<'a> {
+    pipeline: &'a Pipeline,
+    /// Path of the file being processed
+    source: &'a Path,
+    depth: usize,
+    output: &'a mut dyn Output,
+}
+
+impl Output for Nested<'_> {
+    fn write(&mut self, path: PathBuf, data: Vec<u8>) {
+        // The converted file itself, e.g. the database a DataCore export keeps
+        if path == self.source {
+            self.output.write(path, data);
+            return;
+        }
+        self.pipeline
+            .process_at(path, data, self.depth, &mut *self.output);
+    }
+
+    fn warn(&mut self, message: String) {
+        self.output.warn(message);
+    }
+
+    fn processed(&mut self, processor: &str, path: &Path) {
+        self.output.processed(processor, path);
+    }
+}
+
+/// Decodes CryXmlB files to text XML in place.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CryXmlProcessor;
+
+impl Processor for CryXmlProcessor {
+    fn name(&self) -> &str {
+        "cryxml"
+    }
+
+    fn magic(&self) -> &[u8] {
+        b"CryXmlB\0"
+    }
+
+    fn process(&self, path: &Path, data: &[u8], output: &mut dyn Output) -> Result<(), Error> {
+        let xml = CryXml::parse(data)?.to_xml_string()?;
+        output.write(path.to_path_buf(), xml.into_bytes());
+        Ok(())
+    }
+}
+
+/// Expands SOCPAKs, which are ZIP archives, into a directory named after
+/// them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SocpakProcessor;
+
+impl Processor for SocpakProcessor {
+    fn name(&self) -> &str {
+        "socpak"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["socpak"]
+    }
+
+    fn process(&self, path: &Path, data: &[u8], output: &mut dyn Output) -> Result<(), Error> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+        let dir = path.with_extension("");
+
+        // Read everything first so a corrupt archive writes nothing
+        let mut files = Vec::with_capacity(archive.len());
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            if file.is_dir() {
+                continue;
+            }
+            let Some(file_path) = enclosed_path(&dir, file.name()) else {
+                output.warn(format!(
+                    "Skipping {} in {}: path leaves the SOCPAK",
+                    file.name(),
+                    path.display()
+                ));
+                continue;
+            };
+            let mut contents = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut contents)?;
+            files.push((file_path, contents));
+        }
+
+        for (path, contents) in files {
+            output.write(path, contents);
+        }
+        Ok(())
+    }
+}
+
+/// Path of the archive member `name` below `dir`, or `None` if it would
+/// leave `dir`.
+///
+/// Both `/` and `\` separate components, as SOCPAKs use `\` on every
+/// platform. Names that are absolute, carry a drive prefix or contain `..`
+/// are rejected.
+pub fn enclosed_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    if name.starts_with(['/', '\\']) {
+        return None;
+    }
+    let mut path = dir.to_path_buf();
+    let mut components = 0;
+    for component in name.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => return None,
+            c if c.contains([':', '\0']) => return None,
+            c => {
+                path.push(c);
+                components += 1;
+            }
+        }
+    }
+    (components > 0).then_some(path)
+}
+
+/// Exports the main records of DataCore databases, one file per record.
+///
+/// Records are written below [`root`](Self::root), by default the
+/// database's directory, at their file names with the format's extension.
+/// With a [`manifest`](Self::manifest), the content hash of every exported
+/// record is written to an [`ExportManifest`] below the root, and an
+/// [`incremental`](Self::incremental) export skips records whose hash and
+/// file are unchanged since.
+#[derive(Debug, Clone)]
+pub struct DataCoreProcessor {
+    format: String,
+    root: Option<PathBuf>,
+    manifest: bool,
+    incremental: bool,
+}
+
+impl Default for DataCoreProcessor {
+    fn default() -> Self {
+        Self {
+            format: "xml".to_string(),
+            root: None,
+            manifest: false,
+            incremental: false,
+        }
+    }
+}
+
+impl DataCoreProcessor {
+    /// Create a processor exporting XML next to the database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Export format, one of the [`ExporterRegistry::with_builtin`] names.
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Directory the record files are written below.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Write an [`ExportManifest`] of the exported records below the root.
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Skip records that the manifest below the root lists as current and
+    /// whose file exists. Implies [`manifest`](Self::manifest).
+    pub fn incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+}
+
+impl Processor for DataCoreProcessor {
+    fn name(&self) -> &str {
+        "datacore"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dcb"]
+    }
+
+    fn process(&self, path: &Path, data: &[u8], output: &mut dyn Output) -> Result<(), Error> {
+        let database = DataCoreDatabase::parse(data)?;
+        let registry = ExporterRegistry::with_builtin(&database);
+        let exporter = registry
+            .get(&self.format)
+            .ok_or_else(|| format!("unknown export format {}", self.format))?;
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+
+        // XML keeps the hashes of `XmlExporter::export_incremental`, so both
+        // share a manifest
+        let hasher = match self.format.as_str() {
+            "xml" => XmlExporter::new(&database).content_hasher(),
+            format => ContentHasher::new(&database).with_seed(format.as_bytes()),
+        };
+        let mut manifest = if self.incremental {
+            ExportManifest::load(&root)?
+        } else {
+            ExportManifest::new()
+        };
+
+        for record in database.all_main_records() {
+            let file_name = record.file_name().unwrap_or("unknown.xml");
+            let record_path = exporter.path(record);
+            let record_file = root.join(&record_path);
+            let hash =
+                (self.manifest || self.incremental).then(|| hasher.hash_record(record.raw()));
+            if let Some(hash) = hash {
+                if self.incremental && manifest.is_current(file_name, hash) && record_file.exists()
+                {
+                    continue;
+                }
+            }
+
+            match exporter.export(record) {
+                Ok(contents) => {
+                    output.write(record_file, contents);
+                    if let Some(hash) = hash {
+                        manifest.insert(file_name, hash);
+                    }
+                }
+                Err(e) => {
+                    output.warn(format!("Failed to export {}: {}", record_path, e));
+                    manifest.remove(file_name);
+                }
+            }
+        }
+        if self.manifest || self.incremental {
+            output.write(
+                root.join(ExportManifest::FILE_NAME),
+                manifest.to_text().into_bytes(),
+            );
+        }
+        // The database itself is kept
+        output.write(path.to_path_buf(), data.to_vec());
+        Ok(())
+    }
+}
+
+/// Merges split DDS textures (`.dds.N`) into their base files once the
+/// output tree is complete.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DdsMergeProcessor {
+    delete_parts: bool,
+}
+
+impl DdsMergeProcessor {
+    /// Create a processor that keeps the merged parts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delete the `.N` parts once their base file is merged.
+    pub fn delete_parts(mut self, delete: bool) -> Self {
+        self.delete_parts = delete;
+        self
+    }
+}
+
+impl Processor for DdsMergeProcessor {
+    fn name(&self) -> &str {
+        "dds"
+    }
+
+    fn finish(&self, output_dir: &Path) -> Result<Option<FinishReport>, Error> {
+        let options = svarog_dds::MergeTreeOptions::new().delete_parts(self.delete_parts);
+        let report = svarog_dds::merge_dds_tree(output_dir, &options)?;
+
+        let warnings = report
+            .failures()
+            .filter_map(|failure| {
+                let e = failure.status.as_ref().err()?;
+                Some(format!("Failed to merge {}: {}", failure.path.display(), e))
+            })
+            .collect();
+        Ok(Some(FinishReport {
+            summary: format!(
+                "Merged {} textures from {} parts ({} already merged, {} errors)",
+                report.merged(),
+                report.parts(),
+                report.already_merged(),
+                report.failed()
+            ),
+            counts: vec![
+                ("merged", report.merged() as u64),
+                ("parts", report.parts() as u64),
+                ("already_merged", report.already_merged() as u64),
+                ("errors", report.failed() as u64),
+            ],
+            warnings,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Collect {
+        files: Vec<(PathBuf, Vec<u8>)>,
+        warnings: Vec<String>,
+        processed: Vec<String>,
+    }
+
+    impl Output for Collect {
+        fn write(&mut self, path: PathBuf, data: Vec<u8>) {
+            self.files.push((path, data));
+        }
+
+        fn warn(&mut self, message: String) {
+            self.warnings.push(message);
+        }
+
+        fn processed(&mut self, processor: &str, _path: &Path) {
+            self.processed.push(processor.to_string());
+        }
+    }
+
+    fn cryxml() -> Vec<u8> {
+        svarog_cryxml::CryXmlDocument::from_xml("<Root a=\"1\"/>")
+            .unwrap()
+            .to_cryxml()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_nested_processing() {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("Entities\\ship.xml", options).unwrap();
+        zip.write_all(&cryxml()).unwrap();
+        zip.start_file("readme.txt", options).unwrap();
+        zip.write_all(b"text").unwrap();
+        let socpak = zip.finish().unwrap().into_inner();
+
+        let pipeline = Pipeline::new().with(SocpakProcessor).with(CryXmlProcessor);
+        let mut output = Collect::default();
+        pipeline.process(PathBuf::from("out/ship.socpak"), socpak, &mut output);
+
+        assert_eq!(output.processed, ["cryxml", "socpak"]);
+        assert!(output.warnings.is_empty());
+        assert_eq!(output.files.len(), 2);
+        assert_eq!(output.files[0].0, Path::new("out/ship/Entities/ship.xml"));
+        assert!(String::from_utf8_lossy(&output.files[0].1).contains("<Root"));
+        assert_eq!(
+            output.files[1],
+            (PathBuf::from("out/ship/readme.txt"), b"text".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_datacore_incremental() {
+        let mut builder = svarog_datacore::DataCoreBuilder::new();
+        let item = builder.add_struct("Item", None);
+        builder.add_record("Rifle", item, "items/rifle.xml");
+        builder.add_record("Ammo", item, "items/ammo.xml");
+        let dcb = builder.build().unwrap();
+
+        let root = std::env::temp_dir().join(format!("svarog-process-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let pipeline = Pipeline::new().with(DataCoreProcessor::new().root(&root).incremental(true));
+        let run = || {
+            let mut output = Collect::default();
+            pipeline.process(root.join("Game.dcb"), dcb.clone(), &mut output);
+            for (path, data) in &output.files {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, data).unwrap();
+            }
+            output
+                .files
+                .into_iter()
+                .map(|(path, _)| path.strip_prefix(&root).unwrap().to_path_buf())
+                .collect::<Vec<_>>()
+        };
+
+        let manifest = PathBuf::from(ExportManifest::FILE_NAME);
+        assert_eq!(
+            run(),
+            [
+                PathBuf::from("items/rifle.xml"),
+                PathBuf::from("items/ammo.xml"),
+                manifest.clone(),
+                PathBuf::from("Game.dcb"),
+            ]
+        );
+        // Unchanged records are skipped, deleted ones exported again
+        std::fs::remove_file(root.join("items/ammo.xml")).unwrap();
+        assert_eq!(
+            run(),
+            [
+                PathBuf::from("items/ammo.xml"),
+                manifest,
+                PathBuf::from("Game.dcb"),
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_enclosed_path() {
+        let dir = Path::new("out/ship");
+        assert_eq!(
+            enclosed_path(dir, "Entities\\./ship.xml"),
+            Some(PathBuf::from("out/ship/Entities/ship.xml"))
+        );
+        assert_eq!(enclosed_path(dir, "..\\..\\evil.xml"), None);
+        assert_eq!(enclosed_path(dir, "a/../../evil.xml"), None);
+        assert_eq!(enclosed_path(dir, "/etc/passwd"), None);
+        assert_eq!(enclosed_path(dir, "\\Windows\\evil.dll"), None);
+        assert_eq!(enclosed_path(dir, "C:\\evil.dll"), None);
+        assert_eq!(enclosed_path(dir, "./"), None);
+    }
+
+    #[test]
+    fn test_failure_keeps_original() {
+        let pipeline = Pipeline::new().with(SocpakProcessor).with(CryXmlProcessor);
+        let mut output = Collect::default();
+        pipeline.process(
+            PathBuf::from("broken.socpak"),
+            b"not a zip".to_vec(),
+            &mut output,
+        );
+
+        assert!(output.processed.is_empty());
+        assert_eq!(output.warnings.len(), 1);
+        assert_eq!(
+            output.files,
+            [(PathBuf::from("broken.socpak"), b"not a zip".to_vec())]
+        );
+
+        let mut output = Collect::default();
+        pipeline.process(PathBuf::from("plain.XML"), b"<Root/>".to_vec(), &mut output);
+        assert!(output.processed.is_empty());
+        assert_eq!(
+            output.files,
+            [(PathBuf::from("plain.XML"), b"<Root/>".to_vec())]
+        );
+    }
+}
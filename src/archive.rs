@@ -0,0 +1,173 @@
+//! Archive output of `p4k-extract --output-archive`.
+//!
+//! Extracted and converted files are appended to a single ZIP or
+//! Zstandard-compressed tar file instead of being written out one by one,
+//! which keeps a full dump portable and spares the filesystem millions of
+//! small files.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Component, Path};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+/// Container format, chosen from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.zip`, entries compressed with Deflate
+    Zip,
+    /// `.tar.zst` or `.tzst`
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".zip") {
+            Ok(Self::Zip)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Ok(Self::TarZst)
+        } else {
+            anyhow::bail!(
+                "Unsupported archive type for {}, use .zip or .tar.zst",
+                path.display()
+            )
+        }
+    }
+}
+
+enum Writer {
+    Zip(Box<zip::ZipWriter<BufWriter<File>>>),
+    TarZst(tar::Builder<zstd::Encoder<'static, BufWriter<File>>>),
+}
+
+/// Archive being written by `p4k-extract`.
+pub struct ArchiveWriter {
+    writer: Writer,
+    /// Modification time given to tar entries
+    mtime: u64,
+}
+
+impl ArchiveWriter {
+    /// Create the archive at `path`, in the format its extension names.
+    pub fn create(path: &Path) -> Result<Self> {
+        let format = ArchiveFormat::from_path(path)?;
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let file = BufWriter::new(file);
+        let writer = match format {
+            ArchiveFormat::Zip => Writer::Zip(Box::new(zip::ZipWriter::new(file))),
+            ArchiveFormat::TarZst => {
+                let encoder = zstd::Encoder::new(file, 0).context("Failed to start zstd stream")?;
+                Writer::TarZst(tar::Builder::new(encoder))
+            }
+        };
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Ok(Self { writer, mtime })
+    }
+
+    /// Add a file at the relative `path`.
+    pub fn add(&mut self, path: &Path, data: &[u8]) -> Result<()> {
+        let name = entry_name(path);
+        match &mut self.writer {
+            Writer::Zip(zip) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(data.len() as u64 >= u32::MAX as u64);
+                zip.start_file(name, options)?;
+                zip.write_all(data)?;
+            }
+            Writer::TarZst(tar) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(self.mtime);
+                tar.append_data(&mut header, name, data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the archive's trailer and flush it to disk.
+    pub fn finish(self) -> Result<()> {
+        let mut file = match self.writer {
+            Writer::Zip(zip) => zip.finish()?,
+            Writer::TarZst(tar) => tar.into_inner()?.finish()?,
+        };
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Archive entry name of a relative path, with `/` separators.
+fn entry_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_archive_format() {
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("out.ZIP")).unwrap(),
+            ArchiveFormat::Zip
+        );
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("dump/out.tar.zst")).unwrap(),
+            ArchiveFormat::TarZst
+        );
+        assert!(ArchiveFormat::from_path(Path::new("out.tar")).is_err());
+    }
+
+    #[test]
+    fn test_write_archives() {
+        let dir = std::env::temp_dir().join(format!("svarog-archive-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let entry = Path::new("Data").join("Scripts").join("main.lua");
+
+        let zip_path = dir.join("out.zip");
+        let mut writer = ArchiveWriter::create(&zip_path).unwrap();
+        writer.add(&entry, b"print(1)").unwrap();
+        writer.finish().unwrap();
+
+        let mut zip = zip::ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let mut contents = String::new();
+        zip.by_name("Data/Scripts/main.lua")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "print(1)");
+
+        let tar_path = dir.join("out.tar.zst");
+        let mut writer = ArchiveWriter::create(&tar_path).unwrap();
+        writer.add(&entry, b"print(1)").unwrap();
+        writer.finish().unwrap();
+
+        let decoder = zstd::Decoder::new(File::open(&tar_path).unwrap()).unwrap();
+        let mut tar = tar::Archive::new(decoder);
+        let mut entries = tar.entries().unwrap();
+        let mut entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap(), Path::new("Data/Scripts/main.lua"));
+        contents.clear();
+        entry.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "print(1)");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
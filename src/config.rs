@@ -0,0 +1,319 @@
+//! `svarog.toml` configuration.
+//!
+//! The file sets default paths, filters and parallelism, plus named profiles
+//! that override them for a workflow:
+//!
+//! ```toml
+//! p4k = "C:/Program Files/Roberts Space Industries/StarCitizen/LIVE/Data.p4k"
+//! output = "extracted"
+//! parallel = 8
+//!
+//! [profiles.ships-only]
+//! filter = "Data/Objects/Spaceships/*"
+//! record-filter = "*/entities/spaceships/*"
+//! output = "ships"
+//! ```
+//!
+//! Values become clap defaults, so flags and their environment variables on
+//! the command line still take precedence. Relative paths are resolved
+//! against the directory of the file.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Arg, Command};
+use serde::Deserialize;
+
+/// Name of the configuration file.
+const FILE_NAME: &str = "svarog.toml";
+
+/// Settings shared by the top level of the file and each profile.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// Data.p4k used by commands reading an archive.
+    pub p4k: Option<PathBuf>,
+    /// Game.dcb used by `dcb-*` commands and for CHF name resolution.
+    pub dcb: Option<PathBuf>,
+    /// Output directory of `p4k-extract` and `dcb-extract`.
+    pub output: Option<PathBuf>,
+    /// Entry filter of `p4k-extract`, `p4k-list` and `p4k-manifest`.
+    pub filter: Option<String>,
+    /// Record file name filter of `dcb-extract`.
+    pub record_filter: Option<String>,
+    /// Localization file for `dcb-extract` and `dcb-table`.
+    pub locale: Option<PathBuf>,
+    /// Worker count of `p4k-extract`.
+    pub parallel: Option<usize>,
+}
+
+/// Contents of a configuration file.
+struct ConfigFile {
+    defaults: Settings,
+    profiles: BTreeMap<String, Settings>,
+}
+
+impl ConfigFile {
+    fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        // Split off the profiles so unknown top-level keys are still rejected
+        let mut table: toml::Table = toml::from_str(text)?;
+        let profiles = match table.remove("profiles") {
+            Some(profiles) => profiles.try_into()?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            defaults: toml::Value::Table(table).try_into()?,
+            profiles,
+        })
+    }
+}
+
+impl Settings {
+    /// Settings from the configuration file selected by `--config`, or found
+    /// in the current directory or the user configuration directory, with
+    /// the `--profile` given in `args` applied.
+    ///
+    /// Returns empty settings when there is no file and none was asked for.
+    pub fn load(args: &[OsString]) -> Result<Self> {
+        let explicit = early_option(args, "config", "SVAROG_CONFIG").map(PathBuf::from);
+        let profile = early_option(args, "profile", "SVAROG_PROFILE")
+            .map(|p| p.to_string_lossy().into_owned());
+
+        let path = match explicit {
+            Some(path) => path,
+            None => match default_paths().into_iter().find(|p| p.is_file()) {
+                Some(path) => path,
+                None if profile.is_some() => {
+                    anyhow::bail!("--profile was given but no {} was found", FILE_NAME)
+                }
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let file = ConfigFile::parse(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let mut settings = file.defaults;
+        if let Some(name) = profile {
+            let overrides = file.profiles.get(&name).with_context(|| {
+                let names: Vec<_> = file.profiles.keys().map(String::as_str).collect();
+                format!(
+                    "No profile '{}' in {} (available: {})",
+                    name,
+                    path.display(),
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )
+            })?;
+            settings.overlay(overrides);
+        }
+
+        if let Some(base) = path.parent() {
+            settings.resolve_paths(base);
+        }
+        Ok(settings)
+    }
+
+    /// Replace settings with those set in `other`.
+    fn overlay(&mut self, other: &Settings) {
+        let other = other.clone();
+        self.p4k = other.p4k.or(self.p4k.take());
+        self.dcb = other.dcb.or(self.dcb.take());
+        self.output = other.output.or(self.output.take());
+        self.filter = other.filter.or(self.filter.take());
+        self.record_filter = other.record_filter.or(self.record_filter.take());
+        self.locale = other.locale.or(self.locale.take());
+        self.parallel = other.parallel.or(self.parallel);
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        for path in [
+            &mut self.p4k,
+            &mut self.dcb,
+            &mut self.output,
+            &mut self.locale,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if path.is_relative() {
+                *path = base.join(&*path);
+            }
+        }
+    }
+
+    /// Add the `--config` and `--profile` options to `command` and install
+    /// the settings as argument defaults of the matching subcommands.
+    ///
+    /// The options are read by [`load`](Self::load) before clap runs, and
+    /// only declared here so clap accepts them and lists them in `--help`.
+    pub fn apply(&self, command: Command) -> Command {
+        let path = |p: &Option<PathBuf>| p.as_ref().map(|p| p.to_string_lossy().into_owned());
+        let p4k = path(&self.p4k);
+        let dcb = path(&self.dcb);
+        let output = path(&self.output);
+        let locale = path(&self.locale);
+        let parallel = self.parallel.map(|n| n.to_string());
+
+        let defaults = [
+            ("p4k-extract", "p4k", &p4k),
+            ("p4k-extract", "output", &output),
+            ("p4k-extract", "filter", &self.filter),
+            ("p4k-extract", "parallel", &parallel),
+            ("p4k-list", "p4k", &p4k),
+            ("p4k-list", "filter", &self.filter),
+            ("p4k-cat", "p4k", &p4k),
+            ("p4k-manifest", "p4k", &p4k),
+            ("p4k-manifest", "filter", &self.filter),
+            ("shell", "p4k", &p4k),
+            ("search", "p4k", &p4k),
+            ("serve", "p4k", &p4k),
+            ("locale", "p4k", &p4k),
+            ("dcb-extract", "input", &dcb),
+            ("dcb-extract", "output", &output),
+            ("dcb-extract", "filter", &self.record_filter),
+            ("dcb-extract", "locale", &locale),
+            ("dcb-bundle", "input", &dcb),
+            ("dcb-schema", "input", &dcb),
+            ("dcb-table", "input", &dcb),
+            ("dcb-table", "locale", &locale),
+            ("dcb-stats", "input", &dcb),
+            ("chf-process", "dcb", &dcb),
+            ("chf-validate", "dcb", &dcb),
+        ];
+
+        let mut command = command
+            .arg(
+                Arg::new("config")
+                    .long("config")
+                    .global(true)
+                    .value_name("PATH")
+                    .env("SVAROG_CONFIG")
+                    .help("Configuration file (default: ./svarog.toml, then the user config directory)"),
+            )
+            .arg(
+                Arg::new("profile")
+                    .long("profile")
+                    .global(true)
+                    .value_name("NAME")
+                    .env("SVAROG_PROFILE")
+                    .help("Named profile from the configuration file to apply"),
+            );
+        for (subcommand, arg, value) in defaults {
+            if let Some(value) = value.clone() {
+                command = command.mut_subcommand(subcommand, |sub| {
+                    sub.mut_arg(arg, |a| a.default_value(value).required(false))
+                });
+            }
+        }
+        command
+    }
+}
+
+/// Where the configuration file is looked for without `--config`: the
+/// current directory, then the user configuration directory.
+fn default_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(FILE_NAME)];
+    let config_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    if let Some(dir) = config_dir {
+        paths.push(dir.join("svarog").join(FILE_NAME));
+    }
+    paths
+}
+
+/// Value of a global `--name` option, read before clap parses the command
+/// line since the configuration it selects supplies clap's defaults.
+pub fn early_option(args: &[OsString], name: &str, env: &str) -> Option<OsString> {
+    let flag = format!("--{}", name);
+    let prefix = format!("--{}=", name);
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == "--" {
+            break;
+        } else if arg == flag {
+            return iter.next().cloned();
+        } else if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.into());
+        }
+    }
+    std::env::var_os(env)
+}
+
+/// Whether a global `--name` flag is set, read like [`early_option`].
+pub fn early_flag(args: &[OsString], name: &str) -> bool {
+    let flag = format!("--{}", name);
+    args.iter()
+        .skip(1)
+        .take_while(|arg| *arg != "--")
+        .any(|arg| *arg == *flag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_overlay() {
+        let file = ConfigFile::parse(
+            r#"
+            p4k = "Data.p4k"
+            output = "out"
+            filter = "*.xml"
+
+            [profiles.ships-only]
+            output = "ships"
+            parallel = 4
+            "#,
+        )
+        .unwrap();
+        assert!(ConfigFile::parse("paralel = 4").is_err());
+
+        let mut settings = file.defaults;
+        settings.overlay(&file.profiles["ships-only"]);
+        assert_eq!(settings.output, Some(PathBuf::from("ships")));
+        assert_eq!(settings.filter.as_deref(), Some("*.xml"));
+        assert_eq!(settings.parallel, Some(4));
+
+        settings.resolve_paths(Path::new("base"));
+        assert_eq!(settings.p4k, Some(Path::new("base").join("Data.p4k")));
+    }
+
+    #[test]
+    fn test_early_option() {
+        let args: Vec<OsString> = [
+            "svarog",
+            "--profile",
+            "ships-only",
+            "p4k-list",
+            "--config=a.toml",
+        ]
+        .iter()
+        .map(OsString::from)
+        .collect();
+        assert_eq!(
+            early_option(&args, "profile", "SVAROG_TEST_UNSET"),
+            Some("ships-only".into())
+        );
+        assert_eq!(
+            early_option(&args, "config", "SVAROG_TEST_UNSET"),
+            Some("a.toml".into())
+        );
+    }
+}
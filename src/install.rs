@@ -0,0 +1,248 @@
+//! Star Citizen installation detection for `--auto`.
+//!
+//! The launcher installs each channel (LIVE, PTU, EPTU) in its own directory
+//! under `Roberts Space Industries/StarCitizen`, with the archive at
+//! `Data.p4k` and the build description in `build_manifest.id`. On Linux the
+//! same layout is looked for inside the usual Wine prefixes.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::{Arg, ArgAction, Command, ValueEnum};
+
+/// Release channel of an installation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Channel {
+    Live,
+    Ptu,
+    Eptu,
+}
+
+impl Channel {
+    /// Preference order when no channel is requested.
+    const ALL: [Channel; 3] = [Channel::Live, Channel::Ptu, Channel::Eptu];
+
+    fn dir_name(self) -> &'static str {
+        match self {
+            Channel::Live => "LIVE",
+            Channel::Ptu => "PTU",
+            Channel::Eptu => "EPTU",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.dir_name())
+    }
+}
+
+/// Build information from `build_manifest.id`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameVersion {
+    /// Branch name, e.g. `sc-alpha-3.24.2`.
+    pub branch: Option<String>,
+    /// Perforce change number of the build.
+    pub change: Option<String>,
+}
+
+impl GameVersion {
+    fn parse(text: &str) -> Option<Self> {
+        let manifest: serde_json::Value = serde_json::from_str(text).ok()?;
+        let data = &manifest["Data"];
+        let field = |name: &str| match &data[name] {
+            serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+        let version = Self {
+            branch: field("Branch"),
+            change: field("RequestedP4ChangeNum"),
+        };
+        (version != Self::default()).then_some(version)
+    }
+}
+
+impl fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.branch, &self.change) {
+            (Some(branch), Some(change)) => write!(f, "{} (change {})", branch, change),
+            (Some(branch), None) => f.write_str(branch),
+            (None, Some(change)) => write!(f, "change {}", change),
+            (None, None) => f.write_str("unknown version"),
+        }
+    }
+}
+
+/// A detected installation.
+#[derive(Debug, Clone)]
+pub struct Install {
+    pub channel: Channel,
+    pub p4k: PathBuf,
+    pub version: Option<GameVersion>,
+}
+
+impl Install {
+    /// Report the installation as a status line, or an `install` event in
+    /// JSON mode.
+    pub fn report(&self) {
+        if crate::output::json() {
+            crate::output::event(serde_json::json!({
+                "event": "install",
+                "channel": self.channel.dir_name(),
+                "p4k": self.p4k,
+                "branch": self.version.as_ref().and_then(|v| v.branch.as_deref()),
+                "change": self.version.as_ref().and_then(|v| v.change.as_deref()),
+            }));
+            return;
+        }
+        estatus!(
+            "Using Star Citizen {} {}: {}",
+            self.channel,
+            self.version.clone().unwrap_or_default(),
+            self.p4k.display()
+        );
+    }
+}
+
+/// Add the global `--auto` and `--channel` options to `command`.
+///
+/// Like `--config`, they are read from the raw arguments before clap runs,
+/// since the archive they find becomes the default of `--p4k`.
+pub fn args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("auto")
+                .long("auto")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Find Data.p4k in the Star Citizen installation instead of passing --p4k"),
+        )
+        .arg(
+            Arg::new("channel")
+                .long("channel")
+                .global(true)
+                .value_parser(clap::value_parser!(Channel))
+                .env("SVAROG_CHANNEL")
+                .help("Channel used by --auto (default: LIVE, then PTU, then EPTU)"),
+        )
+}
+
+/// Detect the installation when `--auto` is among `args`.
+pub fn from_args(args: &[std::ffi::OsString]) -> Result<Option<Install>> {
+    if !crate::config::early_flag(args, "auto") {
+        return Ok(None);
+    }
+    let channel = match crate::config::early_option(args, "channel", "SVAROG_CHANNEL") {
+        Some(name) => Some(
+            Channel::from_str(&name.to_string_lossy(), true)
+                .map_err(|_| anyhow::anyhow!("Unknown channel '{}'", name.to_string_lossy()))?,
+        ),
+        None => None,
+    };
+    detect(channel).map(Some)
+}
+
+/// Find the installation of `channel`, or of the first channel installed.
+pub fn detect(channel: Option<Channel>) -> Result<Install> {
+    let roots = candidate_roots();
+    find_in(&roots, channel).ok_or_else(|| {
+        let searched: Vec<_> = roots.iter().map(|p| format!("  {}", p.display())).collect();
+        anyhow::anyhow!(
+            "No Star Citizen {} installation found, pass --p4k instead. Searched:\n{}",
+            channel.map_or("".to_string(), |c| format!("{} ", c)),
+            searched.join("\n")
+        )
+    })
+}
+
+fn find_in(roots: &[PathBuf], channel: Option<Channel>) -> Option<Install> {
+    let channels = match channel {
+        Some(channel) => vec![channel],
+        None => Channel::ALL.to_vec(),
+    };
+    // Prefer the channel over the location, so LIVE anywhere beats PTU
+    channels.into_iter().find_map(|channel| {
+        roots.iter().find_map(|root| {
+            let dir = root.join(channel.dir_name());
+            let p4k = dir.join("Data.p4k");
+            p4k.is_file().then(|| Install {
+                channel,
+                version: std::fs::read_to_string(dir.join("build_manifest.id"))
+                    .ok()
+                    .and_then(|text| GameVersion::parse(&text)),
+                p4k,
+            })
+        })
+    })
+}
+
+/// `StarCitizen` directories the launcher may have installed to.
+fn candidate_roots() -> Vec<PathBuf> {
+    const INSTALL_DIR: &str = "Roberts Space Industries/StarCitizen";
+
+    let mut roots = Vec::new();
+    if cfg!(windows) {
+        if let Some(program_files) = std::env::var_os("ProgramFiles") {
+            roots.push(Path::new(&program_files).join(INSTALL_DIR));
+        }
+        // The launcher offers any drive, with or without Program Files
+        for drive in b'C'..=b'Z' {
+            let drive = format!("{}:\\", drive as char);
+            roots.push(Path::new(&drive).join("Program Files").join(INSTALL_DIR));
+            roots.push(Path::new(&drive).join(INSTALL_DIR));
+        }
+    } else {
+        let mut prefixes = Vec::new();
+        if let Some(prefix) = std::env::var_os("WINEPREFIX") {
+            prefixes.push(PathBuf::from(prefix));
+        }
+        if let Some(home) = std::env::var_os("HOME") {
+            let home = Path::new(&home);
+            prefixes.push(home.join(".wine"));
+            // Default prefix of the Lutris installer
+            prefixes.push(home.join("Games").join("star-citizen"));
+        }
+        for prefix in prefixes {
+            roots.push(prefix.join("drive_c/Program Files").join(INSTALL_DIR));
+        }
+    }
+    roots.dedup();
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_version() {
+        let version = GameVersion::parse(
+            r#"{"Data": {"Branch": "sc-alpha-3.24.2", "RequestedP4ChangeNum": "9381701"}}"#,
+        )
+        .unwrap();
+        assert_eq!(version.to_string(), "sc-alpha-3.24.2 (change 9381701)");
+        assert_eq!(GameVersion::parse("{}"), None);
+        assert_eq!(GameVersion::parse("not json"), None);
+    }
+
+    #[test]
+    fn test_find_in() {
+        let root = std::env::temp_dir().join(format!("svarog-install-{}", std::process::id()));
+        for channel in ["PTU", "EPTU"] {
+            std::fs::create_dir_all(root.join(channel)).unwrap();
+            std::fs::write(root.join(channel).join("Data.p4k"), b"").unwrap();
+        }
+        let roots = vec![root.clone()];
+
+        assert_eq!(find_in(&roots, None).unwrap().channel, Channel::Ptu);
+        assert_eq!(
+            find_in(&roots, Some(Channel::Eptu)).unwrap().channel,
+            Channel::Eptu
+        );
+        assert!(find_in(&roots, Some(Channel::Live)).is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
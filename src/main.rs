@@ -4,18 +4,30 @@
 
 use std::collections::HashMap;
 use std::fs;
-use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::process::ExitCode;
 use std::sync::Mutex;
 use std::time::Instant;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use indicatif::{ProgressBar, ProgressStyle};
 
 use svarog::prelude::*;
 
+#[macro_use]
+mod output;
+mod archive;
+mod config;
+mod install;
+mod pipeline;
+mod serve;
+mod shell;
+mod socpak;
+mod watch;
+
+use output::MessageFormat;
+
 /// Progress stage for detailed visualization
 #[derive(Clone, Copy)]
 enum Stage {
@@ -23,6 +35,7 @@ enum Stage {
     SocpakExpand,
     CryXmlDecode,
     DcbExport,
+    Hash,
 }
 
 impl Stage {
@@ -32,6 +45,7 @@ impl Stage {
             Stage::SocpakExpand => "SOCPAK",
             Stage::CryXmlDecode => "CryXML",
             Stage::DcbExport => "DCB",
+            Stage::Hash => "HASH",
         }
     }
 
@@ -41,6 +55,7 @@ impl Stage {
             Stage::SocpakExpand => "yellow",
             Stage::CryXmlDecode => "magenta",
             Stage::DcbExport => "green",
+            Stage::Hash => "white",
         }
     }
 }
@@ -59,7 +74,7 @@ fn create_progress_bar(len: u64, stage: Stage) -> ProgressBar {
             .unwrap()
             .progress_chars("#>-"),
     );
-    pb
+    output::attach(pb, stage.prefix())
 }
 
 /// Format a file path for display (truncate if too long)
@@ -93,11 +108,6 @@ fn try_decode_cryxml_inplace(path: &Path) -> Result<bool> {
     Ok(true)
 }
 
-/// Check if data looks like CryXML (first 8 bytes are "CryXmlB\0")
-fn is_cryxml_data(data: &[u8]) -> bool {
-    CryXml::is_cryxml(data)
-}
-
 /// Svarog - Star Citizen game file extraction tool
 #[derive(Parser)]
 #[command(name = "svarog")]
@@ -105,6 +115,26 @@ fn is_cryxml_data(data: &[u8]) -> bool {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Log filter for tracing output on stderr, e.g. "info" or "svarog_p4k=trace"
+    #[cfg(feature = "tracing")]
+    #[arg(long, global = true, env = "SVAROG_LOG")]
+    log: Option<String>,
+
+    /// Report results on stdout and progress, warnings and errors on stderr
+    /// as JSON lines
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t,
+        env = "SVAROG_MESSAGE_FORMAT"
+    )]
+    message_format: MessageFormat,
+
+    /// Print only warnings, errors and results, without status lines or progress bars
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
 #[derive(Subcommand)]
@@ -116,8 +146,18 @@ enum Commands {
         p4k: PathBuf,
 
         /// Output directory
-        #[arg(short, long, env = "OUTPUT_FOLDER")]
-        output: PathBuf,
+        #[arg(
+            short,
+            long,
+            env = "OUTPUT_FOLDER",
+            required_unless_present = "output_archive"
+        )]
+        output: Option<PathBuf>,
+
+        /// Write everything into a single .zip or .tar.zst archive instead of a
+        /// directory (takes precedence over --output)
+        #[arg(long)]
+        output_archive: Option<PathBuf>,
 
         /// Filter pattern (glob-style, or regex if --regex is set)
         #[arg(short, long)]
@@ -127,7 +167,8 @@ enum Commands {
         #[arg(long)]
         regex: bool,
 
-        /// Incremental extraction: skip files that already exist with matching size
+        /// Incremental extraction: skip files that already exist with matching size,
+        /// and DataCore records whose content hash matches the export manifest
         #[arg(long, default_value = "true")]
         incremental: bool,
 
@@ -139,9 +180,27 @@ enum Commands {
         #[arg(long, default_value = "true")]
         expand_socpak: bool,
 
-        /// Number of parallel workers (0 = auto)
+        /// Merge split DDS textures (.dds.N, .dds.Na) into their base files after extraction
+        #[arg(long, conflicts_with = "output_archive")]
+        merge_dds: bool,
+
+        /// Delete the split DDS parts once merged (with --merge-dds)
+        #[arg(long, requires = "merge_dds")]
+        delete_dds_parts: bool,
+
+        /// Worker threads, split between decoding and writing (0 = one per CPU)
         #[arg(long, short = 'j', default_value = "0")]
         parallel: usize,
+
+        /// Print what would be extracted or skipped, with total sizes and an
+        /// estimated time, without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Read entries ahead of the decoders, which helps most on hard disks;
+        /// io-uring and overlapped need the queued-io feature
+        #[arg(long, value_enum)]
+        io: Option<IoBackendArg>,
     },
 
     /// List contents of a P4K archive
@@ -159,6 +218,39 @@ enum Commands {
         detailed: bool,
     },
 
+    /// Write one decompressed entry of a P4K archive to stdout
+    P4kCat {
+        /// Path to the P4K file
+        #[arg(short, long, env = "INPUT_P4K")]
+        p4k: PathBuf,
+
+        /// Entry path within the archive (case-insensitive, / or \ separators)
+        entry: String,
+
+        /// Decode CryXmlB entries to XML text; other entries are written as is
+        #[arg(long)]
+        xml: bool,
+    },
+
+    /// Write a SHA-256 manifest of the entries of a P4K archive
+    P4kManifest {
+        /// Path to the P4K file
+        #[arg(short, long, env = "INPUT_P4K")]
+        p4k: PathBuf,
+
+        /// Filter pattern (glob-style)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Manifest format
+        #[arg(long, value_enum, default_value_t = ManifestFormat::Json)]
+        format: ManifestFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Convert a CryXmlB file to XML
     CryxmlConvert {
         /// Input CryXmlB file
@@ -170,6 +262,30 @@ enum Commands {
         output: PathBuf,
     },
 
+    /// Convert all CryXmlB files in a directory to XML
+    CryxmlConvertAll {
+        /// Input directory
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output directory (converts in place if omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Compare two CryXmlB or XML files structurally
+    CryxmlDiff {
+        /// Path to the old file
+        old: PathBuf,
+
+        /// Path to the new file
+        new: PathBuf,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Convert an XML file to CryXmlB binary format
     CryxmlCreate {
         /// Input XML file
@@ -188,985 +304,3469 @@ enum Commands {
         input: PathBuf,
 
         /// Output directory
-        #[arg(short, long)]
-        output: PathBuf,
+        #[arg(short, long, required_unless_present = "output_archive")]
+        output: Option<PathBuf>,
+
+        /// Write all records into a single ZIP archive instead of a directory
+        #[arg(long, conflicts_with = "output")]
+        output_archive: Option<PathBuf>,
 
         /// Filter pattern for record file names
         #[arg(short, long)]
         filter: Option<String>,
+
+        /// Localization file (global.ini) used to resolve Locale values
+        #[arg(long)]
+        locale: Option<PathBuf>,
+
+        /// Write canonical XML (sorted attributes and references, normalized floats) for diffing
+        #[arg(long)]
+        canonical: bool,
+
+        /// Name the target of each weak pointer in a PointsToName attribute
+        #[arg(long)]
+        weak_pointer_names: bool,
+
+        /// Export format: xml, json, or FORMAT for a svarog-export-FORMAT program on the PATH
+        #[arg(long, default_value = "xml")]
+        format: String,
     },
 
     /// Process a CHF character file
     ChfProcess {
-        /// Input CHF file
+        /// Input file (CHF, JSON or BIN)
         #[arg(short, long)]
         input: PathBuf,
 
-        /// Output file (CHF or BIN)
+        /// Output file (CHF, JSON or BIN)
         #[arg(short, long)]
         output: PathBuf,
+
+        /// DataCore (DCB) file used to resolve item and port names
+        #[arg(short, long)]
+        dcb: Option<PathBuf>,
+
+        /// Extra name dictionary (one name per line) for hashed names
+        #[arg(short, long)]
+        names: Option<PathBuf>,
+
+        /// Recover from non-canonical CHF files (wrong sizes, trailing
+        /// bytes, unknown trailer) instead of rejecting them
+        #[arg(long)]
+        lenient: bool,
     },
 
-    /// Merge split DDS files
-    DdsMerge {
-        /// Input DDS file (base file without .N suffix)
+    /// Brute-force names for unknown CHF name hashes
+    ChfUnhash {
+        /// Hashes to search for (hex with 0x prefix, or decimal)
+        #[arg(required = true)]
+        hashes: Vec<String>,
+
+        /// Maximum length of the searched part of the name
+        #[arg(short = 'l', long, default_value_t = 5)]
+        max_len: usize,
+
+        /// Fixed text before the searched part, e.g. port_
+        #[arg(short, long, default_value = "")]
+        prefix: String,
+
+        /// Fixed text after the searched part, e.g. _color
+        #[arg(short, long, default_value = "")]
+        suffix: String,
+
+        /// Characters to search with
+        #[arg(short, long)]
+        charset: Option<String>,
+    },
+
+    /// Check a CHF character's references against a DataCore database
+    ChfValidate {
+        /// Character file (CHF, JSON or BIN)
         #[arg(short, long)]
         input: PathBuf,
 
-        /// Output DDS file
+        /// Path to the DCB file
+        #[arg(short, long)]
+        dcb: PathBuf,
+    },
+
+    /// Blend two CHF characters into a new one
+    ChfMorph {
+        /// Character at -t 0.0 (CHF, JSON or BIN)
+        #[arg(long)]
+        from: PathBuf,
+
+        /// Character at -t 1.0 (CHF, JSON or BIN)
+        #[arg(long)]
+        to: PathBuf,
+
+        /// Blend amount from 0.0 (--from) to 1.0 (--to)
+        #[arg(short = 't', long = "amount", default_value_t = 0.5)]
+        amount: f32,
+
+        /// Output file (CHF, JSON or BIN)
         #[arg(short, long)]
         output: PathBuf,
     },
 
-    /// Export DataCore schema as a C header file
-    DcbSchema {
-        /// Path to the DCB file
+    /// Move a CHF character to another body
+    ChfConvertGender {
+        /// Input file (CHF, JSON or BIN)
         #[arg(short, long)]
         input: PathBuf,
 
-        /// Output C header file
+        /// Output file (CHF, JSON or BIN)
         #[arg(short, long)]
         output: PathBuf,
-    },
-}
-
-fn main() -> Result<()> {
-    let cli = Cli::parse();
 
-    match cli.command {
-        Commands::P4kExtract {
-            p4k,
-            output,
-            filter,
-            regex,
-            incremental,
-            extract_dcb,
-            expand_socpak,
-            parallel,
-        } => {
-            cmd_p4k_extract(
-                &p4k,
-                &output,
-                filter.as_deref(),
-                regex,
-                incremental,
-                extract_dcb,
-                expand_socpak,
-                parallel,
-            )?;
-        }
-        Commands::P4kList { p4k, filter, detailed } => {
-            cmd_p4k_list(&p4k, filter.as_deref(), detailed)?;
-        }
-        Commands::CryxmlConvert { input, output } => {
-            cmd_cryxml_convert(&input, &output)?;
-        }
-        Commands::CryxmlCreate { input, output } => {
-            cmd_cryxml_create(&input, &output)?;
-        }
-        Commands::DcbExtract { input, output, filter } => {
-            cmd_dcb_extract(&input, &output, filter.as_deref())?;
-        }
-        Commands::ChfProcess { input, output } => {
-            cmd_chf_process(&input, &output)?;
-        }
-        Commands::DdsMerge { input, output } => {
-            cmd_dds_merge(&input, &output)?;
-        }
-        Commands::DcbSchema { input, output } => {
-            cmd_dcb_schema(&input, &output)?;
-        }
-    }
+        /// GUID of the target gender (body) record
+        #[arg(short, long)]
+        gender: String,
 
-    Ok(())
-}
+        /// JSON mapping of head IDs, records and ports to their equivalents
+        #[arg(short, long)]
+        mapping: Option<PathBuf>,
+    },
 
-/// Case-insensitive path mapper for merging DCB output with existing folders.
-///
-/// The DCB exports files with lowercase paths, but the P4K archive has mixed case.
-/// This mapper ensures we use the existing case when a folder already exists.
-struct CaseInsensitivePathMapper {
-    /// Maps lowercase path components to their actual case on disk
-    component_cache: Mutex<HashMap<String, String>>,
-}
+    /// Apply scripted edits to every CHF file in a directory
+    ChfBatch {
+        /// Directory containing .chf files (e.g. CustomCharacters)
+        #[arg(short, long)]
+        input_dir: PathBuf,
 
-impl CaseInsensitivePathMapper {
-    fn new() -> Self {
-        Self {
-            component_cache: Mutex::new(HashMap::new()),
-        }
-    }
+        /// JSON script with the edits to apply
+        #[arg(short, long)]
+        script: PathBuf,
 
-    /// Resolve a path using existing case from the filesystem.
-    ///
-    /// For each component in the path, checks if a case-insensitive match exists
-    /// on disk and uses that case instead. This allows DCB exports (lowercase)
-    /// to merge with existing P4K extracts (mixed case).
-    fn resolve(&self, base: &Path, relative: &str) -> PathBuf {
-        let mut result = base.to_path_buf();
-        let components: Vec<&str> = relative.split(['/', '\\']).filter(|s| !s.is_empty()).collect();
+        /// Write edited files here instead of overwriting the inputs
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
 
-        for (i, component) in components.iter().enumerate() {
-            let is_last = i == components.len() - 1;
+        /// Keep running and edit files as they are added or changed
+        #[arg(short, long)]
+        watch: bool,
 
-            // Build the cache key (lowercase path so far)
-            let cache_key = if i == 0 {
-                component.to_lowercase()
-            } else {
-                let prefix: String = components[..i]
-                    .iter()
-                    .map(|c| c.to_lowercase())
-                    .collect::<Vec<_>>()
-                    .join("/");
-                format!("{}/{}", prefix, component.to_lowercase())
-            };
+        /// Seconds between directory scans in watch mode
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
 
-            // Check cache first
-            {
-                let cache = self.component_cache.lock().unwrap();
-                if let Some(cached) = cache.get(&cache_key) {
-                    result.push(cached);
-                    continue;
-                }
-            }
+    /// Edit one character with path=value assignments or a JSON patch
+    ChfEdit {
+        /// Input character (.chf, .json or .bin)
+        #[arg(short, long)]
+        input: PathBuf,
 
-            // Try to find existing entry with matching case
-            let matched_name = if result.exists() {
-                self.find_case_insensitive_match(&result, component)
-            } else {
-                None
-            };
+        /// Output file; the extension selects CHF, JSON or BIN
+        #[arg(short, long)]
+        output: PathBuf,
 
-            let actual_name = matched_name.unwrap_or_else(|| component.to_string());
+        /// Edit as PATH=VALUE, e.g. dna.nose.blend0=0.7, dna.jaw.scale=0.8,
+        /// material.eyes.iris_color=#3366ff, port.hair=remove (repeatable)
+        #[arg(
+            long = "set",
+            value_name = "PATH=VALUE",
+            required_unless_present = "patch"
+        )]
+        set: Vec<svarog::chf::ChfOp>,
 
-            // Cache the result (only for directories, not the final file)
-            if !is_last {
-                let mut cache = self.component_cache.lock().unwrap();
-                cache.insert(cache_key, actual_name.clone());
-            }
+        /// JSON script in the chf-batch format, applied before any --set
+        #[arg(long)]
+        patch: Option<PathBuf>,
+    },
 
-            result.push(&actual_name);
-        }
+    /// Merge split DDS files
+    DdsMerge {
+        /// Input DDS file (base file without .N suffix)
+        #[arg(short, long)]
+        input: PathBuf,
 
-        result
-    }
+        /// Output DDS file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
 
-    /// Find a case-insensitive match in a directory.
-    fn find_case_insensitive_match(&self, dir: &Path, target: &str) -> Option<String> {
-        let target_lower = target.to_lowercase();
+    /// Decode a DDS texture (merging split mipmaps) to a viewable image or KTX2
+    DdsConvert {
+        /// Input DDS file (base file without .N suffix)
+        #[arg(short, long)]
+        input: PathBuf,
 
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let name = entry.file_name();
-                if let Some(name_str) = name.to_str() {
-                    if name_str.to_lowercase() == target_lower {
-                        return Some(name_str.to_string());
-                    }
-                }
-            }
-        }
+        /// Output image file
+        #[arg(short, long)]
+        output: PathBuf,
 
-        None
-    }
-}
+        /// Output image format
+        #[arg(long, value_enum, default_value = "png")]
+        to: ImageFormat,
 
-/// Result of SOCPAK extraction
-struct SocpakExtractionResult {
-    files_extracted: usize,
-    cryxml_decoded: usize,
-}
+        /// Zstandard level for KTX2 supercompression (1-22)
+        #[arg(long, value_name = "LEVEL")]
+        zstd: Option<i32>,
+    },
 
-/// Extract a SOCPAK (which is just a ZIP file) to a directory.
-/// Also decodes any CryXML files found inside.
-fn extract_socpak(data: &[u8], output_dir: &Path, pb: Option<&ProgressBar>) -> Result<SocpakExtractionResult> {
-    let cursor = Cursor::new(data);
-    let mut archive = zip::ZipArchive::new(cursor)
-        .context("Failed to open SOCPAK as ZIP archive")?;
+    /// Encode an image to DDS with a generated mipmap chain
+    DdsEncode {
+        /// Input image file (PNG, BMP or JPEG)
+        #[arg(short, long)]
+        input: PathBuf,
 
-    let mut extracted = 0;
-    let mut cryxml_decoded = 0;
+        /// Output DDS file
+        #[arg(short, long)]
+        output: PathBuf,
 
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let name = file.name().to_string();
+        /// Pixel format
+        #[arg(long, value_enum, default_value = "bc7")]
+        format: EncodeFormat,
 
-        // Skip directories
-        if name.ends_with('/') {
-            continue;
-        }
+        /// Mipmap downsampling filter
+        #[arg(long, value_enum, default_value = "box")]
+        filter: MipFilterArg,
 
-        let output_path = output_dir.join(name.replace('\\', "/"));
+        /// Store only the top level, without mipmaps
+        #[arg(long)]
+        no_mips: bool,
 
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
+        /// Move the N largest mipmaps to .dds.N .. .dds.1 files, like the game does
+        #[arg(long, value_name = "N")]
+        split: Option<u8>,
+    },
 
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
+    /// Show DDS header details (dimensions, format, mipmaps, DX10 header)
+    DdsInfo {
+        /// Input DDS file
+        #[arg(short, long)]
+        input: PathBuf,
 
-        // Check if this is a CryXML file and decode it
-        if is_cryxml_data(&contents) {
-            if let Some(pb) = pb {
-                set_progress_message(pb, Stage::CryXmlDecode, &name);
-            }
+        /// Fix the mipmap count and linear size to match the data, in place
+        #[arg(long)]
+        repair: bool,
+    },
 
-            match CryXml::parse(&contents) {
-                Ok(cryxml) => {
-                    if let Ok(xml) = cryxml.to_xml_string() {
-                        fs::write(&output_path, xml)?;
-                        cryxml_decoded += 1;
-                        extracted += 1;
-                        continue;
-                    }
-                }
-                Err(_) => {
-                    // Fall through to write raw contents
-                }
-            }
-        }
+    /// Export a record and every record it references as a self-contained bundle
+    DcbBundle {
+        /// Path to the DCB file
+        #[arg(short, long)]
+        input: PathBuf,
 
-        fs::write(&output_path, contents)?;
-        extracted += 1;
-    }
+        /// Record name or GUID to export
+        #[arg(short, long)]
+        record: String,
 
-    Ok(SocpakExtractionResult {
-        files_extracted: extracted,
-        cryxml_decoded,
-    })
-}
+        /// Output directory
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Export DataCore schema as a C header, Rust source, TypeScript definitions or JSON Schema
+    DcbSchema {
+        /// Path to the DCB file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file (directory for JSON Schema or split C headers)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Schema format
+        #[arg(long, value_enum, default_value_t = SchemaFormat::C)]
+        format: SchemaFormat,
+
+        /// Copy inherited fields into each C struct instead of nesting the parent
+        #[arg(long)]
+        flatten: bool,
+
+        /// Write one C header per struct into the output directory
+        #[arg(long)]
+        split: bool,
+    },
+
+    /// Compare two DataCore databases and report changed records
+    DcbDiff {
+        /// Path to the old DCB file
+        old: PathBuf,
+
+        /// Path to the new DCB file
+        new: PathBuf,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Only pair records by GUID, not by name or structural similarity
+        #[arg(long)]
+        guid_only: bool,
+
+        /// Only report records of this struct type (repeatable, case-insensitive)
+        #[arg(short = 't', long = "type")]
+        types: Vec<String>,
+
+        /// Only report records whose file path starts with this prefix (repeatable)
+        #[arg(long = "path")]
+        paths: Vec<String>,
+    },
+
+    /// Export all records of a struct type as a table
+    DcbTable {
+        /// Path to the DCB file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Struct type name, e.g. AmmoParams
+        #[arg(short = 't', long = "type")]
+        type_name: String,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Table format
+        #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+        format: TableFormat,
+
+        /// Localization file (global.ini) used to resolve Locale values
+        #[arg(long)]
+        locale: Option<PathBuf>,
+    },
+
+    /// Rebuild a DataCore database from edited XML files written by dcb-extract
+    DcbImport {
+        /// Path to the DCB file the XML was extracted from
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Directory of record XML files
+        #[arg(short, long)]
+        xml: PathBuf,
+
+        /// Output DCB file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// Layer the structs and records of other DCB files on top of a DCB file
+    DcbMerge {
+        /// Path to the base DCB file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// DCB files to merge in, applied in order
+        #[arg(short, long, required = true)]
+        with: Vec<PathBuf>,
+
+        /// Output DCB file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// How to resolve records whose GUID or name already exists
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Error)]
+        conflicts: ConflictStrategy,
+    },
+
+    /// Show pool sizes, record counts and other DataCore statistics
+    DcbStats {
+        /// Path to the DCB file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+    },
+
+    /// Browse a P4K archive and its DataCore interactively
+    Shell {
+        /// Path to the P4K file
+        #[arg(short, long, env = "INPUT_P4K")]
+        p4k: PathBuf,
+    },
+
+    /// Serve a read-only JSON API over a P4K archive and its DataCore
+    Serve {
+        /// Path to the P4K file
+        #[arg(short, long, env = "INPUT_P4K")]
+        p4k: PathBuf,
+
+        /// Also load the DataCore, enabling the record endpoints
+        #[arg(long)]
+        dcb: bool,
+
+        /// Older P4K archive to compare against, enabling the diff endpoints
+        #[arg(long, value_name = "P4K")]
+        against: Option<PathBuf>,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+
+        /// Requests handled at once (0 = one per CPU)
+        #[arg(long, short = 'j', default_value = "0")]
+        parallel: usize,
+
+        /// Refuse entries larger than this uncompressed, in MiB (0 = no limit)
+        #[arg(long, value_name = "MIB", default_value = "1024")]
+        max_entry_size: u64,
+
+        /// Refuse entries expanding more than this many times their stored size
+        #[arg(long, value_name = "N")]
+        max_ratio: Option<u64>,
+
+        /// Serve this entry whatever its size (repeatable)
+        #[arg(long = "allow", value_name = "PATH")]
+        allow: Vec<String>,
+    },
+
+    /// Watch a directory and process files as they appear: back up, validate and
+    /// optionally convert CHF characters, and decode CryXmlB files in place
+    Watch {
+        /// Directory to watch, including subdirectories (e.g. CustomCharacters)
+        dir: PathBuf,
+
+        /// Where to keep a copy of each version of a character [default: <DIR>/backups]
+        #[arg(long)]
+        backup_dir: Option<PathBuf>,
+
+        /// Don't back up characters
+        #[arg(long, conflicts_with = "backup_dir")]
+        no_backup: bool,
+
+        /// DataCore (DCB) file to validate character references against
+        #[arg(long)]
+        dcb: Option<PathBuf>,
+
+        /// Write a .json file next to each character
+        #[arg(long)]
+        json: bool,
+
+        /// Also decode CryXmlB files to XML in place, for extracted trees
+        #[arg(long)]
+        cryxml: bool,
+
+        /// Milliseconds without changes before files are processed
+        #[arg(long, default_value_t = 500)]
+        debounce: u64,
+    },
+
+    /// List, extract and re-pack SOCPAK archives
+    Socpak {
+        #[command(subcommand)]
+        command: SocpakCommand,
+    },
+
+    /// Export and query localization text (global.ini)
+    Locale {
+        /// Path to the P4K file to read the localization from
+        #[arg(short, long, env = "INPUT_P4K", global = true)]
+        p4k: Option<PathBuf>,
+
+        /// Read an extracted global.ini instead of the P4K
+        #[arg(long, global = true, conflicts_with = "p4k")]
+        ini: Option<PathBuf>,
+
+        /// Language directory under Data/Localization
+        #[arg(long, global = true, default_value = "english")]
+        lang: String,
+
+        #[command(subcommand)]
+        command: LocaleCommand,
+    },
+
+    /// Search P4K entry names and DataCore records for a piece of text
+    Search {
+        /// Path to the P4K file
+        #[arg(short, long, env = "INPUT_P4K")]
+        p4k: PathBuf,
+
+        /// Text to look for
+        query: String,
+
+        /// Also search record property values (slower)
+        #[arg(long)]
+        properties: bool,
+
+        /// Match letter case exactly
+        #[arg(long)]
+        case_sensitive: bool,
+
+        /// Only search entry names, skipping the DataCore
+        #[arg(long)]
+        no_datacore: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SocpakCommand {
+    /// List the entries of a SOCPAK
+    List {
+        /// SOCPAK file, or entry path within the P4K given with --p4k
+        input: PathBuf,
+
+        /// Read the SOCPAK from this P4K archive
+        #[arg(short, long)]
+        p4k: Option<PathBuf>,
+    },
+
+    /// Extract a SOCPAK into a directory, decoding CryXmlB to XML
+    Extract {
+        /// SOCPAK file, or entry path within the P4K given with --p4k
+        input: PathBuf,
+
+        /// Output directory
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Read the SOCPAK from this P4K archive
+        #[arg(short, long)]
+        p4k: Option<PathBuf>,
+    },
+
+    /// Pack a directory into a SOCPAK
+    Create {
+        /// Directory with the files to pack
+        input: PathBuf,
+
+        /// Output SOCPAK file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Original SOCPAK whose entry order and compression to keep; files that
+        /// were CryXmlB in it are encoded back from XML
+        #[arg(short, long)]
+        template: Option<PathBuf>,
+
+        /// Compression of files not in the template
+        #[arg(long, value_enum, default_value_t = socpak::Compression::Deflate)]
+        compression: socpak::Compression,
+    },
+}
+
+#[derive(Subcommand)]
+enum LocaleCommand {
+    /// Write every key and its text
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = LocaleFormat::Csv)]
+        format: LocaleFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Print the text of one or more keys
+    Lookup {
+        /// Localization keys, with or without the leading @
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+
+    /// Find keys whose name or text contains a piece of text, ignoring case
+    Grep {
+        /// Text to look for
+        text: String,
+    },
+}
+
+/// Output format for table exports
+#[derive(Clone, Copy, ValueEnum)]
+enum TableFormat {
+    /// Comma-separated values
+    Csv,
+    /// Apache Parquet (requires the parquet feature)
+    Parquet,
+}
+
+/// Output format for schema exports
+#[derive(Clone, Copy, ValueEnum)]
+enum SchemaFormat {
+    /// C header
+    C,
+    /// Rust structs and enums with serde derives
+    Rust,
+    /// TypeScript definitions (.d.ts)
+    Typescript,
+    /// One JSON Schema document per struct
+    JsonSchema,
+}
+
+/// Output format for decoded textures
+#[derive(Clone, Copy, ValueEnum)]
+enum ImageFormat {
+    Png,
+    Bmp,
+    /// KTX2 container keeping the compressed blocks and mipmaps
+    Ktx2,
+}
+
+/// Pixel format for encoded textures
+#[derive(Clone, Copy, ValueEnum)]
+enum EncodeFormat {
+    /// BC1 (DXT1), color with 1-bit alpha
+    Bc1,
+    /// BC3 (DXT5), color with alpha
+    Bc3,
+    /// BC4, single channel
+    Bc4,
+    /// BC5, two channels (normal maps)
+    Bc5,
+    /// BC7, high quality color with alpha
+    Bc7,
+    /// Uncompressed 32-bit RGBA
+    Rgba8,
+}
+
+/// I/O backend for reading P4K entries ahead of decompression
+#[derive(Clone, Copy, ValueEnum)]
+enum IoBackendArg {
+    /// Memory map, asking the kernel to page queued entries in
+    Mmap,
+    /// Linux io_uring
+    IoUring,
+    /// Windows overlapped I/O
+    Overlapped,
+    /// io-uring or overlapped when built in, else mmap
+    Native,
+}
+
+impl From<IoBackendArg> for svarog::p4k::IoBackend {
+    fn from(arg: IoBackendArg) -> Self {
+        match arg {
+            IoBackendArg::Mmap => Self::Mmap,
+            IoBackendArg::IoUring => Self::IoUring,
+            IoBackendArg::Overlapped => Self::Overlapped,
+            IoBackendArg::Native => Self::native(),
+        }
+    }
+}
+
+/// Mipmap downsampling filter
+#[derive(Clone, Copy, ValueEnum)]
+enum MipFilterArg {
+    Box,
+    Triangle,
+    CatmullRom,
+    Lanczos3,
+}
+
+/// Conflict resolution for merged records
+#[derive(Clone, Copy, ValueEnum)]
+enum ConflictStrategy {
+    /// Fail on the first conflict
+    Error,
+    /// Keep the existing record
+    Keep,
+    /// Replace the existing record, keeping its GUID
+    Replace,
+    /// Import the incoming record under a new GUID or name
+    Rename,
+}
+
+/// Output format for statistics reports
+#[derive(Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    Text,
+    Json,
+}
+
+/// Output format for DataCore diff reports
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+    /// Markdown with summary and per-record property tables
+    Markdown,
+}
+
+/// Output format for P4K manifests
+#[derive(Clone, Copy, ValueEnum)]
+enum ManifestFormat {
+    /// Entries with sizes, CRC32 and SHA-256, plus a digest of the whole manifest
+    Json,
+    /// `sha256sum` lines, checkable with `sha256sum -c` in an extraction
+    Sha256sum,
+}
+
+/// Output format for localization exports
+#[derive(Clone, Copy, ValueEnum)]
+enum LocaleFormat {
+    /// key,value rows with a header
+    Csv,
+    /// One object mapping keys to text, in file order
+    Json,
+}
+
+fn main() -> ExitCode {
+    // The configuration supplies argument defaults, so it's read first
+    let args: Vec<_> = std::env::args_os().collect();
+    let (settings, install) = match load_settings(&args) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            output::error(&err, None);
+            return ExitCode::FAILURE;
+        }
+    };
+    let matches = install::args(settings.apply(Cli::command())).get_matches_from(args);
+    let cli =
+        Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.format(&mut Cli::command()).exit());
+    output::init(cli.message_format, cli.quiet);
+    if let Some(install) = &install {
+        install.report();
+    }
+
+    #[cfg(feature = "tracing")]
+    if let Some(filter) = &cli.log {
+        init_tracing(filter);
+    }
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            output::error(&err, error_location(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Settings from `svarog.toml`, with the archive found by `--auto` as the
+/// default `--p4k`.
+fn load_settings(
+    args: &[std::ffi::OsString],
+) -> Result<(config::Settings, Option<install::Install>)> {
+    let mut settings = config::Settings::load(args)?;
+    let install = install::from_args(args)?;
+    if let Some(install) = &install {
+        settings.p4k = Some(install.p4k.clone());
+    }
+    Ok((settings, install))
+}
+
+/// Log spans and events to stderr, with the duration of each span when it closes.
+#[cfg(feature = "tracing")]
+fn init_tracing(filter: &str) {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::EnvFilter;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new(filter))
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Find where in the input file parsing failed, from the first svarog error
+/// in the chain that knows its byte offset.
+fn error_location(err: &anyhow::Error) -> Option<(Option<&'static str>, usize)> {
+    err.chain().find_map(|cause| {
+        let e: &dyn ErrorLocation = if let Some(e) = cause.downcast_ref::<svarog::common::Error>() {
+            e
+        } else if let Some(e) = cause.downcast_ref::<svarog::p4k::Error>() {
+            e
+        } else if let Some(e) = cause.downcast_ref::<svarog::datacore::Error>() {
+            e
+        } else if let Some(e) = cause.downcast_ref::<svarog::cryxml::Error>() {
+            e
+        } else if let Some(e) = cause.downcast_ref::<svarog::chf::Error>() {
+            e
+        } else if let Some(e) = cause.downcast_ref::<svarog::dds::Error>() {
+            e
+        } else {
+            cause.downcast_ref::<svarog::wwise::Error>()?
+        };
+        e.offset().map(|offset| (e.section(), offset))
+    })
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Commands::P4kExtract {
+            p4k,
+            output,
+            output_archive,
+            filter,
+            regex,
+            incremental,
+            extract_dcb,
+            expand_socpak,
+            merge_dds,
+            delete_dds_parts,
+            parallel,
+            dry_run,
+            io,
+        } => {
+            let output = match (&output, &output_archive) {
+                (_, Some(archive)) => ExtractOutput::Archive(archive),
+                (Some(dir), None) => ExtractOutput::Directory(dir),
+                (None, None) => unreachable!("clap requires --output or --output-archive"),
+            };
+            cmd_p4k_extract(
+                &p4k,
+                output,
+                filter.as_deref(),
+                regex,
+                incremental,
+                extract_dcb,
+                expand_socpak,
+                merge_dds,
+                delete_dds_parts,
+                parallel,
+                dry_run,
+                io.map(Into::into),
+            )?;
+        }
+        Commands::P4kList { p4k, filter, detailed } => {
+            cmd_p4k_list(&p4k, filter.as_deref(), detailed)?;
+        }
+        Commands::P4kCat { p4k, entry, xml } => {
+            cmd_p4k_cat(&p4k, &entry, xml)?;
+        }
+        Commands::P4kManifest {
+            p4k,
+            filter,
+            format,
+            output,
+        } => {
+            cmd_p4k_manifest(&p4k, filter.as_deref(), format, output.as_deref())?;
+        }
+        Commands::CryxmlConvert { input, output } => {
+            cmd_cryxml_convert(&input, &output)?;
+        }
+        Commands::CryxmlConvertAll { input, output } => {
+            cmd_cryxml_convert_all(&input, output.as_ref())?;
+        }
+        Commands::CryxmlDiff { old, new, output } => {
+            cmd_cryxml_diff(&old, &new, output.as_deref())?;
+        }
+        Commands::CryxmlCreate { input, output } => {
+            cmd_cryxml_create(&input, &output)?;
+        }
+        Commands::DcbExtract {
+            input,
+            output,
+            output_archive,
+            filter,
+            locale,
+            canonical,
+            weak_pointer_names,
+            format,
+        } => {
+            let output = match (&output, &output_archive) {
+                (_, Some(archive)) => ExtractOutput::Archive(archive),
+                (Some(dir), None) => ExtractOutput::Directory(dir),
+                (None, None) => unreachable!("clap requires --output or --output-archive"),
+            };
+            cmd_dcb_extract(
+                &input,
+                output,
+                filter.as_deref(),
+                locale.as_deref(),
+                canonical,
+                weak_pointer_names,
+                &format,
+            )?;
+        }
+        Commands::ChfProcess {
+            input,
+            output,
+            dcb,
+            names,
+            lenient,
+        } => {
+            cmd_chf_process(&input, &output, dcb.as_deref(), names.as_deref(), lenient)?;
+        }
+        Commands::ChfUnhash {
+            hashes,
+            max_len,
+            prefix,
+            suffix,
+            charset,
+        } => {
+            cmd_chf_unhash(&hashes, max_len, &prefix, &suffix, charset.as_deref())?;
+        }
+        Commands::ChfValidate { input, dcb } => {
+            cmd_chf_validate(&input, &dcb)?;
+        }
+        Commands::ChfMorph {
+            from,
+            to,
+            amount,
+            output,
+        } => {
+            cmd_chf_morph(&from, &to, amount, &output)?;
+        }
+        Commands::ChfConvertGender {
+            input,
+            output,
+            gender,
+            mapping,
+        } => {
+            cmd_chf_convert_gender(&input, &output, &gender, mapping.as_deref())?;
+        }
+        Commands::ChfBatch {
+            input_dir,
+            script,
+            output_dir,
+            watch,
+            interval,
+        } => {
+            cmd_chf_batch(&input_dir, &script, output_dir.as_deref(), watch, interval)?;
+        }
+        Commands::ChfEdit {
+            input,
+            output,
+            set,
+            patch,
+        } => {
+            cmd_chf_edit(&input, &output, set, patch.as_deref())?;
+        }
+        Commands::DdsMerge { input, output } => {
+            cmd_dds_merge(&input, &output)?;
+        }
+        Commands::DdsConvert {
+            input,
+            output,
+            to,
+            zstd,
+        } => {
+            cmd_dds_convert(&input, &output, to, zstd)?;
+        }
+        Commands::DdsEncode {
+            input,
+            output,
+            format,
+            filter,
+            no_mips,
+            split,
+        } => {
+            cmd_dds_encode(&input, &output, format, filter, no_mips, split)?;
+        }
+        Commands::DdsInfo { input, repair } => {
+            cmd_dds_info(&input, repair)?;
+        }
+        Commands::DcbBundle {
+            input,
+            record,
+            output,
+        } => {
+            cmd_dcb_bundle(&input, &record, &output)?;
+        }
+        Commands::DcbSchema {
+            input,
+            output,
+            format,
+            flatten,
+            split,
+        } => {
+            cmd_dcb_schema(&input, &output, format, flatten, split)?;
+        }
+        Commands::DcbDiff {
+            old,
+            new,
+            format,
+            output,
+            guid_only,
+            types,
+            paths,
+        } => {
+            cmd_dcb_diff(
+                &old,
+                &new,
+                format,
+                output.as_deref(),
+                guid_only,
+                &types,
+                &paths,
+            )?;
+        }
+        Commands::DcbTable {
+            input,
+            type_name,
+            output,
+            format,
+            locale,
+        } => {
+            cmd_dcb_table(&input, &type_name, &output, format, locale.as_deref())?;
+        }
+        Commands::DcbImport { input, xml, output } => {
+            cmd_dcb_import(&input, &xml, &output)?;
+        }
+        Commands::DcbMerge {
+            input,
+            with,
+            output,
+            conflicts,
+        } => {
+            cmd_dcb_merge(&input, &with, &output, conflicts)?;
+        }
+        Commands::DcbStats { input, format } => {
+            cmd_dcb_stats(&input, format)?;
+        }
+        Commands::Shell { p4k } => {
+            shell::run(&p4k)?;
+        }
+        Commands::Serve {
+            p4k,
+            dcb,
+            against,
+            listen,
+            parallel,
+            max_entry_size,
+            max_ratio,
+            allow,
+        } => {
+            let threads = match parallel {
+                0 => std::thread::available_parallelism().map_or(4, |n| n.get()),
+                n => n,
+            };
+            let mut limits = svarog::p4k::DecompressionLimits::new();
+            if max_entry_size > 0 {
+                limits = limits.max_size(max_entry_size.saturating_mul(1 << 20));
+            }
+            if let Some(ratio) = max_ratio {
+                limits = limits.max_ratio(ratio);
+            }
+            let limits = allow
+                .into_iter()
+                .fold(limits, |limits, name| limits.allow(name));
+            let options = serve::ServeOptions {
+                listen: &listen,
+                dcb,
+                against: against.as_deref(),
+                threads,
+                limits,
+            };
+            serve::run(&p4k, &options)?;
+        }
+        Commands::Watch {
+            dir,
+            backup_dir,
+            no_backup,
+            dcb,
+            json,
+            cryxml,
+            debounce,
+        } => {
+            let backup_dir = backup_dir.unwrap_or_else(|| dir.join("backups"));
+            let options = watch::WatchOptions {
+                backup_dir: (!no_backup).then_some(backup_dir.as_path()),
+                dcb: dcb.as_deref(),
+                json,
+                cryxml,
+                debounce: std::time::Duration::from_millis(debounce),
+            };
+            watch::run(&dir, &options)?;
+        }
+        Commands::Socpak { command } => match command {
+            SocpakCommand::List { input, p4k } => {
+                socpak::list(&socpak::read(&input, p4k.as_deref())?)?;
+            }
+            SocpakCommand::Extract { input, output, p4k } => {
+                socpak::extract(&socpak::read(&input, p4k.as_deref())?, &output)?;
+            }
+            SocpakCommand::Create {
+                input,
+                output,
+                template,
+                compression,
+            } => {
+                socpak::create(&input, &output, template.as_deref(), compression)?;
+            }
+        },
+        Commands::Locale {
+            p4k,
+            ini,
+            lang,
+            command,
+        } => {
+            cmd_locale(p4k.as_deref(), ini.as_deref(), &lang, command)?;
+        }
+        Commands::Search {
+            p4k,
+            query,
+            properties,
+            case_sensitive,
+            no_datacore,
+        } => {
+            cmd_search(&p4k, &query, properties, case_sensitive, no_datacore)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Case-insensitive path mapper for merging DCB output with existing folders.
+///
+/// The DCB exports files with lowercase paths, but the P4K archive has mixed case.
+/// This mapper ensures we use the existing case when a folder already exists.
+struct CaseInsensitivePathMapper {
+    /// Maps lowercase path components to their actual case on disk
+    component_cache: Mutex<HashMap<String, String>>,
+}
+
+impl CaseInsensitivePathMapper {
+    fn new() -> Self {
+        Self {
+            component_cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Resolve a path using existing case from the filesystem.
+    ///
+    /// For each component in the path, checks if a case-insensitive match exists
+    /// on disk and uses that case instead. This allows DCB exports (lowercase)
+    /// to merge with existing P4K extracts (mixed case).
+    fn resolve(&self, base: &Path, relative: &str) -> PathBuf {
+        let mut result = base.to_path_buf();
+        let components: Vec<&str> = relative.split(['/', '\\']).filter(|s| !s.is_empty()).collect();
+
+        for (i, component) in components.iter().enumerate() {
+            let is_last = i == components.len() - 1;
+
+            // Build the cache key (lowercase path so far)
+            let cache_key = if i == 0 {
+                component.to_lowercase()
+            } else {
+                let prefix: String = components[..i]
+                    .iter()
+                    .map(|c| c.to_lowercase())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                format!("{}/{}", prefix, component.to_lowercase())
+            };
+
+            // Check cache first
+            {
+                let cache = self.component_cache.lock().unwrap();
+                if let Some(cached) = cache.get(&cache_key) {
+                    result.push(cached);
+                    continue;
+                }
+            }
+
+            // Try to find existing entry with matching case
+            let matched_name = if result.exists() {
+                self.find_case_insensitive_match(&result, component)
+            } else {
+                None
+            };
+
+            let actual_name = matched_name.unwrap_or_else(|| component.to_string());
+
+            // Cache the result (only for directories, not the final file)
+            if !is_last {
+                let mut cache = self.component_cache.lock().unwrap();
+                cache.insert(cache_key, actual_name.clone());
+            }
+
+            result.push(&actual_name);
+        }
+
+        result
+    }
+
+    /// Find a case-insensitive match in a directory.
+    fn find_case_insensitive_match(&self, dir: &Path, target: &str) -> Option<String> {
+        let target_lower = target.to_lowercase();
+
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                if let Some(name_str) = name.to_str() {
+                    if name_str.to_lowercase() == target_lower {
+                        return Some(name_str.to_string());
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// An entry of `p4k-extract` after the incremental checks.
+enum ExtractJob<'a> {
+    /// Already up to date on disk
+    Skip(ExtractOutcome),
+    Extract {
+        index: usize,
+        name: &'a str,
+        output_path: PathBuf,
+        /// Entry fetched by `--io`; read by the decoder otherwise
+        stored: Option<svarog::p4k::Result<svarog::p4k::RawEntry>>,
+    },
+}
+
+impl ExtractJob<'_> {
+    /// Archive index of the entry to extract.
+    fn index(&self) -> Option<usize> {
+        match self {
+            ExtractJob::Skip(_) => None,
+            ExtractJob::Extract { index, .. } => Some(*index),
+        }
+    }
+}
+
+/// What happened to one entry of `p4k-extract`.
+#[derive(Default)]
+struct ExtractOutcome {
+    /// Entry name with `/` separators
+    name: String,
+    /// Files to write, emptied by [`write_entry`]
+    files: Vec<(PathBuf, Vec<u8>)>,
+    skipped: bool,
+    failed: bool,
+    socpak_files: u64,
+    cryxml_decoded: u64,
+    dcb_records: u64,
+    warnings: Vec<String>,
+}
+
+impl svarog::process::Output for ExtractOutcome {
+    fn write(&mut self, path: PathBuf, data: Vec<u8>) {
+        self.files.push((path, data));
+    }
+
+    fn warn(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+
+    fn processed(&mut self, processor: &str, path: &Path) {
+        match processor {
+            "cryxml" => self.cryxml_decoded += 1,
+            // Everything written so far came out of the SOCPAK
+            "socpak" => self.socpak_files = self.files.len() as u64,
+            // Everything but the database and the manifest is a record
+            "datacore" => {
+                let manifest = svarog::datacore::ExportManifest::FILE_NAME;
+                self.dcb_records = self
+                    .files
+                    .iter()
+                    .filter(|(file, _)| file != path && !file.ends_with(manifest))
+                    .count() as u64;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Decode stage of `p4k-extract`: decompress an entry and pass it through
+/// the processing pipeline.
+fn decode_entry(
+    archive: &P4kArchive,
+    pipeline: &svarog::process::Pipeline,
+    job: ExtractJob<'_>,
+) -> ExtractOutcome {
+    let (index, name, output_path, stored) = match job {
+        ExtractJob::Skip(outcome) => return outcome,
+        ExtractJob::Extract {
+            index,
+            name,
+            output_path,
+            stored,
+        } => (index, name, output_path, stored),
+    };
+    let mut outcome = ExtractOutcome {
+        name: name.replace('\\', "/"),
+        ..Default::default()
+    };
+
+    // Read entry data
+    let data = match stored {
+        Some(stored) => stored.and_then(|raw| archive.decode(raw)),
+        None => archive.read_index(index),
+    };
+    let data = match data {
+        Ok(d) => d,
+        Err(e) => {
+            outcome
+                .warnings
+                .push(format!("Failed to read {}: {}", name, e));
+            outcome.failed = true;
+            return outcome;
+        }
+    };
+
+    pipeline.process(output_path, data, &mut outcome);
+    outcome
+}
+
+/// Write stage of `p4k-extract`.
+fn write_entry(mut outcome: ExtractOutcome) -> ExtractOutcome {
+    for (path, data) in std::mem::take(&mut outcome.files) {
+        // Create parent directories
+        if let Some(parent) = path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                outcome.warnings.push(format!(
+                    "Failed to create directory {}: {}",
+                    parent.display(),
+                    e
+                ));
+                outcome.failed = true;
+                continue;
+            }
+        }
+        if let Err(e) = fs::write(&path, data) {
+            outcome
+                .warnings
+                .push(format!("Failed to write {}: {}", path.display(), e));
+            outcome.failed = true;
+        }
+    }
+    outcome
+}
+
+/// Check if a file is an undecoded CryXML file by reading its magic bytes.
+/// If so, decode it in place. Returns true if decoded.
+fn check_and_decode_cryxml(path: &Path) -> bool {
+    // Read first 8 bytes to check for CryXML magic
+    let mut file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let mut magic = [0u8; 8];
+    if std::io::Read::read_exact(&mut file, &mut magic).is_err() {
+        return false;
+    }
+
+    if &magic != b"CryXmlB\0" {
+        return false;
+    }
+
+    // It's a CryXML file - decode it
+    try_decode_cryxml_inplace(path).unwrap_or(false)
+}
+
+/// File extensions that may hold CryXmlB data.
+const CRYXML_EXTENSIONS: &[&str] = &["xml", "mtl", "cdf", "chrparams", "adb", "rmxml"];
+
+/// Check if a file should be skipped during incremental extraction.
+fn should_skip_file(output_path: &Path, expected_size: u64) -> bool {
+    if let Ok(metadata) = fs::metadata(output_path) {
+        metadata.len() == expected_size
+    } else {
+        false
+    }
+}
+
+/// Check if a directory contains any files (recursively).
+/// Returns false for empty directories or directories containing only empty subdirectories.
+fn has_any_files(dir: &Path) -> bool {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() || (path.is_dir() && has_any_files(&path)) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Check whether an entry has to be extracted to `output_path`, or expanded
+/// into `socpak_dir`, given what is already on disk. Only reads the
+/// filesystem, so `--dry-run` can use it too.
+fn needs_extract(
+    output_path: &Path,
+    socpak_dir: Option<&Path>,
+    size: u64,
+    incremental: bool,
+) -> bool {
+    match socpak_dir {
+        // Empty dirs or dirs with only empty subdirs are re-extracted
+        Some(dir) => !has_any_files(dir),
+        None => !(incremental && should_skip_file(output_path, size)),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_p4k_extract(
+    p4k_path: &PathBuf,
+    output: ExtractOutput<'_>,
+    filter: Option<&str>,
+    use_regex: bool,
+    incremental: bool,
+    extract_dcb: bool,
+    expand_socpak: bool,
+    merge_dds: bool,
+    delete_dds_parts: bool,
+    parallel: usize,
+    dry_run: bool,
+    io: Option<svarog::p4k::IoBackend>,
+) -> Result<()> {
+    // Files go into the archive under their entry names, so there is nothing
+    // on disk to compare against or merge with
+    let (output, archive_path) = match output {
+        ExtractOutput::Directory(dir) => (dir.as_path(), None),
+        ExtractOutput::Archive(path) => (Path::new(""), Some(path)),
+    };
+    let to_archive = archive_path.is_some();
+    let incremental = incremental && !to_archive;
+
+    status!("Opening P4K archive: {}", p4k_path.display());
+
+    let start = Instant::now();
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+
+    status!(
+        "Loaded {} entries in {:?}",
+        archive.entry_count(),
+        start.elapsed()
+    );
+
+    // Compile regex if using regex mode
+    let regex_filter = if use_regex {
+        filter.map(|p| regex::Regex::new(p).context("Invalid regex pattern")).transpose()?
+    } else {
+        None
+    };
+
+    // DataCore databases are exported whether or not the filter matches them
+    let is_dcb = |name: &str| extract_dcb && name.to_lowercase().ends_with(".dcb");
+    let is_selected = |name: &str| match (&regex_filter, filter) {
+        (Some(re), _) => re.is_match(name),
+        (None, Some(pattern)) => glob_match(pattern, name),
+        (None, None) => true,
+    };
+
+    // Collect matching indices
+    let entries: Vec<_> = archive
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| is_selected(e.name) || is_dcb(e.name))
+        .map(|(i, e)| (i, e.name.to_string(), e.uncompressed_size))
+        .collect();
+
+    if !dry_run {
+        status!("Extracting {} entries from P4K...", entries.len());
+    }
+
+    let dcb_count = entries
+        .iter()
+        .filter(|(_, name, _)| is_dcb(name.as_str()))
+        .count();
+    if dcb_count > 0 {
+        status!(
+            "Found {} DCB file(s) - will extract and process DataCore",
+            dcb_count
+        );
+    }
+
+    let total_size = entries.iter().map(|(_, _, size)| size).sum();
+    let pool = pipeline::Pool::new(parallel, entries.len(), total_size);
+
+    // Conversions applied to each entry on its way to disk
+    let mut processors = svarog::process::Pipeline::new();
+    if expand_socpak {
+        processors.push(Box::new(svarog::process::SocpakProcessor));
+    }
+    processors.push(Box::new(svarog::process::CryXmlProcessor));
+    if extract_dcb {
+        // Records are skipped by content hash rather than by file size
+        processors.push(Box::new(
+            svarog::process::DataCoreProcessor::new()
+                .root(output)
+                .manifest(!to_archive)
+                .incremental(incremental),
+        ));
+    }
+    if merge_dds {
+        processors.push(Box::new(
+            svarog::process::DdsMergeProcessor::new().delete_parts(delete_dds_parts),
+        ));
+    }
+
+    // Path mapper for case-insensitive merging
+    let path_mapper = CaseInsensitivePathMapper::new();
+
+    if dry_run {
+        let plan = ExtractPlan {
+            output,
+            to_archive,
+            incremental,
+            expand_socpak,
+            extract_dcb,
+            pool,
+            processors: &processors,
+        };
+        return print_extract_plan(&archive, &entries, &plan, &path_mapper);
+    }
+
+    let mut archive_out = match archive_path {
+        Some(path) => Some((path, archive::ArchiveWriter::create(path)?)),
+        None => None,
+    };
+
+    let pb = create_progress_bar(entries.len() as u64, Stage::P4kExtract);
+
+    if !to_archive {
+        fs::create_dir_all(output)?;
+    }
+
+    status!(
+        "Using {} decode and {} write workers",
+        pool.decoders,
+        pool.writers
+    );
+
+    // Statistics
+    let mut extracted = 0u64;
+    let mut skipped = 0u64;
+    let mut socpak_expanded = 0u64;
+    let mut cryxml_decoded = 0u64;
+    let mut dcb_records = 0u64;
+    let mut errors = 0u64;
+
+    let start = Instant::now();
+
+    // Incremental checks run on the pool's feeder thread, in archive order
+    let jobs = entries.iter().map(|(idx, name, size)| {
+        let name_normalized = name.replace('\\', "/");
+        let output_path = if to_archive {
+            PathBuf::from(&name_normalized)
+        } else {
+            path_mapper.resolve(output, &name_normalized)
+        };
+
+        // Check if this is a SOCPAK file
+        let is_socpak = expand_socpak && name_normalized.to_lowercase().ends_with(".socpak");
+
+        // For SOCPAK files, we check if the extracted directory exists
+        let socpak_dir = if is_socpak {
+            Some(output_path.with_extension(""))
+        } else {
+            None
+        };
+
+        let mut skip = ExtractOutcome {
+            name: name_normalized,
+            skipped: true,
+            ..Default::default()
+        };
+        let should_extract = to_archive
+            || is_dcb(name)
+            || needs_extract(&output_path, socpak_dir.as_deref(), *size, incremental);
+        if !to_archive {
+            if let Some(ref dir) = socpak_dir {
+                if !should_extract {
+                    // Directory has actual files - delete .socpak if present
+                    if output_path.exists() {
+                        let _ = fs::remove_file(&output_path);
+                    }
+                } else if dir.exists() {
+                    // No files found - remove empty tree and re-extract
+                    let _ = fs::remove_dir_all(dir);
+                }
+            } else if !should_extract && check_and_decode_cryxml(&output_path) {
+                // File exists with matching size - but it was undecoded CryXML
+                skip.cryxml_decoded += 1;
+            }
+        }
+
+        if should_extract {
+            ExtractJob::Extract {
+                index: *idx,
+                name,
+                output_path,
+                stored: None,
+            }
+        } else {
+            ExtractJob::Skip(skip)
+        }
+    });
+
+    // Queued reads run on the feeder thread too, ahead of the decoders
+    let jobs: Box<dyn Iterator<Item = ExtractJob<'_>> + Send + '_> = match io {
+        Some(backend) => {
+            let options = svarog::p4k::ReadAheadOptions::new().backend(backend);
+            let read_ahead = archive
+                .read_ahead(jobs, ExtractJob::index, options)
+                .with_context(|| format!("Failed to set up {} reads", backend))?;
+            status!("Reading entries ahead through {}", backend);
+            Box::new(read_ahead.map(|(mut job, raw)| {
+                if let ExtractJob::Extract { stored, .. } = &mut job {
+                    *stored = raw;
+                }
+                job
+            }))
+        }
+        None => Box::new(jobs),
+    };
+
+    pool.run(
+        jobs,
+        |job| decode_entry(&archive, &processors, job),
+        |outcome| {
+            if to_archive {
+                outcome
+            } else {
+                write_entry(outcome)
+            }
+        },
+        |mut outcome| {
+            // Entries are added to the archive in archive order
+            if let Some((_, writer)) = &mut archive_out {
+                for (path, data) in std::mem::take(&mut outcome.files) {
+                    if let Err(e) = writer.add(&path, &data) {
+                        warning!("Failed to add {} to archive: {:#}", path.display(), e);
+                        outcome.failed = true;
+                    }
+                }
+            }
+
+            // Update progress with current file
+            let stage = if outcome.socpak_files > 0 {
+                Stage::SocpakExpand
+            } else {
+                Stage::P4kExtract
+            };
+            set_progress_message(&pb, stage, &outcome.name);
+            for message in &outcome.warnings {
+                warning!("{}", message);
+            }
+            if outcome.skipped {
+                skipped += 1;
+            } else if outcome.failed {
+                errors += 1;
+            } else {
+                extracted += 1;
+            }
+            socpak_expanded += outcome.socpak_files;
+            cryxml_decoded += outcome.cryxml_decoded;
+            dcb_records += outcome.dcb_records;
+            pb.inc(1);
+        },
+    );
+
+    pb.finish_with_message("P4K extraction complete");
+
+    let extracted_count = extracted;
+    let skipped_count = skipped;
+    let error_count = errors;
+
+    status!(
+        "\nExtracted {} files, skipped {} (unchanged), {} errors in {:?}",
+        extracted_count,
+        skipped_count,
+        error_count,
+        start.elapsed()
+    );
+    if output::json() {
+        output::emit(serde_json::json!({
+            "stage": Stage::P4kExtract.prefix(),
+            "extracted": extracted_count,
+            "skipped": skipped_count,
+            "errors": error_count,
+            "socpak_files": socpak_expanded,
+            "cryxml_decoded": cryxml_decoded,
+            "dcb_records": dcb_records,
+            "elapsed_ms": start.elapsed().as_millis(),
+        }));
+    }
+
+    // Debug: warn if incremental mode isn't working as expected
+    if incremental && skipped_count == 0 && extracted_count > 0 {
+        warning!("Warning: incremental mode enabled but no files were skipped - this may indicate a path mismatch");
+    }
+
+    let socpak_count = socpak_expanded;
+    let cryxml_count = cryxml_decoded;
+
+    if socpak_count > 0 || cryxml_count > 0 || dcb_records > 0 {
+        let mut parts = Vec::new();
+        if socpak_count > 0 {
+            parts.push(format!("{} files from SOCPAK archives", socpak_count));
+        }
+        if cryxml_count > 0 {
+            parts.push(format!("{} CryXML decoded", cryxml_count));
+        }
+        if dcb_records > 0 {
+            parts.push(format!("{} DataCore records exported", dcb_records));
+        }
+        status!("{}", parts.join(", "));
+    }
+
+    // Steps over the whole output tree, such as merging split DDS textures
+    if !to_archive {
+        for processor in processors.processors() {
+            let finish_start = Instant::now();
+            let name = processor.name();
+            match processor.finish(output) {
+                Ok(Some(report)) => {
+                    for message in &report.warnings {
+                        warning!("{}", message);
+                    }
+                    status!("\n{} in {:?}", report.summary, finish_start.elapsed());
+                    if output::json() {
+                        let mut value = serde_json::json!({
+                            "stage": name.to_uppercase(),
+                            "elapsed_ms": finish_start.elapsed().as_millis(),
+                        });
+                        for (key, count) in &report.counts {
+                            value[*key] = (*count).into();
+                        }
+                        output::emit(value);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => warning!("Failed to run {} on {}: {}", name, output.display(), e),
+            }
+        }
+    }
+
+    if let Some((path, writer)) = archive_out {
+        writer.finish().context("Failed to finish archive")?;
+        status!("\nWrote {}", path.display());
+        if output::json() {
+            output::emit(serde_json::json!({ "archive": path }));
+        }
+    }
+
+    Ok(())
+}
+
+/// Settings of a `p4k-extract --dry-run`.
+struct ExtractPlan<'a> {
+    output: &'a Path,
+    to_archive: bool,
+    incremental: bool,
+    expand_socpak: bool,
+    extract_dcb: bool,
+    pool: pipeline::Pool,
+    /// Conversions timed on the sampled entries
+    processors: &'a svarog::process::Pipeline,
+}
+
+/// Number and size of the entries given one action by `--dry-run`.
+#[derive(Default, serde::Serialize)]
+struct PlanTotal {
+    count: u64,
+    uncompressed_size: u64,
+    compressed_size: u64,
+}
+
+/// Entries decoded by `--dry-run` to estimate the time of the real run.
+const PLAN_SAMPLE_ENTRIES: usize = 64;
+
+/// Uncompressed size after which the `--dry-run` sample stops early.
+const PLAN_SAMPLE_BYTES: u64 = 256 << 20;
+
+/// `p4k-extract --dry-run`: print the action for each entry and the totals,
+/// reading the output directory but never writing to it.
+///
+/// The time estimate comes from decoding an evenly spread sample of the
+/// entries to extract, scaled to all of them and the decode workers. Disk
+/// writes aren't part of it.
+fn print_extract_plan(
+    archive: &P4kArchive,
+    entries: &[(usize, String, u64)],
+    plan: &ExtractPlan<'_>,
+    path_mapper: &CaseInsensitivePathMapper,
+) -> Result<()> {
+    let mut extract = PlanTotal::default();
+    let mut expand = PlanTotal::default();
+    let mut skip = PlanTotal::default();
+    let mut planned = Vec::new();
+
+    for (idx, name, size) in entries {
+        let name_normalized = name.replace('\\', "/");
+        let output_path = if plan.to_archive {
+            PathBuf::from(&name_normalized)
+        } else {
+            path_mapper.resolve(plan.output, &name_normalized)
+        };
+        let socpak_dir = (plan.expand_socpak
+            && name_normalized.to_lowercase().ends_with(".socpak"))
+        .then(|| output_path.with_extension(""));
+
+        // DataCore records are compared with the manifest while exporting
+        let is_dcb = plan.extract_dcb && name_normalized.to_lowercase().ends_with(".dcb");
+
+        let should_extract = plan.to_archive
+            || is_dcb
+            || needs_extract(&output_path, socpak_dir.as_deref(), *size, plan.incremental);
+        let (action, total) = if !should_extract {
+            ("skip", &mut skip)
+        } else if socpak_dir.is_some() {
+            ("expand", &mut expand)
+        } else {
+            ("extract", &mut extract)
+        };
+        let compressed_size = archive.get(*idx).map_or(0, |e| e.compressed_size);
+        total.count += 1;
+        total.uncompressed_size += size;
+        total.compressed_size += compressed_size;
+
+        if output::json() {
+            output::emit(serde_json::json!({
+                "action": action,
+                "name": name_normalized,
+                "compressed_size": compressed_size,
+                "uncompressed_size": size,
+            }));
+        } else {
+            println!("{:<8} {:>12} {}", action, size, name_normalized);
+        }
+
+        if should_extract {
+            planned.push(ExtractJob::Extract {
+                index: *idx,
+                name,
+                output_path,
+                stored: None,
+            });
+        }
+    }
+
+    let to_do = planned.len();
+    let step = to_do.div_ceil(PLAN_SAMPLE_ENTRIES).max(1);
+    let sample_start = Instant::now();
+    let mut sampled = 0;
+    let mut sampled_bytes = 0;
+    for job in planned.into_iter().step_by(step) {
+        if sampled_bytes >= PLAN_SAMPLE_BYTES {
+            break;
+        }
+        let outcome = decode_entry(archive, plan.processors, job);
+        sampled_bytes += outcome
+            .files
+            .iter()
+            .map(|(_, data)| data.len() as u64)
+            .sum::<u64>();
+        sampled += 1;
+    }
+    let estimate = (sampled > 0).then(|| {
+        sample_start
+            .elapsed()
+            .mul_f64(to_do as f64 / sampled as f64)
+            / plan.pool.decoders as u32
+    });
+
+    status!(
+        "\nWould extract {} files ({}), expand {} SOCPAKs ({}) and skip {} unchanged ({})",
+        extract.count,
+        format_size(extract.uncompressed_size),
+        expand.count,
+        format_size(expand.uncompressed_size),
+        skip.count,
+        format_size(skip.uncompressed_size)
+    );
+    status!(
+        "Reading {} compressed from the archive",
+        format_size(extract.compressed_size + expand.compressed_size)
+    );
+    if let Some(estimate) = estimate {
+        status!(
+            "Estimated time: {} with {} decode workers (from {} sampled entries, excluding disk writes)",
+            format_duration(estimate),
+            plan.pool.decoders,
+            sampled
+        );
+    }
+    if output::json() {
+        output::emit(serde_json::json!({
+            "stage": Stage::P4kExtract.prefix(),
+            "dry_run": true,
+            "extract": extract,
+            "expand": expand,
+            "skip": skip,
+            "estimated_ms": estimate.map(|e| e.as_millis()),
+        }));
+    }
+
+    Ok(())
+}
+
+/// Format a byte count with a binary unit, e.g. `1.5 GiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Format a duration to the second, e.g. `1h 02m 03s`.
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {:02}s", m, s),
+        (h, m, s) => format!("{}h {:02}m {:02}s", h, m, s),
+    }
+}
+
+fn cmd_p4k_list(p4k_path: &PathBuf, filter: Option<&str>, detailed: bool) -> Result<()> {
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+
+    let mut count = 0;
+    for entry in archive.iter() {
+        if let Some(pattern) = filter {
+            if !glob_match(pattern, entry.name) {
+                continue;
+            }
+        }
+
+        if output::json() {
+            output::emit(serde_json::json!({
+                "name": entry.name,
+                "compressed_size": entry.compressed_size,
+                "uncompressed_size": entry.uncompressed_size,
+                "encrypted": entry.is_encrypted,
+            }));
+        } else if detailed {
+            println!(
+                "{:>12} {:>12} {} {}",
+                entry.compressed_size,
+                entry.uncompressed_size,
+                if entry.is_encrypted { "E" } else { " " },
+                entry.name
+            );
+        } else {
+            println!("{}", entry.name);
+        }
+        count += 1;
+    }
 
-/// Check if a file is an undecoded CryXML file by reading its magic bytes.
-/// If so, decode it in place. Returns true if decoded.
-fn check_and_decode_cryxml(path: &Path) -> bool {
-    // Read first 8 bytes to check for CryXML magic
-    let mut file = match fs::File::open(path) {
-        Ok(f) => f,
-        Err(_) => return false,
+    status!("\nTotal: {} entries", count);
+
+    Ok(())
+}
+
+fn cmd_p4k_cat(p4k_path: &Path, name: &str, xml: bool) -> Result<()> {
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+    let entry = archive
+        .find(name)
+        .with_context(|| format!("Entry not found: {}", name))?;
+    let data = archive
+        .read(&entry)
+        .with_context(|| format!("Failed to read {}", entry.name))?;
+
+    write_stdout(&cat_contents(data, xml)?)
+}
+
+/// Entry contents as `p4k-cat` writes them, with CryXmlB decoded to XML
+/// when `xml` is set.
+fn cat_contents(data: Vec<u8>, xml: bool) -> Result<Vec<u8>> {
+    if !xml || !CryXml::is_cryxml(&data) {
+        return Ok(data);
+    }
+    let cryxml = CryXml::parse(&data).context("Failed to parse CryXmlB")?;
+    Ok(cryxml
+        .to_xml_string()
+        .context("Failed to convert to XML")?
+        .into_bytes())
+}
+
+/// Write a command's output to stdout.
+fn write_stdout(data: &[u8]) -> Result<()> {
+    write_piped(std::io::stdout().lock(), data)
+}
+
+/// Write `data` to the start of a pipeline, treating a closed pipe as
+/// success.
+fn write_piped(mut out: impl std::io::Write, data: &[u8]) -> Result<()> {
+    match out.write_all(data).and_then(|()| out.flush()) {
+        // The reader of a pipeline (e.g. `head`) went away early
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("Failed to write to stdout"),
+    }
+}
+
+/// One file of a P4K manifest.
+#[derive(Debug, serde::Serialize)]
+struct ManifestEntry {
+    /// Entry name with `/` separators
+    name: String,
+    size: u64,
+    compressed_size: u64,
+    /// CRC32 stored in the archive, as 8 hex digits
+    crc32: String,
+    /// SHA-256 of the decompressed contents, before any CryXmlB decoding
+    sha256: String,
+}
+
+fn cmd_p4k_manifest(
+    p4k_path: &Path,
+    filter: Option<&str>,
+    format: ManifestFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let start = Instant::now();
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+
+    let indices = manifest_indices(&archive, filter);
+    estatus!("Hashing {} entries...", indices.len());
+    let pb = create_progress_bar(indices.len() as u64, Stage::Hash);
+    let entries = manifest_entries(&archive, &indices, &pb)?;
+    pb.finish_and_clear();
+
+    // The manifest digest is the SHA-256 of the sha256sum listing, so it can
+    // be checked against either format
+    let listing = manifest_listing(&entries);
+    let digest = format!("{:x}", Sha256::digest(listing.as_bytes()));
+
+    let manifest = serde_json::json!({
+        "archive": archive.name(),
+        "entries": entries.len(),
+        "total_size": entries.iter().map(|e| e.size).sum::<u64>(),
+        "sha256": digest,
+        "files": entries,
+    });
+
+    match (format, output) {
+        (ManifestFormat::Json, None) if output::json() => output::emit(manifest),
+        (format, output) => {
+            let data = match format {
+                ManifestFormat::Json => serde_json::to_string_pretty(&manifest)?,
+                ManifestFormat::Sha256sum => listing,
+            };
+            match output {
+                Some(path) => fs::write(path, data).context("Failed to write output file")?,
+                None => write_stdout(data.as_bytes())?,
+            }
+        }
+    }
+
+    estatus!(
+        "Manifest of {} entries in {:?}, sha256 {}",
+        entries.len(),
+        start.elapsed(),
+        digest
+    );
+
+    Ok(())
+}
+
+/// Indices of the files in `archive` matching `filter`.
+fn manifest_indices(archive: &P4kArchive, filter: Option<&str>) -> Vec<usize> {
+    archive
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !e.name.ends_with(['\\', '/']))
+        .filter(|(_, e)| filter.map_or(true, |pattern| glob_match(pattern, e.name)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Read and hash the entries at `indices`, in parallel.
+fn manifest_entries(
+    archive: &P4kArchive,
+    indices: &[usize],
+    pb: &ProgressBar,
+) -> Result<Vec<ManifestEntry>> {
+    use rayon::prelude::*;
+    use sha2::{Digest, Sha256};
+
+    indices
+        .par_iter()
+        .map(|&idx| -> Result<ManifestEntry> {
+            let entry = archive.get(idx).expect("index from iter");
+            let data = archive
+                .read_index(idx)
+                .with_context(|| format!("Failed to read {}", entry.name))?;
+            pb.inc(1);
+            Ok(ManifestEntry {
+                name: entry.name.replace('\\', "/"),
+                size: data.len() as u64,
+                compressed_size: entry.compressed_size,
+                crc32: format!("{:08x}", entry.crc32),
+                sha256: format!("{:x}", Sha256::digest(&data)),
+            })
+        })
+        .collect()
+}
+
+/// The manifest in `sha256sum` format.
+fn manifest_listing(entries: &[ManifestEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{}  {}\n", e.sha256, e.name))
+        .collect()
+}
+
+fn cmd_cryxml_convert(input: &PathBuf, output: &PathBuf) -> Result<()> {
+    status!(
+        "Converting CryXmlB to XML: {} -> {}",
+        input.display(),
+        output.display()
+    );
+
+    let data = fs::read(input).context("Failed to read input file")?;
+
+    if !CryXml::is_cryxml(&data) {
+        anyhow::bail!("Input file is not a CryXmlB file");
+    }
+
+    let cryxml = CryXml::parse(&data).context("Failed to parse CryXmlB")?;
+    let xml = cryxml.to_xml_string().context("Failed to convert to XML")?;
+    fs::write(output, xml).context("Failed to write output file")?;
+
+    status!("Conversion complete");
+
+    Ok(())
+}
+
+fn cmd_cryxml_convert_all(input: &PathBuf, output: Option<&PathBuf>) -> Result<()> {
+    use svarog::cryxml::{convert_tree_with, ConvertOptions};
+
+    let start = Instant::now();
+    let mut options = ConvertOptions::new().extensions(CRYXML_EXTENSIONS);
+    match output {
+        Some(output) => {
+            status!(
+                "Converting CryXmlB files: {} -> {}",
+                input.display(),
+                output.display()
+            );
+            options = options.output_dir(output);
+        }
+        None => status!("Converting CryXmlB files in place: {}", input.display()),
+    }
+
+    let pb = output::attach(ProgressBar::new_spinner(), Stage::CryXmlDecode.prefix());
+    let report = convert_tree_with(input, &options, |result| {
+        pb.set_message(result.path.display().to_string());
+        pb.inc(1);
+    })
+    .context("Failed to scan input directory")?;
+    pb.finish_and_clear();
+
+    for failure in report.failures() {
+        if let Err(e) = &failure.status {
+            warning!("Failed to convert {}: {}", failure.path.display(), e);
+        }
+    }
+
+    status!(
+        "Converted {} files, {} not CryXmlB, {} errors in {:?}",
+        report.converted(),
+        report.not_cryxml(),
+        report.failed(),
+        start.elapsed()
+    );
+    if output::json() {
+        output::emit(serde_json::json!({
+            "converted": report.converted(),
+            "not_cryxml": report.not_cryxml(),
+            "errors": report.failed(),
+            "elapsed_ms": start.elapsed().as_millis(),
+        }));
+    }
+
+    Ok(())
+}
+
+fn cmd_cryxml_diff(old: &Path, new: &Path, output: Option<&Path>) -> Result<()> {
+    use svarog::cryxml::CryXmlDiff;
+
+    let old_data = fs::read(old).context("Failed to read old file")?;
+    let new_data = fs::read(new).context("Failed to read new file")?;
+    let diff =
+        CryXmlDiff::compare_bytes(&old_data, &new_data).context("Failed to parse input files")?;
+
+    estatus!("{} changes", diff.changes.len());
+
+    let report = diff.to_string();
+    match output {
+        Some(path) => fs::write(path, report).context("Failed to write output file")?,
+        None => print!("{}", report),
+    }
+
+    Ok(())
+}
+
+fn cmd_cryxml_create(input: &PathBuf, output: &PathBuf) -> Result<()> {
+    use svarog::cryxml::builder::CryXmlBuilder;
+
+    status!(
+        "Converting XML to CryXmlB: {} -> {}",
+        input.display(),
+        output.display()
+    );
+
+    let xml = fs::read_to_string(input).context("Failed to read input file")?;
+
+    let builder = CryXmlBuilder::from_xml(&xml).context("Failed to parse XML")?;
+    let cryxml_bytes = builder.build().context("Failed to build CryXmlB")?;
+    fs::write(output, cryxml_bytes).context("Failed to write output file")?;
+
+    status!(
+        "Conversion complete ({} bytes)",
+        fs::metadata(output)?.len()
+    );
+
+    Ok(())
+}
+
+/// Destination of extracted files or DataCore records
+enum ExtractOutput<'a> {
+    Directory(&'a PathBuf),
+    Archive(&'a PathBuf),
+}
+
+fn cmd_dcb_extract(
+    input: &PathBuf,
+    output: ExtractOutput<'_>,
+    filter: Option<&str>,
+    locale: Option<&Path>,
+    canonical: bool,
+    weak_pointer_names: bool,
+    format: &str,
+) -> Result<()> {
+    status!("Loading DataCore: {}", input.display());
+
+    let start = Instant::now();
+    let data = fs::read(input).context("Failed to read input file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+
+    status!(
+        "Loaded in {:?}: {} structs, {} enums, {} records",
+        start.elapsed(),
+        database.struct_definitions().len(),
+        database.enum_definitions().len(),
+        database.records().len()
+    );
+
+    // Count main records
+    let main_records: Vec<_> = database.all_main_records().collect();
+    let filtered_records: Vec<_> = if let Some(pattern) = filter {
+        main_records
+            .into_iter()
+            .filter(|r| {
+                r.file_name()
+                    .map(|name| glob_match(pattern, name))
+                    .unwrap_or(false)
+            })
+            .collect()
+    } else {
+        main_records
+    };
+
+    let locale = match locale {
+        Some(path) => {
+            let resolver = svarog::datacore::LocaleResolver::open(path)
+                .context("Failed to read localization file")?;
+            status!("Loaded {} localization entries", resolver.len());
+            Some(resolver)
+        }
+        None => None,
+    };
+
+    let mut xml_exporter = svarog::XmlExporter::new(&database)
+        .canonical(canonical)
+        .weak_pointer_names(weak_pointer_names);
+    if let Some(locale) = &locale {
+        xml_exporter = xml_exporter.with_locale(locale);
+    }
+    let mut registry = svarog::datacore::ExporterRegistry::new();
+    registry.register(xml_exporter.clone());
+    registry.register(svarog::datacore::JsonExporter::new());
+    registry.register_commands();
+    let exporter = registry.get(format).with_context(|| {
+        format!(
+            "Unknown export format '{}' (available: {})",
+            format,
+            registry.names().collect::<Vec<_>>().join(", ")
+        )
+    })?;
+
+    let pb = ProgressBar::new(filtered_records.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")?
+            .progress_chars("#>-"),
+    );
+    let pb = output::attach(pb, Stage::DcbExport.prefix());
+
+    let output = match output {
+        ExtractOutput::Directory(dir) => dir,
+        ExtractOutput::Archive(path) => {
+            if exporter.name() != "xml" {
+                anyhow::bail!("--output-archive only supports the xml format");
+            }
+            status!(
+                "Exporting {} records to {}...",
+                filtered_records.len(),
+                path.display()
+            );
+
+            let start = Instant::now();
+            let file = fs::File::create(path).context("Failed to create archive")?;
+            let writer = std::io::BufWriter::new(file);
+            let options = svarog::datacore::ZipExportOptions::default();
+            let records: Vec<_> = filtered_records.iter().map(|r| r.raw()).collect();
+            let mut writer = xml_exporter
+                .export_records_to_zip(&records, writer, &options, |i, _| pb.set_position(i as u64))
+                .context("Failed to export records")?;
+            std::io::Write::flush(&mut writer)?;
+
+            pb.finish_with_message("Done");
+            status!(
+                "Exported {} records in {:?}",
+                filtered_records.len(),
+                start.elapsed()
+            );
+            if output::json() {
+                output::emit(serde_json::json!({
+                    "archive": path,
+                    "exported": filtered_records.len(),
+                    "errors": 0,
+                    "elapsed_ms": start.elapsed().as_millis(),
+                }));
+            }
+            return Ok(());
+        }
     };
 
-    let mut magic = [0u8; 8];
-    if std::io::Read::read_exact(&mut file, &mut magic).is_err() {
-        return false;
+    status!(
+        "Exporting {} records to {}...",
+        filtered_records.len(),
+        output.display()
+    );
+
+    fs::create_dir_all(output)?;
+
+    let start = Instant::now();
+    let mut exported = 0;
+    let mut errors = 0;
+
+    for &record in &filtered_records {
+        let path = exporter.path(record);
+        let output_path = output.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+
+        // Create parent directories
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Export record
+        match exporter.export(record) {
+            Ok(data) => {
+                fs::write(&output_path, data)?;
+                exported += 1;
+            }
+            Err(e) => {
+                warning!("Error exporting {}: {}", path, e);
+                errors += 1;
+            }
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_with_message("Done");
+    status!(
+        "Exported {} records in {:?} ({} errors)",
+        exported,
+        start.elapsed(),
+        errors
+    );
+    if output::json() {
+        output::emit(serde_json::json!({
+            "output": output,
+            "exported": exported,
+            "errors": errors,
+            "elapsed_ms": start.elapsed().as_millis(),
+        }));
+    }
+
+    Ok(())
+}
+
+fn cmd_chf_process(
+    input: &PathBuf,
+    output: &PathBuf,
+    dcb: Option<&Path>,
+    names: Option<&Path>,
+    lenient: bool,
+) -> Result<()> {
+    use svarog::chf::parts::{load_dictionary, ChfData};
+    use svarog::chf::ParseOptions;
+
+    status!(
+        "Processing CHF: {} -> {}",
+        input.display(),
+        output.display()
+    );
+
+    if let Some(names) = names {
+        let added = load_dictionary(names).context("Failed to read name dictionary")?;
+        status!("Loaded {} names from {}", added, names.display());
+    }
+
+    let chf = read_chf_with(input, &ParseOptions::new().strict(!lenient))?;
+
+    status!(
+        "Loaded CHF: {} bytes, modded: {}",
+        chf.data().len(),
+        chf.is_modded()
+    );
+    for warning in chf.warnings() {
+        warning!("Warning: {}", warning);
+    }
+
+    let mut summary = serde_json::json!({
+        "input": input,
+        "output": output,
+        "size": chf.data().len(),
+        "modded": chf.is_modded(),
+        "warnings": chf.warnings().iter().map(ToString::to_string).collect::<Vec<_>>(),
+    });
+
+    // Parse and display character data
+    if let Ok(data) = ChfData::parse(chf.data()) {
+        status!("Gender ID: {}", data.gender_id());
+        summary["gender_id"] = data.gender_id().to_string().into();
+
+        // Show DNA summary
+        let mut active_blends = 0;
+        for (face_part, blends) in data.dna().iter_face_parts() {
+            let blend_count = blends.iter().filter(|b| !b.is_zero()).count();
+            if blend_count > 0 {
+                active_blends += blend_count;
+                status!("  {}: {} active blends", face_part, blend_count);
+            }
+        }
+        status!("DNA: {} total active blends", active_blends);
+        summary["active_blends"] = active_blends.into();
+
+        // Show item port tree if present
+        if let Some(port) = data.item_port() {
+            status!("Item ports: {} total, depth {}", port.count(), port.depth());
+            summary["item_ports"] = port.count().into();
+        }
+
+        // Show materials
+        if !data.materials().is_empty() {
+            status!("Materials: {}", data.materials().len());
+        }
+        summary["materials"] = data.materials().len().into();
+
+        // Resolve item and port names through the DataCore
+        if let Some(dcb) = dcb {
+            status!("Loading DataCore: {}", dcb.display());
+            let dcb_data = fs::read(dcb).context("Failed to read DCB file")?;
+            let database =
+                DataCoreDatabase::parse(&dcb_data).context("Failed to parse DataCore")?;
+            let resolved = data.resolve(&database).to_string();
+            if output::json() {
+                summary["resolved"] = resolved.into();
+            } else {
+                print!("{}", resolved);
+            }
+        }
+    }
+
+    write_chf(&chf, output)?;
+
+    status!("Output written");
+    if output::json() {
+        output::emit(summary);
+    }
+
+    Ok(())
+}
+
+fn cmd_chf_validate(input: &Path, dcb: &Path) -> Result<()> {
+    use svarog::chf::parts::ChfData;
+
+    let chf = read_chf(input)?;
+    let character = ChfData::parse(chf.data()).context("Failed to parse character data")?;
+
+    status!("Loading DataCore: {}", dcb.display());
+    let data = fs::read(dcb).context("Failed to read DCB file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+
+    let report = character.validate(&database);
+    println!("{}", report);
+
+    if !report.is_valid() {
+        anyhow::bail!("{} has unresolved references", input.display());
+    }
+    Ok(())
+}
+
+fn cmd_chf_morph(from: &Path, to: &Path, amount: f32, output: &Path) -> Result<()> {
+    use svarog::chf::parts::ChfData;
+
+    if !(0.0..=1.0).contains(&amount) {
+        anyhow::bail!("Blend amount must be between 0.0 and 1.0, got {}", amount);
     }
 
-    if &magic != b"CryXmlB\0" {
-        return false;
-    }
+    status!(
+        "Morphing CHF: {} -> {} at {}",
+        from.display(),
+        to.display(),
+        amount
+    );
 
-    // It's a CryXML file - decode it
-    try_decode_cryxml_inplace(path).unwrap_or(false)
-}
+    let a = ChfData::parse(read_chf(from)?.data()).context("Failed to parse --from character")?;
+    let b = ChfData::parse(read_chf(to)?.data()).context("Failed to parse --to character")?;
+    let blended = ChfData::lerp(&a, &b, amount).context("Failed to blend characters")?;
 
-/// Scan an already-extracted SOCPAK directory for undecoded CryXML files.
-/// Only checks files with XML-like extensions. Returns count of decoded files.
-fn decode_cryxml_in_directory(dir: &Path, pb: Option<&ProgressBar>) -> Result<usize> {
-    let mut decoded = 0;
+    write_chf(&ChfFile::new(blended.to_bytes(), true), output)?;
 
-    if !dir.exists() || !dir.is_dir() {
-        return Ok(0);
-    }
+    status!("Output written to {}", output.display());
 
-    // Iterate lazily - don't collect into a Vec
-    for entry in walkdir::WalkDir::new(dir)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
+    Ok(())
+}
 
-        // Only check files with XML-like extensions that might be CryXML
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        if !matches!(
-            ext.to_lowercase().as_str(),
-            "xml" | "mtl" | "cdf" | "chrparams" | "adb" | "rmxml"
-        ) {
-            continue;
+fn cmd_chf_convert_gender(
+    input: &Path,
+    output: &Path,
+    gender: &str,
+    mapping: Option<&Path>,
+) -> Result<()> {
+    use svarog::chf::parts::ChfData;
+    use svarog::chf::GenderMapping;
+
+    let target: CigGuid = gender
+        .parse()
+        .with_context(|| format!("Invalid gender GUID: {}", gender))?;
+    let mapping = match mapping {
+        Some(path) => {
+            let json = fs::read_to_string(path).context("Failed to read mapping")?;
+            GenderMapping::from_json(&json).context("Failed to parse mapping")?
         }
+        None => GenderMapping::new(),
+    };
 
-        if let Some(pb) = pb {
-            let rel_path = path.strip_prefix(dir).unwrap_or(path);
-            set_progress_message(pb, Stage::CryXmlDecode, &rel_path.display().to_string());
-        }
+    let chf = read_chf(input)?;
+    let mut data = ChfData::parse(chf.data()).context("Failed to parse character data")?;
 
-        if check_and_decode_cryxml(path) {
-            decoded += 1;
-        }
-    }
+    status!(
+        "Converting {} from {} to {}",
+        input.display(),
+        data.gender_id(),
+        target
+    );
+    data.convert_gender(target, &mapping);
 
-    Ok(decoded)
-}
+    write_chf(&ChfFile::new(data.to_bytes(), true), output)?;
 
-/// Check if a file should be skipped during incremental extraction.
-fn should_skip_file(output_path: &Path, expected_size: u64) -> bool {
-    if let Ok(metadata) = fs::metadata(output_path) {
-        metadata.len() == expected_size
-    } else {
-        false
-    }
-}
+    status!("Output written to {}", output.display());
 
-/// Check if a directory contains any files (recursively).
-/// Returns false for empty directories or directories containing only empty subdirectories.
-fn has_any_files(dir: &Path) -> bool {
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                return true;
-            } else if path.is_dir() && has_any_files(&path) {
-                return true;
-            }
-        }
-    }
-    false
+    Ok(())
 }
 
-fn cmd_p4k_extract(
-    p4k_path: &PathBuf,
-    output: &PathBuf,
-    filter: Option<&str>,
-    use_regex: bool,
-    incremental: bool,
-    extract_dcb: bool,
-    expand_socpak: bool,
-    _parallel: usize,
+fn cmd_chf_batch(
+    input_dir: &Path,
+    script: &Path,
+    output_dir: Option<&Path>,
+    watch: bool,
+    interval: u64,
 ) -> Result<()> {
-    println!("Opening P4K archive: {}", p4k_path.display());
+    use std::time::{Duration, SystemTime};
+    use svarog::chf::{chf_files, ChfScript};
 
-    let start = Instant::now();
-    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+    let json = fs::read_to_string(script).context("Failed to read script")?;
+    let script = ChfScript::from_json(&json).context("Failed to parse script")?;
 
-    println!("Loaded {} entries in {:?}", archive.entry_count(), start.elapsed());
+    status!(
+        "Applying {} edit(s) to {}",
+        script.ops.len(),
+        input_dir.display()
+    );
 
-    // Compile regex if using regex mode
-    let regex_filter = if use_regex {
-        filter.map(|p| regex::Regex::new(p).context("Invalid regex pattern")).transpose()?
-    } else {
-        None
-    };
+    let report = script
+        .apply_to_dir(input_dir, output_dir)
+        .context("Failed to read input directory")?;
+    for result in report.failures() {
+        if let Err(e) = &result.status {
+            warning!("  {}: {}", result.path.display(), e);
+        }
+    }
+    status!(
+        "Edited {} file(s), {} failed",
+        report.succeeded(),
+        report.failed()
+    );
 
-    // Collect matching indices
-    let entries: Vec<_> = if let Some(ref re) = regex_filter {
-        archive
-            .iter()
-            .enumerate()
-            .filter(|(_, e)| re.is_match(e.name))
-            .map(|(i, e)| (i, e.name.to_string(), e.uncompressed_size))
-            .collect()
-    } else if let Some(pattern) = filter {
-        archive
-            .iter()
-            .enumerate()
-            .filter(|(_, e)| glob_match(pattern, e.name))
-            .map(|(i, e)| (i, e.name.to_string(), e.uncompressed_size))
-            .collect()
-    } else {
-        archive
-            .iter()
-            .enumerate()
-            .map(|(i, e)| (i, e.name.to_string(), e.uncompressed_size))
-            .collect()
-    };
+    if !watch {
+        return Ok(());
+    }
 
-    println!("Extracting {} entries from P4K...", entries.len());
+    // Poll modification times; edits written back in place update the
+    // recorded time so they are not picked up again.
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let mut seen: HashMap<PathBuf, Option<SystemTime>> = chf_files(input_dir)?
+        .into_iter()
+        .map(|path| {
+            let time = modified(&path);
+            (path, time)
+        })
+        .collect();
+
+    status!("Watching {} (Ctrl+C to stop)", input_dir.display());
+    loop {
+        std::thread::sleep(Duration::from_secs(interval.max(1)));
+
+        for path in chf_files(input_dir)? {
+            let time = modified(&path);
+            if seen.get(&path) == Some(&time) {
+                continue;
+            }
 
-    // Find all DCB entries if extraction is requested
-    let dcb_entries: Vec<(usize, String)> = if extract_dcb {
-        archive
-            .iter()
-            .enumerate()
-            .filter(|(_, e)| e.name.to_lowercase().ends_with(".dcb"))
-            .map(|(i, e)| (i, e.name.to_string()))
-            .collect()
-    } else {
-        Vec::new()
+            let output = match (output_dir, path.file_name()) {
+                (Some(dir), Some(name)) => dir.join(name),
+                _ => path.clone(),
+            };
+            match script.apply_to_file(&path, &output) {
+                Ok(()) => status!("  Edited {}", path.display()),
+                // Files still being copied fail to parse and are retried
+                // once their modification time changes
+                Err(e) => warning!("  {}: {}", path.display(), e),
+            }
+            seen.insert(path.clone(), modified(&path));
+        }
+    }
+}
+
+fn cmd_chf_edit(
+    input: &Path,
+    output: &Path,
+    set: Vec<svarog::chf::ChfOp>,
+    patch: Option<&Path>,
+) -> Result<()> {
+    use svarog::chf::parts::ChfData;
+    use svarog::chf::ChfScript;
+
+    let mut script = match patch {
+        Some(path) => {
+            let json = fs::read_to_string(path).context("Failed to read patch")?;
+            ChfScript::from_json(&json).context("Failed to parse patch")?
+        }
+        None => ChfScript::default(),
     };
+    script.ops.extend(set);
 
-    if !dcb_entries.is_empty() {
-        println!(
-            "Found {} DCB file(s) - will extract and process DataCore",
-            dcb_entries.len()
-        );
+    let chf = read_chf(input)?;
+    let mut data = ChfData::parse(chf.data()).context("Failed to parse character data")?;
+    for (i, op) in script.ops.iter().enumerate() {
+        op.apply(&mut data)
+            .with_context(|| format!("Edit {} of {} failed", i + 1, script.ops.len()))?;
     }
+    write_chf(&ChfFile::new(data.to_bytes(), true), output)?;
 
-    // Track ALL SOCPAK directories for CryXML post-processing check
-    let mut all_socpak_dirs: Vec<PathBuf> = Vec::new();
-
-    let pb = create_progress_bar(entries.len() as u64, Stage::P4kExtract);
+    status!(
+        "Applied {} edit(s): {} -> {}",
+        script.ops.len(),
+        input.display(),
+        output.display()
+    );
+    if output::json() {
+        output::emit(serde_json::json!({
+            "input": input,
+            "output": output,
+            "edits": script.ops.len(),
+        }));
+    }
 
-    fs::create_dir_all(output)?;
+    Ok(())
+}
 
-    // Statistics
-    let extracted = AtomicU64::new(0);
-    let skipped = AtomicU64::new(0);
-    let socpak_expanded = AtomicU64::new(0);
-    let cryxml_decoded = AtomicU64::new(0);
-    let errors = AtomicU64::new(0);
+fn cmd_chf_unhash(
+    hashes: &[String],
+    max_len: usize,
+    prefix: &str,
+    suffix: &str,
+    charset: Option<&str>,
+) -> Result<()> {
+    use svarog::chf::parts::BruteForce;
+
+    let targets = hashes
+        .iter()
+        .map(|h| {
+            let parsed = match h.strip_prefix("0x").or_else(|| h.strip_prefix("0X")) {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => h.parse(),
+            };
+            parsed.with_context(|| format!("Invalid hash: {}", h))
+        })
+        .collect::<Result<Vec<u32>>>()?;
 
-    // Path mapper for case-insensitive merging
-    let path_mapper = CaseInsensitivePathMapper::new();
+    let mut search = BruteForce::new(max_len).prefix(prefix).suffix(suffix);
+    if let Some(charset) = charset {
+        search = search.charset(charset);
+    }
 
+    status!(
+        "Searching {} hash(es) up to {} characters...",
+        targets.len(),
+        max_len
+    );
     let start = Instant::now();
+    let found = search.find(&targets);
 
-    for (idx, name, size) in &entries {
-        let name_normalized = name.replace('\\', "/");
-        let output_path = path_mapper.resolve(output, &name_normalized);
+    for (hash, name) in &found {
+        println!("  0x{:08X} = {}", hash.value(), name);
+    }
+    status!(
+        "Found {} candidate(s) in {:?}",
+        found.len(),
+        start.elapsed()
+    );
 
-        // Check if this is a SOCPAK file
-        let is_socpak = expand_socpak && name_normalized.to_lowercase().ends_with(".socpak");
+    Ok(())
+}
 
-        // For SOCPAK files, we check if the extracted directory exists
-        let socpak_dir = if is_socpak {
-            Some(output_path.with_extension(""))
-        } else {
-            None
-        };
+/// Read a CHF file, a JSON character, or a decompressed BIN dump for any
+/// other extension.
+fn read_chf(path: &Path) -> Result<ChfFile> {
+    read_chf_with(path, &svarog::chf::ParseOptions::default())
+}
 
-        let should_extract = if let Some(ref dir) = socpak_dir {
-            // Track all SOCPAK dirs for CryXML post-processing
-            all_socpak_dirs.push(dir.clone());
-            // For SOCPAK, check if directory exists and contains files
-            if dir.exists() {
-                // Check if directory has any files (recursively)
-                // Empty dirs or dirs with only empty subdirs should be re-extracted
-                let has_files = has_any_files(dir);
-                if has_files {
-                    // Directory has actual files - skip extraction, delete .socpak if present
-                    if output_path.exists() {
-                        let _ = fs::remove_file(&output_path);
-                    }
-                    false
-                } else {
-                    // No files found - remove empty tree and re-extract
-                    let _ = fs::remove_dir_all(dir);
-                    true
-                }
-            } else {
-                true
-            }
-        } else if incremental {
-            let dominated = should_skip_file(&output_path, *size);
-            if dominated {
-                // File exists with matching size - but check if it's undecoded CryXML
-                if check_and_decode_cryxml(&output_path) {
-                    cryxml_decoded.fetch_add(1, Ordering::Relaxed);
-                }
-            }
-            !dominated
-        } else {
-            true
-        };
+/// Like [`read_chf`], with explicit options for `.chf` files.
+fn read_chf_with(path: &Path, options: &svarog::chf::ParseOptions) -> Result<ChfFile> {
+    use svarog::chf::parts::ChfData;
 
-        if !should_extract {
-            skipped.fetch_add(1, Ordering::Relaxed);
-            pb.inc(1);
-            continue;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("chf") => ChfFile::from_chf_with(path, options).context("Failed to read CHF file"),
+        Some("json") => {
+            let json = fs::read_to_string(path).context("Failed to read JSON file")?;
+            let data = ChfData::from_json(&json).context("Failed to parse JSON character")?;
+            Ok(ChfFile::new(data.to_bytes(), true))
         }
+        _ => ChfFile::from_bin(path, true).context("Failed to read BIN file"),
+    }
+}
 
-        // Update progress with current file
-        set_progress_message(&pb, Stage::P4kExtract, &name_normalized);
+/// Write a CHF file, a JSON character, or a decompressed BIN dump for any
+/// other extension.
+fn write_chf(chf: &ChfFile, path: &Path) -> Result<()> {
+    use svarog::chf::parts::ChfData;
 
-        // Create parent directories
-        if let Some(parent) = output_path.parent() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                eprintln!("Failed to create directory {}: {}", parent.display(), e);
-                errors.fetch_add(1, Ordering::Relaxed);
-                pb.inc(1);
-                continue;
-            }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("chf") => chf.write_to_chf(path).context("Failed to write CHF file"),
+        Some("json") => {
+            let data = ChfData::parse(chf.data()).context("Failed to parse character data")?;
+            fs::write(path, data.to_json()?).context("Failed to write JSON file")
         }
+        _ => chf.write_to_bin(path).context("Failed to write BIN file"),
+    }
+}
 
-        // Read entry data
-        let data = match archive.read_index(*idx) {
-            Ok(d) => d,
-            Err(e) => {
-                eprintln!("Failed to read {}: {}", name, e);
-                errors.fetch_add(1, Ordering::Relaxed);
-                pb.inc(1);
-                continue;
-            }
-        };
+fn cmd_dds_merge(input: &PathBuf, output: &PathBuf) -> Result<()> {
+    status!("Merging DDS: {} -> {}", input.display(), output.display());
 
-        if let Some(socpak_dir) = socpak_dir {
-            // Extract SOCPAK contents to a directory with the same basename
-            set_progress_message(&pb, Stage::SocpakExpand, &name_normalized);
+    let merged = merge_dds(input).context("Failed to merge DDS files")?;
+    fs::write(output, merged).context("Failed to write output file")?;
 
-            match extract_socpak(&data, &socpak_dir, Some(&pb)) {
-                Ok(result) => {
-                    socpak_expanded.fetch_add(result.files_extracted as u64, Ordering::Relaxed);
-                    cryxml_decoded.fetch_add(result.cryxml_decoded as u64, Ordering::Relaxed);
-                    extracted.fetch_add(1, Ordering::Relaxed);
-                    // Don't write the .socpak file - we've extracted it
-                }
-                Err(e) => {
-                    eprintln!("Failed to extract SOCPAK {}: {}", name, e);
-                    // Fall back to writing the raw file
-                    if let Err(e) = fs::write(&output_path, &data) {
-                        eprintln!("Failed to write {}: {}", name, e);
-                        errors.fetch_add(1, Ordering::Relaxed);
-                    } else {
-                        extracted.fetch_add(1, Ordering::Relaxed);
-                    }
-                }
-            }
-        } else {
-            // Write regular file, decoding CryXML if applicable
-            let data_to_write = if is_cryxml_data(&data) {
-                // Decode CryXML to text XML
-                set_progress_message(&pb, Stage::CryXmlDecode, &name_normalized);
-                match CryXml::parse(&data) {
-                    Ok(cryxml) => match cryxml.to_xml_string() {
-                        Ok(xml) => {
-                            cryxml_decoded.fetch_add(1, Ordering::Relaxed);
-                            xml.into_bytes()
-                        }
-                        Err(_) => data, // Fall back to raw data
-                    },
-                    Err(_) => data, // Fall back to raw data
-                }
-            } else {
-                data
-            };
+    let gloss = svarog::dds::merge_glossmap(input).context("Failed to merge glossmap")?;
+    if let Some(gloss) = gloss {
+        let gloss_output = svarog::dds::glossmap_path(output);
+        fs::write(&gloss_output, gloss).context("Failed to write glossmap")?;
+        status!("Glossmap: {}", gloss_output.display());
+    }
+
+    status!("Merge complete");
+
+    Ok(())
+}
 
-            if let Err(e) = fs::write(&output_path, data_to_write) {
-                eprintln!("Failed to write {}: {}", name, e);
-                errors.fetch_add(1, Ordering::Relaxed);
+fn cmd_dds_info(input: &Path, repair: bool) -> Result<()> {
+    use svarog::dds::{repair_header, DdsInfo};
+
+    let mut data = fs::read(input).context("Failed to read DDS file")?;
+    let info = DdsInfo::parse(&data).context("Failed to parse DDS header")?;
+
+    let (width, height) = info.dimensions();
+    println!("File: {}", input.display());
+    println!("Dimensions: {}x{}", width, height);
+    if info.is_volume() {
+        println!("Depth: {}", info.depth());
+    }
+    match info.format() {
+        Some(format) => println!("Format: {}", format),
+        None => println!("Format: unsupported"),
+    }
+    println!("Mipmaps: {}", info.mip_count());
+    if info.is_cubemap() {
+        println!("Cubemap: yes");
+    }
+    if let Some(dx10) = info.dx10() {
+        println!(
+            "DX10: DXGI format {}, dimension {}, array size {}, misc flags {:#x}",
+            { dx10.dxgi_format },
+            { dx10.resource_dimension },
+            { dx10.array_size },
+            { dx10.misc_flag }
+        );
+    }
+    match info.expected_data_size() {
+        Some(expected) => println!(
+            "Data: {} of {} bytes{}",
+            info.data_size(),
+            expected,
+            if info.is_complete() {
+                ""
             } else {
-                extracted.fetch_add(1, Ordering::Relaxed);
+                " (incomplete, merge split files first)"
             }
-        }
+        ),
+        None => println!("Data: {} bytes", info.data_size()),
+    }
 
-        pb.inc(1);
+    if repair {
+        if repair_header(&mut data).context("Failed to repair DDS header")? {
+            fs::write(input, &data).context("Failed to write DDS file")?;
+            let info = DdsInfo::parse(&data)?;
+            println!("Header repaired: {} mipmaps", info.mip_count());
+        } else {
+            println!("Header is consistent, nothing to repair");
+        }
     }
 
-    pb.finish_with_message("P4K extraction complete");
+    Ok(())
+}
 
-    let extracted_count = extracted.load(Ordering::Relaxed);
-    let skipped_count = skipped.load(Ordering::Relaxed);
-    let error_count = errors.load(Ordering::Relaxed);
+fn cmd_dds_convert(
+    input: &PathBuf,
+    output: &PathBuf,
+    to: ImageFormat,
+    zstd: Option<i32>,
+) -> Result<()> {
+    use svarog::dds::{dds_to_ktx2, decode_to_rgba, Ktx2Options, Supercompression};
 
-    println!(
-        "\nExtracted {} files, skipped {} (unchanged), {} errors in {:?}",
-        extracted_count,
-        skipped_count,
-        error_count,
-        start.elapsed()
+    status!(
+        "Converting DDS: {} -> {}",
+        input.display(),
+        output.display()
     );
 
-    // Debug: warn if incremental mode isn't working as expected
-    if incremental && skipped_count == 0 && extracted_count > 0 {
-        eprintln!("Warning: incremental mode enabled but no files were skipped - this may indicate a path mismatch");
-    }
-
-    let socpak_count = socpak_expanded.load(Ordering::Relaxed);
-    let cryxml_count = cryxml_decoded.load(Ordering::Relaxed);
+    let merged = merge_dds(input).context("Failed to merge DDS files")?;
 
-    if socpak_count > 0 || cryxml_count > 0 {
-        let mut parts = Vec::new();
-        if socpak_count > 0 {
-            parts.push(format!("{} files from SOCPAK archives", socpak_count));
-        }
-        if cryxml_count > 0 {
-            parts.push(format!("{} CryXML decoded", cryxml_count));
+    let format = match to {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Bmp => image::ImageFormat::Bmp,
+        ImageFormat::Ktx2 => {
+            let mut options = Ktx2Options::new();
+            if let Some(level) = zstd {
+                options = options.supercompression(Supercompression::Zstd(level));
+            }
+            let ktx = dds_to_ktx2(&merged, &options).context("Failed to convert to KTX2")?;
+            fs::write(output, &ktx).context("Failed to write output file")?;
+            status!("Converted to KTX2 ({} bytes)", ktx.len());
+            return Ok(());
         }
-        println!("{}", parts.join(", "));
-    }
+    };
+    let image = decode_to_rgba(&merged).context("Failed to decode DDS")?;
+    image
+        .save_with_format(output, format)
+        .context("Failed to write output image")?;
 
-    // Process ALL SOCPAK directories for any undecoded CryXML files
-    if !all_socpak_dirs.is_empty() {
-        println!(
-            "\nVerifying CryXML decoding in {} SOCPAK directories...",
-            all_socpak_dirs.len()
-        );
+    status!("Converted {}x{} texture", image.width(), image.height());
 
-        let cryxml_pb = create_progress_bar(all_socpak_dirs.len() as u64, Stage::CryXmlDecode);
-        let mut total_decoded = 0u64;
+    Ok(())
+}
 
-        for dir in &all_socpak_dirs {
-            let dir_name = dir.file_name().unwrap_or_default().to_string_lossy();
-            set_progress_message(&cryxml_pb, Stage::CryXmlDecode, &dir_name);
+fn cmd_dds_encode(
+    input: &Path,
+    output: &Path,
+    format: EncodeFormat,
+    filter: MipFilterArg,
+    no_mips: bool,
+    split: Option<u8>,
+) -> Result<()> {
+    use svarog::dds::{encode_dds, split_dds, DdsFormat, DdsInfo, EncodeOptions, MipFilter};
 
-            match decode_cryxml_in_directory(dir, Some(&cryxml_pb)) {
-                Ok(count) => {
-                    total_decoded += count as u64;
-                }
-                Err(e) => {
-                    eprintln!("Error processing {}: {}", dir.display(), e);
-                }
-            }
+    status!("Encoding DDS: {} -> {}", input.display(), output.display());
 
-            cryxml_pb.inc(1);
-        }
+    let image = image::open(input)
+        .context("Failed to read input image")?
+        .to_rgba8();
 
-        cryxml_pb.finish_with_message("CryXML verification complete");
+    let format = match format {
+        EncodeFormat::Bc1 => DdsFormat::Bc1,
+        EncodeFormat::Bc3 => DdsFormat::Bc3,
+        EncodeFormat::Bc4 => DdsFormat::Bc4 { signed: false },
+        EncodeFormat::Bc5 => DdsFormat::Bc5 { signed: false },
+        EncodeFormat::Bc7 => DdsFormat::Bc7,
+        EncodeFormat::Rgba8 => DdsFormat::RGBA8,
+    };
+    let filter = match filter {
+        MipFilterArg::Box => MipFilter::Box,
+        MipFilterArg::Triangle => MipFilter::Triangle,
+        MipFilterArg::CatmullRom => MipFilter::CatmullRom,
+        MipFilterArg::Lanczos3 => MipFilter::Lanczos3,
+    };
+    let options = EncodeOptions::new(format).filter(filter).mipmaps(!no_mips);
+    let dds = encode_dds(&image, &options).context("Failed to encode DDS")?;
+    let info = DdsInfo::parse(&dds)?;
 
-        if total_decoded > 0 {
-            println!("Decoded {} additional CryXML files", total_decoded);
-        }
+    let (base, parts) = match split {
+        Some(count) => split_dds(&dds, count).context("Failed to split DDS")?,
+        None => (dds, Vec::new()),
+    };
+    fs::write(output, base).context("Failed to write output file")?;
+    for (index, part) in &parts {
+        let mut path = output.as_os_str().to_owned();
+        path.push(format!(".{}", index));
+        fs::write(&path, part).context("Failed to write split file")?;
     }
 
-    // Extract and process all DCB files
-    for (dcb_idx, dcb_name) in &dcb_entries {
-        println!("\nProcessing DataCore: {}", dcb_name);
+    status!(
+        "Encoded {}x{} {} texture with {} mipmaps",
+        image.width(),
+        image.height(),
+        format,
+        info.mip_count()
+    );
+    if !parts.is_empty() {
+        status!("Split into {} part files", parts.len());
+    }
 
-        let dcb_start = Instant::now();
-        let dcb_data = match archive.read_index(*dcb_idx) {
-            Ok(data) => data,
-            Err(e) => {
-                eprintln!("Failed to read {}: {}", dcb_name, e);
-                continue;
-            }
-        };
+    Ok(())
+}
 
-        let database = match DataCoreDatabase::parse(&dcb_data) {
-            Ok(db) => db,
-            Err(e) => {
-                eprintln!("Failed to parse {}: {}", dcb_name, e);
-                continue;
-            }
-        };
+fn cmd_dcb_schema(
+    input: &PathBuf,
+    output: &PathBuf,
+    format: SchemaFormat,
+    flatten: bool,
+    split: bool,
+) -> Result<()> {
+    use svarog::datacore::{
+        CHeaderExporter, CHeaderInheritance, JsonSchemaExporter, RustCodegen, TypeScriptExporter,
+    };
 
-        println!(
-            "Loaded DataCore in {:?}: {} structs, {} enums, {} records",
-            dcb_start.elapsed(),
-            database.struct_definitions().len(),
-            database.enum_definitions().len(),
-            database.records().len()
-        );
+    status!("Loading DataCore: {}", input.display());
 
-        // Export to XML (with incremental support)
-        let main_records: Vec<_> = database.main_records().collect();
+    let start = Instant::now();
+    let data = fs::read(input).context("Failed to read input file")?;
+    let db = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
 
-        // In incremental mode, filter out records that already have XML files
-        let records_to_export: Vec<_> = if incremental {
-            main_records
-                .iter()
-                .filter(|record| {
-                    let file_name = database.record_file_name(record).unwrap_or("unknown.xml");
-                    let output_path = path_mapper.resolve(output, file_name);
-                    let output_path = output_path.with_extension("xml");
-                    !output_path.exists()
-                })
-                .collect()
-        } else {
-            main_records.iter().collect()
-        };
+    status!(
+        "Loaded in {:?}: {} structs, {} enums",
+        start.elapsed(),
+        db.struct_definitions().len(),
+        db.enum_definitions().len()
+    );
 
-        let skipped_dcb = main_records.len() - records_to_export.len();
-        if skipped_dcb > 0 {
-            println!(
-                "Exporting {} DataCore records ({} already exist, skipped)...",
-                records_to_export.len(),
-                skipped_dcb
-            );
-        } else {
-            println!("Exporting {} DataCore records...", records_to_export.len());
+    let schema = match format {
+        SchemaFormat::C => {
+            status!("Generating C header schema...");
+            let inheritance = if flatten {
+                CHeaderInheritance::Flatten
+            } else {
+                CHeaderInheritance::Nested
+            };
+            let exporter = CHeaderExporter::new(&db).inheritance(inheritance);
+            if split {
+                let count = exporter
+                    .export_split(output)
+                    .context("Failed to write C headers")?;
+                status!("Exported {} headers to {}", count, output.display());
+                return Ok(());
+            }
+            exporter.export_all()
+        }
+        SchemaFormat::Rust => {
+            status!("Generating Rust schema...");
+            RustCodegen::new(&db).export_all()
+        }
+        SchemaFormat::Typescript => {
+            status!("Generating TypeScript definitions...");
+            TypeScriptExporter::new(&db).export_all()
+        }
+        SchemaFormat::JsonSchema => {
+            status!("Generating JSON Schema documents...");
+            let count = JsonSchemaExporter::new(&db)
+                .export_all(output)
+                .context("Failed to write JSON Schema documents")?;
+            status!("Exported {} schemas to {}", count, output.display());
+            return Ok(());
         }
+    };
 
-        if records_to_export.is_empty() {
-            println!("All DataCore records already exported, nothing to do");
-        } else {
-            let dcb_pb = create_progress_bar(records_to_export.len() as u64, Stage::DcbExport);
+    fs::write(output, &schema).context("Failed to write output file")?;
 
-            let exporter = svarog::XmlExporter::new(&database);
-            let mut dcb_exported = 0;
-            let mut dcb_errors = 0;
+    status!(
+        "Exported {} structs and {} enums to {}",
+        db.struct_definitions().len(),
+        db.enum_definitions().len(),
+        output.display()
+    );
 
-            for record in &records_to_export {
-                let file_name = database
-                    .record_file_name(record)
-                    .unwrap_or("unknown.xml");
+    Ok(())
+}
 
-                // Update progress with current file
-                set_progress_message(&dcb_pb, Stage::DcbExport, file_name);
+fn cmd_dcb_bundle(input: &Path, record: &str, output: &Path) -> Result<()> {
+    status!("Loading DataCore: {}", input.display());
 
-                // Use path mapper to merge with existing case
-                let output_path = path_mapper.resolve(output, file_name);
-                let output_path = output_path.with_extension("xml");
+    let start = Instant::now();
+    let data = fs::read(input).context("Failed to read input file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
 
-                // Create parent directories
-                if let Some(parent) = output_path.parent() {
-                    let _ = fs::create_dir_all(parent);
-                }
+    status!(
+        "Loaded in {:?}: {} records",
+        start.elapsed(),
+        database.records().len()
+    );
 
-                // Export record
-                match exporter.export_record(record) {
-                    Ok(xml) => {
-                        if let Err(e) = fs::write(&output_path, xml) {
-                            eprintln!("Failed to write {}: {}", file_name, e);
-                            dcb_errors += 1;
-                        } else {
-                            dcb_exported += 1;
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("Error exporting {}: {}", file_name, e);
-                        dcb_errors += 1;
-                    }
-                }
+    let root = match record.parse::<CigGuid>() {
+        Ok(guid) => database.get_record(&guid),
+        Err(_) => database
+            .records()
+            .iter()
+            .find(|r| database.record_name(r) == Some(record)),
+    }
+    .with_context(|| format!("Record not found: {}", record))?;
 
-                dcb_pb.inc(1);
-            }
+    let exporter = svarog::XmlExporter::new(&database);
+    let dependencies = exporter
+        .export_with_dependencies(root, output)
+        .context("Failed to export bundle")?;
+
+    status!(
+        "Exported {} with {} dependencies to {}",
+        record,
+        dependencies.records.len() - 1,
+        output.display()
+    );
 
-            dcb_pb.finish_with_message("DCB export complete");
-            println!(
-                "Exported {} DataCore records ({} errors) in {:?}",
-                dcb_exported,
-                dcb_errors,
-                dcb_start.elapsed()
-            );
+    if !dependencies.weak.is_empty() {
+        status!("Weak references (not exported):");
+        for id in &dependencies.weak {
+            let name = database
+                .get_record(id)
+                .and_then(|r| database.record_name(r))
+                .unwrap_or("?");
+            status!("  {} {}", id, name);
         }
     }
 
     Ok(())
 }
 
-fn cmd_p4k_list(p4k_path: &PathBuf, filter: Option<&str>, detailed: bool) -> Result<()> {
-    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+fn cmd_dcb_table(
+    input: &Path,
+    type_name: &str,
+    output: &Path,
+    format: TableFormat,
+    locale: Option<&Path>,
+) -> Result<()> {
+    use svarog::datacore::{LocaleResolver, TableExporter};
 
-    let mut count = 0;
-    for entry in archive.iter() {
-        if let Some(pattern) = filter {
-            if !glob_match(pattern, entry.name) {
-                continue;
-            }
-        }
+    let data = fs::read(input).context("Failed to read input file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
 
-        if detailed {
-            println!(
-                "{:>12} {:>12} {} {}",
-                entry.compressed_size,
-                entry.uncompressed_size,
-                if entry.is_encrypted { "E" } else { " " },
-                entry.name
-            );
-        } else {
-            println!("{}", entry.name);
-        }
-        count += 1;
+    let locale = locale
+        .map(LocaleResolver::open)
+        .transpose()
+        .context("Failed to load localization file")?;
+    let mut exporter = TableExporter::new(&database);
+    if let Some(locale) = &locale {
+        exporter = exporter.with_locale(locale);
+    }
+
+    let table = exporter
+        .table_by_name(type_name)
+        .with_context(|| format!("Struct type not found: {}", type_name))?;
+
+    let mut file = fs::File::create(output).context("Failed to create output file")?;
+    match format {
+        TableFormat::Csv => table.write_csv(&mut file).context("Failed to write CSV")?,
+        #[cfg(feature = "parquet")]
+        TableFormat::Parquet => table
+            .write_parquet(file)
+            .context("Failed to write Parquet")?,
+        #[cfg(not(feature = "parquet"))]
+        TableFormat::Parquet => anyhow::bail!("svarog was built without the parquet feature"),
     }
 
-    println!("\nTotal: {} entries", count);
+    status!(
+        "Wrote {} rows x {} columns to {}",
+        table.rows.len(),
+        table.columns.len(),
+        output.display()
+    );
 
     Ok(())
 }
 
-fn cmd_cryxml_convert(input: &PathBuf, output: &PathBuf) -> Result<()> {
-    println!("Converting CryXmlB to XML: {} -> {}", input.display(), output.display());
+fn cmd_dcb_import(input: &Path, xml: &Path, output: &Path) -> Result<()> {
+    use svarog::datacore::{DataCoreBuilder, XmlImporter};
+
+    status!("Loading DataCore: {}", input.display());
 
     let data = fs::read(input).context("Failed to read input file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+    let mut builder =
+        DataCoreBuilder::from_database(&database).context("Failed to load DataCore")?;
 
-    if !CryXml::is_cryxml(&data) {
-        anyhow::bail!("Input file is not a CryXmlB file");
+    let start = Instant::now();
+    let count = XmlImporter::new(&mut builder)
+        .import_dir(xml)
+        .context("Failed to import XML")?;
+    status!("Imported {} files in {:?}", count, start.elapsed());
+
+    let stats = builder.compact();
+    if stats.instances_removed > 0 {
+        status!("Dropped {} replaced instances", stats.instances_removed);
+    }
+    for issue in builder.validate() {
+        warning!("warning: {}", issue);
     }
 
-    let cryxml = CryXml::parse(&data).context("Failed to parse CryXmlB")?;
-    let xml = cryxml.to_xml_string().context("Failed to convert to XML")?;
-    fs::write(output, xml).context("Failed to write output file")?;
-
-    println!("Conversion complete");
+    builder
+        .write_to_file(output)
+        .context("Failed to write output file")?;
+    status!("Wrote {}", output.display());
 
     Ok(())
 }
 
-fn cmd_cryxml_create(input: &PathBuf, output: &PathBuf) -> Result<()> {
-    use svarog::cryxml::builder::CryXmlBuilder;
+fn cmd_dcb_merge(
+    input: &Path,
+    layers: &[PathBuf],
+    output: &Path,
+    conflicts: ConflictStrategy,
+) -> Result<()> {
+    use svarog::datacore::{DataCoreBuilder, MergeStrategy};
 
-    println!("Converting XML to CryXmlB: {} -> {}", input.display(), output.display());
+    status!("Loading DataCore: {}", input.display());
 
-    let xml = fs::read_to_string(input).context("Failed to read input file")?;
+    let data = fs::read(input).context("Failed to read input file")?;
+    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+    let mut builder =
+        DataCoreBuilder::from_database(&database).context("Failed to load DataCore")?;
+
+    let strategy = match conflicts {
+        ConflictStrategy::Error => MergeStrategy::Error,
+        ConflictStrategy::Keep => MergeStrategy::KeepExisting,
+        ConflictStrategy::Replace => MergeStrategy::Replace,
+        ConflictStrategy::Rename => MergeStrategy::Rename,
+    };
 
-    let builder = CryXmlBuilder::from_xml(&xml).context("Failed to parse XML")?;
-    let cryxml_bytes = builder.build().context("Failed to build CryXmlB")?;
-    fs::write(output, cryxml_bytes).context("Failed to write output file")?;
+    for layer in layers {
+        let data =
+            fs::read(layer).with_context(|| format!("Failed to read {}", layer.display()))?;
+        let other = DataCoreDatabase::parse(&data)
+            .with_context(|| format!("Failed to parse {}", layer.display()))?;
+        let stats = builder
+            .merge(&other, strategy)
+            .with_context(|| format!("Failed to merge {}", layer.display()))?;
+        status!(
+            "Merged {}: {} added, {} replaced, {} renamed, {} skipped, {} new structs",
+            layer.display(),
+            stats.records_added,
+            stats.records_replaced,
+            stats.records_renamed,
+            stats.records_skipped,
+            stats.structs_added
+        );
+    }
+
+    for issue in builder.validate() {
+        warning!("warning: {}", issue);
+    }
 
-    println!("Conversion complete ({} bytes)", fs::metadata(output)?.len());
+    builder
+        .write_to_file(output)
+        .context("Failed to write output file")?;
+    status!("Wrote {}", output.display());
 
     Ok(())
 }
 
-fn cmd_dcb_extract(input: &PathBuf, output: &PathBuf, filter: Option<&str>) -> Result<()> {
-    println!("Loading DataCore: {}", input.display());
+/// Keep the records of a DataCore diff whose type is one of `types` and whose
+/// file path starts with one of `paths`; an empty list matches anything.
+fn retain_diff_records(
+    diff: &mut svarog::datacore::DataCoreDiff,
+    types: &[String],
+    paths: &[String],
+) {
+    if types.is_empty() && paths.is_empty() {
+        return;
+    }
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| p.replace('\\', "/").to_lowercase())
+        .collect();
+    diff.retain_records(|record| {
+        let type_matches = types.is_empty()
+            || types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&record.type_name));
+        let path_matches = paths.is_empty() || {
+            let file_name = record.file_name.replace('\\', "/").to_lowercase();
+            paths.iter().any(|p| file_name.starts_with(p.as_str()))
+        };
+        type_matches && path_matches
+    });
+}
+
+fn cmd_dcb_diff(
+    old: &Path,
+    new: &Path,
+    format: ReportFormat,
+    output: Option<&Path>,
+    guid_only: bool,
+    types: &[String],
+    paths: &[String],
+) -> Result<()> {
+    use svarog::datacore::{DataCoreDiff, MatchOptions, RecordMatching};
 
     let start = Instant::now();
-    let data = fs::read(input).context("Failed to read input file")?;
-    let database = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+    let old_data = fs::read(old).context("Failed to read old DCB file")?;
+    let old_db = DataCoreDatabase::parse(&old_data).context("Failed to parse old DataCore")?;
+    let new_data = fs::read(new).context("Failed to read new DCB file")?;
+    let new_db = DataCoreDatabase::parse(&new_data).context("Failed to parse new DataCore")?;
 
-    println!(
-        "Loaded in {:?}: {} structs, {} enums, {} records",
+    estatus!(
+        "Loaded in {:?}: {} -> {} records",
         start.elapsed(),
-        database.struct_definitions().len(),
-        database.enum_definitions().len(),
-        database.records().len()
+        old_db.records().len(),
+        new_db.records().len()
     );
 
-    // Count main records
-    let main_records: Vec<_> = database.main_records().collect();
-    let filtered_records: Vec<_> = if let Some(pattern) = filter {
-        main_records
-            .into_iter()
-            .filter(|r| {
-                database
-                    .record_file_name(r)
-                    .map(|name| glob_match(pattern, name))
-                    .unwrap_or(false)
-            })
-            .collect()
-    } else {
-        main_records
+    let options = MatchOptions {
+        by_name: !guid_only,
+        by_structure: !guid_only,
+        ..Default::default()
     };
+    let matching = RecordMatching::with_options(&old_db, &new_db, &options);
+    let mut diff = DataCoreDiff::compare_matched(&old_db, &new_db, &matching);
 
-    println!("Exporting {} records to {}...", filtered_records.len(), output.display());
+    retain_diff_records(&mut diff, types, paths);
 
-    fs::create_dir_all(output)?;
+    let reidentified = matching.reidentified().count();
+    if reidentified > 0 {
+        estatus!("{} records matched across a GUID change", reidentified);
+    }
 
-    let exporter = svarog::XmlExporter::new(&database);
-    let pb = ProgressBar::new(filtered_records.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")?
-            .progress_chars("#>-"),
+    estatus!(
+        "{} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+    estatus!(
+        "Schema: {} structs changed, {} enums changed",
+        diff.schema.added_structs.len()
+            + diff.schema.removed_structs.len()
+            + diff.schema.changed_structs.len(),
+        diff.schema.added_enums.len()
+            + diff.schema.removed_enums.len()
+            + diff.schema.changed_enums.len()
     );
 
-    let start = Instant::now();
-    let mut exported = 0;
-    let mut errors = 0;
-
-    for record in &filtered_records {
-        let file_name = database
-            .record_file_name(record)
-            .unwrap_or("unknown.xml");
-
-        // Convert path separators and add .xml extension
-        let output_path = output.join(file_name.replace('/', std::path::MAIN_SEPARATOR_STR));
-        let output_path = output_path.with_extension("xml");
+    let report = match format {
+        ReportFormat::Text => diff.to_string(),
+        ReportFormat::Json => diff.to_json().context("Failed to serialize diff")? + "\n",
+        ReportFormat::Markdown => diff.to_markdown(),
+    };
 
-        // Create parent directories
-        if let Some(parent) = output_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
+    match output {
+        Some(path) => fs::write(path, report).context("Failed to write output file")?,
+        None => print!("{}", report),
+    }
 
-        // Export record
-        match exporter.export_record(record) {
-            Ok(xml) => {
-                fs::write(&output_path, xml)?;
-                exported += 1;
-            }
-            Err(e) => {
-                eprintln!("Error exporting {}: {}", file_name, e);
-                errors += 1;
-            }
-        }
+    Ok(())
+}
 
-        pb.inc(1);
+fn cmd_dcb_stats(input: &Path, format: DiffFormat) -> Result<()> {
+    let start = Instant::now();
+    let data = fs::read(input).context("Failed to read DCB file")?;
+    let db = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+    let stats = db.statistics();
+    estatus!("Analyzed in {:?}", start.elapsed());
+
+    match format {
+        DiffFormat::Text if !output::json() => print!("{}", stats),
+        _ => println!(
+            "{}",
+            stats.to_json().context("Failed to serialize statistics")?
+        ),
     }
 
-    pb.finish_with_message("Done");
-    println!(
-        "Exported {} records in {:?} ({} errors)",
-        exported,
-        start.elapsed(),
-        errors
-    );
-
     Ok(())
 }
 
-fn cmd_chf_process(input: &PathBuf, output: &PathBuf) -> Result<()> {
-    use svarog::chf::parts::ChfData;
+fn cmd_search(
+    p4k_path: &Path,
+    query: &str,
+    properties: bool,
+    case_sensitive: bool,
+    no_datacore: bool,
+) -> Result<()> {
+    use svarog::datacore::{SearchField, SearchOptions};
 
-    println!("Processing CHF: {} -> {}", input.display(), output.display());
+    let start = Instant::now();
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
 
-    let chf = if input.extension().and_then(|e| e.to_str()) == Some("chf") {
-        ChfFile::from_chf(input).context("Failed to read CHF file")?
+    let needle = if case_sensitive {
+        query.to_string()
     } else {
-        ChfFile::from_bin(input, true).context("Failed to read BIN file")?
+        query.to_lowercase()
     };
-
-    println!("Loaded CHF: {} bytes, modded: {}", chf.data().len(), chf.is_modded());
-
-    // Parse and display character data
-    if let Ok(data) = ChfData::parse(chf.data()) {
-        println!("Gender ID: {}", data.gender_id());
-
-        // Show DNA summary
-        let mut active_blends = 0;
-        for (face_part, blends) in data.dna().iter_face_parts() {
-            let blend_count = blends.iter().filter(|b| !b.is_zero()).count();
-            if blend_count > 0 {
-                active_blends += blend_count;
-                println!(
-                    "  {}: {} active blends",
-                    face_part, blend_count
-                );
+    let mut entry_hits = 0;
+    let mut dcb_entries = Vec::new();
+    for entry in archive.iter() {
+        let matches = if case_sensitive {
+            entry.name.contains(&needle)
+        } else {
+            entry.name.to_lowercase().contains(&needle)
+        };
+        if matches {
+            if output::json() {
+                output::emit(serde_json::json!({ "entry": entry.name }));
+            } else {
+                println!("p4k     {}", entry.name);
             }
+            entry_hits += 1;
         }
-        println!("DNA: {} total active blends", active_blends);
-
-        // Show item port tree if present
-        if let Some(port) = data.item_port() {
-            println!("Item ports: {} total, depth {}", port.count(), port.depth());
-        }
-
-        // Show materials
-        if !data.materials().is_empty() {
-            println!("Materials: {}", data.materials().len());
+        if entry.name.to_lowercase().ends_with(".dcb") {
+            dcb_entries.push(entry);
         }
     }
 
-    if output.extension().and_then(|e| e.to_str()) == Some("chf") {
-        chf.write_to_chf(output).context("Failed to write CHF file")?;
-    } else {
-        chf.write_to_bin(output).context("Failed to write BIN file")?;
+    let mut record_hits = 0;
+    if !no_datacore {
+        let options = SearchOptions {
+            properties,
+            case_sensitive,
+        };
+        for entry in dcb_entries {
+            let data = archive
+                .read(&entry)
+                .with_context(|| format!("Failed to read {}", entry.name))?;
+            let db = DataCoreDatabase::parse(&data)
+                .with_context(|| format!("Failed to parse {}", entry.name))?;
+
+            for hit in db.search(query, &options) {
+                record_hits += 1;
+                if output::json() {
+                    output::emit(serde_json::json!({ "database": entry.name, "record": hit }));
+                    continue;
+                }
+                let location = match &hit.field {
+                    SearchField::Name => "name".to_string(),
+                    SearchField::Type => "type".to_string(),
+                    SearchField::File => "file".to_string(),
+                    SearchField::Property(path) => format!("{} = {}", path, hit.value),
+                };
+                println!(
+                    "record  {} in {}: {}",
+                    hit.record, hit.record.file_name, location
+                );
+            }
+        }
     }
 
-    println!("Output written");
+    estatus!(
+        "{} entries and {} record hits in {:?}",
+        entry_hits,
+        record_hits,
+        start.elapsed()
+    );
 
     Ok(())
 }
 
-fn cmd_dds_merge(input: &PathBuf, output: &PathBuf) -> Result<()> {
-    println!("Merging DDS: {} -> {}", input.display(), output.display());
-
-    let merged = merge_dds(input).context("Failed to merge DDS files")?;
-    fs::write(output, merged).context("Failed to write output file")?;
-
-    println!("Merge complete");
+fn cmd_locale(
+    p4k_path: Option<&Path>,
+    ini: Option<&Path>,
+    lang: &str,
+    command: LocaleCommand,
+) -> Result<()> {
+    let locale = load_locale(p4k_path, ini, lang)?;
+
+    match command {
+        LocaleCommand::Export { format, output } => {
+            let data = match format {
+                LocaleFormat::Csv => {
+                    let mut data = Vec::new();
+                    locale.write_csv(&mut data)?;
+                    data
+                }
+                LocaleFormat::Json => locale
+                    .to_json()
+                    .context("Failed to serialize JSON")?
+                    .into_bytes(),
+            };
+            match output {
+                Some(path) => {
+                    fs::write(&path, &data).context("Failed to write output file")?;
+                    status!("Exported {} keys to {}", locale.len(), path.display());
+                }
+                None => write_stdout(&data)?,
+            }
+        }
+        LocaleCommand::Lookup { keys } => {
+            let mut missing = 0;
+            for key in &keys {
+                let text = locale.get(key);
+                if output::json() {
+                    output::emit(serde_json::json!({ "key": key, "text": text }));
+                } else if let Some(text) = text {
+                    println!("{}", text);
+                }
+                if text.is_none() {
+                    missing += 1;
+                    warning!("Key not found: {}", key);
+                }
+            }
+            if missing > 0 {
+                anyhow::bail!("{} of {} keys not found", missing, keys.len());
+            }
+        }
+        LocaleCommand::Grep { text } => {
+            let mut hits = 0;
+            for (key, value) in locale.grep(&text) {
+                hits += 1;
+                if output::json() {
+                    output::emit(serde_json::json!({ "key": key, "text": value }));
+                } else {
+                    println!("{}={}", key, value);
+                }
+            }
+            estatus!("{} of {} keys match", hits, locale.len());
+        }
+    }
 
     Ok(())
 }
 
-fn cmd_dcb_schema(input: &PathBuf, output: &PathBuf) -> Result<()> {
-    use svarog::datacore::CHeaderExporter;
-
-    println!("Loading DataCore: {}", input.display());
-
-    let start = Instant::now();
-    let data = fs::read(input).context("Failed to read input file")?;
-    let db = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
-
-    println!(
-        "Loaded in {:?}: {} structs, {} enums",
-        start.elapsed(),
-        db.struct_definitions().len(),
-        db.enum_definitions().len()
-    );
-
-    println!("Generating C header schema...");
-
-    let exporter = CHeaderExporter::new(&db);
-    let header = exporter.export_all();
-
-    fs::write(output, &header).context("Failed to write output file")?;
-
-    println!(
-        "Exported {} structs and {} enums to {}",
-        db.struct_definitions().len(),
-        db.enum_definitions().len(),
-        output.display()
-    );
-
-    Ok(())
+/// Read `global.ini` of `lang` from the archive, or the extracted `ini`.
+fn load_locale(
+    p4k_path: Option<&Path>,
+    ini: Option<&Path>,
+    lang: &str,
+) -> Result<svarog::datacore::LocaleResolver> {
+    use svarog::datacore::LocaleResolver;
+
+    match (ini, p4k_path) {
+        (Some(ini), _) => {
+            LocaleResolver::open(ini).with_context(|| format!("Failed to read {}", ini.display()))
+        }
+        (None, Some(p4k_path)) => {
+            let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+            let name = format!("Data\\Localization\\{}\\global.ini", lang);
+            let entry = archive
+                .find(&name)
+                .with_context(|| format!("No localization for language '{}': {}", lang, name))?;
+            let data = archive
+                .read(&entry)
+                .with_context(|| format!("Failed to read {}", entry.name))?;
+            Ok(LocaleResolver::from_bytes(&data))
+        }
+        (None, None) => anyhow::bail!("Pass --p4k or --ini"),
+    }
 }
 
 /// Simple glob matching for filtering.
@@ -1204,3 +3804,161 @@ fn glob_match(pattern: &str, name: &str) -> bool {
         name_lower.contains(&pattern_lower)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write an archive of `files`, stored uncompressed, to the temporary
+    /// directory.
+    pub(crate) fn write_p4k(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("svarog-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    /// Writer failing every write with `kind`.
+    struct FailingWriter(std::io::ErrorKind);
+
+    impl std::io::Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(self.0.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cat_contents() {
+        let cryxml = svarog::cryxml::builder::CryXmlBuilder::from_xml(r#"<Ammo Speed="900"/>"#)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let xml = String::from_utf8(cat_contents(cryxml.clone(), true).unwrap()).unwrap();
+        assert!(xml.contains(r#"<Ammo Speed="900""#));
+        assert_eq!(cat_contents(cryxml.clone(), false).unwrap(), cryxml);
+        assert_eq!(cat_contents(b"plain".to_vec(), true).unwrap(), b"plain");
+
+        // Right magic, broken body
+        let truncated = cryxml[..cryxml.len() / 2].to_vec();
+        let err = cat_contents(truncated, true).unwrap_err();
+        assert_eq!(err.to_string(), "Failed to parse CryXmlB");
+    }
+
+    #[test]
+    fn test_write_piped() {
+        let mut out = Vec::new();
+        write_piped(&mut out, b"data").unwrap();
+        assert_eq!(out, b"data");
+
+        write_piped(FailingWriter(std::io::ErrorKind::BrokenPipe), b"data").unwrap();
+        let err = write_piped(FailingWriter(std::io::ErrorKind::PermissionDenied), b"data");
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_load_locale() {
+        let p4k = write_p4k(
+            "locale.p4k",
+            &[(
+                "Data/Localization/german/global.ini",
+                b"item_NameGladius=Gladius\r\n",
+            )],
+        );
+        let locale = load_locale(Some(&p4k), None, "German").unwrap();
+        assert_eq!(locale.get("@item_NameGladius"), Some("Gladius"));
+
+        let err = load_locale(Some(&p4k), None, "english").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No localization for language 'english': Data\\Localization\\english\\global.ini"
+        );
+        let missing = p4k.with_file_name("missing.ini");
+        assert!(load_locale(Some(&p4k), Some(&missing), "german").is_err());
+        assert!(load_locale(None, None, "english").is_err());
+    }
+
+    #[test]
+    fn test_manifest() {
+        let files: &[(&str, &[u8])] = &[
+            ("Data/Scripts/main.lua", b"hello"),
+            ("Data/Libs/profile.xml", b"<Profile/>"),
+        ];
+        let archive = P4kArchive::open(write_p4k("manifest.p4k", files)).unwrap();
+
+        let indices = manifest_indices(&archive, Some("*.lua"));
+        assert_eq!(indices.len(), 1);
+        let entries = manifest_entries(&archive, &indices, &ProgressBar::hidden()).unwrap();
+        assert_eq!(entries[0].name, "Data/Scripts/main.lua");
+        assert_eq!(entries[0].size, 5);
+        assert_eq!(entries[0].crc32, "3610a686");
+        assert_eq!(
+            manifest_listing(&entries),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  \
+             Data/Scripts/main.lua\n"
+        );
+        assert_eq!(manifest_indices(&archive, None).len(), 2);
+    }
+
+    #[test]
+    fn test_manifest_read_error() {
+        let path = write_p4k("manifest-broken.p4k", &[("Data/a.txt", b"a")]);
+        // Break the signature of the only local header
+        let mut data = fs::read(&path).unwrap();
+        data[..4].fill(0);
+        fs::write(&path, data).unwrap();
+
+        let archive = P4kArchive::open(&path).unwrap();
+        let indices = manifest_indices(&archive, None);
+        let err = manifest_entries(&archive, &indices, &ProgressBar::hidden()).unwrap_err();
+        assert_eq!(err.to_string(), "Failed to read Data\\a.txt");
+    }
+
+    #[test]
+    fn test_needs_extract() {
+        let dir = std::env::temp_dir().join(format!("svarog-plan-{}", std::process::id()));
+        let socpak = dir.join("ship");
+        fs::create_dir_all(socpak.join("empty")).unwrap();
+        let file = dir.join("main.lua");
+        fs::write(&file, b"print(1)").unwrap();
+
+        assert!(!needs_extract(&file, None, 8, true));
+        assert!(needs_extract(&file, None, 8, false));
+        assert!(needs_extract(&file, None, 9, true));
+        assert!(needs_extract(&dir.join("missing.lua"), None, 8, true));
+
+        // Directories holding only empty directories are expanded again
+        let socpak_file = dir.join("ship.socpak");
+        assert!(needs_extract(&socpak_file, Some(&socpak), 8, true));
+        fs::write(socpak.join("empty").join("part.xml"), b"<Part/>").unwrap();
+        assert!(!needs_extract(&socpak_file, Some(&socpak), 8, false));
+    }
+
+    #[test]
+    fn test_plan_formatting() {
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 << 40), "5.0 TiB");
+        assert_eq!(format_size(5 << 50), "5120.0 TiB");
+
+        let secs = std::time::Duration::from_secs;
+        assert_eq!(format_duration(secs(7)), "7s");
+        assert_eq!(format_duration(secs(65)), "1m 05s");
+        assert_eq!(format_duration(secs(3723)), "1h 02m 03s");
+    }
+}
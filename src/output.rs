@@ -0,0 +1,256 @@
+//! Output mode selected with the global `--message-format` and `--quiet` flags.
+//!
+//! By default commands print status lines on stdout and draw progress bars on
+//! stderr. `--quiet` drops both, leaving warnings, errors and the output a
+//! command exists to produce. With `--message-format json`, results go to
+//! stdout and progress, warnings and errors to stderr as one JSON object per
+//! line, for driving svarog from other tools and CI pipelines.
+
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle, TermLike};
+use serde_json::{json, Value};
+
+/// Print a status line on stdout, unless `--quiet` or JSON output was
+/// requested.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::output::human() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Like [`status!`], on stderr, for commands whose stdout is their result.
+macro_rules! estatus {
+    ($($arg:tt)*) => {
+        if $crate::output::human() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Report a non-fatal problem on stderr, as a `warning` event in JSON mode.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::output::warning(&format!($($arg)*))
+    };
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    /// Status lines and progress bars
+    #[default]
+    Human,
+    /// One JSON object per line on stdout and stderr
+    Json,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Mode {
+    format: MessageFormat,
+    quiet: bool,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// Select the output mode, before any command runs.
+pub fn init(format: MessageFormat, quiet: bool) {
+    let _ = MODE.set(Mode { format, quiet });
+}
+
+fn mode() -> Mode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+/// Whether results are reported as JSON.
+pub fn json() -> bool {
+    mode().format == MessageFormat::Json
+}
+
+/// Whether status lines are shown.
+pub fn human() -> bool {
+    let mode = mode();
+    mode.format == MessageFormat::Human && !mode.quiet
+}
+
+/// Print a result on stdout as one JSON line.
+pub fn emit(value: Value) {
+    println!("{}", value);
+}
+
+/// Print an event on stderr as one JSON line.
+pub fn event(value: Value) {
+    let _ = writeln!(io::stderr().lock(), "{}", value);
+}
+
+/// Report a non-fatal problem on stderr.
+pub fn warning(message: &str) {
+    if json() {
+        event(json!({ "event": "warning", "message": message }));
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// Report the error a command failed with on stderr.
+pub fn error(err: &anyhow::Error, location: Option<(Option<&str>, usize)>) {
+    if json() {
+        event(error_event(err, location));
+        return;
+    }
+
+    eprintln!("Error: {err:?}");
+    match location {
+        Some((Some(section), offset)) => {
+            eprintln!("\nLocation: {section}, byte offset {offset:#x} ({offset})")
+        }
+        Some((None, offset)) => eprintln!("\nLocation: byte offset {offset:#x} ({offset})"),
+        None => {}
+    }
+}
+
+/// The `error` event of a failed command.
+fn error_event(err: &anyhow::Error, location: Option<(Option<&str>, usize)>) -> Value {
+    let (section, offset) = location.map_or((None, None), |(s, o)| (s, Some(o)));
+    json!({
+        "event": "error",
+        "message": format!("{err:#}"),
+        "section": section,
+        "offset": offset,
+    })
+}
+
+/// Route a progress bar to the selected output: hidden with `--quiet`,
+/// reported as `progress` events in JSON mode, drawn as styled otherwise.
+pub fn attach(pb: ProgressBar, stage: &'static str) -> ProgressBar {
+    let mode = mode();
+    if mode.quiet {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    } else if mode.format == MessageFormat::Json {
+        pb.set_style(ProgressStyle::with_template("{pos}|{len}|{msg}").unwrap());
+        pb.set_draw_target(ProgressDrawTarget::term_like(Box::new(JsonProgress {
+            stage,
+        })));
+    }
+    pb
+}
+
+/// Draw target turning each redraw of a bar into a `progress` event.
+///
+/// Bars attached in JSON mode render as `pos|len|msg`, which is parsed back
+/// here; redraws are rate limited by indicatif as for a terminal.
+#[derive(Debug)]
+struct JsonProgress {
+    stage: &'static str,
+}
+
+impl JsonProgress {
+    fn draw(&self, line: &str) -> io::Result<()> {
+        if let Some(value) = progress_event(self.stage, line) {
+            event(value);
+        }
+        Ok(())
+    }
+}
+
+/// The `progress` event of a bar rendered as `pos|len|msg`, or `None` for
+/// the padding and separators drawn between redraws.
+fn progress_event(stage: &str, line: &str) -> Option<Value> {
+    let mut fields = line.trim_end().splitn(3, '|');
+    let (Some(position), Some(length)) = (fields.next(), fields.next()) else {
+        return None;
+    };
+    Some(json!({
+        "event": "progress",
+        "stage": stage,
+        "position": position.parse::<u64>().ok(),
+        "length": length.parse::<u64>().ok(),
+        "message": fields.next().unwrap_or(""),
+    }))
+}
+
+impl TermLike for JsonProgress {
+    fn width(&self) -> u16 {
+        4096
+    }
+
+    fn move_cursor_up(&self, _n: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_down(&self, _n: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_right(&self, _n: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn move_cursor_left(&self, _n: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_line(&self, s: &str) -> io::Result<()> {
+        self.draw(s)
+    }
+
+    fn write_str(&self, s: &str) -> io::Result<()> {
+        self.draw(s)
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_event() {
+        assert_eq!(
+            progress_event("P4K", "3|10|Data\\a|b.xml\n"),
+            Some(json!({
+                "event": "progress",
+                "stage": "P4K",
+                "position": 3,
+                "length": 10,
+                "message": "Data\\a|b.xml",
+            }))
+        );
+        assert_eq!(progress_event("P4K", "   "), None);
+        assert_eq!(
+            progress_event("DCB", "?|10"),
+            Some(json!({
+                "event": "progress",
+                "stage": "DCB",
+                "position": null,
+                "length": 10,
+                "message": "",
+            }))
+        );
+    }
+
+    #[test]
+    fn test_error_event() {
+        let err = anyhow::anyhow!("bad magic").context("Failed to parse DataCore");
+        assert_eq!(
+            error_event(&err, Some((Some("header"), 4))),
+            json!({
+                "event": "error",
+                "message": "Failed to parse DataCore: bad magic",
+                "section": "header",
+                "offset": 4,
+            })
+        );
+        assert_eq!(error_event(&err, None)["offset"], Value::Null);
+    }
+}
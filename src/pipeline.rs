@@ -0,0 +1,163 @@
+//! Worker pool behind `p4k-extract --parallel`.
+//!
+//! Jobs flow through two bounded stages: decoders read, decompress and
+//! convert entries, and writers put the results on disk. Each job's outcome
+//! is handed back to the calling thread in job order, so progress and
+//! warnings read the same as a sequential run however the work was spread.
+
+use std::collections::BTreeMap;
+use std::thread;
+
+use crossbeam_channel::{bounded, unbounded};
+
+/// Average entry size below which extraction spends its time creating files
+/// rather than decompressing them.
+const SMALL_ENTRY: u64 = 64 * 1024;
+
+/// Jobs queued ahead of each worker.
+const QUEUE_PER_WORKER: usize = 4;
+
+/// Number of threads in each stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pool {
+    pub decoders: usize,
+    pub writers: usize,
+}
+
+impl Pool {
+    /// Split `parallel` threads (0 for one per CPU) between the stages for
+    /// `entries` entries totalling `total_size` bytes.
+    ///
+    /// Trees of small files are bound by the filesystem and get half of the
+    /// threads as writers; large entries are bound by decompression and
+    /// CryXmlB decoding, and get a quarter.
+    pub fn new(parallel: usize, entries: usize, total_size: u64) -> Self {
+        let threads = match parallel {
+            0 => thread::available_parallelism().map_or(4, |n| n.get()),
+            n => n,
+        };
+        let average = total_size / entries.max(1) as u64;
+        let writers = if average < SMALL_ENTRY {
+            threads / 2
+        } else {
+            threads / 4
+        }
+        .max(1);
+        Self {
+            decoders: threads.saturating_sub(writers).max(1),
+            writers,
+        }
+    }
+
+    /// Pass each of `jobs` through `decode` and `write`, calling `report`
+    /// with the outcomes on this thread, in the order of `jobs`.
+    pub fn run<J, D, O>(
+        &self,
+        jobs: impl Iterator<Item = J> + Send,
+        decode: impl Fn(J) -> D + Sync,
+        write: impl Fn(D) -> O + Sync,
+        mut report: impl FnMut(O),
+    ) where
+        J: Send,
+        D: Send,
+        O: Send,
+    {
+        let (job_tx, job_rx) = bounded(self.decoders * QUEUE_PER_WORKER);
+        let (write_tx, write_rx) = bounded(self.writers * QUEUE_PER_WORKER);
+        let (done_tx, done_rx) = unbounded();
+
+        thread::scope(|scope| {
+            scope.spawn(move || {
+                for job in jobs.enumerate() {
+                    if job_tx.send(job).is_err() {
+                        break;
+                    }
+                }
+            });
+            for _ in 0..self.decoders {
+                let (job_rx, write_tx, decode) = (job_rx.clone(), write_tx.clone(), &decode);
+                scope.spawn(move || {
+                    for (seq, job) in job_rx {
+                        if write_tx.send((seq, decode(job))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            for _ in 0..self.writers {
+                let (write_rx, done_tx, write) = (write_rx.clone(), done_tx.clone(), &write);
+                scope.spawn(move || {
+                    for (seq, decoded) in write_rx {
+                        if done_tx.send((seq, write(decoded))).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            // Only the workers hold the channels now, so they close as the
+            // stages finish
+            drop((job_rx, write_tx, write_rx, done_tx));
+
+            let mut next = 0;
+            let mut finished = BTreeMap::new();
+            for (seq, outcome) in done_rx {
+                finished.insert(seq, outcome);
+                while let Some(outcome) = finished.remove(&next) {
+                    report(outcome);
+                    next += 1;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pool_size() {
+        assert_eq!(
+            Pool::new(8, 1000, 1000 * 1024),
+            Pool {
+                decoders: 4,
+                writers: 4
+            }
+        );
+        assert_eq!(
+            Pool::new(8, 10, 10 << 20),
+            Pool {
+                decoders: 6,
+                writers: 2
+            }
+        );
+        assert_eq!(
+            Pool::new(1, 0, 0),
+            Pool {
+                decoders: 1,
+                writers: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_run_reports_in_order() {
+        let pool = Pool {
+            decoders: 4,
+            writers: 2,
+        };
+        let mut reported = Vec::new();
+        pool.run(
+            0..100u64,
+            |n| {
+                // Finish out of order
+                thread::sleep(std::time::Duration::from_micros((100 - n) * 10));
+                n * 2
+            },
+            |n| n + 1,
+            |n| reported.push(n),
+        );
+        let expected: Vec<u64> = (0..100).map(|n| n * 2 + 1).collect();
+        assert_eq!(reported, expected);
+    }
+}
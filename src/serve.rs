@@ -0,0 +1,584 @@
+//! Read-only HTTP API over a P4K archive and its DataCore database.
+//!
+//! Responses are JSON, except entry contents, which are sent as stored
+//! (or as XML text with `?xml`). Errors are `{"error": "..."}` objects with
+//! a 4xx or 5xx status. Lists take `offset` and `limit` parameters.
+//!
+//! - `GET /` - archive, DataCore and comparison summary
+//! - `GET /entries?filter=` - entries matching a `p4k-list` style pattern
+//! - `GET /entries/<path>[?xml]` - contents of an entry
+//! - `GET /records?type=&name=&file=&main` - records matching every filter
+//! - `GET /records/<guid or name>[?format=xml]` - a record's properties
+//! - `GET /diff/entries?filter=&kind=` - entries changed since `--against`
+//! - `GET /diff/records?type=&path=` - records changed since `--against`,
+//!   as `dcb-diff --format json` reports them
+//!
+//! Entries over the `--max-entry-size` or `--max-ratio` limits are refused
+//! with a 413 status rather than decompressed.
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use svarog::datacore::{DataCoreDiff, JsonExporter, Record};
+use svarog::p4k::{DecompressionLimits, EntrySummary, P4kChangeKind, P4kDiff};
+use svarog::prelude::*;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Page size of list endpoints without a `limit` parameter.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// Settings of [`run`].
+pub struct ServeOptions<'a> {
+    /// Address to listen on, e.g. `127.0.0.1:8080`.
+    pub listen: &'a str,
+    /// Load the DataCore of each archive, enabling the record endpoints.
+    pub dcb: bool,
+    /// Older archive to compare against, enabling the diff endpoints.
+    pub against: Option<&'a Path>,
+    /// Requests handled at once.
+    pub threads: usize,
+    /// Limits on the entries read from the archives.
+    pub limits: DecompressionLimits,
+}
+
+/// Serve the API until the process is stopped.
+pub fn run(p4k_path: &Path, options: &ServeOptions<'_>) -> Result<()> {
+    let current = Snapshot::load(p4k_path, options)?;
+    let previous = options
+        .against
+        .map(|path| Snapshot::load(path, options))
+        .transpose()?;
+    let api = Api {
+        current,
+        previous,
+        entry_diff: OnceLock::new(),
+        record_diff: OnceLock::new(),
+    };
+
+    let server = Server::http(options.listen)
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {}: {}", options.listen, e))?;
+    status!(
+        "Serving {} on http://{}",
+        p4k_path.display(),
+        options.listen
+    );
+
+    std::thread::scope(|scope| {
+        for _ in 0..options.threads.max(1) {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    api.handle(request);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// An archive and, with `--dcb`, its DataCore.
+struct Snapshot {
+    archive: P4kArchive,
+    database: Option<DataCoreDatabase>,
+}
+
+impl Snapshot {
+    fn load(path: &Path, options: &ServeOptions<'_>) -> Result<Self> {
+        let archive = P4kArchive::open(path)
+            .with_context(|| format!("Failed to open P4K archive {}", path.display()))?
+            .with_limits(options.limits.clone());
+        let database = if options.dcb {
+            let entry = archive
+                .iter()
+                .find(|e| e.name.to_lowercase().ends_with(".dcb"))
+                .with_context(|| format!("No DataCore database in {}", archive.name()))?;
+            status!("Loading {} from {}...", entry.name, archive.name());
+            let data = archive
+                .read(&entry)
+                .with_context(|| format!("Failed to read {}", entry.name))?;
+            Some(DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?)
+        } else {
+            None
+        };
+        Ok(Self { archive, database })
+    }
+
+    fn database(&self) -> ApiResult<&DataCoreDatabase> {
+        self.database
+            .as_ref()
+            .ok_or_else(|| ApiError::not_found("Records are only served with --dcb"))
+    }
+}
+
+/// Shared state of the request handlers.
+struct Api {
+    current: Snapshot,
+    previous: Option<Snapshot>,
+    /// Diffs against `previous`, computed on first request.
+    entry_diff: OnceLock<P4kDiff>,
+    record_diff: OnceLock<DataCoreDiff>,
+}
+
+impl Api {
+    fn handle(&self, request: Request) {
+        let reply = if matches!(request.method(), Method::Get | Method::Head) {
+            self.route(request.url())
+        } else {
+            Err(ApiError::new(405, "Only GET requests are supported"))
+        };
+        let reply = reply.unwrap_or_else(ApiError::into_reply);
+
+        let response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header("Content-Type", reply.content_type))
+            .with_header(header("Access-Control-Allow-Origin", "*"));
+        if let Err(e) = request.respond(response) {
+            warning!("Failed to send response: {}", e);
+        }
+    }
+
+    fn route(&self, url: &str) -> ApiResult<Reply> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let path =
+            percent_decode(path, false).ok_or_else(|| ApiError::bad_request("Malformed path"))?;
+        let query = Query::parse(query)?;
+
+        match path.trim_end_matches('/') {
+            "" => self.index(),
+            "/entries" => self.entries(&query),
+            "/records" => self.records(&query),
+            "/diff/entries" => self.diff_entries(&query),
+            "/diff/records" => self.diff_records(&query),
+            path => {
+                if let Some(name) = path.strip_prefix("/entries/") {
+                    self.entry(name, &query)
+                } else if let Some(key) = path.strip_prefix("/records/") {
+                    self.record(key, &query)
+                } else {
+                    Err(ApiError::not_found(format!("No endpoint {}", path)))
+                }
+            }
+        }
+    }
+
+    fn index(&self) -> ApiResult<Reply> {
+        let summary = |snapshot: &Snapshot| {
+            json!({
+                "archive": snapshot.archive.name(),
+                "entries": snapshot.archive.entry_count(),
+                "records": snapshot.database.as_ref().map(|db| db.records().len()),
+            })
+        };
+        Reply::json(&json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "current": summary(&self.current),
+            "against": self.previous.as_ref().map(summary),
+        }))
+    }
+
+    fn entries(&self, query: &Query) -> ApiResult<Reply> {
+        let filter = query.get("filter");
+        let entries = self
+            .current
+            .archive
+            .iter()
+            .filter(|entry| filter.map_or(true, |pattern| crate::glob_match(pattern, entry.name)))
+            .map(|entry| {
+                json!({
+                    "name": entry.name,
+                    "compressed_size": entry.compressed_size,
+                    "uncompressed_size": entry.uncompressed_size,
+                    "crc32": entry.crc32,
+                    "encrypted": entry.is_encrypted,
+                })
+            });
+        Reply::json(&query.page("entries", entries)?)
+    }
+
+    fn entry(&self, name: &str, query: &Query) -> ApiResult<Reply> {
+        let archive = &self.current.archive;
+        let entry = archive
+            .find(name)
+            .ok_or_else(|| ApiError::not_found(format!("No entry {}", name)))?;
+        let data = archive.read(&entry).map_err(|e| match e {
+            svarog::p4k::Error::SizeLimitExceeded { .. }
+            | svarog::p4k::Error::RatioLimitExceeded { .. } => ApiError::new(413, e.to_string()),
+            e => anyhow::Error::new(e)
+                .context(format!("Failed to read {}", entry.name))
+                .into(),
+        })?;
+
+        if query.flag("xml") && CryXml::is_cryxml(&data) {
+            let cryxml = CryXml::parse(&data).context("Failed to parse CryXmlB")?;
+            let xml = cryxml.to_xml_string().context("Failed to convert to XML")?;
+            Ok(Reply::new(
+                "application/xml; charset=utf-8",
+                xml.into_bytes(),
+            ))
+        } else {
+            Ok(Reply::new("application/octet-stream", data))
+        }
+    }
+
+    fn records(&self, query: &Query) -> ApiResult<Reply> {
+        let db = self.current.database()?;
+        let type_name = query.get("type");
+        let name = query.get("name").map(str::to_lowercase);
+        let file = query.get("file").map(normalize_path);
+        let main_only = query.flag("main");
+
+        let records = db
+            .all_records()
+            .filter(|record| {
+                type_name.map_or(true, |t| {
+                    record
+                        .type_name()
+                        .is_some_and(|r| r.eq_ignore_ascii_case(t))
+                }) && name.as_ref().map_or(true, |n| {
+                    record
+                        .name()
+                        .is_some_and(|r| r.to_lowercase().contains(n.as_str()))
+                }) && file.as_ref().map_or(true, |f| {
+                    record
+                        .file_name()
+                        .is_some_and(|r| normalize_path(r).starts_with(f.as_str()))
+                }) && (!main_only || record.is_main())
+            })
+            .map(|record| record_summary(&record));
+        Reply::json(&query.page("records", records)?)
+    }
+
+    fn record(&self, key: &str, query: &Query) -> ApiResult<Reply> {
+        let db = self.current.database()?;
+        let record = db
+            .record_by_guid_str(key)
+            .or_else(|| db.record_by_name(key))
+            .ok_or_else(|| ApiError::not_found(format!("No record {}", key)))?;
+
+        match query.get("format").unwrap_or("json") {
+            "json" => {
+                let value = JsonExporter::record_value(record).context("Failed to read record")?;
+                Reply::json(&value)
+            }
+            "xml" => {
+                let xml = XmlExporter::new(db)
+                    .export_record(record.raw())
+                    .context("Failed to export record")?;
+                Ok(Reply::new(
+                    "application/xml; charset=utf-8",
+                    xml.into_bytes(),
+                ))
+            }
+            format => Err(ApiError::bad_request(format!(
+                "Unknown format {}, expected json or xml",
+                format
+            ))),
+        }
+    }
+
+    fn diff_entries(&self, query: &Query) -> ApiResult<Reply> {
+        let previous = self.previous()?;
+        let diff = self
+            .entry_diff
+            .get_or_init(|| P4kDiff::compare(&previous.archive, &self.current.archive));
+
+        let kind = match query.get("kind") {
+            None => None,
+            Some("added") => Some(P4kChangeKind::Added),
+            Some("removed") => Some(P4kChangeKind::Removed),
+            Some("modified") => Some(P4kChangeKind::Modified),
+            Some(kind) => {
+                return Err(ApiError::bad_request(format!(
+                    "Unknown kind {}, expected added, removed or modified",
+                    kind
+                )))
+            }
+        };
+        let filter = query.get("filter");
+
+        let entry = |entry: &Option<EntrySummary>| {
+            entry
+                .as_ref()
+                .map(|e| json!({ "size": e.size, "crc32": e.crc32 }))
+        };
+        let changes = diff
+            .changes()
+            .iter()
+            .filter(|change| kind.map_or(true, |k| change.kind == k))
+            .filter(|change| {
+                filter.map_or(true, |pattern| crate::glob_match(pattern, change.name()))
+            })
+            .map(|change| {
+                json!({
+                    "kind": format!("{:?}", change.kind).to_lowercase(),
+                    "name": change.name(),
+                    "old": entry(&change.old),
+                    "new": entry(&change.new),
+                })
+            });
+
+        let mut page = query.page("changes", changes)?;
+        page["unchanged"] = json!(diff.unchanged());
+        Reply::json(&page)
+    }
+
+    fn diff_records(&self, query: &Query) -> ApiResult<Reply> {
+        let previous = self.previous()?;
+        let old = previous.database()?;
+        let new = self.current.database()?;
+        let diff = self
+            .record_diff
+            .get_or_init(|| DataCoreDiff::compare(old, new));
+
+        let types = query.get_all("type");
+        let paths = query.get_all("path");
+        if types.is_empty() && paths.is_empty() {
+            return Reply::json(diff);
+        }
+        let mut diff = diff.clone();
+        crate::retain_diff_records(&mut diff, &types, &paths);
+        Reply::json(&diff)
+    }
+
+    fn previous(&self) -> ApiResult<&Snapshot> {
+        self.previous
+            .as_ref()
+            .ok_or_else(|| ApiError::not_found("Diffs are only served with --against"))
+    }
+}
+
+/// GUID, name, type and file of a record.
+fn record_summary(record: &Record<'_>) -> Value {
+    json!({
+        "guid": record.id().to_string(),
+        "name": record.name(),
+        "type": record.type_name(),
+        "file": record.file_name(),
+        "main": record.is_main(),
+    })
+}
+
+/// Lowercase with `/` separators, for prefix matching of file paths.
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("header names and values are ASCII")
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+/// A response body and its status.
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn new(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    fn json(value: &impl serde::Serialize) -> ApiResult<Self> {
+        let body = serde_json::to_vec(value).context("Failed to serialize response")?;
+        Ok(Self::new("application/json", body))
+    }
+}
+
+/// A failed request, reported as `{"error": message}`.
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(400, message)
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(404, message)
+    }
+
+    fn into_reply(self) -> Reply {
+        Reply {
+            status: self.status,
+            content_type: "application/json",
+            body: json!({ "error": self.message }).to_string().into_bytes(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::new(500, format!("{:#}", err))
+    }
+}
+
+/// Decoded query string parameters, in order.
+#[derive(Debug, Default)]
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(query: &str) -> ApiResult<Self> {
+        query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                percent_decode(key, true)
+                    .zip(percent_decode(value, true))
+                    .ok_or_else(|| ApiError::bad_request(format!("Malformed parameter {}", pair)))
+            })
+            .collect::<ApiResult<_>>()
+            .map(Self)
+    }
+
+    /// First value of a parameter.
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value of a repeated parameter.
+    fn get_all(&self, key: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
+    /// Whether a flag is set: present without a value, or `true` or `1`.
+    fn flag(&self, key: &str) -> bool {
+        self.get(key)
+            .is_some_and(|v| matches!(v, "" | "true" | "1"))
+    }
+
+    fn usize(&self, key: &str, default: usize) -> ApiResult<usize> {
+        match self.get(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| ApiError::bad_request(format!("{} must be a number", key))),
+            None => Ok(default),
+        }
+    }
+
+    /// The page of `items` selected by `offset` and `limit`, with the total
+    /// count: `{"total": .., "offset": .., "<name>": [..]}`.
+    fn page(&self, name: &str, items: impl Iterator<Item = Value>) -> ApiResult<Value> {
+        let offset = self.usize("offset", 0)?;
+        let limit = self.usize("limit", DEFAULT_LIMIT)?;
+
+        let mut total = 0;
+        let mut page = Vec::new();
+        for item in items {
+            if total >= offset && page.len() < limit {
+                page.push(item);
+            }
+            total += 1;
+        }
+        let mut value = json!({ "total": total, "offset": offset });
+        value[name] = Value::Array(page);
+        Ok(value)
+    }
+}
+
+/// Decode `%XX` escapes, and `+` as a space in query strings.
+fn percent_decode(s: &str, plus_as_space: bool) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                let hex = std::str::from_utf8(hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' if plus_as_space => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(
+            percent_decode("Data%5CGame.dcb", false).as_deref(),
+            Some("Data\\Game.dcb")
+        );
+        assert_eq!(percent_decode("a+b%20c", true).as_deref(), Some("a b c"));
+        assert_eq!(percent_decode("a+b", false).as_deref(), Some("a+b"));
+        assert_eq!(percent_decode("%C3%A9", false).as_deref(), Some("\u{e9}"));
+        assert_eq!(percent_decode("%zz", false), None);
+        assert_eq!(percent_decode("%4", false), None);
+        assert_eq!(percent_decode("%FF", false), None);
+    }
+
+    #[test]
+    fn test_query() {
+        let query =
+            Query::parse("type=AmmoParams&path=libs%2Fammo&path=libs/weapons&main&xml=0").unwrap();
+        assert_eq!(query.get("type"), Some("AmmoParams"));
+        assert_eq!(query.get_all("path"), ["libs/ammo", "libs/weapons"]);
+        assert!(query.flag("main"));
+        assert!(!query.flag("xml"));
+        assert!(!query.flag("missing"));
+
+        assert!(Query::parse("name=%zz").is_err());
+        assert_eq!(Query::parse("").unwrap().get("type"), None);
+    }
+
+    #[test]
+    fn test_page() {
+        let items = || (0..5).map(|i| json!(i));
+
+        let query = Query::parse("offset=1&limit=2").unwrap();
+        assert_eq!(
+            query.page("items", items()).unwrap(),
+            json!({ "total": 5, "offset": 1, "items": [1, 2] })
+        );
+
+        let query = Query::parse("offset=4").unwrap();
+        assert_eq!(query.page("items", items()).unwrap()["items"], json!([4]));
+
+        let err = Query::parse("limit=ten")
+            .unwrap()
+            .page("items", items())
+            .unwrap_err();
+        assert_eq!(err.status, 400);
+    }
+}
@@ -0,0 +1,445 @@
+//! Interactive shell over a P4K archive and its DataCore database.
+//!
+//! The archive is opened once and the DataCore is parsed on the first `dcb`
+//! command, so repeated lookups don't pay for loading them again.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Editor, Helper};
+use svarog::datacore::SearchOptions;
+use svarog::prelude::*;
+
+const COMMANDS: &[&str] = &[
+    "ls", "cd", "pwd", "cat", "find", "dcb", "help", "exit", "quit",
+];
+const DCB_COMMANDS: &[&str] = &["get", "find"];
+
+const HELP: &str = "\
+Commands:
+  ls [dir]            List a directory
+  cd <dir>            Change directory (.. for the parent, / for the root)
+  pwd                 Show the current directory
+  cat <file>          Print a file, decoding CryXmlB to XML
+  find <text>         List files below the current directory whose path contains text
+  dcb get <record>    Print a DataCore record as XML, by name or GUID
+  dcb find <text>     List records whose name, type or file contains text
+  help                Show this help
+  exit, quit          Leave the shell";
+
+/// Run the shell until the user exits.
+pub fn run(p4k_path: &Path) -> Result<()> {
+    let archive = P4kArchive::open(p4k_path).context("Failed to open P4K archive")?;
+    println!(
+        "{}: {} entries. Type 'help' for commands.",
+        archive.name(),
+        archive.entry_count()
+    );
+
+    let mut editor: Editor<Shell, DefaultHistory> =
+        Editor::new().context("Failed to start line editor")?;
+    editor.set_helper(Some(Shell::new(archive)));
+
+    loop {
+        let prompt = format!(
+            "svarog:\\{}> ",
+            editor.helper().map_or("", |s| s.cwd.as_str())
+        );
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e).context("Failed to read input"),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let Some(shell) = editor.helper_mut() else {
+            break;
+        };
+        match shell.execute(line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Shell state, also used by the line editor for completion.
+struct Shell {
+    archive: P4kArchive,
+    /// Current directory with a trailing separator, empty at the root.
+    cwd: String,
+    database: Option<DataCoreDatabase>,
+    /// Sorted record names, for completion.
+    record_names: Vec<String>,
+}
+
+impl Shell {
+    fn new(archive: P4kArchive) -> Self {
+        Self {
+            archive,
+            cwd: String::new(),
+            database: None,
+            record_names: Vec::new(),
+        }
+    }
+
+    /// Run one command line. Returns `false` when the shell should exit.
+    fn execute(&mut self, line: &str) -> Result<bool> {
+        let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match command {
+            "ls" => self.ls(rest)?,
+            "cd" => self.cd(rest)?,
+            "pwd" => println!("\\{}", self.cwd),
+            "cat" => self.cat(rest)?,
+            "find" => self.find(rest)?,
+            "dcb" => self.dcb(rest)?,
+            "help" => println!("{}", HELP),
+            "exit" | "quit" => return Ok(false),
+            _ => anyhow::bail!("Unknown command '{}', type 'help' for a list", command),
+        }
+        Ok(true)
+    }
+
+    fn ls(&self, arg: &str) -> Result<()> {
+        let dir = self.resolve_dir(arg);
+        let children = self.children(&dir);
+        if children.is_empty() {
+            anyhow::bail!("No such directory: {}", arg);
+        }
+        for (name, is_dir) in children.values() {
+            if *is_dir {
+                println!("{}\\", name);
+            } else {
+                println!("{}", name);
+            }
+        }
+        Ok(())
+    }
+
+    fn cd(&mut self, arg: &str) -> Result<()> {
+        let dir = self.resolve_dir(if arg.is_empty() { "\\" } else { arg });
+        if self.archive.dir(&dir).is_none() {
+            anyhow::bail!("No such directory: {}", arg);
+        }
+        self.cwd = dir;
+        Ok(())
+    }
+
+    fn cat(&self, arg: &str) -> Result<()> {
+        if arg.is_empty() {
+            anyhow::bail!("Usage: cat <file>");
+        }
+        let path = self.resolve(arg);
+        let entry = self
+            .archive
+            .find(&path)
+            .with_context(|| format!("No such file: {}", arg))?;
+        let data = self
+            .archive
+            .read(&entry)
+            .with_context(|| format!("Failed to read {}", entry.name))?;
+
+        if CryXml::is_cryxml(&data) {
+            let cryxml = CryXml::parse(&data).context("Failed to parse CryXmlB")?;
+            println!(
+                "{}",
+                cryxml.to_xml_string().context("Failed to convert to XML")?
+            );
+        } else if let Ok(text) = std::str::from_utf8(&data) {
+            println!("{}", text);
+        } else {
+            println!(
+                "<{} bytes of binary data, use p4k-cat to write it out>",
+                data.len()
+            );
+        }
+        Ok(())
+    }
+
+    fn find(&self, text: &str) -> Result<()> {
+        if text.is_empty() {
+            anyhow::bail!("Usage: find <text>");
+        }
+        let needle = text.to_lowercase().replace('/', "\\");
+        let mut count = 0;
+        for entry in self.archive.iter() {
+            if starts_with_ignore_case(entry.name, &self.cwd)
+                && entry.name[self.cwd.len()..]
+                    .to_lowercase()
+                    .contains(&needle)
+            {
+                println!("{}", entry.name);
+                count += 1;
+            }
+        }
+        println!("{} matches", count);
+        Ok(())
+    }
+
+    fn dcb(&mut self, args: &str) -> Result<()> {
+        let (command, arg) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let arg = arg.trim();
+        let db = self.database()?;
+
+        match command {
+            "get" => {
+                let record = db
+                    .record_by_name(arg)
+                    .or_else(|| db.record_by_guid_str(arg))
+                    .with_context(|| format!("No record named {}", arg))?;
+                let xml = XmlExporter::new(db)
+                    .export_record(record.raw())
+                    .context("Failed to export record")?;
+                println!("{}", xml);
+            }
+            "find" => {
+                let hits = db.search(arg, &SearchOptions::default());
+                let mut last = None;
+                for hit in &hits {
+                    // A record can match on several fields
+                    if last != Some(hit.record.id) {
+                        println!("{}  {}", hit.record, hit.record.file_name);
+                        last = Some(hit.record.id);
+                    }
+                }
+            }
+            _ => anyhow::bail!("Usage: dcb get <record> | dcb find <text>"),
+        }
+        Ok(())
+    }
+
+    /// The DataCore database, parsed from the archive on first use.
+    fn database(&mut self) -> Result<&DataCoreDatabase> {
+        if self.database.is_none() {
+            let entry = self
+                .archive
+                .iter()
+                .find(|e| e.name.to_lowercase().ends_with(".dcb"))
+                .context("No DataCore database in this archive")?;
+            println!("Loading {}...", entry.name);
+            let data = self
+                .archive
+                .read(&entry)
+                .with_context(|| format!("Failed to read {}", entry.name))?;
+            let db = DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?;
+
+            let mut names: Vec<String> = db
+                .records()
+                .iter()
+                .filter_map(|r| db.record_name(r))
+                .map(String::from)
+                .collect();
+            names.sort_unstable();
+            names.dedup();
+            self.record_names = names;
+            self.database = Some(db);
+        }
+        Ok(self.database.as_ref().expect("database was just loaded"))
+    }
+
+    /// Resolve a path typed by the user against the current directory.
+    ///
+    /// Both `/` and `\` separate components; a leading separator starts at
+    /// the root.
+    fn resolve(&self, path: &str) -> String {
+        let mut parts: Vec<&str> = if path.starts_with(['/', '\\']) {
+            Vec::new()
+        } else {
+            self.cwd.split('\\').filter(|p| !p.is_empty()).collect()
+        };
+        for part in path.split(['/', '\\']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                _ => parts.push(part),
+            }
+        }
+        parts.join("\\")
+    }
+
+    /// Like [`resolve`](Self::resolve), with a trailing separator unless at
+    /// the root.
+    fn resolve_dir(&self, path: &str) -> String {
+        let mut dir = self.resolve(path);
+        if !dir.is_empty() {
+            dir.push('\\');
+        }
+        dir
+    }
+
+    /// Files and directories directly below `dir`, keyed by lowercase name.
+    fn children(&self, dir: &str) -> BTreeMap<String, (String, bool)> {
+        let mut children = BTreeMap::new();
+        let Some(dir) = self.archive.dir(dir) else {
+            return children;
+        };
+        let dirs = dir.dirs().map(|dir| (dir.name(), true));
+        let files = dir
+            .files()
+            .map(|entry| (entry.name.rsplit('\\').next().unwrap_or(entry.name), false));
+        for (name, is_dir) in dirs.chain(files) {
+            children
+                .entry(name.to_lowercase())
+                .or_insert_with(|| (name.to_string(), is_dir));
+        }
+        children
+    }
+
+    fn complete_path(&self, word: &str, dirs_only: bool) -> Vec<String> {
+        let split = word.rfind(['/', '\\']).map_or(0, |i| i + 1);
+        let (typed_dir, partial) = word.split_at(split);
+        let dir = self.resolve_dir(typed_dir);
+        self.children(&dir)
+            .into_values()
+            .filter(|(name, is_dir)| {
+                (*is_dir || !dirs_only) && starts_with_ignore_case(name, partial)
+            })
+            .map(|(name, is_dir)| {
+                format!("{}{}{}", typed_dir, name, if is_dir { "\\" } else { "" })
+            })
+            .collect()
+    }
+
+    fn complete_record(&self, partial: &str) -> Vec<String> {
+        self.record_names
+            .iter()
+            .filter(|name| starts_with_ignore_case(name, partial))
+            .take(200)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Completer for Shell {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let words: Vec<&str> = line[..start].split_whitespace().collect();
+
+        let candidates = match words.as_slice() {
+            [] => COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .map(|c| c.to_string())
+                .collect(),
+            ["cd"] | ["ls"] => self.complete_path(word, true),
+            ["cat"] | ["find"] => self.complete_path(word, false),
+            ["dcb"] => DCB_COMMANDS
+                .iter()
+                .filter(|c| c.starts_with(word))
+                .map(|c| c.to_string())
+                .collect(),
+            ["dcb", "get"] => self.complete_record(word),
+            _ => Vec::new(),
+        };
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for Shell {
+    type Hint = String;
+}
+
+impl Highlighter for Shell {}
+
+impl Validator for Shell {}
+
+impl Helper for Shell {}
+
+/// ASCII case-insensitive prefix check, as P4K paths are matched.
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A shell over an archive of `files`.
+    fn shell(name: &str, files: &[(&str, &[u8])]) -> Shell {
+        let path = crate::tests::write_p4k(name, files);
+        Shell::new(P4kArchive::open(&path).unwrap())
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut shell = shell(
+            "navigation.p4k",
+            &[
+                ("Data/Scripts/main.lua", b"print(1)"),
+                ("Data/Libs/Config/profile.xml", b"<Profile/>"),
+            ],
+        );
+
+        assert!(shell.execute("cd Data/Scripts").unwrap());
+        assert_eq!(shell.cwd, "Data\\Scripts\\");
+        assert_eq!(shell.resolve("../Libs/./Config"), "Data\\Libs\\Config");
+        assert_eq!(shell.resolve("/Data"), "Data");
+        assert_eq!(shell.resolve_dir(""), "Data\\Scripts\\");
+        assert!(shell.execute("cat main.lua").unwrap());
+
+        let err = shell.execute("cd Missing").unwrap_err();
+        assert_eq!(err.to_string(), "No such directory: Missing");
+        assert_eq!(shell.cwd, "Data\\Scripts\\");
+        assert!(shell.execute("cat missing.lua").is_err());
+        assert!(shell.execute("cd ..\\..").unwrap());
+        assert_eq!(shell.cwd, "");
+
+        assert!(shell.execute("frobnicate").is_err());
+        assert!(!shell.execute("exit").unwrap());
+    }
+
+    #[test]
+    fn test_completion() {
+        let shell = shell(
+            "completion.p4k",
+            &[
+                ("Data/Scripts/main.lua", b"print(1)"),
+                ("Data/Sounds/click.wem", b"RIFF"),
+                ("Data/readme.txt", b"hello"),
+            ],
+        );
+
+        assert_eq!(shell.complete_path("da", true), ["Data\\"]);
+        assert_eq!(
+            shell.complete_path("Data/S", true),
+            ["Data/Scripts\\", "Data/Sounds\\"]
+        );
+        assert_eq!(shell.complete_path("Data\\r", false), ["Data\\readme.txt"]);
+        assert!(shell.complete_path("Data\\r", true).is_empty());
+        assert!(shell.complete_path("Nothing/", false).is_empty());
+    }
+
+    #[test]
+    fn test_dcb_without_database() {
+        let mut shell = shell("no-dcb.p4k", &[("Data/readme.txt", b"hello")]);
+        let err = shell.execute("dcb find ammo").unwrap_err();
+        assert_eq!(err.to_string(), "No DataCore database in this archive");
+    }
+}
@@ -0,0 +1,323 @@
+//! `svarog socpak`: list, extract and re-pack SOCPAK archives.
+//!
+//! A SOCPAK is a ZIP archive of object container files, mostly CryXmlB.
+//! `extract` decodes CryXmlB to XML like `p4k-extract` does. `create` packs
+//! a directory back into a ZIP; given the original SOCPAK as a template it
+//! keeps its entry order and per-entry compression, and re-encodes files
+//! that were CryXmlB there and have been decoded to XML since.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use svarog::cryxml::builder::CryXmlBuilder;
+use svarog::prelude::*;
+
+/// Compression of entries not taken from a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    Store,
+    Deflate,
+    Zstd,
+}
+
+impl Compression {
+    fn method(self) -> zip::CompressionMethod {
+        match self {
+            Compression::Store => zip::CompressionMethod::Stored,
+            Compression::Deflate => zip::CompressionMethod::Deflated,
+            Compression::Zstd => zip::CompressionMethod::Zstd,
+        }
+    }
+}
+
+/// Read a SOCPAK from disk, or from inside `p4k` when given.
+pub fn read(path: &Path, p4k: Option<&Path>) -> Result<Vec<u8>> {
+    match p4k {
+        Some(p4k) => {
+            let archive = P4kArchive::open(p4k).context("Failed to open P4K archive")?;
+            let name = path.to_string_lossy();
+            let entry = archive
+                .find(&name)
+                .with_context(|| format!("Entry not found: {}", name))?;
+            archive
+                .read(&entry)
+                .with_context(|| format!("Failed to read {}", entry.name))
+        }
+        None => fs::read(path).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn open(data: &[u8]) -> Result<zip::ZipArchive<Cursor<&[u8]>>> {
+    zip::ZipArchive::new(Cursor::new(data)).context("Failed to open SOCPAK as ZIP archive")
+}
+
+/// Print the entries of a SOCPAK.
+pub fn list(data: &[u8]) -> Result<()> {
+    let mut archive = open(data)?;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        if crate::output::json() {
+            crate::output::emit(serde_json::json!({
+                "name": file.name(),
+                "compressed_size": file.compressed_size(),
+                "uncompressed_size": file.size(),
+                "compression": file.compression().to_string(),
+            }));
+        } else {
+            println!(
+                "{:>12} {:>12} {:<8} {}",
+                file.compressed_size(),
+                file.size(),
+                file.compression().to_string(),
+                file.name()
+            );
+        }
+    }
+    status!("\nTotal: {} entries", archive.len());
+    Ok(())
+}
+
+/// Extract a SOCPAK into `output`, decoding CryXmlB to XML.
+pub fn extract(data: &[u8], output: &Path) -> Result<()> {
+    let mut archive = open(data)?;
+    let pipeline = svarog::process::Pipeline::new().with(svarog::process::CryXmlProcessor);
+    let mut socpak = crate::ExtractOutcome::default();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let Some(path) = svarog::process::enclosed_path(output, file.name()) else {
+            warning!("Skipping {}: path leaves the output directory", file.name());
+            continue;
+        };
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        pipeline.process(path, contents, &mut socpak);
+    }
+
+    for message in &socpak.warnings {
+        warning!("{}", message);
+    }
+    for (path, contents) in &socpak.files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    status!(
+        "Extracted {} files ({} CryXML decoded) to {}",
+        socpak.files.len(),
+        socpak.cryxml_decoded,
+        output.display()
+    );
+    if crate::output::json() {
+        crate::output::emit(serde_json::json!({
+            "output": output,
+            "extracted": socpak.files.len(),
+            "cryxml_decoded": socpak.cryxml_decoded,
+        }));
+    }
+    Ok(())
+}
+
+/// How an entry was stored in the template.
+struct TemplateEntry {
+    position: usize,
+    /// `None` for methods this build can't write, which use `--compression`
+    method: Option<zip::CompressionMethod>,
+    cryxml: bool,
+}
+
+/// Pack the files below `dir` into a SOCPAK at `output`.
+pub fn create(
+    dir: &Path,
+    output: &Path,
+    template: Option<&Path>,
+    compression: Compression,
+) -> Result<()> {
+    let template = match template {
+        Some(path) => read_template(
+            &fs::read(path)
+                .with_context(|| format!("Failed to read template {}", path.display()))?,
+        )?,
+        None => HashMap::new(),
+    };
+
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    // Template entries keep their order, new files follow sorted by name
+    files.sort_by_cached_key(|name| {
+        let position = template.get(&name.to_lowercase()).map(|t| t.position);
+        (position.unwrap_or(usize::MAX), name.clone())
+    });
+
+    let file = fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+    let mut encoded = 0;
+    for name in &files {
+        let path = dir.join(name);
+        let mut data =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let original = template.get(&name.to_lowercase());
+
+        if original.is_some_and(|t| t.cryxml) && !CryXml::is_cryxml(&data) {
+            let xml =
+                String::from_utf8(data).with_context(|| format!("{} is not XML text", name))?;
+            data = CryXmlBuilder::from_xml(&xml)
+                .and_then(|builder| builder.build())
+                .with_context(|| format!("Failed to encode {} as CryXmlB", name))?;
+            encoded += 1;
+        }
+
+        let method = original
+            .and_then(|t| t.method)
+            .unwrap_or(compression.method());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(data.len() as u64 >= u32::MAX as u64);
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(&data)?;
+    }
+    zip.finish()?.flush()?;
+
+    let missing = template.len().saturating_sub(
+        files
+            .iter()
+            .filter(|name| template.contains_key(&name.to_lowercase()))
+            .count(),
+    );
+    if missing > 0 {
+        warning!(
+            "{} entries of the template are not in {}",
+            missing,
+            dir.display()
+        );
+    }
+    status!(
+        "Packed {} files ({} encoded as CryXmlB) into {}",
+        files.len(),
+        encoded,
+        output.display()
+    );
+    if crate::output::json() {
+        crate::output::emit(serde_json::json!({
+            "output": output,
+            "packed": files.len(),
+            "cryxml_encoded": encoded,
+            "missing": missing,
+        }));
+    }
+    Ok(())
+}
+
+/// Entries of a template SOCPAK by lowercase name.
+fn read_template(data: &[u8]) -> Result<HashMap<String, TemplateEntry>> {
+    let mut archive = open(data)?;
+    let mut entries = HashMap::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let method = match file.compression() {
+            method @ (zip::CompressionMethod::Stored
+            | zip::CompressionMethod::Deflated
+            | zip::CompressionMethod::Zstd) => Some(method),
+            _ => None,
+        };
+        let mut magic = [0u8; 8];
+        let cryxml = file.read_exact(&mut magic).is_ok() && CryXml::is_cryxml(&magic);
+        entries.insert(
+            file.name().replace('\\', "/").to_lowercase(),
+            TemplateEntry {
+                position: i,
+                method,
+                cryxml,
+            },
+        );
+    }
+    Ok(entries)
+}
+
+/// Relative paths, with `/` separators, of the files below `dir`.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path: PathBuf = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let name: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            files.push(name.join("/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_with_template() {
+        let root = std::env::temp_dir().join(format!("svarog-socpak-{}", std::process::id()));
+        let dir = root.join("pack");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("b.bin"), b"binary").unwrap();
+        fs::write(dir.join("a.txt"), b"text").unwrap();
+        fs::write(dir.join("new.txt"), b"new").unwrap();
+
+        // Template lists b.bin stored, then a.txt deflated
+        let template = root.join("original.socpak");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&template).unwrap());
+        let options = |method| zip::write::SimpleFileOptions::default().compression_method(method);
+        zip.start_file("sub/b.bin", options(zip::CompressionMethod::Stored))
+            .unwrap();
+        zip.write_all(b"old").unwrap();
+        zip.start_file("a.txt", options(zip::CompressionMethod::Deflated))
+            .unwrap();
+        zip.write_all(b"old").unwrap();
+        zip.finish().unwrap();
+
+        let output = root.join("out.socpak");
+        create(&dir, &output, Some(&template), Compression::Zstd).unwrap();
+
+        let data = fs::read(&output).unwrap();
+        let mut archive = open(&data).unwrap();
+        let entries: Vec<_> = (0..archive.len())
+            .map(|i| {
+                let file = archive.by_index(i).unwrap();
+                (file.name().to_string(), file.compression())
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("sub/b.bin".to_string(), zip::CompressionMethod::Stored),
+                ("a.txt".to_string(), zip::CompressionMethod::Deflated),
+                ("new.txt".to_string(), zip::CompressionMethod::Zstd),
+            ]
+        );
+        let mut contents = String::new();
+        archive
+            .by_name("sub/b.bin")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "binary");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+//! `svarog watch`: process files as they appear in a directory.
+//!
+//! Character files (`.chf`) are backed up, validated and optionally written
+//! out as JSON; with `cryxml`, CryXmlB files dropped into an extracted tree
+//! are decoded to XML in place. Changes are collected until the directory
+//! has been quiet for the debounce delay, so files still being written are
+//! only processed once.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use svarog::chf::parts::ChfData;
+use svarog::prelude::*;
+
+/// What to do with the files that appear.
+pub struct WatchOptions<'a> {
+    /// Directory for copies of each version of a character, or `None` to
+    /// skip backups.
+    pub backup_dir: Option<&'a Path>,
+    /// DataCore to validate item, material and gender references against.
+    pub dcb: Option<&'a Path>,
+    /// Write a `.json` file next to each character.
+    pub json: bool,
+    /// Decode CryXmlB files to XML in place.
+    pub cryxml: bool,
+    /// Quiet time after the last change before files are processed.
+    pub debounce: Duration,
+}
+
+/// Watch `dir` and its subdirectories until interrupted.
+pub fn run(dir: &Path, options: &WatchOptions<'_>) -> Result<()> {
+    let database = match options.dcb {
+        Some(dcb) => {
+            status!("Loading DataCore: {}", dcb.display());
+            let data = fs::read(dcb).context("Failed to read DCB file")?;
+            Some(DataCoreDatabase::parse(&data).context("Failed to parse DataCore")?)
+        }
+        None => None,
+    };
+    if let Some(backup_dir) = options.backup_dir {
+        fs::create_dir_all(backup_dir).context("Failed to create backup directory")?;
+    }
+
+    // Event paths are compared against the backup directory, which is
+    // below the watched one by default, so both are made absolute
+    let watched = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to open {}", dir.display()))?;
+    let backup_dir = options.backup_dir.and_then(|d| d.canonicalize().ok());
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    watcher
+        .watch(&watched, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+    status!("Watching {} (Ctrl+C to stop)", dir.display());
+    let mut pending = BTreeSet::<PathBuf>::new();
+    loop {
+        let event = if pending.is_empty() {
+            rx.recv().ok()
+        } else {
+            match rx.recv_timeout(options.debounce) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => {
+                    for path in std::mem::take(&mut pending) {
+                        process(&path, options, database.as_ref());
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => None,
+            }
+        };
+
+        match event {
+            Some(Ok(event))
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) =>
+            {
+                pending.extend(event.paths.into_iter().filter(|path| {
+                    let in_backups = backup_dir
+                        .as_ref()
+                        .is_some_and(|backups| path.starts_with(backups));
+                    !in_backups && is_watched(path, options)
+                }));
+            }
+            Some(Ok(_)) => {}
+            Some(Err(e)) => warning!("Watch error: {}", e),
+            None => anyhow::bail!("File watcher stopped"),
+        }
+    }
+}
+
+fn is_watched(path: &Path, options: &WatchOptions<'_>) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("chf") => true,
+        _ => options.cryxml,
+    }
+}
+
+fn process(path: &Path, options: &WatchOptions<'_>, database: Option<&DataCoreDatabase>) {
+    // Deleted or renamed again before the debounce delay ran out
+    if !path.is_file() {
+        return;
+    }
+    let is_chf = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("chf"));
+    let result = if is_chf {
+        process_chf(path, options, database)
+    } else {
+        process_cryxml(path)
+    };
+    if let Err(e) = result {
+        warning!("  {}: {:#}", path.display(), e);
+    }
+}
+
+fn process_chf(
+    path: &Path,
+    options: &WatchOptions<'_>,
+    database: Option<&DataCoreDatabase>,
+) -> Result<()> {
+    let backup = match options.backup_dir {
+        Some(dir) => Some(backup(path, dir)?),
+        None => None,
+    };
+
+    let chf = crate::read_chf(path)?;
+    let character = ChfData::parse(chf.data()).context("Failed to parse character data")?;
+    let report = database.map(|db| character.validate(db));
+    let valid = report.as_ref().map_or(true, |r| r.is_valid());
+
+    let json = if options.json {
+        let json_path = path.with_extension("json");
+        crate::write_chf(&chf, &json_path)?;
+        Some(json_path)
+    } else {
+        None
+    };
+
+    if crate::output::json() {
+        crate::output::emit(serde_json::json!({
+            "path": path,
+            "backup": backup,
+            "valid": valid,
+            "json": json,
+        }));
+    } else {
+        status!("  {}", path.display());
+        if let Some(backup) = &backup {
+            status!("    backed up to {}", backup.display());
+        }
+        if let Some(json) = &json {
+            status!("    wrote {}", json.display());
+        }
+    }
+    if let Some(report) = report.filter(|r| !r.is_valid()) {
+        warning!(
+            "  {} has unresolved references:\n{}",
+            path.display(),
+            report
+        );
+    }
+    Ok(())
+}
+
+fn process_cryxml(path: &Path) -> Result<()> {
+    if crate::try_decode_cryxml_inplace(path)? {
+        if crate::output::json() {
+            crate::output::emit(serde_json::json!({ "path": path, "decoded": true }));
+        } else {
+            status!("  Decoded {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Copy `path` into `dir` as `<stem>.<unix time>.<ext>`.
+fn backup(path: &Path, dir: &Path) -> Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    let target = dir.join(format!("{}.{}.{}", stem, secs, ext));
+    fs::copy(path, &target)
+        .with_context(|| format!("Failed to back up to {}", target.display()))?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(cryxml: bool) -> WatchOptions<'static> {
+        WatchOptions {
+            backup_dir: None,
+            dcb: None,
+            json: false,
+            cryxml,
+            debounce: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn test_is_watched() {
+        assert!(is_watched(Path::new("chars/Alice.CHF"), &options(false)));
+        assert!(!is_watched(Path::new("Libs/profile.xml"), &options(false)));
+        assert!(is_watched(Path::new("Libs/profile.xml"), &options(true)));
+    }
+
+    #[test]
+    fn test_backup() {
+        let dir = std::env::temp_dir().join(format!("svarog-watch-{}", std::process::id()));
+        let backups = dir.join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        let path = dir.join("Alice.chf");
+        fs::write(&path, b"character").unwrap();
+
+        let copy = backup(&path, &backups).unwrap();
+        assert_eq!(copy.parent(), Some(backups.as_path()));
+        let name = copy.file_name().unwrap().to_str().unwrap();
+        let secs = name
+            .strip_prefix("Alice.")
+            .and_then(|rest| rest.strip_suffix(".chf"))
+            .unwrap();
+        assert!(secs.parse::<u64>().is_ok());
+        assert_eq!(fs::read(&copy).unwrap(), b"character");
+
+        let err = backup(&path, &dir.join("missing")).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to back up to "));
+    }
+}